@@ -0,0 +1,126 @@
+//! Numeric aggregation over row subsets (group subtotals).
+
+/// Aggregate function over a numeric column. Null (NaN) cells are skipped, except
+/// by `Count`, which counts rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Mean,
+    Min,
+    Max,
+    Count,
+}
+
+impl Aggregate {
+    /// Parse an aggregate name: `sum`, `mean` (or `avg`), `min`, `max`, `count`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        Ok(match s {
+            "sum" => Self::Sum,
+            "mean" | "avg" => Self::Mean,
+            "min" => Self::Min,
+            "max" => Self::Max,
+            "count" => Self::Count,
+            _ => return Err(format!("unknown aggregate: {s:?}")),
+        })
+    }
+}
+
+/// Running totals from which every `Aggregate` can be read.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Accumulator {
+    rows: usize,
+    values: usize,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Self {
+            rows: 0,
+            values: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl Accumulator {
+    /// Add one cell; NaN counts as a row but not as a value.
+    pub fn push(&mut self, value: f64) {
+        self.rows += 1;
+        if value.is_nan() {
+            return;
+        }
+        self.values += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Add every cell pushed to `other`.
+    pub fn merge(&mut self, other: &Self) {
+        self.rows += other.rows;
+        self.values += other.values;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// The aggregate's value, or NaN when there are no non-null values.
+    pub fn value(&self, aggregate: Aggregate) -> f64 {
+        if aggregate == Aggregate::Count {
+            return self.rows as f64;
+        }
+        if self.values == 0 {
+            return f64::NAN;
+        }
+        match aggregate {
+            Aggregate::Sum => self.sum,
+            Aggregate::Mean => self.sum / self.values as f64,
+            Aggregate::Min => self.min,
+            Aggregate::Max => self.max,
+            Aggregate::Count => self.rows as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregates_skip_nulls() {
+        let mut acc = Accumulator::default();
+        for v in [4.0, f64::NAN, 1.0, 7.0] {
+            acc.push(v);
+        }
+        assert_eq!(
+            (
+                acc.value(Aggregate::Sum),
+                acc.value(Aggregate::Mean),
+                acc.value(Aggregate::Min),
+                acc.value(Aggregate::Max),
+                acc.value(Aggregate::Count),
+            ),
+            (12.0, 4.0, 1.0, 7.0, 4.0)
+        );
+    }
+
+    #[test]
+    fn empty_is_nan_except_count() {
+        let mut acc = Accumulator::default();
+        acc.push(f64::NAN);
+        assert!(acc.value(Aggregate::Sum).is_nan());
+        assert!(acc.value(Aggregate::Max).is_nan());
+        assert!((acc.value(Aggregate::Count) - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn parse_names() {
+        assert_eq!(Aggregate::parse("avg"), Ok(Aggregate::Mean));
+        assert!(Aggregate::parse("median").is_err());
+    }
+}
@@ -0,0 +1,567 @@
+//! Arrow IPC stream export: columns of the store as one record batch with Arrow
+//! types and validity bitmaps, for Arrow readers (arrow-js, DuckDB-WASM,
+//! Perspective).
+//!
+//! Float64 columns are `Float64`, Bool columns `Bool`, string columns `Utf8`, and
+//! multi-value string columns `List<Utf8>`; engine nulls (NaN, empty strings)
+//! are Arrow nulls. The metadata flatbuffers are written directly.
+
+// Lengths and offsets go out as the signed integers of the format
+#![allow(clippy::cast_possible_wrap)]
+
+use crate::columnar_store::{ColumnData, ColumnarStore};
+
+/// `MetadataVersion::V5`.
+const METADATA_VERSION: i16 = 4;
+/// `MessageHeader` union tags.
+const HEADER_SCHEMA: u8 = 1;
+const HEADER_RECORD_BATCH: u8 = 3;
+/// `Type` union tags.
+const TYPE_FLOATING_POINT: u8 = 3;
+const TYPE_UTF8: u8 = 5;
+const TYPE_BOOL: u8 = 6;
+const TYPE_LIST: u8 = 12;
+/// `Precision::DOUBLE`.
+const PRECISION_DOUBLE: i16 = 2;
+const CONTINUATION: u32 = 0xFFFF_FFFF;
+
+/// An Arrow IPC stream of data rows `rows` (in order) × `columns` (table column
+/// and field name): a schema message, one record batch, and the end marker.
+pub fn write_ipc_stream(
+    store: &ColumnarStore,
+    rows: &[u32],
+    columns: &[(usize, String)],
+) -> Vec<u8> {
+    let fields = columns
+        .iter()
+        .map(|(col, name)| field(name, store.data.get(*col)))
+        .collect();
+    let schema = Fb::Table(vec![
+        (0, Scalar::I16(0)),
+        (1, Scalar::Ref(Fb::Tables(fields))),
+    ]);
+
+    let mut body = Body::default();
+    for &(col, _) in columns {
+        body.column(store.data.get(col), rows);
+    }
+    let batch = Fb::Table(vec![
+        (0, Scalar::I64(rows.len() as i64)),
+        (1, Scalar::Ref(Fb::Structs(body.nodes))),
+        (2, Scalar::Ref(Fb::Structs(body.buffers))),
+    ]);
+
+    let mut out = Vec::new();
+    write_message(&mut out, HEADER_SCHEMA, schema, &[]);
+    write_message(&mut out, HEADER_RECORD_BATCH, batch, &body.bytes);
+    out.extend_from_slice(&CONTINUATION.to_le_bytes());
+    out.extend_from_slice(&0_u32.to_le_bytes());
+    out
+}
+
+/// Schema field of a column (`Utf8` for missing columns, written as nulls).
+fn field(name: &str, column: Option<&ColumnData>) -> Fb {
+    let (type_tag, type_table, children) = match column {
+        Some(ColumnData::Float64(_)) => (
+            TYPE_FLOATING_POINT,
+            Fb::Table(vec![(0, Scalar::I16(PRECISION_DOUBLE))]),
+            Vec::new(),
+        ),
+        Some(ColumnData::Bool(_)) => (TYPE_BOOL, Fb::Table(Vec::new()), Vec::new()),
+        Some(ColumnData::StringList { .. }) => {
+            (TYPE_LIST, Fb::Table(Vec::new()), vec![field("item", None)])
+        }
+        Some(ColumnData::Float64List { .. }) => (
+            TYPE_LIST,
+            Fb::Table(Vec::new()),
+            vec![field("item", Some(&ColumnData::Float64(Vec::new())))],
+        ),
+        Some(ColumnData::Strings { .. }) | None => (TYPE_UTF8, Fb::Table(Vec::new()), Vec::new()),
+    };
+    Fb::Table(vec![
+        (0, Scalar::Ref(Fb::Str(name.to_string()))),
+        (1, Scalar::Bool(true)),
+        (2, Scalar::U8(type_tag)),
+        (3, Scalar::Ref(type_table)),
+        (5, Scalar::Ref(Fb::Tables(children))),
+    ])
+}
+
+/// A framed message: continuation marker, metadata length, the `Message`
+/// flatbuffer padded to 8 bytes, then the body.
+fn write_message(out: &mut Vec<u8>, header_type: u8, header: Fb, body: &[u8]) {
+    let message = Fb::Table(vec![
+        (0, Scalar::I16(METADATA_VERSION)),
+        (1, Scalar::U8(header_type)),
+        (2, Scalar::Ref(header)),
+        (3, Scalar::I64(body.len() as i64)),
+    ]);
+    let mut metadata = message.finish();
+    metadata.resize(metadata.len().next_multiple_of(8), 0);
+    out.extend_from_slice(&CONTINUATION.to_le_bytes());
+    out.extend_from_slice(&(metadata.len() as u32).to_le_bytes());
+    out.extend_from_slice(&metadata);
+    out.extend_from_slice(body);
+}
+
+/// Record batch body: buffers padded to 8 bytes, with their `Buffer` (offset,
+/// length) and the `FieldNode` (length, null count) of each array.
+#[derive(Debug, Default)]
+struct Body {
+    bytes: Vec<u8>,
+    buffers: Vec<[i64; 2]>,
+    nodes: Vec<[i64; 2]>,
+}
+
+impl Body {
+    fn buffer(&mut self, data: &[u8]) {
+        self.buffers
+            .push([self.bytes.len() as i64, data.len() as i64]);
+        self.bytes.extend_from_slice(data);
+        self.bytes.resize(self.bytes.len().next_multiple_of(8), 0);
+    }
+
+    /// A node with validity bitmap `valid` (left out when nothing is null).
+    fn node(&mut self, valid: &[bool]) {
+        let nulls = valid.iter().filter(|&&v| !v).count();
+        self.nodes.push([valid.len() as i64, nulls as i64]);
+        if nulls == 0 {
+            self.buffer(&[]);
+        } else {
+            self.buffer(&bitmap(valid.iter().copied()));
+        }
+    }
+
+    fn column(&mut self, column: Option<&ColumnData>, rows: &[u32]) {
+        match column {
+            Some(ColumnData::Float64(values)) => {
+                let values: Vec<f64> = rows.iter().map(|&row| values[row as usize]).collect();
+                self.node(&values.iter().map(|v| !v.is_nan()).collect::<Vec<_>>());
+                let bytes: Vec<u8> = values
+                    .iter()
+                    .flat_map(|v| if v.is_nan() { 0.0 } else { *v }.to_le_bytes())
+                    .collect();
+                self.buffer(&bytes);
+            }
+            Some(ColumnData::Bool(values)) => {
+                let values: Vec<f64> = rows.iter().map(|&row| values[row as usize]).collect();
+                self.node(&values.iter().map(|v| !v.is_nan()).collect::<Vec<_>>());
+                self.buffer(&bitmap(values.iter().map(|&v| !v.is_nan() && v != 0.0)));
+            }
+            Some(ColumnData::Strings { ids, intern }) => {
+                let texts: Vec<&str> = rows
+                    .iter()
+                    .map(|&row| intern.resolve(ids[row as usize]))
+                    .collect();
+                self.node(&texts.iter().map(|t| !t.is_empty()).collect::<Vec<_>>());
+                self.strings(&texts);
+            }
+            Some(list @ ColumnData::StringList { intern, .. }) => {
+                self.node(&vec![true; rows.len()]);
+                let mut offsets = vec![0_i32];
+                let mut items = Vec::new();
+                for &row in rows {
+                    items.extend(
+                        list.list_ids(row as usize)
+                            .iter()
+                            .map(|&id| intern.resolve(id)),
+                    );
+                    offsets.push(items.len() as i32);
+                }
+                self.buffer(
+                    &offsets
+                        .iter()
+                        .flat_map(|o| o.to_le_bytes())
+                        .collect::<Vec<_>>(),
+                );
+                self.node(&vec![true; items.len()]);
+                self.strings(&items);
+            }
+            Some(series @ ColumnData::Float64List { .. }) => {
+                self.node(&vec![true; rows.len()]);
+                let mut offsets = vec![0_i32];
+                let mut items = Vec::new();
+                for &row in rows {
+                    items.extend_from_slice(series.series(row as usize));
+                    offsets.push(items.len() as i32);
+                }
+                self.buffer(
+                    &offsets
+                        .iter()
+                        .flat_map(|o| o.to_le_bytes())
+                        .collect::<Vec<_>>(),
+                );
+                self.node(&items.iter().map(|v| !v.is_nan()).collect::<Vec<_>>());
+                let bytes: Vec<u8> = items
+                    .iter()
+                    .flat_map(|v| if v.is_nan() { 0.0 } else { *v }.to_le_bytes())
+                    .collect();
+                self.buffer(&bytes);
+            }
+            None => {
+                self.node(&vec![false; rows.len()]);
+                self.strings(&vec![""; rows.len()]);
+            }
+        }
+    }
+
+    /// Offsets and data buffers of a `Utf8` array.
+    fn strings(&mut self, texts: &[&str]) {
+        let mut offsets = Vec::with_capacity((texts.len() + 1) * 4);
+        let mut data = Vec::new();
+        offsets.extend_from_slice(&0_i32.to_le_bytes());
+        for text in texts {
+            data.extend_from_slice(text.as_bytes());
+            offsets.extend_from_slice(&(data.len() as i32).to_le_bytes());
+        }
+        self.buffer(&offsets);
+        self.buffer(&data);
+    }
+}
+
+/// LSB-first bit-packed booleans.
+fn bitmap(bits: impl Iterator<Item = bool>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for (i, bit) in bits.enumerate() {
+        if i % 8 == 0 {
+            bytes.push(0);
+        }
+        if bit {
+            *bytes.last_mut().unwrap() |= 1 << (i % 8);
+        }
+    }
+    bytes
+}
+
+/// A flatbuffer object. Tables list their fields by slot.
+#[derive(Debug)]
+enum Fb {
+    Table(Vec<(u16, Scalar)>),
+    Str(String),
+    /// Vector of tables.
+    Tables(Vec<Self>),
+    /// Vector of 16-byte structs of two longs (`FieldNode`, `Buffer`).
+    Structs(Vec<[i64; 2]>),
+}
+
+#[derive(Debug)]
+enum Scalar {
+    U8(u8),
+    Bool(bool),
+    I16(i16),
+    I64(i64),
+    /// Offset to an object written after the table.
+    Ref(Fb),
+}
+
+impl Scalar {
+    const fn size(&self) -> usize {
+        match self {
+            Self::U8(_) | Self::Bool(_) => 1,
+            Self::I16(_) => 2,
+            Self::Ref(_) => 4,
+            Self::I64(_) => 8,
+        }
+    }
+}
+
+impl Fb {
+    /// A flatbuffer with `self` as its root.
+    fn finish(&self) -> Vec<u8> {
+        let mut buf = vec![0; 4];
+        let root = self.write(&mut buf);
+        patch_offset(&mut buf, 0, root);
+        buf
+    }
+
+    /// Append the object (children after their parent, so offsets point
+    /// forward) and return its position.
+    fn write(&self, buf: &mut Vec<u8>) -> usize {
+        match self {
+            Self::Table(fields) => {
+                // Inline layout: the vtable offset, then each field at its alignment
+                let mut size: usize = 4;
+                let mut slots = Vec::with_capacity(fields.len());
+                for (_, value) in fields {
+                    size = size.next_multiple_of(value.size());
+                    slots.push(size);
+                    size += value.size();
+                }
+                let slot_count = fields
+                    .iter()
+                    .map(|&(id, _)| id as usize + 1)
+                    .max()
+                    .unwrap_or(0);
+                let vtable_len = 4 + 2 * slot_count;
+                // Start the table 8-aligned
+                while !(buf.len() + vtable_len).is_multiple_of(8) {
+                    buf.push(0);
+                }
+                let vtable = buf.len();
+                let mut entries = vec![0_u16; slot_count];
+                for (&(id, _), &offset) in fields.iter().zip(&slots) {
+                    entries[id as usize] = offset as u16;
+                }
+                buf.extend_from_slice(&(vtable_len as u16).to_le_bytes());
+                buf.extend_from_slice(&(size as u16).to_le_bytes());
+                for entry in entries {
+                    buf.extend_from_slice(&entry.to_le_bytes());
+                }
+
+                let table = buf.len();
+                buf.resize(table + size, 0);
+                buf[table..table + 4].copy_from_slice(&((table - vtable) as i32).to_le_bytes());
+                for ((_, value), &offset) in fields.iter().zip(&slots) {
+                    let at = table + offset;
+                    match value {
+                        Scalar::U8(v) => buf[at] = *v,
+                        Scalar::Bool(v) => buf[at] = u8::from(*v),
+                        Scalar::I16(v) => buf[at..at + 2].copy_from_slice(&v.to_le_bytes()),
+                        Scalar::I64(v) => buf[at..at + 8].copy_from_slice(&v.to_le_bytes()),
+                        Scalar::Ref(_) => {}
+                    }
+                }
+                for ((_, value), &offset) in fields.iter().zip(&slots) {
+                    if let Scalar::Ref(child) = value {
+                        let child = child.write(buf);
+                        patch_offset(buf, table + offset, child);
+                    }
+                }
+                table
+            }
+            Self::Str(text) => {
+                buf.resize(buf.len().next_multiple_of(4), 0);
+                let at = buf.len();
+                buf.extend_from_slice(&(text.len() as u32).to_le_bytes());
+                buf.extend_from_slice(text.as_bytes());
+                buf.push(0);
+                at
+            }
+            Self::Tables(items) => {
+                buf.resize(buf.len().next_multiple_of(4), 0);
+                let at = buf.len();
+                buf.extend_from_slice(&(items.len() as u32).to_le_bytes());
+                buf.resize(at + 4 + 4 * items.len(), 0);
+                for (i, item) in items.iter().enumerate() {
+                    let child = item.write(buf);
+                    patch_offset(buf, at + 4 + 4 * i, child);
+                }
+                at
+            }
+            Self::Structs(items) => {
+                // The structs (after the length) 8-aligned
+                while !(buf.len() + 4).is_multiple_of(8) {
+                    buf.push(0);
+                }
+                let at = buf.len();
+                buf.extend_from_slice(&(items.len() as u32).to_le_bytes());
+                for item in items {
+                    for v in item {
+                        buf.extend_from_slice(&v.to_le_bytes());
+                    }
+                }
+                at
+            }
+        }
+    }
+}
+
+/// Point the offset at `at` to `target`.
+fn patch_offset(buf: &mut [u8], at: usize, target: usize) {
+    buf[at..at + 4].copy_from_slice(&((target - at) as u32).to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u32_at(buf: &[u8], at: usize) -> usize {
+        u32::from_le_bytes(buf[at..at + 4].try_into().unwrap()) as usize
+    }
+
+    fn i64_at(buf: &[u8], at: usize) -> i64 {
+        i64::from_le_bytes(buf[at..at + 8].try_into().unwrap())
+    }
+
+    /// Position of field `id` of the table at `table`.
+    fn field_at(buf: &[u8], table: usize, id: usize) -> Option<usize> {
+        let soffset = i32::from_le_bytes(buf[table..table + 4].try_into().unwrap());
+        let vtable = (table as i64 - i64::from(soffset)) as usize;
+        let vtable_len = u16::from_le_bytes([buf[vtable], buf[vtable + 1]]) as usize;
+        let slot = vtable + 4 + 2 * id;
+        if slot + 2 > vtable + vtable_len {
+            return None;
+        }
+        match u16::from_le_bytes([buf[slot], buf[slot + 1]]) {
+            0 => None,
+            offset => Some(table + offset as usize),
+        }
+    }
+
+    /// Target of the offset field `id` of the table at `table`.
+    fn deref(buf: &[u8], table: usize, id: usize) -> usize {
+        let at = field_at(buf, table, id).unwrap();
+        at + u32_at(buf, at)
+    }
+
+    fn string(buf: &[u8], at: usize) -> &str {
+        std::str::from_utf8(&buf[at + 4..at + 4 + u32_at(buf, at)]).unwrap()
+    }
+
+    /// Framed messages of a stream: (metadata, body).
+    fn messages(stream: &[u8]) -> Vec<(&[u8], &[u8])> {
+        let mut messages = Vec::new();
+        let mut at = 0;
+        loop {
+            assert_eq!(u32_at(stream, at), CONTINUATION as usize);
+            let len = u32_at(stream, at + 4);
+            if len == 0 {
+                assert_eq!(at + 8, stream.len());
+                return messages;
+            }
+            assert_eq!(len % 8, 0);
+            let metadata = &stream[at + 8..at + 8 + len];
+            let message = u32_at(metadata, 0);
+            let body_len = i64_at(metadata, field_at(metadata, message, 3).unwrap()) as usize;
+            let body = &stream[at + 8 + len..at + 8 + len + body_len];
+            messages.push((metadata, body));
+            at += 8 + len + body_len;
+        }
+    }
+
+    #[test]
+    fn stream_holds_schema_and_batch() {
+        let mut store = ColumnarStore::new();
+        store.init(3, 3);
+        store.set_column_float64(0, &[1.5, f64::NAN, -2.0]);
+        store.set_column_bool(1, &[1.0, 0.0, f64::NAN]);
+        store.set_column_strings(
+            2,
+            &[String::new(), "ab".to_string(), "c".to_string()],
+            &[1, 0, 2],
+        );
+        store.finalize();
+
+        let columns = [
+            (2, "name".to_string()),
+            (0, "price".to_string()),
+            (1, "ok".to_string()),
+        ];
+        let stream = write_ipc_stream(&store, &[2, 0, 1], &columns);
+        let messages = messages(&stream);
+        assert_eq!(messages.len(), 2);
+
+        // Schema: names and type tags in column order
+        let (schema_meta, schema_body) = messages[0];
+        assert!(schema_body.is_empty());
+        let message = u32_at(schema_meta, 0);
+        assert_eq!(
+            schema_meta[field_at(schema_meta, message, 1).unwrap()],
+            HEADER_SCHEMA
+        );
+        let schema = deref(schema_meta, message, 2);
+        let fields = deref(schema_meta, schema, 1);
+        assert_eq!(u32_at(schema_meta, fields), 3);
+        let field = |i: usize| {
+            let at = fields + 4 + 4 * i;
+            let table = at + u32_at(schema_meta, at);
+            let type_tag = schema_meta[field_at(schema_meta, table, 2).unwrap()];
+            (string(schema_meta, deref(schema_meta, table, 0)), type_tag)
+        };
+        assert_eq!(
+            (0..3).map(field).collect::<Vec<_>>(),
+            vec![
+                ("name", TYPE_UTF8),
+                ("price", TYPE_FLOATING_POINT),
+                ("ok", TYPE_BOOL)
+            ]
+        );
+
+        // Record batch: 3 rows, a null in each column
+        let (meta, body) = messages[1];
+        let message = u32_at(meta, 0);
+        assert_eq!(
+            meta[field_at(meta, message, 1).unwrap()],
+            HEADER_RECORD_BATCH
+        );
+        let batch = deref(meta, message, 2);
+        assert_eq!(i64_at(meta, field_at(meta, batch, 0).unwrap()), 3);
+        let nodes = deref(meta, batch, 1);
+        assert_eq!(u32_at(meta, nodes), 3);
+        assert_eq!((nodes + 4) % 8, 0);
+        let node = |i: usize| {
+            [
+                i64_at(meta, nodes + 4 + 16 * i),
+                i64_at(meta, nodes + 12 + 16 * i),
+            ]
+        };
+        assert_eq!(
+            vec![node(0), node(1), node(2)],
+            vec![[3, 1], [3, 1], [3, 1]]
+        );
+
+        let buffers = deref(meta, batch, 2);
+        assert_eq!(u32_at(meta, buffers), 7);
+        let buffer = |i: usize| {
+            let (offset, len) = (
+                i64_at(meta, buffers + 4 + 16 * i),
+                i64_at(meta, buffers + 12 + 16 * i),
+            );
+            assert_eq!(offset % 8, 0);
+            &body[offset as usize..(offset + len) as usize]
+        };
+        // Utf8: validity (row 1 is empty), offsets, data
+        assert_eq!(buffer(0), &[0b011]);
+        assert_eq!(buffer(1), &[0, 0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0, 3, 0, 0, 0]);
+        assert_eq!(buffer(2), b"cab");
+        // Float64: validity, values
+        assert_eq!(buffer(3), &[0b011]);
+        let values: Vec<f64> = buffer(4)
+            .chunks(8)
+            .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        assert_eq!(values, vec![-2.0, 1.5, 0.0]);
+        // Bool: validity, values
+        assert_eq!(buffer(5), &[0b110]);
+        assert_eq!(buffer(6), &[0b010]);
+    }
+
+    #[test]
+    fn list_columns_nest_a_utf8_child() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 2);
+        store.set_column_string_list(
+            0,
+            &["x".to_string(), "yz".to_string()],
+            &[0, 2, 3],
+            &[0, 1, 1],
+        );
+        store.finalize();
+
+        let stream = write_ipc_stream(&store, &[1, 0], &[(0, "tags".to_string())]);
+        let messages = messages(&stream);
+        let (meta, body) = messages[1];
+        let batch = deref(meta, u32_at(meta, 0), 2);
+        let nodes = deref(meta, batch, 1);
+        let node = |i: usize| {
+            [
+                i64_at(meta, nodes + 4 + 16 * i),
+                i64_at(meta, nodes + 12 + 16 * i),
+            ]
+        };
+        assert_eq!(vec![node(0), node(1)], vec![[2, 0], [3, 0]]);
+
+        let buffers = deref(meta, batch, 2);
+        assert_eq!(u32_at(meta, buffers), 5);
+        let buffer = |i: usize| {
+            let (offset, len) = (
+                i64_at(meta, buffers + 4 + 16 * i),
+                i64_at(meta, buffers + 12 + 16 * i),
+            );
+            &body[offset as usize..(offset + len) as usize]
+        };
+        // List offsets: [yz], [x, yz]
+        assert_eq!(buffer(1), &[0, 0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0]);
+        assert_eq!(buffer(4), b"yzxyz");
+    }
+}
@@ -0,0 +1,236 @@
+/// Fixed-length bit set, typically over physical row indices (one bit per row).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowBitmap {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl RowBitmap {
+    /// Create a bitmap of `len` rows with every bit cleared.
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    /// Create a bitmap of `len` rows with every bit set.
+    pub fn full(len: usize) -> Self {
+        let mut bitmap = Self {
+            words: vec![u64::MAX; len.div_ceil(64)],
+            len,
+        };
+        bitmap.mask_tail();
+        bitmap
+    }
+
+    /// Build a bitmap by evaluating `pred` for every row in `0..len`.
+    pub fn from_fn(len: usize, mut pred: impl FnMut(usize) -> bool) -> Self {
+        let mut bitmap = Self::new(len);
+        for (w, word) in bitmap.words.iter_mut().enumerate() {
+            let base = w * 64;
+            let end = (base + 64).min(len);
+            let mut bits = 0u64;
+            for row in base..end {
+                if pred(row) {
+                    bits |= 1 << (row - base);
+                }
+            }
+            *word = bits;
+        }
+        bitmap
+    }
+
+    /// Parallel `from_fn`: 64-row words are filled on the rayon pool.
+    #[cfg(feature = "threads")]
+    pub fn par_from_fn(len: usize, pred: impl Fn(usize) -> bool + Sync) -> Self {
+        use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+        let mut bitmap = Self::new(len);
+        bitmap
+            .words
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(w, word)| {
+                let base = w * 64;
+                let end = (base + 64).min(len);
+                let mut bits = 0u64;
+                for row in base..end {
+                    if pred(row) {
+                        bits |= 1 << (row - base);
+                    }
+                }
+                *word = bits;
+            });
+        bitmap
+    }
+
+    /// Number of rows covered by the bitmap.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether row `idx` is set. Out-of-range rows are never set.
+    #[inline]
+    pub fn contains(&self, idx: usize) -> bool {
+        idx < self.len && self.words[idx / 64] & (1 << (idx % 64)) != 0
+    }
+
+    /// Set row `idx`. Out-of-range rows are ignored.
+    #[inline]
+    pub fn insert(&mut self, idx: usize) {
+        if idx < self.len {
+            self.words[idx / 64] |= 1 << (idx % 64);
+        }
+    }
+
+    /// Clear row `idx`. Out-of-range rows are ignored.
+    #[inline]
+    pub fn remove(&mut self, idx: usize) {
+        if idx < self.len {
+            self.words[idx / 64] &= !(1 << (idx % 64));
+        }
+    }
+
+    /// Intersect in place (`self &= other`). Bitmaps must cover the same rows.
+    pub fn and_assign(&mut self, other: &Self) {
+        debug_assert_eq!(self.len, other.len, "bitmap length mismatch");
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= b;
+        }
+    }
+
+    /// Union in place (`self |= other`). Bitmaps must cover the same rows.
+    pub fn or_assign(&mut self, other: &Self) {
+        debug_assert_eq!(self.len, other.len, "bitmap length mismatch");
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a |= b;
+        }
+    }
+
+    /// Whether every set row of `self` is also set in `other`.
+    pub fn is_subset_of(&self, other: &Self) -> bool {
+        self.len == other.len
+            && self
+                .words
+                .iter()
+                .zip(&other.words)
+                .all(|(a, b)| a & !b == 0)
+    }
+
+    /// Number of set rows.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Iterate set rows in ascending order.
+    pub fn iter_ones(&self) -> impl Iterator<Item = u32> + '_ {
+        self.words.iter().enumerate().flat_map(|(w, &word)| {
+            let mut bits = word;
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+                let tz = bits.trailing_zeros();
+                bits &= bits - 1;
+                Some((w * 64) as u32 + tz)
+            })
+        })
+    }
+
+    /// Clear bits past `len` in the last word so counts and iteration stay exact.
+    fn mask_tail(&mut self) {
+        let rem = self.len % 64;
+        if rem != 0 {
+            if let Some(last) = self.words.last_mut() {
+                *last &= (1u64 << rem) - 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_is_all_clear() {
+        let bitmap = RowBitmap::new(70);
+        assert_eq!(bitmap.len(), 70);
+        assert_eq!(bitmap.count_ones(), 0);
+        assert_eq!(bitmap.iter_ones().count(), 0);
+    }
+
+    #[test]
+    fn full_masks_tail_bits() {
+        let bitmap = RowBitmap::full(70);
+        assert_eq!(bitmap.count_ones(), 70);
+        assert!(bitmap.contains(69));
+        assert!(!bitmap.contains(70));
+        assert_eq!(bitmap.iter_ones().last(), Some(69));
+    }
+
+    #[test]
+    fn empty_bitmap() {
+        let bitmap = RowBitmap::full(0);
+        assert!(bitmap.is_empty());
+        assert_eq!(bitmap.count_ones(), 0);
+    }
+
+    #[test]
+    fn from_fn_matches_predicate() {
+        let bitmap = RowBitmap::from_fn(130, |i| i % 3 == 0);
+        let ones: Vec<u32> = bitmap.iter_ones().collect();
+        let expected: Vec<u32> = (0..130).filter(|i| i % 3 == 0).collect();
+        assert_eq!(ones, expected);
+    }
+
+    #[test]
+    fn insert_remove_contains() {
+        let mut bitmap = RowBitmap::new(100);
+        bitmap.insert(3);
+        bitmap.insert(64);
+        bitmap.insert(500); // ignored
+        assert!(bitmap.contains(3));
+        assert!(bitmap.contains(64));
+        assert!(!bitmap.contains(500));
+        bitmap.remove(3);
+        assert!(!bitmap.contains(3));
+        assert_eq!(bitmap.count_ones(), 1);
+    }
+
+    #[test]
+    fn and_or_assign() {
+        let mut a = RowBitmap::from_fn(10, |i| i < 6);
+        let b = RowBitmap::from_fn(10, |i| i >= 4);
+        let mut c = a.clone();
+        a.and_assign(&b);
+        assert_eq!(a.iter_ones().collect::<Vec<_>>(), vec![4, 5]);
+        c.or_assign(&b);
+        assert_eq!(c.count_ones(), 10);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn par_from_fn_matches_from_fn() {
+        let pred = |i: usize| i % 7 == 3 || i % 64 == 1;
+        assert_eq!(
+            RowBitmap::par_from_fn(1000, pred),
+            RowBitmap::from_fn(1000, pred)
+        );
+    }
+
+    #[test]
+    fn subset() {
+        let small = RowBitmap::from_fn(100, |i| i % 10 == 0);
+        let large = RowBitmap::from_fn(100, |i| i % 5 == 0);
+        assert!(small.is_subset_of(&large));
+        assert!(!large.is_subset_of(&small));
+        assert!(RowBitmap::new(100).is_subset_of(&small));
+        assert!(!small.is_subset_of(&RowBitmap::full(99)));
+    }
+}
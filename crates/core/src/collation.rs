@@ -0,0 +1,151 @@
+//! Locale-aware string collation (feature `collation`, backed by ICU4X).
+
+use std::cmp::Ordering;
+
+use icu_collator::options::{CollatorOptions, Strength};
+use icu_collator::preferences::CollationNumericOrdering;
+use icu_collator::{Collator, CollatorBorrowed, CollatorPreferences};
+use icu_locale_core::Locale;
+
+use crate::columnar_store::StringInternTable;
+
+/// Which differences collation considers significant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CollationStrength {
+    /// Base letters only ("a" = "á" = "A").
+    Primary,
+    /// Base letters and accents ("a" = "A" ≠ "á").
+    Secondary,
+    /// Base letters, accents, and case.
+    #[default]
+    Tertiary,
+}
+
+/// Collation options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CollationOptions {
+    pub strength: CollationStrength,
+    /// Compare digit runs by numeric value ("a9" < "a10").
+    pub numeric: bool,
+}
+
+/// A locale collator with the options it was built from.
+pub struct Collation {
+    locale: String,
+    options: CollationOptions,
+    collator: CollatorBorrowed<'static>,
+}
+
+impl std::fmt::Debug for Collation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Collation")
+            .field("locale", &self.locale)
+            .field("options", &self.options)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Collation {
+    /// Build a collator for a BCP-47 locale (e.g. `"de"`, `"sv-SE"`).
+    pub fn new(locale: &str, options: CollationOptions) -> Result<Self, String> {
+        let parsed: Locale = locale
+            .parse()
+            .map_err(|e| format!("invalid locale {locale:?}: {e}"))?;
+        let mut prefs = CollatorPreferences::from(&parsed);
+        if options.numeric {
+            prefs.numeric_ordering = Some(CollationNumericOrdering::True);
+        }
+        let mut icu_options = CollatorOptions::default();
+        icu_options.strength = Some(match options.strength {
+            CollationStrength::Primary => Strength::Primary,
+            CollationStrength::Secondary => Strength::Secondary,
+            CollationStrength::Tertiary => Strength::Tertiary,
+        });
+        let collator = Collator::try_new(prefs, icu_options)
+            .map_err(|e| format!("no collation data for {locale:?}: {e}"))?;
+        Ok(Self {
+            locale: locale.to_string(),
+            options,
+            collator,
+        })
+    }
+
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    pub const fn options(&self) -> CollationOptions {
+        self.options
+    }
+
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        self.collator.compare(a, b)
+    }
+
+    /// Rank every interned string in collation order (index = intern ID). Strings
+    /// that collate equal share a rank, so sorting can compare `u32`s.
+    pub fn ranks(&self, intern: &StringInternTable) -> Vec<u32> {
+        let mut ids: Vec<u32> = (0..intern.len() as u32).collect();
+        ids.sort_by(|&a, &b| self.compare(intern.resolve(a), intern.resolve(b)));
+        let mut ranks = vec![0; ids.len()];
+        let mut rank = 0;
+        for (i, &id) in ids.iter().enumerate() {
+            if i > 0
+                && self.compare(intern.resolve(ids[i - 1]), intern.resolve(id)) != Ordering::Equal
+            {
+                rank += 1;
+            }
+            ranks[id as usize] = rank;
+        }
+        ranks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn german_umlaut_sorts_with_base_letter() {
+        let collation = Collation::new("de", CollationOptions::default()).unwrap();
+        assert_eq!(collation.compare("Ärzte", "Zebra"), Ordering::Less);
+        assert_eq!(collation.compare("Ärzte", "Arzt"), Ordering::Greater);
+    }
+
+    #[test]
+    fn numeric_option() {
+        let plain = Collation::new("en", CollationOptions::default()).unwrap();
+        assert_eq!(plain.compare("a10", "a9"), Ordering::Less);
+        let numeric = Collation::new(
+            "en",
+            CollationOptions {
+                numeric: true,
+                ..CollationOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(numeric.compare("a10", "a9"), Ordering::Greater);
+    }
+
+    #[test]
+    fn ranks_share_equal_strings() {
+        let mut intern = StringInternTable::new();
+        for s in ["", "b", "A", "a", "Ä"] {
+            intern.intern(s);
+        }
+        let collation = Collation::new(
+            "de",
+            CollationOptions {
+                strength: CollationStrength::Primary,
+                numeric: false,
+            },
+        )
+        .unwrap();
+        assert_eq!(collation.ranks(&intern), vec![0, 2, 1, 1, 1]);
+    }
+
+    #[test]
+    fn invalid_locale_is_error() {
+        assert!(Collation::new("not a locale!", CollationOptions::default()).is_err());
+    }
+}
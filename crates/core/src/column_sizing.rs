@@ -0,0 +1,242 @@
+//! User-set column widths (resize, auto-size) layered over the JS column layouts.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+use crate::layout::{ColumnLayout, DimensionValue};
+
+/// How `resize_column` makes room for a column's new width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResizeMode {
+    /// The table grows or shrinks; other columns keep their widths.
+    #[default]
+    Grow,
+    /// The right neighbor absorbs the difference, like a spreadsheet, so the
+    /// table keeps its width. The last column grows the table instead.
+    Neighbor,
+}
+
+/// `(min, max)` width of a column, px.
+pub fn width_limits(column: &ColumnLayout) -> (f32, f32) {
+    (
+        column.min_width.unwrap_or(0.0),
+        column.max_width.unwrap_or(f32::INFINITY),
+    )
+}
+
+/// Fix `columns` at the given widths (`None` = leave the column as is): the width
+/// replaces the column's flex sizing, so the layout no longer grows or shrinks it.
+pub fn apply_column_widths(columns: &mut [ColumnLayout], widths: &[Option<f32>]) {
+    for (column, width) in columns.iter_mut().zip(widths) {
+        if let Some(width) = *width {
+            column.width = width;
+            column.flex_grow = 0.0;
+            column.flex_shrink = 0.0;
+            column.flex_basis = DimensionValue::Auto;
+        }
+    }
+}
+
+/// Resize column `col` of `widths` towards `new_width` within its `limits`.
+///
+/// With `ResizeMode::Neighbor`, the column only moves as far as its right
+/// neighbor can follow within its own limits. Out-of-range columns are ignored.
+pub fn resize_column(
+    widths: &mut [f32],
+    limits: &[(f32, f32)],
+    col: usize,
+    new_width: f32,
+    mode: ResizeMode,
+) {
+    let limit = |i: usize| limits.get(i).copied().unwrap_or((0.0, f32::INFINITY));
+    let Some(&old) = widths.get(col) else {
+        return;
+    };
+    let (min, max) = limit(col);
+    let new_width = new_width.clamp(min, max.max(min));
+    let neighbor = col + 1;
+    match mode {
+        ResizeMode::Neighbor if neighbor < widths.len() => {
+            let (n_min, n_max) = limit(neighbor);
+            let n_old = widths[neighbor];
+            let n_new = (n_old - (new_width - old)).clamp(n_min, n_max.max(n_min));
+            widths[col] = old + (n_old - n_new);
+            widths[neighbor] = n_new;
+        }
+        _ => widths[col] = new_width,
+    }
+}
+
+/// Widths for the columns `keys` from `{ [key]: width }`, taking each used entry
+/// out of `by_key`; the rest stay there, pending until a column with that key
+/// arrives (see [`rekey_widths`]).
+pub fn widths_by_key<S: BuildHasher>(
+    keys: &[Option<String>],
+    by_key: &mut HashMap<String, f32, S>,
+) -> Vec<Option<f32>> {
+    keys.iter()
+        .map(|key| key.as_ref().and_then(|key| by_key.remove(key)))
+        .collect()
+}
+
+/// Re-index `widths` of the columns `keys` for the columns `new_keys`.
+///
+/// Keyed columns take the width of their key (or a `pending` one), unkeyed ones
+/// keep the width at their index. Widths of keys no longer present move to
+/// `pending`.
+pub fn rekey_widths<S: BuildHasher>(
+    keys: &[Option<String>],
+    widths: &[Option<f32>],
+    new_keys: &[Option<String>],
+    pending: &mut HashMap<String, f32, S>,
+) -> Vec<Option<f32>> {
+    let mut by_key: HashMap<&str, f32> = keys
+        .iter()
+        .zip(widths)
+        .filter_map(|(key, width)| Some((key.as_deref()?, (*width)?)))
+        .collect();
+    let new_widths = new_keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| {
+            key.as_ref().map_or_else(
+                || widths.get(i).copied().flatten(),
+                |key| by_key.remove(key.as_str()).or_else(|| pending.remove(key)),
+            )
+        })
+        .collect();
+    pending.extend(
+        by_key
+            .into_iter()
+            .map(|(key, width)| (key.to_string(), width)),
+    );
+    new_widths
+}
+
+/// The widths of keyed columns, plus the `pending` ones, by key.
+pub fn keyed_widths<'a, S: BuildHasher>(
+    keys: &'a [Option<String>],
+    widths: &[Option<f32>],
+    pending: &'a HashMap<String, f32, S>,
+) -> BTreeMap<&'a str, f32> {
+    let mut by_key: BTreeMap<&str, f32> = pending
+        .iter()
+        .map(|(key, &width)| (key.as_str(), width))
+        .collect();
+    for (key, width) in keys.iter().zip(widths) {
+        if let (Some(key), Some(width)) = (key, width) {
+            by_key.insert(key, *width);
+        }
+    }
+    by_key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: [(f32, f32); 3] = [(50.0, 200.0), (80.0, f32::INFINITY), (0.0, 120.0)];
+
+    #[test]
+    fn grow_clamps_to_limits() {
+        let mut widths = vec![100.0, 100.0, 100.0];
+        resize_column(&mut widths, &LIMITS, 0, 300.0, ResizeMode::Grow);
+        resize_column(&mut widths, &LIMITS, 1, 10.0, ResizeMode::Grow);
+        resize_column(&mut widths, &LIMITS, 7, 10.0, ResizeMode::Grow);
+        assert_eq!(widths, vec![200.0, 80.0, 100.0]);
+    }
+
+    #[test]
+    fn neighbor_absorbs_the_difference() {
+        let mut widths = vec![100.0, 100.0, 100.0];
+        resize_column(&mut widths, &LIMITS, 0, 110.0, ResizeMode::Neighbor);
+        assert_eq!(widths, vec![110.0, 90.0, 100.0]);
+
+        // The neighbor stops at its min width, and so does the column
+        resize_column(&mut widths, &LIMITS, 0, 180.0, ResizeMode::Neighbor);
+        assert_eq!(widths, vec![120.0, 80.0, 100.0]);
+
+        // Shrinking grows the neighbor up to its max
+        let mut widths = vec![100.0, 100.0, 110.0];
+        resize_column(&mut widths, &LIMITS, 1, 80.0, ResizeMode::Neighbor);
+        assert_eq!(widths, vec![100.0, 90.0, 120.0]);
+    }
+
+    #[test]
+    fn last_column_grows_the_table() {
+        let mut widths = vec![100.0, 100.0, 100.0];
+        resize_column(&mut widths, &LIMITS, 2, 110.0, ResizeMode::Neighbor);
+        assert_eq!(widths, vec![100.0, 100.0, 110.0]);
+    }
+
+    #[test]
+    fn applied_widths_replace_flex_sizing() {
+        let mut columns = vec![
+            ColumnLayout {
+                width: 100.0,
+                flex_grow: 1.0,
+                flex_basis: DimensionValue::Length(40.0),
+                ..ColumnLayout::default()
+            };
+            2
+        ];
+        apply_column_widths(&mut columns, &[None, Some(150.0)]);
+        assert_eq!(
+            columns
+                .iter()
+                .map(|c| (c.width, c.flex_grow))
+                .collect::<Vec<_>>(),
+            vec![(100.0, 1.0), (150.0, 0.0)]
+        );
+        assert!(matches!(columns[0].flex_basis, DimensionValue::Length(_)));
+        assert!(matches!(columns[1].flex_basis, DimensionValue::Auto));
+    }
+
+    fn keys(keys: &[&str]) -> Vec<Option<String>> {
+        keys.iter()
+            .map(|k| (!k.is_empty()).then(|| (*k).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn widths_follow_their_key_across_reorders() {
+        let before = keys(&["a", "", "c"]);
+        let mut saved = HashMap::from([("a".to_string(), 120.0), ("c".to_string(), 60.0)]);
+        let widths = widths_by_key(&before, &mut saved);
+        assert_eq!(widths, vec![Some(120.0), None, Some(60.0)]);
+        assert!(saved.is_empty());
+
+        let mut pending = HashMap::new();
+        let after = keys(&["c", "", "a"]);
+        let widths = rekey_widths(&before, &widths, &after, &mut pending);
+        assert_eq!(widths, vec![Some(60.0), None, Some(120.0)]);
+        assert!(pending.is_empty());
+        assert_eq!(
+            keyed_widths(&after, &widths, &pending),
+            BTreeMap::from([("a", 120.0), ("c", 60.0)])
+        );
+    }
+
+    #[test]
+    fn widths_of_absent_keys_stay_pending() {
+        let before = keys(&["a", "b"]);
+        let mut pending = HashMap::from([("a".to_string(), 90.0), ("z".to_string(), 40.0)]);
+        let widths = widths_by_key(&before, &mut pending);
+        assert_eq!(widths, vec![Some(90.0), None]);
+        assert_eq!(pending, HashMap::from([("z".to_string(), 40.0)]));
+        assert_eq!(
+            keyed_widths(&before, &widths, &pending),
+            BTreeMap::from([("a", 90.0), ("z", 40.0)])
+        );
+
+        // "a" leaves and waits; "z" arrives and takes its width
+        let after = keys(&["b", "z"]);
+        let widths = rekey_widths(&before, &widths, &after, &mut pending);
+        assert_eq!(widths, vec![None, Some(40.0)]);
+        assert_eq!(pending, HashMap::from([("a".to_string(), 90.0)]));
+
+        let widths = rekey_widths(&after, &widths, &before, &mut pending);
+        assert_eq!(widths, vec![Some(90.0), None]);
+        assert_eq!(pending, HashMap::from([("z".to_string(), 40.0)]));
+    }
+}
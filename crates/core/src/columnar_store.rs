@@ -1,6 +1,47 @@
-use std::collections::HashMap;
-
-use crate::types::{ColumnFilter, FilterOp, FilterValue, GlobalFilter, SortConfig, SortDirection};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+
+#[cfg(feature = "threads")]
+use rayon::iter::{IntoParallelRefIterator, ParallelExtend, ParallelIterator};
+#[cfg(feature = "threads")]
+use rayon::slice::ParallelSliceMut;
+
+use crate::aggregate::{Accumulator, Aggregate};
+use crate::bitmap::RowBitmap;
+use crate::cardinality::DistinctCounter;
+#[cfg(feature = "collation")]
+use crate::collation::Collation;
+use crate::expr::Expr;
+use crate::format::FormatSpec;
+use crate::layout_buffer::{CellStyle, RowAreas};
+use crate::natural_sort::natural_cmp;
+use crate::prefix_index::PrefixIndex;
+use crate::radix_sort::{counting_sort_by_rank, f64_key, radix_sort_by_key};
+#[cfg(feature = "threads")]
+use crate::radix_sort::{par_counting_sort_by_rank, par_radix_sort_by_key};
+#[cfg(test)]
+use crate::relative_date::RelativeDate;
+use crate::sparkline;
+use crate::state::{GroupKey, SavedState, Writer};
+use crate::text_wrap::wrap_lines;
+use crate::tree::TreeData;
+use crate::types::{
+    AutoHeight, ColumnFilter, ConditionKind, ConditionStats, ConditionalFormat, FacetCount,
+    FilterOp, FilterValue, FormatCondition, GlobalFilter, GroupHeaders, GroupSort, NullOrder,
+    QueryDescriptor, RebuildProgress, RowMerge, ScrollAnchor, SortConfig, SortDirection,
+    ViewPreset, ViewStats,
+};
+use crate::validation::{ColumnValidation, ValidationRules};
+use crate::virtual_scroll::{
+    compute_virtual_slice, compute_virtual_slice_variable, RowHeightIndex, ScrollState,
+    VirtualSlice,
+};
+use crate::visualization::DataVisualization;
+use crate::window::WindowFn;
+
+/// `(min, max)` by column.
+type ColumnRanges = HashMap<usize, (f64, f64)>;
 
 /// Column data type tag.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -8,6 +49,8 @@ pub enum ColumnType {
     Float64,
     String,
     Bool,
+    StringList,
+    Float64List,
 }
 
 /// Type-specific columnar data.
@@ -22,6 +65,38 @@ pub enum ColumnData {
     },
     /// Bool stored as f64: 0.0 = false, 1.0 = true, NaN = null.
     Bool(Vec<f64>),
+    /// Multi-value string column (e.g. tags): row `r` holds the intern IDs
+    /// `values[offsets[r]..offsets[r + 1]]`. An empty list is null.
+    StringList {
+        offsets: Vec<u32>,
+        values: Vec<u32>,
+        intern: StringInternTable,
+    },
+    /// Numeric series column (e.g. sparklines): row `r` holds
+    /// `values[offsets[r]..offsets[r + 1]]`. An empty series is null.
+    Float64List { offsets: Vec<u32>, values: Vec<f64> },
+}
+
+impl ColumnData {
+    /// Intern IDs of a `StringList` row. Empty for other column types.
+    pub fn list_ids(&self, row: usize) -> &[u32] {
+        match self {
+            Self::StringList {
+                offsets, values, ..
+            } => &values[offsets[row] as usize..offsets[row + 1] as usize],
+            _ => &[],
+        }
+    }
+
+    /// Values of a `Float64List` row. Empty for other column types.
+    pub fn series(&self, row: usize) -> &[f64] {
+        match self {
+            Self::Float64List { offsets, values } => {
+                &values[offsets[row] as usize..offsets[row + 1] as usize]
+            }
+            _ => &[],
+        }
+    }
 }
 
 /// Interned string table for efficient comparison and compact storage.
@@ -66,6 +141,16 @@ impl StringInternTable {
         self.offsets.len()
     }
 
+    /// ID of an already-interned string.
+    pub fn get(&self, s: &str) -> Option<u32> {
+        self.lookup.get(s).copied()
+    }
+
+    /// Iterate `(id, string)` pairs in ID order.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> {
+        (0..self.offsets.len() as u32).map(|id| (id, self.resolve(id)))
+    }
+
     pub const fn is_empty(&self) -> bool {
         self.offsets.is_empty()
     }
@@ -77,6 +162,218 @@ impl Default for StringInternTable {
     }
 }
 
+/// Cached result of a single column filter condition, valid for one data generation.
+#[derive(Debug)]
+struct FilterCacheEntry {
+    generation: u64,
+    filter: ColumnFilter,
+    bitmap: RowBitmap,
+    /// Rows the bitmap was evaluated on; bits outside it are unknown (stored as 0).
+    /// `None` = every row. A partial entry is reusable while the incoming mask is a
+    /// subset of its domain.
+    domain: Option<RowBitmap>,
+}
+
+/// Synthetic header row of a group in a grouped view (`set_group_by`). Its view
+/// position holds the group's first data row as a placeholder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupRow {
+    /// View position of the header row.
+    pub pos: u32,
+    /// Nesting level: index into the `group_by` columns.
+    pub depth: u32,
+    /// The group's data rows, as a range of `grouped_rows`.
+    pub rows: std::ops::Range<u32>,
+    pub expanded: bool,
+}
+
+/// Derived column computed over the sorted view (`add_window_column`).
+#[derive(Debug)]
+struct WindowColumn {
+    source: usize,
+    function: WindowFn,
+    /// Per data row.
+    values: Vec<f64>,
+}
+
+/// Prefix-sum index of the view's row heights, valid until the view or the default
+/// row height changes.
+#[derive(Debug)]
+struct ViewRowHeights {
+    default_height: f64,
+    /// View position of each data row (`u32::MAX` = not in view).
+    positions: Vec<u32>,
+    /// Row heights including any expanded detail area.
+    index: RowHeightIndex,
+    /// Detail area height per view position; empty when no row is expanded.
+    detail: Vec<f32>,
+    /// Group header height per view position; empty without group headers.
+    group_header: Vec<f32>,
+    /// View positions where a group starts, ascending.
+    group_starts: Vec<u32>,
+}
+
+/// Rows filtered, sorted, or merged per unit of work of `rebuild_view_step`.
+const REBUILD_STEP_ROWS: usize = 1 << 14;
+
+/// A time-sliced view rebuild in progress (`rebuild_view_step`).
+#[derive(Debug)]
+struct PendingRebuild {
+    started: f64,
+    stats: ViewStats,
+    phase: RebuildPhase,
+}
+
+#[derive(Debug)]
+enum RebuildPhase {
+    /// Filtering physical rows from `next_row` on into `indices`.
+    Filter { indices: Vec<u32>, next_row: usize },
+    /// Sorting runs of `REBUILD_STEP_ROWS` rows, from `next` on.
+    SortRuns { indices: Vec<u32>, next: usize },
+    /// Merging adjacent sorted runs of `width` rows of `src` into `dst`: the pair
+    /// starting at `left`, from positions `i` (left run) and `j` (right run).
+    Merge {
+        src: Vec<u32>,
+        dst: Vec<u32>,
+        width: usize,
+        left: usize,
+        i: usize,
+        j: usize,
+    },
+}
+
+/// Stable ascending order of every row by one column (nulls first, as the smallest
+/// value), valid for one data generation. Serves both directions and null placements.
+#[derive(Debug)]
+struct SortPermutation {
+    generation: u64,
+    column_index: usize,
+    natural: bool,
+    order: Vec<u32>,
+    /// Bit `i` is set when `order[i]` ties with `order[i - 1]`.
+    ties: RowBitmap,
+    /// Number of leading null rows in `order`.
+    null_count: usize,
+}
+
+/// Number of sort permutations kept.
+const SORT_PERMUTATION_SLOTS: usize = 4;
+
+/// `always_visible` marks: kept at the start or the end of the view.
+const ALWAYS_VISIBLE_TOP: u8 = 1;
+const ALWAYS_VISIBLE_BOTTOM: u8 = 2;
+
+/// Externally evaluated filter predicate, called as `(row, cell value)` with `None`
+/// for null cells. Returns `true` to keep the row.
+pub type CustomFilterFn = Box<dyn FnMut(usize, Option<FilterValue>) -> bool>;
+
+/// Externally measured text height: `(text, available width) -> height` in px.
+pub type MeasureTextFn = Box<dyn FnMut(&str, f64) -> f64>;
+
+/// Externally measured text width: `text -> width` in px.
+pub type MeasureWidthFn = Box<dyn FnMut(&str) -> f64>;
+
+/// Monotonic clock in milliseconds, used only for view statistics.
+pub type ClockFn = fn() -> f64;
+
+/// Predicates are only ever called through `&mut`, so with `threads` they are held
+/// in a `SyncWrapper`, which keeps the store `Sync` for worker threads.
+#[cfg(feature = "threads")]
+type PredicateSlot = sync_wrapper::SyncWrapper<CustomFilterFn>;
+#[cfg(not(feature = "threads"))]
+type PredicateSlot = CustomFilterFn;
+#[cfg(feature = "threads")]
+type MeasureSlot = sync_wrapper::SyncWrapper<MeasureTextFn>;
+#[cfg(not(feature = "threads"))]
+type MeasureSlot = MeasureTextFn;
+#[cfg(feature = "threads")]
+type WidthSlot = sync_wrapper::SyncWrapper<MeasureWidthFn>;
+#[cfg(not(feature = "threads"))]
+type WidthSlot = MeasureWidthFn;
+
+/// Rows at or above which sorting and filtering run on the rayon pool.
+#[cfg(feature = "threads")]
+const PARALLEL_MIN_ROWS: usize = 1 << 16;
+
+/// Whether the rayon pool may be used. On wasm32 it stays off until the host has
+/// started the worker pool, so pages without cross-origin isolation run single-threaded.
+static PARALLEL_ENABLED: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(!cfg!(target_arch = "wasm32"));
+
+/// Allow or stop large sorts, filters and view writes on the rayon pool. Without
+/// the `threads` feature they always run on the calling thread.
+pub fn set_parallel_enabled(enabled: bool) {
+    PARALLEL_ENABLED.store(enabled, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Whether work over `rows` rows should run on the rayon pool.
+#[cfg(feature = "threads")]
+fn parallel(rows: usize) -> bool {
+    rows >= PARALLEL_MIN_ROWS && PARALLEL_ENABLED.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+/// A custom predicate registered for one column.
+struct CustomFilter {
+    column_index: usize,
+    predicate: PredicateSlot,
+}
+
+impl CustomFilter {
+    fn call(&mut self, row: usize, value: Option<FilterValue>) -> bool {
+        #[cfg(feature = "threads")]
+        let predicate = self.predicate.get_mut();
+        #[cfg(not(feature = "threads"))]
+        let predicate = &mut self.predicate;
+        predicate(row, value)
+    }
+}
+
+impl std::fmt::Debug for CustomFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomFilter")
+            .field("column_index", &self.column_index)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The registered text measure callback.
+struct TextMeasure(MeasureSlot);
+
+impl TextMeasure {
+    fn call(&mut self, text: &str, width: f64) -> f64 {
+        #[cfg(feature = "threads")]
+        let measure = self.0.get_mut();
+        #[cfg(not(feature = "threads"))]
+        let measure = &mut self.0;
+        measure(text, width)
+    }
+}
+
+impl std::fmt::Debug for TextMeasure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextMeasure").finish_non_exhaustive()
+    }
+}
+
+/// The registered text width callback.
+struct TextWidthMeasure(WidthSlot);
+
+impl TextWidthMeasure {
+    fn call(&mut self, text: &str) -> f64 {
+        #[cfg(feature = "threads")]
+        let measure = self.0.get_mut();
+        #[cfg(not(feature = "threads"))]
+        let measure = &mut self.0;
+        measure(text)
+    }
+}
+
+impl std::fmt::Debug for TextWidthMeasure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextWidthMeasure").finish_non_exhaustive()
+    }
+}
+
 /// Columnar data store: one typed array per column.
 #[derive(Debug)]
 pub struct ColumnarStore {
@@ -85,31 +382,202 @@ pub struct ColumnarStore {
     pub generation: u64,
     view_indices: Vec<u32>,
     view_dirty: bool,
+    /// Bumped whenever `rebuild_view` changes the view or row values.
+    view_version: u64,
+    /// Rows whose values changed via the cell update APIs since the last rebuild.
+    updated_rows: Vec<u32>,
+    /// Columns of `updated_rows` cells.
+    updated_columns: Vec<usize>,
+    /// Frames a row update is reported for (`set_update_flash_frames`; 0 = off).
+    update_flash_frames: u32,
+    /// Frame counter (`advance_update_tick`) and the tick of each data row's last
+    /// cell update (0 = none), kept while flashing is on.
+    update_tick: u64,
+    row_updated_at: Vec<u64>,
+    /// Data rows taken out of the view by `remove_row` (their data stays in place).
+    removed_rows: Vec<bool>,
+    removed_count: usize,
+    /// Version of each data row (`bump_row_version`; 0 = never changed).
+    row_versions: Vec<u32>,
+    /// Time-sliced rebuild in progress; the current view stays in place meanwhile.
+    pending_rebuild: Option<PendingRebuild>,
     sort_configs: Vec<SortConfig>,
+    group_sort: Option<GroupSort>,
+    /// User-defined row order by data row, replacing the sort (`set_manual_row_order`).
+    manual_order: Option<Vec<u32>>,
+    /// Columns the view is grouped by, outermost first (`set_group_by`); empty = off.
+    group_by: Vec<usize>,
+    /// Key paths of collapsed groups (`set_group_expanded`).
+    collapsed_groups: HashSet<Vec<Option<u64>>>,
+    /// Group header rows of the view, by ascending position.
+    view_groups: Vec<GroupRow>,
+    /// Grouped rows of the view in group order, collapsed ones included.
+    grouped_rows: Vec<u32>,
+    /// Filtered rows when the view is not just them (pagination or grouping).
+    filtered_rows: Option<Vec<u32>>,
+    /// Rows passing the filters in tree mode, before ancestors are added and
+    /// collapsed branches dropped.
+    matched_rows: Option<Vec<u32>>,
+    /// Break sort ties by ascending row index (default), independent of input order.
+    row_order_tiebreak: bool,
     column_filters: Vec<ColumnFilter>,
+    filter_cache: Vec<FilterCacheEntry>,
+    /// Recently used single-column sort orders, least recently used first.
+    sort_permutations: Vec<SortPermutation>,
+    /// `Float64` keys of expression sorts during a sort, addressed as the columns
+    /// after `data` (`push_expression_columns`); empty otherwise.
+    sort_scratch: Vec<ColumnData>,
+    /// Observed pass rate (passed / scanned) per condition from previous rebuilds.
+    selectivity: Vec<(ColumnFilter, f64)>,
+    column_formats: HashMap<usize, FormatSpec>,
+    /// Validation rules by column with the errors they found (`set_column_validation`).
+    validations: HashMap<usize, ColumnValidation>,
+    /// Conditional formatting rules (`set_conditional_formats`).
+    conditional_formats: Vec<ConditionalFormat>,
+    /// View version and per-rule column statistics of `conditional_formats`:
+    /// the cut-off value of `Top` rules, the min and max of `Scale` rules.
+    conditional_bounds: Option<(u64, Vec<(f64, f64)>)>,
+    /// Color scales and data bars by column (`set_data_visualization`).
+    visualizations: HashMap<usize, DataVisualization>,
+    /// View version and filtered `(min, max)` of each visualized column.
+    visual_ranges: Option<(u64, ColumnRanges)>,
+    /// Merged cells per column (`set_row_merge`).
+    row_merges: HashMap<usize, RowMerge>,
+    /// Layout overrides by (data row, column) (`set_cell_style`).
+    cell_styles: HashMap<(u32, u32), CellStyle>,
+    /// Column spans by (data row, column) (`set_cell_col_span`).
+    col_spans: HashMap<(u32, u32), u32>,
+    /// Parent-child rows (`set_tree_structure`); `None` = flat rows.
+    tree: Option<TreeData>,
+    /// Column indented by tree depth, and the indent per level in px.
+    tree_indent: (usize, f32),
+    /// Derived columns by ID (`add_window_column`); `None` = removed.
+    window_columns: Vec<Option<WindowColumn>>,
+    prefix_indexes: HashMap<usize, PrefixIndex>,
+    /// Per-column rank of each intern ID for non-bytewise string ordering (index = ID).
+    /// Filled before sorting; cleared when data or ordering rules change.
+    string_ranks: HashMap<usize, Vec<u32>>,
+    /// User-defined value order per string column (e.g. Open < In Progress < Done).
+    custom_sort_orders: HashMap<usize, Vec<String>>,
+    #[cfg(feature = "collation")]
+    default_collation: Option<Collation>,
+    #[cfg(feature = "collation")]
+    column_collations: HashMap<usize, Collation>,
     global_filter: Option<GlobalFilter>,
+    custom_filters: Vec<CustomFilter>,
+    /// Parsed sources of the `Expression` column filters.
+    filter_exprs: HashMap<String, Expr>,
+    now_ms: f64,
+    utc_offset_minutes: i32,
+    presets: BTreeMap<String, ViewPreset>,
+    clock: Option<ClockFn>,
+    last_view_stats: ViewStats,
     row_height: f64,
     viewport_height: f64,
     overscan: usize,
+    /// Per-data-row heights (NaN = default, or not yet measured with auto-height);
+    /// empty = uniform rows unless auto-height is on.
+    row_heights: Vec<f64>,
+    view_row_heights: Option<ViewRowHeights>,
+    /// Data rows showing a detail area below them (`set_expanded_rows`).
+    expanded_rows: Vec<bool>,
+    detail_height: f64,
+    group_headers: Option<GroupHeaders>,
+    /// Per-data-row `ALWAYS_VISIBLE_*` marks (`set_always_visible_rows`); empty = none.
+    always_visible: Vec<u8>,
+    /// Marked rows at the start and end of the current view.
+    always_visible_counts: (usize, usize),
+    auto_height: Option<AutoHeight>,
+    text_measure: Option<TextMeasure>,
+    /// Breaks auto-height text into lines (`set_text_width_measure`).
+    text_width_measure: Option<TextWidthMeasure>,
+    /// Lazy-row mode: rows arrive in blocks of this many rows.
+    row_block_size: Option<usize>,
+    loaded_blocks: Vec<bool>,
+    /// Remote row model: the key of the query the loaded rows answer; sorting and
+    /// filtering are left to the server.
+    remote_query: Option<String>,
     page_index: Option<u32>,
     page_size: Option<u32>,
     filtered_total: usize,
 }
 
 impl ColumnarStore {
-    pub const fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             data: Vec::new(),
             row_count: 0,
             generation: 0,
             view_indices: Vec::new(),
             view_dirty: true,
+            view_version: 0,
+            updated_rows: Vec::new(),
+            updated_columns: Vec::new(),
+            update_flash_frames: 0,
+            update_tick: 1,
+            row_updated_at: Vec::new(),
+            removed_rows: Vec::new(),
+            removed_count: 0,
+            row_versions: Vec::new(),
+            pending_rebuild: None,
             sort_configs: Vec::new(),
+            group_sort: None,
+            manual_order: None,
+            group_by: Vec::new(),
+            collapsed_groups: HashSet::new(),
+            view_groups: Vec::new(),
+            grouped_rows: Vec::new(),
+            filtered_rows: None,
+            matched_rows: None,
+            row_order_tiebreak: true,
             column_filters: Vec::new(),
+            filter_cache: Vec::new(),
+            sort_permutations: Vec::new(),
+            sort_scratch: Vec::new(),
+            selectivity: Vec::new(),
+            column_formats: HashMap::new(),
+            validations: HashMap::new(),
+            conditional_formats: Vec::new(),
+            conditional_bounds: None,
+            visualizations: HashMap::new(),
+            visual_ranges: None,
+            row_merges: HashMap::new(),
+            cell_styles: HashMap::new(),
+            col_spans: HashMap::new(),
+            tree: None,
+            tree_indent: (0, 16.0),
+            window_columns: Vec::new(),
+            prefix_indexes: HashMap::new(),
+            string_ranks: HashMap::new(),
+            custom_sort_orders: HashMap::new(),
+            #[cfg(feature = "collation")]
+            default_collation: None,
+            #[cfg(feature = "collation")]
+            column_collations: HashMap::new(),
             global_filter: None,
+            custom_filters: Vec::new(),
+            filter_exprs: HashMap::new(),
+            now_ms: 0.0,
+            utc_offset_minutes: 0,
+            presets: BTreeMap::new(),
+            clock: None,
+            last_view_stats: ViewStats::default(),
             row_height: 36.0,
             viewport_height: 600.0,
             overscan: 5,
+            row_heights: Vec::new(),
+            view_row_heights: None,
+            expanded_rows: Vec::new(),
+            detail_height: 0.0,
+            group_headers: None,
+            always_visible: Vec::new(),
+            always_visible_counts: (0, 0),
+            auto_height: None,
+            text_measure: None,
+            text_width_measure: None,
+            row_block_size: None,
+            loaded_blocks: Vec::new(),
+            remote_query: None,
             page_index: None,
             page_size: None,
             filtered_total: 0,
@@ -127,6 +595,17 @@ impl ColumnarStore {
         }
         self.row_count = row_count;
         self.generation += 1;
+        self.row_heights.clear();
+        self.row_updated_at.clear();
+        self.removed_rows.clear();
+        self.removed_count = 0;
+        self.row_versions.clear();
+        self.expanded_rows.clear();
+        self.always_visible.clear();
+        self.cell_styles.clear();
+        self.col_spans.clear();
+        self.tree = None;
+        self.view_row_heights = None;
         self.view_dirty = true;
     }
 
@@ -158,8 +637,48 @@ impl ColumnarStore {
         }
     }
 
-    /// Finalize after all columns are set. Marks view as dirty.
-    pub const fn finalize(&mut self) {
+    /// Set a multi-value string column. `offsets` has `row_count + 1` entries;
+    /// row `r` holds `ids[offsets[r]..offsets[r + 1]]`, each an index into `unique`.
+    pub fn set_column_string_list(
+        &mut self,
+        col_idx: usize,
+        unique: &[String],
+        offsets: &[u32],
+        ids: &[u32],
+    ) {
+        if col_idx < self.data.len() {
+            let mut intern = StringInternTable::new();
+            let id_map: Vec<u32> = unique.iter().map(|s| intern.intern(s)).collect();
+            self.data[col_idx] = ColumnData::StringList {
+                offsets: offsets.to_vec(),
+                values: ids.iter().map(|&id| id_map[id as usize]).collect(),
+                intern,
+            };
+        }
+    }
+
+    /// Set a numeric series column. `offsets` has `row_count + 1` entries;
+    /// row `r` holds `values[offsets[r]..offsets[r + 1]]`.
+    pub fn set_column_float64_list(&mut self, col_idx: usize, offsets: &[u32], values: &[f64]) {
+        if col_idx < self.data.len() {
+            self.data[col_idx] = ColumnData::Float64List {
+                offsets: offsets.to_vec(),
+                values: values.to_vec(),
+            };
+        }
+    }
+
+    /// Finalize after all columns are set. Marks view as dirty and drops cached filter results.
+    pub fn finalize(&mut self) {
+        if self.auto_height.is_some() {
+            self.row_heights.clear();
+            self.view_row_heights = None;
+        }
+        self.filter_cache.clear();
+        self.string_ranks.clear();
+        self.sort_permutations.clear();
+        self.refresh_prefix_indexes();
+        self.revalidate();
         self.view_dirty = true;
     }
 
@@ -178,6 +697,11 @@ impl ColumnarStore {
                 ColumnData::Strings { ids, .. } => {
                     ids.resize(new_count, 0);
                 }
+                ColumnData::StringList { offsets, .. }
+                | ColumnData::Float64List { offsets, .. } => {
+                    let end = offsets.last().copied().unwrap_or(0);
+                    offsets.resize(new_count + 1, end);
+                }
             }
         }
 
@@ -225,942 +749,6921 @@ impl ColumnarStore {
         }
     }
 
+    /// Append multi-value string rows starting at `offset`: merge new unique strings
+    /// into the intern table and splice the lists in place of rows
+    /// `offset..offset + new_offsets.len() - 1`.
+    pub fn append_column_string_list(
+        &mut self,
+        col_idx: usize,
+        offset: usize,
+        new_unique: &[String],
+        new_offsets: &[u32],
+        new_ids: &[u32],
+    ) {
+        if let Some(ColumnData::StringList {
+            offsets,
+            values,
+            intern,
+        }) = self.data.get_mut(col_idx)
+        {
+            let id_map: Vec<u32> = new_unique.iter().map(|s| intern.intern(s)).collect();
+            splice_list_rows(offsets, values, offset, new_offsets, new_ids, |&id| {
+                id_map[id as usize]
+            });
+        }
+    }
+
+    /// Append numeric series rows starting at `offset`, in place of rows
+    /// `offset..offset + new_offsets.len() - 1`.
+    pub fn append_column_float64_list(
+        &mut self,
+        col_idx: usize,
+        offset: usize,
+        new_offsets: &[u32],
+        new_values: &[f64],
+    ) {
+        if let Some(ColumnData::Float64List { offsets, values }) = self.data.get_mut(col_idx) {
+            splice_list_rows(offsets, values, offset, new_offsets, new_values, |&v| v);
+        }
+    }
+
     /// Finalize append. Marks view dirty so `rebuild_view()` runs on next access.
-    pub const fn finalize_append(&mut self) {
+    pub fn finalize_append(&mut self) {
+        self.filter_cache.clear();
+        self.string_ranks.clear();
+        self.sort_permutations.clear();
+        self.refresh_prefix_indexes();
+        self.revalidate();
         self.view_dirty = true;
     }
 
-    // ── View management ───────────────────────────────────────────────
+    /// Append `count` null rows (e.g. for a paste past the last row) and finalize;
+    /// returns their data rows.
+    pub fn append_empty_rows(&mut self, count: usize) -> std::ops::Range<usize> {
+        let start = self.row_count;
+        self.begin_append(count);
+        for col in &mut self.data {
+            if let ColumnData::Strings { ids, intern } = col {
+                let empty = intern.intern("");
+                ids[start..].fill(empty);
+            }
+        }
+        self.finalize_append();
+        start..self.row_count
+    }
 
-    /// Set sort configuration. Marks view dirty.
-    pub fn set_sort(&mut self, configs: Vec<SortConfig>) {
-        self.sort_configs = configs;
-        self.view_dirty = true;
+    // ── Lazy row blocks ──────────────────────────────────────────────
+
+    /// Enable lazy-row mode: `row_count` is the full size, but rows are loaded in
+    /// blocks of `size` (block `b` = rows `b * size..(b + 1) * size`), or `None` for
+    /// fully loaded data. Resets the loaded set.
+    pub fn set_row_block_size(&mut self, size: Option<usize>) {
+        self.row_block_size = size.filter(|&s| s > 0);
+        self.loaded_blocks.clear();
     }
 
-    /// Set column filters. Marks view dirty.
-    pub fn set_column_filters(&mut self, filters: Vec<ColumnFilter>) {
-        self.column_filters = filters;
-        self.view_dirty = true;
+    pub const fn row_block_size(&self) -> Option<usize> {
+        self.row_block_size
     }
 
-    /// Set global filter. Marks view dirty.
-    pub fn set_global_filter(&mut self, filter: Option<GlobalFilter>) {
-        self.global_filter = filter;
-        self.view_dirty = true;
+    /// Record that block `block` has been written (with the `append_column_*` APIs
+    /// at its row offset). Finalizes like `finalize_append`; auto-height rows of the
+    /// block are remeasured. Blocks past the row count are ignored.
+    pub fn mark_block_loaded(&mut self, block: usize) {
+        let Some(size) = self.row_block_size else {
+            return;
+        };
+        if block > self.row_count / size {
+            return;
+        }
+        if self.loaded_blocks.len() <= block {
+            self.loaded_blocks.resize(block + 1, false);
+        }
+        self.loaded_blocks[block] = true;
+        if self.auto_height.is_some() {
+            let rows = block * size..((block + 1) * size).min(self.row_count);
+            for row in rows {
+                self.set_row_height(row, f64::NAN);
+            }
+        }
+        self.finalize_append();
     }
 
-    /// Set pagination state. Marks view dirty.
-    pub const fn set_pagination(&mut self, page_index: Option<u32>, page_size: Option<u32>) {
-        self.page_index = page_index;
-        self.page_size = page_size;
-        self.view_dirty = true;
+    /// Forget a loaded block (e.g. evicted by the data source) so it is requested
+    /// again when needed.
+    pub fn mark_block_unloaded(&mut self, block: usize) {
+        if let Some(loaded) = self.loaded_blocks.get_mut(block) {
+            *loaded = false;
+        }
     }
 
-    /// Get the total number of rows after filtering but before pagination.
-    pub const fn filtered_total(&self) -> usize {
-        self.filtered_total
+    /// Whether a data row holds loaded data (always true outside lazy-row mode).
+    pub fn is_row_loaded(&self, row: usize) -> bool {
+        self.row_block_size
+            .is_none_or(|size| self.loaded_blocks.get(row / size).copied().unwrap_or(false))
     }
 
-    /// Set scroll configuration.
-    pub const fn set_scroll_config(
-        &mut self,
-        row_height: f64,
-        viewport_height: f64,
-        overscan: usize,
-    ) {
-        self.row_height = row_height;
-        self.viewport_height = viewport_height;
-        self.overscan = overscan;
+    /// Unloaded blocks holding the rows at `view_positions`, ascending and unique.
+    /// Empty outside lazy-row mode.
+    pub fn missing_blocks(&self, view_positions: impl IntoIterator<Item = usize>) -> Vec<usize> {
+        let Some(size) = self.row_block_size else {
+            return Vec::new();
+        };
+        let mut blocks: Vec<usize> = view_positions
+            .into_iter()
+            .filter_map(|pos| self.view_indices.get(pos))
+            .map(|&row| row as usize / size)
+            .filter(|&b| !self.loaded_blocks.get(b).copied().unwrap_or(false))
+            .collect();
+        blocks.sort_unstable();
+        blocks.dedup();
+        blocks
     }
 
-    /// Rebuild the view index array: filter → sort pipeline.
-    /// Skips if not dirty.
-    pub fn rebuild_view(&mut self) {
-        if !self.view_dirty {
+    // ── Remote row model ─────────────────────────────────────────────
+
+    /// Delegate sorting and filtering to a server: sorts and filters are still
+    /// tracked (see `query_descriptor`), but the view keeps rows in stored order,
+    /// which is the server's result order. Combine with lazy-row mode to load the
+    /// result in blocks. A query change forgets every loaded block.
+    pub fn set_remote_rows(&mut self, enabled: bool) {
+        self.remote_query = enabled.then(|| self.query_descriptor().key);
+        self.view_dirty = true;
+    }
+
+    pub const fn is_remote(&self) -> bool {
+        self.remote_query.is_some()
+    }
+
+    /// Set the size of the server's result. Rows past `total` are dropped, new
+    /// rows start as nulls; a changed count forgets every loaded block.
+    pub fn set_remote_row_count(&mut self, total: usize) {
+        if total == self.row_count {
             return;
         }
-        self.view_dirty = false;
+        if total > self.row_count {
+            self.begin_append(total - self.row_count);
+        } else {
+            for col in &mut self.data {
+                match col {
+                    ColumnData::Float64(v) | ColumnData::Bool(v) => v.truncate(total),
+                    ColumnData::Strings { ids, .. } => ids.truncate(total),
+                    ColumnData::StringList {
+                        offsets, values, ..
+                    } => {
+                        offsets.truncate(total + 1);
+                        values.truncate(offsets.last().copied().unwrap_or(0) as usize);
+                    }
+                    ColumnData::Float64List { offsets, values } => {
+                        offsets.truncate(total + 1);
+                        values.truncate(offsets.last().copied().unwrap_or(0) as usize);
+                    }
+                }
+            }
+            self.row_count = total;
+            self.row_heights.truncate(total);
+            self.generation += 1;
+        }
+        self.loaded_blocks.clear();
+        self.view_dirty = true;
+    }
 
-        let mut indices: Vec<u32> = (0..self.row_count as u32).collect();
+    /// The current sort and filter state in canonical form, for the server.
+    pub fn query_descriptor(&self) -> QueryDescriptor {
+        let mut column_filters = self.column_filters.clone();
+        column_filters.sort_by_key(|f| f.column_index);
+        let view = ViewPreset {
+            column_filters,
+            global_filter: self.global_filter.clone().filter(|gf| !gf.query.is_empty()),
+            sort_configs: self.sort_configs.clone(),
+        };
+        let mut w = Writer(Vec::new());
+        w.sort_configs(&view.sort_configs);
+        w.column_filters(&view.column_filters);
+        w.opt_str(view.global_filter.as_ref().map(|gf| gf.query.as_str()));
+        let key = w.0.iter().fold(String::new(), |mut key, b| {
+            let _ = write!(key, "{b:02x}");
+            key
+        });
+        QueryDescriptor { view, key }
+    }
 
-        // 1. Apply column filters (AND logic)
-        if !self.column_filters.is_empty() {
-            let filters = std::mem::take(&mut self.column_filters);
-            filter_indices_columnar(&mut indices, self, &filters);
-            self.column_filters = filters;
-        }
+    // ── Cell updates ─────────────────────────────────────────────────
 
-        // 2. Apply global filter (OR across string columns)
-        if let Some(gf) = self.global_filter.take() {
-            if !gf.query.is_empty() {
-                global_filter_indices(&mut indices, self, &gf);
+    /// Overwrite one Float64 cell (NaN = null). If only cell updates happened since
+    /// the last rebuild, `rebuild_view()` re-positions just the updated rows.
+    pub fn update_float64(&mut self, col_idx: usize, row: usize, value: f64) {
+        if let Some(ColumnData::Float64(v)) = self.data.get_mut(col_idx) {
+            if let Some(cell) = v.get_mut(row) {
+                *cell = value;
+                self.note_cell_update(col_idx, row);
             }
-            self.global_filter = Some(gf);
         }
+    }
 
-        // 3. Sort
-        if !self.sort_configs.is_empty() {
-            let configs = std::mem::take(&mut self.sort_configs);
-            sort_indices_columnar(&mut indices, self, &configs);
-            self.sort_configs = configs;
+    /// Overwrite one Bool cell (0.0 = false, 1.0 = true, NaN = null).
+    pub fn update_bool(&mut self, col_idx: usize, row: usize, value: f64) {
+        if let Some(ColumnData::Bool(v)) = self.data.get_mut(col_idx) {
+            if let Some(cell) = v.get_mut(row) {
+                *cell = value;
+                self.note_cell_update(col_idx, row);
+            }
         }
+    }
 
-        // 4. Pagination slice
-        self.filtered_total = indices.len();
-        if let (Some(page_index), Some(page_size)) = (self.page_index, self.page_size) {
-            let start = (page_index as usize) * (page_size as usize);
-            let end = start.saturating_add(page_size as usize).min(indices.len());
-            if start < indices.len() {
-                indices = indices[start..end].to_vec();
-            } else {
-                indices.clear();
-            }
+    /// Overwrite one string cell (empty string = null), interning new values.
+    pub fn update_string(&mut self, col_idx: usize, row: usize, value: &str) {
+        let Some(ColumnData::Strings { ids, intern }) = self.data.get_mut(col_idx) else {
+            return;
+        };
+        if row >= ids.len() {
+            return;
+        }
+        let interned = intern.len();
+        ids[row] = intern.intern(value);
+        if intern.len() != interned && self.prefix_indexes.contains_key(&col_idx) {
+            self.prefix_indexes
+                .insert(col_idx, PrefixIndex::build(intern));
         }
+        self.note_cell_update(col_idx, row);
+    }
 
-        self.view_indices = indices;
+    /// Rows updated since the last rebuild (may contain duplicates).
+    pub fn updated_rows(&self) -> &[u32] {
+        &self.updated_rows
     }
 
-    /// Get the view indices (valid after `rebuild_view`).
-    pub fn view_indices(&self) -> &[u32] {
-        &self.view_indices
+    /// Patch cached filter bitmaps of the column for the changed row and queue the
+    /// row for re-positioning.
+    fn note_cell_update(&mut self, col_idx: usize, row: usize) {
+        let mut cache = std::mem::take(&mut self.filter_cache);
+        for entry in cache
+            .iter_mut()
+            .filter(|e| self.filter_reads_column(&e.filter, col_idx))
+        {
+            if entry.domain.as_ref().is_none_or(|d| d.contains(row)) {
+                if match_column_filter(self, &entry.filter, row) {
+                    entry.bitmap.insert(row);
+                } else {
+                    entry.bitmap.remove(row);
+                }
+            }
+        }
+        self.filter_cache = cache;
+        self.sort_permutations.retain(|p| p.column_index != col_idx);
+        if let Some(mut validation) = self.validations.remove(&col_idx) {
+            validation.check_row(self, col_idx, row);
+            self.validations.insert(col_idx, validation);
+        }
+        if self
+            .auto_height
+            .as_ref()
+            .is_some_and(|a| a.columns.iter().any(|c| c.column_index == col_idx))
+        {
+            self.set_row_height(row, f64::NAN);
+        }
+        self.updated_rows.push(row as u32);
+        if self.update_flash_frames > 0 {
+            if self.row_updated_at.len() <= row {
+                self.row_updated_at.resize(self.row_count.max(row + 1), 0);
+            }
+            self.row_updated_at[row] = self.update_tick;
+        }
+        if !self.updated_columns.contains(&col_idx) {
+            self.updated_columns.push(col_idx);
+        }
     }
 
-    /// Get scroll config values.
-    pub const fn row_height(&self) -> f64 {
-        self.row_height
+    /// Report each row's cell updates for `frames` frames (`row_update_age`), e.g.
+    /// to flash changed values; 0 turns it off and forgets recorded updates.
+    pub fn set_update_flash_frames(&mut self, frames: u32) {
+        self.update_flash_frames = frames;
+        if frames == 0 {
+            self.row_updated_at = Vec::new();
+        }
     }
 
-    pub const fn viewport_height(&self) -> f64 {
-        self.viewport_height
+    /// Start a new frame for `row_update_age`: updates from here on are age 0.
+    pub const fn advance_update_tick(&mut self) {
+        self.update_tick += 1;
     }
 
-    pub const fn overscan(&self) -> usize {
-        self.overscan
+    /// Frames since a cell of data row `row` was last updated, if within the
+    /// `set_update_flash_frames` window.
+    pub fn row_update_age(&self, row: usize) -> Option<u32> {
+        let at = *self.row_updated_at.get(row).filter(|&&at| at > 0)?;
+        let age = self.update_tick - at;
+        (age < u64::from(self.update_flash_frames)).then_some(age as u32)
     }
 
-    /// Get the Float64 data pointer for a column (for zero-copy JS access).
-    /// Returns None if column is not Float64.
-    pub fn get_float64_ptr(&self, col_idx: usize) -> Option<(*const f64, usize)> {
-        match self.data.get(col_idx) {
-            Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => Some((v.as_ptr(), v.len())),
-            _ => None,
+    /// Check the cells of column `col_idx` against `rules`: all of them now and
+    /// when data is loaded (`finalize`, `finalize_append`), and each cell as it is
+    /// updated. `None` drops the column's rules.
+    pub fn set_column_validation(&mut self, col_idx: usize, rules: Option<ValidationRules>) {
+        match rules {
+            Some(rules) => {
+                let mut validation = ColumnValidation::new(rules);
+                validation.check_all(self, col_idx);
+                self.validations.insert(col_idx, validation);
+            }
+            None => {
+                self.validations.remove(&col_idx);
+            }
         }
     }
 
-    /// Get the column type.
-    pub fn column_type(&self, col_idx: usize) -> Option<ColumnType> {
-        self.data.get(col_idx).map(|d| match d {
-            ColumnData::Float64(_) => ColumnType::Float64,
-            ColumnData::Strings { .. } => ColumnType::String,
-            ColumnData::Bool(_) => ColumnType::Bool,
-        })
+    pub fn has_validation(&self) -> bool {
+        !self.validations.is_empty()
     }
-}
 
-impl Default for ColumnarStore {
-    fn default() -> Self {
-        Self::new()
+    /// `validation::ERROR_*` bits of the cell of data row `row`, column `col_idx`
+    /// (0 = valid or no rules).
+    pub fn cell_validation_errors(&self, col_idx: usize, row: usize) -> u32 {
+        self.validations.get(&col_idx).map_or(0, |v| v.errors(row))
     }
-}
 
-// ── Index operations on ColumnarStore ─────────────────────────────────
+    /// Check every column with rules again, after a data load.
+    fn revalidate(&mut self) {
+        let mut validations = std::mem::take(&mut self.validations);
+        for (&col_idx, validation) in &mut validations {
+            validation.check_all(self, col_idx);
+        }
+        self.validations = validations;
+    }
 
-/// Sort indices by comparing columnar data directly.
-pub fn sort_indices_columnar(indices: &mut [u32], store: &ColumnarStore, configs: &[SortConfig]) {
-    if configs.is_empty() {
-        return;
+    /// Take data row `row` out of every view, keeping its data (and data row index)
+    /// in place. Returns false when it is out of range or already removed. Marks
+    /// view dirty.
+    pub fn remove_row(&mut self, row: usize) -> bool {
+        if row >= self.row_count || self.is_row_removed(row) {
+            return false;
+        }
+        if self.removed_rows.len() <= row {
+            self.removed_rows.resize(self.row_count, false);
+        }
+        self.removed_rows[row] = true;
+        self.removed_count += 1;
+        self.view_dirty = true;
+        true
     }
 
-    indices.sort_by(|&a, &b| {
-        for config in configs {
-            let ordering = compare_columnar(store, config.column_index, a as usize, b as usize);
-            let ordering = match config.direction {
-                SortDirection::Ascending => ordering,
-                SortDirection::Descending => ordering.reverse(),
-            };
-            if ordering != std::cmp::Ordering::Equal {
-                return ordering;
-            }
+    /// Bring back a row taken out by `remove_row`. Returns false when it was not
+    /// removed. Marks view dirty.
+    pub fn restore_row(&mut self, row: usize) -> bool {
+        if !self.is_row_removed(row) {
+            return false;
         }
-        std::cmp::Ordering::Equal
-    });
-}
+        self.removed_rows[row] = false;
+        self.removed_count -= 1;
+        self.view_dirty = true;
+        true
+    }
 
-/// Filter indices by column filters (AND logic: row must pass all filters).
-pub fn filter_indices_columnar(
-    indices: &mut Vec<u32>,
-    store: &ColumnarStore,
-    filters: &[ColumnFilter],
-) {
-    if filters.is_empty() {
-        return;
+    pub fn is_row_removed(&self, row: usize) -> bool {
+        self.removed_rows.get(row).copied().unwrap_or(false)
     }
-    indices.retain(|&idx| {
-        let row = idx as usize;
-        filters.iter().all(|f| match_column_filter(store, f, row))
-    });
-}
 
-/// Check if a single row passes a column filter.
-fn match_column_filter(store: &ColumnarStore, filter: &ColumnFilter, row: usize) -> bool {
-    match store.data.get(filter.column_index) {
-        Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => {
-            let val = v[row];
-            if val.is_nan() {
-                return false; // NaN never passes
+    /// Drop the rows taken out by `remove_row` from `indices`.
+    fn drop_removed_rows(&self, indices: &mut Vec<u32>) {
+        if self.removed_count > 0 {
+            indices.retain(|&row| !self.is_row_removed(row as usize));
+        }
+    }
+
+    /// Version of data row `row`: how many times `bump_row_version` was called
+    /// for it.
+    pub fn row_version(&self, row: usize) -> u32 {
+        self.row_versions.get(row).copied().unwrap_or(0)
+    }
+
+    /// Count a change of data row `row` (e.g. an optimistic edit awaiting the
+    /// server) and return its new version.
+    pub fn bump_row_version(&mut self, row: usize) -> u32 {
+        if self.row_versions.len() <= row {
+            self.row_versions.resize(self.row_count.max(row + 1), 0);
+        }
+        self.row_versions[row] += 1;
+        self.row_versions[row]
+    }
+
+    /// Whether a column filter or custom predicate is set on `col_idx`, or an
+    /// expression filter reads it.
+    pub fn column_filtered(&self, col_idx: usize) -> bool {
+        self.column_filters
+            .iter()
+            .any(|f| self.filter_reads_column(f, col_idx))
+            || self
+                .custom_filters
+                .iter()
+                .any(|f| f.column_index == col_idx)
+    }
+
+    /// Whether `filter` reads the values of `col_idx`: its own column, or any
+    /// column its expression refers to.
+    fn filter_reads_column(&self, filter: &ColumnFilter, col_idx: usize) -> bool {
+        filter.column_index == col_idx
+            || match (&filter.op, &filter.value) {
+                (FilterOp::Expression, FilterValue::String(source)) => self
+                    .filter_exprs
+                    .get(source)
+                    .is_some_and(|expr| expr.columns().contains(&col_idx)),
+                _ => false,
             }
-            match &filter.value {
-                FilterValue::Float64(target) => match filter.op {
-                    FilterOp::Eq => (val - target).abs() < f64::EPSILON,
-                    FilterOp::Neq => (val - target).abs() >= f64::EPSILON,
-                    FilterOp::Gt => val > *target,
-                    FilterOp::Gte => val >= *target - f64::EPSILON,
-                    FilterOp::Lt => val < *target,
-                    FilterOp::Lte => val <= *target + f64::EPSILON,
-                    FilterOp::Contains | FilterOp::StartsWith | FilterOp::EndsWith => false,
-                },
-                FilterValue::Bool(target) => {
-                    let val_bool = val != 0.0;
-                    match filter.op {
-                        FilterOp::Eq => val_bool == *target,
-                        FilterOp::Neq => val_bool != *target,
-                        _ => false,
+    }
+
+    /// Direction of the sort key on `col_idx` and its 1-based priority among the
+    /// sort keys, if the view is sorted by it.
+    pub fn column_sort(&self, col_idx: usize) -> Option<(SortDirection, usize)> {
+        self.sort_configs
+            .iter()
+            .position(|c| c.column_index == col_idx)
+            .map(|i| (self.sort_configs[i].direction, i + 1))
+    }
+
+    /// Whether values of `col_idx` decide which rows the view holds or their order
+    /// (a filter, the global filter, a custom predicate, or a sort key).
+    fn column_affects_view(&self, col_idx: usize) -> bool {
+        self.column_filtered(col_idx)
+            || self.sort_configs.iter().any(|c| c.column_index == col_idx)
+            || (self
+                .global_filter
+                .as_ref()
+                .is_some_and(|gf| !gf.query.is_empty())
+                && matches!(
+                    self.data.get(col_idx),
+                    Some(ColumnData::Strings { .. } | ColumnData::StringList { .. })
+                ))
+    }
+
+    // ── Prefix index ─────────────────────────────────────────────────
+
+    /// Enable or disable the sorted prefix/suffix index for a string column.
+    /// When enabled, `StartsWith`/`EndsWith` filters on that column use a
+    /// binary-search range over unique strings instead of a per-row scan.
+    pub fn set_prefix_index(&mut self, col_idx: usize, enabled: bool) {
+        if !enabled {
+            self.prefix_indexes.remove(&col_idx);
+            return;
+        }
+        if let Some(ColumnData::Strings { intern, .. }) = self.data.get(col_idx) {
+            self.prefix_indexes
+                .insert(col_idx, PrefixIndex::build(intern));
+        }
+    }
+
+    /// Whether a prefix index is enabled for a column.
+    pub fn has_prefix_index(&self, col_idx: usize) -> bool {
+        self.prefix_indexes.contains_key(&col_idx)
+    }
+
+    /// Rebuild enabled prefix indexes after string data changed. Indexes on
+    /// columns that are no longer string columns are dropped.
+    fn refresh_prefix_indexes(&mut self) {
+        let data = &self.data;
+        self.prefix_indexes.retain(|&col_idx, index| {
+            if let Some(ColumnData::Strings { intern, .. }) = data.get(col_idx) {
+                *index = PrefixIndex::build(intern);
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    // ── View management ───────────────────────────────────────────────
+
+    /// Set sort configuration. Marks view dirty.
+    pub fn set_sort(&mut self, configs: Vec<SortConfig>) {
+        self.sort_configs = configs;
+        self.view_dirty = true;
+    }
+
+    /// Keep groups of rows contiguous while sorting (see `GroupSort`), or `None` for
+    /// a plain sort. Marks view dirty.
+    pub const fn set_group_sort(&mut self, group_sort: Option<GroupSort>) {
+        self.group_sort = group_sort;
+        self.view_dirty = true;
+    }
+
+    pub const fn group_sort(&self) -> Option<&GroupSort> {
+        self.group_sort.as_ref()
+    }
+
+    /// Order the view by `order`, a list of data rows, instead of sorting: rows
+    /// that pass the filters keep their place in it, and rows missing from it
+    /// follow in row order. Kept across data reloads; `None` = sort. Marks view dirty.
+    pub fn set_manual_row_order(&mut self, order: Option<Vec<u32>>) {
+        self.manual_order = order;
+        self.view_dirty = true;
+    }
+
+    pub fn manual_row_order(&self) -> Option<&[u32]> {
+        self.manual_order.as_deref()
+    }
+
+    /// Move the row at view position `from` to view position `to`, switching to a
+    /// manual row order seeded with the current one. Returns false when either
+    /// position is out of the view. Marks view dirty.
+    pub fn move_row(&mut self, from: usize, to: usize) -> bool {
+        let (Some(&row), Some(&target)) = (self.view_indices.get(from), self.view_indices.get(to))
+        else {
+            return false;
+        };
+        if row == target {
+            return true;
+        }
+        let seed = self.manual_order.take().unwrap_or_else(|| {
+            self.filtered_rows
+                .clone()
+                .unwrap_or_else(|| self.view_indices.clone())
+        });
+        let mut order = self.complete_row_order(seed);
+        order.retain(|&r| r != row);
+        let at = order
+            .iter()
+            .position(|&r| r == target)
+            .unwrap_or(order.len());
+        order.insert(if from < to { at + 1 } else { at }, row);
+        self.manual_order = Some(order);
+        self.view_dirty = true;
+        true
+    }
+
+    /// Put data row `row` at view position `at` (after the last row when `at` is
+    /// past the view), switching to a manual row order seeded with the current
+    /// one, e.g. for a row just inserted. Marks view dirty.
+    pub fn place_row(&mut self, row: usize, at: usize) {
+        let row = row as u32;
+        let target = self.view_indices.get(at).copied();
+        let seed = self.manual_order.take().unwrap_or_else(|| {
+            self.filtered_rows
+                .clone()
+                .unwrap_or_else(|| self.view_indices.clone())
+        });
+        let mut order = self.complete_row_order(seed);
+        order.retain(|&r| r != row);
+        let at = target
+            .and_then(|target| order.iter().position(|&r| r == target))
+            .unwrap_or(order.len());
+        order.insert(at, row);
+        self.manual_order = Some(order);
+        self.view_dirty = true;
+    }
+
+    /// `order` without duplicates or rows past the end, followed by every data row
+    /// it lacks in row order.
+    fn complete_row_order(&self, order: Vec<u32>) -> Vec<u32> {
+        let mut seen = vec![false; self.row_count];
+        let mut complete: Vec<u32> = order
+            .into_iter()
+            .filter(|&row| {
+                seen.get_mut(row as usize)
+                    .is_some_and(|seen| !std::mem::replace(seen, true))
+            })
+            .collect();
+        complete.extend((0..self.row_count as u32).filter(|&row| !seen[row as usize]));
+        complete
+    }
+
+    /// Order rows that compare equal on every sort key by original row index (the
+    /// default), so equal-valued rows never swap places between rebuilds. When
+    /// disabled, ties keep the order the rows reach the sort in. Every sort path is
+    /// stable either way. Marks view dirty.
+    pub const fn set_row_order_tiebreak(&mut self, enabled: bool) {
+        self.row_order_tiebreak = enabled;
+        self.view_dirty = true;
+    }
+
+    pub const fn row_order_tiebreak(&self) -> bool {
+        self.row_order_tiebreak
+    }
+
+    /// Whether the next `rebuild_view` has work to do.
+    pub const fn view_needs_rebuild(&self) -> bool {
+        self.view_dirty || !self.updated_rows.is_empty()
+    }
+
+    /// Changes whenever a `rebuild_view` picks up new data, cell updates, or view
+    /// settings, and when row merges or cell styles change; equal versions mean
+    /// identical rows in identical order, laid out alike.
+    pub const fn view_version(&self) -> u64 {
+        self.view_version
+    }
+
+    /// Set column filters. Marks view dirty.
+    pub fn set_column_filters(&mut self, filters: Vec<ColumnFilter>) {
+        self.column_filters = filters;
+        self.parse_filter_expressions();
+        self.view_dirty = true;
+    }
+
+    /// Parse the sources of `Expression` filters; ones that fail to parse match
+    /// no rows.
+    fn parse_filter_expressions(&mut self) {
+        self.filter_exprs = parse_filter_expressions(&self.column_filters)
+            .filter_map(|(source, expr)| Some((source.to_string(), expr.ok()?)))
+            .collect();
+    }
+
+    /// Register (or clear with `None`) the display format of a column, used by
+    /// `formatted` filter conditions. Marks view dirty.
+    pub fn set_column_format(&mut self, col_idx: usize, spec: Option<FormatSpec>) {
+        match spec {
+            Some(spec) => self.column_formats.insert(col_idx, spec),
+            None => self.column_formats.remove(&col_idx),
+        };
+        let mut cache = std::mem::take(&mut self.filter_cache);
+        cache.retain(|e| !self.filter_reads_column(&e.filter, col_idx));
+        self.filter_cache = cache;
+        self.view_dirty = true;
+    }
+
+    /// Style data cells by `rules` (see `conditional_style`), replacing the
+    /// previous rules.
+    pub fn set_conditional_formats(&mut self, rules: Vec<ConditionalFormat>) {
+        self.conditional_formats = rules;
+        self.conditional_bounds = None;
+    }
+
+    pub fn conditional_formats(&self) -> &[ConditionalFormat] {
+        &self.conditional_formats
+    }
+
+    /// Compute the column statistics of `Top` and `Scale` rules over the filtered
+    /// rows, unless they are current for this view. Call after `rebuild_view` and
+    /// before `conditional_style`.
+    pub fn resolve_conditional_formats(&mut self) {
+        if self
+            .conditional_bounds
+            .as_ref()
+            .is_some_and(|(version, _)| *version == self.view_version)
+        {
+            return;
+        }
+        let bounds = self
+            .conditional_formats
+            .iter()
+            .map(|rule| {
+                let mut values = match rule.condition {
+                    FormatCondition::Match(_) => return (f64::NAN, f64::NAN),
+                    _ => self.column_values(rule.column_index, self.filtered_rows()),
+                };
+                values.retain(|v| !v.is_nan());
+                match rule.condition {
+                    FormatCondition::Top {
+                        count,
+                        percent,
+                        bottom,
+                    } => {
+                        let n = if percent {
+                            (values.len() as f64 * count / 100.0).ceil()
+                        } else {
+                            count.floor()
+                        };
+                        let n = (n.max(0.0) as usize).min(values.len());
+                        if n == 0 {
+                            return (f64::NAN, f64::NAN);
+                        }
+                        let (_, &mut cutoff, _) = values.select_nth_unstable_by(n - 1, |a, b| {
+                            if bottom {
+                                a.total_cmp(b)
+                            } else {
+                                b.total_cmp(a)
+                            }
+                        });
+                        (cutoff, f64::NAN)
                     }
+                    _ => min_max(&values),
                 }
-                FilterValue::String(_) => false,
+            })
+            .collect();
+        self.conditional_bounds = Some((self.view_version, bounds));
+    }
+
+    /// Style of the cell at data row `row` of column `col_idx`: the `style_id` of
+    /// the first rule of the column it meets, 0 if none. `Top` and `Scale` rules
+    /// need `resolve_conditional_formats`, and never match null cells.
+    pub fn conditional_style(&self, col_idx: usize, row: usize) -> u32 {
+        let bounds = self.conditional_bounds.as_ref().map_or(&[][..], |(_, b)| b);
+        let value = || match self.data.get(col_idx) {
+            Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => v[row],
+            _ => f64::NAN,
+        };
+        for (i, rule) in self.conditional_formats.iter().enumerate() {
+            if rule.column_index != col_idx {
+                continue;
             }
-        }
-        Some(ColumnData::Strings { ids, intern }) => {
-            let resolved = intern.resolve(ids[row]);
-            match &filter.value {
-                FilterValue::String(target) => match filter.op {
-                    FilterOp::Eq => resolved == target.as_str(),
-                    FilterOp::Neq => resolved != target.as_str(),
-                    FilterOp::Gt => resolved > target.as_str(),
-                    FilterOp::Gte => resolved >= target.as_str(),
-                    FilterOp::Lt => resolved < target.as_str(),
-                    FilterOp::Lte => resolved <= target.as_str(),
-                    FilterOp::Contains => resolved.to_lowercase().contains(&target.to_lowercase()),
-                    FilterOp::StartsWith => {
-                        resolved.to_lowercase().starts_with(&target.to_lowercase())
+            let (lo, hi) = bounds.get(i).copied().unwrap_or((f64::NAN, f64::NAN));
+            match rule.condition {
+                FormatCondition::Match(ref filter) => {
+                    if match_column_filter(self, filter, row) {
+                        return rule.style_id;
                     }
-                    FilterOp::EndsWith => resolved.to_lowercase().ends_with(&target.to_lowercase()),
-                },
-                _ => false,
+                }
+                FormatCondition::Top { bottom, .. } => {
+                    let v = value();
+                    if (bottom && v <= lo) || (!bottom && v >= lo) {
+                        return rule.style_id;
+                    }
+                }
+                FormatCondition::Scale { buckets } => {
+                    let v = value();
+                    if v.is_nan() || lo.is_nan() || lo > hi {
+                        continue;
+                    }
+                    let last = buckets.max(1) - 1;
+                    let step = if hi > lo {
+                        ((v - lo) / (hi - lo) * f64::from(last + 1)).floor() as u32
+                    } else {
+                        0
+                    };
+                    return rule.style_id + step.min(last);
+                }
             }
         }
-        None => false,
+        0
     }
-}
 
-/// Filter indices by global filter (OR across all string columns, case-insensitive contains).
-pub fn global_filter_indices(indices: &mut Vec<u32>, store: &ColumnarStore, filter: &GlobalFilter) {
-    let query = filter.query.to_lowercase();
-    if query.is_empty() {
-        return;
+    /// Show column `col_idx` as a color scale or data bars (`None` = off).
+    pub fn set_data_visualization(&mut self, col_idx: usize, visual: Option<DataVisualization>) {
+        match visual {
+            Some(visual) => self.visualizations.insert(col_idx, visual),
+            None => self.visualizations.remove(&col_idx),
+        };
+        self.visual_ranges = None;
+    }
+
+    pub fn data_visualization(&self, col_idx: usize) -> Option<&DataVisualization> {
+        self.visualizations.get(&col_idx)
+    }
+
+    /// Compute the `(min, max)` of the visualized columns over the filtered rows,
+    /// unless current for this view. Call after `rebuild_view` and before
+    /// `data_visual`.
+    pub fn resolve_data_visualizations(&mut self) {
+        if self
+            .visual_ranges
+            .as_ref()
+            .is_some_and(|(version, _)| *version == self.view_version)
+        {
+            return;
+        }
+        let ranges = self
+            .visualizations
+            .iter()
+            .filter(|(_, visual)| visual.domain.is_none())
+            .map(|(&col_idx, _)| {
+                let mut values = self.column_values(col_idx, self.filtered_rows());
+                values.retain(|v| !v.is_nan());
+                (col_idx, min_max(&values))
+            })
+            .collect();
+        self.visual_ranges = Some((self.view_version, ranges));
+    }
+
+    /// Visualization of the cell at data row `row` of column `col_idx`: its 0..1
+    /// place in the column's range (NaN for nulls and plain columns) and its
+    /// packed `0xRRGGBBAA` color-scale color (0 = none).
+    pub fn data_visual(&self, col_idx: usize, row: usize) -> (f64, u32) {
+        let Some(visual) = self.visualizations.get(&col_idx) else {
+            return (f64::NAN, 0);
+        };
+        let value = match self.data.get(col_idx) {
+            Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => v[row],
+            _ => f64::NAN,
+        };
+        let range = self
+            .visual_ranges
+            .as_ref()
+            .and_then(|(_, ranges)| ranges.get(&col_idx))
+            .copied()
+            .unwrap_or((f64::NAN, f64::NAN));
+        let t = visual.normalize(value, range);
+        (t, visual.color(t))
+    }
+
+    /// Merge the cells of a column across view rows (`None` = one cell per row).
+    pub fn set_row_merge(&mut self, col_idx: usize, merge: Option<RowMerge>) {
+        match merge {
+            Some(merge) => self.row_merges.insert(col_idx, merge),
+            None => self.row_merges.remove(&col_idx),
+        };
+        self.view_version += 1;
+    }
+
+    /// Columns with merged cells.
+    pub fn row_merge_columns(&self) -> impl Iterator<Item = usize> + '_ {
+        self.row_merges.keys().copied()
+    }
+
+    pub fn has_row_merges(&self) -> bool {
+        !self.row_merges.is_empty()
+    }
+
+    /// Whether the cell of column `col_idx` at view position `pos` belongs to the
+    /// merged cell of the row above.
+    pub fn merged_with_previous(&self, col_idx: usize, pos: usize) -> bool {
+        let (Some(merge), Some(prev)) = (self.row_merges.get(&col_idx), pos.checked_sub(1)) else {
+            return false;
+        };
+        let (Some(&row), Some(&prev_row)) =
+            (self.view_indices.get(pos), self.view_indices.get(prev))
+        else {
+            return false;
+        };
+        match merge {
+            RowMerge::EqualValues => {
+                let key = self.group_key(col_idx, row as usize);
+                key.is_some() && key == self.group_key(col_idx, prev_row as usize)
+            }
+            RowMerge::Spans(spans) => {
+                let span = |row: u32| spans.get(row as usize).copied().unwrap_or(1);
+                span(row) == 0
+                    && self.view_indices[..pos]
+                        .iter()
+                        .rev()
+                        .enumerate()
+                        .find(|&(_, &above)| span(above) != 0)
+                        .is_some_and(|(distance, &host)| span(host) as usize > distance + 1)
+            }
+        }
+    }
+
+    /// Override the layout of the cell at data row `row`, column `col_idx`, on top
+    /// of its column's style (`None` = clear). Follows the row through sorts.
+    pub fn set_cell_style(&mut self, row: usize, col_idx: usize, style: Option<CellStyle>) {
+        let key = (row as u32, col_idx as u32);
+        match style {
+            Some(style) => self.cell_styles.insert(key, style),
+            None => self.cell_styles.remove(&key),
+        };
+        self.view_version += 1;
+    }
+
+    pub fn clear_cell_styles(&mut self) {
+        self.cell_styles.clear();
+        self.view_version += 1;
+    }
+
+    pub fn has_cell_styles(&self) -> bool {
+        !self.cell_styles.is_empty()
+    }
+
+    /// Layout override of the cell at view position `pos`, column `col_idx`.
+    pub fn view_cell_style(&self, pos: usize, col_idx: usize) -> Option<&CellStyle> {
+        let &row = self.view_indices.get(pos)?;
+        self.cell_styles.get(&(row, col_idx as u32))
+    }
+
+    /// Make the cell at data row `row`, column `col_idx` span `span` visible
+    /// columns, covering the cells to its right (`span <= 1` = clear). Follows the
+    /// row through sorts.
+    pub fn set_cell_col_span(&mut self, row: usize, col_idx: usize, span: usize) {
+        let key = (row as u32, col_idx as u32);
+        if span > 1 {
+            self.col_spans.insert(key, span as u32);
+        } else {
+            self.col_spans.remove(&key);
+        }
+        self.view_version += 1;
+    }
+
+    pub fn clear_col_spans(&mut self) {
+        self.col_spans.clear();
+        self.view_version += 1;
+    }
+
+    pub fn has_col_spans(&self) -> bool {
+        !self.col_spans.is_empty()
+    }
+
+    /// Column span of the cell at view position `pos`, column `col_idx`.
+    pub fn view_col_span(&self, pos: usize, col_idx: usize) -> Option<usize> {
+        let &row = self.view_indices.get(pos)?;
+        self.col_spans
+            .get(&(row, col_idx as u32))
+            .map(|&span| span as usize)
+    }
+
+    /// Arrange the rows as a tree: `parents[row]` is the parent data row of each
+    /// row (`NO_PARENT` = root), or `None` for flat rows. All rows start collapsed;
+    /// the view shows expanded branches only. Marks view dirty.
+    pub fn set_tree_structure(&mut self, parents: Option<Vec<u32>>) {
+        self.tree = parents.map(TreeData::new);
+        self.view_dirty = true;
+    }
+
+    pub const fn tree(&self) -> Option<&TreeData> {
+        self.tree.as_ref()
+    }
+
+    /// Expand or collapse data row `row` of the tree. Marks view dirty.
+    pub fn set_tree_expanded(&mut self, row: usize, expanded: bool) {
+        if let Some(tree) = &mut self.tree {
+            tree.set_expanded(row, expanded);
+            self.view_dirty = true;
+        }
+    }
+
+    /// Expand or collapse every row of the tree. Marks view dirty.
+    pub fn set_tree_all_expanded(&mut self, expanded: bool) {
+        if let Some(tree) = &mut self.tree {
+            tree.set_all_expanded(expanded);
+            self.view_dirty = true;
+        }
+    }
+
+    /// Indent the cells of column `col_idx` by `indent` px per tree level.
+    pub const fn set_tree_indent(&mut self, col_idx: usize, indent: f32) {
+        self.tree_indent = (col_idx, indent);
+        self.view_version += 1;
+    }
+
+    /// Indented column and indent per level, with tree data.
+    pub const fn tree_indent(&self) -> Option<(usize, f32)> {
+        match self.tree {
+            Some(_) => Some(self.tree_indent),
+            None => None,
+        }
+    }
+
+    /// Tree depth of view position `pos` (0 for flat rows).
+    pub fn view_depth(&self, pos: usize) -> usize {
+        match (&self.tree, self.view_indices.get(pos)) {
+            (Some(tree), Some(&row)) => tree.depth(row as usize),
+            _ => 0,
+        }
+    }
+
+    /// Per data row, `aggregate` of column `col_idx` over the row's descendants
+    /// (NaN without tree data or a numeric column). Covers all rows, filtered
+    /// or not.
+    pub fn tree_rollup(&self, col_idx: usize, aggregate: Aggregate) -> Vec<f64> {
+        let (Some(tree), Some(ColumnData::Float64(values) | ColumnData::Bool(values))) =
+            (&self.tree, self.data.get(col_idx))
+        else {
+            return vec![f64::NAN; self.row_count];
+        };
+        tree.rollup(|row| values.get(row).copied().unwrap_or(f64::NAN))
+            .iter()
+            .map(|acc| acc.value(aggregate))
+            .collect()
+    }
+
+    /// Add a derived column: `function` of column `col_idx` over the sorted view,
+    /// recomputed on every rebuild. Returns its ID. Marks view dirty.
+    pub fn add_window_column(&mut self, col_idx: usize, function: WindowFn) -> usize {
+        self.window_columns.push(Some(WindowColumn {
+            source: col_idx,
+            function,
+            values: Vec::new(),
+        }));
+        self.view_dirty = true;
+        self.window_columns.len() - 1
+    }
+
+    /// Remove window column `id`. Other IDs stay valid.
+    pub fn remove_window_column(&mut self, id: usize) {
+        if let Some(slot) = self.window_columns.get_mut(id) {
+            *slot = None;
+        }
+        while self.window_columns.last().is_some_and(Option::is_none) {
+            self.window_columns.pop();
+        }
+    }
+
+    /// Value of window column `id` at data row `row`; NaN for rows outside the
+    /// view, unknown IDs, or before the next rebuild.
+    pub fn window_value(&self, id: usize, row: usize) -> f64 {
+        self.window_columns
+            .get(id)
+            .and_then(Option::as_ref)
+            .and_then(|w| w.values.get(row).copied())
+            .unwrap_or(f64::NAN)
+    }
+
+    fn compute_window_columns(&mut self) {
+        if self.window_columns.is_empty() {
+            return;
+        }
+        let mut columns = std::mem::take(&mut self.window_columns);
+        let rows = self.filtered_rows();
+        for column in columns.iter_mut().flatten() {
+            let values = column
+                .function
+                .compute(&self.column_values(column.source, rows));
+            column.values.clear();
+            column.values.resize(self.row_count, f64::NAN);
+            for (&row, value) in rows.iter().zip(values) {
+                column.values[row as usize] = value;
+            }
+        }
+        self.window_columns = columns;
+    }
+
+    /// Registered display format of a column, if any.
+    pub fn column_format(&self, col_idx: usize) -> Option<&FormatSpec> {
+        self.column_formats.get(&col_idx)
+    }
+
+    /// Set global filter. Marks view dirty.
+    pub fn set_global_filter(&mut self, filter: Option<GlobalFilter>) {
+        self.global_filter = filter;
+        self.view_dirty = true;
+    }
+
+    /// Register (or clear with `None`) a custom predicate for a column, replacing any
+    /// previous one. Custom predicates run only on rows that pass every native filter
+    /// and are never cached. Marks view dirty.
+    pub fn set_custom_filter(&mut self, col_idx: usize, predicate: Option<CustomFilterFn>) {
+        self.custom_filters.retain(|f| f.column_index != col_idx);
+        if let Some(predicate) = predicate {
+            self.custom_filters.push(CustomFilter {
+                column_index: col_idx,
+                predicate: PredicateSlot::from(predicate),
+            });
+        }
+        self.view_dirty = true;
+    }
+
+    /// Mark the view dirty so custom predicates are re-evaluated on the next rebuild
+    /// (e.g. after state they depend on changed).
+    pub const fn invalidate_custom_filters(&mut self) {
+        self.view_dirty = true;
+    }
+
+    /// Set the reference time (epoch ms) and UTC offset (minutes east of UTC) that
+    /// relative date filters (`Within`) are resolved against. Marks view dirty and
+    /// drops cached relative-date results when either changes.
+    pub fn set_time_reference(&mut self, now_ms: f64, utc_offset_minutes: i32) {
+        if now_ms.to_bits() == self.now_ms.to_bits()
+            && utc_offset_minutes == self.utc_offset_minutes
+        {
+            return;
+        }
+        self.now_ms = now_ms;
+        self.utc_offset_minutes = utc_offset_minutes;
+        self.filter_cache
+            .retain(|e| !matches!(e.filter.value, FilterValue::RelativeDate(_)));
+        self.view_dirty = true;
+    }
+
+    /// Reference time (epoch ms) used by relative date filters.
+    pub const fn now_ms(&self) -> f64 {
+        self.now_ms
+    }
+
+    /// Typed value of a cell, `None` for null.
+    pub fn cell_value(&self, col_idx: usize, row: usize) -> Option<FilterValue> {
+        match self.data.get(col_idx)? {
+            ColumnData::Float64(v) => {
+                let val = *v.get(row)?;
+                (!val.is_nan()).then_some(FilterValue::Float64(val))
+            }
+            ColumnData::Bool(v) => {
+                let val = *v.get(row)?;
+                (!val.is_nan()).then_some(FilterValue::Bool(val != 0.0))
+            }
+            ColumnData::Strings { ids, intern } => {
+                let resolved = intern.resolve(*ids.get(row)?);
+                (!resolved.is_empty()).then(|| FilterValue::String(resolved.to_string()))
+            }
+            col @ ColumnData::StringList {
+                intern, offsets, ..
+            } => {
+                if row + 1 >= offsets.len() {
+                    return None;
+                }
+                let tags = col.list_ids(row);
+                (!tags.is_empty()).then(|| {
+                    FilterValue::StringList(
+                        tags.iter()
+                            .map(|&id| intern.resolve(id).to_string())
+                            .collect(),
+                    )
+                })
+            }
+            // A series has no scalar value to filter or edit on
+            ColumnData::Float64List { .. } => None,
+        }
+    }
+
+    /// Keep only rows accepted by every custom predicate (optionally skipping the one
+    /// on `exclude_column`).
+    fn apply_custom_filters(
+        &mut self,
+        indices: &mut Vec<u32>,
+        exclude_column: Option<usize>,
+        mut stats: Option<&mut Vec<ConditionStats>>,
+    ) {
+        if self.custom_filters.is_empty() {
+            return;
+        }
+        let mut filters = std::mem::take(&mut self.custom_filters);
+        for filter in &mut filters {
+            if Some(filter.column_index) == exclude_column {
+                continue;
+            }
+            let t0 = self.clock_ms();
+            let scanned = indices.len();
+            indices.retain(|&idx| {
+                let row = idx as usize;
+                filter.call(row, self.cell_value(filter.column_index, row))
+            });
+            if let Some(stats) = stats.as_deref_mut() {
+                stats.push(ConditionStats {
+                    kind: ConditionKind::Custom,
+                    column_index: Some(filter.column_index),
+                    rows_scanned: scanned,
+                    rows_passed: indices.len(),
+                    elapsed_us: self.elapsed_us(t0),
+                    cached: false,
+                });
+            }
+        }
+        self.custom_filters = filters;
+    }
+
+    // ── Filter presets ────────────────────────────────────────────────
+
+    /// Snapshot of the current filters, global filter, and sort.
+    pub fn view_state(&self) -> ViewPreset {
+        ViewPreset {
+            column_filters: self.column_filters.clone(),
+            global_filter: self.global_filter.clone(),
+            sort_configs: self.sort_configs.clone(),
+        }
+    }
+
+    /// Replace filters, global filter, and sort with `state`. Marks view dirty.
+    pub fn set_view_state(&mut self, state: ViewPreset) {
+        self.column_filters = state.column_filters;
+        self.parse_filter_expressions();
+        self.global_filter = state.global_filter;
+        self.sort_configs = state.sort_configs;
+        self.view_dirty = true;
+    }
+
+    /// Save the current view state under `name`, replacing any existing preset.
+    pub fn save_preset(&mut self, name: &str) {
+        let state = self.view_state();
+        self.presets.insert(name.to_string(), state);
+    }
+
+    /// Store a preset under `name` without touching the current view.
+    pub fn insert_preset(&mut self, name: String, preset: ViewPreset) {
+        self.presets.insert(name, preset);
+    }
+
+    /// Apply a saved preset. Returns `false` if no preset has that name.
+    pub fn apply_preset(&mut self, name: &str) -> bool {
+        let Some(preset) = self.presets.get(name).cloned() else {
+            return false;
+        };
+        self.set_view_state(preset);
+        true
+    }
+
+    /// Remove a saved preset. Returns `false` if no preset has that name.
+    pub fn delete_preset(&mut self, name: &str) -> bool {
+        self.presets.remove(name).is_some()
+    }
+
+    /// Saved presets in name order.
+    pub fn presets(&self) -> impl Iterator<Item = (&str, &ViewPreset)> {
+        self.presets.iter().map(|(name, p)| (name.as_str(), p))
+    }
+
+    /// The store's part of a `SavedState`: view state, grouping and collapsed
+    /// groups, expanded tree rows, and manual row order. Column and scroll fields
+    /// are left at their defaults.
+    pub fn saved_state(&self) -> SavedState {
+        let collapsed_groups = self
+            .collapsed_groups
+            .iter()
+            .map(|path| {
+                path.iter()
+                    .zip(&self.group_by)
+                    .map(|(&key, &col_idx)| match (key, self.data.get(col_idx)) {
+                        (None, _) => GroupKey::None,
+                        (Some(id), Some(ColumnData::Strings { intern, .. })) => {
+                            GroupKey::Text(intern.resolve(id as u32).to_string())
+                        }
+                        (Some(bits), _) => GroupKey::Number(bits),
+                    })
+                    .collect()
+            })
+            .collect();
+        SavedState {
+            view: self.view_state(),
+            group_by: self.group_by.clone(),
+            collapsed_groups,
+            expanded_rows: self
+                .tree
+                .as_ref()
+                .map(|tree| tree.expanded_rows().collect())
+                .unwrap_or_default(),
+            row_order: self.manual_order.clone(),
+            ..SavedState::default()
+        }
+    }
+
+    /// Apply the store's part of `state` (see `saved_state`). Collapsed groups
+    /// whose text no longer occurs in the data are dropped. Marks view dirty.
+    pub fn restore_saved_state(&mut self, state: &SavedState) {
+        self.set_view_state(state.view.clone());
+        self.set_group_by(state.group_by.clone());
+        for path in &state.collapsed_groups {
+            let keys: Option<Vec<Option<u64>>> = path
+                .iter()
+                .zip(&self.group_by)
+                .map(|(key, &col_idx)| match (key, self.data.get(col_idx)) {
+                    (GroupKey::None, _) => Some(None),
+                    (GroupKey::Number(bits), _) => Some(Some(*bits)),
+                    (GroupKey::Text(s), Some(ColumnData::Strings { intern, .. })) => {
+                        intern.get(s).map(|id| Some(u64::from(id)))
+                    }
+                    (GroupKey::Text(_), _) => None,
+                })
+                .collect();
+            self.collapsed_groups.extend(keys);
+        }
+        if let Some(tree) = &mut self.tree {
+            tree.set_all_expanded(false);
+            for &row in &state.expanded_rows {
+                tree.set_expanded(row as usize, true);
+            }
+        }
+        self.set_manual_row_order(state.row_order.clone());
+    }
+
+    /// Set pagination state. Marks view dirty.
+    pub const fn set_pagination(&mut self, page_index: Option<u32>, page_size: Option<u32>) {
+        self.page_index = page_index;
+        self.page_size = page_size;
+        self.view_dirty = true;
+    }
+
+    /// Get the total number of rows after filtering but before pagination.
+    pub const fn filtered_total(&self) -> usize {
+        self.filtered_total
+    }
+
+    /// Set scroll configuration.
+    pub const fn set_scroll_config(
+        &mut self,
+        row_height: f64,
+        viewport_height: f64,
+        overscan: usize,
+    ) {
+        self.row_height = row_height;
+        self.viewport_height = viewport_height;
+        self.overscan = overscan;
+    }
+
+    /// Rebuild the view index array: filter → sort pipeline.
+    /// Skips if not dirty. When only cell updates happened (and pagination is off),
+    /// re-positions the updated rows instead of re-running the pipeline.
+    #[allow(clippy::too_many_lines)]
+    pub fn rebuild_view(&mut self) {
+        if !self.view_dirty && self.updated_rows.is_empty() {
+            return;
+        }
+        self.view_version += 1;
+        if !self.view_dirty
+            && self.remote_query.is_none()
+            && self.page_size.is_none()
+            && self.group_sort.is_none()
+            && self.always_visible.is_empty()
+            && self.tree.is_none()
+            && self.group_by.is_empty()
+            && self.window_columns.is_empty()
+            && self.row_order_tiebreak
+            && self.manual_order.is_none()
+            && self.sort_configs.iter().all(|c| c.expression.is_none())
+        {
+            let columns = std::mem::take(&mut self.updated_columns);
+            if columns.iter().any(|&col| self.column_affects_view(col)) {
+                self.reposition_updated_rows();
+            } else {
+                // Only displayed values changed
+                self.updated_rows.clear();
+            }
+            return;
+        }
+        self.view_dirty = false;
+        self.updated_rows.clear();
+        self.updated_columns.clear();
+        self.pending_rebuild = None;
+        let started = self.clock_ms();
+        let mut stats = ViewStats::default();
+
+        if self.remote_query.is_some() {
+            let key = self.query_descriptor().key;
+            if self.remote_query.as_ref() != Some(&key) {
+                self.loaded_blocks.clear();
+                self.remote_query = Some(key);
+            }
+            let indices: Vec<u32> = (0..self.row_count as u32).collect();
+            stats.rows_filtered = indices.len();
+            self.finish_view(indices, stats, started);
+            return;
+        }
+
+        // 1. Apply column filters (AND of cached per-condition bitmaps)
+        let mut indices: Vec<u32> = if self.column_filters.is_empty() {
+            (0..self.row_count as u32).collect()
+        } else {
+            self.column_filter_mask(None, Some(&mut stats.conditions))
+                .iter_ones()
+                .collect()
+        };
+
+        // 2. Apply global filter (OR across string columns)
+        if let Some(gf) = self.global_filter.take() {
+            if !gf.query.is_empty() {
+                let t0 = self.clock_ms();
+                let scanned = indices.len();
+                global_filter_indices(&mut indices, self, &gf);
+                stats.conditions.push(ConditionStats {
+                    kind: ConditionKind::Global,
+                    column_index: None,
+                    rows_scanned: scanned,
+                    rows_passed: indices.len(),
+                    elapsed_us: self.elapsed_us(t0),
+                    cached: false,
+                });
+            }
+            self.global_filter = Some(gf);
+        }
+
+        // 3. Apply custom predicates to the survivors of native filtering
+        self.apply_custom_filters(&mut indices, None, Some(&mut stats.conditions));
+        self.drop_removed_rows(&mut indices);
+        stats.rows_filtered = indices.len();
+
+        // 4. Sort, or the manual row order in its place
+        if let Some(order) = &self.manual_order {
+            let t0 = self.clock_ms();
+            let mut rank: Vec<usize> = (order.len()..order.len() + self.row_count).collect();
+            for (i, &row) in order.iter().enumerate() {
+                if let Some(r) = rank.get_mut(row as usize) {
+                    *r = (*r).min(i);
+                }
+            }
+            indices.sort_unstable_by_key(|&row| rank[row as usize]);
+            stats.sort_us = self.elapsed_us(t0);
+        } else if !self.sort_configs.is_empty() || self.group_sort.is_some() {
+            let t0 = self.clock_ms();
+            let configs = std::mem::take(&mut self.sort_configs);
+            let column_count = self.data.len();
+            // Stable sorts over ascending input = final tie-break on row index
+            if self.row_order_tiebreak && !indices.is_sorted() {
+                indices.sort_unstable();
+            }
+            let resolved = self.push_expression_columns(&configs, &indices);
+            self.prepare_string_ranks(&resolved);
+            match resolved.as_ref() {
+                _ if self.group_sort.is_some() => self.sort_grouped(&mut indices, &resolved),
+                // Most of the table is visible: reuse (or build) the column permutation
+                [config]
+                    if config.column_index < column_count
+                        && self.row_order_tiebreak
+                        && indices.len() >= self.row_count / 8 =>
+                {
+                    indices = self.sort_by_permutation(&indices, config);
+                }
+                _ => sort_indices_columnar(&mut indices, self, &resolved),
+            }
+            drop(resolved);
+            self.sort_scratch.clear();
+            self.sort_configs = configs;
+            stats.sort_us = self.elapsed_us(t0);
+        }
+
+        self.finish_view(indices, stats, started);
+    }
+
+    /// Steps after sorting, shared by `rebuild_view` and `rebuild_view_step`:
+    /// tree flattening, pagination, grouping and window columns over `indices`.
+    fn finish_view(&mut self, mut indices: Vec<u32>, mut stats: ViewStats, started: f64) {
+        // 5. Tree data: expanded branches, keeping the ancestors of filtered rows
+        self.matched_rows = self.tree.is_some().then(|| indices.clone());
+        if let Some(tree) = &self.tree {
+            indices = tree.flatten(&indices);
+        }
+
+        // 6. Pagination slice, always-visible rows held out of it
+        self.filtered_total = indices.len();
+        self.filtered_rows =
+            (self.page_size.is_some() || !self.group_by.is_empty()).then(|| indices.clone());
+        let (top, bottom) = self.take_always_visible(&mut indices);
+        if let (Some(page_index), Some(page_size)) = (self.page_index, self.page_size) {
+            let start = (page_index as usize) * (page_size as usize);
+            let end = start.saturating_add(page_size as usize).min(indices.len());
+            if start < indices.len() {
+                indices = indices[start..end].to_vec();
+            } else {
+                indices.clear();
+            }
+        }
+
+        // 7. Grouping: a header row above each group of the page
+        self.view_groups.clear();
+        self.grouped_rows.clear();
+        if !self.group_by.is_empty() {
+            indices = self.group_view(&indices, top.len());
+        }
+        self.always_visible_counts = (top.len(), bottom.len());
+        if !top.is_empty() || !bottom.is_empty() {
+            indices = top.into_iter().chain(indices).chain(bottom).collect();
+        }
+
+        self.view_indices = indices;
+        self.view_row_heights = None;
+
+        // 8. Window columns over the sorted rows of all pages
+        self.compute_window_columns();
+        stats.total_us = self.elapsed_us(started);
+        self.last_view_stats = stats;
+    }
+
+    /// Advance a time-sliced `rebuild_view` by about `budget_ms` (at least one unit of
+    /// `REBUILD_STEP_ROWS` rows), starting one if the view needs rebuilding. The
+    /// current view stays in place until the step that reports `done`. A filter,
+    /// sort, or data change between steps discards the partial work and starts
+    /// over. Rebuilds that cannot be sliced (manual order, grouped or expression
+    /// sorts) run to completion in one step.
+    pub fn rebuild_view_step(&mut self, budget_ms: f64) -> RebuildProgress {
+        let pending = self.pending_rebuild.take();
+        let restarted = pending.is_some() && self.view_needs_rebuild();
+        let done = RebuildProgress {
+            done: true,
+            progress: 1.0,
+            restarted,
+        };
+        let mut pending = match pending {
+            Some(pending) if !restarted => pending,
+            _ if !self.view_needs_rebuild() => return done,
+            _ if self.remote_query.is_some()
+                || self.manual_order.is_some()
+                || self.group_sort.is_some()
+                || self.sort_configs.iter().any(|c| c.expression.is_some()) =>
+            {
+                self.rebuild_view();
+                return done;
+            }
+            _ => {
+                self.view_dirty = false;
+                self.updated_rows.clear();
+                self.updated_columns.clear();
+                PendingRebuild {
+                    started: self.clock_ms(),
+                    stats: ViewStats::default(),
+                    phase: RebuildPhase::Filter {
+                        indices: Vec::new(),
+                        next_row: 0,
+                    },
+                }
+            }
+        };
+
+        let deadline = self.clock_ms() + budget_ms;
+        loop {
+            if let Some(indices) = self.rebuild_unit(&mut pending) {
+                self.view_version += 1;
+                self.finish_view(indices, pending.stats, pending.started);
+                return done;
+            }
+            if self.clock_ms() >= deadline {
+                break;
+            }
+        }
+        let progress = self.rebuild_progress(&pending.phase);
+        self.pending_rebuild = Some(pending);
+        RebuildProgress {
+            done: false,
+            progress,
+            restarted,
+        }
+    }
+
+    /// One unit of work of a sliced rebuild; the sorted, filtered rows once done.
+    fn rebuild_unit(&mut self, pending: &mut PendingRebuild) -> Option<Vec<u32>> {
+        match &mut pending.phase {
+            RebuildPhase::Filter { indices, next_row } => {
+                let end = (*next_row + REBUILD_STEP_ROWS).min(self.row_count);
+                let mut chunk: Vec<u32> = (*next_row as u32..end as u32).collect();
+                filter_indices_columnar(&mut chunk, self, &self.column_filters);
+                if let Some(gf) = &self.global_filter {
+                    global_filter_indices(&mut chunk, self, gf);
+                }
+                indices.append(&mut chunk);
+                *next_row = end;
+                if end < self.row_count {
+                    return None;
+                }
+                let mut indices = std::mem::take(indices);
+                self.apply_custom_filters(&mut indices, None, None);
+                self.drop_removed_rows(&mut indices);
+                pending.stats.rows_filtered = indices.len();
+                if self.sort_configs.is_empty() || indices.len() <= 1 {
+                    return Some(indices);
+                }
+                let configs = std::mem::take(&mut self.sort_configs);
+                self.prepare_string_ranks(&configs);
+                self.sort_configs = configs;
+                pending.phase = RebuildPhase::SortRuns { indices, next: 0 };
+                None
+            }
+            RebuildPhase::SortRuns { indices, next } => {
+                let end = (*next + REBUILD_STEP_ROWS).min(indices.len());
+                sort_indices_columnar(&mut indices[*next..end], self, &self.sort_configs);
+                *next = end;
+                if end < indices.len() {
+                    return None;
+                }
+                let src = std::mem::take(indices);
+                if src.len() <= REBUILD_STEP_ROWS {
+                    return Some(src);
+                }
+                let dst = Vec::with_capacity(src.len());
+                pending.phase = RebuildPhase::Merge {
+                    src,
+                    dst,
+                    width: REBUILD_STEP_ROWS,
+                    left: 0,
+                    i: 0,
+                    j: REBUILD_STEP_ROWS,
+                };
+                None
+            }
+            RebuildPhase::Merge {
+                src,
+                dst,
+                width,
+                left,
+                i,
+                j,
+            } => {
+                let n = src.len();
+                let configs = &self.sort_configs;
+                for _ in 0..REBUILD_STEP_ROWS {
+                    let mid = (*left + *width).min(n);
+                    let end = (*left + 2 * *width).min(n);
+                    // Ties take the left run first, keeping the merge stable
+                    if *i < mid
+                        && (*j >= end || compare_rows(self, configs, src[*j], src[*i]).is_ge())
+                    {
+                        dst.push(src[*i]);
+                        *i += 1;
+                    } else if *j < end {
+                        dst.push(src[*j]);
+                        *j += 1;
+                    } else {
+                        *left = end;
+                        if *left >= n {
+                            std::mem::swap(src, dst);
+                            dst.clear();
+                            *width *= 2;
+                            *left = 0;
+                            if *width >= n {
+                                return Some(std::mem::take(src));
+                            }
+                        }
+                        *i = *left;
+                        *j = (*left + *width).min(n);
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    /// Fraction of a sliced rebuild done: filtering is the first half when the
+    /// view is sorted, merge passes share the rest of the sort.
+    fn rebuild_progress(&self, phase: &RebuildPhase) -> f64 {
+        let sort_share = if self.sort_configs.is_empty() {
+            0.0
+        } else {
+            0.5
+        };
+        match phase {
+            RebuildPhase::Filter { next_row, .. } => {
+                (1.0 - sort_share) * *next_row as f64 / self.row_count.max(1) as f64
+            }
+            RebuildPhase::SortRuns { indices, next } => {
+                let passes = f64::from(merge_passes(indices.len()));
+                0.5 + 0.5 * (*next as f64 / indices.len().max(1) as f64) / (passes + 1.0)
+            }
+            RebuildPhase::Merge {
+                src, dst, width, ..
+            } => {
+                let passes = f64::from(merge_passes(src.len()));
+                let done = f64::from((width / REBUILD_STEP_ROWS).trailing_zeros());
+                let pass = dst.len() as f64 / src.len().max(1) as f64;
+                0.5 + 0.5 * (1.0 + done + pass) / (passes + 1.0)
+            }
+        }
+    }
+
+    /// Key path of `row` through the first `levels` `group_by` columns.
+    fn group_path(&self, row: u32, levels: usize) -> Vec<Option<u64>> {
+        self.group_by[..levels]
+            .iter()
+            .map(|&col_idx| self.group_key(col_idx, row as usize))
+            .collect()
+    }
+
+    /// Order `indices` into nested groups (each in order of its first row), and
+    /// return the view: a header row before each group, without the contents of
+    /// collapsed groups. Header positions start at `pos_offset`.
+    fn group_view(&mut self, indices: &[u32], pos_offset: usize) -> Vec<u32> {
+        let levels = self.group_by.len();
+        let paths: Vec<Vec<Option<u64>>> = indices
+            .iter()
+            .map(|&row| self.group_path(row, levels))
+            .collect();
+        // Rank of each row's group at every level, by first appearance
+        let mut ranks = vec![vec![0_u32; levels]; indices.len()];
+        for level in 0..levels {
+            let mut first: HashMap<&[Option<u64>], u32> = HashMap::new();
+            for (rank, path) in ranks.iter_mut().zip(&paths) {
+                let next = first.len() as u32;
+                rank[level] = *first.entry(&path[..=level]).or_insert(next);
+            }
+        }
+        let mut order: Vec<usize> = (0..indices.len()).collect();
+        order.sort_by(|&a, &b| ranks[a].cmp(&ranks[b]));
+
+        let mut view = Vec::with_capacity(indices.len());
+        let mut collapsed: Option<usize> = None;
+        for (i, &idx) in order.iter().enumerate() {
+            let path = &paths[idx];
+            let changed = i.checked_sub(1).map_or(0, |prev| {
+                let prev_path = &paths[order[prev]];
+                (0..levels)
+                    .find(|&l| path[l] != prev_path[l])
+                    .unwrap_or(levels)
+            });
+            if collapsed.is_some_and(|level| changed <= level) {
+                collapsed = None;
+            }
+            for level in changed..levels {
+                if collapsed.is_some() {
+                    break;
+                }
+                let end = order[i..]
+                    .iter()
+                    .position(|&other| paths[other][..=level] != path[..=level])
+                    .map_or(order.len(), |len| i + len);
+                let expanded = !self.collapsed_groups.contains(&path[..=level]);
+                self.view_groups.push(GroupRow {
+                    pos: (pos_offset + view.len()) as u32,
+                    depth: level as u32,
+                    rows: i as u32..end as u32,
+                    expanded,
+                });
+                view.push(indices[idx]);
+                if !expanded {
+                    collapsed = Some(level);
+                }
+            }
+            if collapsed.is_none() {
+                view.push(indices[idx]);
+            }
+        }
+        self.grouped_rows = order.iter().map(|&idx| indices[idx]).collect();
+        view
+    }
+
+    /// Group the view by `columns`, outermost first, with a header row above each
+    /// group (empty = no grouping). Groups start expanded. Marks view dirty.
+    pub fn set_group_by(&mut self, columns: Vec<usize>) {
+        self.group_by = columns;
+        self.collapsed_groups.clear();
+        self.view_dirty = true;
+    }
+
+    pub fn group_by(&self) -> &[usize] {
+        &self.group_by
+    }
+
+    /// Expand or collapse the group whose header row is at view position `pos`.
+    /// Marks view dirty; no-op for other rows.
+    pub fn set_group_expanded(&mut self, pos: usize, expanded: bool) {
+        let Some(group) = self.view_group(pos) else {
+            return;
+        };
+        let path = self.group_path(self.view_indices[pos], group.depth as usize + 1);
+        if expanded {
+            self.collapsed_groups.remove(&path);
+        } else {
+            self.collapsed_groups.insert(path);
+        }
+        self.view_dirty = true;
+    }
+
+    /// Group header row at view position `pos`, if it is one.
+    pub fn view_group(&self, pos: usize) -> Option<&GroupRow> {
+        let i = self
+            .view_groups
+            .binary_search_by_key(&(pos as u32), |g| g.pos)
+            .ok()?;
+        Some(&self.view_groups[i])
+    }
+
+    /// Data row at view position `pos`, unless it is a group header row.
+    pub fn view_data_row(&self, pos: usize) -> Option<u32> {
+        self.view_data_rows(pos..pos.saturating_add(1))
+            .next()
+            .map(|(_, row)| row)
+    }
+
+    /// 0-based index of the data row at view position `pos` among the rows passing
+    /// the filters across pages (`filtered_total`); `None` for group header rows
+    /// and positions past the view.
+    pub fn view_filtered_index(&self, pos: usize) -> Option<usize> {
+        let len = self.view_indices.len();
+        if pos >= len || self.view_group(pos).is_some() {
+            return None;
+        }
+        let (top, bottom) = self.always_visible_counts;
+        if pos < top {
+            return Some(pos);
+        }
+        if pos >= len - bottom {
+            return Some(self.filtered_total - (len - pos));
+        }
+        let page_start = match (self.page_index, self.page_size) {
+            (Some(index), Some(size)) => index as usize * size as usize,
+            _ => 0,
+        };
+        // Grouped: the rows after the innermost header, collapsed groups counted
+        let in_page = self.view_groups_in(0..pos).last().map_or(pos - top, |g| {
+            g.rows.start as usize + pos - g.pos as usize - 1
+        });
+        Some(top + page_start + in_page)
+    }
+
+    /// View positions in `range` and their data rows, without group header rows
+    /// (which repeat the data row of their group's first row).
+    pub fn view_data_rows(
+        &self,
+        range: std::ops::Range<usize>,
+    ) -> impl Iterator<Item = (usize, u32)> + '_ {
+        let end = range.end.min(self.view_indices.len());
+        (range.start.min(end)..end)
+            .filter(|&pos| self.view_group(pos).is_none())
+            .map(|pos| (pos, self.view_indices[pos]))
+    }
+
+    /// Group header rows at view positions `range`.
+    pub fn view_groups_in(&self, range: std::ops::Range<usize>) -> &[GroupRow] {
+        let start = self
+            .view_groups
+            .partition_point(|g| (g.pos as usize) < range.start);
+        let end = self
+            .view_groups
+            .partition_point(|g| (g.pos as usize) < range.end);
+        &self.view_groups[start..end.max(start)]
+    }
+
+    /// Data rows of `group`.
+    pub fn group_rows(&self, group: &GroupRow) -> &[u32] {
+        &self.grouped_rows[group.rows.start as usize..group.rows.end as usize]
+    }
+
+    /// `aggregate` of column `col_idx` over the data rows of `group`.
+    pub fn group_aggregate(&self, group: &GroupRow, col_idx: usize, aggregate: Aggregate) -> f64 {
+        self.aggregate(col_idx, self.group_rows(group), aggregate)
+    }
+
+    /// Every row passing the filters in sort order, across all pages and without
+    /// group rows.
+    pub fn filtered_rows(&self) -> &[u32] {
+        self.filtered_rows.as_deref().unwrap_or(&self.view_indices)
+    }
+
+    /// The rows passing the filters themselves, in sort order: `filtered_rows`
+    /// without the tree ancestors kept only as context, and with the matches
+    /// inside collapsed branches.
+    pub fn matched_rows(&self) -> &[u32] {
+        self.matched_rows
+            .as_deref()
+            .unwrap_or_else(|| self.filtered_rows())
+    }
+
+    /// Numeric cells of column `col_idx` at data rows `rows`; NaN for nulls and
+    /// non-numeric columns.
+    pub fn column_values(&self, col_idx: usize, rows: &[u32]) -> Vec<f64> {
+        match self.data.get(col_idx) {
+            Some(ColumnData::Float64(values) | ColumnData::Bool(values)) => {
+                rows.iter().map(|&row| values[row as usize]).collect()
+            }
+            _ => vec![f64::NAN; rows.len()],
+        }
+    }
+
+    /// `aggregate` of column `col_idx` over data rows `rows`.
+    pub fn aggregate(&self, col_idx: usize, rows: &[u32], aggregate: Aggregate) -> f64 {
+        self.accumulate(col_idx, rows).value(aggregate)
+    }
+
+    /// Running totals of column `col_idx` over data rows `rows`; non-numeric
+    /// columns count as nulls.
+    pub fn accumulate(&self, col_idx: usize, rows: &[u32]) -> Accumulator {
+        let mut acc = Accumulator::default();
+        match self.data.get(col_idx) {
+            Some(ColumnData::Float64(values) | ColumnData::Bool(values)) => {
+                for &row in rows {
+                    acc.push(values[row as usize]);
+                }
+            }
+            _ => {
+                for _ in rows {
+                    acc.push(f64::NAN);
+                }
+            }
+        }
+        acc
+    }
+
+    /// Remove the always-visible rows from `indices`, returning the top and bottom
+    /// ones in their view order.
+    fn take_always_visible(&self, indices: &mut Vec<u32>) -> (Vec<u32>, Vec<u32>) {
+        let (mut top, mut bottom) = (Vec::new(), Vec::new());
+        if self.always_visible.is_empty() {
+            return (top, bottom);
+        }
+        // Rows added since `set_always_visible_rows` are unmarked
+        let mark = |row: u32| self.always_visible.get(row as usize).copied().unwrap_or(0);
+        indices.retain(|&row| match mark(row) {
+            ALWAYS_VISIBLE_TOP => {
+                top.push(row);
+                false
+            }
+            ALWAYS_VISIBLE_BOTTOM => {
+                bottom.push(row);
+                false
+            }
+            _ => true,
+        });
+        (top, bottom)
+    }
+
+    /// Incremental rebuild after cell updates: drop the updated rows from the view,
+    /// re-filter them, and binary-search insert the survivors. Ties are broken by
+    /// row index, matching the stable full sort over ascending indices.
+    fn reposition_updated_rows(&mut self) {
+        let started = self.clock_ms();
+        let mut rows = std::mem::take(&mut self.updated_rows);
+        rows.sort_unstable();
+        rows.dedup();
+
+        let mut view = std::mem::take(&mut self.view_indices);
+        view.retain(|r| rows.binary_search(r).is_err());
+
+        filter_indices_columnar(&mut rows, self, &self.column_filters);
+        if let Some(gf) = self
+            .global_filter
+            .as_ref()
+            .filter(|gf| !gf.query.is_empty())
+        {
+            global_filter_indices(&mut rows, self, gf);
+        }
+        self.apply_custom_filters(&mut rows, None, None);
+        self.drop_removed_rows(&mut rows);
+
+        let t0 = self.clock_ms();
+        let configs = std::mem::take(&mut self.sort_configs);
+        self.prepare_string_ranks(&configs);
+        for row in rows {
+            let pos = view.partition_point(|&r| {
+                compare_rows(self, &configs, r, row).then(r.cmp(&row)) == std::cmp::Ordering::Less
+            });
+            view.insert(pos, row);
+        }
+        self.sort_configs = configs;
+
+        self.filtered_total = view.len();
+        self.view_indices = view;
+        self.view_row_heights = None;
+        self.last_view_stats = ViewStats {
+            conditions: Vec::new(),
+            rows_filtered: self.filtered_total,
+            sort_us: self.elapsed_us(t0),
+            total_us: self.elapsed_us(started),
+        };
+    }
+
+    /// Grouping key of a cell; equal keys share a group. `None` for multi-value
+    /// columns and missing columns.
+    fn group_key(&self, col_idx: usize, row: usize) -> Option<u64> {
+        match self.data.get(col_idx)? {
+            ColumnData::Float64(v) | ColumnData::Bool(v) => {
+                let val = v[row];
+                Some(if val.is_nan() { u64::MAX } else { f64_key(val) })
+            }
+            ColumnData::Strings { ids, .. } => Some(u64::from(ids[row])),
+            ColumnData::StringList { .. } | ColumnData::Float64List { .. } => None,
+        }
+    }
+
+    /// Sort `indices` by group aggregate, then group value, then `configs`. Groups
+    /// with a null aggregate sort as the smallest. Aggregates cover only `indices`.
+    fn sort_grouped(&self, indices: &mut [u32], configs: &[SortConfig]) {
+        let Some(gs) = self.group_sort else {
+            return;
+        };
+        let group_key = |row: usize| self.group_key(gs.group_column, row);
+        let value = |row: usize| match self.data.get(gs.aggregate_column) {
+            Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => v[row],
+            _ => f64::NAN,
+        };
+
+        let mut group_ids: HashMap<u64, u32> = HashMap::new();
+        let mut first_rows: Vec<u32> = Vec::new();
+        let mut totals: Vec<Accumulator> = Vec::new();
+        let mut keyed: Vec<(u32, u32)> = Vec::with_capacity(indices.len());
+        for &row in indices.iter() {
+            let key = group_key(row as usize).unwrap_or(0);
+            let gid = *group_ids.entry(key).or_insert_with(|| {
+                first_rows.push(row);
+                totals.push(Accumulator::default());
+                (totals.len() - 1) as u32
+            });
+            totals[gid as usize].push(value(row as usize));
+            keyed.push((gid, row));
+        }
+
+        let aggregates: Vec<f64> = totals.iter().map(|t| t.value(gs.aggregate)).collect();
+        let mut groups: Vec<u32> = (0..totals.len() as u32).collect();
+        groups.sort_by(|&a, &b| {
+            let (x, y) = (aggregates[a as usize], aggregates[b as usize]);
+            let ordering = match (x.is_nan(), y.is_nan()) {
+                (true, true) => std::cmp::Ordering::Equal,
+                (true, false) => std::cmp::Ordering::Less,
+                (false, true) => std::cmp::Ordering::Greater,
+                (false, false) => x.total_cmp(&y),
+            };
+            let ordering = match gs.direction {
+                SortDirection::Ascending => ordering,
+                SortDirection::Descending => ordering.reverse(),
+            };
+            ordering.then_with(|| {
+                compare_columnar(
+                    self,
+                    gs.group_column,
+                    first_rows[a as usize] as usize,
+                    first_rows[b as usize] as usize,
+                )
+            })
+        });
+        let mut group_rank = vec![0u32; groups.len()];
+        for (rank, &gid) in groups.iter().enumerate() {
+            group_rank[gid as usize] = rank as u32;
+        }
+
+        keyed.sort_by(|&(ga, a), &(gb, b)| {
+            group_rank[ga as usize]
+                .cmp(&group_rank[gb as usize])
+                .then_with(|| compare_rows(self, configs, a, b))
+        });
+        for (slot, (_, row)) in indices.iter_mut().zip(keyed) {
+            *slot = row;
+        }
+    }
+
+    /// Order `rows` (ascending row indices) by one sort key using the cached ascending
+    /// permutation of the column, building it if needed. Descending order walks tie
+    /// groups in reverse so ties keep ascending row order, like the stable sort.
+    fn sort_by_permutation(&mut self, rows: &[u32], config: &SortConfig) -> Vec<u32> {
+        let generation = self.generation;
+        let slot = self.sort_permutations.iter().position(|p| {
+            p.generation == generation
+                && p.column_index == config.column_index
+                && p.natural == config.natural
+        });
+        let perm = match slot {
+            Some(slot) => self.sort_permutations.remove(slot),
+            None => self.build_sort_permutation(config),
+        };
+
+        let mut member = RowBitmap::new(self.row_count);
+        for &row in rows {
+            member.insert(row as usize);
+        }
+        let mut out = Vec::with_capacity(rows.len());
+        let mut emit = |range: &[u32]| {
+            #[cfg(feature = "threads")]
+            if parallel(range.len()) {
+                out.par_extend(
+                    range
+                        .par_iter()
+                        .copied()
+                        .filter(|&r| member.contains(r as usize)),
+                );
+                return;
+            }
+            out.extend(
+                range
+                    .iter()
+                    .copied()
+                    .filter(|&r| member.contains(r as usize)),
+            );
+        };
+        let descending = config.direction == SortDirection::Descending;
+        let nulls_first = config
+            .nulls
+            .map_or(!descending, |order| order == NullOrder::First);
+        let (nulls, values) = perm.order.split_at(perm.null_count);
+        if nulls_first {
+            emit(nulls);
+        }
+        if descending {
+            let mut end = values.len();
+            while end > 0 {
+                let mut start = end - 1;
+                while start > 0 && perm.ties.contains(perm.null_count + start) {
+                    start -= 1;
+                }
+                emit(&values[start..end]);
+                end = start;
+            }
+        } else {
+            emit(values);
+        }
+        if !nulls_first {
+            emit(nulls);
+        }
+
+        if self.sort_permutations.len() >= SORT_PERMUTATION_SLOTS {
+            self.sort_permutations.remove(0);
+        }
+        self.sort_permutations.push(perm);
+        out
+    }
+
+    fn build_sort_permutation(&self, config: &SortConfig) -> SortPermutation {
+        let key = [SortConfig {
+            column_index: config.column_index,
+            direction: SortDirection::Ascending,
+            natural: config.natural,
+            nulls: None,
+            expression: None,
+        }];
+        let mut order: Vec<u32> = (0..self.row_count as u32).collect();
+        sort_indices_columnar(&mut order, self, &key);
+        let ties = RowBitmap::from_fn(order.len(), |i| {
+            i > 0 && compare_rows(self, &key, order[i - 1], order[i]).is_eq()
+        });
+        let null_count = order
+            .iter()
+            .take_while(|&&row| is_null_cell(self, config.column_index, row as usize))
+            .count();
+        SortPermutation {
+            generation: self.generation,
+            column_index: config.column_index,
+            natural: config.natural,
+            order,
+            ties,
+            null_count,
+        }
+    }
+
+    // ── String ordering ───────────────────────────────────────────────
+
+    /// Set the collation used to sort string columns: engine-wide default when
+    /// `col_idx` is `None`, otherwise for one column (overriding the default).
+    /// `None` restores byte-wise ordering. Marks view dirty.
+    #[cfg(feature = "collation")]
+    pub fn set_collation(&mut self, col_idx: Option<usize>, collation: Option<Collation>) {
+        match (col_idx, collation) {
+            (None, collation) => {
+                self.default_collation = collation;
+                self.string_ranks.clear();
+                self.sort_permutations.clear();
+            }
+            (Some(col), Some(collation)) => {
+                self.column_collations.insert(col, collation);
+                self.string_ranks.remove(&col);
+                self.sort_permutations.retain(|p| p.column_index != col);
+            }
+            (Some(col), None) => {
+                self.column_collations.remove(&col);
+                self.string_ranks.remove(&col);
+                self.sort_permutations.retain(|p| p.column_index != col);
+            }
+        }
+        self.view_dirty = true;
+    }
+
+    /// Collation applied to a column, if any (column override, then engine default).
+    #[cfg(feature = "collation")]
+    pub fn collation(&self, col_idx: usize) -> Option<&Collation> {
+        self.column_collations
+            .get(&col_idx)
+            .or(self.default_collation.as_ref())
+    }
+
+    /// Sort a string column by an explicit value order: listed values in list order,
+    /// then unlisted values in their usual order. Nulls stay first. `None` clears it.
+    /// Marks view dirty.
+    pub fn set_custom_sort_order(&mut self, col_idx: usize, values: Option<Vec<String>>) {
+        match values {
+            Some(values) => self.custom_sort_orders.insert(col_idx, values),
+            None => self.custom_sort_orders.remove(&col_idx),
+        };
+        self.string_ranks.remove(&col_idx);
+        self.sort_permutations.retain(|p| p.column_index != col_idx);
+        self.view_dirty = true;
+    }
+
+    /// Explicit value order registered for a column.
+    pub fn custom_sort_order(&self, col_idx: usize) -> Option<&[String]> {
+        self.custom_sort_orders.get(&col_idx).map(Vec::as_slice)
+    }
+
+    /// Evaluate `expression` sort keys for `rows` into `sort_scratch` columns,
+    /// returning configs that point at them. Rows outside `rows` are left null.
+    /// The caller clears `sort_scratch` after sorting.
+    fn push_expression_columns<'a>(
+        &mut self,
+        configs: &'a [SortConfig],
+        rows: &[u32],
+    ) -> Cow<'a, [SortConfig]> {
+        if configs.iter().all(|c| c.expression.is_none()) {
+            return Cow::Borrowed(configs);
+        }
+        let column_count = self.data.len();
+        let mut resolved = configs.to_vec();
+        for config in &mut resolved {
+            let Some(source) = config.expression.take() else {
+                continue;
+            };
+            let mut scratch = vec![f64::NAN; self.row_count];
+            if let Ok(expr) = Expr::parse(&source) {
+                for &row in rows {
+                    let row = row as usize;
+                    scratch[row] = expr.eval(&|col| match self.data.get(col) {
+                        Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => v[row],
+                        _ => f64::NAN,
+                    });
+                }
+            }
+            config.column_index = column_count + self.sort_scratch.len();
+            config.natural = false;
+            self.sort_scratch.push(ColumnData::Float64(scratch));
+        }
+        Cow::Owned(resolved)
+    }
+
+    /// Column `col_idx` of `data`, or past its end, of `sort_scratch`.
+    fn sort_column(&self, col_idx: usize) -> Option<&ColumnData> {
+        self.data.get(col_idx).or_else(|| {
+            col_idx
+                .checked_sub(self.data.len())
+                .and_then(|i| self.sort_scratch.get(i))
+        })
+    }
+
+    /// Build rank tables for sorted string columns that need non-bytewise ordering.
+    fn prepare_string_ranks(&mut self, configs: &[SortConfig]) {
+        for config in configs {
+            let col_idx = config.column_index;
+            let Some(ColumnData::Strings { intern, .. }) = self.data.get(col_idx) else {
+                continue;
+            };
+            if self
+                .string_ranks
+                .get(&col_idx)
+                .is_some_and(|ranks| ranks.len() == intern.len())
+            {
+                continue;
+            }
+            let base = self.collation_ranks(col_idx, intern);
+            let ranks = match self.custom_sort_orders.get(&col_idx) {
+                Some(order) => custom_order_ranks(order, intern, base.as_deref()),
+                None => match base {
+                    Some(ranks) => ranks,
+                    None => continue,
+                },
+            };
+            self.string_ranks.insert(col_idx, ranks);
+        }
+    }
+
+    #[cfg(feature = "collation")]
+    fn collation_ranks(&self, col_idx: usize, intern: &StringInternTable) -> Option<Vec<u32>> {
+        self.collation(col_idx).map(|c| c.ranks(intern))
+    }
+
+    #[cfg(not(feature = "collation"))]
+    #[allow(clippy::unused_self)]
+    const fn collation_ranks(&self, _: usize, _: &StringInternTable) -> Option<Vec<u32>> {
+        None
+    }
+
+    // ── View stats ────────────────────────────────────────────────────
+
+    /// Set the clock used to time `rebuild_view` stages. Without one, all
+    /// timings in `last_view_stats` are 0.
+    pub fn set_clock(&mut self, clock: Option<ClockFn>) {
+        self.clock = clock;
+    }
+
+    /// Per-condition statistics of the most recent `rebuild_view`.
+    pub const fn last_view_stats(&self) -> &ViewStats {
+        &self.last_view_stats
+    }
+
+    fn clock_ms(&self) -> f64 {
+        self.clock.map_or(0.0, |clock| clock())
+    }
+
+    fn elapsed_us(&self, since_ms: f64) -> f64 {
+        (self.clock_ms() - since_ms) * 1000.0
+    }
+
+    /// AND together the bitmaps of all column filters (optionally skipping those on
+    /// `exclude_column`). Fully cached conditions apply first; the rest run in order of
+    /// estimated cost per rejected row (see `condition_rank`), each evaluated only on
+    /// rows that survived the conditions before it.
+    fn column_filter_mask(
+        &mut self,
+        exclude_column: Option<usize>,
+        mut stats: Option<&mut Vec<ConditionStats>>,
+    ) -> RowBitmap {
+        let generation = self.generation;
+        let filters = std::mem::take(&mut self.column_filters);
+        let mut cache = std::mem::take(&mut self.filter_cache);
+        cache.retain(|e| e.generation == generation && filters.contains(&e.filter));
+        self.selectivity.retain(|(f, _)| filters.contains(f));
+
+        let mut order: Vec<&ColumnFilter> = filters
+            .iter()
+            .filter(|f| Some(f.column_index) != exclude_column)
+            .collect();
+        let complete =
+            |f: &ColumnFilter| cache.iter().any(|e| e.filter == *f && e.domain.is_none());
+        order.sort_by(|a, b| {
+            complete(b)
+                .cmp(&complete(a))
+                .then_with(|| self.condition_rank(a).total_cmp(&self.condition_rank(b)))
+        });
+
+        let mut mask = RowBitmap::full(self.row_count);
+        for filter in order {
+            let t0 = self.clock_ms();
+            let reusable = cache.iter().position(|e| {
+                e.filter == *filter && e.domain.as_ref().is_none_or(|d| mask.is_subset_of(d))
+            });
+            let cached = reusable.is_some();
+            let scanned = if cached { 0 } else { mask.count_ones() };
+            let idx = reusable.unwrap_or_else(|| {
+                let entry = if scanned == self.row_count {
+                    FilterCacheEntry {
+                        generation,
+                        filter: filter.clone(),
+                        bitmap: filter_bitmap_columnar(self, filter),
+                        domain: None,
+                    }
+                } else {
+                    FilterCacheEntry {
+                        generation,
+                        filter: filter.clone(),
+                        bitmap: filter_bitmap_within(self, filter, &mask),
+                        domain: Some(mask.clone()),
+                    }
+                };
+                cache.retain(|e| e.filter != *filter);
+                cache.push(entry);
+                cache.len() - 1
+            });
+            mask.and_assign(&cache[idx].bitmap);
+
+            if !cached && scanned > 0 {
+                let passed = cache[idx].bitmap.count_ones();
+                let rate = passed as f64 / scanned as f64;
+                match self.selectivity.iter_mut().find(|(f, _)| f == filter) {
+                    Some(entry) => entry.1 = rate,
+                    None => self.selectivity.push((filter.clone(), rate)),
+                }
+            }
+            if let Some(stats) = stats.as_deref_mut() {
+                stats.push(ConditionStats {
+                    kind: ConditionKind::Column(filter.op),
+                    column_index: Some(filter.column_index),
+                    rows_scanned: scanned,
+                    rows_passed: cache[idx].bitmap.count_ones(),
+                    elapsed_us: self.elapsed_us(t0),
+                    cached,
+                });
+            }
+        }
+
+        self.filter_cache = cache;
+        self.column_filters = filters;
+        mask
+    }
+
+    /// Expected evaluation cost per rejected row: `cost / (1 - pass rate)`. Lower runs
+    /// earlier. The pass rate comes from previous runs, or a per-operator guess.
+    fn condition_rank(&self, filter: &ColumnFilter) -> f64 {
+        let string_column = matches!(
+            self.data.get(filter.column_index),
+            Some(ColumnData::Strings { .. } | ColumnData::StringList { .. })
+        );
+        let mut cost = match filter.op {
+            FilterOp::IsNull | FilterOp::IsNotNull | FilterOp::IsBlank | FilterOp::Within => 1.0,
+            FilterOp::Eq
+            | FilterOp::Neq
+            | FilterOp::Gt
+            | FilterOp::Gte
+            | FilterOp::Lt
+            | FilterOp::Lte => {
+                if string_column {
+                    2.0
+                } else {
+                    1.0
+                }
+            }
+            FilterOp::ContainsAny | FilterOp::ContainsAll => 3.0,
+            FilterOp::StartsWith | FilterOp::EndsWith => {
+                if self.prefix_indexes.contains_key(&filter.column_index) {
+                    1.0
+                } else {
+                    8.0
+                }
+            }
+            FilterOp::Contains => 10.0,
+            FilterOp::Expression => 4.0,
+        };
+        if filter.formatted && self.column_formats.contains_key(&filter.column_index) {
+            cost += 20.0;
+        }
+        let pass_rate = self
+            .selectivity
+            .iter()
+            .find(|(f, _)| f == filter)
+            .map_or_else(
+                || if filter.op == FilterOp::Eq { 0.1 } else { 0.5 },
+                |(_, rate)| *rate,
+            );
+        cost / (1.0 - pass_rate).max(1e-3)
+    }
+
+    /// Distinct values of `col_idx` with row counts, computed with every active filter
+    /// applied except the ones on `col_idx` itself (faceted-search semantics).
+    /// Sorted by count descending, then by value ascending; nulls sort first on ties.
+    pub fn filter_facets(&mut self, col_idx: usize) -> Vec<FacetCount> {
+        let indices = self.facet_rows(col_idx);
+        let mut facets: Vec<(Option<FilterValue>, usize)> = match self.data.get(col_idx) {
+            Some(ColumnData::Float64(v)) => count_distinct(
+                &indices,
+                |row| v[row].to_bits(),
+                |bits| {
+                    let val = f64::from_bits(bits);
+                    (!val.is_nan()).then_some(FilterValue::Float64(val))
+                },
+            ),
+            Some(ColumnData::Bool(v)) => count_distinct(
+                &indices,
+                |row| v[row].to_bits(),
+                |bits| {
+                    let val = f64::from_bits(bits);
+                    (!val.is_nan()).then_some(FilterValue::Bool(val != 0.0))
+                },
+            ),
+            Some(ColumnData::Strings { ids, intern }) => count_distinct(
+                &indices,
+                |row| u64::from(ids[row]),
+                |id| {
+                    let value = intern.resolve(id as u32);
+                    (!value.is_empty()).then(|| FilterValue::String(value.to_string()))
+                },
+            ),
+            Some(col @ ColumnData::StringList { intern, .. }) => {
+                count_list_values(&indices, col, intern)
+            }
+            Some(ColumnData::Float64List { .. }) | None => Vec::new(),
+        };
+
+        facets.sort_by(|(va, ca), (vb, cb)| {
+            cb.cmp(ca)
+                .then_with(|| compare_facet_values(va.as_ref(), vb.as_ref()))
+        });
+        facets
+            .into_iter()
+            .map(|(value, count)| FacetCount { value, count })
+            .collect()
+    }
+
+    /// Number of distinct values of `col_idx` over the rows `filter_facets` counts
+    /// (items of list columns; nulls not counted). Exact when `exact` or for up
+    /// to `cardinality::EXACT_LIMIT` rows, a `HyperLogLog` estimate otherwise.
+    pub fn distinct_count(&mut self, col_idx: usize, exact: bool) -> usize {
+        let indices = self.facet_rows(col_idx);
+        let mut counter = DistinctCounter::new(indices.len(), exact);
+        let number = |n: f64| (!n.is_nan()).then(|| f64_key(n));
+        match self.data.get(col_idx) {
+            Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => {
+                for &row in &indices {
+                    if let Some(key) = number(v[row as usize]) {
+                        counter.insert(key);
+                    }
+                }
+            }
+            Some(ColumnData::Strings { ids, .. }) => {
+                for &row in &indices {
+                    if !is_null_cell(self, col_idx, row as usize) {
+                        counter.insert(u64::from(ids[row as usize]));
+                    }
+                }
+            }
+            Some(col @ ColumnData::StringList { .. }) => {
+                for &row in &indices {
+                    for &id in col.list_ids(row as usize) {
+                        counter.insert(u64::from(id));
+                    }
+                }
+            }
+            Some(col @ ColumnData::Float64List { .. }) => {
+                for &row in &indices {
+                    for &n in col.series(row as usize) {
+                        if let Some(key) = number(n) {
+                            counter.insert(key);
+                        }
+                    }
+                }
+            }
+            None => {}
+        }
+        counter.count()
+    }
+
+    /// Rows passing every filter except the ones on `col_idx`.
+    fn facet_rows(&mut self, col_idx: usize) -> Vec<u32> {
+        let mut indices: Vec<u32> = self
+            .column_filter_mask(Some(col_idx), None)
+            .iter_ones()
+            .collect();
+        if let Some(gf) = &self.global_filter {
+            global_filter_indices(&mut indices, self, gf);
+        }
+        self.apply_custom_filters(&mut indices, Some(col_idx), None);
+        self.drop_removed_rows(&mut indices);
+        indices
+    }
+
+    /// Get the view indices (valid after `rebuild_view`).
+    pub fn view_indices(&self) -> &[u32] {
+        &self.view_indices
+    }
+
+    /// Sparkline values of a series column for view positions `view_range`:
+    /// `bucket_count` values per row (see [`sparkline::downsample`]), appended to
+    /// `out`. Rows of other column types are all NaN.
+    pub fn sparkline_data(
+        &self,
+        view_range: std::ops::Range<usize>,
+        col_idx: usize,
+        bucket_count: usize,
+        out: &mut Vec<f32>,
+    ) {
+        let end = view_range.end.min(self.view_indices.len());
+        for &row in &self.view_indices[view_range.start.min(end)..end] {
+            let series = self
+                .data
+                .get(col_idx)
+                .map_or(&[][..], |c| c.series(row as usize));
+            sparkline::downsample(series, bucket_count, out);
+        }
+    }
+
+    /// View position of a data row, or `None` if filtered out or on another page.
+    pub fn view_position(&self, row: usize) -> Option<usize> {
+        self.view_data_rows(0..usize::MAX)
+            .find(|&(_, r)| r as usize == row)
+            .map(|(pos, _)| pos)
+    }
+
+    /// Get scroll config values.
+    pub const fn row_height(&self) -> f64 {
+        self.row_height
+    }
+
+    pub const fn viewport_height(&self) -> f64 {
+        self.viewport_height
+    }
+
+    pub const fn overscan(&self) -> usize {
+        self.overscan
+    }
+
+    // ── Row heights ───────────────────────────────────────────────────
+
+    /// Switch to variable row heights (index = data row, NaN = default height), or
+    /// back to uniform rows with `None`. Heights follow their rows through sorting.
+    pub fn set_row_heights(&mut self, heights: Option<Vec<f64>>) {
+        self.row_heights = heights.unwrap_or_default();
+        self.view_row_heights = None;
+    }
+
+    /// Set one row's height (NaN = default), enabling variable heights if needed.
+    /// O(log n) once the view's height index is built.
+    pub fn set_row_height(&mut self, row: usize, height: f64) {
+        if row >= self.row_count {
+            return;
+        }
+        if self.row_heights.len() < self.row_count {
+            self.row_heights.resize(self.row_count, f64::NAN);
+        }
+        self.row_heights[row] = height;
+        if let Some(view) = &mut self.view_row_heights {
+            let pos = view.positions[row];
+            if pos != u32::MAX {
+                let height = if height.is_nan() {
+                    view.default_height
+                } else {
+                    height
+                };
+                let pos = pos as usize;
+                let areas = view.detail.get(pos).copied().unwrap_or(0.0)
+                    + view.group_header.get(pos).copied().unwrap_or(0.0);
+                view.index.set_height(pos, height + f64::from(areas));
+            }
+        }
+    }
+
+    pub const fn has_variable_row_heights(&self) -> bool {
+        !self.row_heights.is_empty()
+            || self.auto_height.is_some()
+            || !self.expanded_rows.is_empty()
+            || self.group_headers.is_some()
+    }
+
+    /// Expand the rows at view positions `view_rows` with a `detail_height` px
+    /// detail area below each, collapsing all others. Expansion sticks to the data
+    /// rows through later sorts and filters.
+    pub fn set_expanded_rows(&mut self, view_rows: &[usize], detail_height: f64) {
+        self.expanded_rows.clear();
+        self.detail_height = detail_height;
+        if !view_rows.is_empty() {
+            self.expanded_rows.resize(self.row_count, false);
+            for &pos in view_rows {
+                if let Some(&row) = self.view_indices.get(pos) {
+                    self.expanded_rows[row as usize] = true;
+                }
+            }
+        }
+        self.view_row_heights = None;
+    }
+
+    /// Keep data rows `top` and `bottom` at the start and end of the view, outside
+    /// pagination, while they pass the filters (empty = none). Marks view dirty.
+    pub fn set_always_visible_rows(&mut self, top: &[u32], bottom: &[u32]) {
+        self.always_visible.clear();
+        if !top.is_empty() || !bottom.is_empty() {
+            self.always_visible.resize(self.row_count, 0);
+            for (rows, mark) in [(top, ALWAYS_VISIBLE_TOP), (bottom, ALWAYS_VISIBLE_BOTTOM)] {
+                for &row in rows {
+                    if let Some(m) = self.always_visible.get_mut(row as usize) {
+                        *m = mark;
+                    }
+                }
+            }
+        }
+        self.view_dirty = true;
+    }
+
+    /// Always-visible rows at the start and end of the current view.
+    pub const fn always_visible_counts(&self) -> (usize, usize) {
+        self.always_visible_counts
+    }
+
+    pub fn is_row_expanded(&self, row: usize) -> bool {
+        self.expanded_rows.get(row).copied().unwrap_or(false)
+    }
+
+    pub const fn detail_height(&self) -> f64 {
+        self.detail_height
+    }
+
+    /// Size rows to their wrapped text (see `AutoHeight`), or `None` to turn it off.
+    /// Rows are measured lazily as they enter the viewport (`virtual_slice`); until
+    /// then they count as the default row height. Replaces any explicit heights.
+    pub fn set_auto_height(&mut self, auto_height: Option<AutoHeight>) {
+        self.auto_height = auto_height;
+        self.row_heights.clear();
+        self.view_row_heights = None;
+    }
+
+    pub const fn auto_height(&self) -> Option<&AutoHeight> {
+        self.auto_height.as_ref()
+    }
+
+    /// Measure text with `measure` instead of the character-count estimate, or
+    /// `None` to go back to the estimate. Remeasures all rows.
+    pub fn set_text_measure(&mut self, measure: Option<MeasureTextFn>) {
+        self.text_measure = measure.map(|m| TextMeasure(MeasureSlot::from(m)));
+        if self.auto_height.is_some() {
+            self.row_heights.clear();
+            self.view_row_heights = None;
+        }
+    }
+
+    /// Break auto-height text at the width of `set_text_width_measure` instead of
+    /// by character count, or `None` to go back to the count. Remeasures all rows
+    /// unless a `set_text_measure` callback measures them.
+    pub fn set_text_width_measure(&mut self, measure: Option<MeasureWidthFn>) {
+        self.text_width_measure = measure.map(|m| TextWidthMeasure(WidthSlot::from(m)));
+        if self.auto_height.is_some() && self.text_measure.is_none() {
+            self.row_heights.clear();
+            self.view_row_heights = None;
+        }
+    }
+
+    /// Break `text` into the lines its row height is estimated from in an
+    /// auto-height column `text_width` px wide, appending their byte ranges to
+    /// `out` (see `text_wrap::wrap_lines`).
+    pub fn wrap_auto_height_text(
+        &mut self,
+        text: &str,
+        text_width: f64,
+        out: &mut Vec<std::ops::Range<usize>>,
+    ) {
+        let char_width = self.auto_height.as_ref().map_or(1.0, |a| a.char_width);
+        wrap_text(
+            self.text_width_measure.as_mut(),
+            char_width,
+            text,
+            text_width,
+            out,
+        );
+    }
+
+    /// Auto-height of one data row from its measured columns.
+    fn measure_row(&mut self, row: usize) -> f64 {
+        let Some(auto) = self.auto_height.take() else {
+            return f64::NAN;
+        };
+        let mut text = String::new();
+        let mut lines = Vec::new();
+        let mut text_height: f64 = 0.0;
+        for column in &auto.columns {
+            text.clear();
+            self.write_cell_text(column.column_index, row, &mut text);
+            let height = if let Some(measure) = &mut self.text_measure {
+                measure.call(&text, column.text_width)
+            } else {
+                lines.clear();
+                wrap_text(
+                    self.text_width_measure.as_mut(),
+                    auto.char_width,
+                    &text,
+                    column.text_width,
+                    &mut lines,
+                );
+                lines.len() as f64 * auto.line_height
+            };
+            text_height = text_height.max(height);
+        }
+        let height = (text_height + auto.padding).max(auto.min_height);
+        self.auto_height = Some(auto);
+        height
+    }
+
+    /// Widest display text of column `col_idx` over data rows `rows`, as measured
+    /// by `measure` (text → width in px); 0 for no rows.
+    pub fn column_content_width(
+        &self,
+        col_idx: usize,
+        rows: impl IntoIterator<Item = usize>,
+        mut measure: impl FnMut(&str) -> f64,
+    ) -> f64 {
+        let mut text = String::new();
+        rows.into_iter().fold(0.0, |widest: f64, row| {
+            text.clear();
+            self.write_cell_text(col_idx, row, &mut text);
+            widest.max(measure(&text))
+        })
+    }
+
+    /// Whether the cell of view position `pos` in column `col_idx` matches the
+    /// global filter (`set_global_filter`), for highlighting; false without one.
+    pub fn view_cell_matches_global_filter(&self, pos: usize, col_idx: usize) -> bool {
+        let Some(filter) = self.global_filter.as_ref().filter(|f| !f.query.is_empty()) else {
+            return false;
+        };
+        self.view_indices.get(pos).is_some_and(|&row| {
+            string_cell_contains(self, col_idx, row as usize, &filter.query.to_lowercase())
+        })
+    }
+
+    /// Mean and widest display text width of column `col_idx` over data rows
+    /// `rows`, as measured by `measure` (text → width in px); `(0, 0)` for no
+    /// rows. Sizes `min-content`/`max-content` column tracks.
+    pub fn column_width_stats(
+        &self,
+        col_idx: usize,
+        rows: impl IntoIterator<Item = usize>,
+        mut measure: impl FnMut(&str) -> f64,
+    ) -> (f64, f64) {
+        let mut text = String::new();
+        let (mut sum, mut widest, mut count) = (0.0, 0.0_f64, 0_usize);
+        for row in rows {
+            text.clear();
+            self.write_cell_text(col_idx, row, &mut text);
+            let width = measure(&text);
+            sum += width;
+            widest = widest.max(width);
+            count += 1;
+        }
+        (if count == 0 { 0.0 } else { sum / count as f64 }, widest)
+    }
+
+    /// Up to `sample_size` data rows spread evenly over the view (0 = every row).
+    pub fn view_sample(&self, sample_size: usize) -> impl Iterator<Item = usize> + '_ {
+        let step = match sample_size {
+            0 => 1,
+            n => self.view_indices.len().div_ceil(n).max(1),
+        };
+        self.view_indices
+            .iter()
+            .step_by(step)
+            .map(|&row| row as usize)
+    }
+
+    /// Display text of a cell: formatted numbers and strings, `true`/`false`,
+    /// comma-joined lists; empty for nulls.
+    pub fn write_cell_text(&self, col_idx: usize, row: usize, out: &mut String) {
+        use std::fmt::Write;
+        match self.data.get(col_idx) {
+            Some(ColumnData::Float64(v)) if !v[row].is_nan() => {
+                match self.column_formats.get(&col_idx) {
+                    Some(spec) => spec.write_f64(v[row], out),
+                    None => {
+                        let _ = write!(out, "{}", v[row]);
+                    }
+                }
+            }
+            Some(ColumnData::Bool(v)) if !v[row].is_nan() => {
+                out.push_str(if v[row] == 0.0 { "false" } else { "true" });
+            }
+            Some(ColumnData::Strings { ids, intern }) => {
+                let text = intern.resolve(ids[row]);
+                match self.column_formats.get(&col_idx) {
+                    Some(spec) if !text.is_empty() => spec.write_str(text, out),
+                    _ => out.push_str(text),
+                }
+            }
+            Some(col @ ColumnData::StringList { intern, .. }) => {
+                for (i, &id) in col.list_ids(row).iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    out.push_str(intern.resolve(id));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Rows of the view to render for `state`, using per-row heights when set
+    /// (`default_height` fills the gaps). With auto-height, rows in the slice that
+    /// were not measured yet are measured and the slice is recomputed once.
+    pub fn virtual_slice(&mut self, state: &ScrollState, default_height: f64) -> VirtualSlice {
+        let Some(index) = self.view_row_heights(default_height) else {
+            return compute_virtual_slice(state);
+        };
+        let slice = compute_virtual_slice_variable(state, index);
+        if self.auto_height.is_none() {
+            return slice;
+        }
+        let total = self.view_indices.len();
+        let pinned_top = state.pinned_top.unwrap_or(0).min(total);
+        let pinned_bottom = state.pinned_bottom.unwrap_or(0).min(total - pinned_top);
+        let unmeasured: Vec<usize> = (0..pinned_top)
+            .chain(slice.start_index..slice.end_index)
+            .chain(total - pinned_bottom..total)
+            .map(|pos| self.view_indices[pos] as usize)
+            .filter(|&row| self.row_heights.get(row).is_none_or(|h| h.is_nan()))
+            .collect();
+        if unmeasured.is_empty() {
+            return slice;
+        }
+        for row in unmeasured {
+            let height = self.measure_row(row);
+            self.set_row_height(row, height);
+        }
+        self.view_row_heights(default_height)
+            .map_or(slice, |index| compute_virtual_slice_variable(state, index))
+    }
+
+    /// Heights of the view's rows in view order, with missing heights filled by
+    /// `default_height`. Rebuilt in O(n) after the view or default height changes;
+    /// `None` for uniform rows.
+    pub fn view_row_heights(&mut self, default_height: f64) -> Option<&RowHeightIndex> {
+        self.view_row_layout(default_height).map(|(index, _)| index)
+    }
+
+    /// `view_row_heights` (row areas included) with the detail and group header
+    /// areas of each view position.
+    pub fn view_row_layout(
+        &mut self,
+        default_height: f64,
+    ) -> Option<(&RowHeightIndex, RowAreas<'_>)> {
+        if !self.has_variable_row_heights() {
+            return None;
+        }
+        let stale = self
+            .view_row_heights
+            .as_ref()
+            .is_none_or(|v| v.default_height.to_bits() != default_height.to_bits());
+        if stale {
+            let mut positions = vec![u32::MAX; self.row_count];
+            let detail: Vec<f32> = if self.expanded_rows.is_empty() {
+                Vec::new()
+            } else {
+                self.view_indices
+                    .iter()
+                    .map(|&row| {
+                        if self.is_row_expanded(row as usize) {
+                            self.detail_height as f32
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect()
+            };
+            let mut group_starts = Vec::new();
+            let group_header: Vec<f32> = match self.group_break_column() {
+                Some((col_idx, height)) => {
+                    let mut prev = None;
+                    self.view_indices
+                        .iter()
+                        .enumerate()
+                        .map(|(pos, &row)| {
+                            let key = Some(self.group_key(col_idx, row as usize));
+                            if key == prev {
+                                return 0.0;
+                            }
+                            prev = key;
+                            group_starts.push(pos as u32);
+                            height as f32
+                        })
+                        .collect()
+                }
+                None => Vec::new(),
+            };
+            let heights = self
+                .view_indices
+                .iter()
+                .enumerate()
+                .map(|(pos, &row)| {
+                    positions[row as usize] = pos as u32;
+                    let areas = detail.get(pos).copied().unwrap_or(0.0)
+                        + group_header.get(pos).copied().unwrap_or(0.0);
+                    let height = match self.row_heights.get(row as usize) {
+                        Some(&h) if !h.is_nan() => h,
+                        _ => default_height,
+                    };
+                    height + f64::from(areas)
+                })
+                .collect();
+            self.view_row_heights = Some(ViewRowHeights {
+                default_height,
+                positions,
+                index: RowHeightIndex::new(heights),
+                detail,
+                group_header,
+                group_starts,
+            });
+        }
+        self.view_row_heights.as_ref().map(|v| {
+            (
+                &v.index,
+                RowAreas {
+                    detail: &v.detail,
+                    group_header: &v.group_header,
+                },
+            )
+        })
+    }
+
+    /// Show a header row above the first row of each group (`None` = off).
+    pub fn set_group_headers(&mut self, group_headers: Option<GroupHeaders>) {
+        self.group_headers = group_headers;
+        self.view_row_heights = None;
+    }
+
+    pub const fn group_headers(&self) -> Option<&GroupHeaders> {
+        self.group_headers.as_ref()
+    }
+
+    /// Column whose value changes start a new group, and the header height.
+    fn group_break_column(&self) -> Option<(usize, f64)> {
+        let headers = self.group_headers.as_ref()?;
+        let col_idx = headers
+            .column
+            .or_else(|| self.group_sort.map(|gs| gs.group_column))?;
+        Some((col_idx, headers.height))
+    }
+
+    /// Group header to pin at content offset `y` (the top of the data region, in
+    /// view-row space): the view position of the group's first row, the header
+    /// height, and the content offset of the next group's header, which pushes the
+    /// pinned one up as it arrives. `None` without group headers.
+    pub fn sticky_group_header(
+        &mut self,
+        default_height: f64,
+        y: f64,
+    ) -> Option<(usize, f32, Option<f64>)> {
+        self.view_row_layout(default_height)?;
+        let view = self.view_row_heights.as_ref()?;
+        let pos = view.index.row_at_offset(y.max(0.0)) as u32;
+        let i = view.group_starts.partition_point(|&start| start <= pos);
+        let start = *view.group_starts.get(i.checked_sub(1)?)? as usize;
+        let next = view
+            .group_starts
+            .get(i)
+            .map(|&next| view.index.offset(next as usize));
+        Some((start, view.group_header[start], next))
+    }
+
+    /// Top of view row `pos`'s cells relative to the first view row and their
+    /// height, px, without its group header and detail areas. `None` past the view.
+    pub fn view_row_extent(&mut self, pos: usize, default_height: f64) -> Option<(f64, f64)> {
+        if pos >= self.view_indices.len() {
+            return None;
+        }
+        Some(match self.view_row_layout(default_height) {
+            Some((index, areas)) => {
+                let (group_header, detail) = areas.margins(pos);
+                (
+                    index.offset(pos) + f64::from(group_header),
+                    index.height(pos) - f64::from(group_header) - f64::from(detail),
+                )
+            }
+            None => (pos as f64 * default_height, default_height),
+        })
+    }
+
+    /// Top of view row `pos` relative to the first view row, px.
+    pub fn view_row_offset(&mut self, pos: usize, default_height: f64) -> f64 {
+        self.view_row_heights(default_height)
+            .map_or(pos as f64 * default_height, |index| index.offset(pos))
+    }
+
+    /// Anchor for the current view scrolled to `scroll_top` (relative to the
+    /// segment after `pinned_top` rows): data row `row` if it is in that segment,
+    /// else the row at the top of the viewport. `None` for an empty segment.
+    pub fn scroll_anchor(
+        &mut self,
+        row: Option<usize>,
+        scroll_top: f64,
+        default_height: f64,
+        pinned_top: usize,
+    ) -> Option<ScrollAnchor> {
+        let total = self.view_indices.len();
+        if pinned_top >= total {
+            return None;
+        }
+        let origin = self.view_row_offset(pinned_top, default_height);
+        let pos = match row.and_then(|r| self.view_position(r)) {
+            Some(pos) if pos >= pinned_top => pos,
+            _ => {
+                let y = origin + scroll_top.max(0.0);
+                let pos = self.view_row_heights(default_height).map_or_else(
+                    || (y / default_height) as usize,
+                    |index| index.row_at_offset(y),
+                );
+                pos.clamp(pinned_top, total - 1)
+            }
+        };
+        Some(ScrollAnchor {
+            row: self.view_indices[pos] as usize,
+            offset: self.view_row_offset(pos, default_height) - origin - scroll_top,
+        })
+    }
+
+    /// `scroll_top` that puts `anchor.row` back at `anchor.offset` in the current
+    /// view, or `None` if the row is no longer in the scrolled segment.
+    pub fn anchored_scroll_top(
+        &mut self,
+        anchor: &ScrollAnchor,
+        default_height: f64,
+        pinned_top: usize,
+    ) -> Option<f64> {
+        let pos = self
+            .view_position(anchor.row)
+            .filter(|&pos| pos >= pinned_top)?;
+        let top = self.view_row_offset(pos, default_height)
+            - self.view_row_offset(pinned_top, default_height);
+        Some((top - anchor.offset).max(0.0))
+    }
+
+    /// Get the Float64 data pointer for a column (for zero-copy JS access).
+    /// Returns None if column is not Float64.
+    pub fn get_float64_ptr(&self, col_idx: usize) -> Option<(*const f64, usize)> {
+        match self.data.get(col_idx) {
+            Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => Some((v.as_ptr(), v.len())),
+            _ => None,
+        }
+    }
+
+    /// Get the column type.
+    pub fn column_type(&self, col_idx: usize) -> Option<ColumnType> {
+        self.data.get(col_idx).map(|d| match d {
+            ColumnData::Float64(_) => ColumnType::Float64,
+            ColumnData::Strings { .. } => ColumnType::String,
+            ColumnData::Bool(_) => ColumnType::Bool,
+            ColumnData::StringList { .. } => ColumnType::StringList,
+            ColumnData::Float64List { .. } => ColumnType::Float64List,
+        })
+    }
+}
+
+impl Default for ColumnarStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ── Index operations on ColumnarStore ─────────────────────────────────
+
+/// Sort indices by comparing columnar data directly. Stable: rows that compare
+/// equal keep their input order, on every path (radix, counting, comparison).
+pub fn sort_indices_columnar(indices: &mut [u32], store: &ColumnarStore, configs: &[SortConfig]) {
+    if configs.is_empty() {
+        return;
+    }
+    if let [config] = configs {
+        if sort_single_key_fast(indices, store, config) {
+            return;
+        }
+    }
+
+    #[cfg(feature = "threads")]
+    if parallel(indices.len()) {
+        indices.par_sort_by(|&a, &b| compare_rows(store, configs, a, b));
+        return;
+    }
+    indices.sort_by(|&a, &b| compare_rows(store, configs, a, b));
+}
+
+/// Rows below which the comparison sort is used even for single-key sorts.
+const FAST_SORT_MIN_ROWS: usize = 64;
+
+/// Single-key sort without comparisons: radix sort on order-preserving key bits for
+/// Float64/Bool columns, counting sort by rank for string columns with few distinct
+/// values. Returns `false` (leaving `indices` untouched) when neither applies.
+/// Produces the same order as the stable comparison sort.
+fn sort_single_key_fast(indices: &mut [u32], store: &ColumnarStore, config: &SortConfig) -> bool {
+    if indices.len() < FAST_SORT_MIN_ROWS {
+        return false;
+    }
+    let descending = config.direction == SortDirection::Descending;
+    // `None` treats null as the smallest value
+    let nulls_first = config
+        .nulls
+        .map_or(!descending, |order| order == NullOrder::First);
+    match store.sort_column(config.column_index) {
+        Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => {
+            let null_key = if nulls_first { 0 } else { u64::MAX };
+            let key = |row: u32| {
+                let value = v[row as usize];
+                if value.is_nan() {
+                    return null_key;
+                }
+                let key = f64_key(value);
+                // Keep 0 and MAX free for nulls
+                if descending { !key } else { key }.clamp(1, u64::MAX - 1)
+            };
+            #[cfg(feature = "threads")]
+            if parallel(indices.len()) {
+                par_radix_sort_by_key(indices, key);
+                return true;
+            }
+            radix_sort_by_key(indices, key);
+            true
+        }
+        Some(ColumnData::Strings { ids, intern }) => {
+            if intern.len().saturating_mul(4) > indices.len() {
+                return false;
+            }
+            let custom = store.custom_sort_orders.contains_key(&config.column_index);
+            let stored = store
+                .string_ranks
+                .get(&config.column_index)
+                .filter(|r| r.len() == intern.len() && (custom || !config.natural));
+            let ranks: Cow<'_, [u32]> = stored.map_or_else(
+                || {
+                    Cow::Owned(if config.natural {
+                        rank_strings(intern, natural_cmp)
+                    } else {
+                        rank_strings(intern, str::cmp)
+                    })
+                },
+                |ranks| Cow::Borrowed(ranks.as_slice()),
+            );
+            let null_id = config.nulls.and_then(|_| intern.get(""));
+            let max_rank = ranks.iter().copied().max().unwrap_or(0) as usize;
+            let rank = |row: u32| {
+                let id = ids[row as usize];
+                if Some(id) == null_id {
+                    return if nulls_first { 0 } else { max_rank + 2 };
+                }
+                let rank = ranks[id as usize] as usize;
+                1 + if descending { max_rank - rank } else { rank }
+            };
+            #[cfg(feature = "threads")]
+            if parallel(indices.len()) {
+                par_counting_sort_by_rank(indices, max_rank + 3, rank);
+                return true;
+            }
+            counting_sort_by_rank(indices, max_rank + 3, rank);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Rank every interned string by `cmp` (index = intern ID); equal strings share a rank.
+fn rank_strings(
+    intern: &StringInternTable,
+    cmp: impl Fn(&str, &str) -> std::cmp::Ordering,
+) -> Vec<u32> {
+    let mut ids: Vec<u32> = (0..intern.len() as u32).collect();
+    ids.sort_by(|&a, &b| cmp(intern.resolve(a), intern.resolve(b)));
+    let mut ranks = vec![0; ids.len()];
+    let mut rank = 0;
+    for (i, &id) in ids.iter().enumerate() {
+        if i > 0 && cmp(intern.resolve(ids[i - 1]), intern.resolve(id)).is_ne() {
+            rank += 1;
+        }
+        ranks[id as usize] = rank;
+    }
+    ranks
+}
+
+/// Compare two rows by a list of sort keys.
+fn compare_rows(
+    store: &ColumnarStore,
+    configs: &[SortConfig],
+    a: u32,
+    b: u32,
+) -> std::cmp::Ordering {
+    for config in configs {
+        if let Some(nulls) = config.nulls {
+            let col = config.column_index;
+            let a_first = match (
+                is_null_cell(store, col, a as usize),
+                is_null_cell(store, col, b as usize),
+            ) {
+                (true, true) => continue,
+                (true, false) => Some(nulls == NullOrder::First),
+                (false, true) => Some(nulls == NullOrder::Last),
+                (false, false) => None,
+            };
+            if let Some(a_first) = a_first {
+                return if a_first {
+                    std::cmp::Ordering::Less
+                } else {
+                    std::cmp::Ordering::Greater
+                };
+            }
+        }
+        let ordering = if config.natural {
+            compare_natural(store, config.column_index, a as usize, b as usize)
+        } else {
+            compare_columnar(store, config.column_index, a as usize, b as usize)
+        };
+        let ordering = match config.direction {
+            SortDirection::Ascending => ordering,
+            SortDirection::Descending => ordering.reverse(),
+        };
+        if ordering != std::cmp::Ordering::Equal {
+            return ordering;
+        }
+    }
+    std::cmp::Ordering::Equal
+}
+
+/// Merge passes over `REBUILD_STEP_ROWS`-row sorted runs to sort `len` rows.
+const fn merge_passes(len: usize) -> u32 {
+    len.div_ceil(REBUILD_STEP_ROWS)
+        .next_power_of_two()
+        .trailing_zeros()
+}
+
+/// Smallest and largest of `values`; `(inf, -inf)` when empty.
+fn min_max(values: &[f64]) -> (f64, f64) {
+    values
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        })
+}
+
+/// Filter indices by column filters (AND logic: row must pass all filters).
+pub fn filter_indices_columnar(
+    indices: &mut Vec<u32>,
+    store: &ColumnarStore,
+    filters: &[ColumnFilter],
+) {
+    if filters.is_empty() {
+        return;
+    }
+    #[cfg(feature = "threads")]
+    if parallel(indices.len()) {
+        *indices = indices
+            .par_iter()
+            .copied()
+            .filter(|&idx| {
+                filters
+                    .iter()
+                    .all(|f| match_column_filter(store, f, idx as usize))
+            })
+            .collect();
+        return;
+    }
+    indices.retain(|&idx| {
+        let row = idx as usize;
+        filters.iter().all(|f| match_column_filter(store, f, row))
+    });
+}
+
+/// Evaluate a single column filter over every row, producing a row bitmap.
+pub fn filter_bitmap_columnar(store: &ColumnarStore, filter: &ColumnFilter) -> RowBitmap {
+    indexed_filter_bitmap(store, filter).unwrap_or_else(|| {
+        #[cfg(feature = "threads")]
+        if parallel(store.row_count) {
+            return RowBitmap::par_from_fn(store.row_count, |row| {
+                match_column_filter(store, filter, row)
+            });
+        }
+        RowBitmap::from_fn(store.row_count, |row| {
+            match_column_filter(store, filter, row)
+        })
+    })
+}
+
+/// Evaluate a column filter only on the rows set in `domain`; other rows stay clear.
+fn filter_bitmap_within(
+    store: &ColumnarStore,
+    filter: &ColumnFilter,
+    domain: &RowBitmap,
+) -> RowBitmap {
+    if let Some(mut bitmap) = indexed_filter_bitmap(store, filter) {
+        bitmap.and_assign(domain);
+        return bitmap;
+    }
+    let mut bitmap = RowBitmap::new(store.row_count);
+    for row in domain.iter_ones() {
+        if match_column_filter(store, filter, row as usize) {
+            bitmap.insert(row as usize);
+        }
+    }
+    bitmap
+}
+
+/// `StartsWith`/`EndsWith` via the column's prefix index, if one is enabled and current.
+fn indexed_filter_bitmap(store: &ColumnarStore, filter: &ColumnFilter) -> Option<RowBitmap> {
+    if filter.formatted {
+        return None;
+    }
+    let FilterValue::String(target) = &filter.value else {
+        return None;
+    };
+    let Some(ColumnData::Strings { ids, intern }) = store.data.get(filter.column_index) else {
+        return None;
+    };
+    let index = store
+        .prefix_indexes
+        .get(&filter.column_index)
+        .filter(|index| index.intern_len() == intern.len())?;
+    let matching = match filter.op {
+        FilterOp::StartsWith => index.starts_with(target),
+        FilterOp::EndsWith => index.ends_with(target),
+        _ => return None,
+    };
+    Some(RowBitmap::from_fn(store.row_count, |row| {
+        matching.contains(ids[row] as usize)
+    }))
+}
+
+/// The sources of the `Expression` filters among `filters`, each with its parsed
+/// expression or parse error.
+pub fn parse_filter_expressions(
+    filters: &[ColumnFilter],
+) -> impl Iterator<Item = (&str, Result<Expr, String>)> {
+    filters
+        .iter()
+        .filter(|f| f.op == FilterOp::Expression)
+        .filter_map(|f| match &f.value {
+            FilterValue::String(source) => Some((source.as_str(), Expr::parse(source))),
+            _ => None,
+        })
+}
+
+/// Check if a single row passes a column filter.
+fn match_column_filter(store: &ColumnarStore, filter: &ColumnFilter, row: usize) -> bool {
+    if filter.op == FilterOp::Expression {
+        return match_expression_filter(store, filter, row);
+    }
+    if matches!(
+        filter.op,
+        FilterOp::IsNull | FilterOp::IsNotNull | FilterOp::IsBlank
+    ) {
+        return match_null_filter(store, filter.column_index, filter.op, row);
+    }
+    if filter.formatted {
+        if let Some(matched) = match_formatted_filter(store, filter, row) {
+            return matched;
+        }
+    }
+    match store.data.get(filter.column_index) {
+        Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => {
+            let val = v[row];
+            if val.is_nan() {
+                return false; // NaN never passes
+            }
+            match &filter.value {
+                FilterValue::Float64(target) => match filter.op {
+                    FilterOp::Eq => (val - target).abs() < f64::EPSILON,
+                    FilterOp::Neq => (val - target).abs() >= f64::EPSILON,
+                    FilterOp::Gt => val > *target,
+                    FilterOp::Gte => val >= *target - f64::EPSILON,
+                    FilterOp::Lt => val < *target,
+                    FilterOp::Lte => val <= *target + f64::EPSILON,
+                    FilterOp::Contains
+                    | FilterOp::StartsWith
+                    | FilterOp::EndsWith
+                    | FilterOp::IsNull
+                    | FilterOp::IsNotNull
+                    | FilterOp::IsBlank
+                    | FilterOp::ContainsAny
+                    | FilterOp::ContainsAll
+                    | FilterOp::Within
+                    | FilterOp::Expression => false,
+                },
+                FilterValue::Bool(target) => {
+                    let val_bool = val != 0.0;
+                    match filter.op {
+                        FilterOp::Eq => val_bool == *target,
+                        FilterOp::Neq => val_bool != *target,
+                        _ => false,
+                    }
+                }
+                FilterValue::RelativeDate(range) => {
+                    filter.op == FilterOp::Within
+                        && range.contains(val, store.now_ms, store.utc_offset_minutes)
+                }
+                FilterValue::String(_) | FilterValue::StringList(_) => false,
+            }
+        }
+        Some(ColumnData::Strings { ids, intern }) => {
+            let resolved = intern.resolve(ids[row]);
+            if resolved.is_empty() {
+                return false; // null sentinel never passes
+            }
+            match &filter.value {
+                FilterValue::String(target) => match_string_op(resolved, filter.op, target),
+                FilterValue::StringList(targets) => match filter.op {
+                    FilterOp::ContainsAny => targets.iter().any(|t| t == resolved),
+                    FilterOp::ContainsAll => targets.iter().all(|t| t == resolved),
+                    _ => false,
+                },
+                _ => false,
+            }
+        }
+        Some(col @ ColumnData::StringList { intern, .. }) => {
+            let tags = col.list_ids(row);
+            if tags.is_empty() {
+                return false; // empty list is null
+            }
+            match_list_filter(tags, intern, filter)
+        }
+        Some(ColumnData::Float64List { .. }) | None => false,
+    }
+}
+
+/// Evaluate an `Expression` filter for a row; non-numeric columns read as null.
+fn match_expression_filter(store: &ColumnarStore, filter: &ColumnFilter, row: usize) -> bool {
+    let FilterValue::String(source) = &filter.value else {
+        return false;
+    };
+    store.filter_exprs.get(source).is_some_and(|expr| {
+        let result = expr.eval(&|col| match store.data.get(col) {
+            Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => v[row],
+            _ => f64::NAN,
+        });
+        !result.is_nan() && result != 0.0
+    })
+}
+
+/// Match a multi-value row. `ContainsAny`/`ContainsAll` compare whole tags exactly;
+/// other string operators pass if any tag matches (`Neq`: if no tag equals the target).
+fn match_list_filter(tags: &[u32], intern: &StringInternTable, filter: &ColumnFilter) -> bool {
+    match (&filter.value, filter.op) {
+        (FilterValue::StringList(targets), FilterOp::ContainsAny) => targets
+            .iter()
+            .filter_map(|t| intern.get(t))
+            .any(|id| tags.contains(&id)),
+        (FilterValue::StringList(targets), FilterOp::ContainsAll) => targets
+            .iter()
+            .all(|t| intern.get(t).is_some_and(|id| tags.contains(&id))),
+        (FilterValue::String(target), FilterOp::Neq) => {
+            !tags.iter().any(|&id| intern.resolve(id) == target)
+        }
+        (FilterValue::String(target), op) => tags
+            .iter()
+            .any(|&id| match_string_op(intern.resolve(id), op, target)),
+        _ => false,
+    }
+}
+
+/// Compare a cell string against a target. Pattern operators are case-insensitive.
+fn match_string_op(resolved: &str, op: FilterOp, target: &str) -> bool {
+    match op {
+        FilterOp::Eq => resolved == target,
+        FilterOp::Neq => resolved != target,
+        FilterOp::Gt => resolved > target,
+        FilterOp::Gte => resolved >= target,
+        FilterOp::Lt => resolved < target,
+        FilterOp::Lte => resolved <= target,
+        FilterOp::Contains => resolved.to_lowercase().contains(&target.to_lowercase()),
+        FilterOp::StartsWith => resolved.to_lowercase().starts_with(&target.to_lowercase()),
+        FilterOp::EndsWith => resolved.to_lowercase().ends_with(&target.to_lowercase()),
+        FilterOp::IsNull
+        | FilterOp::IsNotNull
+        | FilterOp::IsBlank
+        | FilterOp::ContainsAny
+        | FilterOp::ContainsAll
+        | FilterOp::Within
+        | FilterOp::Expression => false,
+    }
+}
+
+/// Match a string target against the formatted text of a numeric cell.
+/// Returns `None` when the condition does not apply (no registered format,
+/// non-numeric column, or non-string target) so raw matching is used instead.
+fn match_formatted_filter(
+    store: &ColumnarStore,
+    filter: &ColumnFilter,
+    row: usize,
+) -> Option<bool> {
+    let spec = store.column_format(filter.column_index)?;
+    let FilterValue::String(target) = &filter.value else {
+        return None;
+    };
+    match store.data.get(filter.column_index)? {
+        ColumnData::Float64(v) | ColumnData::Bool(v) => {
+            let val = v[row];
+            Some(!val.is_nan() && match_string_op(&spec.format_f64(val), filter.op, target))
+        }
+        ColumnData::Strings { .. }
+        | ColumnData::StringList { .. }
+        | ColumnData::Float64List { .. } => None,
+    }
+}
+
+/// Evaluate a null-aware operator. Float64/Bool nulls are NaN; string nulls are the
+/// empty-string sentinel. `IsBlank` additionally matches whitespace-only strings.
+fn match_null_filter(store: &ColumnarStore, col_idx: usize, op: FilterOp, row: usize) -> bool {
+    let (is_null, is_blank) = match store.data.get(col_idx) {
+        Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => {
+            let null = v[row].is_nan();
+            (null, null)
+        }
+        Some(ColumnData::Strings { ids, intern }) => {
+            let resolved = intern.resolve(ids[row]);
+            (resolved.is_empty(), resolved.trim().is_empty())
+        }
+        Some(col @ ColumnData::StringList { intern, .. }) => {
+            let tags = col.list_ids(row);
+            (
+                tags.is_empty(),
+                tags.iter().all(|&id| intern.resolve(id).trim().is_empty()),
+            )
+        }
+        Some(col @ ColumnData::Float64List { .. }) => {
+            let null = col.series(row).is_empty();
+            (null, null)
+        }
+        None => return false,
+    };
+    match op {
+        FilterOp::IsNull => is_null,
+        FilterOp::IsNotNull => !is_null,
+        FilterOp::IsBlank => is_blank,
+        _ => false,
+    }
+}
+
+/// Filter indices by global filter (OR across all string and string-list columns,
+/// case-insensitive contains).
+pub fn global_filter_indices(indices: &mut Vec<u32>, store: &ColumnarStore, filter: &GlobalFilter) {
+    let query = filter.query.to_lowercase();
+    if query.is_empty() {
+        return;
+    }
+
+    // Collect string column indices
+    let string_cols: Vec<usize> = store
+        .data
+        .iter()
+        .enumerate()
+        .filter_map(|(i, d)| {
+            matches!(
+                d,
+                ColumnData::Strings { .. } | ColumnData::StringList { .. }
+            )
+            .then_some(i)
+        })
+        .collect();
+
+    if string_cols.is_empty() {
+        return;
+    }
+
+    #[cfg(feature = "threads")]
+    if parallel(indices.len()) {
+        *indices = indices
+            .par_iter()
+            .copied()
+            .filter(|&idx| {
+                string_cols
+                    .iter()
+                    .any(|&col_idx| string_cell_contains(store, col_idx, idx as usize, &query))
+            })
+            .collect();
+        return;
+    }
+    indices.retain(|&idx| {
+        string_cols
+            .iter()
+            .any(|&col_idx| string_cell_contains(store, col_idx, idx as usize, &query))
+    });
+}
+
+/// Whether the string (or any string list item) of a cell contains the
+/// lowercase `query`, ignoring case. False for other column types.
+fn string_cell_contains(store: &ColumnarStore, col_idx: usize, row: usize, query: &str) -> bool {
+    match store.data.get(col_idx) {
+        Some(ColumnData::Strings { ids, intern }) => {
+            intern.resolve(ids[row]).to_lowercase().contains(query)
+        }
+        Some(col @ ColumnData::StringList { intern, .. }) => col
+            .list_ids(row)
+            .iter()
+            .any(|&id| intern.resolve(id).to_lowercase().contains(query)),
+        _ => false,
+    }
+}
+
+/// Count rows per distinct key, converting each key to a facet value once.
+/// Keys that map to the same value (e.g. NaN payloads, bool encodings) are merged.
+fn count_distinct(
+    indices: &[u32],
+    key: impl Fn(usize) -> u64,
+    to_value: impl Fn(u64) -> Option<FilterValue>,
+) -> Vec<(Option<FilterValue>, usize)> {
+    let mut counts: HashMap<u64, usize> = HashMap::new();
+    for &idx in indices {
+        *counts.entry(key(idx as usize)).or_insert(0) += 1;
+    }
+    let mut merged: Vec<(Option<FilterValue>, usize)> = Vec::with_capacity(counts.len());
+    for (k, count) in counts {
+        let value = to_value(k);
+        if let Some(entry) = merged.iter_mut().find(|(v, _)| *v == value) {
+            entry.1 += count;
+        } else {
+            merged.push((value, count));
+        }
+    }
+    merged
+}
+
+/// Count rows per tag of a `StringList` column. A row counts once per distinct tag
+/// it holds; rows with an empty list count towards the null facet.
+fn count_list_values(
+    indices: &[u32],
+    col: &ColumnData,
+    intern: &StringInternTable,
+) -> Vec<(Option<FilterValue>, usize)> {
+    let mut counts: HashMap<Option<u32>, usize> = HashMap::new();
+    let mut seen: Vec<u32> = Vec::new();
+    for &idx in indices {
+        let tags = col.list_ids(idx as usize);
+        if tags.is_empty() {
+            *counts.entry(None).or_insert(0) += 1;
+            continue;
+        }
+        seen.clear();
+        for &id in tags {
+            if !seen.contains(&id) {
+                seen.push(id);
+                *counts.entry(Some(id)).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(id, count)| {
+            let value = id.map(|id| FilterValue::String(intern.resolve(id).to_string()));
+            (value, count)
+        })
+        .collect()
+}
+
+fn compare_facet_values(a: Option<&FilterValue>, b: Option<&FilterValue>) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    match (a, b) {
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (Some(FilterValue::Float64(x)), Some(FilterValue::Float64(y))) => x.total_cmp(y),
+        (Some(FilterValue::Bool(x)), Some(FilterValue::Bool(y))) => x.cmp(y),
+        (Some(FilterValue::String(x)), Some(FilterValue::String(y))) => x.cmp(y),
+        // Both null, or mixed types (cannot occur within one column)
+        _ => Ordering::Equal,
+    }
+}
+
+fn compare_columnar(
+    store: &ColumnarStore,
+    col_idx: usize,
+    row_a: usize,
+    row_b: usize,
+) -> std::cmp::Ordering {
+    match store.sort_column(col_idx) {
+        Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => {
+            let a = v[row_a];
+            let b = v[row_b];
+            // NaN handling: NaN is "less than" any real value
+            match (a.is_nan(), b.is_nan()) {
+                (true, true) => std::cmp::Ordering::Equal,
+                (true, false) => std::cmp::Ordering::Less,
+                (false, true) => std::cmp::Ordering::Greater,
+                (false, false) => a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal),
+            }
+        }
+        Some(ColumnData::Strings { ids, intern }) => {
+            if let Some(ranks) = store
+                .string_ranks
+                .get(&col_idx)
+                .filter(|r| r.len() == intern.len())
+            {
+                return ranks[ids[row_a] as usize].cmp(&ranks[ids[row_b] as usize]);
+            }
+            let a = intern.resolve(ids[row_a]);
+            let b = intern.resolve(ids[row_b]);
+            a.cmp(b)
+        }
+        // Lists compare tag by tag; an empty (null) list sorts first.
+        Some(col @ ColumnData::StringList { intern, .. }) => col
+            .list_ids(row_a)
+            .iter()
+            .map(|&id| intern.resolve(id))
+            .cmp(col.list_ids(row_b).iter().map(|&id| intern.resolve(id))),
+        // Series compare value by value, the same way
+        Some(col @ ColumnData::Float64List { .. }) => {
+            let (a, b) = (col.series(row_a), col.series(row_b));
+            a.iter()
+                .zip(b)
+                .map(|(x, y)| x.total_cmp(y))
+                .find(|o| o.is_ne())
+                .unwrap_or_else(|| a.len().cmp(&b.len()))
+        }
+        None => std::cmp::Ordering::Equal,
+    }
+}
+
+/// Rank intern IDs for a custom sort order: null first, then listed values in
+/// list order, then unlisted values by `base` ranks (or byte-wise without them).
+fn custom_order_ranks(
+    order: &[String],
+    intern: &StringInternTable,
+    base: Option<&[u32]>,
+) -> Vec<u32> {
+    let listed = order.len() as u32;
+    let mut position = vec![None; intern.len()];
+    for (pos, value) in order.iter().enumerate() {
+        if let Some(id) = intern.get(value) {
+            position[id as usize].get_or_insert(pos as u32 + 1);
+        }
+    }
+    if let Some(null_id) = intern.get("") {
+        position[null_id as usize] = Some(0);
+    }
+
+    let mut unlisted: Vec<u32> = (0..intern.len() as u32)
+        .filter(|&id| position[id as usize].is_none())
+        .collect();
+    match base {
+        Some(base) => unlisted.sort_by_key(|&id| base[id as usize]),
+        None => unlisted.sort_by(|&a, &b| intern.resolve(a).cmp(intern.resolve(b))),
+    }
+    let mut rank = listed;
+    for (i, &id) in unlisted.iter().enumerate() {
+        let tied =
+            i > 0 && base.is_some_and(|base| base[unlisted[i - 1] as usize] == base[id as usize]);
+        if !tied {
+            rank += 1;
+        }
+        position[id as usize] = Some(rank);
+    }
+    position
+        .into_iter()
+        .map(Option::unwrap_or_default)
+        .collect()
+}
+
+/// Replace list rows `offset..offset + new_offsets.len() - 1` (clamped to the
+/// column) with the rows of `new_offsets`/`new_items`, mapped by `map`.
+fn splice_list_rows<S, T>(
+    offsets: &mut [u32],
+    values: &mut Vec<T>,
+    offset: usize,
+    new_offsets: &[u32],
+    new_items: &[S],
+    map: impl FnMut(&S) -> T,
+) {
+    let row_count = offsets.len() - 1;
+    let end = (offset + new_offsets.len().saturating_sub(1)).min(row_count);
+    if offset >= end {
+        return;
+    }
+    let rows = end - offset;
+
+    let start = offsets[offset] as usize;
+    let old_end = offsets[end] as usize;
+    let local_end = new_offsets[rows] as usize;
+    let local_start = new_offsets[0] as usize;
+    values.splice(
+        start..old_end,
+        new_items[local_start..local_end].iter().map(map),
+    );
+
+    // Rewrite offsets for the appended rows, then shift the rows after them.
+    for i in 1..=rows {
+        offsets[offset + i] = (start + (new_offsets[i] - new_offsets[0]) as usize) as u32;
+    }
+    let new_end = offsets[end];
+    for o in &mut offsets[end + 1..] {
+        *o = *o - old_end as u32 + new_end;
+    }
+}
+
+/// Break `text` into lines `text_width` px wide: by `measure` widths when set, else
+/// `char_width` px per character.
+fn wrap_text(
+    measure: Option<&mut TextWidthMeasure>,
+    char_width: f64,
+    text: &str,
+    text_width: f64,
+    out: &mut Vec<std::ops::Range<usize>>,
+) {
+    if let Some(measure) = measure {
+        wrap_lines(text, |line| measure.call(line) <= text_width, out);
+    } else {
+        let max_chars = (text_width / char_width.max(1.0)).floor().max(1.0);
+        wrap_lines(text, |line| line.chars().count() as f64 <= max_chars, out);
+    }
+}
+
+/// Whether a cell holds the column type's null sentinel (NaN, empty string, empty list).
+fn is_null_cell(store: &ColumnarStore, col_idx: usize, row: usize) -> bool {
+    match store.sort_column(col_idx) {
+        Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => v[row].is_nan(),
+        Some(ColumnData::Strings { ids, intern }) => intern.resolve(ids[row]).is_empty(),
+        Some(col @ ColumnData::StringList { .. }) => col.list_ids(row).is_empty(),
+        Some(col @ ColumnData::Float64List { .. }) => col.series(row).is_empty(),
+        None => false,
+    }
+}
+
+/// Natural-order comparison for string columns; other columns (and columns with a
+/// custom sort order) compare as usual.
+fn compare_natural(
+    store: &ColumnarStore,
+    col_idx: usize,
+    row_a: usize,
+    row_b: usize,
+) -> std::cmp::Ordering {
+    match store.sort_column(col_idx) {
+        Some(ColumnData::Strings { ids, intern })
+            if !store.custom_sort_orders.contains_key(&col_idx) =>
+        {
+            natural_cmp(intern.resolve(ids[row_a]), intern.resolve(ids[row_b]))
+        }
+        _ => compare_columnar(store, col_idx, row_a, row_b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::AutoHeightColumn;
+
+    // ── Direct column setter tests ────────────────────────────────────
+
+    #[test]
+    fn init_and_set_columns_direct() {
+        let mut store = ColumnarStore::new();
+        store.init(3, 4);
+
+        assert_eq!(store.row_count, 4);
+        assert_eq!(store.data.len(), 3);
+        assert_eq!(store.generation, 1);
+    }
+
+    #[test]
+    fn set_column_float64_direct() {
+        let mut store = ColumnarStore::new();
+        store.init(3, 4);
+        store.set_column_float64(1, &[30.0, 25.0, 35.0, 28.0]);
+
+        assert_eq!(store.column_type(1), Some(ColumnType::Float64));
+        if let ColumnData::Float64(v) = &store.data[1] {
+            assert_eq!(v, &[30.0, 25.0, 35.0, 28.0]);
+        } else {
+            panic!("expected Float64");
+        }
+    }
+
+    #[test]
+    fn set_column_bool_direct() {
+        let mut store = ColumnarStore::new();
+        store.init(3, 4);
+        store.set_column_bool(2, &[1.0, 0.0, 1.0, f64::NAN]);
+
+        assert_eq!(store.column_type(2), Some(ColumnType::Bool));
+        if let ColumnData::Bool(v) = &store.data[2] {
+            assert!((v[0] - 1.0).abs() < f64::EPSILON);
+            assert!((v[1] - 0.0).abs() < f64::EPSILON);
+            assert!(v[3].is_nan());
+        } else {
+            panic!("expected Bool");
+        }
+    }
+
+    #[test]
+    fn set_column_strings_direct() {
+        let mut store = ColumnarStore::new();
+        store.init(3, 4);
+        let unique = vec![
+            String::new(),
+            "Alice".to_string(),
+            "Bob".to_string(),
+            "Charlie".to_string(),
+            "Alice Smith".to_string(),
+        ];
+        store.set_column_strings(0, &unique, &[1, 2, 3, 4]);
+
+        assert_eq!(store.column_type(0), Some(ColumnType::String));
+        if let ColumnData::Strings { ids, intern } = &store.data[0] {
+            assert_eq!(intern.resolve(ids[0]), "Alice");
+            assert_eq!(intern.resolve(ids[1]), "Bob");
+            assert_eq!(intern.resolve(ids[2]), "Charlie");
+            assert_eq!(intern.resolve(ids[3]), "Alice Smith");
+        } else {
+            panic!("expected Strings");
+        }
+    }
+
+    #[test]
+    fn direct_ingestion_roundtrip_with_sort() {
+        let mut store = ColumnarStore::new();
+        store.init(3, 4);
+        let unique = vec![
+            String::new(),
+            "Alice".to_string(),
+            "Bob".to_string(),
+            "Charlie".to_string(),
+            "Alice Smith".to_string(),
+        ];
+        store.set_column_strings(0, &unique, &[1, 2, 3, 4]);
+        store.set_column_float64(1, &[30.0, 25.0, 35.0, 28.0]);
+        store.set_column_bool(2, &[1.0, 0.0, 1.0, f64::NAN]);
+        store.finalize();
+
+        store.set_sort(vec![SortConfig {
+            column_index: 1,
+            direction: SortDirection::Ascending,
+            ..SortConfig::default()
+        }]);
+        store.rebuild_view();
+
+        // Bob(25)=1, Alice Smith(28)=3, Alice(30)=0, Charlie(35)=2
+        assert_eq!(store.view_indices(), &[1, 3, 0, 2]);
+    }
+
+    #[test]
+    fn sort_columnar_strings() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 4);
+        let unique = vec![
+            String::new(),
+            "Alice".into(),
+            "Bob".into(),
+            "Charlie".into(),
+            "Alice Smith".into(),
+        ];
+        store.set_column_strings(0, &unique, &[1, 2, 3, 4]);
+        store.finalize();
+
+        let mut indices: Vec<u32> = (0..4).collect();
+        sort_indices_columnar(
+            &mut indices,
+            &store,
+            &[SortConfig {
+                column_index: 0,
+                direction: SortDirection::Ascending,
+                ..SortConfig::default()
+            }],
+        );
+        // Alice, Alice Smith, Bob, Charlie
+        assert_eq!(indices, vec![0, 3, 1, 2]);
+    }
+
+    #[test]
+    fn sort_null_order_independent_of_direction() {
+        let store = make_store_with_nulls();
+        let sorted = |col, direction, nulls| {
+            let mut indices: Vec<u32> = (0..4).collect();
+            sort_indices_columnar(
+                &mut indices,
+                &store,
+                &[SortConfig {
+                    column_index: col,
+                    direction,
+                    nulls,
+                    ..SortConfig::default()
+                }],
+            );
+            indices
+        };
+        // Floats: 1, null, 3, null
+        let (asc, desc) = (SortDirection::Ascending, SortDirection::Descending);
+        assert_eq!(sorted(1, asc, None), vec![1, 3, 0, 2]);
+        assert_eq!(sorted(1, desc, None), vec![2, 0, 1, 3]);
+        assert_eq!(sorted(1, asc, Some(NullOrder::Last)), vec![0, 2, 1, 3]);
+        assert_eq!(sorted(1, desc, Some(NullOrder::First)), vec![1, 3, 2, 0]);
+        // Strings: Alice, null, blank, Bob
+        assert_eq!(sorted(0, asc, Some(NullOrder::Last)), vec![2, 0, 3, 1]);
+        assert_eq!(sorted(0, desc, Some(NullOrder::Last)), vec![3, 0, 2, 1]);
+    }
+
+    #[test]
+    fn custom_sort_order_ranks_listed_values_first() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 6);
+        let unique = vec![
+            String::new(),
+            "Done".into(),
+            "Open".into(),
+            "Blocked".into(),
+            "In Progress".into(),
+            "Archived".into(),
+        ];
+        store.set_column_strings(0, &unique, &[1, 2, 0, 3, 4, 5]);
+        store.finalize();
+        store.set_sort(vec![SortConfig {
+            column_index: 0,
+            direction: SortDirection::Ascending,
+            ..SortConfig::default()
+        }]);
+        store.set_custom_sort_order(
+            0,
+            Some(vec!["Open".into(), "In Progress".into(), "Done".into()]),
+        );
+        store.rebuild_view();
+        // null, Open, In Progress, Done, then unlisted byte-wise: Archived, Blocked
+        assert_eq!(store.view_indices(), &[2, 1, 4, 0, 5, 3]);
+
+        // Ranks are rebuilt after appended values extend the intern table
+        store.begin_append(1);
+        store.append_column_strings(0, 6, &["Backlog".to_string()], &[0]);
+        store.finalize_append();
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 1, 4, 0, 5, 6, 3]);
+
+        store.set_custom_sort_order(0, None);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 5, 6, 3, 0, 4, 1]);
+    }
+
+    #[test]
+    fn sort_columnar_strings_natural() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 4);
+        let unique = vec![
+            String::new(),
+            "v1.10".into(),
+            "v1.9".into(),
+            "file10".into(),
+            "file2".into(),
+        ];
+        store.set_column_strings(0, &unique, &[1, 2, 3, 4]);
+        store.finalize();
+
+        let mut config = SortConfig {
+            column_index: 0,
+            direction: SortDirection::Ascending,
+            ..SortConfig::default()
+        };
+        let mut indices: Vec<u32> = (0..4).collect();
+        sort_indices_columnar(&mut indices, &store, std::slice::from_ref(&config));
+        // file10, file2, v1.10, v1.9
+        assert_eq!(indices, vec![2, 3, 0, 1]);
+
+        config.natural = true;
+        sort_indices_columnar(&mut indices, &store, &[config]);
+        // file2, file10, v1.9, v1.10
+        assert_eq!(indices, vec![3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn rebuild_view_idempotent() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 4);
+        store.set_column_float64(0, &[30.0, 25.0, 35.0, 28.0]);
+        store.finalize();
+        store.set_sort(vec![SortConfig {
+            column_index: 0,
+            direction: SortDirection::Ascending,
+            ..SortConfig::default()
+        }]);
+
+        store.rebuild_view();
+        let first = store.view_indices().to_vec();
+        store.rebuild_view(); // should be no-op (not dirty)
+        let second = store.view_indices().to_vec();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn scroll_config_stored() {
+        let mut store = ColumnarStore::new();
+        store.set_scroll_config(40.0, 800.0, 3);
+        assert!((store.row_height() - 40.0).abs() < f64::EPSILON);
+        assert!((store.viewport_height() - 800.0).abs() < f64::EPSILON);
+        assert_eq!(store.overscan(), 3);
+    }
+
+    #[test]
+    fn generation_increments() {
+        let mut store = ColumnarStore::new();
+        assert_eq!(store.generation, 0);
+
+        store.init(1, 2);
+        assert_eq!(store.generation, 1);
+
+        store.init(1, 3);
+        assert_eq!(store.generation, 2);
+    }
+
+    #[test]
+    fn view_version_tracks_rebuilds() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 3);
+        store.set_column_float64(0, &[1.0, 2.0, 3.0]);
+        store.rebuild_view();
+        let v1 = store.view_version();
+
+        // Nothing changed: no bump
+        store.rebuild_view();
+        assert_eq!(store.view_version(), v1);
+
+        store.update_float64(0, 1, 5.0);
+        store.rebuild_view();
+        let v2 = store.view_version();
+        assert!(v2 > v1);
+
+        store.set_sort(Vec::new());
+        store.rebuild_view();
+        assert!(store.view_version() > v2);
+    }
+
+    #[test]
+    fn get_float64_ptr_accessible() {
+        let mut store = ColumnarStore::new();
+        store.init(2, 4);
+        store.set_column_float64(0, &[30.0, 25.0, 35.0, 28.0]);
+
+        let (ptr, len) = store.get_float64_ptr(0).expect("should be Float64");
+        assert_eq!(len, 4);
+        assert!(!ptr.is_null());
+    }
+
+    #[test]
+    fn get_float64_ptr_returns_none_for_string_column() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 2);
+        store.set_column_strings(0, &[String::new(), "A".into()], &[0, 1]);
+
+        assert!(store.get_float64_ptr(0).is_none());
+    }
+
+    #[test]
+    fn get_float64_ptr_works_for_bool_column() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 4);
+        store.set_column_bool(0, &[1.0, 0.0, 1.0, f64::NAN]);
+
+        let result = store.get_float64_ptr(0);
+        assert!(result.is_some());
+        let (ptr, len) = result.unwrap();
+        assert_eq!(len, 4);
+        assert!(!ptr.is_null());
+    }
+
+    // ── StringInternTable coverage ───────────────────────────────────
+
+    #[test]
+    fn intern_cache_hit_returns_same_id() {
+        let mut intern = StringInternTable::new();
+        let id1 = intern.intern("hello");
+        let id2 = intern.intern("hello");
+        assert_eq!(id1, id2);
+        assert_eq!(intern.len(), 1);
+    }
+
+    #[test]
+    fn intern_table_len_and_is_empty() {
+        let mut intern = StringInternTable::new();
+        assert!(intern.is_empty());
+        assert_eq!(intern.len(), 0);
+
+        intern.intern("a");
+        assert!(!intern.is_empty());
+        assert_eq!(intern.len(), 1);
+
+        intern.intern("b");
+        assert_eq!(intern.len(), 2);
+    }
+
+    #[test]
+    fn intern_table_default() {
+        let intern = StringInternTable::default();
+        assert!(intern.is_empty());
+        assert_eq!(intern.len(), 0);
+    }
+
+    // ── ColumnarStore Default impl ───────────────────────────────────
+
+    #[test]
+    fn columnar_store_default() {
+        let store = ColumnarStore::default();
+        assert_eq!(store.row_count, 0);
+        assert_eq!(store.generation, 0);
+        assert!(store.data.is_empty());
+    }
+
+    // ── compare_columnar NaN handling ────────────────────────────────
+
+    #[test]
+    fn compare_columnar_nan_both() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 3);
+        store.set_column_float64(0, &[f64::NAN, f64::NAN, 5.0]);
+
+        let result = compare_columnar(&store, 0, 0, 1);
+        assert_eq!(result, std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn compare_columnar_nan_left_only() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 2);
+        store.set_column_float64(0, &[f64::NAN, 5.0]);
+
+        let result = compare_columnar(&store, 0, 0, 1);
+        assert_eq!(result, std::cmp::Ordering::Less);
+    }
+
+    #[test]
+    fn compare_columnar_nan_right_only() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 2);
+        store.set_column_float64(0, &[5.0, f64::NAN]);
+
+        let result = compare_columnar(&store, 0, 0, 1);
+        assert_eq!(result, std::cmp::Ordering::Greater);
+    }
+
+    #[test]
+    fn sort_descending_exercises_nan_branches() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 3);
+        store.set_column_float64(0, &[f64::NAN, 10.0, 5.0]);
+
+        let mut indices: Vec<u32> = vec![0, 1, 2];
+        sort_indices_columnar(
+            &mut indices,
+            &store,
+            &[SortConfig {
+                column_index: 0,
+                direction: SortDirection::Descending,
+                ..SortConfig::default()
+            }],
+        );
+        // Descending: 10.0(1), 5.0(2), NaN(0)
+        assert_eq!(indices, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn compare_columnar_none_column() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 2);
+        store.set_column_float64(0, &[1.0, 2.0]);
+
+        let result = compare_columnar(&store, 99, 0, 1);
+        assert_eq!(result, std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn sort_multi_key_equal_fallthrough() {
+        let mut store = ColumnarStore::new();
+        store.init(2, 3);
+        // All same value in column 0
+        store.set_column_float64(0, &[30.0, 30.0, 30.0]);
+        // Different values in column 1
+        store.set_column_strings(
+            1,
+            &[
+                String::new(),
+                "Charlie".into(),
+                "Alice".into(),
+                "Bob".into(),
+            ],
+            &[1, 2, 3],
+        );
+
+        let mut indices: Vec<u32> = vec![0, 1, 2];
+        sort_indices_columnar(
+            &mut indices,
+            &store,
+            &[
+                SortConfig {
+                    column_index: 0,
+                    direction: SortDirection::Ascending,
+                    ..SortConfig::default()
+                },
+                SortConfig {
+                    column_index: 1,
+                    direction: SortDirection::Ascending,
+                    ..SortConfig::default()
+                },
+            ],
+        );
+        // All ages equal (30), so sort by name: Alice(1), Bob(2), Charlie(0)
+        assert_eq!(indices, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn sort_columnar_empty_configs_no_change() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 4);
+        store.set_column_float64(0, &[30.0, 25.0, 35.0, 28.0]);
+
+        let mut indices: Vec<u32> = (0..4).collect();
+        sort_indices_columnar(&mut indices, &store, &[]);
+        assert_eq!(indices, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn sort_columnar_all_equal_returns_equal() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 3);
+        store.set_column_float64(0, &[42.0, 42.0, 42.0]);
+
+        let mut indices: Vec<u32> = (0..3).collect();
+        sort_indices_columnar(
+            &mut indices,
+            &store,
+            &[SortConfig {
+                column_index: 0,
+                direction: SortDirection::Ascending,
+                ..SortConfig::default()
+            }],
+        );
+        assert_eq!(indices, vec![0, 1, 2]);
+    }
+
+    // ── Column filter tests ─────────────────────────────────────────
+
+    fn make_store_for_filter() -> ColumnarStore {
+        let mut store = ColumnarStore::new();
+        store.init(3, 4);
+        let unique = vec![
+            String::new(),
+            "Alice".to_string(),
+            "Bob".to_string(),
+            "Charlie".to_string(),
+            "Dave".to_string(),
+        ];
+        store.set_column_strings(0, &unique, &[1, 2, 3, 4]); // names
+        store.set_column_float64(1, &[30.0, 25.0, 35.0, 28.0]); // ages
+        store.set_column_bool(2, &[1.0, 0.0, 1.0, f64::NAN]); // active
+        store.finalize();
+        store
+    }
+
+    #[test]
+    fn filter_float64_eq() {
+        let store = make_store_for_filter();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            &[ColumnFilter::new(
+                1,
+                FilterOp::Eq,
+                FilterValue::Float64(30.0),
+            )],
+        );
+        assert_eq!(indices, vec![0]); // Alice=30
+    }
+
+    #[test]
+    fn filter_float64_gt() {
+        let store = make_store_for_filter();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            &[ColumnFilter::new(
+                1,
+                FilterOp::Gt,
+                FilterValue::Float64(28.0),
+            )],
+        );
+        assert_eq!(indices, vec![0, 2]); // Alice=30, Charlie=35
+    }
+
+    #[test]
+    fn filter_float64_lte() {
+        let store = make_store_for_filter();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            &[ColumnFilter::new(
+                1,
+                FilterOp::Lte,
+                FilterValue::Float64(28.0),
+            )],
+        );
+        assert_eq!(indices, vec![1, 3]); // Bob=25, Dave=28
+    }
+
+    #[test]
+    fn filter_float64_neq() {
+        let store = make_store_for_filter();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            &[ColumnFilter::new(
+                1,
+                FilterOp::Neq,
+                FilterValue::Float64(30.0),
+            )],
+        );
+        assert_eq!(indices, vec![1, 2, 3]); // all except Alice
+    }
+
+    #[test]
+    fn filter_float64_gte_lt() {
+        let store = make_store_for_filter();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            &[ColumnFilter::new(
+                1,
+                FilterOp::Gte,
+                FilterValue::Float64(28.0),
+            )],
+        );
+        assert_eq!(indices, vec![0, 2, 3]); // Alice=30, Charlie=35, Dave=28
+
+        let mut indices2: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices2,
+            &store,
+            &[ColumnFilter::new(
+                1,
+                FilterOp::Lt,
+                FilterValue::Float64(28.0),
+            )],
+        );
+        assert_eq!(indices2, vec![1]); // Bob=25
+    }
+
+    #[test]
+    fn filter_string_eq() {
+        let store = make_store_for_filter();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            &[ColumnFilter::new(
+                0,
+                FilterOp::Eq,
+                FilterValue::String("Bob".to_string()),
+            )],
+        );
+        assert_eq!(indices, vec![1]);
+    }
+
+    #[test]
+    fn filter_string_contains() {
+        let store = make_store_for_filter();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            // Alice, Charlie
+            &[ColumnFilter::new(
+                0,
+                FilterOp::Contains,
+                FilterValue::String("li".to_string()),
+            )],
+        );
+        assert_eq!(indices, vec![0, 2]);
+    }
+
+    #[test]
+    fn filter_string_starts_with() {
+        let store = make_store_for_filter();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            &[ColumnFilter::new(
+                0,
+                FilterOp::StartsWith,
+                FilterValue::String("ch".to_string()),
+            )],
+        );
+        assert_eq!(indices, vec![2]); // Charlie
+    }
+
+    #[test]
+    fn filter_string_ends_with() {
+        let store = make_store_for_filter();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            &[ColumnFilter::new(
+                0,
+                FilterOp::EndsWith,
+                FilterValue::String("ve".to_string()),
+            )],
+        );
+        assert_eq!(indices, vec![3]); // Dave
+    }
+
+    #[test]
+    fn filter_bool_eq() {
+        let store = make_store_for_filter();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            &[ColumnFilter::new(2, FilterOp::Eq, FilterValue::Bool(true))],
+        );
+        assert_eq!(indices, vec![0, 2]); // Alice, Charlie (NaN excluded)
+    }
+
+    #[test]
+    fn filter_bool_neq() {
+        let store = make_store_for_filter();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            &[ColumnFilter::new(2, FilterOp::Neq, FilterValue::Bool(true))],
+        );
+        assert_eq!(indices, vec![1]); // Bob=false (NaN excluded)
+    }
+
+    #[test]
+    fn filter_nan_always_excluded() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 3);
+        store.set_column_float64(0, &[f64::NAN, 5.0, f64::NAN]);
+        store.finalize();
+
+        let mut indices: Vec<u32> = (0..3).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            &[ColumnFilter::new(
+                0,
+                FilterOp::Gte,
+                FilterValue::Float64(0.0),
+            )],
+        );
+        assert_eq!(indices, vec![1]); // only row 1 (5.0)
+    }
+
+    #[test]
+    fn filter_multiple_columns_and_logic() {
+        let store = make_store_for_filter();
+        let mut indices: Vec<u32> = (0..4).collect();
+        // name contains "li" AND age > 28
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            &[
+                ColumnFilter::new(0, FilterOp::Contains, FilterValue::String("li".to_string())),
+                ColumnFilter::new(1, FilterOp::Gt, FilterValue::Float64(28.0)),
+            ],
+        );
+        assert_eq!(indices, vec![0, 2]); // Alice(30) and Charlie(35) both contain "li" and > 28
+    }
+
+    #[test]
+    fn filter_empty_filters_no_change() {
+        let store = make_store_for_filter();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(&mut indices, &store, &[]);
+        assert_eq!(indices, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn filter_invalid_column_excludes_all() {
+        let store = make_store_for_filter();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            &[ColumnFilter::new(
+                99,
+                FilterOp::Eq,
+                FilterValue::Float64(30.0),
+            )],
+        );
+        assert_eq!(indices, Vec::<u32>::new());
+    }
+
+    #[test]
+    fn filter_string_ops_on_float_col_returns_false() {
+        let store = make_store_for_filter();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            // float64 column
+            &[ColumnFilter::new(
+                1,
+                FilterOp::Contains,
+                FilterValue::Float64(30.0),
+            )],
+        );
+        assert_eq!(indices, Vec::<u32>::new());
+    }
+
+    // ── Null-aware filter tests ─────────────────────────────────────
+
+    fn make_store_with_nulls() -> ColumnarStore {
+        let mut store = ColumnarStore::new();
+        store.init(3, 4);
+        let unique = vec![
+            String::new(),
+            "Alice".to_string(),
+            "  ".to_string(),
+            "Bob".to_string(),
+        ];
+        store.set_column_strings(0, &unique, &[1, 0, 2, 3]); // Alice, null, blank, Bob
+        store.set_column_float64(1, &[1.0, f64::NAN, 3.0, f64::NAN]);
+        store.set_column_bool(2, &[f64::NAN, 1.0, 0.0, 1.0]);
+        store.finalize();
+        store
+    }
+
+    fn filter_rows(store: &ColumnarStore, column_index: usize, op: FilterOp) -> Vec<u32> {
+        let mut indices: Vec<u32> = (0..store.row_count as u32).collect();
+        filter_indices_columnar(
+            &mut indices,
+            store,
+            &[ColumnFilter::new(
+                column_index,
+                op,
+                FilterValue::String(String::new()),
+            )],
+        );
+        indices
+    }
+
+    #[test]
+    fn filter_is_null_per_column_type() {
+        let store = make_store_with_nulls();
+        assert_eq!(filter_rows(&store, 0, FilterOp::IsNull), vec![1]);
+        assert_eq!(filter_rows(&store, 1, FilterOp::IsNull), vec![1, 3]);
+        assert_eq!(filter_rows(&store, 2, FilterOp::IsNull), vec![0]);
+    }
+
+    #[test]
+    fn filter_is_not_null_per_column_type() {
+        let store = make_store_with_nulls();
+        assert_eq!(filter_rows(&store, 0, FilterOp::IsNotNull), vec![0, 2, 3]);
+        assert_eq!(filter_rows(&store, 1, FilterOp::IsNotNull), vec![0, 2]);
+        assert_eq!(filter_rows(&store, 2, FilterOp::IsNotNull), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn filter_is_blank_includes_whitespace_strings() {
+        let store = make_store_with_nulls();
+        assert_eq!(filter_rows(&store, 0, FilterOp::IsBlank), vec![1, 2]);
+        assert_eq!(filter_rows(&store, 1, FilterOp::IsBlank), vec![1, 3]);
+        assert!(filter_rows(&store, 99, FilterOp::IsNull).is_empty());
+    }
+
+    #[test]
+    fn filter_string_neq_excludes_nulls() {
+        let store = make_store_with_nulls();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            &[ColumnFilter::new(
+                0,
+                FilterOp::Neq,
+                FilterValue::String("Alice".to_string()),
+            )],
+        );
+        assert_eq!(indices, vec![2, 3]); // blank + Bob; null row excluded
+    }
+
+    #[test]
+    fn null_ops_on_wrong_value_type_still_evaluate() {
+        let store = make_store_with_nulls();
+        let mut indices: Vec<u32> = (0..4).collect();
+        filter_indices_columnar(
+            &mut indices,
+            &store,
+            &[ColumnFilter::new(
+                1,
+                FilterOp::IsNull,
+                FilterValue::Float64(0.0),
+            )],
+        );
+        assert_eq!(indices, vec![1, 3]);
+    }
+
+    // ── Formatted filter tests ──────────────────────────────────────
+
+    fn make_store_with_dates() -> ColumnarStore {
+        let mut store = ColumnarStore::new();
+        store.init(2, 4);
+        // 2024-01-15, 2024-02-01, 2023-01-20, null
+        store.set_column_float64(
+            0,
+            &[
+                1_705_276_800_000.0,
+                1_706_745_600_000.0,
+                1_674_172_800_000.0,
+                f64::NAN,
+            ],
+        );
+        store.set_column_float64(1, &[1_200.0, 950.0, 1_249.0, 2_000_000.0]);
+        store.finalize();
+        store
+    }
+
+    fn formatted_filter(column_index: usize, op: FilterOp, text: &str) -> ColumnFilter {
+        ColumnFilter {
+            column_index,
+            op,
+            value: FilterValue::String(text.to_string()),
+            formatted: true,
+        }
+    }
+
+    #[test]
+    fn formatted_filter_matches_date_text() {
+        let mut store = make_store_with_dates();
+        store.set_column_format(0, Some(FormatSpec::parse("MMM YYYY").unwrap()));
+        store.set_column_filters(vec![formatted_filter(0, FilterOp::Contains, "jan")]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2]);
+
+        store.set_column_filters(vec![formatted_filter(0, FilterOp::Eq, "Jan 2024")]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0]);
+    }
+
+    #[test]
+    fn formatted_filter_matches_compact_numbers() {
+        let mut store = make_store_with_dates();
+        store.set_column_format(1, Some(FormatSpec::Compact));
+        store.set_column_filters(vec![formatted_filter(1, FilterOp::Eq, "1.2k")]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2]);
+    }
+
+    #[test]
+    fn formatted_filter_without_format_uses_raw_value() {
+        let mut store = make_store_with_dates();
+        store.set_column_filters(vec![formatted_filter(0, FilterOp::Contains, "jan")]);
+        store.rebuild_view();
+        assert!(store.view_indices().is_empty()); // string target never matches raw numbers
+    }
+
+    #[test]
+    fn changing_format_invalidates_cached_filter() {
+        let mut store = make_store_with_dates();
+        store.set_column_format(0, Some(FormatSpec::parse("MMM YYYY").unwrap()));
+        store.set_column_filters(vec![formatted_filter(0, FilterOp::Contains, "2024")]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1]);
+
+        store.set_column_format(0, Some(FormatSpec::parse("MMM").unwrap()));
+        store.rebuild_view();
+        assert!(store.view_indices().is_empty());
+        assert!(store.column_format(0).is_some());
+
+        store.set_column_format(0, None);
+        assert!(store.column_format(0).is_none());
+    }
+
+    // ── Prefix index tests ──────────────────────────────────────────
+
+    #[test]
+    fn prefix_index_matches_scan_results() {
+        let mut store = make_store_for_filter();
+        for (op, target) in [
+            (FilterOp::StartsWith, "ch"),
+            (FilterOp::StartsWith, "A"),
+            (FilterOp::EndsWith, "E"),
+            (FilterOp::EndsWith, "ob"),
+        ] {
+            let filter = ColumnFilter::new(0, op, FilterValue::String(target.to_string()));
+            store.set_prefix_index(0, false);
+            let scanned = filter_bitmap_columnar(&store, &filter);
+            store.set_prefix_index(0, true);
+            assert!(indexed_filter_bitmap(&store, &filter).is_some());
+            assert_eq!(filter_bitmap_columnar(&store, &filter), scanned);
+        }
+    }
+
+    #[test]
+    fn prefix_index_refreshed_after_append() {
+        let mut store = make_store_for_filter();
+        store.set_prefix_index(0, true);
+        assert!(store.has_prefix_index(0));
+
+        store.begin_append(1);
+        store.append_column_strings(0, 4, &[String::new(), "Charlotte".to_string()], &[1]);
+        // Stale until finalized: falls back to scanning.
+        let filter = ColumnFilter::new(
+            0,
+            FilterOp::StartsWith,
+            FilterValue::String("char".to_string()),
+        );
+        assert!(indexed_filter_bitmap(&store, &filter).is_none());
+        store.finalize_append();
+
+        store.set_column_filters(vec![filter]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 4]);
+    }
+
+    #[test]
+    fn prefix_index_ignored_for_non_string_columns() {
+        let mut store = make_store_for_filter();
+        store.set_prefix_index(1, true);
+        assert!(!store.has_prefix_index(1));
+        store.set_prefix_index(0, true);
+        store.set_prefix_index(0, false);
+        assert!(!store.has_prefix_index(0));
+    }
+
+    // ── String list column tests ────────────────────────────────────
+
+    fn make_store_with_tags() -> ColumnarStore {
+        let mut store = ColumnarStore::new();
+        store.init(1, 4);
+        let unique = vec!["red".to_string(), "green".to_string(), "blue".to_string()];
+        // [red, green], [blue], [], [green, blue, red]
+        store.set_column_string_list(0, &unique, &[0, 2, 3, 3, 6], &[0, 1, 2, 1, 2, 0]);
+        store.finalize();
+        store
+    }
+
+    fn matching_rows(store: &ColumnarStore, filter: &ColumnFilter) -> Vec<u32> {
+        filter_bitmap_columnar(store, filter).iter_ones().collect()
+    }
+
+    fn tag_filter(op: FilterOp, tags: &[&str]) -> ColumnFilter {
+        ColumnFilter::new(
+            0,
+            op,
+            FilterValue::StringList(tags.iter().map(ToString::to_string).collect()),
+        )
+    }
+
+    #[test]
+    fn string_list_contains_any() {
+        let store = make_store_with_tags();
+        assert_eq!(store.column_type(0), Some(ColumnType::StringList));
+        assert_eq!(
+            matching_rows(
+                &store,
+                &tag_filter(FilterOp::ContainsAny, &["blue", "pink"])
+            ),
+            vec![1, 3]
+        );
+        assert!(matching_rows(&store, &tag_filter(FilterOp::ContainsAny, &["pink"])).is_empty());
+    }
+
+    #[test]
+    fn string_list_contains_all() {
+        let store = make_store_with_tags();
+        assert_eq!(
+            matching_rows(
+                &store,
+                &tag_filter(FilterOp::ContainsAll, &["red", "green"])
+            ),
+            vec![0, 3]
+        );
+        assert!(
+            matching_rows(&store, &tag_filter(FilterOp::ContainsAll, &["red", "pink"])).is_empty()
+        );
+    }
+
+    #[test]
+    fn string_list_exact_tag_match() {
+        let store = make_store_with_tags();
+        // "re" is a substring of "red" and "green" but not a tag
+        assert!(matching_rows(&store, &tag_filter(FilterOp::ContainsAny, &["re"])).is_empty());
+        let contains =
+            ColumnFilter::new(0, FilterOp::Contains, FilterValue::String("re".to_string()));
+        assert_eq!(matching_rows(&store, &contains), vec![0, 3]);
+    }
+
+    #[test]
+    fn string_list_empty_is_null() {
+        let store = make_store_with_tags();
+        assert_eq!(filter_rows(&store, 0, FilterOp::IsNull), vec![2]);
+        let neq = ColumnFilter::new(0, FilterOp::Neq, FilterValue::String("blue".to_string()));
+        assert_eq!(matching_rows(&store, &neq), vec![0]);
+    }
+
+    #[test]
+    fn contains_any_on_string_column_is_membership() {
+        let store = make_store_for_filter();
+        assert_eq!(
+            matching_rows(&store, &tag_filter(FilterOp::ContainsAny, &["Bob", "Dave"])),
+            vec![1, 3]
+        );
+    }
+
+    #[test]
+    fn string_list_facets_count_each_tag() {
+        let mut store = make_store_with_tags();
+        let facets = store.filter_facets(0);
+        let pairs: Vec<(Option<&str>, usize)> = facets
+            .iter()
+            .map(|f| {
+                let v = match &f.value {
+                    Some(FilterValue::String(s)) => Some(s.as_str()),
+                    _ => None,
+                };
+                (v, f.count)
+            })
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (Some("blue"), 2),
+                (Some("green"), 2),
+                (Some("red"), 2),
+                (None, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn string_list_global_filter_and_sort() {
+        let mut store = make_store_with_tags();
+        store.set_global_filter(Some(GlobalFilter {
+            query: "BLU".to_string(),
+        }));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[1, 3]);
+        assert!(store.view_cell_matches_global_filter(1, 0));
+        assert!(!store.view_cell_matches_global_filter(2, 0));
+        assert!(!store.view_cell_matches_global_filter(0, 1));
+
+        store.set_global_filter(None);
+        store.set_sort(vec![SortConfig {
+            column_index: 0,
+            direction: SortDirection::Ascending,
+            ..SortConfig::default()
+        }]);
+        store.rebuild_view();
+        // [], [blue], [green, ...], [red, ...]
+        assert_eq!(store.view_indices(), &[2, 1, 3, 0]);
+    }
+
+    #[test]
+    fn string_list_append() {
+        let mut store = make_store_with_tags();
+        store.begin_append(2);
+        // [pink], [red, pink]
+        store.append_column_string_list(
+            0,
+            4,
+            &["pink".to_string(), "red".to_string()],
+            &[0, 1, 3],
+            &[0, 1, 0],
+        );
+        store.finalize_append();
+        assert_eq!(store.row_count, 6);
+        assert_eq!(
+            matching_rows(&store, &tag_filter(FilterOp::ContainsAny, &["pink"])),
+            vec![4, 5]
+        );
+        assert_eq!(
+            matching_rows(&store, &tag_filter(FilterOp::ContainsAll, &["red"])),
+            vec![0, 3, 5]
+        );
+    }
+
+    // ── Series column tests ─────────────────────────────────────────
+
+    #[test]
+    fn sparkline_data_follows_view() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 3);
+        // [1, 2, 3, 4], [], [10, 20]
+        store.set_column_float64_list(0, &[0, 4, 4, 6], &[1.0, 2.0, 3.0, 4.0, 10.0, 20.0]);
+        store.finalize();
+        assert_eq!(store.column_type(0), Some(ColumnType::Float64List));
+        assert!(is_null_cell(&store, 0, 1));
+        assert_eq!(store.cell_value(0, 0), None);
+
+        store.set_sort(vec![SortConfig {
+            column_index: 0,
+            direction: SortDirection::Descending,
+            ..SortConfig::default()
+        }]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 0, 1]);
+
+        let mut out = Vec::new();
+        store.sparkline_data(0..2, 0, 2, &mut out);
+        assert_eq!(out, vec![10.0, 20.0, 1.5, 3.5]);
+        out.clear();
+        store.sparkline_data(2..9, 0, 2, &mut out);
+        assert!(out.len() == 2 && out.iter().all(|v| v.is_nan()));
+
+        store.begin_append(1);
+        store.append_column_float64_list(0, 3, &[0, 1], &[7.0]);
+        store.finalize_append();
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 3, 0, 1]);
+        assert_eq!(store.data[0].series(3), &[7.0]);
+    }
+
+    // ── Relative date filter tests ──────────────────────────────────
+
+    fn within(range: RelativeDate) -> ColumnFilter {
+        ColumnFilter::new(0, FilterOp::Within, FilterValue::RelativeDate(range))
+    }
+
+    #[test]
+    fn within_uses_injected_now() {
+        // 2024-05-15T10:30:00Z
+        let now = 1_715_769_000_000.0;
+        let mut store = ColumnarStore::new();
+        store.init(1, 5);
+        // now, -3 days, -10 days, null, -40 days
+        store.set_column_float64(
+            0,
+            &[
+                now,
+                now - 259_200_000.0,
+                now - 864_000_000.0,
+                f64::NAN,
+                now - 3_456_000_000.0,
+            ],
+        );
+        store.finalize();
+        store.set_time_reference(now, 0);
+
+        store.set_column_filters(vec![within(RelativeDate::LastDays(7))]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1]);
+
+        store.set_column_filters(vec![within(RelativeDate::ThisMonth)]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1, 2]);
+
+        // Moving "now" forward 30 days invalidates the cached result
+        store.set_time_reference(now + 2_592_000_000.0, 0);
+        store.rebuild_view();
+        assert!(store.view_indices().is_empty());
+        store.set_column_filters(vec![within(RelativeDate::LastMonth)]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1, 2]);
+    }
+
+    #[test]
+    fn within_ignores_non_numeric_columns() {
+        let store = make_store_for_filter();
+        let filter = ColumnFilter {
+            column_index: 0,
+            ..within(RelativeDate::Today)
+        };
+        assert_eq!(filter_bitmap_columnar(&store, &filter).count_ones(), 0);
+    }
+
+    // ── View stats tests ────────────────────────────────────────────
+
+    thread_local! {
+        static FAKE_CLOCK: std::cell::Cell<f64> = const { std::cell::Cell::new(0.0) };
+    }
+
+    /// Advances 0.5 ms per call.
+    fn fake_clock() -> f64 {
+        FAKE_CLOCK.with(|c| {
+            let t = c.get() + 0.5;
+            c.set(t);
+            t
+        })
+    }
+
+    #[test]
+    fn view_stats_per_condition() {
+        let mut store = make_store_for_filter();
+        store.set_clock(Some(fake_clock));
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Gt,
+            FilterValue::Float64(26.0),
+        )]);
+        store.set_global_filter(Some(GlobalFilter {
+            query: "a".to_string(),
+        }));
+        store.set_custom_filter(0, Some(Box::new(|row, _| row != 0)));
+        store.rebuild_view();
+
+        let stats = store.last_view_stats();
+        assert_eq!(stats.conditions.len(), 3);
+        let column = &stats.conditions[0];
+        assert_eq!(column.kind, ConditionKind::Column(FilterOp::Gt));
+        assert_eq!(column.column_index, Some(1));
+        assert_eq!((column.rows_scanned, column.rows_passed), (4, 3));
+        assert!(!column.cached);
+        assert!(column.elapsed_us > 0.0);
+
+        // Alice, Charlie, Dave all contain "a"
+        let global = &stats.conditions[1];
+        assert_eq!(global.kind, ConditionKind::Global);
+        assert_eq!((global.rows_scanned, global.rows_passed), (3, 3));
+
+        let custom = &stats.conditions[2];
+        assert_eq!(custom.kind, ConditionKind::Custom);
+        assert_eq!((custom.rows_scanned, custom.rows_passed), (3, 2));
+        assert_eq!(stats.rows_filtered, 2);
+        assert!(stats.total_us >= column.elapsed_us + global.elapsed_us);
+    }
+
+    #[test]
+    fn view_stats_report_cache_hits() {
+        let mut store = make_store_for_filter();
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Gt,
+            FilterValue::Float64(26.0),
+        )]);
+        store.rebuild_view();
+        store.set_sort(Vec::new());
+        store.rebuild_view();
+
+        let column = &store.last_view_stats().conditions[0];
+        assert!(column.cached);
+        assert_eq!((column.rows_scanned, column.rows_passed), (0, 3));
+        assert!(column.elapsed_us.abs() < f64::EPSILON); // no clock configured
+    }
+
+    // ── Custom filter tests ─────────────────────────────────────────
+
+    #[test]
+    fn custom_filter_runs_after_native_filters() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut store = make_store_for_filter();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let seen_in = Rc::clone(&seen);
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Gte,
+            FilterValue::Float64(28.0),
+        )]);
+        store.set_custom_filter(
+            0,
+            Some(Box::new(move |row, value| {
+                seen_in.borrow_mut().push(row);
+                matches!(value, Some(FilterValue::String(s)) if s.len() > 4)
+            })),
+        );
+        store.rebuild_view();
+        // Native filter keeps Alice(30), Charlie(35), Dave(28); only those reach the callback
+        assert_eq!(*seen.borrow(), vec![0, 2, 3]);
+        assert_eq!(store.view_indices(), &[0, 2]);
+
+        store.set_custom_filter(0, None);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2, 3]);
+    }
+
+    #[test]
+    fn custom_filter_receives_null_as_none() {
+        let mut store = make_store_for_filter();
+        store.set_custom_filter(2, Some(Box::new(|_, value| value.is_none())));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[3]);
+    }
+
+    #[test]
+    fn custom_filter_excluded_from_own_facets() {
+        let mut store = make_store_for_filter();
+        store.set_custom_filter(1, Some(Box::new(|row, _| row == 0)));
+        store.set_custom_filter(2, Some(Box::new(|row, _| row != 1)));
+        let total: usize = store.filter_facets(1).iter().map(|f| f.count).sum();
+        assert_eq!(total, 3); // only the column-2 predicate applies
+    }
+
+    #[test]
+    fn expression_filter_compares_columns() {
+        let mut store = make_store_for_filter();
+        let expression = |source: &str| {
+            ColumnFilter::new(
+                1,
+                FilterOp::Expression,
+                FilterValue::String(source.to_string()),
+            )
+        };
+        store.set_column_filters(vec![expression("$1 * $2 > 28")]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2]);
+
+        store.set_column_filters(vec![expression("$1 >")]);
+        store.rebuild_view();
+        assert!(store.view_indices().is_empty());
+
+        store.set_view_state(ViewPreset {
+            column_filters: vec![expression("$1 <= 28")],
+            ..ViewPreset::default()
+        });
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[1, 3]);
+    }
+
+    #[test]
+    fn expression_filter_follows_referenced_columns() {
+        let mut store = make_store_for_filter();
+        store.set_column_filters(vec![ColumnFilter::new(
+            0,
+            FilterOp::Expression,
+            FilterValue::String("$1 > 29".to_string()),
+        )]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2]);
+        assert!(store.column_filtered(1) && !store.column_filtered(2));
+
+        store.update_float64(1, 1, 40.0);
+        store.update_float64(1, 2, 20.0);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1]);
+
+        store.set_column_format(1, FormatSpec::parse("0.0").ok());
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1]);
+    }
+
+    #[test]
+    fn distinct_count_skips_nulls_and_own_filter() {
+        let mut store = make_store_with_tags();
+        assert_eq!(store.distinct_count(0, true), 3);
+        let mut store = make_store_for_filter();
+        assert_eq!(store.distinct_count(2, true), 2);
+        // The appended row's empty name is null
+        store.append_empty_rows(1);
+        assert_eq!(store.distinct_count(0, true), 4);
+        store.set_custom_filter(1, Some(Box::new(|row, _| row == 0)));
+        assert_eq!(store.distinct_count(1, false), 4);
+        assert_eq!(store.distinct_count(0, false), 1);
+    }
+
+    #[test]
+    fn cell_value_types() {
+        let store = make_store_for_filter();
+        assert_eq!(
+            store.cell_value(0, 1),
+            Some(FilterValue::String("Bob".to_string()))
+        );
+        assert_eq!(store.cell_value(1, 2), Some(FilterValue::Float64(35.0)));
+        assert_eq!(store.cell_value(2, 1), Some(FilterValue::Bool(false)));
+        assert_eq!(store.cell_value(2, 3), None);
+        assert_eq!(store.cell_value(9, 0), None);
+
+        let tags = make_store_with_tags();
+        assert_eq!(
+            tags.cell_value(0, 1),
+            Some(FilterValue::StringList(vec!["blue".to_string()]))
+        );
+        assert_eq!(tags.cell_value(0, 2), None);
+    }
+
+    // ── Filter preset tests ─────────────────────────────────────────
+
+    #[test]
+    fn preset_save_and_apply() {
+        let mut store = make_store_for_filter();
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Gt,
+            FilterValue::Float64(28.0),
+        )]);
+        store.set_sort(vec![SortConfig {
+            column_index: 1,
+            direction: SortDirection::Descending,
+            ..SortConfig::default()
+        }]);
+        store.save_preset("older");
+        store.rebuild_view();
+        let expected = store.view_indices().to_vec();
+
+        store.set_column_filters(Vec::new());
+        store.set_sort(Vec::new());
+        store.set_global_filter(Some(GlobalFilter {
+            query: "bob".to_string(),
+        }));
+        store.save_preset("bob");
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[1]);
+
+        assert!(store.apply_preset("older"));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), expected.as_slice());
+        assert!(store.view_state().global_filter.is_none());
+    }
+
+    #[test]
+    fn preset_list_and_delete() {
+        let mut store = make_store_for_filter();
+        store.save_preset("b");
+        store.save_preset("a");
+        let names: Vec<&str> = store.presets().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["a", "b"]);
+
+        assert!(store.delete_preset("a"));
+        assert!(!store.delete_preset("a"));
+        assert!(!store.apply_preset("a"));
+        assert_eq!(store.presets().count(), 1);
+    }
+
+    #[test]
+    fn insert_preset_does_not_change_view() {
+        let mut store = make_store_for_filter();
+        let preset = ViewPreset {
+            global_filter: Some(GlobalFilter {
+                query: "x".to_string(),
+            }),
+            ..ViewPreset::default()
+        };
+        store.insert_preset("x".to_string(), preset.clone());
+        assert_eq!(store.view_state(), ViewPreset::default());
+        assert!(store.apply_preset("x"));
+        assert_eq!(store.view_state(), preset);
+    }
+
+    // ── Filter bitmap cache tests ───────────────────────────────────
+
+    #[test]
+    fn filter_bitmap_matches_indices() {
+        let store = make_store_for_filter();
+        let filter = ColumnFilter::new(1, FilterOp::Gt, FilterValue::Float64(28.0));
+        let bitmap = filter_bitmap_columnar(&store, &filter);
+        assert_eq!(bitmap.iter_ones().collect::<Vec<_>>(), vec![0, 2]);
+    }
+
+    #[test]
+    fn filter_cache_reuses_unchanged_conditions() {
+        let mut store = make_store_for_filter();
+        let age_filter = ColumnFilter::new(1, FilterOp::Gte, FilterValue::Float64(28.0));
+        store.set_column_filters(vec![
+            age_filter.clone(),
+            ColumnFilter::new(0, FilterOp::Contains, FilterValue::String("a".to_string())),
+        ]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2, 3]); // Alice, Charlie, Dave
+
+        // Mutate ages behind the cache's back: the age condition is unchanged,
+        // so its cached bitmap must be reused instead of re-scanning the column.
+        store.data[1] = ColumnData::Float64(vec![0.0, 0.0, 0.0, 0.0]);
+        store.set_column_filters(vec![
+            age_filter,
+            ColumnFilter::new(0, FilterOp::Contains, FilterValue::String("li".to_string())),
+        ]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2]); // Alice, Charlie
+        assert_eq!(store.filter_cache.len(), 2);
+    }
+
+    #[test]
+    fn filter_cache_invalidated_by_generation_and_finalize() {
+        let mut store = make_store_for_filter();
+        let filter = ColumnFilter::new(1, FilterOp::Gt, FilterValue::Float64(28.0));
+        store.set_column_filters(vec![filter.clone()]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2]);
+
+        store.set_column_float64(1, &[40.0, 40.0, 10.0, 10.0]);
+        store.finalize();
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1]);
+
+        store.begin_append(1);
+        store.append_column_float64(1, 4, &[50.0]);
+        store.set_column_filters(vec![filter]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1, 4]);
+        assert!(store
+            .filter_cache
+            .iter()
+            .all(|e| e.generation == store.generation));
+    }
+
+    #[test]
+    fn filter_cache_drops_removed_conditions() {
+        let mut store = make_store_for_filter();
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Gt,
+            FilterValue::Float64(28.0),
+        )]);
+        store.rebuild_view();
+        assert_eq!(store.filter_cache.len(), 1);
+
+        store.set_column_filters(vec![ColumnFilter::new(
+            2,
+            FilterOp::Eq,
+            FilterValue::Bool(true),
+        )]);
+        store.rebuild_view();
+        assert_eq!(store.filter_cache.len(), 1);
+        assert_eq!(store.view_indices(), &[0, 2]);
+    }
+
+    // ── Cell update tests ───────────────────────────────────────────
+
+    fn sort_by(column_index: usize, direction: SortDirection) -> Vec<SortConfig> {
+        vec![SortConfig {
+            column_index,
+            direction,
+            ..SortConfig::default()
+        }]
+    }
+
+    fn sort_by_expression(expression: &str, direction: SortDirection) -> SortConfig {
+        SortConfig {
+            expression: Some(expression.to_string()),
+            ..sort_by(0, direction).remove(0)
+        }
+    }
+
+    #[test]
+    fn sort_by_computed_expression() {
+        let mut store = make_store_for_filter();
+        // age * active: 30, 0, 35, null
+        store.set_sort(vec![sort_by_expression(
+            "$1 * $2",
+            SortDirection::Ascending,
+        )]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[3, 1, 0, 2]);
+        assert!(store.sort_scratch.is_empty());
+        assert_eq!(store.data.len(), 3);
+
+        // age % 10: 0, 5, 5, 8 — ties broken by name descending
+        let mut configs = vec![sort_by_expression("$1 % 10", SortDirection::Ascending)];
+        configs.extend(sort_by(0, SortDirection::Descending));
+        store.set_sort(configs);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2, 1, 3]);
+
+        // Cell updates re-evaluate the expression
+        store.set_sort(vec![sort_by_expression(
+            "$1 * $2",
+            SortDirection::Descending,
+        )]);
+        store.rebuild_view();
+        store.update_float64(1, 0, 50.0);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2, 1, 3]);
+    }
+
+    #[test]
+    fn view_row_heights_follow_rows() {
+        let mut store = make_store_for_filter();
+        store.rebuild_view();
+        assert!(store.view_row_heights(30.0).is_none());
+
+        store.set_row_heights(Some(vec![10.0, f64::NAN, 50.0]));
+        store.set_sort(sort_by(1, SortDirection::Ascending));
+        store.rebuild_view();
+        // Bob(default), Dave(default, beyond the array), Alice(10), Charlie(50)
+        let index = store.view_row_heights(30.0).unwrap();
+        assert_eq!(
+            (0..4).map(|i| index.height(i)).collect::<Vec<_>>(),
+            vec![30.0, 30.0, 10.0, 50.0]
+        );
+        assert!((index.total() - 120.0).abs() < f64::EPSILON);
+
+        store.set_row_height(3, 5.0); // Dave, view position 1
+        let index = store.view_row_heights(30.0).unwrap();
+        assert!((index.offset(2) - 35.0).abs() < f64::EPSILON);
+        let index = store.view_row_heights(20.0).unwrap();
+        assert!((index.total() - 85.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn expanded_rows_add_detail_height() {
+        let mut store = make_store_for_filter();
+        store.rebuild_view();
+        store.set_expanded_rows(&[1, 3], 100.0); // Bob, Dave
+        let heights = |store: &mut ColumnarStore| {
+            let (index, areas) = store.view_row_layout(30.0).unwrap();
+            (
+                (0..4).map(|i| index.height(i)).collect::<Vec<_>>(),
+                areas.detail.to_vec(),
+            )
+        };
+        assert_eq!(
+            heights(&mut store),
+            (vec![30.0, 130.0, 30.0, 130.0], vec![0.0, 100.0, 0.0, 100.0])
+        );
+
+        // Expansion follows the rows: Bob, Dave, Alice, Charlie
+        store.set_sort(sort_by(1, SortDirection::Ascending));
+        store.rebuild_view();
+        store.set_row_height(1, 20.0);
+        assert_eq!(
+            heights(&mut store),
+            (vec![120.0, 130.0, 30.0, 30.0], vec![100.0, 100.0, 0.0, 0.0])
+        );
+
+        store.set_expanded_rows(&[], 100.0);
+        assert!(!store.is_row_expanded(1));
+    }
+
+    #[test]
+    fn view_row_extent_skips_row_areas() {
+        let mut store = make_store_for_filter();
+        store.rebuild_view();
+        assert_eq!(store.view_row_extent(2, 30.0), Some((60.0, 30.0)));
+        assert_eq!(store.view_row_extent(4, 30.0), None);
+
+        store.set_expanded_rows(&[1], 100.0); // Bob
+        store.set_row_height(2, 50.0);
+        assert_eq!(store.view_row_extent(1, 30.0), Some((30.0, 30.0)));
+        assert_eq!(store.view_row_extent(2, 30.0), Some((160.0, 50.0)));
+    }
+
+    #[test]
+    fn always_visible_rows_bracket_the_view() {
+        let mut store = make_store_for_filter();
+        store.set_sort(sort_by(1, SortDirection::Ascending));
+        store.set_always_visible_rows(&[2, 0], &[1]); // Charlie, Alice | Bob
+        store.set_pagination(Some(0), Some(1));
+        store.rebuild_view();
+        // Sorted by age: Bob 25, Dave 28, Alice 30, Charlie 35; page 0 of the rest = Dave
+        assert_eq!(store.view_indices(), &[0, 2, 3, 1]);
+        assert_eq!(store.always_visible_counts(), (2, 1));
+        assert_eq!(store.filtered_total(), 4);
+        assert_eq!(
+            (0..4)
+                .map(|pos| store.view_filtered_index(pos))
+                .collect::<Vec<_>>(),
+            vec![Some(0), Some(1), Some(2), Some(3)]
+        );
+
+        // Filtered-out rows drop out of the view
+        store.set_pagination(None, None);
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Gt,
+            FilterValue::Float64(26.0),
+        )]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2, 3]);
+        assert_eq!(store.always_visible_counts(), (2, 0));
+
+        // Rows appended after marking stay in the body
+        let added = store.append_empty_rows(1);
+        store.update_float64(1, added.start, 40.0);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2, 3, 4]);
+
+        store.set_always_visible_rows(&[], &[]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[3, 0, 2, 4]);
+        assert_eq!(store.always_visible_counts(), (0, 0));
+    }
+
+    #[test]
+    fn row_merges_join_cells_to_the_row_above() {
+        let mut store = make_store_for_filter();
+        store.set_sort(sort_by(2, SortDirection::Ascending));
+        store.rebuild_view();
+        // Dave(null), Bob(false), Alice(true), Charlie(true)
+        store.set_row_merge(2, Some(RowMerge::EqualValues));
+        store.set_row_merge(0, Some(RowMerge::Spans(vec![0, 2, 0, 0])));
+        let merged = |store: &ColumnarStore, col: usize| {
+            (0..4)
+                .map(|pos| store.merged_with_previous(col, pos))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(merged(&store, 2), vec![false, false, false, true]);
+        // Bob spans Alice but not Charlie; Dave has no span above
+        assert_eq!(merged(&store, 0), vec![false, false, true, false]);
+        assert_eq!(merged(&store, 1), vec![false; 4]);
+
+        store.set_row_merge(2, None);
+        store.set_row_merge(0, None);
+        assert!(!store.has_row_merges());
+    }
+
+    #[test]
+    fn cell_styles_follow_their_rows() {
+        let mut store = make_store_for_filter();
+        let indent = CellStyle {
+            padding: Some([0.0, 0.0, 0.0, 16.0]),
+            ..CellStyle::default()
+        };
+        store.set_cell_style(2, 0, Some(indent)); // Charlie
+        store.rebuild_view();
+        assert_eq!(store.view_cell_style(2, 0), Some(&indent));
+        assert_eq!(store.view_cell_style(2, 1), None);
+
+        store.set_sort(sort_by(1, SortDirection::Descending));
+        store.rebuild_view();
+        // Charlie 35 first
+        assert_eq!(store.view_cell_style(0, 0), Some(&indent));
+
+        store.set_cell_style(2, 0, None);
+        assert!(!store.has_cell_styles());
     }
 
-    // Collect string column indices
-    let string_cols: Vec<usize> = store
-        .data
-        .iter()
-        .enumerate()
-        .filter_map(|(i, d)| matches!(d, ColumnData::Strings { .. }).then_some(i))
-        .collect();
+    #[test]
+    fn col_spans_follow_their_rows() {
+        let mut store = make_store_for_filter();
+        store.set_cell_col_span(2, 0, 3); // Charlie
+        store.rebuild_view();
+        assert_eq!(store.view_col_span(2, 0), Some(3));
+        assert_eq!(store.view_col_span(2, 1), None);
 
-    if string_cols.is_empty() {
-        return;
-    }
+        store.set_sort(sort_by(1, SortDirection::Descending));
+        store.rebuild_view();
+        // Charlie 35 first
+        assert_eq!(store.view_col_span(0, 0), Some(3));
 
-    indices.retain(|&idx| {
-        let row = idx as usize;
-        string_cols.iter().any(|&col_idx| {
-            if let Some(ColumnData::Strings { ids, intern }) = store.data.get(col_idx) {
-                let resolved = intern.resolve(ids[row]);
-                resolved.to_lowercase().contains(&query)
-            } else {
-                false
-            }
-        })
-    });
-}
+        store.set_cell_col_span(2, 0, 1);
+        assert!(!store.has_col_spans());
+    }
 
-fn compare_columnar(
-    store: &ColumnarStore,
-    col_idx: usize,
-    row_a: usize,
-    row_b: usize,
-) -> std::cmp::Ordering {
-    match store.data.get(col_idx) {
-        Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => {
-            let a = v[row_a];
-            let b = v[row_b];
-            // NaN handling: NaN is "less than" any real value
-            match (a.is_nan(), b.is_nan()) {
-                (true, true) => std::cmp::Ordering::Equal,
-                (true, false) => std::cmp::Ordering::Less,
-                (false, true) => std::cmp::Ordering::Greater,
-                (false, false) => a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal),
-            }
-        }
-        Some(ColumnData::Strings { ids, intern }) => {
-            let a = intern.resolve(ids[row_a]);
-            let b = intern.resolve(ids[row_b]);
-            a.cmp(b)
-        }
-        None => std::cmp::Ordering::Equal,
+    #[test]
+    fn validation_follows_loads_and_edits() {
+        use crate::validation::{ERROR_MAX, ERROR_REQUIRED};
+        let mut store = make_store_for_filter();
+        let rules = ValidationRules {
+            required: true,
+            max: Some(30.0),
+            ..ValidationRules::default()
+        };
+        store.set_column_validation(1, Some(rules));
+        assert!(store.has_validation());
+        let errors = |store: &ColumnarStore| {
+            (0..store.row_count)
+                .map(|row| store.cell_validation_errors(1, row))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(errors(&store), vec![0, 0, ERROR_MAX, 0]);
+        store.update_float64(1, 0, 99.0);
+        store.update_float64(1, 2, 1.0);
+        assert_eq!(errors(&store), vec![ERROR_MAX, 0, 0, 0]);
+
+        // Appended rows are null
+        let rows = store.append_empty_rows(1);
+        assert_eq!(store.cell_validation_errors(1, rows.start), ERROR_REQUIRED);
+        store.set_column_validation(1, None);
+        assert!(!store.has_validation());
+        assert_eq!(store.cell_validation_errors(1, rows.start), 0);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn removed_rows_leave_the_view() {
+        let mut store = make_store_for_filter();
+        store.rebuild_view();
+        assert!(store.remove_row(1));
+        assert!(!store.remove_row(1));
+        assert!(!store.remove_row(99));
+        assert!(store.rebuild_view_step(f64::INFINITY).done);
+        assert_eq!(store.view_indices(), &[0, 2, 3]);
+
+        assert!(store.restore_row(1));
+        assert!(!store.restore_row(1));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1, 2, 3]);
 
-    // ── Direct column setter tests ────────────────────────────────────
+        // Updating a removed row's sorted cell doesn't bring it back
+        store.set_sort(sort_by(1, SortDirection::Ascending));
+        store.rebuild_view();
+        store.remove_row(1);
+        store.rebuild_view();
+        store.update_float64(1, 1, 29.0);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[3, 0, 2]);
+
+        assert_eq!(store.row_version(2), 0);
+        assert_eq!(store.bump_row_version(2), 1);
+        assert_eq!(store.bump_row_version(2), 2);
+        assert_eq!(store.row_version(2), 2);
+    }
 
     #[test]
-    fn init_and_set_columns_direct() {
-        let mut store = ColumnarStore::new();
+    fn place_row_inserts_at_view_position() {
+        let mut store = make_store_for_filter();
+        store.rebuild_view();
+        store.place_row(3, 1);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 3, 1, 2]);
+        store.place_row(0, 99);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[3, 1, 2, 0]);
+    }
+
+    #[test]
+    fn manual_row_order_replaces_sort() {
+        let mut store = make_store_for_filter();
+        store.set_sort(sort_by(1, SortDirection::Ascending));
+        store.rebuild_view();
+        // Bob 25, Dave 28, Alice 30, Charlie 35
+        assert_eq!(store.view_indices(), &[1, 3, 0, 2]);
+
+        // Drag Charlie to the top, then Bob below Alice
+        assert!(store.move_row(3, 0));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 1, 3, 0]);
+        assert!(store.move_row(1, 3));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 3, 0, 1]);
+        assert!(!store.move_row(0, 4));
+
+        // Rows missing from the order follow in row order
+        store.set_manual_row_order(Some(vec![3, 0]));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[3, 0, 1, 2]);
+
+        // Kept across a reload
         store.init(3, 4);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[3, 0, 1, 2]);
 
-        assert_eq!(store.row_count, 4);
-        assert_eq!(store.data.len(), 3);
-        assert_eq!(store.generation, 1);
+        store.set_sort(Vec::new());
+        store.set_manual_row_order(None);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1, 2, 3]);
     }
 
     #[test]
-    fn set_column_float64_direct() {
-        let mut store = ColumnarStore::new();
-        store.init(3, 4);
-        store.set_column_float64(1, &[30.0, 25.0, 35.0, 28.0]);
+    fn tree_view_flattens_expanded_branches() {
+        let mut store = make_store_for_filter();
+        // Alice ─┬─ Bob
+        //        └─ Charlie ─── Dave
+        store.set_tree_structure(Some(vec![crate::tree::NO_PARENT, 0, 0, 2]));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0]);
 
-        assert_eq!(store.column_type(1), Some(ColumnType::Float64));
-        if let ColumnData::Float64(v) = &store.data[1] {
-            assert_eq!(v, &[30.0, 25.0, 35.0, 28.0]);
-        } else {
-            panic!("expected Float64");
-        }
+        store.set_tree_all_expanded(true);
+        store.set_sort(sort_by(1, SortDirection::Descending));
+        store.rebuild_view();
+        // Siblings by age: Charlie 35 before Bob 25
+        assert_eq!(store.view_indices(), &[0, 2, 3, 1]);
+        assert_eq!(
+            (0..4).map(|p| store.view_depth(p)).collect::<Vec<_>>(),
+            vec![0, 1, 2, 1]
+        );
+
+        // Dave matches; Alice and Charlie stay as his ancestors
+        store.set_tree_expanded(2, false);
+        store.set_column_filters(vec![ColumnFilter::new(
+            0,
+            FilterOp::Eq,
+            FilterValue::String("Dave".to_string()),
+        )]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2]);
+        assert_eq!(store.matched_rows(), &[3]);
+
+        // Sums of the descendants' ages; NaN for leaves
+        let sums = store.tree_rollup(1, Aggregate::Sum);
+        assert_eq!(
+            sums.iter()
+                .map(|v| if v.is_nan() { -1.0 } else { *v })
+                .collect::<Vec<_>>(),
+            vec![88.0, -1.0, 28.0, -1.0]
+        );
     }
 
     #[test]
-    fn set_column_bool_direct() {
-        let mut store = ColumnarStore::new();
-        store.init(3, 4);
-        store.set_column_bool(2, &[1.0, 0.0, 1.0, f64::NAN]);
+    fn group_by_adds_collapsible_group_rows() {
+        let mut store = make_store_for_filter();
+        store.set_group_by(vec![2]);
+        store.rebuild_view();
+        // true: Alice, Charlie | false: Bob | null: Dave
+        assert_eq!(store.view_indices(), &[0, 0, 2, 1, 1, 3, 3]);
+        let groups = |store: &ColumnarStore| {
+            store
+                .view_groups_in(0..usize::MAX)
+                .iter()
+                .map(|g| (g.pos, g.rows.clone(), g.expanded))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            groups(&store),
+            vec![(0, 0..2, true), (3, 2..3, true), (5, 3..4, true)]
+        );
+        let sum = store.group_aggregate(store.view_group(0).unwrap(), 1, Aggregate::Sum);
+        assert!((sum - 65.0).abs() < f64::EPSILON);
+        assert_eq!(store.filtered_rows(), &[0, 1, 2, 3]);
+        assert!(store.view_group(1).is_none());
 
-        assert_eq!(store.column_type(2), Some(ColumnType::Bool));
-        if let ColumnData::Bool(v) = &store.data[2] {
-            assert!((v[0] - 1.0).abs() < f64::EPSILON);
-            assert!((v[1] - 0.0).abs() < f64::EPSILON);
-            assert!(v[3].is_nan());
-        } else {
-            panic!("expected Bool");
-        }
+        store.set_group_expanded(3, false);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 0, 2, 1, 3, 3]);
+        assert_eq!(
+            groups(&store),
+            vec![(0, 0..2, true), (3, 2..3, false), (4, 3..4, true)]
+        );
+        // Group header rows are not data rows
+        assert_eq!(
+            store.view_data_rows(0..usize::MAX).collect::<Vec<_>>(),
+            vec![(1, 0), (2, 2), (5, 3)]
+        );
+        assert_eq!(store.view_position(0), Some(1));
+        assert_eq!(store.view_position(1), None);
+        assert_eq!(
+            (0..7)
+                .map(|pos| store.view_filtered_index(pos))
+                .collect::<Vec<_>>(),
+            vec![None, Some(0), Some(1), None, None, Some(3), None]
+        );
+
+        // Nested: a level-1 group per name; collapsing "true" hides its subgroups
+        store.set_group_by(vec![2, 0]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices().len(), 11);
+        store.set_group_expanded(0, false);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1, 1, 1, 3, 3, 3]);
     }
 
     #[test]
-    fn set_column_strings_direct() {
-        let mut store = ColumnarStore::new();
-        store.init(3, 4);
-        let unique = vec![
-            "".to_string(),
-            "Alice".to_string(),
-            "Bob".to_string(),
-            "Charlie".to_string(),
-            "Alice Smith".to_string(),
-        ];
-        store.set_column_strings(0, &unique, &[1, 2, 3, 4]);
+    fn saved_state_keeps_collapsed_groups_by_value() {
+        let mut store = make_store_for_filter();
+        store.set_group_by(vec![0]);
+        store.rebuild_view();
+        store.set_group_expanded(2, false); // Bob
+        store.set_manual_row_order(Some(vec![3, 1]));
+        let saved = store.saved_state();
+        assert_eq!(
+            saved.collapsed_groups,
+            vec![vec![GroupKey::Text("Bob".to_string())]]
+        );
 
-        assert_eq!(store.column_type(0), Some(ColumnType::String));
-        if let ColumnData::Strings { ids, intern } = &store.data[0] {
-            assert_eq!(intern.resolve(ids[0]), "Alice");
-            assert_eq!(intern.resolve(ids[1]), "Bob");
-            assert_eq!(intern.resolve(ids[2]), "Charlie");
-            assert_eq!(intern.resolve(ids[3]), "Alice Smith");
-        } else {
-            panic!("expected Strings");
-        }
+        // Reloaded data interns the names in another order
+        let mut reloaded = ColumnarStore::new();
+        reloaded.init(3, 4);
+        let unique = ["Dave", "Charlie", "Bob", "Alice"].map(String::from);
+        reloaded.set_column_strings(0, &unique, &[3, 2, 1, 0]);
+        reloaded.finalize();
+        reloaded.restore_saved_state(&saved);
+        reloaded.rebuild_view();
+        // Manual order Dave, Bob, then the rest; Bob's group stays collapsed
+        assert_eq!(reloaded.view_indices(), &[3, 3, 1, 0, 0, 2, 2]);
+        assert_eq!(reloaded.manual_row_order(), Some(&[3, 1][..]));
     }
 
     #[test]
-    fn direct_ingestion_roundtrip_with_sort() {
-        let mut store = ColumnarStore::new();
-        store.init(3, 4);
-        let unique = vec![
-            "".to_string(),
-            "Alice".to_string(),
-            "Bob".to_string(),
-            "Charlie".to_string(),
-            "Alice Smith".to_string(),
-        ];
-        store.set_column_strings(0, &unique, &[1, 2, 3, 4]);
-        store.set_column_float64(1, &[30.0, 25.0, 35.0, 28.0]);
-        store.set_column_bool(2, &[1.0, 0.0, 1.0, f64::NAN]);
-        store.finalize();
+    fn conditional_formats_style_cells_by_value_rank_and_scale() {
+        let mut store = make_store_for_filter();
+        store.set_conditional_formats(vec![
+            ConditionalFormat {
+                column_index: 1,
+                condition: FormatCondition::Match(ColumnFilter::new(
+                    0,
+                    FilterOp::Eq,
+                    FilterValue::String("Bob".to_string()),
+                )),
+                style_id: 1,
+            },
+            ConditionalFormat {
+                column_index: 1,
+                condition: FormatCondition::Top {
+                    count: 50.0,
+                    percent: true,
+                    bottom: false,
+                },
+                style_id: 2,
+            },
+            ConditionalFormat {
+                column_index: 2,
+                condition: FormatCondition::Scale { buckets: 4 },
+                style_id: 10,
+            },
+        ]);
+        store.rebuild_view();
+        store.resolve_conditional_formats();
+        let styles = |store: &ColumnarStore, col| {
+            (0..4)
+                .map(|row| store.conditional_style(col, row))
+                .collect::<Vec<_>>()
+        };
+        // Ages 30, 25 (Bob), 35, 28: Bob's rule comes first; top half is 30 and 35
+        assert_eq!(styles(&store, 1), vec![2, 1, 2, 0]);
+        // Active true/false/true/null over 0..1
+        assert_eq!(styles(&store, 2), vec![13, 10, 13, 0]);
+        assert_eq!(styles(&store, 0), vec![0, 0, 0, 0]);
+
+        // Statistics follow the filtered rows
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Lt,
+            FilterValue::Float64(30.0),
+        )]);
+        store.rebuild_view();
+        store.resolve_conditional_formats();
+        assert_eq!(styles(&store, 1), vec![2, 1, 2, 2]);
+    }
+
+    #[test]
+    fn data_visuals_use_the_filtered_range() {
+        use crate::visualization::{parse_color, VisualKind};
+        let mut store = make_store_for_filter();
+        store.set_data_visualization(
+            1,
+            Some(DataVisualization {
+                kind: VisualKind::ColorScale(vec![
+                    parse_color("#000000").unwrap(),
+                    parse_color("#ffffff").unwrap(),
+                ]),
+                domain: None,
+            }),
+        );
+        store.rebuild_view();
+        store.resolve_data_visualizations();
+        // Ages 30, 25, 35, 28 over 25..35
+        assert_eq!(store.data_visual(1, 0), (0.5, 0x8080_80FF));
+        assert_eq!(store.data_visual(1, 2), (1.0, 0xFFFF_FFFF));
+        assert!(store.data_visual(0, 0).0.is_nan());
+
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Lte,
+            FilterValue::Float64(30.0),
+        )]);
+        store.rebuild_view();
+        store.resolve_data_visualizations();
+        assert_eq!(store.data_visual(1, 0), (1.0, 0xFFFF_FFFF));
+        assert_eq!(store.data_visual(1, 1), (0.0, 0x0000_00FF));
+    }
 
+    #[test]
+    fn window_columns_follow_the_sorted_view() {
+        let mut store = make_store_for_filter();
         store.set_sort(vec![SortConfig {
             column_index: 1,
             direction: SortDirection::Ascending,
+            ..SortConfig::default()
         }]);
+        let running = store.add_window_column(1, WindowFn::Running(Aggregate::Sum));
+        let rank = store.add_window_column(2, WindowFn::Rank);
+        store.set_pagination(Some(1), Some(2));
+        store.rebuild_view();
+        // Sorted by age: Bob(25), Dave(28), Alice(30), Charlie(35); page 2 = Alice, Charlie
+        assert_eq!(store.view_indices(), &[0, 2]);
+        let values = |store: &ColumnarStore, id: usize| {
+            (0..4)
+                .map(|row| store.window_value(id, row))
+                .collect::<Vec<_>>()
+        };
+        // Running totals continue across pages
+        assert_eq!(values(&store, running), vec![83.0, 25.0, 118.0, 53.0]);
+
+        // Ranks in view order (null, true, true); filtered-out rows are NaN
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Gt,
+            FilterValue::Float64(26.0),
+        )]);
+        store.set_pagination(None, None);
         store.rebuild_view();
+        let ranks = values(&store, rank);
+        assert!(ranks[1].is_nan() && ranks[3].is_nan());
+        assert_eq!(vec![ranks[0], ranks[2]], vec![2.0, 2.0]);
 
-        // Bob(25)=1, Alice Smith(28)=3, Alice(30)=0, Charlie(35)=2
-        assert_eq!(store.view_indices(), &[1, 3, 0, 2]);
+        store.remove_window_column(running);
+        assert!(store.window_value(running, 0).is_nan());
+        assert_eq!(values(&store, rank)[..1].to_vec(), vec![2.0]);
     }
 
     #[test]
-    fn sort_columnar_strings() {
-        let mut store = ColumnarStore::new();
-        store.init(1, 4);
-        let unique = vec![
-            "".into(),
-            "Alice".into(),
-            "Bob".into(),
-            "Charlie".into(),
-            "Alice Smith".into(),
-        ];
-        store.set_column_strings(0, &unique, &[1, 2, 3, 4]);
-        store.finalize();
+    fn group_headers_start_each_group() {
+        let mut store = make_store_for_filter();
+        store.set_sort(sort_by(2, SortDirection::Ascending));
+        store.rebuild_view();
+        // Dave(null), Bob(false), Alice(true), Charlie(true)
+        store.set_group_headers(Some(GroupHeaders {
+            column: Some(2),
+            height: 12.0,
+            sticky: true,
+        }));
+        let (index, areas) = store.view_row_layout(30.0).unwrap();
+        assert_eq!(areas.group_header, &[12.0, 12.0, 12.0, 0.0]);
+        assert_eq!(
+            (0..4).map(|i| index.height(i)).collect::<Vec<_>>(),
+            vec![42.0, 42.0, 42.0, 30.0]
+        );
 
-        let mut indices: Vec<u32> = (0..4).collect();
-        sort_indices_columnar(
-            &mut indices,
-            &store,
-            &[SortConfig {
+        // Within Bob's group: pinned with Alice's header 84px down
+        assert_eq!(
+            store.sticky_group_header(30.0, 50.0),
+            Some((1, 12.0, Some(84.0)))
+        );
+        assert_eq!(
+            store.sticky_group_header(30.0, 120.0),
+            Some((2, 12.0, None))
+        );
+
+        store.set_group_headers(None);
+        assert!(store.sticky_group_header(30.0, 0.0).is_none());
+    }
+
+    fn auto_height_on_names(text_width: f64) -> AutoHeight {
+        AutoHeight {
+            columns: vec![AutoHeightColumn {
                 column_index: 0,
-                direction: SortDirection::Ascending,
+                text_width,
             }],
+            line_height: 10.0,
+            padding: 4.0,
+            min_height: 20.0,
+            char_width: 5.0,
+        }
+    }
+
+    fn slice_state(scroll_top: f64) -> ScrollState {
+        ScrollState {
+            scroll_top,
+            viewport_height: 30.0,
+            row_height: 20.0,
+            total_rows: 4,
+            overscan: 0,
+            pinned_top: None,
+            pinned_bottom: None,
+        }
+    }
+
+    #[test]
+    fn auto_height_measures_rows_in_view() {
+        let mut store = make_store_for_filter();
+        store.rebuild_view();
+        // 3 chars per line: Alice → 2 lines, Bob → 1, Charlie → 3, Dave → 2
+        store.set_auto_height(Some(auto_height_on_names(15.0)));
+        let slice = store.virtual_slice(&slice_state(0.0), 20.0);
+        // Measured rows 0 and 1 (24px, 20px) cover the 30px viewport
+        assert_eq!((slice.start_index, slice.end_index), (0, 2));
+        let index = store.view_row_heights(20.0).unwrap();
+        assert_eq!(
+            (0..4).map(|i| index.height(i)).collect::<Vec<_>>(),
+            vec![24.0, 20.0, 20.0, 20.0]
+        );
+
+        store.virtual_slice(&slice_state(50.0), 20.0);
+        let index = store.view_row_heights(20.0).unwrap();
+        assert_eq!((index.height(2), index.height(3)), (34.0, 24.0));
+
+        // Edits remeasure the row
+        store.update_string(0, 1, "Al");
+        store.virtual_slice(&slice_state(0.0), 20.0);
+        assert!((store.view_row_heights(20.0).unwrap().height(1) - 20.0).abs() < f64::EPSILON);
+        assert!((store.view_row_heights(20.0).unwrap().height(0) - 24.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn auto_height_uses_measure_callback() {
+        let mut store = make_store_for_filter();
+        store.rebuild_view();
+        store.set_auto_height(Some(auto_height_on_names(100.0)));
+        store.set_text_measure(Some(Box::new(|text: &str, width: f64| {
+            (text.len() as f64).mul_add(10.0, width)
+        })));
+        store.virtual_slice(&slice_state(0.0), 20.0);
+        // "Alice": 50 + 100 + padding 4
+        assert!((store.view_row_heights(20.0).unwrap().height(0) - 154.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn auto_height_wraps_by_text_width_measure() {
+        let mut store = make_store_for_filter();
+        store.rebuild_view();
+        store.set_auto_height(Some(auto_height_on_names(25.0)));
+        // 10px glyphs: two characters per 25px line
+        store.set_text_width_measure(Some(Box::new(|text: &str| text.len() as f64 * 10.0)));
+        store.virtual_slice(&slice_state(0.0), 20.0);
+        // "Alice": Al / ic / e → 3 lines of 10px + padding 4
+        assert!((store.view_row_heights(20.0).unwrap().height(0) - 34.0).abs() < f64::EPSILON);
+        let mut lines = Vec::new();
+        store.wrap_auto_height_text("Alice", 25.0, &mut lines);
+        assert_eq!(lines, vec![0..2, 2..4, 4..5]);
+    }
+
+    #[test]
+    fn content_width_over_sampled_rows() {
+        let mut store = make_store_for_filter();
+        store.rebuild_view();
+        let chars = |text: &str| text.chars().count() as f64;
+        assert_eq!(
+            vec![
+                store.column_content_width(0, store.view_sample(0), chars),
+                store.column_content_width(0, [1, 3], chars),
+                store.column_content_width(2, store.view_sample(0), chars),
+                store.column_content_width(0, [], chars),
+            ],
+            vec![7.0, 4.0, 5.0, 0.0] // Charlie | Dave | false
+        );
+        assert_eq!(store.view_sample(2).collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(store.view_sample(3).collect::<Vec<_>>(), vec![0, 2]);
+    }
+
+    #[test]
+    fn width_stats_over_rows() {
+        let mut store = make_store_for_filter();
+        store.rebuild_view();
+        let chars = |text: &str| text.chars().count() as f64;
+        assert_eq!(
+            vec![
+                store.column_width_stats(0, store.view_sample(0), chars),
+                store.column_width_stats(0, [1, 3], chars),
+                store.column_width_stats(0, [], chars),
+            ],
+            vec![(4.75, 7.0), (3.5, 4.0), (0.0, 0.0)] // Alice Bob Charlie Dave
+        );
+    }
+
+    #[test]
+    fn view_position_of_data_row() {
+        let mut store = make_store_for_filter();
+        store.set_sort(sort_by(1, SortDirection::Descending));
+        store.rebuild_view();
+        assert_eq!(
+            (store.view_position(2), store.view_position(1)),
+            (Some(0), Some(3))
         );
-        // Alice, Alice Smith, Bob, Charlie
-        assert_eq!(indices, vec![0, 3, 1, 2]);
+        assert_eq!(store.view_position(9), None);
     }
 
     #[test]
-    fn rebuild_view_idempotent() {
+    fn scroll_anchor_survives_resort() {
         let mut store = ColumnarStore::new();
-        store.init(1, 4);
-        store.set_column_float64(0, &[30.0, 25.0, 35.0, 28.0]);
-        store.finalize();
-        store.set_sort(vec![SortConfig {
-            column_index: 0,
-            direction: SortDirection::Ascending,
-        }]);
+        store.init(1, 10);
+        store.set_column_float64(0, &(0..10).map(f64::from).collect::<Vec<_>>());
+        store.rebuild_view();
+
+        // Top row at scroll 25 (10px rows) is row 2, 5px scrolled out
+        let top = store.scroll_anchor(None, 25.0, 10.0, 0).unwrap();
+        let pinned = store.scroll_anchor(Some(5), 25.0, 10.0, 0).unwrap();
+        assert_eq!(
+            (top, pinned.offset),
+            (
+                ScrollAnchor {
+                    row: 2,
+                    offset: -5.0
+                },
+                25.0
+            )
+        );
 
+        // Reversed: row 2 at view position 7, row 5 at 4
+        store.set_sort(sort_by(0, SortDirection::Descending));
         store.rebuild_view();
-        let first = store.view_indices().to_vec();
-        store.rebuild_view(); // should be no-op (not dirty)
-        let second = store.view_indices().to_vec();
-        assert_eq!(first, second);
+        assert_eq!(
+            (
+                store.anchored_scroll_top(&top, 10.0, 0),
+                store.anchored_scroll_top(&pinned, 10.0, 0)
+            ),
+            (Some(75.0), Some(15.0))
+        );
+
+        store.set_column_filters(vec![ColumnFilter::new(
+            0,
+            FilterOp::Gt,
+            FilterValue::Float64(5.0),
+        )]);
+        store.rebuild_view();
+        assert_eq!(store.anchored_scroll_top(&top, 10.0, 0), None);
     }
 
     #[test]
-    fn scroll_config_stored() {
+    fn lazy_row_blocks() {
         let mut store = ColumnarStore::new();
-        store.set_scroll_config(40.0, 800.0, 3);
-        assert!((store.row_height() - 40.0).abs() < f64::EPSILON);
-        assert!((store.viewport_height() - 800.0).abs() < f64::EPSILON);
-        assert_eq!(store.overscan(), 3);
+        store.init(1, 10);
+        store.set_column_float64(0, &[f64::NAN; 10]);
+        store.finalize();
+        store.set_row_block_size(Some(4));
+        store.rebuild_view();
+        assert_eq!(store.missing_blocks(2..9), vec![0, 1, 2]);
+        assert!(!store.is_row_loaded(5));
+
+        store.append_column_float64(0, 4, &[1.0, 2.0, 3.0, 4.0]);
+        store.mark_block_loaded(1);
+        assert!(store.is_row_loaded(5));
+        assert_eq!(store.missing_blocks(2..9), vec![0, 2]);
+        // Out-of-range blocks don't grow the loaded marks
+        store.mark_block_loaded(usize::MAX / 2);
+        assert_eq!(store.loaded_blocks.len(), 2);
+        assert_eq!(store.missing_blocks(4..8), Vec::<usize>::new());
+
+        // Loading marks the view dirty so new values are sorted in
+        store.set_sort(sort_by(0, SortDirection::Descending));
+        store.rebuild_view();
+        assert_eq!(&store.view_indices()[..4], &[7, 6, 5, 4]);
+        // Missing blocks follow the view order
+        assert_eq!(store.missing_blocks(0..5), vec![0]);
+
+        store.mark_block_unloaded(1);
+        assert_eq!(store.missing_blocks(0..1), vec![1]);
+        store.set_row_block_size(None);
+        assert!(store.missing_blocks(0..10).is_empty());
     }
 
     #[test]
-    fn generation_increments() {
+    fn remote_rows_keep_server_order() {
         let mut store = ColumnarStore::new();
-        assert_eq!(store.generation, 0);
+        store.init(1, 8);
+        store.set_column_float64(0, &[f64::NAN; 8]);
+        store.finalize();
+        store.set_row_block_size(Some(4));
+        store.set_remote_rows(true);
+        store.append_column_float64(0, 0, &[3.0, 1.0, 2.0, 0.0]);
+        store.mark_block_loaded(0);
+
+        // Sorts and filters are tracked, not applied
+        store.set_sort(sort_by(0, SortDirection::Ascending));
+        store.set_column_filters(vec![ColumnFilter::new(
+            0,
+            FilterOp::Gt,
+            FilterValue::Float64(1.0),
+        )]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1, 2, 3, 4, 5, 6, 7]);
+        let query = store.query_descriptor();
+        assert_eq!(
+            query.view.sort_configs,
+            sort_by(0, SortDirection::Ascending)
+        );
+        assert_eq!(query.view.column_filters.len(), 1);
+        // The query changed: loaded rows belong to the old result
+        assert_eq!(store.missing_blocks(0..8), vec![0, 1]);
 
-        store.init(1, 2);
-        assert_eq!(store.generation, 1);
+        store.set_remote_row_count(3);
+        store.append_column_float64(0, 0, &[2.0, 3.0, 5.0]);
+        store.mark_block_loaded(0);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1, 2]);
+        assert!(store.missing_blocks(0..3).is_empty());
+        // Re-sending the count keeps loaded blocks
+        store.set_remote_row_count(3);
+        store.rebuild_view();
+        assert!(store.missing_blocks(0..3).is_empty());
 
-        store.init(1, 3);
-        assert_eq!(store.generation, 2);
+        store.set_remote_rows(false);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1, 2]);
+        store.set_sort(sort_by(0, SortDirection::Descending));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 1, 0]);
     }
 
     #[test]
-    fn get_float64_ptr_accessible() {
-        let mut store = ColumnarStore::new();
-        store.init(2, 4);
-        store.set_column_float64(0, &[30.0, 25.0, 35.0, 28.0]);
-
-        let (ptr, len) = store.get_float64_ptr(0).expect("should be Float64");
-        assert_eq!(len, 4);
-        assert!(!ptr.is_null());
+    fn query_descriptor_is_canonical() {
+        let filter = |column_index| {
+            ColumnFilter::new(
+                column_index,
+                FilterOp::Eq,
+                FilterValue::String("a".to_string()),
+            )
+        };
+        let mut a = ColumnarStore::new();
+        a.set_column_filters(vec![filter(2), filter(0)]);
+        a.set_global_filter(Some(GlobalFilter {
+            query: String::new(),
+        }));
+        let mut b = ColumnarStore::new();
+        b.set_column_filters(vec![filter(0), filter(2)]);
+        assert_eq!(a.query_descriptor(), b.query_descriptor());
+        assert_eq!(a.query_descriptor().view.global_filter, None);
+        b.set_sort(sort_by(0, SortDirection::Ascending));
+        assert_ne!(a.query_descriptor().key, b.query_descriptor().key);
     }
 
     #[test]
-    fn get_float64_ptr_returns_none_for_string_column() {
-        let mut store = ColumnarStore::new();
-        store.init(1, 2);
-        store.set_column_strings(0, &["".into(), "A".into()], &[0, 1]);
-
-        assert!(store.get_float64_ptr(0).is_none());
+    fn sort_keeps_input_order_of_ties() {
+        let store = make_store_for_filter();
+        // active: true, false, true, null — with both the radix and comparison paths
+        let mut configs = sort_by(2, SortDirection::Descending);
+        for multi_key in [false, true] {
+            if multi_key {
+                configs.extend(sort_by(2, SortDirection::Descending));
+            }
+            let mut indices = vec![3, 2, 1, 0];
+            sort_indices_columnar(&mut indices, &store, &configs);
+            assert_eq!(indices, vec![2, 0, 1, 3]);
+        }
     }
 
     #[test]
-    fn get_float64_ptr_works_for_bool_column() {
-        let mut store = ColumnarStore::new();
-        store.init(1, 4);
-        store.set_column_bool(0, &[1.0, 0.0, 1.0, f64::NAN]);
+    fn row_order_tiebreak_survives_updates() {
+        let mut store = make_store_for_filter();
+        assert!(store.row_order_tiebreak());
+        store.set_sort(sort_by(2, SortDirection::Descending));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2, 1, 3]);
 
-        let result = store.get_float64_ptr(0);
-        assert!(result.is_some());
-        let (ptr, len) = result.unwrap();
-        assert_eq!(len, 4);
-        assert!(!ptr.is_null());
-    }
+        // Row 3 joins the `true` tie and lands by row index, not at the end
+        store.update_bool(2, 3, 1.0);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2, 3, 1]);
+        store.update_bool(2, 0, 1.0); // unchanged value, still first
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2, 3, 1]);
 
-    // ── StringInternTable coverage ───────────────────────────────────
+        store.set_row_order_tiebreak(false);
+        store.update_bool(2, 0, 0.0);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 3, 0, 1]);
+    }
 
     #[test]
-    fn intern_cache_hit_returns_same_id() {
-        let mut intern = StringInternTable::new();
-        let id1 = intern.intern("hello");
-        let id2 = intern.intern("hello");
-        assert_eq!(id1, id2);
-        assert_eq!(intern.len(), 1);
+    fn invalid_sort_expression_sorts_as_null() {
+        let mut store = make_store_for_filter();
+        store.set_sort(vec![sort_by_expression("$1 *", SortDirection::Descending)]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1, 2, 3]);
     }
 
     #[test]
-    fn intern_table_len_and_is_empty() {
-        let mut intern = StringInternTable::new();
-        assert!(intern.is_empty());
-        assert_eq!(intern.len(), 0);
+    fn update_repositions_rows_incrementally() {
+        let mut store = make_store_for_filter();
+        store.set_sort(sort_by(1, SortDirection::Ascending));
+        store.rebuild_view();
+        // Bob(25), Dave(28), Alice(30), Charlie(35)
+        assert_eq!(store.view_indices(), &[1, 3, 0, 2]);
 
-        intern.intern("a");
-        assert!(!intern.is_empty());
-        assert_eq!(intern.len(), 1);
+        store.update_float64(1, 2, 20.0); // Charlie → 20
+        store.update_float64(1, 1, 30.0); // Bob ties Alice, sorts after her by row index
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 3, 0, 1]);
+        assert!(store.updated_rows().is_empty());
+        assert!(store.last_view_stats().conditions.is_empty());
 
-        intern.intern("b");
-        assert_eq!(intern.len(), 2);
+        // Same order as a full rebuild
+        store.set_sort(sort_by(1, SortDirection::Ascending));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 3, 0, 1]);
     }
 
     #[test]
-    fn intern_table_default() {
-        let intern = StringInternTable::default();
-        assert!(intern.is_empty());
-        assert_eq!(intern.len(), 0);
+    fn update_refilters_rows_and_patches_cache() {
+        let mut store = make_store_for_filter();
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Gte,
+            FilterValue::Float64(28.0),
+        )]);
+        store.set_sort(sort_by(0, SortDirection::Descending));
+        store.rebuild_view();
+        // Dave, Charlie, Alice
+        assert_eq!(store.view_indices(), &[3, 2, 0]);
+
+        store.update_float64(1, 2, 10.0); // Charlie drops out
+        store.update_float64(1, 1, 40.0); // Bob joins
+        store.update_string(0, 0, "Zoe"); // Alice renamed, moves to the top
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 3, 1]);
+        assert_eq!(store.filtered_total(), 3);
+
+        // A full rebuild reusing the patched filter bitmap agrees
+        store.set_sort(sort_by(0, SortDirection::Descending));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 3, 1]);
+        assert!(store.last_view_stats().conditions[0].cached);
     }
 
-    // ── ColumnarStore Default impl ───────────────────────────────────
+    #[test]
+    fn update_with_pagination_rebuilds_fully() {
+        let mut store = make_store_for_filter();
+        store.set_sort(sort_by(1, SortDirection::Ascending));
+        store.set_pagination(Some(0), Some(2));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[1, 3]);
+
+        store.update_float64(1, 2, 1.0);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 1]);
+    }
 
     #[test]
-    fn columnar_store_default() {
-        let store = ColumnarStore::default();
-        assert_eq!(store.row_count, 0);
-        assert_eq!(store.generation, 0);
-        assert!(store.data.is_empty());
+    fn row_update_age_counts_frames_within_window() {
+        let mut store = make_store_for_filter();
+        store.update_float64(1, 0, 5.0);
+        assert_eq!(store.row_update_age(0), None); // off by default
+
+        store.set_update_flash_frames(3);
+        store.update_float64(1, 2, 5.0);
+        assert_eq!(store.row_update_age(2), Some(0));
+        assert_eq!(store.row_update_age(1), None);
+        store.advance_update_tick();
+        store.advance_update_tick();
+        assert_eq!(store.row_update_age(2), Some(2));
+        store.update_float64(1, 1, 5.0);
+        store.advance_update_tick();
+        assert_eq!(store.row_update_age(2), None);
+        assert_eq!(store.row_update_age(1), Some(1));
+
+        store.set_update_flash_frames(0);
+        assert_eq!(store.row_update_age(1), None);
     }
 
-    // ── compare_columnar NaN handling ────────────────────────────────
+    // ── Fast single-key sort tests ──────────────────────────────────
 
     #[test]
-    fn compare_columnar_nan_both() {
+    fn fast_single_key_sort_matches_comparison_sort() {
+        let n = 500;
+        let floats: Vec<f64> = (0..n)
+            .map(|i| match i % 11 {
+                0 => f64::NAN,
+                1 => -0.0,
+                2 => 0.0,
+                k => f64::from((i * 37) % 50) - 25.0 + f64::from(k) / 4.0,
+            })
+            .collect();
+        let bools: Vec<f64> = (0..n)
+            .map(|i| [0.0, 1.0, f64::NAN][(i % 3) as usize])
+            .collect();
+        let unique = vec![
+            String::new(),
+            "file10".to_string(),
+            "file2".to_string(),
+            "Bob".to_string(),
+            "alice".to_string(),
+        ];
+        let ids: Vec<u32> = (0..n).map(|i| i * 7 % 5).collect();
+
         let mut store = ColumnarStore::new();
-        store.init(1, 3);
-        store.set_column_float64(0, &[f64::NAN, f64::NAN, 5.0]);
+        store.init(3, n as usize);
+        store.set_column_float64(0, &floats);
+        store.set_column_bool(1, &bools);
+        store.set_column_strings(2, &unique, &ids);
+        store.finalize();
 
-        let result = compare_columnar(&store, 0, 0, 1);
-        assert_eq!(result, std::cmp::Ordering::Equal);
+        let nulls = [None, Some(NullOrder::First), Some(NullOrder::Last)];
+        let directions = [SortDirection::Ascending, SortDirection::Descending];
+        for column_index in 0..3 {
+            for direction in directions {
+                for nulls in nulls {
+                    for natural in [false, true] {
+                        let config = SortConfig {
+                            column_index,
+                            direction,
+                            natural,
+                            nulls,
+                            ..SortConfig::default()
+                        };
+                        let mut fast: Vec<u32> = (0..n).rev().collect();
+                        let mut expected = fast.clone();
+                        assert!(sort_single_key_fast(&mut fast, &store, &config));
+                        expected.sort_by(|&a, &b| {
+                            compare_rows(&store, std::slice::from_ref(&config), a, b)
+                        });
+                        assert_eq!(fast, expected, "{config:?}");
+                    }
+                }
+            }
+        }
     }
 
     #[test]
-    fn compare_columnar_nan_left_only() {
-        let mut store = ColumnarStore::new();
-        store.init(1, 2);
-        store.set_column_float64(0, &[f64::NAN, 5.0]);
+    fn fast_sort_skips_high_cardinality_strings() {
+        let mut store = make_store_for_filter();
+        let config = &sort_by(0, SortDirection::Ascending)[0];
+        let mut indices: Vec<u32> = (0..4).collect();
+        assert!(!sort_single_key_fast(&mut indices, &store, config));
 
-        let result = compare_columnar(&store, 0, 0, 1);
-        assert_eq!(result, std::cmp::Ordering::Less);
+        store.init(1, 100);
+        let unique: Vec<String> = (0..60).map(|i| format!("v{i}")).collect();
+        let ids: Vec<u32> = (0..100).map(|i| i % 60).collect();
+        store.set_column_strings(0, &unique, &ids);
+        store.finalize();
+        let mut indices: Vec<u32> = (0..100).collect();
+        assert!(!sort_single_key_fast(&mut indices, &store, config));
     }
 
-    #[test]
-    fn compare_columnar_nan_right_only() {
-        let mut store = ColumnarStore::new();
-        store.init(1, 2);
-        store.set_column_float64(0, &[5.0, f64::NAN]);
+    // ── Group sort tests ────────────────────────────────────────────
 
-        let result = compare_columnar(&store, 0, 0, 1);
-        assert_eq!(result, std::cmp::Ordering::Greater);
+    fn make_store_with_regions() -> ColumnarStore {
+        let mut store = ColumnarStore::new();
+        store.init(2, 6);
+        let unique = vec![
+            String::new(),
+            "East".to_string(),
+            "West".to_string(),
+            "North".to_string(),
+        ];
+        store.set_column_strings(0, &unique, &[1, 2, 1, 3, 2, 3]);
+        store.set_column_float64(1, &[10.0, 50.0, 30.0, 5.0, 20.0, 1.0]);
+        store.finalize();
+        store
     }
 
     #[test]
-    fn sort_descending_exercises_nan_branches() {
-        let mut store = ColumnarStore::new();
-        store.init(1, 3);
-        store.set_column_float64(0, &[f64::NAN, 10.0, 5.0]);
+    fn group_sort_orders_groups_by_subtotal() {
+        let mut store = make_store_with_regions();
+        store.set_group_sort(Some(GroupSort {
+            group_column: 0,
+            aggregate_column: 1,
+            aggregate: Aggregate::Sum,
+            direction: SortDirection::Descending,
+        }));
+        store.set_sort(sort_by(1, SortDirection::Ascending));
+        store.rebuild_view();
+        // West (70): 20, 50 · East (40): 10, 30 · North (6): 1, 5
+        assert_eq!(store.view_indices(), &[4, 1, 0, 2, 5, 3]);
 
-        let mut indices: Vec<u32> = vec![0, 1, 2];
-        sort_indices_columnar(
-            &mut indices,
-            &store,
-            &[SortConfig {
-                column_index: 0,
-                direction: SortDirection::Descending,
-            }],
-        );
-        // Descending: 10.0(1), 5.0(2), NaN(0)
-        assert_eq!(indices, vec![1, 2, 0]);
+        // Subtotals cover only visible rows
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Lt,
+            FilterValue::Float64(25.0),
+        )]);
+        store.rebuild_view();
+        // West (20) · East (10) · North (6)
+        assert_eq!(store.view_indices(), &[4, 0, 5, 3]);
     }
 
     #[test]
-    fn compare_columnar_none_column() {
-        let mut store = ColumnarStore::new();
-        store.init(1, 2);
-        store.set_column_float64(0, &[1.0, 2.0]);
+    fn group_sort_ties_break_by_group_value() {
+        let mut store = make_store_with_regions();
+        store.set_group_sort(Some(GroupSort {
+            group_column: 0,
+            aggregate_column: 1,
+            aggregate: Aggregate::Count,
+            direction: SortDirection::Ascending,
+        }));
+        store.rebuild_view();
+        // Every group has 2 rows: East, North, West; rows keep input order
+        assert_eq!(store.view_indices(), &[0, 2, 3, 5, 1, 4]);
 
-        let result = compare_columnar(&store, 99, 0, 1);
-        assert_eq!(result, std::cmp::Ordering::Equal);
+        store.set_group_sort(None);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1, 2, 3, 4, 5]);
     }
 
-    #[test]
-    fn sort_multi_key_equal_fallthrough() {
-        let mut store = ColumnarStore::new();
-        store.init(2, 3);
-        // All same value in column 0
-        store.set_column_float64(0, &[30.0, 30.0, 30.0]);
-        // Different values in column 1
-        store.set_column_strings(
-            1,
-            &["".into(), "Charlie".into(), "Alice".into(), "Bob".into()],
-            &[1, 2, 3],
-        );
+    // ── Sort permutation cache tests ────────────────────────────────
 
-        let mut indices: Vec<u32> = vec![0, 1, 2];
-        sort_indices_columnar(
-            &mut indices,
-            &store,
-            &[
-                SortConfig {
-                    column_index: 0,
-                    direction: SortDirection::Ascending,
-                },
-                SortConfig {
+    #[test]
+    fn sort_permutation_serves_both_directions() {
+        let mut store = make_store_with_nulls();
+        store.set_column_filters(vec![ColumnFilter::new(
+            0,
+            FilterOp::Neq,
+            FilterValue::String("Bob".into()),
+        )]);
+        for direction in [SortDirection::Ascending, SortDirection::Descending] {
+            for nulls in [None, Some(NullOrder::First), Some(NullOrder::Last)] {
+                let configs = vec![SortConfig {
                     column_index: 1,
-                    direction: SortDirection::Ascending,
-                },
-            ],
-        );
-        // All ages equal (30), so sort by name: Alice(1), Bob(2), Charlie(0)
-        assert_eq!(indices, vec![1, 2, 0]);
+                    direction,
+                    nulls,
+                    ..SortConfig::default()
+                }];
+                store.set_sort(configs.clone());
+                store.rebuild_view();
+                let mut expected: Vec<u32> = vec![0, 2]; // Neq excludes nulls and Bob
+                sort_indices_columnar(&mut expected, &store, &configs);
+                assert_eq!(store.view_indices(), expected.as_slice());
+            }
+        }
+        assert_eq!(store.sort_permutations.len(), 1);
+        assert_eq!(store.sort_permutations[0].null_count, 2);
     }
 
     #[test]
-    fn sort_columnar_empty_configs_no_change() {
+    fn sort_permutation_keeps_ties_in_row_order() {
         let mut store = ColumnarStore::new();
-        store.init(1, 4);
-        store.set_column_float64(0, &[30.0, 25.0, 35.0, 28.0]);
-
-        let mut indices: Vec<u32> = (0..4).collect();
-        sort_indices_columnar(&mut indices, &store, &[]);
-        assert_eq!(indices, vec![0, 1, 2, 3]);
+        store.init(1, 6);
+        store.set_column_float64(0, &[2.0, 1.0, 2.0, f64::NAN, 1.0, f64::NAN]);
+        store.finalize();
+        store.set_sort(sort_by(0, SortDirection::Descending));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2, 1, 4, 3, 5]);
+        store.set_sort(sort_by(0, SortDirection::Ascending));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[3, 5, 1, 4, 0, 2]);
     }
 
     #[test]
-    fn sort_columnar_all_equal_returns_equal() {
+    fn sort_permutations_evicted_and_invalidated() {
         let mut store = ColumnarStore::new();
-        store.init(1, 3);
-        store.set_column_float64(0, &[42.0, 42.0, 42.0]);
+        store.init(SORT_PERMUTATION_SLOTS + 1, 3);
+        for col in 0..=SORT_PERMUTATION_SLOTS {
+            store.set_column_float64(col, &[3.0, 1.0, 2.0]);
+        }
+        store.finalize();
+        for col in 0..=SORT_PERMUTATION_SLOTS {
+            store.set_sort(sort_by(col, SortDirection::Ascending));
+            store.rebuild_view();
+        }
+        assert_eq!(store.sort_permutations.len(), SORT_PERMUTATION_SLOTS);
+        assert!(store.sort_permutations.iter().all(|p| p.column_index != 0));
 
-        let mut indices: Vec<u32> = (0..3).collect();
-        sort_indices_columnar(
-            &mut indices,
-            &store,
-            &[SortConfig {
-                column_index: 0,
-                direction: SortDirection::Ascending,
-            }],
-        );
-        assert_eq!(indices, vec![0, 1, 2]);
+        // A cell update drops the column's permutation; the result stays correct
+        store.update_float64(SORT_PERMUTATION_SLOTS, 0, 0.0);
+        assert_eq!(store.sort_permutations.len(), SORT_PERMUTATION_SLOTS - 1);
+        store.set_sort(sort_by(SORT_PERMUTATION_SLOTS, SortDirection::Descending));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 1, 0]);
     }
 
-    // ── Column filter tests ─────────────────────────────────────────
+    // ── Sliced rebuild tests ────────────────────────────────────────
 
-    fn make_store_for_filter() -> ColumnarStore {
+    fn sliced_rebuild_store() -> ColumnarStore {
+        let n = 3 * REBUILD_STEP_ROWS + 100;
+        let values: Vec<f64> = (0..n).map(|i| ((i * 7919) % 1000) as f64).collect();
         let mut store = ColumnarStore::new();
-        store.init(3, 4);
-        let unique = vec![
-            "".to_string(),
-            "Alice".to_string(),
-            "Bob".to_string(),
-            "Charlie".to_string(),
-            "Dave".to_string(),
-        ];
-        store.set_column_strings(0, &unique, &[1, 2, 3, 4]); // names
-        store.set_column_float64(1, &[30.0, 25.0, 35.0, 28.0]); // ages
-        store.set_column_bool(2, &[1.0, 0.0, 1.0, f64::NAN]); // active
+        store.init(1, n);
+        store.set_column_float64(0, &values);
         store.finalize();
+        store.set_column_filters(vec![ColumnFilter::new(
+            0,
+            FilterOp::Gte,
+            FilterValue::Float64(10.0),
+        )]);
         store
     }
 
     #[test]
-    fn filter_float64_eq() {
-        let store = make_store_for_filter();
-        let mut indices: Vec<u32> = (0..4).collect();
-        filter_indices_columnar(
-            &mut indices,
-            &store,
-            &[ColumnFilter {
-                column_index: 1,
-                op: FilterOp::Eq,
-                value: FilterValue::Float64(30.0),
-            }],
-        );
-        assert_eq!(indices, vec![0]); // Alice=30
+    fn rebuild_view_step_matches_rebuild_view() {
+        let mut expected = sliced_rebuild_store();
+        expected.set_sort(sort_by(0, SortDirection::Descending));
+        expected.rebuild_view();
+
+        let mut store = sliced_rebuild_store();
+        store.set_sort(sort_by(0, SortDirection::Descending));
+        let mut steps = 0;
+        let mut last = 0.0;
+        loop {
+            let progress = store.rebuild_view_step(0.0);
+            assert!(!progress.restarted);
+            assert!(progress.progress >= last);
+            last = progress.progress;
+            steps += 1;
+            if progress.done {
+                break;
+            }
+            // The previous view stays in place until the rebuild completes
+            assert!(store.view_indices().is_empty());
+        }
+        assert!(steps > 4);
+        assert_eq!(store.view_indices(), expected.view_indices());
+        assert_eq!(store.filtered_total(), expected.filtered_total());
+        assert!(store.rebuild_view_step(0.0).done);
     }
 
     #[test]
-    fn filter_float64_gt() {
-        let store = make_store_for_filter();
-        let mut indices: Vec<u32> = (0..4).collect();
-        filter_indices_columnar(
-            &mut indices,
-            &store,
-            &[ColumnFilter {
-                column_index: 1,
-                op: FilterOp::Gt,
-                value: FilterValue::Float64(28.0),
-            }],
-        );
-        assert_eq!(indices, vec![0, 2]); // Alice=30, Charlie=35
+    fn rebuild_view_step_restarts_on_view_change() {
+        let mut store = sliced_rebuild_store();
+        assert!(!store.rebuild_view_step(0.0).done);
+        store.set_column_filters(Vec::new());
+        let progress = store.rebuild_view_step(0.0);
+        assert!(progress.restarted && !progress.done);
+        while !store.rebuild_view_step(0.0).done {}
+        assert_eq!(store.view_indices().len(), store.row_count);
+
+        // `rebuild_view` (e.g. per frame) keeps the current view meanwhile
+        store.set_sort(sort_by(0, SortDirection::Descending));
+        assert!(!store.rebuild_view_step(0.0).done);
+        assert!(!store.view_needs_rebuild());
+        store.rebuild_view();
+        assert_eq!(store.view_indices()[0], 0);
+        while !store.rebuild_view_step(0.0).done {}
+        assert_ne!(store.view_indices()[0], 0);
     }
 
+    // ── Parallel tests ──────────────────────────────────────────────
+
+    #[cfg(feature = "threads")]
     #[test]
-    fn filter_float64_lte() {
-        let store = make_store_for_filter();
-        let mut indices: Vec<u32> = (0..4).collect();
-        filter_indices_columnar(
-            &mut indices,
-            &store,
-            &[ColumnFilter {
-                column_index: 1,
-                op: FilterOp::Lte,
-                value: FilterValue::Float64(28.0),
-            }],
-        );
-        assert_eq!(indices, vec![1, 3]); // Bob=25, Dave=28
+    fn parallel_sort_and_filter_match_sequential() {
+        let n = PARALLEL_MIN_ROWS + 1000;
+        let values: Vec<f64> = (0..n).map(|i| ((i * 7919) % 1000) as f64).collect();
+        let mut store = ColumnarStore::new();
+        store.init(1, n);
+        store.set_column_float64(0, &values);
+        store.finalize();
+
+        let mut indices: Vec<u32> = (0..n as u32).collect();
+        sort_indices_columnar(&mut indices, &store, &sort_by(0, SortDirection::Ascending));
+        let mut expected: Vec<u32> = (0..n as u32).collect();
+        expected.sort_by(|&a, &b| values[a as usize].total_cmp(&values[b as usize]));
+        assert_eq!(indices, expected);
+
+        let filter = ColumnFilter::new(0, FilterOp::Lt, FilterValue::Float64(100.0));
+        let mut filtered: Vec<u32> = (0..n as u32).collect();
+        filter_indices_columnar(&mut filtered, &store, std::slice::from_ref(&filter));
+        let expected: Vec<u32> = (0..n as u32)
+            .filter(|&i| values[i as usize] < 100.0)
+            .collect();
+        assert_eq!(filtered, expected);
+        let bitmap = filter_bitmap_columnar(&store, &filter);
+        assert_eq!(bitmap.iter_ones().collect::<Vec<_>>(), expected);
     }
 
+    #[cfg(feature = "threads")]
     #[test]
-    fn filter_float64_neq() {
-        let store = make_store_for_filter();
-        let mut indices: Vec<u32> = (0..4).collect();
-        filter_indices_columnar(
-            &mut indices,
-            &store,
-            &[ColumnFilter {
-                column_index: 1,
-                op: FilterOp::Neq,
-                value: FilterValue::Float64(30.0),
-            }],
-        );
-        assert_eq!(indices, vec![1, 2, 3]); // all except Alice
+    fn parallel_string_sort_and_global_filter_match_sequential() {
+        let n = PARALLEL_MIN_ROWS + 1000;
+        let unique: Vec<String> = ["pear", "Apple", "fig", "banana"]
+            .map(String::from)
+            .to_vec();
+        let ids: Vec<u32> = (0..n as u32).map(|i| (i * 7) % 4).collect();
+        let mut store = ColumnarStore::new();
+        store.init(1, n);
+        store.set_column_strings(0, &unique, &ids);
+        store.finalize();
+
+        let mut indices: Vec<u32> = (0..n as u32).collect();
+        sort_indices_columnar(&mut indices, &store, &sort_by(0, SortDirection::Descending));
+        let mut expected: Vec<u32> = (0..n as u32).collect();
+        expected.sort_by(|&a, &b| {
+            unique[ids[b as usize] as usize].cmp(&unique[ids[a as usize] as usize])
+        });
+        assert_eq!(indices, expected);
+
+        let mut filtered: Vec<u32> = (0..n as u32).collect();
+        let query = GlobalFilter {
+            query: "AN".to_string(),
+        };
+        global_filter_indices(&mut filtered, &store, &query);
+        let expected: Vec<u32> = (0..n as u32).filter(|&i| ids[i as usize] == 3).collect();
+        assert_eq!(filtered, expected);
+
+        // Fewer rows than PARALLEL_MIN_ROWS filter on the calling thread
+        let mut sequential: Vec<u32> = (0..1000).collect();
+        global_filter_indices(&mut sequential, &store, &query);
+        assert_eq!(sequential, expected[..250]);
     }
 
+    // ── Collation tests ─────────────────────────────────────────────
+
+    #[cfg(feature = "collation")]
     #[test]
-    fn filter_float64_gte_lt() {
-        let store = make_store_for_filter();
-        let mut indices: Vec<u32> = (0..4).collect();
-        filter_indices_columnar(
-            &mut indices,
-            &store,
-            &[ColumnFilter {
-                column_index: 1,
-                op: FilterOp::Gte,
-                value: FilterValue::Float64(28.0),
-            }],
-        );
-        assert_eq!(indices, vec![0, 2, 3]); // Alice=30, Charlie=35, Dave=28
+    fn collation_sorts_umlauts_with_base_letter() {
+        use crate::collation::CollationOptions;
 
-        let mut indices2: Vec<u32> = (0..4).collect();
-        filter_indices_columnar(
-            &mut indices2,
-            &store,
-            &[ColumnFilter {
-                column_index: 1,
-                op: FilterOp::Lt,
-                value: FilterValue::Float64(28.0),
-            }],
-        );
-        assert_eq!(indices2, vec![1]); // Bob=25
+        let mut store = ColumnarStore::new();
+        store.init(1, 3);
+        let unique = vec![
+            String::new(),
+            "Zebra".to_string(),
+            "Ärzte".to_string(),
+            "Apfel".to_string(),
+        ];
+        store.set_column_strings(0, &unique, &[1, 2, 3]);
+        store.finalize();
+        store.set_sort(vec![SortConfig {
+            column_index: 0,
+            direction: SortDirection::Ascending,
+            ..SortConfig::default()
+        }]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 0, 1]); // byte-wise: Apfel, Zebra, Ärzte
+
+        let collation = Collation::new("de", CollationOptions::default()).unwrap();
+        store.set_collation(None, Some(collation));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 1, 0]); // Apfel, Ärzte, Zebra
+
+        // Clearing the default restores byte-wise order
+        store.set_collation(None, None);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 0, 1]);
     }
 
-    #[test]
-    fn filter_string_eq() {
-        let store = make_store_for_filter();
-        let mut indices: Vec<u32> = (0..4).collect();
-        filter_indices_columnar(
-            &mut indices,
-            &store,
-            &[ColumnFilter {
-                column_index: 0,
-                op: FilterOp::Eq,
-                value: FilterValue::String("Bob".to_string()),
-            }],
-        );
-        assert_eq!(indices, vec![1]);
+    // ── Condition ordering tests ────────────────────────────────────
+
+    fn age_filter(op: FilterOp, age: f64) -> ColumnFilter {
+        ColumnFilter::new(1, op, FilterValue::Float64(age))
     }
 
-    #[test]
-    fn filter_string_contains() {
-        let store = make_store_for_filter();
-        let mut indices: Vec<u32> = (0..4).collect();
-        filter_indices_columnar(
-            &mut indices,
-            &store,
-            &[ColumnFilter {
-                column_index: 0,
-                op: FilterOp::Contains,
-                value: FilterValue::String("li".to_string()), // Alice, Charlie
-            }],
-        );
-        assert_eq!(indices, vec![0, 2]);
+    fn name_contains(s: &str) -> ColumnFilter {
+        ColumnFilter::new(0, FilterOp::Contains, FilterValue::String(s.to_string()))
     }
 
-    #[test]
-    fn filter_string_starts_with() {
-        let store = make_store_for_filter();
-        let mut indices: Vec<u32> = (0..4).collect();
-        filter_indices_columnar(
-            &mut indices,
-            &store,
-            &[ColumnFilter {
-                column_index: 0,
-                op: FilterOp::StartsWith,
-                value: FilterValue::String("ch".to_string()),
-            }],
-        );
-        assert_eq!(indices, vec![2]); // Charlie
+    fn stats_order(store: &ColumnarStore) -> Vec<(FilterOp, usize)> {
+        store
+            .last_view_stats()
+            .conditions
+            .iter()
+            .map(|c| match c.kind {
+                ConditionKind::Column(op) => (op, c.rows_scanned),
+                _ => unreachable!(),
+            })
+            .collect()
     }
 
     #[test]
-    fn filter_string_ends_with() {
-        let store = make_store_for_filter();
-        let mut indices: Vec<u32> = (0..4).collect();
-        filter_indices_columnar(
-            &mut indices,
-            &store,
-            &[ColumnFilter {
-                column_index: 0,
-                op: FilterOp::EndsWith,
-                value: FilterValue::String("ve".to_string()),
-            }],
+    fn cheap_conditions_run_before_contains() {
+        let mut store = make_store_for_filter();
+        store.set_column_filters(vec![name_contains("a"), age_filter(FilterOp::Gt, 29.0)]);
+        store.rebuild_view();
+        // Numeric comparison scans all 4 rows; contains only sees its 2 survivors
+        assert_eq!(
+            stats_order(&store),
+            vec![(FilterOp::Gt, 4), (FilterOp::Contains, 2)]
         );
-        assert_eq!(indices, vec![3]); // Dave
+        assert_eq!(store.view_indices(), &[0, 2]);
     }
 
     #[test]
-    fn filter_bool_eq() {
-        let store = make_store_for_filter();
-        let mut indices: Vec<u32> = (0..4).collect();
-        filter_indices_columnar(
-            &mut indices,
-            &store,
-            &[ColumnFilter {
-                column_index: 2,
-                op: FilterOp::Eq,
-                value: FilterValue::Bool(true),
-            }],
+    fn observed_selectivity_reorders_conditions() {
+        let mut store = make_store_for_filter();
+        // Gt 0 passes every row; Lt 26 passes one
+        store.set_column_filters(vec![
+            age_filter(FilterOp::Gt, 0.0),
+            age_filter(FilterOp::Lt, 26.0),
+        ]);
+        store.rebuild_view();
+        assert_eq!(
+            stats_order(&store),
+            vec![(FilterOp::Gt, 4), (FilterOp::Lt, 4)]
         );
-        assert_eq!(indices, vec![0, 2]); // Alice, Charlie (NaN excluded)
-    }
 
-    #[test]
-    fn filter_bool_neq() {
-        let store = make_store_for_filter();
-        let mut indices: Vec<u32> = (0..4).collect();
-        filter_indices_columnar(
-            &mut indices,
-            &store,
-            &[ColumnFilter {
-                column_index: 2,
-                op: FilterOp::Neq,
-                value: FilterValue::Bool(true),
-            }],
+        // New data: estimates from the previous run put the selective condition first
+        store.finalize();
+        store.rebuild_view();
+        assert_eq!(
+            stats_order(&store),
+            vec![(FilterOp::Lt, 4), (FilterOp::Gt, 1)]
         );
-        assert_eq!(indices, vec![1]); // Bob=false (NaN excluded)
+        assert_eq!(store.view_indices(), &[1]);
     }
 
     #[test]
-    fn filter_nan_always_excluded() {
-        let mut store = ColumnarStore::new();
-        store.init(1, 3);
-        store.set_column_float64(0, &[f64::NAN, 5.0, f64::NAN]);
-        store.finalize();
+    fn partial_bitmaps_reused_only_within_domain() {
+        let mut store = make_store_for_filter();
+        let age = age_filter(FilterOp::Gt, 29.0);
+        store.set_column_filters(vec![age.clone(), name_contains("e")]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2]); // Alice, Charlie
 
-        let mut indices: Vec<u32> = (0..3).collect();
-        filter_indices_columnar(
-            &mut indices,
-            &store,
-            &[ColumnFilter {
-                column_index: 0,
-                op: FilterOp::Gte,
-                value: FilterValue::Float64(0.0),
-            }],
+        // Same conditions: both served from cache
+        store.set_sort(Vec::new());
+        store.rebuild_view();
+        assert!(store.last_view_stats().conditions.iter().all(|c| c.cached));
+
+        // Dropping the age filter widens the domain, so "contains" is re-evaluated
+        store.set_column_filters(vec![name_contains("e")]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2, 3]); // Alice, Charlie, Dave
+        assert!(!store.last_view_stats().conditions[0].cached);
+        assert_eq!(store.filter_cache.len(), 1);
+        assert!(store.filter_cache[0].domain.is_none());
+
+        // Narrowing again reuses the complete bitmap
+        store.set_column_filters(vec![name_contains("e"), age]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 2]);
+        assert_eq!(
+            store.last_view_stats().conditions[0].kind,
+            ConditionKind::Column(FilterOp::Contains)
         );
-        assert_eq!(indices, vec![1]); // only row 1 (5.0)
+        assert!(store.last_view_stats().conditions[0].cached);
     }
 
+    // ── Facet tests ─────────────────────────────────────────────────
+
     #[test]
-    fn filter_multiple_columns_and_logic() {
-        let store = make_store_for_filter();
-        let mut indices: Vec<u32> = (0..4).collect();
-        // name contains "li" AND age > 28
-        filter_indices_columnar(
-            &mut indices,
-            &store,
-            &[
-                ColumnFilter {
-                    column_index: 0,
-                    op: FilterOp::Contains,
-                    value: FilterValue::String("li".to_string()),
+    fn facets_exclude_own_column_filter() {
+        let mut store = make_store_for_filter();
+        store.set_column_filters(vec![
+            ColumnFilter::new(2, FilterOp::Eq, FilterValue::Bool(true)),
+            ColumnFilter::new(1, FilterOp::Gt, FilterValue::Float64(26.0)),
+        ]);
+
+        // Facets for "active" ignore the active filter but respect age > 26:
+        // Alice(true), Charlie(true), Dave(null)
+        let facets = store.filter_facets(2);
+        assert_eq!(
+            facets,
+            vec![
+                FacetCount {
+                    value: Some(FilterValue::Bool(true)),
+                    count: 2
                 },
-                ColumnFilter {
-                    column_index: 1,
-                    op: FilterOp::Gt,
-                    value: FilterValue::Float64(28.0),
+                FacetCount {
+                    value: None,
+                    count: 1
                 },
-            ],
+            ]
         );
-        assert_eq!(indices, vec![0, 2]); // Alice(30) and Charlie(35) both contain "li" and > 28
-    }
 
-    #[test]
-    fn filter_empty_filters_no_change() {
-        let store = make_store_for_filter();
-        let mut indices: Vec<u32> = (0..4).collect();
-        filter_indices_columnar(&mut indices, &store, &[]);
-        assert_eq!(indices, vec![0, 1, 2, 3]);
+        // Facets for "age" ignore age > 26 but respect active == true
+        let facets = store.filter_facets(1);
+        assert_eq!(
+            facets,
+            vec![
+                FacetCount {
+                    value: Some(FilterValue::Float64(30.0)),
+                    count: 1
+                },
+                FacetCount {
+                    value: Some(FilterValue::Float64(35.0)),
+                    count: 1
+                },
+            ]
+        );
     }
 
     #[test]
-    fn filter_invalid_column_excludes_all() {
-        let store = make_store_for_filter();
-        let mut indices: Vec<u32> = (0..4).collect();
-        filter_indices_columnar(
-            &mut indices,
-            &store,
-            &[ColumnFilter {
-                column_index: 99,
-                op: FilterOp::Eq,
-                value: FilterValue::Float64(30.0),
-            }],
+    fn facets_string_counts_sorted_by_count() {
+        let mut store = ColumnarStore::new();
+        store.init(1, 6);
+        let unique = vec![
+            String::new(),
+            "red".to_string(),
+            "blue".to_string(),
+            "green".to_string(),
+        ];
+        store.set_column_strings(0, &unique, &[1, 2, 1, 3, 1, 0]);
+        store.finalize();
+
+        // The empty string is the null facet
+        let facets = store.filter_facets(0);
+        let values: Vec<(Option<String>, usize)> = facets
+            .into_iter()
+            .map(|f| match f.value {
+                Some(FilterValue::String(s)) => (Some(s), f.count),
+                None => (None, f.count),
+                other => panic!("unexpected facet value {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                (Some("red".to_string()), 3),
+                (None, 1),
+                (Some("blue".to_string()), 1),
+                (Some("green".to_string()), 1),
+            ]
         );
-        assert_eq!(indices, Vec::<u32>::new());
     }
 
     #[test]
-    fn filter_string_ops_on_float_col_returns_false() {
-        let store = make_store_for_filter();
-        let mut indices: Vec<u32> = (0..4).collect();
-        filter_indices_columnar(
-            &mut indices,
-            &store,
-            &[ColumnFilter {
-                column_index: 1, // float64 column
-                op: FilterOp::Contains,
-                value: FilterValue::Float64(30.0),
-            }],
-        );
-        assert_eq!(indices, Vec::<u32>::new());
+    fn facets_respect_global_filter_and_invalid_column() {
+        let mut store = make_store_for_filter();
+        store.set_global_filter(Some(GlobalFilter {
+            query: "li".to_string(),
+        }));
+        let facets = store.filter_facets(1);
+        assert_eq!(facets.iter().map(|f| f.count).sum::<usize>(), 2); // Alice, Charlie
+        assert!(store.filter_facets(99).is_empty());
     }
 
     // ── Global filter tests ─────────────────────────────────────────
@@ -1231,15 +7734,16 @@ mod tests {
     fn rebuild_view_filter_then_sort() {
         let mut store = make_store_for_filter();
         // Filter: age >= 28 → Alice(30), Charlie(35), Dave(28)
-        store.set_column_filters(vec![ColumnFilter {
-            column_index: 1,
-            op: FilterOp::Gte,
-            value: FilterValue::Float64(28.0),
-        }]);
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Gte,
+            FilterValue::Float64(28.0),
+        )]);
         // Sort: age ascending → Dave(28), Alice(30), Charlie(35)
         store.set_sort(vec![SortConfig {
             column_index: 1,
             direction: SortDirection::Ascending,
+            ..SortConfig::default()
         }]);
         store.rebuild_view();
         assert_eq!(store.view_indices(), &[3, 0, 2]); // Dave=3, Alice=0, Charlie=2
@@ -1255,6 +7759,7 @@ mod tests {
         store.set_sort(vec![SortConfig {
             column_index: 1,
             direction: SortDirection::Descending,
+            ..SortConfig::default()
         }]);
         store.rebuild_view();
         // Desc by age: Charlie(35)=2, Alice(30)=0, Dave(28)=3
@@ -1266,11 +7771,11 @@ mod tests {
         let mut store = make_store_for_filter();
         store.rebuild_view(); // clears dirty
 
-        store.set_column_filters(vec![ColumnFilter {
-            column_index: 1,
-            op: FilterOp::Gt,
-            value: FilterValue::Float64(30.0),
-        }]);
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Gt,
+            FilterValue::Float64(30.0),
+        )]);
         store.rebuild_view();
         assert_eq!(store.view_indices(), &[2]); // only Charlie=35
     }
@@ -1294,11 +7799,11 @@ mod tests {
         filter_indices_columnar(
             &mut indices,
             &store,
-            &[ColumnFilter {
-                column_index: 0,
-                op: FilterOp::Neq,
-                value: FilterValue::String("Alice".to_string()),
-            }],
+            &[ColumnFilter::new(
+                0,
+                FilterOp::Neq,
+                FilterValue::String("Alice".to_string()),
+            )],
         );
         assert_eq!(indices, vec![1, 2, 3]); // Bob, Charlie, Dave
     }
@@ -1356,11 +7861,11 @@ mod tests {
     fn test_pagination_with_filter() {
         let mut store = make_store_for_filter();
         // Filter: age >= 28 → Alice(30), Charlie(35), Dave(28) → 3 rows
-        store.set_column_filters(vec![ColumnFilter {
-            column_index: 1,
-            op: FilterOp::Gte,
-            value: FilterValue::Float64(28.0),
-        }]);
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Gte,
+            FilterValue::Float64(28.0),
+        )]);
         store.set_pagination(Some(0), Some(2)); // first 2 of 3
         store.rebuild_view();
         assert_eq!(store.filtered_total(), 3);
@@ -1371,15 +7876,16 @@ mod tests {
     fn test_pagination_with_filter_and_sort() {
         let mut store = make_store_for_filter();
         // Filter: age >= 28 → Alice(30), Charlie(35), Dave(28)
-        store.set_column_filters(vec![ColumnFilter {
-            column_index: 1,
-            op: FilterOp::Gte,
-            value: FilterValue::Float64(28.0),
-        }]);
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Gte,
+            FilterValue::Float64(28.0),
+        )]);
         // Sort ascending → Dave(28)=3, Alice(30)=0, Charlie(35)=2
         store.set_sort(vec![SortConfig {
             column_index: 1,
             direction: SortDirection::Ascending,
+            ..SortConfig::default()
         }]);
         store.set_pagination(Some(1), Some(2)); // page 1 → only Charlie(35)=2
         store.rebuild_view();
@@ -1432,11 +7938,11 @@ mod tests {
         filter_indices_columnar(
             &mut indices,
             &store,
-            &[ColumnFilter {
-                column_index: 0,
-                op: FilterOp::Gt,
-                value: FilterValue::String("Bob".to_string()),
-            }],
+            &[ColumnFilter::new(
+                0,
+                FilterOp::Gt,
+                FilterValue::String("Bob".to_string()),
+            )],
         );
         assert_eq!(indices, vec![2, 3]); // Charlie, Dave
 
@@ -1444,11 +7950,11 @@ mod tests {
         filter_indices_columnar(
             &mut indices2,
             &store,
-            &[ColumnFilter {
-                column_index: 0,
-                op: FilterOp::Gte,
-                value: FilterValue::String("Charlie".to_string()),
-            }],
+            &[ColumnFilter::new(
+                0,
+                FilterOp::Gte,
+                FilterValue::String("Charlie".to_string()),
+            )],
         );
         assert_eq!(indices2, vec![2, 3]); // Charlie, Dave
 
@@ -1456,11 +7962,11 @@ mod tests {
         filter_indices_columnar(
             &mut indices3,
             &store,
-            &[ColumnFilter {
-                column_index: 0,
-                op: FilterOp::Lt,
-                value: FilterValue::String("Bob".to_string()),
-            }],
+            &[ColumnFilter::new(
+                0,
+                FilterOp::Lt,
+                FilterValue::String("Bob".to_string()),
+            )],
         );
         assert_eq!(indices3, vec![0]); // Alice
     }
@@ -1472,7 +7978,7 @@ mod tests {
         let mut store = ColumnarStore::new();
         store.init(2, 2);
         store.set_column_float64(0, &[10.0, 20.0]);
-        let unique = vec!["".to_string(), "Alice".to_string(), "Bob".to_string()];
+        let unique = vec![String::new(), "Alice".to_string(), "Bob".to_string()];
         store.set_column_strings(1, &unique, &[1, 2]);
         store.finalize();
 
@@ -1540,13 +8046,13 @@ mod tests {
     fn append_strings_merges_intern() {
         let mut store = ColumnarStore::new();
         store.init(1, 2);
-        let unique = vec!["".to_string(), "Alice".to_string(), "Bob".to_string()];
+        let unique = vec![String::new(), "Alice".to_string(), "Bob".to_string()];
         store.set_column_strings(0, &unique, &[1, 2]);
         store.finalize();
 
         store.begin_append(2);
         // New batch: "" (null), "Bob" (existing), "Charlie" (new)
-        let new_unique = vec!["".to_string(), "Bob".to_string(), "Charlie".to_string()];
+        let new_unique = vec![String::new(), "Bob".to_string(), "Charlie".to_string()];
         let new_ids: Vec<u32> = vec![2, 1]; // Charlie, Bob
         store.append_column_strings(0, 2, &new_unique, &new_ids);
 
@@ -1567,13 +8073,13 @@ mod tests {
         let mut store = ColumnarStore::new();
         store.init(1, 1);
         // Initial: "" (0), "Alpha" (1)
-        let unique = vec!["".to_string(), "Alpha".to_string()];
+        let unique = vec![String::new(), "Alpha".to_string()];
         store.set_column_strings(0, &unique, &[1]);
         store.finalize();
 
         store.begin_append(2);
         // New batch has different local ordering: "" (0), "Beta" (1), "Alpha" (2)
-        let new_unique = vec!["".to_string(), "Beta".to_string(), "Alpha".to_string()];
+        let new_unique = vec![String::new(), "Beta".to_string(), "Alpha".to_string()];
         let new_ids: Vec<u32> = vec![1, 2]; // Beta, Alpha (local IDs)
         store.append_column_strings(0, 1, &new_unique, &new_ids);
 
@@ -1602,6 +8108,7 @@ mod tests {
         store.set_sort(vec![SortConfig {
             column_index: 0,
             direction: SortDirection::Ascending,
+            ..SortConfig::default()
         }]);
         store.rebuild_view();
 
@@ -1620,11 +8127,11 @@ mod tests {
         store.append_column_float64(0, 2, &[20.0, 5.0]);
         store.finalize_append();
 
-        store.set_column_filters(vec![ColumnFilter {
-            column_index: 0,
-            op: FilterOp::Gte,
-            value: FilterValue::Float64(20.0),
-        }]);
+        store.set_column_filters(vec![ColumnFilter::new(
+            0,
+            FilterOp::Gte,
+            FilterValue::Float64(20.0),
+        )]);
         store.rebuild_view();
 
         // 30.0(0), 20.0(2)
@@ -0,0 +1,168 @@
+//! Batched engine commands in a compact binary format, for applying several
+//! operations in one call (e.g. from a Web Worker, with the buffer transferred
+//! by `postMessage`).
+//!
+//! Layout: the magic `RWTC`, a version byte, a command count, then each command
+//! as a tag byte followed by its fields. Sorts and filters are encoded as in
+//! `state`; counts and indices are LEB128 varints and floats little-endian f64.
+
+use crate::state::{Reader, Writer};
+use crate::types::{ColumnFilter, SortConfig};
+
+const MAGIC: &[u8; 4] = b"RWTC";
+
+/// Format version written by `encode`; `decode` reads this and older versions.
+pub const VERSION: u8 = 1;
+
+/// One operation of a batch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Replace the sort (empty = unsorted).
+    SetSort(Vec<SortConfig>),
+    /// Replace the column filters.
+    SetFilters(Vec<ColumnFilter>),
+    /// Set or clear the global text filter.
+    SetGlobalFilter(Option<String>),
+    /// Scroll offsets in px.
+    Scroll { top: f64, left: f64 },
+    /// Viewport size in px.
+    Resize { width: f64, height: f64 },
+    /// Resize a column to `width` px, taking the difference from its right
+    /// neighbor when `neighbor` is set (see `column_sizing::resize_column`).
+    ResizeColumn {
+        column_index: usize,
+        width: f64,
+        neighbor: bool,
+    },
+}
+
+/// Serialize `commands` as the current format version.
+pub fn encode(commands: &[Command]) -> Vec<u8> {
+    let mut w = Writer(MAGIC.to_vec());
+    w.0.push(VERSION);
+    w.len(commands.len());
+    for command in commands {
+        match command {
+            Command::SetSort(sorts) => {
+                w.0.push(0);
+                w.sort_configs(sorts);
+            }
+            Command::SetFilters(filters) => {
+                w.0.push(1);
+                w.column_filters(filters);
+            }
+            Command::SetGlobalFilter(query) => {
+                w.0.push(2);
+                w.opt_str(query.as_deref());
+            }
+            Command::Scroll { top, left } => {
+                w.0.push(3);
+                w.f64(*top);
+                w.f64(*left);
+            }
+            Command::Resize { width, height } => {
+                w.0.push(4);
+                w.f64(*width);
+                w.f64(*height);
+            }
+            Command::ResizeColumn {
+                column_index,
+                width,
+                neighbor,
+            } => {
+                w.0.push(5);
+                w.len(*column_index);
+                w.f64(*width);
+                w.0.push(u8::from(*neighbor));
+            }
+        }
+    }
+    w.0
+}
+
+/// Parse bytes written by `encode` of this or an older version.
+pub fn decode(bytes: &[u8]) -> Result<Vec<Command>, String> {
+    let mut r = Reader::new(bytes);
+    if r.take(4)? != MAGIC {
+        return Err("not a command batch".to_string());
+    }
+    let version = r.byte()?;
+    if version == 0 || version > VERSION {
+        return Err(format!("unsupported command batch version {version}"));
+    }
+    (0..r.len()?)
+        .map(|_| {
+            Ok(match r.byte()? {
+                0 => Command::SetSort(r.sort_configs()?),
+                1 => Command::SetFilters(r.column_filters()?),
+                2 => Command::SetGlobalFilter(r.opt_str()?),
+                3 => Command::Scroll {
+                    top: r.f64()?,
+                    left: r.f64()?,
+                },
+                4 => Command::Resize {
+                    width: r.f64()?,
+                    height: r.f64()?,
+                },
+                5 => Command::ResizeColumn {
+                    column_index: r.len()?,
+                    width: r.f64()?,
+                    neighbor: r.byte()? != 0,
+                },
+                tag => return Err(format!("unknown command tag {tag}")),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FilterOp, FilterValue, SortDirection};
+
+    fn batch() -> Vec<Command> {
+        vec![
+            Command::SetSort(vec![SortConfig {
+                column_index: 2,
+                direction: SortDirection::Descending,
+                ..SortConfig::default()
+            }]),
+            Command::SetFilters(vec![ColumnFilter::new(
+                1,
+                FilterOp::Contains,
+                FilterValue::String("ab".to_string()),
+            )]),
+            Command::SetGlobalFilter(None),
+            Command::Scroll {
+                top: 1200.0,
+                left: 0.0,
+            },
+            Command::Resize {
+                width: 800.0,
+                height: 600.0,
+            },
+            Command::ResizeColumn {
+                column_index: 3,
+                width: 140.0,
+                neighbor: true,
+            },
+        ]
+    }
+
+    #[test]
+    fn round_trips() {
+        let bytes = encode(&batch());
+        assert_eq!(&bytes[..6], b"RWTC\x01\x06");
+        assert_eq!(decode(&bytes).unwrap(), batch());
+        assert_eq!(decode(&encode(&[])).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn rejects_foreign_truncated_and_unknown_bytes() {
+        let bytes = encode(&batch());
+        assert!(decode(b"RWTS\x01\x00").is_err());
+        assert!(decode(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode(b"RWTC\x01\x01\x09").is_err());
+        assert!(decode(b"RWTC\x02\x00").is_err());
+    }
+}
@@ -0,0 +1,171 @@
+//! Duplicate rows: rows sharing the values of a set of key columns.
+
+use std::collections::HashMap;
+
+use crate::columnar_store::{ColumnData, ColumnarStore};
+use crate::radix_sort::f64_key;
+
+/// Which row of a duplicate group `dedupe` keeps, in data row order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keep {
+    First,
+    Last,
+}
+
+/// Push the key of data row `row` in column `col` to `key`. Nulls are equal to
+/// each other; list cells match by their items in order.
+fn push_key(store: &ColumnarStore, col: usize, row: usize, key: &mut Vec<u64>) {
+    let number = |n: f64| if n.is_nan() { u64::MAX } else { f64_key(n) };
+    match store.data.get(col) {
+        Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => key.push(number(v[row])),
+        Some(ColumnData::Strings { ids, .. }) => key.push(u64::from(ids[row])),
+        Some(data @ ColumnData::StringList { .. }) => {
+            let items = data.list_ids(row);
+            key.push(items.len() as u64);
+            key.extend(items.iter().map(|&id| u64::from(id)));
+        }
+        Some(data @ ColumnData::Float64List { .. }) => {
+            let items = data.series(row);
+            key.push(items.len() as u64);
+            key.extend(items.iter().map(|&n| number(n)));
+        }
+        None => {}
+    }
+}
+
+/// Check that `cols` names at least one column and only columns of `store`;
+/// otherwise every row would share the same key.
+fn check_key_columns(store: &ColumnarStore, cols: &[usize]) -> Result<(), String> {
+    if cols.is_empty() {
+        return Err("no key columns".to_string());
+    }
+    if let Some(col) = cols.iter().find(|&&col| col >= store.data.len()) {
+        return Err(format!("key column {col} out of range"));
+    }
+    Ok(())
+}
+
+/// Groups of two or more of `rows` with equal values in every column of
+/// `cols`, each in data row order, ordered by their first row. Fails when
+/// `cols` is empty or out of range.
+pub fn find_duplicates(
+    store: &ColumnarStore,
+    rows: &[u32],
+    cols: &[usize],
+) -> Result<Vec<Vec<u32>>, String> {
+    check_key_columns(store, cols)?;
+    let mut sorted = rows.to_vec();
+    sorted.sort_unstable();
+    let mut group_ids: HashMap<Vec<u64>, usize> = HashMap::new();
+    let mut groups: Vec<Vec<u32>> = Vec::new();
+    let mut key = Vec::with_capacity(cols.len());
+    for row in sorted {
+        key.clear();
+        for &col in cols {
+            push_key(store, col, row as usize, &mut key);
+        }
+        if let Some(&gid) = group_ids.get(&key) {
+            groups[gid].push(row);
+        } else {
+            group_ids.insert(key.clone(), groups.len());
+            groups.push(vec![row]);
+        }
+    }
+    groups.retain(|group| group.len() > 1);
+    Ok(groups)
+}
+
+/// Remove all but one row of each duplicate group among the rows passing the
+/// filters (see [`find_duplicates`]).
+///
+/// Tree ancestors shown only as context are left alone. Returns the removed
+/// data rows, ascending.
+pub fn dedupe(store: &mut ColumnarStore, cols: &[usize], keep: Keep) -> Result<Vec<u32>, String> {
+    let groups = find_duplicates(store, store.matched_rows(), cols)?;
+    let mut removed: Vec<u32> = groups
+        .iter()
+        .flat_map(|group| match keep {
+            Keep::First => &group[1..],
+            Keep::Last => &group[..group.len() - 1],
+        })
+        .copied()
+        .collect();
+    removed.sort_unstable();
+    for &row in &removed {
+        store.remove_row(row as usize);
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> ColumnarStore {
+        let mut store = ColumnarStore::new();
+        store.init(2, 6);
+        let names = ["a", "b"].map(String::from);
+        store.set_column_strings(0, &names, &[0, 1, 0, 0, 1, 0]);
+        store.set_column_float64(1, &[1.0, 1.0, 1.0, f64::NAN, 2.0, f64::NAN]);
+        store.finalize();
+        store.rebuild_view();
+        store
+    }
+
+    #[test]
+    fn groups_rows_by_key_tuple() {
+        let store = sample();
+        let all: Vec<u32> = (0..6).collect();
+        assert_eq!(
+            find_duplicates(&store, &all, &[0]),
+            Ok(vec![vec![0, 2, 3, 5], vec![1, 4]])
+        );
+        assert_eq!(
+            find_duplicates(&store, &all, &[0, 1]),
+            Ok(vec![vec![0, 2], vec![3, 5]])
+        );
+        assert_eq!(
+            find_duplicates(&store, &[5, 4, 0, 2], &[0, 1]),
+            Ok(vec![vec![0, 2]])
+        );
+
+        // No key, or a missing column, would make every row a duplicate
+        assert!(find_duplicates(&store, &all, &[]).is_err());
+        assert!(find_duplicates(&store, &all, &[0, 2]).is_err());
+    }
+
+    #[test]
+    fn dedupe_removes_all_but_one_per_group() {
+        let mut store = sample();
+        assert_eq!(dedupe(&mut store, &[0, 1], Keep::Last), Ok(vec![0, 3]));
+        assert!(store.is_row_removed(0) && !store.is_row_removed(2));
+
+        let mut store = sample();
+        assert!(dedupe(&mut store, &[], Keep::First).is_err());
+        assert_eq!(store.filtered_rows().len(), 6);
+        assert_eq!(dedupe(&mut store, &[0], Keep::First), Ok(vec![2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn dedupe_skips_tree_ancestors_of_matches() {
+        use crate::types::{ColumnFilter, FilterOp, FilterValue};
+
+        // Rows 0 and 2 ("a", 1) are collapsed parents of the rows matching `$1 = 2`
+        let mut store = ColumnarStore::new();
+        store.init(2, 5);
+        let names = ["a", "b"].map(String::from);
+        store.set_column_strings(0, &names, &[0, 1, 0, 1, 1]);
+        store.set_column_float64(1, &[1.0, 2.0, 1.0, 2.0, 2.0]);
+        store.finalize();
+        let root = crate::tree::NO_PARENT;
+        store.set_tree_structure(Some(vec![root, 0, root, 2, 2]));
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Eq,
+            FilterValue::Float64(2.0),
+        )]);
+        store.rebuild_view();
+        assert_eq!(dedupe(&mut store, &[0, 1], Keep::First), Ok(vec![3, 4]));
+        assert!(!store.is_row_removed(0) && !store.is_row_removed(2));
+    }
+}
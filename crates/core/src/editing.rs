@@ -0,0 +1,386 @@
+//! Cell edit sessions: text typed into one cell at a time, coerced to the
+//! column's type on commit into a `CellEdit` that can be reverted. Pasted
+//! blocks of cells are coerced the same way.
+
+use crate::columnar_store::{ColumnData, ColumnarStore};
+
+/// A cell value as the in-place update path writes it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellValue {
+    /// Float64 or Bool cell (NaN = null).
+    Number(f64),
+    /// String cell (empty = null).
+    Text(String),
+}
+
+impl CellValue {
+    /// Equal values, with null numbers equal to each other.
+    fn same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Number(a), Self::Number(b)) => {
+                a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan())
+            }
+            _ => self == other,
+        }
+    }
+}
+
+/// A written cell: data row, column, and its value before and after.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellEdit {
+    pub row: usize,
+    pub col: usize,
+    before: CellValue,
+    after: CellValue,
+}
+
+impl CellEdit {
+    /// Write the value from before the edit back.
+    pub fn revert(&self, store: &mut ColumnarStore) {
+        write(store, self.row, self.col, &self.before);
+    }
+
+    /// Write the edited value again.
+    pub fn apply(&self, store: &mut ColumnarStore) {
+        write(store, self.row, self.col, &self.after);
+    }
+}
+
+/// The cell being edited and its pending text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EditSession {
+    row: usize,
+    col: usize,
+    value: String,
+}
+
+/// Edit session over a `ColumnarStore`.
+///
+/// `begin` opens a cell with its current value as text, `set_value` replaces the
+/// text, and `commit` coerces it to the column type and writes it through the
+/// store's cell update path (so the next rebuild re-positions just that row).
+/// A failed commit leaves the session open.
+#[derive(Debug, Clone, Default)]
+pub struct Editor {
+    session: Option<EditSession>,
+}
+
+impl Editor {
+    /// Start editing data row `row`, column `col`, replacing any open session.
+    /// Fails for missing cells and multi-value columns.
+    pub fn begin(&mut self, store: &ColumnarStore, row: usize, col: usize) -> Result<(), String> {
+        let value = match read(store, row, col)? {
+            CellValue::Number(v) if v.is_nan() => String::new(),
+            CellValue::Number(v) if matches!(store.data[col], ColumnData::Bool(_)) => {
+                (v != 0.0).to_string()
+            }
+            CellValue::Number(v) => v.to_string(),
+            CellValue::Text(text) => text,
+        };
+        self.session = Some(EditSession { row, col, value });
+        Ok(())
+    }
+
+    /// `(row, col)` of the cell being edited.
+    pub fn cell(&self) -> Option<(usize, usize)> {
+        self.session.as_ref().map(|s| (s.row, s.col))
+    }
+
+    /// Pending text of the cell being edited.
+    pub fn value(&self) -> Option<&str> {
+        self.session.as_ref().map(|s| s.value.as_str())
+    }
+
+    /// Replace the pending text; false without a session.
+    pub fn set_value(&mut self, value: &str) -> bool {
+        self.session.as_mut().is_some_and(|s| {
+            s.value = value.to_string();
+            true
+        })
+    }
+
+    /// Coerce the pending text to the column type and write it, closing the
+    /// session. Returns the edit (`None` when the value didn't change), or the
+    /// reason the text was rejected (the session stays open).
+    pub fn commit(&mut self, store: &mut ColumnarStore) -> Result<Option<CellEdit>, String> {
+        let session = self.session.as_ref().ok_or("no cell is being edited")?;
+        let (row, col) = (session.row, session.col);
+        read(store, row, col)?;
+        let value = coerce(&store.data[col], &session.value)?;
+        self.session = None;
+        Ok(write_cell(store, row, col, value))
+    }
+
+    /// Close the session without writing.
+    pub fn cancel(&mut self) {
+        self.session = None;
+    }
+}
+
+/// Write `value` to data row `row`, column `col` through the cell update path.
+/// Returns the edit, or `None` when the cell is missing, of another type, or
+/// already holds `value`.
+pub fn write_cell(
+    store: &mut ColumnarStore,
+    row: usize,
+    col: usize,
+    value: CellValue,
+) -> Option<CellEdit> {
+    let before = read(store, row, col).ok()?;
+    if before.same(&value) || !write(store, row, col, &value) {
+        return None;
+    }
+    Some(CellEdit {
+        row,
+        col,
+        before,
+        after: value,
+    })
+}
+
+/// Cells written by `paste`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Paste {
+    /// Edits of the cells that changed.
+    pub edits: Vec<CellEdit>,
+    /// Cells written or already holding their value.
+    pub applied: usize,
+    /// Data row and column of the cells whose text didn't fit the column.
+    pub rejected: Vec<(usize, usize)>,
+    /// Fields past the last column.
+    pub clipped: usize,
+}
+
+/// Split clipboard text into lines of fields separated by `separator`.
+///
+/// As spreadsheets copy them, fields may be `"`-quoted (with `""` for a quote
+/// and line breaks inside), and a trailing line break adds no line.
+pub fn parse_clipboard(text: &str, separator: char) -> Vec<Vec<String>> {
+    let mut lines = Vec::new();
+    let mut line = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.next_if_eq(&'"').is_some() => field.push('"'),
+                '"' => quoted = false,
+                c => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            c if c == separator => line.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                line.push(std::mem::take(&mut field));
+                lines.push(std::mem::take(&mut line));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !line.is_empty() {
+        line.push(field);
+        lines.push(line);
+    }
+    lines
+}
+
+/// Write pasted `lines` of fields to data rows `rows` × table columns `cols`.
+///
+/// Line n goes to `rows[n]` and field n to `cols[n]`, coerced to the column
+/// type like `Editor::commit`. Lines past the last row are dropped.
+pub fn paste(
+    store: &mut ColumnarStore,
+    rows: &[u32],
+    cols: &[usize],
+    lines: &[Vec<String>],
+) -> Paste {
+    let mut paste = Paste::default();
+    for (&row, fields) in rows.iter().zip(lines) {
+        let row = row as usize;
+        paste.clipped += fields.len().saturating_sub(cols.len());
+        for (&col, field) in cols.iter().zip(fields) {
+            let value = read(store, row, col).and_then(|_| coerce(&store.data[col], field));
+            match value {
+                Ok(value) => {
+                    paste.applied += 1;
+                    paste.edits.extend(write_cell(store, row, col, value));
+                }
+                Err(_) => paste.rejected.push((row, col)),
+            }
+        }
+    }
+    paste
+}
+
+fn read(store: &ColumnarStore, row: usize, col: usize) -> Result<CellValue, String> {
+    let missing = || format!("no cell at row {row}, column {col}");
+    match store.data.get(col).ok_or_else(missing)? {
+        ColumnData::Float64(v) | ColumnData::Bool(v) => v
+            .get(row)
+            .map(|&v| CellValue::Number(v))
+            .ok_or_else(missing),
+        ColumnData::Strings { ids, intern } => ids
+            .get(row)
+            .map(|&id| CellValue::Text(intern.resolve(id).to_string()))
+            .ok_or_else(missing),
+        ColumnData::StringList { .. } | ColumnData::Float64List { .. } => {
+            Err(format!("column {col} holds lists and can't be edited"))
+        }
+    }
+}
+
+/// Parse `text` for a cell of `column`: numbers (`_` and `,` separators allowed)
+/// for Float64, `true`/`false`/`yes`/`no`/`1`/`0` for Bool, anything for
+/// strings. Blank text is null.
+fn coerce(column: &ColumnData, text: &str) -> Result<CellValue, String> {
+    let trimmed = text.trim();
+    match column {
+        ColumnData::Float64(_) if trimmed.is_empty() => Ok(CellValue::Number(f64::NAN)),
+        ColumnData::Float64(_) => trimmed
+            .replace(['_', ','], "")
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .map(CellValue::Number)
+            .ok_or_else(|| format!("{trimmed:?} is not a number")),
+        ColumnData::Bool(_) => match trimmed.to_ascii_lowercase().as_str() {
+            "" => Ok(CellValue::Number(f64::NAN)),
+            "true" | "yes" | "1" => Ok(CellValue::Number(1.0)),
+            "false" | "no" | "0" => Ok(CellValue::Number(0.0)),
+            _ => Err(format!("{trimmed:?} is not true or false")),
+        },
+        ColumnData::Strings { .. } => Ok(CellValue::Text(text.to_string())),
+        ColumnData::StringList { .. } | ColumnData::Float64List { .. } => {
+            Err("list columns can't be edited".to_string())
+        }
+    }
+}
+
+/// Write `value` if it fits the column's type; returns whether it did.
+fn write(store: &mut ColumnarStore, row: usize, col: usize, value: &CellValue) -> bool {
+    match (store.data.get(col), value) {
+        (Some(ColumnData::Float64(_)), &CellValue::Number(v)) => store.update_float64(col, row, v),
+        (Some(ColumnData::Bool(_)), &CellValue::Number(v)) => store.update_bool(col, row, v),
+        (Some(ColumnData::Strings { .. }), CellValue::Text(text)) => {
+            store.update_string(col, row, text);
+        }
+        _ => return false,
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FilterValue;
+
+    fn store() -> ColumnarStore {
+        let mut store = ColumnarStore::new();
+        store.init(3, 2);
+        store.set_column_float64(0, &[1.5, f64::NAN]);
+        store.set_column_bool(1, &[1.0, 0.0]);
+        store.set_column_strings(2, &[String::new(), "a".to_string()], &[1, 0]);
+        store.finalize();
+        store
+    }
+
+    #[test]
+    fn commit_coerces_to_the_column_type() {
+        let mut store = store();
+        let mut editor = Editor::default();
+        editor.begin(&store, 0, 0).unwrap();
+        assert_eq!(editor.value(), Some("1.5"));
+
+        editor.set_value("twelve");
+        assert_eq!(
+            editor.commit(&mut store),
+            Err("\"twelve\" is not a number".to_string())
+        );
+        // Still editing after a rejected value
+        editor.set_value(" 1,200 ");
+        let edit = editor.commit(&mut store).unwrap().unwrap();
+        assert_eq!((edit.row, edit.col), (0, 0));
+        assert_eq!(store.cell_value(0, 0), Some(FilterValue::Float64(1200.0)));
+        assert_eq!(store.updated_rows(), &[0]);
+
+        editor.begin(&store, 1, 1).unwrap();
+        assert_eq!(editor.value(), Some("false"));
+        editor.set_value("Yes");
+        editor.commit(&mut store).unwrap();
+        assert_eq!(store.cell_value(1, 1), Some(FilterValue::Bool(true)));
+
+        // Committing the unchanged (null) value writes nothing
+        editor.begin(&store, 1, 0).unwrap();
+        assert_eq!(editor.commit(&mut store), Ok(None));
+
+        editor.begin(&store, 0, 2).unwrap();
+        editor.cancel();
+        assert!(editor.commit(&mut store).is_err());
+        assert!(editor.begin(&store, 5, 0).is_err());
+    }
+
+    #[test]
+    fn edits_revert_and_apply() {
+        let mut store = store();
+        let edit = write_cell(&mut store, 1, 2, CellValue::Text("b".into())).unwrap();
+        assert_eq!(
+            store.cell_value(2, 1),
+            Some(FilterValue::String("b".into()))
+        );
+        edit.revert(&mut store);
+        assert_eq!(store.cell_value(2, 1), None);
+        edit.apply(&mut store);
+        assert_eq!(
+            store.cell_value(2, 1),
+            Some(FilterValue::String("b".into()))
+        );
+
+        // Wrong type for the column
+        assert_eq!(write_cell(&mut store, 0, 2, CellValue::Number(1.0)), None);
+    }
+
+    #[test]
+    fn parse_clipboard_splits_quoted_fields() {
+        assert_eq!(
+            parse_clipboard("a\t\"b\"\"c\"\r\n\"multi\nline\"\t\n", '\t'),
+            vec![
+                vec!["a".to_string(), "b\"c".to_string()],
+                vec!["multi\nline".to_string(), String::new()],
+            ]
+        );
+        assert_eq!(
+            parse_clipboard("1,\"2,5\"", ','),
+            vec![vec!["1".to_string(), "2,5".to_string()]]
+        );
+        assert!(parse_clipboard("", '\t').is_empty());
+    }
+
+    #[test]
+    fn paste_coerces_and_reports_rejected_cells() {
+        let mut store = store();
+        let lines = parse_clipboard("2\tyes\tx\textra\nnope\t1\n", '\t');
+        let paste = paste(&mut store, &[1, 0], &[0, 1, 2], &lines);
+        assert_eq!(paste.applied, 4);
+        assert_eq!(paste.rejected, vec![(0, 0)]);
+        assert_eq!(paste.clipped, 1);
+        // Row 0 column 1 already held true
+        assert_eq!(paste.edits.len(), 3);
+        assert_eq!(store.cell_value(0, 1), Some(FilterValue::Float64(2.0)));
+        assert_eq!(
+            store.cell_value(2, 1),
+            Some(FilterValue::String("x".into()))
+        );
+    }
+
+    #[test]
+    fn empty_rows_are_null() {
+        let mut store = store();
+        assert_eq!(store.append_empty_rows(2), 2..4);
+        assert_eq!(store.cell_value(2, 3), None);
+        assert_eq!(store.cell_value(0, 3), None);
+    }
+}
@@ -0,0 +1,253 @@
+//! Export of table cells as TSV, CSV, or JSON text, written from the columnar
+//! data in one pass (for clipboard copy and downloads).
+
+use std::fmt::Write;
+
+use crate::columnar_store::ColumnarStore;
+
+/// Text format of an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Tab-separated, as spreadsheets paste it.
+    Tsv,
+    /// Comma-separated (RFC 4180 quoting).
+    Csv,
+    /// An array of rows: arrays of cells, or objects keyed by header.
+    Json,
+}
+
+impl ExportFormat {
+    /// Parse a format name: `tsv`, `csv`, `json`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        Ok(match s {
+            "tsv" => Self::Tsv,
+            "csv" => Self::Csv,
+            "json" => Self::Json,
+            _ => return Err(format!("unknown export format: {s:?}")),
+        })
+    }
+}
+
+/// What to export: data rows in order × table columns in order, with
+/// `include(i, col)` picking the cells of `rows[i]` written (the others are left
+/// empty).
+pub struct ExportRange<'a, F> {
+    pub rows: &'a [u32],
+    pub cols: &'a [usize],
+    /// Header of each table column, written as the first line (keys in JSON).
+    pub headers: Option<&'a [String]>,
+    pub include: F,
+}
+
+/// Write the cells of `range` as `format` text: their display text (formatted
+/// numbers, `true`/`false`, comma-joined lists), one line per row. Empty and
+/// excluded cells are empty fields, or `null` in JSON.
+pub fn export(
+    store: &ColumnarStore,
+    format: ExportFormat,
+    range: &ExportRange<impl Fn(usize, usize) -> bool>,
+) -> String {
+    let header = |col: usize| {
+        range
+            .headers
+            .and_then(|headers| headers.get(col))
+            .map_or("", String::as_str)
+    };
+    let mut out = String::new();
+    let mut text = String::new();
+    if format == ExportFormat::Json {
+        out.push('[');
+        for (i, &row) in range.rows.iter().enumerate() {
+            out.push_str(if i == 0 { "\n  " } else { ",\n  " });
+            out.push(if range.headers.is_some() { '{' } else { '[' });
+            for (j, &col) in range.cols.iter().enumerate() {
+                if j > 0 {
+                    out.push_str(", ");
+                }
+                if range.headers.is_some() {
+                    write_json_string(header(col), &mut out);
+                    out.push_str(": ");
+                }
+                text.clear();
+                if (range.include)(i, col) {
+                    store.write_cell_text(col, row as usize, &mut text);
+                }
+                if text.is_empty() {
+                    out.push_str("null");
+                } else {
+                    write_json_string(&text, &mut out);
+                }
+            }
+            out.push(if range.headers.is_some() { '}' } else { ']' });
+        }
+        out.push_str(if range.rows.is_empty() { "]" } else { "\n]" });
+        return out;
+    }
+
+    let separator = if format == ExportFormat::Tsv {
+        '\t'
+    } else {
+        ','
+    };
+    let write_line = |cells: &mut dyn Iterator<Item = &str>, out: &mut String| {
+        for (j, cell) in cells.enumerate() {
+            if j > 0 {
+                out.push(separator);
+            }
+            write_field(cell, separator, out);
+        }
+        out.push('\n');
+    };
+    if range.headers.is_some() {
+        write_line(&mut range.cols.iter().map(|&col| header(col)), &mut out);
+    }
+    let mut cells = vec![String::new(); range.cols.len()];
+    for (i, &row) in range.rows.iter().enumerate() {
+        for (cell, &col) in cells.iter_mut().zip(range.cols) {
+            cell.clear();
+            if (range.include)(i, col) {
+                store.write_cell_text(col, row as usize, cell);
+            }
+        }
+        write_line(&mut cells.iter().map(String::as_str), &mut out);
+    }
+    out
+}
+
+/// A TSV/CSV field, quoted (with doubled quotes) when it holds the separator, a
+/// quote, or a line break.
+fn write_field(text: &str, separator: char, out: &mut String) {
+    if text.contains([separator, '"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&text.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(text);
+    }
+}
+
+fn write_json_string(text: &str, out: &mut String) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::FormatSpec;
+
+    fn store() -> ColumnarStore {
+        let mut store = ColumnarStore::new();
+        store.init(3, 3);
+        store.set_column_float64(0, &[1234.5, f64::NAN, 2.0]);
+        store.set_column_bool(1, &[1.0, 0.0, f64::NAN]);
+        store.set_column_strings(
+            2,
+            &[
+                String::new(),
+                "a, \"b\"".to_string(),
+                "tab\there".to_string(),
+            ],
+            &[1, 2, 0],
+        );
+        store.finalize();
+        store.set_column_format(0, Some(FormatSpec::parse("#,##0.00").unwrap()));
+        store
+    }
+
+    #[test]
+    fn tsv_and_csv_quote_formatted_text() {
+        let store = store();
+        let headers = ["n".to_string(), "b".to_string(), "s".to_string()];
+        let range = ExportRange {
+            rows: &[2, 0, 1],
+            cols: &[2, 0, 1],
+            headers: Some(&headers),
+            include: |_, _| true,
+        };
+        assert_eq!(
+            export(&store, ExportFormat::Tsv, &range),
+            "s\tn\tb\n\t2.00\t\n\"a, \"\"b\"\"\"\t1,234.50\ttrue\n\"tab\there\"\t\tfalse\n"
+        );
+
+        // Cells left out of the range are empty
+        let range = ExportRange {
+            rows: range.rows,
+            cols: range.cols,
+            headers: None,
+            include: |i, col| i != 2 || col == 2,
+        };
+        assert_eq!(
+            export(&store, ExportFormat::Csv, &range),
+            ",2.00,\n\"a, \"\"b\"\"\",\"1,234.50\",true\ntab\there,,\n"
+        );
+    }
+
+    #[test]
+    fn json_rows_are_arrays_or_objects() {
+        let store = store();
+        let headers = ["n".to_string(), "b".to_string(), "s".to_string()];
+        let range = ExportRange {
+            rows: &[0],
+            cols: &[0, 2],
+            headers: Some(&headers),
+            include: |_, _| true,
+        };
+        assert_eq!(
+            export(&store, ExportFormat::Json, &range),
+            "[\n  {\"n\": \"1,234.50\", \"s\": \"a, \\\"b\\\"\"}\n]"
+        );
+        let range = ExportRange {
+            rows: &[1, 2],
+            cols: &[0, 1],
+            headers: None,
+            include: |_, _| true,
+        };
+        assert_eq!(
+            export(&store, ExportFormat::Json, &range),
+            "[\n  [null, \"false\"],\n  [\"2.00\", null]\n]"
+        );
+        let range = ExportRange {
+            rows: &[],
+            cols: &[0, 1],
+            headers: None,
+            include: |_, _| true,
+        };
+        assert_eq!(export(&store, ExportFormat::Json, &range), "[]");
+    }
+
+    #[test]
+    fn grouped_view_exports_each_row_once() {
+        let mut store = store();
+        store.set_group_by(vec![1]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices().len(), 6);
+        let rows: Vec<u32> = store
+            .view_data_rows(0..usize::MAX)
+            .map(|(_, row)| row)
+            .collect();
+        let range = ExportRange {
+            rows: &rows,
+            cols: &[0],
+            headers: None,
+            include: |_, _| true,
+        };
+        assert_eq!(
+            export(&store, ExportFormat::Csv, &range),
+            "\"1,234.50\"\n\n2.00\n"
+        );
+    }
+}
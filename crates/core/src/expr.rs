@@ -0,0 +1,370 @@
+//! Numeric expressions over row values, e.g. `$2 * $5` or `abs($1 - $3) / 100`.
+//!
+//! Grammar: numbers, column references `$<index>`, `+ - * / %`, unary minus,
+//! parentheses, and the functions `abs(x)`, `round(x)`, `min(a, b)`, `max(a, b)`.
+//! One comparison (`< <= > >= == !=`) may join two of those, giving 1 or 0,
+//! e.g. `$3 > $2` for filters. Null (NaN) operands propagate to the result.
+
+/// Binary arithmetic operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+/// Built-in function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Func {
+    Abs,
+    Round,
+    Min,
+    Max,
+}
+
+impl Func {
+    const fn arity(self) -> usize {
+        match self {
+            Self::Abs | Self::Round => 1,
+            Self::Min | Self::Max => 2,
+        }
+    }
+}
+
+/// Parsed expression tree.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Column(usize),
+    Neg(Box<Self>),
+    Binary(BinOp, Box<Self>, Box<Self>),
+    Call(Func, Vec<Self>),
+}
+
+impl Expr {
+    /// Parse an expression string.
+    pub fn parse(src: &str) -> Result<Self, String> {
+        let mut parser = Parser { src, pos: 0 };
+        let expr = parser.comparison()?;
+        parser.skip_ws();
+        if parser.pos < src.len() {
+            return Err(format!(
+                "unexpected input at {}: {:?}",
+                parser.pos,
+                parser.rest()
+            ));
+        }
+        Ok(expr)
+    }
+
+    /// Evaluate with `column(index)` supplying the current row's values.
+    pub fn eval(&self, column: &impl Fn(usize) -> f64) -> f64 {
+        match self {
+            Self::Number(n) => *n,
+            Self::Column(idx) => column(*idx),
+            Self::Neg(e) => -e.eval(column),
+            Self::Binary(op, a, b) => {
+                let (a, b) = (a.eval(column), b.eval(column));
+                match op {
+                    BinOp::Add => a + b,
+                    BinOp::Sub => a - b,
+                    BinOp::Mul => a * b,
+                    BinOp::Div => a / b,
+                    BinOp::Rem => a % b,
+                    _ if a.is_nan() || b.is_nan() => f64::NAN,
+                    BinOp::Lt => f64::from(u8::from(a < b)),
+                    BinOp::Le => f64::from(u8::from(a <= b)),
+                    BinOp::Gt => f64::from(u8::from(a > b)),
+                    BinOp::Ge => f64::from(u8::from(a >= b)),
+                    BinOp::Eq => f64::from(u8::from((a - b).abs() < f64::EPSILON)),
+                    BinOp::Ne => f64::from(u8::from((a - b).abs() >= f64::EPSILON)),
+                }
+            }
+            Self::Call(func, args) => {
+                let arg = |i: usize| args[i].eval(column);
+                match func {
+                    Func::Abs => arg(0).abs(),
+                    Func::Round => arg(0).round(),
+                    // NaN-propagating, unlike f64::min/max
+                    Func::Min | Func::Max => {
+                        let (a, b) = (arg(0), arg(1));
+                        if a.is_nan() || b.is_nan() {
+                            f64::NAN
+                        } else if *func == Func::Min {
+                            a.min(b)
+                        } else {
+                            a.max(b)
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Column indices referenced by the expression.
+    pub fn columns(&self) -> Vec<usize> {
+        let mut out = Vec::new();
+        self.collect_columns(&mut out);
+        out.sort_unstable();
+        out.dedup();
+        out
+    }
+
+    fn collect_columns(&self, out: &mut Vec<usize>) {
+        match self {
+            Self::Number(_) => {}
+            Self::Column(idx) => out.push(*idx),
+            Self::Neg(e) => e.collect_columns(out),
+            Self::Binary(_, a, b) => {
+                a.collect_columns(out);
+                b.collect_columns(out);
+            }
+            Self::Call(_, args) => args.iter().for_each(|a| a.collect_columns(out)),
+        }
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn skip_ws(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.src.len() - trimmed.len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_ws();
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, ch: char) -> bool {
+        if self.peek() == Some(ch) {
+            self.pos += ch.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, token: &str) -> bool {
+        self.skip_ws();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, ch: char) -> Result<(), String> {
+        if self.eat(ch) {
+            Ok(())
+        } else {
+            Err(format!("expected {ch:?} at {}", self.pos))
+        }
+    }
+
+    /// comparison := sum (('<' | '<=' | '>' | '>=' | '==' | '!=') sum)?
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let lhs = self.sum()?;
+        let op = if self.eat_str("<=") {
+            BinOp::Le
+        } else if self.eat_str(">=") {
+            BinOp::Ge
+        } else if self.eat_str("==") {
+            BinOp::Eq
+        } else if self.eat_str("!=") {
+            BinOp::Ne
+        } else if self.eat('<') {
+            BinOp::Lt
+        } else if self.eat('>') {
+            BinOp::Gt
+        } else {
+            return Ok(lhs);
+        };
+        Ok(Expr::Binary(op, Box::new(lhs), Box::new(self.sum()?)))
+    }
+
+    /// sum := product (('+' | '-') product)*
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.product()?;
+        loop {
+            let op = if self.eat('+') {
+                BinOp::Add
+            } else if self.eat('-') {
+                BinOp::Sub
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.product()?));
+        }
+    }
+
+    /// product := unary (('*' | '/' | '%') unary)*
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                BinOp::Mul
+            } else if self.eat('/') {
+                BinOp::Div
+            } else if self.eat('%') {
+                BinOp::Rem
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    /// unary := '-' unary | atom
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.atom()
+    }
+
+    /// atom := number | '$' index | func '(' args ')' | '(' comparison ')'
+    fn atom(&mut self) -> Result<Expr, String> {
+        let start = self.pos;
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let inner = self.comparison()?;
+                self.expect(')')?;
+                Ok(inner)
+            }
+            Some('$') => {
+                self.pos += 1;
+                let digits = self.take_while(|c| c.is_ascii_digit());
+                digits
+                    .parse()
+                    .map(Expr::Column)
+                    .map_err(|_| format!("expected column index after '$' at {start}"))
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let text = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                text.parse()
+                    .map(Expr::Number)
+                    .map_err(|_| format!("invalid number {text:?} at {start}"))
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let name = self.take_while(|c| c.is_ascii_alphanumeric());
+                let func = match name {
+                    "abs" => Func::Abs,
+                    "round" => Func::Round,
+                    "min" => Func::Min,
+                    "max" => Func::Max,
+                    _ => return Err(format!("unknown function {name:?} at {start}")),
+                };
+                self.expect('(')?;
+                let mut args = vec![self.comparison()?];
+                while self.eat(',') {
+                    args.push(self.comparison()?);
+                }
+                self.expect(')')?;
+                if args.len() != func.arity() {
+                    return Err(format!(
+                        "{name} takes {} argument(s), got {}",
+                        func.arity(),
+                        args.len()
+                    ));
+                }
+                Ok(Expr::Call(func, args))
+            }
+            _ => Err(format!("expected a value at {start}")),
+        }
+    }
+
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        let len = self
+            .rest()
+            .find(|c: char| !pred(c))
+            .unwrap_or(self.src.len() - start);
+        self.pos += len;
+        &self.src[start..self.pos]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(src: &str, row: &[f64]) -> f64 {
+        Expr::parse(src).unwrap().eval(&|i| row[i])
+    }
+
+    #[test]
+    fn precedence_and_parens() {
+        assert_eq!(
+            (
+                eval("1 + 2 * 3", &[]),
+                eval("(1 + 2) * 3", &[]),
+                eval("10 - 4 - 3", &[]),
+                eval("-2 * -3", &[]),
+                eval("7 % 4 / 2", &[]),
+            ),
+            (7.0, 9.0, 3.0, 6.0, 1.5)
+        );
+    }
+
+    #[test]
+    fn column_refs_and_functions() {
+        let row = [100.0, 1.25, -3.0];
+        assert_eq!(
+            (
+                eval("$0 * $1", &row),
+                eval("abs($2) + max($0, 5)", &row),
+                eval("round($1 * 2)", &row),
+            ),
+            (125.0, 103.0, 3.0)
+        );
+        assert_eq!(Expr::parse("$3 + $1 * $3").unwrap().columns(), vec![1, 3]);
+    }
+
+    #[test]
+    fn comparisons() {
+        let row = [3.0, 4.0, f64::NAN];
+        assert_eq!(
+            (
+                eval("$1 > $0", &row),
+                eval("$0 * 2 <= 5", &row),
+                eval("$0 == 3", &row),
+                eval("($0 != 3) + 1", &row),
+            ),
+            (1.0, 0.0, 1.0, 1.0)
+        );
+        assert!(eval("$2 < 1", &row).is_nan());
+        assert!(Expr::parse("1 < 2 < 3").is_err());
+    }
+
+    #[test]
+    fn null_propagates() {
+        assert!(eval("$0 + 1", &[f64::NAN]).is_nan());
+        assert!(eval("min($0, 1)", &[f64::NAN]).is_nan());
+    }
+
+    #[test]
+    fn parse_errors() {
+        for src in ["", "1 +", "$", "(1", "foo(1)", "min(1)", "1 2", "1..2"] {
+            assert!(Expr::parse(src).is_err(), "{src:?}");
+        }
+    }
+}
@@ -0,0 +1,407 @@
+//! Display formatting of cell values from format spec strings.
+//!
+//! Supported specs:
+//! - `compact` — abbreviated numbers (`1.2k`, `3.4M`, `5B`, `1T`)
+//! - number patterns — `0`, `0.00`, `#,##0.00`, `0.0%`, with literal text around
+//!   them (`$#,##0.00`, `0.0 kg`, `"USD" 0`); quoted or backslash-escaped `0`
+//!   and `#` are literal (`"No. #"0`, `\#0`)
+//! - date patterns over epoch-millisecond timestamps (UTC) — `YYYY-MM-DD HH:mm`, `MMM YYYY`
+//! - text patterns — `@` stands for the cell text (`@ kg`, `#@`)
+
+use std::fmt::Write;
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const MS_PER_DAY: f64 = 86_400_000.0;
+
+/// One token of a date pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DateToken {
+    Year4,
+    Year2,
+    MonthName,
+    MonthShort,
+    Month2,
+    Month,
+    Day2,
+    Day,
+    Hour2,
+    Hour,
+    Minute2,
+    Second2,
+    Literal(String),
+}
+
+/// A parsed display format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatSpec {
+    /// Abbreviated magnitude with one decimal (trailing `.0` trimmed).
+    Compact,
+    /// Fixed decimals with optional thousands grouping between `prefix` and
+    /// `suffix`; `percent` (a `%` in either) multiplies by 100.
+    Number {
+        decimals: usize,
+        grouping: bool,
+        percent: bool,
+        prefix: String,
+        suffix: String,
+    },
+    /// Timestamp (epoch milliseconds, UTC) rendered through a token list.
+    Date(Vec<DateToken>),
+    /// Cell text (or plain number) between `prefix` and `suffix`.
+    Text { prefix: String, suffix: String },
+}
+
+impl FormatSpec {
+    /// Parse a format spec string.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        if spec.eq_ignore_ascii_case("compact") {
+            return Ok(Self::Compact);
+        }
+        if let Some((prefix, suffix)) = spec.split_once('@') {
+            return Ok(Self::Text {
+                prefix: literal(prefix),
+                suffix: literal(suffix),
+            });
+        }
+        let mut placeholders = digit_placeholders(spec);
+        if let Some(start) = placeholders.next() {
+            let last = placeholders.last().unwrap_or(start);
+            let (prefix, body, suffix) = (&spec[..start], &spec[start..=last], &spec[last + 1..]);
+            let decimals = body.split_once('.').map_or(0, |(_, frac)| {
+                frac.chars().filter(|c| matches!(c, '0' | '#')).count()
+            });
+            return Ok(Self::Number {
+                decimals,
+                grouping: body.contains(','),
+                percent: prefix.contains('%') || suffix.contains('%'),
+                prefix: literal(prefix),
+                suffix: literal(suffix),
+            });
+        }
+        let tokens = parse_date_tokens(spec);
+        if tokens.iter().any(|t| !matches!(t, DateToken::Literal(_))) {
+            return Ok(Self::Date(tokens));
+        }
+        Err(format!("unsupported format spec: {spec:?}"))
+    }
+
+    /// Format a numeric cell value. NaN (null) formats as an empty string.
+    pub fn format_f64(&self, value: f64) -> String {
+        let mut out = String::new();
+        self.write_f64(value, &mut out);
+        out
+    }
+
+    /// Append the formatted value to `out`. NaN (null) appends nothing.
+    pub fn write_f64(&self, value: f64, out: &mut String) {
+        if value.is_nan() {
+            return;
+        }
+        match self {
+            Self::Compact => write_compact(value, out),
+            Self::Number {
+                decimals,
+                grouping,
+                percent,
+                prefix,
+                suffix,
+            } => {
+                let v = if *percent { value * 100.0 } else { value };
+                out.push_str(prefix);
+                write_fixed(v, *decimals, *grouping, out);
+                out.push_str(suffix);
+            }
+            Self::Date(tokens) => write_date(value, tokens, out),
+            Self::Text { prefix, suffix } => {
+                let _ = write!(out, "{prefix}{value}{suffix}");
+            }
+        }
+    }
+
+    /// Append a text cell: between the affixes of a text pattern, as is otherwise.
+    pub fn write_str(&self, text: &str, out: &mut String) {
+        if let Self::Text { prefix, suffix } = self {
+            out.push_str(prefix);
+            out.push_str(text);
+            out.push_str(suffix);
+        } else {
+            out.push_str(text);
+        }
+    }
+}
+
+fn parse_date_tokens(spec: &str) -> Vec<DateToken> {
+    const PATTERNS: [(&str, DateToken); 12] = [
+        ("YYYY", DateToken::Year4),
+        ("YY", DateToken::Year2),
+        ("MMMM", DateToken::MonthName),
+        ("MMM", DateToken::MonthShort),
+        ("MM", DateToken::Month2),
+        ("M", DateToken::Month),
+        ("DD", DateToken::Day2),
+        ("D", DateToken::Day),
+        ("HH", DateToken::Hour2),
+        ("H", DateToken::Hour),
+        ("mm", DateToken::Minute2),
+        ("ss", DateToken::Second2),
+    ];
+
+    let mut tokens = Vec::new();
+    let mut rest = spec;
+    'outer: while !rest.is_empty() {
+        for (pat, token) in &PATTERNS {
+            if let Some(tail) = rest.strip_prefix(pat) {
+                tokens.push(token.clone());
+                rest = tail;
+                continue 'outer;
+            }
+        }
+        let ch = rest.chars().next().unwrap_or_default();
+        if let Some(DateToken::Literal(lit)) = tokens.last_mut() {
+            lit.push(ch);
+        } else {
+            tokens.push(DateToken::Literal(ch.to_string()));
+        }
+        rest = &rest[ch.len_utf8()..];
+    }
+    tokens
+}
+
+/// Literal text of a pattern part: quotes dropped, backslash escapes resolved.
+fn literal(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut quoted = false;
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => quoted = !quoted,
+            '\\' if !quoted => out.extend(chars.next()),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Byte offsets of the `0` and `#` digit placeholders of a number pattern,
+/// skipping quoted text and backslash-escaped characters.
+fn digit_placeholders(spec: &str) -> impl Iterator<Item = usize> + '_ {
+    let (mut quoted, mut escaped) = (false, false);
+    spec.char_indices().filter_map(move |(i, ch)| {
+        if escaped {
+            escaped = false;
+            return None;
+        }
+        match ch {
+            '"' => quoted = !quoted,
+            '\\' if !quoted => escaped = true,
+            '0' | '#' if !quoted => return Some(i),
+            _ => {}
+        }
+        None
+    })
+}
+
+fn write_compact(value: f64, out: &mut String) {
+    const UNITS: [(f64, &str); 5] = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "k"), (1.0, "")];
+    let round = |unit: usize| (value / UNITS[unit].0 * 10.0).round() / 10.0;
+    let abs = value.abs();
+    let mut unit = UNITS
+        .iter()
+        .position(|&(scale, _)| abs >= scale)
+        .unwrap_or(UNITS.len() - 1);
+    let mut scaled = round(unit);
+    // Rounding up to 1000 of a unit (999_950 → 1000k) moves to the next one
+    if scaled.abs() >= 1000.0 && unit > 0 {
+        unit -= 1;
+        scaled = round(unit);
+    }
+    let _ = write!(out, "{scaled}{}", UNITS[unit].1);
+}
+
+fn write_fixed(value: f64, decimals: usize, grouping: bool, out: &mut String) {
+    let text = format!("{:.*}", decimals, value.abs());
+    let (int_part, frac_part) = text.split_once('.').unwrap_or((&text, ""));
+    if value < 0.0 && text.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        out.push('-');
+    }
+    if grouping {
+        let len = int_part.len();
+        for (i, ch) in int_part.chars().enumerate() {
+            if i > 0 && (len - i) % 3 == 0 {
+                out.push(',');
+            }
+            out.push(ch);
+        }
+    } else {
+        out.push_str(int_part);
+    }
+    if !frac_part.is_empty() {
+        out.push('.');
+        out.push_str(frac_part);
+    }
+}
+
+/// Civil (year, month 1-12, day 1-31) from days since 1970-01-01 (proleptic Gregorian).
+pub const fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Days since 1970-01-01 from a civil date (inverse of `civil_from_days`).
+pub const fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let m = month as i64;
+    let mp = if m > 2 { m - 3 } else { m + 9 };
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Largest timestamp magnitude a JS `Date` holds, ms (±100 million days).
+const MAX_TIMESTAMP_MS: f64 = 8.64e15;
+
+/// Timestamps that are infinite or outside the JS `Date` range append nothing.
+fn write_date(ms: f64, tokens: &[DateToken], out: &mut String) {
+    if !ms.is_finite() || ms.abs() > MAX_TIMESTAMP_MS {
+        return;
+    }
+    let days = (ms / MS_PER_DAY).floor();
+    let ms_of_day = days.mul_add(-MS_PER_DAY, ms) as i64;
+    let (year, month, day) = civil_from_days(days as i64);
+    let hour = ms_of_day / 3_600_000;
+    let minute = ms_of_day / 60_000 % 60;
+    let second = ms_of_day / 1000 % 60;
+    let month_name = MONTH_NAMES[(month - 1) as usize];
+
+    for token in tokens {
+        let _ = match token {
+            DateToken::Year4 => write!(out, "{year:04}"),
+            DateToken::Year2 => write!(out, "{:02}", year.rem_euclid(100)),
+            DateToken::MonthName => write!(out, "{month_name}"),
+            DateToken::MonthShort => write!(out, "{}", &month_name[..3]),
+            DateToken::Month2 => write!(out, "{month:02}"),
+            DateToken::Month => write!(out, "{month}"),
+            DateToken::Day2 => write!(out, "{day:02}"),
+            DateToken::Day => write!(out, "{day}"),
+            DateToken::Hour2 => write!(out, "{hour:02}"),
+            DateToken::Hour => write!(out, "{hour}"),
+            DateToken::Minute2 => write!(out, "{minute:02}"),
+            DateToken::Second2 => write!(out, "{second:02}"),
+            DateToken::Literal(lit) => write!(out, "{lit}"),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(spec: &str, v: f64) -> String {
+        FormatSpec::parse(spec).unwrap().format_f64(v)
+    }
+
+    #[test]
+    fn compact_numbers() {
+        assert_eq!(fmt("compact", 950.0), "950");
+        assert_eq!(fmt("compact", 1234.0), "1.2k");
+        assert_eq!(fmt("compact", 1_250_000.0), "1.3M");
+        assert_eq!(fmt("compact", 3_000_000_000.0), "3B");
+        assert_eq!(fmt("compact", -2_500.0), "-2.5k");
+        assert_eq!(fmt("compact", 999_950.0), "1M");
+        assert_eq!(fmt("compact", 999.96), "1k");
+        assert_eq!(fmt("compact", -999_999.0), "-1M");
+        assert_eq!(fmt("compact", 0.25), "0.3");
+    }
+
+    #[test]
+    fn number_patterns() {
+        assert_eq!(fmt("0", 12.6), "13");
+        assert_eq!(fmt("0.00", 12.345), "12.35");
+        assert_eq!(fmt("#,##0.00", 1_234_567.891), "1,234,567.89");
+        assert_eq!(fmt("#,##0", -1234.0), "-1,234");
+        assert_eq!(fmt("0.0%", 0.256), "25.6%");
+        assert_eq!(fmt("0.0", -0.01), "0.0");
+        assert_eq!(fmt("$#,##0.00", 1234.5), "$1,234.50");
+        assert_eq!(fmt("0.0 kg", 2.26), "2.3 kg");
+        assert_eq!(fmt("\"USD\" 0", -3.0), "USD -3");
+        assert_eq!(fmt("0%", 0.5), "50%");
+        // Quoted and escaped placeholders are literal text
+        assert_eq!(fmt("\"No. \"0", 7.0), "No. 7");
+        assert_eq!(fmt("\"#\"0", 7.0), "#7");
+        assert_eq!(fmt("\\#0", 7.0), "#7");
+        assert_eq!(fmt("0 \"pcs #10\"", 7.0), "7 pcs #10");
+    }
+
+    #[test]
+    fn text_patterns() {
+        let spec = FormatSpec::parse("#@ (\"x\")").unwrap();
+        let mut out = String::new();
+        spec.write_str("tag", &mut out);
+        assert_eq!(out, "#tag (x)");
+        assert_eq!(spec.format_f64(1.5), "#1.5 (x)");
+    }
+
+    #[test]
+    fn date_patterns() {
+        // 2024-01-15T13:05:09Z
+        let ts = 1_705_323_909_000.0;
+        assert_eq!(fmt("YYYY-MM-DD HH:mm:ss", ts), "2024-01-15 13:05:09");
+        assert_eq!(fmt("MMM YYYY", ts), "Jan 2024");
+        assert_eq!(fmt("MMMM D, YY", ts), "January 15, 24");
+        assert_eq!(fmt("M/D H", ts), "1/15 13");
+    }
+
+    #[test]
+    fn date_before_epoch() {
+        // 1969-12-31T23:00:00Z
+        assert_eq!(fmt("YYYY-MM-DD HH", -3_600_000.0), "1969-12-31 23");
+    }
+
+    #[test]
+    fn null_formats_empty() {
+        assert_eq!(fmt("compact", f64::NAN), "");
+        assert_eq!(fmt("YYYY", f64::NAN), "");
+        assert_eq!(fmt("YYYY", f64::INFINITY), "");
+        assert_eq!(fmt("YYYY-MM-DD", 1e300), "");
+        assert_eq!(fmt("YYYY-MM-DD", -8.64e15), "-271821-04-20");
+    }
+
+    #[test]
+    fn parse_rejects_unknown_spec() {
+        assert!(FormatSpec::parse("bogus").is_err());
+    }
+
+    #[test]
+    fn civil_roundtrip() {
+        for days in [-800_000, -1, 0, 59, 365, 11_016, 19_737, 2_932_896] {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+        assert_eq!(civil_from_days(19_737), (2024, 1, 15));
+    }
+}
@@ -0,0 +1,216 @@
+//! Undo/redo log of engine changes, grouped into transactions.
+
+/// Transactions kept for undo by default.
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// Changes of type `T` in transactions that undo and redo as a unit.
+///
+/// Each `record`ed change is its own transaction unless made between `begin`
+/// and `end` (which nest; the outermost pair makes one transaction). Recording
+/// clears the redo log. The caller applies the changes: `undo` hands back the
+/// last transaction to revert (last change first), `redo` the last undone one
+/// to apply again (first change first).
+#[derive(Debug, Clone)]
+pub struct History<T> {
+    undo: Vec<Vec<T>>,
+    redo: Vec<Vec<T>>,
+    /// Changes of the open transaction and its nesting depth.
+    open: Vec<T>,
+    depth: usize,
+    limit: usize,
+    /// The last transaction is a lone `record_merged` change, still open to
+    /// merges.
+    mergeable: bool,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            open: Vec::new(),
+            depth: 0,
+            limit: DEFAULT_HISTORY_LIMIT,
+            mergeable: false,
+        }
+    }
+}
+
+impl<T> History<T> {
+    /// Keep at most `limit` transactions for undo, dropping the oldest.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.trim();
+    }
+
+    pub fn record(&mut self, change: T) {
+        self.mergeable = false;
+        self.open.push(change);
+        self.redo.clear();
+        if self.depth == 0 {
+            self.close();
+        }
+    }
+
+    /// Like `record`, but folds `change` into the last change when that was
+    /// recorded by `record_merged` right before and `merge(last, &change)` does
+    /// (returns true), e.g. for the steps of one column resize drag.
+    pub fn record_merged(&mut self, change: T, merge: impl FnOnce(&mut T, &T) -> bool) {
+        if self.mergeable {
+            if let Some([last]) = self.undo.last_mut().map(Vec::as_mut_slice) {
+                if merge(last, &change) {
+                    self.redo.clear();
+                    return;
+                }
+            }
+        }
+        self.record(change);
+        self.mergeable = self.depth == 0;
+    }
+
+    /// Open a transaction (or nest in the open one).
+    pub const fn begin(&mut self) {
+        self.depth += 1;
+        self.mergeable = false;
+    }
+
+    /// Close the innermost `begin`; the outermost one makes its changes one
+    /// transaction. Returns false without an open transaction.
+    pub fn end(&mut self) -> bool {
+        let Some(depth) = self.depth.checked_sub(1) else {
+            return false;
+        };
+        self.depth = depth;
+        if depth == 0 {
+            self.close();
+        }
+        true
+    }
+
+    pub const fn in_transaction(&self) -> bool {
+        self.depth > 0
+    }
+
+    pub const fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || !self.open.is_empty()
+    }
+
+    pub const fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// The last transaction, to revert in reverse order; closes an open one first.
+    pub fn undo(&mut self) -> Option<&[T]> {
+        self.depth = 0;
+        self.mergeable = false;
+        self.close();
+        let changes = self.undo.pop()?;
+        self.redo.push(changes);
+        self.redo.last().map(Vec::as_slice)
+    }
+
+    /// The last undone transaction, to apply again in order.
+    pub fn redo(&mut self) -> Option<&[T]> {
+        self.mergeable = false;
+        let changes = self.redo.pop()?;
+        self.undo.push(changes);
+        self.undo.last().map(Vec::as_slice)
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.open.clear();
+        self.depth = 0;
+        self.mergeable = false;
+    }
+
+    fn close(&mut self) {
+        if !self.open.is_empty() {
+            self.undo.push(std::mem::take(&mut self.open));
+            self.trim();
+        }
+    }
+
+    fn trim(&mut self) {
+        let excess = self.undo.len().saturating_sub(self.limit);
+        self.undo.drain(..excess);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transactions_undo_as_a_unit() {
+        let mut history = History::default();
+        history.record(1);
+        history.begin();
+        history.record(2);
+        history.begin();
+        history.record(3);
+        assert!(history.end());
+        history.record(4);
+        assert!(history.end());
+        assert!(!history.end());
+
+        assert_eq!(history.undo(), Some(&[2, 3, 4][..]));
+        assert_eq!(history.undo(), Some(&[1][..]));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some(&[1][..]));
+
+        // Recording drops the redo log
+        history.record(5);
+        assert!(!history.can_redo());
+        assert_eq!(history.undo(), Some(&[5][..]));
+    }
+
+    #[test]
+    fn undo_closes_an_open_transaction_and_limit_drops_oldest() {
+        let mut history = History::default();
+        history.begin();
+        history.record('a');
+        assert!(history.can_undo());
+        assert_eq!(history.undo(), Some(&['a'][..]));
+        assert!(!history.in_transaction());
+
+        history.set_limit(2);
+        for change in ['b', 'c', 'd'] {
+            history.record(change);
+        }
+        assert_eq!(history.undo(), Some(&['d'][..]));
+        assert_eq!(history.undo(), Some(&['c'][..]));
+        assert_eq!(history.undo(), None);
+    }
+
+    #[test]
+    fn merged_changes_fold_into_one_step() {
+        // (key, value): changes with the same key merge
+        let merge = |last: &mut (char, i32), change: &(char, i32)| {
+            let same = last.0 == change.0;
+            if same {
+                last.1 = change.1;
+            }
+            same
+        };
+        let mut history = History::default();
+        history.record(('x', 0));
+        for step in 1..=3 {
+            history.record_merged(('a', step), merge);
+        }
+        history.record_merged(('b', 1), merge);
+        history.record_merged(('b', 2), merge);
+        assert_eq!(history.undo(), Some(&[('b', 2)][..]));
+
+        // Nothing merges across an undo or a plain record
+        history.record_merged(('a', 4), merge);
+        history.record(('y', 0));
+        history.record_merged(('a', 5), merge);
+        assert_eq!(history.undo(), Some(&[('a', 5)][..]));
+        assert_eq!(history.undo(), Some(&[('y', 0)][..]));
+        assert_eq!(history.undo(), Some(&[('a', 4)][..]));
+        assert_eq!(history.undo(), Some(&[('a', 3)][..]));
+        assert_eq!(history.undo(), Some(&[('x', 0)][..]));
+    }
+}
@@ -1,7 +1,10 @@
+use std::borrow::Cow;
 use std::hash::{Hash, Hasher};
 
 use taffy::prelude::*;
-use taffy::{GridAutoFlow, GridTemplateRepetition, MinMax, Overflow, Point, TaffyTree};
+use taffy::{
+    GridAutoFlow, GridTemplateArea, GridTemplateRepetition, MinMax, Overflow, Point, TaffyTree,
+};
 
 use crate::layout_buffer;
 
@@ -14,22 +17,66 @@ pub enum Align {
     Right,
 }
 
-/// A CSS dimension value: length(px), percent, or auto.
+/// Vertical text alignment within a cell's content box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub enum VerticalAlign {
+    Top,
+    #[default]
+    Middle,
+    Bottom,
+}
+
+/// Orientation of a column's header label (`FIELD_ROTATION`).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum HeaderOrientation {
+    #[default]
+    Horizontal,
+    /// Rotated 90° counterclockwise, reading bottom to top.
+    Vertical,
+    /// Rotated counterclockwise by the given degrees.
+    Angled(f32),
+}
+
+impl HeaderOrientation {
+    /// Counterclockwise rotation of the label, degrees.
+    pub const fn degrees(self) -> f32 {
+        match self {
+            Self::Horizontal => 0.0,
+            Self::Vertical => 90.0,
+            Self::Angled(degrees) => degrees,
+        }
+    }
+
+    /// Width and height of the box around a `label_width` × `line_height` label
+    /// at this orientation.
+    pub fn extent(self, label_width: f32, line_height: f32) -> (f32, f32) {
+        let (sin, cos) = self.degrees().to_radians().sin_cos();
+        let (sin, cos) = (sin.abs(), cos.abs());
+        (
+            label_width.mul_add(cos, line_height * sin),
+            label_width.mul_add(sin, line_height * cos),
+        )
+    }
+}
+
+/// A CSS dimension value: length(px), percent, calc, or auto.
 #[derive(Debug, Clone, Copy, Default)]
 pub enum DimensionValue {
     #[default]
     Auto,
     Length(f32),
     Percent(f32),
+    Calc(CalcValue),
 }
 
-/// A CSS length value: length(px) or percent (no auto).
+/// A CSS length value: length(px), percent, or calc (no auto).
 #[derive(Debug, Clone, Copy, Default)]
 pub enum LengthValue {
     #[default]
     Zero,
     Length(f32),
     Percent(f32),
+    Calc(CalcValue),
 }
 
 /// A CSS length value that also supports auto.
@@ -39,6 +86,286 @@ pub enum LengthAutoValue {
     Auto,
     Length(f32),
     Percent(f32),
+    Calc(CalcValue),
+}
+
+/// `px + percent × basis`: the linear form of a `calc()` sum.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CalcTerm {
+    pub px: f32,
+    /// Fraction of the basis (`50%` = 0.5).
+    pub percent: f32,
+}
+
+impl CalcTerm {
+    /// Px value against a percentage basis.
+    pub const fn resolve(self, basis: f32) -> f32 {
+        self.percent.mul_add(basis, self.px)
+    }
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            px: self.px + other.px,
+            percent: self.percent + other.percent,
+        }
+    }
+
+    fn scale(self, factor: f32) -> Self {
+        Self {
+            px: self.px * factor,
+            percent: self.percent * factor,
+        }
+    }
+}
+
+/// A CSS math function (`calc()`, `min()`, `max()`, `clamp()`), reduced to
+/// `clamp(min, value, max)` over linear terms.
+///
+/// Percentages resolve against the viewport at layout time: its width for
+/// horizontal values and the row height for vertical ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CalcValue {
+    pub value: CalcTerm,
+    pub min: Option<CalcTerm>,
+    pub max: Option<CalcTerm>,
+}
+
+impl CalcValue {
+    /// Parse a `calc()`, `min()`, `max()` or `clamp()` expression over px and %
+    /// lengths, e.g. `calc(100% - 40px)` or `clamp(80px, 25%, 300px)`.
+    ///
+    /// `None` for anything else, including nestings that do not reduce to a
+    /// single clamp (`min(a, b, c)`, `min(a, b) + max(c, d)`).
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if !["calc(", "min(", "max(", "clamp("]
+            .iter()
+            .any(|f| s.starts_with(f))
+        {
+            return None;
+        }
+        let mut parser = CalcParser { input: s, pos: 0 };
+        let value = parser.sum()?.into_length();
+        parser.skip_whitespace();
+        (parser.pos == s.len()).then_some(value)
+    }
+
+    /// Px value against a percentage basis.
+    pub fn resolve(&self, basis: f32) -> f32 {
+        let value = self.value.resolve(basis);
+        let value = self.max.map_or(value, |max| value.min(max.resolve(basis)));
+        self.min.map_or(value, |min| value.max(min.resolve(basis)))
+    }
+
+    const fn linear(value: CalcTerm) -> Self {
+        Self {
+            value,
+            min: None,
+            max: None,
+        }
+    }
+
+    const fn is_linear(&self) -> bool {
+        self.min.is_none() && self.max.is_none()
+    }
+
+    fn add(self, other: Self) -> Option<Self> {
+        let (clamped, offset) = match (self.is_linear(), other.is_linear()) {
+            (_, true) => (self, other.value),
+            (true, false) => (other, self.value),
+            (false, false) => return None,
+        };
+        Some(Self {
+            value: clamped.value.add(offset),
+            min: clamped.min.map(|min| min.add(offset)),
+            max: clamped.max.map(|max| max.add(offset)),
+        })
+    }
+
+    fn scale(self, factor: f32) -> Self {
+        let (min, max) = if factor < 0.0 {
+            (self.max, self.min)
+        } else {
+            (self.min, self.max)
+        };
+        Self {
+            value: self.value.scale(factor),
+            min: min.map(|min| min.scale(factor)),
+            max: max.map(|max| max.scale(factor)),
+        }
+    }
+}
+
+/// Operand of a math expression: a bare number or a length.
+#[derive(Clone, Copy)]
+enum CalcOperand {
+    Number(f32),
+    Length(CalcValue),
+}
+
+impl CalcOperand {
+    /// Bare numbers count as px.
+    const fn into_length(self) -> CalcValue {
+        match self {
+            Self::Number(px) => CalcValue::linear(CalcTerm { px, percent: 0.0 }),
+            Self::Length(v) => v,
+        }
+    }
+
+    fn add(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (Self::Number(a), Self::Number(b)) => Some(Self::Number(a + b)),
+            (a, b) => a.into_length().add(b.into_length()).map(Self::Length),
+        }
+    }
+
+    fn scale(self, factor: f32) -> Self {
+        match self {
+            Self::Number(n) => Self::Number(n * factor),
+            Self::Length(v) => Self::Length(v.scale(factor)),
+        }
+    }
+}
+
+/// Recursive-descent parser behind `CalcValue::parse`.
+struct CalcParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl CalcParser<'_> {
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        let found = self.rest().starts_with(c);
+        if found {
+            self.pos += c.len_utf8();
+        }
+        found
+    }
+
+    /// `product (('+' | '-') product)*`
+    fn sum(&mut self) -> Option<CalcOperand> {
+        let mut acc = self.product()?;
+        loop {
+            if self.eat('+') {
+                acc = acc.add(self.product()?)?;
+            } else if self.eat('-') {
+                acc = acc.add(self.product()?.scale(-1.0))?;
+            } else {
+                return Some(acc);
+            }
+        }
+    }
+
+    /// `factor (('*' | '/') factor)*`; one side of `*` and the divisor must be
+    /// bare numbers.
+    fn product(&mut self) -> Option<CalcOperand> {
+        let mut acc = self.factor()?;
+        loop {
+            if self.eat('*') {
+                acc = match (acc, self.factor()?) {
+                    (CalcOperand::Number(n), v) | (v, CalcOperand::Number(n)) => v.scale(n),
+                    _ => return None,
+                };
+            } else if self.eat('/') {
+                match self.factor()? {
+                    CalcOperand::Number(n) if n != 0.0 => acc = acc.scale(n.recip()),
+                    _ => return None,
+                }
+            } else {
+                return Some(acc);
+            }
+        }
+    }
+
+    /// A number with an optional `px`/`%` unit, a parenthesized sum, or a
+    /// math function.
+    fn factor(&mut self) -> Option<CalcOperand> {
+        if self.eat('-') {
+            return Some(self.factor()?.scale(-1.0));
+        }
+        if self.eat('(') {
+            let value = self.sum()?;
+            return self.eat(')').then_some(value);
+        }
+        if let Some(name) = ["calc(", "min(", "max(", "clamp("]
+            .into_iter()
+            .find(|f| self.rest().starts_with(f))
+        {
+            self.pos += name.len();
+            return self.function(name);
+        }
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let n = rest[..len].parse::<f32>().ok()?;
+        self.pos += len;
+        if self.rest().starts_with("px") {
+            self.pos += 2;
+            Some(CalcOperand::Length(CalcValue::linear(CalcTerm {
+                px: n,
+                percent: 0.0,
+            })))
+        } else if self.rest().starts_with('%') {
+            self.pos += 1;
+            Some(CalcOperand::Length(CalcValue::linear(CalcTerm {
+                px: 0.0,
+                percent: n / 100.0,
+            })))
+        } else {
+            Some(CalcOperand::Number(n))
+        }
+    }
+
+    /// Arguments and closing paren of the math function `name` (opening paren
+    /// already consumed).
+    fn function(&mut self, name: &str) -> Option<CalcOperand> {
+        if name == "calc(" {
+            let value = self.sum()?;
+            return self.eat(')').then_some(value);
+        }
+        let mut args = Vec::new();
+        loop {
+            args.push(self.sum()?.into_length());
+            if self.eat(')') {
+                break;
+            }
+            if !self.eat(',') {
+                return None;
+            }
+        }
+        if !args.iter().all(CalcValue::is_linear) {
+            return None;
+        }
+        let value = match (name, args.as_slice()) {
+            ("min(" | "max(", [a]) => *a,
+            ("min(", [a, b]) => CalcValue {
+                max: Some(b.value),
+                ..*a
+            },
+            ("max(", [a, b]) => CalcValue {
+                min: Some(b.value),
+                ..*a
+            },
+            ("clamp(", [min, value, max]) => CalcValue {
+                value: value.value,
+                min: Some(min.value),
+                max: Some(max.value),
+            },
+            _ => return None,
+        };
+        Some(CalcOperand::Length(value))
+    }
 }
 
 /// Rect with top/right/bottom/left values.
@@ -105,13 +432,16 @@ pub enum TrackSizeValue {
     MinMax(Box<Self>, Box<Self>),
     FitContentPx(f32),
     FitContentPercent(f32),
+    Calc(CalcValue),
 }
 
-/// An item in a grid track list: either a single track or a `repeat()`.
+/// An item in a grid track list: a single track, a `repeat()`, or the names
+/// (`[a b]`) of the grid line at that point of the list.
 #[derive(Debug, Clone)]
 pub enum TrackListItem {
     Single(TrackSizeValue),
     Repeat(RepeatValue, Vec<TrackSizeValue>),
+    Names(Vec<String>),
 }
 
 /// The repeat count for a CSS `repeat()` function.
@@ -123,21 +453,67 @@ pub enum RepeatValue {
 }
 
 /// Grid placement value for a single edge (start or end).
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Default)]
 pub enum GridPlacementValue {
     #[default]
     Auto,
     Line(i16),
     Span(u16),
+    /// The nth line with this name (`header 2`), or of the same-named grid area's
+    /// edge (`header` = `header-start` at a start, `header-end` at an end).
+    Named(String, i16),
+    /// Span up to the nth line with this name (`span header`).
+    NamedSpan(String, u16),
 }
 
 /// Grid line value with start/end placement (e.g., `grid-row: 1 / span 2`).
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct GridLineValue {
     pub start: GridPlacementValue,
     pub end: GridPlacementValue,
 }
 
+/// A named grid area (`grid-template-areas`) between 1-based grid lines, with
+/// `row_end`/`column_end` past its last track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridAreaValue {
+    pub name: String,
+    pub row_start: u16,
+    pub row_end: u16,
+    pub column_start: u16,
+    pub column_end: u16,
+}
+
+/// Named areas of `grid-template-areas` rows such as `"header header"`: each
+/// name's bounding rectangle, in order of first appearance. `.` cells are
+/// unnamed.
+pub fn parse_grid_template_areas(rows: &[&str]) -> Vec<GridAreaValue> {
+    let mut areas: Vec<GridAreaValue> = Vec::new();
+    for (row, line) in (1..).zip(rows) {
+        for (col, name) in (1..).zip(line.split_whitespace()) {
+            if name.chars().all(|c| c == '.') {
+                continue;
+            }
+            match areas.iter_mut().find(|area| area.name == name) {
+                Some(area) => {
+                    area.row_start = area.row_start.min(row);
+                    area.row_end = area.row_end.max(row + 1);
+                    area.column_start = area.column_start.min(col);
+                    area.column_end = area.column_end.max(col + 1);
+                }
+                None => areas.push(GridAreaValue {
+                    name: name.to_string(),
+                    row_start: row,
+                    row_end: row + 1,
+                    column_start: col,
+                    column_end: col + 1,
+                }),
+            }
+        }
+    }
+    areas
+}
+
 /// CSS flex-direction enum.
 #[derive(Debug, Clone, Copy, Default)]
 pub enum FlexDirectionValue {
@@ -188,6 +564,13 @@ pub enum BoxSizingValue {
     ContentBox,
 }
 
+/// Side a frozen column is pinned to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColumnPin {
+    Left,
+    Right,
+}
+
 /// Layout configuration for a single column (flex/grid child).
 #[derive(Debug, Clone)]
 pub struct ColumnLayout {
@@ -197,6 +580,8 @@ pub struct ColumnLayout {
     pub min_width: Option<f32>,
     pub max_width: Option<f32>,
     pub align: Align,
+    /// Vertical alignment of the first text line (`FIELD_BASELINE`).
+    pub vertical_align: VerticalAlign,
     // Flex child properties
     pub flex_basis: DimensionValue,
     pub height: DimensionValue,
@@ -214,6 +599,48 @@ pub struct ColumnLayout {
     pub grid_row: Option<GridLineValue>,
     pub grid_column: Option<GridLineValue>,
     pub justify_self: Option<AlignValue>,
+    /// Frozen column: kept at a fixed x regardless of `Viewport::scroll_left`.
+    pub pinned: Option<ColumnPin>,
+    /// Items laid out inside each data cell of the column (icon, text, badge...).
+    pub content: Option<Box<CellContent>>,
+    /// Intrinsic width of the column's cell text, for `auto`, `min-content`,
+    /// `max-content`, and `fit-content()` sizing. `None` sizes like an empty cell.
+    pub content_size: Option<ContentSize>,
+    /// Rotation of the header label; rotated labels grow the header row to fit
+    /// (`rotated_header_height`).
+    pub header_orientation: HeaderOrientation,
+    /// Width of the header label text, px (0 = unknown).
+    pub header_label_width: f32,
+}
+
+/// Content box widths of a column's cells, px (padding and border excluded).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ContentSize {
+    /// Min-content width: the average cell text width, as wrapping text would
+    /// settle around it.
+    pub min: f32,
+    /// Max-content width: the widest cell text.
+    pub max: f32,
+}
+
+impl ContentSize {
+    /// Width of the content under `available` width, like a CSS text box: the
+    /// min- or max-content width, or the available width between the two.
+    const fn width(self, available: AvailableSpace) -> f32 {
+        match available {
+            AvailableSpace::MinContent => self.min,
+            AvailableSpace::MaxContent => self.max,
+            AvailableSpace::Definite(w) => w.min(self.max).max(self.min),
+        }
+    }
+}
+
+/// Child items of a column's cells: the cell's content box is a flex/grid
+/// container (`container`), and each item is a child styled like a column.
+#[derive(Debug, Clone, Default)]
+pub struct CellContent {
+    pub container: ContainerLayout,
+    pub items: Vec<ColumnLayout>,
 }
 
 impl Default for ColumnLayout {
@@ -225,6 +652,7 @@ impl Default for ColumnLayout {
             min_width: None,
             max_width: None,
             align: Align::default(),
+            vertical_align: VerticalAlign::default(),
             flex_basis: DimensionValue::Auto,
             height: DimensionValue::Auto,
             min_height: DimensionValue::Auto,
@@ -240,7 +668,79 @@ impl Default for ColumnLayout {
             grid_row: None,
             grid_column: None,
             justify_self: None,
+            pinned: None,
+            content: None,
+            content_size: None,
+            header_orientation: HeaderOrientation::default(),
+            header_label_width: 0.0,
+        }
+    }
+}
+
+/// Header row overrides (`LayoutEngine::set_header_layout`).
+///
+/// Header cells are laid out in `container` instead of the data row's container,
+/// and a column with an override takes its box and text styles (padding, border,
+/// align, height...) from it, keeping its data sizing so headers stay over their cells.
+#[derive(Debug, Clone, Default)]
+pub struct HeaderLayout {
+    pub container: Option<ContainerLayout>,
+    /// Per-column overrides by column index (`None` = the column's own style).
+    pub columns: Vec<Option<ColumnLayout>>,
+}
+
+impl HeaderLayout {
+    /// Header styles of `columns`: each override with its column's width, flex
+    /// sizing, margin, and pin.
+    pub fn header_columns(&self, columns: &[ColumnLayout]) -> Vec<ColumnLayout> {
+        columns
+            .iter()
+            .enumerate()
+            .map(|(col_idx, col)| match self.columns.get(col_idx) {
+                Some(Some(header)) => ColumnLayout {
+                    width: col.width,
+                    flex_grow: col.flex_grow,
+                    flex_shrink: col.flex_shrink,
+                    flex_basis: col.flex_basis,
+                    min_width: col.min_width,
+                    max_width: col.max_width,
+                    margin: col.margin,
+                    pinned: col.pinned,
+                    content: None,
+                    content_size: col.content_size,
+                    header_label_width: col.header_label_width,
+                    ..header.clone()
+                },
+                _ => col.clone(),
+            })
+            .collect()
+    }
+
+    /// The overrides of columns `col_ids`, in that order (e.g. the visible columns).
+    #[must_use]
+    pub fn select(&self, col_ids: &[usize]) -> Self {
+        Self {
+            container: self.container.clone(),
+            columns: col_ids
+                .iter()
+                .map(|&col_idx| self.columns.get(col_idx).cloned().flatten())
+                .collect(),
+        }
+    }
+
+    /// Hash of the overrides, for frame keys.
+    pub fn key(&self) -> u64 {
+        let mut hasher = std::hash::DefaultHasher::new();
+        if let Some(container) = &self.container {
+            hash_container(&mut hasher, container);
+        }
+        for col in &self.columns {
+            col.is_some().hash(&mut hasher);
+            if let Some(col) = col {
+                hash_column(&mut hasher, col);
+            }
         }
+        hasher.finish()
     }
 }
 
@@ -266,6 +766,7 @@ pub struct ContainerLayout {
     // Grid container properties
     pub grid_template_rows: Vec<TrackListItem>,
     pub grid_template_columns: Vec<TrackListItem>,
+    pub grid_template_areas: Vec<GridAreaValue>,
     pub grid_auto_rows: Vec<TrackSizeValue>,
     pub grid_auto_columns: Vec<TrackSizeValue>,
     pub grid_auto_flow: GridAutoFlowValue,
@@ -292,6 +793,7 @@ impl Default for ContainerLayout {
             border: RectValue::default(),
             grid_template_rows: Vec::new(),
             grid_template_columns: Vec::new(),
+            grid_template_areas: Vec::new(),
             grid_auto_rows: Vec::new(),
             grid_auto_columns: Vec::new(),
             grid_auto_flow: GridAutoFlowValue::Row,
@@ -307,10 +809,56 @@ pub struct Viewport {
     pub height: f32,
     pub row_height: f32,
     pub header_height: f32,
-    pub scroll_top: f32,
+    pub scroll_top: f64,
+    /// Horizontal scroll offset, subtracted from the x of every non-pinned column.
+    /// Renderers that translate content themselves leave it at 0.
+    pub scroll_left: f32,
     /// Approximate single-line text height (fontSize * lineHeightRatio).
     /// Used as default min-height for auto-height columns.
     pub line_height: f32,
+    /// Keep the header pinned at y = 0 in `compute_into_buffer` instead of
+    /// scrolling it away; header cells are tagged `REGION_STICKY_HEADER`.
+    pub sticky_header: bool,
+    /// Height of the footer row laid out after the header (0 = no footer).
+    pub footer_height: f32,
+    /// Pin the footer to the bottom of the viewport instead of placing it after
+    /// the last row; such footer cells are also tagged `REGION_STICKY_HEADER`.
+    pub sticky_footer: bool,
+}
+
+impl Viewport {
+    /// Footer cells laid out for `col_count` columns (0 without a footer).
+    pub fn footer_cells(&self, col_count: usize) -> usize {
+        if self.footer_height > 0.0 {
+            col_count
+        } else {
+            0
+        }
+    }
+}
+
+/// Transposed table mode: records laid out as columns and fields (the table
+/// columns) as rows (`LayoutEngine::compute_transposed_into_buffer`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransposedLayout {
+    /// Width of each record column, px.
+    pub record_width: f32,
+    /// Width of the field label column frozen at the left, px.
+    pub label_width: f32,
+}
+
+impl TransposedLayout {
+    /// Width of the label column plus `records` record columns.
+    pub const fn content_width(&self, records: usize) -> f32 {
+        (records as f32).mul_add(self.record_width, self.label_width)
+    }
+}
+
+/// y of row `row` below `top` with rows of `row_height`, scrolled up by
+/// `scroll_top`. Computed in f64 so the viewport y stays exact at scroll offsets
+/// beyond f32 precision (millions of rows).
+fn scrolled_row_y(row: usize, row_height: f32, top: f32, scroll_top: f64) -> f32 {
+    (row as f64).mul_add(f64::from(row_height), f64::from(top) - scroll_top) as f32
 }
 
 /// Parameters for row-pinned layout computation (reduces argument count).
@@ -320,12 +868,21 @@ pub struct RowPinnedLayoutParams<'a> {
     pub container: &'a ContainerLayout,
     pub pinned_top: usize,
     pub pinned_bottom: usize,
-    pub scroll_top: f32,
+    pub scroll_top: f64,
     pub total_rows: usize,
     pub middle_range: std::ops::Range<usize>,
     pub header_row_count: usize,
 }
 
+/// Set `FIELD_ROTATION` of the header cells `first_cell..` from the header
+/// orientation of their columns.
+fn rotate_header_labels(columns: &[ColumnLayout], buf: &mut [f32], first_cell: usize) {
+    for (col_idx, col) in columns.iter().enumerate() {
+        buf[(first_cell + col_idx) * layout_buffer::LAYOUT_STRIDE
+            + layout_buffer::FIELD_ROTATION] = col.header_orientation.degrees();
+    }
+}
+
 /// Column position from Taffy layout result (includes cross-axis info).
 #[derive(Debug, Clone)]
 struct ColumnPosition {
@@ -337,47 +894,113 @@ struct ColumnPosition {
     border: [f32; 4],
 }
 
+/// Apply `viewport.scroll_left`. Without pinned columns every column shifts by it.
+///
+/// Otherwise pinned columns get fixed x positions: left-pinned packed from x = 0,
+/// right-pinned packed against the viewport's right edge, and the scrollable
+/// columns packed between them, shifted by `scroll_left`. Returns the pinned
+/// region widths.
+fn pin_columns(
+    columns: &[ColumnLayout],
+    positions: &mut [ColumnPosition],
+    viewport: &Viewport,
+) -> (f32, f32) {
+    if columns.iter().all(|c| c.pinned.is_none()) {
+        for pos in positions {
+            pos.x -= viewport.scroll_left;
+        }
+        return (0.0, 0.0);
+    }
+    let region_width = |pin: ColumnPin| -> f32 {
+        columns
+            .iter()
+            .zip(positions.iter())
+            .filter(|(c, _)| c.pinned == Some(pin))
+            .map(|(_, p)| p.width)
+            .sum()
+    };
+    let left = region_width(ColumnPin::Left);
+    let right = region_width(ColumnPin::Right);
+    let mut left_x = 0.0;
+    let mut scroll_x = left - viewport.scroll_left;
+    let mut right_x = viewport.width - right;
+    for (col, pos) in columns.iter().zip(positions.iter_mut()) {
+        let x = match col.pinned {
+            Some(ColumnPin::Left) => &mut left_x,
+            Some(ColumnPin::Right) => &mut right_x,
+            None => &mut scroll_x,
+        };
+        pos.x = *x;
+        *x += pos.width;
+    }
+    (left, right)
+}
+
+/// Flag cells of pinned columns in a row-major buffer of `cell_count` cells.
+fn tag_pinned_cells(columns: &[ColumnLayout], buf: &mut [f32], cell_count: usize) {
+    for (col_idx, col) in columns.iter().enumerate() {
+        let region = match col.pinned {
+            Some(ColumnPin::Left) => layout_buffer::REGION_PINNED_LEFT,
+            Some(ColumnPin::Right) => layout_buffer::REGION_PINNED_RIGHT,
+            None => continue,
+        };
+        for cell in (col_idx..cell_count).step_by(columns.len()) {
+            layout_buffer::add_region(buf, cell..cell + 1, region);
+        }
+    }
+}
+
 // ── Conversion helpers: our value types → Taffy types ──────────────────
 
-const fn dimension_to_taffy(d: DimensionValue) -> Dimension {
+// `basis` is the length percentages inside `Calc` values resolve against.
+
+fn dimension_to_taffy(d: DimensionValue, basis: f32) -> Dimension {
     match d {
         DimensionValue::Auto => Dimension::auto(),
         DimensionValue::Length(v) => Dimension::length(v),
         DimensionValue::Percent(v) => Dimension::percent(v),
+        DimensionValue::Calc(c) => Dimension::length(c.resolve(basis)),
     }
 }
 
-const fn length_to_taffy(l: LengthValue) -> LengthPercentage {
+fn length_to_taffy(l: LengthValue, basis: f32) -> LengthPercentage {
     match l {
         LengthValue::Zero => LengthPercentage::length(0.0),
         LengthValue::Length(v) => LengthPercentage::length(v),
         LengthValue::Percent(v) => LengthPercentage::percent(v),
+        LengthValue::Calc(c) => LengthPercentage::length(c.resolve(basis)),
     }
 }
 
-const fn length_auto_to_taffy(l: LengthAutoValue) -> LengthPercentageAuto {
+fn length_auto_to_taffy(l: LengthAutoValue, basis: f32) -> LengthPercentageAuto {
     match l {
         LengthAutoValue::Auto => LengthPercentageAuto::auto(),
         LengthAutoValue::Length(v) => LengthPercentageAuto::length(v),
         LengthAutoValue::Percent(v) => LengthPercentageAuto::percent(v),
+        LengthAutoValue::Calc(c) => LengthPercentageAuto::length(c.resolve(basis)),
     }
 }
 
-const fn length_rect_to_taffy(r: &RectValue<LengthValue>) -> Rect<LengthPercentage> {
+/// Padding and border percentages resolve against the width on every side.
+fn length_rect_to_taffy(r: &RectValue<LengthValue>, basis: f32) -> Rect<LengthPercentage> {
     Rect {
-        top: length_to_taffy(r.top),
-        right: length_to_taffy(r.right),
-        bottom: length_to_taffy(r.bottom),
-        left: length_to_taffy(r.left),
+        top: length_to_taffy(r.top, basis),
+        right: length_to_taffy(r.right, basis),
+        bottom: length_to_taffy(r.bottom, basis),
+        left: length_to_taffy(r.left, basis),
     }
 }
 
-const fn length_auto_rect_to_taffy(r: &RectValue<LengthAutoValue>) -> Rect<LengthPercentageAuto> {
+/// Margin percentages resolve against the width on every side.
+fn length_auto_rect_to_taffy(
+    r: &RectValue<LengthAutoValue>,
+    basis: f32,
+) -> Rect<LengthPercentageAuto> {
     Rect {
-        top: length_auto_to_taffy(r.top),
-        right: length_auto_to_taffy(r.right),
-        bottom: length_auto_to_taffy(r.bottom),
-        left: length_auto_to_taffy(r.left),
+        top: length_auto_to_taffy(r.top, basis),
+        right: length_auto_to_taffy(r.right, basis),
+        bottom: length_auto_to_taffy(r.bottom, basis),
+        left: length_auto_to_taffy(r.left, basis),
     }
 }
 
@@ -437,13 +1060,14 @@ const fn overflow_to_taffy(o: OverflowValue) -> Overflow {
 
 // ── Grid conversion helpers: our grid types → Taffy grid types ─────────
 
-fn track_size_to_min(v: &TrackSizeValue) -> MinTrackSizingFunction {
+fn track_size_to_min(v: &TrackSizeValue, basis: f32) -> MinTrackSizingFunction {
     match v {
         TrackSizeValue::Length(px) => MinTrackSizingFunction::length(*px),
+        TrackSizeValue::Calc(c) => MinTrackSizingFunction::length(c.resolve(basis)),
         TrackSizeValue::Percent(pct) => MinTrackSizingFunction::percent(*pct / 100.0),
         TrackSizeValue::MinContent => MinTrackSizingFunction::min_content(),
         TrackSizeValue::MaxContent => MinTrackSizingFunction::max_content(),
-        TrackSizeValue::MinMax(min, _) => track_size_to_min(min),
+        TrackSizeValue::MinMax(min, _) => track_size_to_min(min, basis),
         // Fr/FitContent/Auto → auto for min sizing
         TrackSizeValue::Auto
         | TrackSizeValue::Fr(_)
@@ -452,9 +1076,10 @@ fn track_size_to_min(v: &TrackSizeValue) -> MinTrackSizingFunction {
     }
 }
 
-fn track_size_to_max(v: &TrackSizeValue) -> MaxTrackSizingFunction {
+fn track_size_to_max(v: &TrackSizeValue, basis: f32) -> MaxTrackSizingFunction {
     match v {
         TrackSizeValue::Length(px) => MaxTrackSizingFunction::length(*px),
+        TrackSizeValue::Calc(c) => MaxTrackSizingFunction::length(c.resolve(basis)),
         TrackSizeValue::Percent(pct) => MaxTrackSizingFunction::percent(*pct / 100.0),
         TrackSizeValue::Fr(fr) => MaxTrackSizingFunction::fr(*fr),
         TrackSizeValue::Auto => MaxTrackSizingFunction::auto(),
@@ -464,60 +1089,97 @@ fn track_size_to_max(v: &TrackSizeValue) -> MaxTrackSizingFunction {
         TrackSizeValue::FitContentPercent(pct) => {
             MaxTrackSizingFunction::fit_content_percent(*pct / 100.0)
         }
-        TrackSizeValue::MinMax(_, max) => track_size_to_max(max),
+        TrackSizeValue::MinMax(_, max) => track_size_to_max(max, basis),
     }
 }
 
-fn track_size_to_taffy(v: &TrackSizeValue) -> TrackSizingFunction {
+fn track_size_to_taffy(v: &TrackSizeValue, basis: f32) -> TrackSizingFunction {
     match v {
         TrackSizeValue::MinMax(min, max) => MinMax {
-            min: track_size_to_min(min),
-            max: track_size_to_max(max),
+            min: track_size_to_min(min, basis),
+            max: track_size_to_max(max, basis),
         },
         _ => MinMax {
-            min: track_size_to_min(v),
-            max: track_size_to_max(v),
+            min: track_size_to_min(v, basis),
+            max: track_size_to_max(v, basis),
         },
     }
 }
 
-fn track_list_to_taffy(items: &[TrackListItem]) -> Vec<GridTemplateComponent<String>> {
-    items
-        .iter()
-        .map(|item| match item {
-            TrackListItem::Single(v) => GridTemplateComponent::Single(track_size_to_taffy(v)),
-            TrackListItem::Repeat(rep, tracks) => {
+/// Taffy tracks of a track list, and the names of each grid line between them
+/// (empty when no line is named).
+fn track_list_to_taffy(
+    items: &[TrackListItem],
+    basis: f32,
+) -> (Vec<GridTemplateComponent<String>>, Vec<Vec<String>>) {
+    let mut tracks = Vec::new();
+    let mut line_names = vec![Vec::new()];
+    for item in items {
+        match item {
+            TrackListItem::Single(v) => {
+                tracks.push(GridTemplateComponent::Single(track_size_to_taffy(v, basis)));
+                line_names.push(Vec::new());
+            }
+            TrackListItem::Repeat(rep, repeated) => {
                 let count = match rep {
                     RepeatValue::Count(n) => RepetitionCount::Count(*n),
                     RepeatValue::AutoFill => RepetitionCount::AutoFill,
                     RepeatValue::AutoFit => RepetitionCount::AutoFit,
                 };
-                GridTemplateComponent::Repeat(GridTemplateRepetition {
+                tracks.push(GridTemplateComponent::Repeat(GridTemplateRepetition {
                     count,
-                    tracks: tracks.iter().map(track_size_to_taffy).collect(),
+                    tracks: repeated
+                        .iter()
+                        .map(|v| track_size_to_taffy(v, basis))
+                        .collect(),
                     line_names: vec![],
-                })
+                }));
+                line_names.push(Vec::new());
             }
-        })
-        .collect()
+            TrackListItem::Names(names) => {
+                if let Some(line) = line_names.last_mut() {
+                    line.extend(names.iter().cloned());
+                }
+            }
+        }
+    }
+    if line_names.iter().all(Vec::is_empty) {
+        line_names.clear();
+    }
+    (tracks, line_names)
+}
+
+fn grid_area_to_taffy(area: &GridAreaValue) -> GridTemplateArea<String> {
+    GridTemplateArea {
+        name: area.name.clone(),
+        row_start: area.row_start,
+        row_end: area.row_end,
+        column_start: area.column_start,
+        column_end: area.column_end,
+    }
 }
 
-fn auto_tracks_to_taffy(tracks: &[TrackSizeValue]) -> Vec<TrackSizingFunction> {
-    tracks.iter().map(track_size_to_taffy).collect()
+fn auto_tracks_to_taffy(tracks: &[TrackSizeValue], basis: f32) -> Vec<TrackSizingFunction> {
+    tracks
+        .iter()
+        .map(|v| track_size_to_taffy(v, basis))
+        .collect()
 }
 
-fn grid_placement_to_taffy(v: GridPlacementValue) -> GridPlacement {
+fn grid_placement_to_taffy(v: &GridPlacementValue) -> GridPlacement {
     match v {
         GridPlacementValue::Auto => GridPlacement::Auto,
-        GridPlacementValue::Line(n) => GridPlacement::from_line_index(n),
-        GridPlacementValue::Span(n) => GridPlacement::from_span(n),
+        GridPlacementValue::Line(n) => GridPlacement::from_line_index(*n),
+        GridPlacementValue::Span(n) => GridPlacement::from_span(*n),
+        GridPlacementValue::Named(name, n) => GridPlacement::NamedLine(name.clone(), *n),
+        GridPlacementValue::NamedSpan(name, n) => GridPlacement::NamedSpan(name.clone(), *n),
     }
 }
 
-fn grid_line_to_taffy(v: GridLineValue) -> Line<GridPlacement> {
+fn grid_line_to_taffy(v: &GridLineValue) -> Line<GridPlacement> {
     Line {
-        start: grid_placement_to_taffy(v.start),
-        end: grid_placement_to_taffy(v.end),
+        start: grid_placement_to_taffy(&v.start),
+        end: grid_placement_to_taffy(&v.end),
     }
 }
 
@@ -530,12 +1192,22 @@ const fn grid_auto_flow_to_taffy(v: GridAutoFlowValue) -> GridAutoFlow {
     }
 }
 
+/// A root node and its column leaves kept in the Taffy tree across layouts,
+/// so unchanged styles keep Taffy's per-node caches.
+struct ColumnTree {
+    /// `column_tree_key` of the container and column count.
+    key: u64,
+    root: NodeId,
+    children: Vec<NodeId>,
+}
+
 /// Cached result from `compute_column_positions`.
 #[derive(Clone)]
 struct ColumnLayoutCache {
     key: u64,
     positions: Vec<ColumnPosition>,
     effective_height: f32,
+    content_width: f32,
 }
 
 /// Hash an f32 by its bit pattern.
@@ -550,10 +1222,21 @@ fn hash_opt_f32<H: Hasher>(h: &mut H, v: Option<f32>) {
     }
 }
 
+fn hash_calc<H: Hasher>(h: &mut H, c: &CalcValue) {
+    for term in [Some(c.value), c.min, c.max] {
+        term.is_some().hash(h);
+        if let Some(term) = term {
+            hash_f32(h, term.px);
+            hash_f32(h, term.percent);
+        }
+    }
+}
+
 fn hash_dimension<H: Hasher>(h: &mut H, v: DimensionValue) {
     std::mem::discriminant(&v).hash(h);
     match v {
         DimensionValue::Length(f) | DimensionValue::Percent(f) => hash_f32(h, f),
+        DimensionValue::Calc(c) => hash_calc(h, &c),
         DimensionValue::Auto => {}
     }
 }
@@ -562,6 +1245,7 @@ fn hash_length<H: Hasher>(h: &mut H, v: LengthValue) {
     std::mem::discriminant(&v).hash(h);
     match v {
         LengthValue::Length(f) | LengthValue::Percent(f) => hash_f32(h, f),
+        LengthValue::Calc(c) => hash_calc(h, &c),
         LengthValue::Zero => {}
     }
 }
@@ -570,6 +1254,7 @@ fn hash_length_auto<H: Hasher>(h: &mut H, v: LengthAutoValue) {
     std::mem::discriminant(&v).hash(h);
     match v {
         LengthAutoValue::Length(f) | LengthAutoValue::Percent(f) => hash_f32(h, f),
+        LengthAutoValue::Calc(c) => hash_calc(h, &c),
         LengthAutoValue::Auto => {}
     }
 }
@@ -595,11 +1280,13 @@ fn hash_opt_align<H: Hasher>(h: &mut H, v: Option<&AlignValue>) {
     }
 }
 
-fn hash_grid_placement<H: Hasher>(h: &mut H, v: GridPlacementValue) {
-    std::mem::discriminant(&v).hash(h);
+fn hash_grid_placement<H: Hasher>(h: &mut H, v: &GridPlacementValue) {
+    std::mem::discriminant(v).hash(h);
     match v {
         GridPlacementValue::Line(n) => n.hash(h),
         GridPlacementValue::Span(n) => n.hash(h),
+        GridPlacementValue::Named(name, n) => (name, n).hash(h),
+        GridPlacementValue::NamedSpan(name, n) => (name, n).hash(h),
         GridPlacementValue::Auto => {}
     }
 }
@@ -607,8 +1294,8 @@ fn hash_grid_placement<H: Hasher>(h: &mut H, v: GridPlacementValue) {
 fn hash_opt_grid_line<H: Hasher>(h: &mut H, v: Option<&GridLineValue>) {
     v.is_some().hash(h);
     if let Some(gl) = v {
-        hash_grid_placement(h, gl.start);
-        hash_grid_placement(h, gl.end);
+        hash_grid_placement(h, &gl.start);
+        hash_grid_placement(h, &gl.end);
     }
 }
 
@@ -620,6 +1307,7 @@ fn hash_track_size<H: Hasher>(h: &mut H, v: &TrackSizeValue) {
         | TrackSizeValue::Fr(f)
         | TrackSizeValue::FitContentPx(f)
         | TrackSizeValue::FitContentPercent(f) => hash_f32(h, *f),
+        TrackSizeValue::Calc(c) => hash_calc(h, c),
         TrackSizeValue::MinMax(a, b) => {
             hash_track_size(h, a);
             hash_track_size(h, b);
@@ -643,6 +1331,7 @@ fn hash_track_list_item<H: Hasher>(h: &mut H, item: &TrackListItem) {
                 hash_track_size(h, s);
             }
         }
+        TrackListItem::Names(names) => names.hash(h),
     }
 }
 
@@ -653,6 +1342,7 @@ fn hash_column<H: Hasher>(h: &mut H, col: &ColumnLayout) {
     hash_opt_f32(h, col.min_width);
     hash_opt_f32(h, col.max_width);
     std::mem::discriminant(&col.align).hash(h);
+    col.vertical_align.hash(h);
     hash_dimension(h, col.flex_basis);
     hash_dimension(h, col.height);
     hash_dimension(h, col.min_height);
@@ -668,6 +1358,12 @@ fn hash_column<H: Hasher>(h: &mut H, col: &ColumnLayout) {
     hash_opt_grid_line(h, col.grid_row.as_ref());
     hash_opt_grid_line(h, col.grid_column.as_ref());
     hash_opt_align(h, col.justify_self.as_ref());
+    if let Some(size) = col.content_size {
+        hash_f32(h, size.min);
+        hash_f32(h, size.max);
+    }
+    hash_f32(h, col.header_orientation.degrees());
+    hash_f32(h, col.header_label_width);
 }
 
 fn hash_container<H: Hasher>(h: &mut H, c: &ContainerLayout) {
@@ -700,6 +1396,10 @@ fn hash_container<H: Hasher>(h: &mut H, c: &ContainerLayout) {
     for item in &c.grid_template_columns {
         hash_track_list_item(h, item);
     }
+    for area in &c.grid_template_areas {
+        (&area.name, area.row_start, area.row_end).hash(h);
+        (area.column_start, area.column_end).hash(h);
+    }
     c.grid_auto_rows.len().hash(h);
     for ts in &c.grid_auto_rows {
         hash_track_size(h, ts);
@@ -732,14 +1432,145 @@ fn hash_layout_inputs(
     hasher.finish()
 }
 
+/// Key of the persistent column tree for `columns` columns in `container`.
+fn column_tree_key(container: &ContainerLayout, columns: usize) -> u64 {
+    let mut hasher = std::hash::DefaultHasher::new();
+    columns.hash(&mut hasher);
+    hash_container(&mut hasher, container);
+    hasher.finish()
+}
+
+/// Hash of everything that shapes a layout buffer except `viewport.scroll_top`:
+/// equal keys mean rows land at the same positions relative to the scroll offset.
+pub fn viewport_layout_key(
+    columns: &[ColumnLayout],
+    container: &ContainerLayout,
+    viewport: &Viewport,
+) -> u64 {
+    let mut hasher = std::hash::DefaultHasher::new();
+    hash_layout_inputs(
+        columns,
+        container,
+        viewport.width,
+        viewport.row_height,
+        viewport.line_height,
+    )
+    .hash(&mut hasher);
+    for col in columns {
+        col.pinned.hash(&mut hasher);
+        if let Some(content) = &col.content {
+            hash_container(&mut hasher, &content.container);
+            content.items.len().hash(&mut hasher);
+            for item in &content.items {
+                hash_column(&mut hasher, item);
+            }
+        }
+    }
+    hash_f32(&mut hasher, viewport.height);
+    hash_f32(&mut hasher, viewport.header_height);
+    hash_f32(&mut hasher, viewport.scroll_left);
+    viewport.sticky_header.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Header row height the rotated header labels of `columns` need.
+///
+/// That is the height of each label's rotated box (`HeaderOrientation::extent`)
+/// plus the column's px vertical padding and border; 0 without rotated labels.
+pub fn rotated_header_height(columns: &[ColumnLayout], line_height: f32) -> f32 {
+    columns
+        .iter()
+        .filter(|col| col.header_orientation != HeaderOrientation::Horizontal)
+        .map(|col| {
+            let (_, height) = col
+                .header_orientation
+                .extent(col.header_label_width, line_height);
+            height
+                + fixed_length(col.padding.top)
+                + fixed_length(col.padding.bottom)
+                + fixed_length(col.border.top)
+                + fixed_length(col.border.bottom)
+        })
+        .fold(0.0, f32::max)
+}
+
+/// Px value of a length; percentages and `calc()` count as 0.
+const fn fixed_length(value: LengthValue) -> f32 {
+    match value {
+        LengthValue::Length(px) => px,
+        LengthValue::Zero | LengthValue::Percent(_) | LengthValue::Calc(_) => 0.0,
+    }
+}
+
+/// Item cells per data row from `LayoutEngine::append_cell_items`, for sizing
+/// the layout buffer.
+pub fn cell_items_per_row(columns: &[ColumnLayout]) -> usize {
+    columns
+        .iter()
+        .filter_map(|c| c.content.as_ref())
+        .map(|content| content.items.len())
+        .sum()
+}
+
+/// Fill `FIELD_BASELINE` of cells `cells` for drawing with `textBaseline = "middle"`.
+///
+/// The baseline is the middle of the first text line: a `line_height` line placed
+/// in the content box by the column's (or cell item's) `vertical_align`. Run after
+/// every pass that moves or resizes cells.
+pub fn write_baselines(
+    columns: &[ColumnLayout],
+    line_height: f32,
+    buf: &mut [f32],
+    cells: std::ops::Range<usize>,
+) {
+    use layout_buffer::{
+        FIELD_BASELINE, FIELD_BORDER_BOTTOM, FIELD_BORDER_TOP, FIELD_COL, FIELD_HEIGHT, FIELD_ITEM,
+        FIELD_PADDING_BOTTOM, FIELD_PADDING_TOP, FIELD_REGION, FIELD_Y, LAYOUT_STRIDE,
+    };
+    for cell in cells {
+        let base = cell * LAYOUT_STRIDE;
+        let column = columns.get(buf[base + FIELD_COL] as usize);
+        let column =
+            if buf[base + FIELD_REGION] as u32 & layout_buffer::REGION_CELL_ITEM as u32 != 0 {
+                column
+                    .and_then(|c| c.content.as_ref())
+                    .and_then(|content| content.items.get(buf[base + FIELD_ITEM] as usize))
+            } else {
+                column
+            };
+        let top =
+            buf[base + FIELD_Y] + buf[base + FIELD_PADDING_TOP] + buf[base + FIELD_BORDER_TOP];
+        let bottom = buf[base + FIELD_Y] + buf[base + FIELD_HEIGHT]
+            - buf[base + FIELD_PADDING_BOTTOM]
+            - buf[base + FIELD_BORDER_BOTTOM];
+        buf[base + FIELD_BASELINE] =
+            match column.map_or_else(VerticalAlign::default, |c| c.vertical_align) {
+                VerticalAlign::Top => top + line_height / 2.0,
+                VerticalAlign::Middle => f32::midpoint(top, bottom),
+                VerticalAlign::Bottom => bottom - line_height / 2.0,
+            };
+    }
+}
+
+/// Column layout results `LayoutEngine` keeps by default.
+pub const DEFAULT_LAYOUT_CACHE_CAPACITY: usize = 8;
+
 /// Layout engine powered by Taffy (supports Flexbox and CSS Grid).
 pub struct LayoutEngine {
-    pub(crate) tree: TaffyTree<()>,
-    /// Two-slot LRU cache for column layout results.
-    /// Slot 0 and 1 hold independent cached results (typically header-height and row-height).
-    cache_slots: [Option<ColumnLayoutCache>; 2],
-    /// Tracks which slot was used least recently (0 or 1).
-    cache_lru: usize,
+    pub(crate) tree: TaffyTree<ContentSize>,
+    /// LRU cache of column layout results keyed by their inputs, least recently
+    /// used first (one entry per header, row, group row... height in use).
+    cache: Vec<ColumnLayoutCache>,
+    cache_capacity: usize,
+    /// Persistent column trees in `tree`, least recently used first (at most
+    /// `cache_capacity`, and at least one).
+    column_trees: Vec<ColumnTree>,
+    /// Left/right pinned region widths from the last buffer computation.
+    pinned_widths: (f32, f32),
+    /// Width of the laid-out columns from the last column layout.
+    content_width: f32,
+    /// Header row overrides (`set_header_layout`).
+    header: Option<HeaderLayout>,
 }
 
 impl LayoutEngine {
@@ -747,44 +1578,122 @@ impl LayoutEngine {
     pub fn new() -> Self {
         Self {
             tree: TaffyTree::new(),
-            cache_slots: [None, None],
-            cache_lru: 0,
+            cache: Vec::new(),
+            cache_capacity: DEFAULT_LAYOUT_CACHE_CAPACITY,
+            column_trees: Vec::new(),
+            pinned_widths: (0.0, 0.0),
+            content_width: 0.0,
+            header: None,
         }
     }
 
+    /// Lay out header cells with `header`'s container and column overrides,
+    /// indexed like the columns passed to `compute_into_buffer*`; `None` lays
+    /// them out like data cells.
+    pub fn set_header_layout(&mut self, header: Option<HeaderLayout>) {
+        self.header = header;
+    }
+
+    /// Widths of the left- and right-pinned column regions in the last
+    /// `compute_into_buffer*` call; `(0, 0)` when no column is pinned.
+    pub const fn pinned_widths(&self) -> (f32, f32) {
+        self.pinned_widths
+    }
+
+    /// Total content width of the last laid-out columns: the right edge of the
+    /// last column (margins included) plus the container's right padding and
+    /// border. Sizes the horizontal scrollbar.
+    pub const fn content_width(&self) -> f32 {
+        self.content_width
+    }
+
     /// Invalidate all cached layout results. Call when column definitions or
-    /// container properties change.
+    /// container properties change. The column trees stay: the next layout
+    /// restyles only the nodes whose styles changed.
     pub fn invalidate_cache(&mut self) {
-        self.cache_slots = [None, None];
-        self.cache_lru = 0;
+        self.cache.clear();
     }
 
-    /// Build a Taffy style for a column child node.
-    fn column_style(col: &ColumnLayout, line_height: f32) -> Style {
-        log::debug!(
-            "[layout] column_style: w={}, grow={}, shrink={}, basis={:?}, align_self={:?}",
-            col.width,
-            col.flex_grow,
-            col.flex_shrink,
-            col.flex_basis,
-            col.align_self
-        );
-        Style {
-            size: Size {
-                width: if col.width > 0.0 {
-                    Dimension::length(col.width)
-                } else {
+    /// Keep up to `capacity` column layout results (default
+    /// `DEFAULT_LAYOUT_CACHE_CAPACITY`; 0 disables caching), evicting the least
+    /// recently used.
+    pub fn set_cache_capacity(&mut self, capacity: usize) {
+        self.cache_capacity = capacity;
+        let excess = self.cache.len().saturating_sub(capacity);
+        self.cache.drain(..excess);
+        self.evict_column_trees(capacity.max(1));
+    }
+
+    /// Drop the least recently used column trees beyond `max`.
+    fn evict_column_trees(&mut self, max: usize) {
+        let excess = self.column_trees.len().saturating_sub(max);
+        for tree in self.column_trees.drain(..excess) {
+            for node in tree.children.into_iter().chain([tree.root]) {
+                self.tree.remove(node).expect("failed to remove node");
+            }
+        }
+    }
+
+    /// Root and leaves of the persistent tree for `count` columns in
+    /// `container`, created on first use.
+    fn column_tree(&mut self, container: &ContainerLayout, count: usize) -> (NodeId, Vec<NodeId>) {
+        let key = column_tree_key(container, count);
+        if let Some(i) = self.column_trees.iter().position(|t| t.key == key) {
+            let tree = self.column_trees.remove(i);
+            let nodes = (tree.root, tree.children.clone());
+            self.column_trees.push(tree);
+            return nodes;
+        }
+        self.evict_column_trees(self.cache_capacity.max(1) - 1);
+        let children: Vec<_> = (0..count)
+            .map(|_| self.tree.new_leaf(Style::default()))
+            .collect::<Result<_, _>>()
+            .expect("failed to create child node");
+        let root = self
+            .tree
+            .new_with_children(Style::default(), &children)
+            .expect("failed to create root node");
+        self.column_trees.push(ColumnTree {
+            key,
+            root,
+            children: children.clone(),
+        });
+        (root, children)
+    }
+
+    /// Set the style of `node`, unless unchanged (which would discard its
+    /// cached layout).
+    fn update_style(&mut self, node: NodeId, style: Style) {
+        if self.tree.style(node).is_ok_and(|current| *current != style) {
+            self.tree
+                .set_style(node, style)
+                .expect("failed to set style");
+        }
+    }
+
+    /// Build a Taffy style for a column child node; `basis` is the row size
+    /// `Calc` percentages resolve against.
+    fn column_style(col: &ColumnLayout, line_height: f32, basis: Size<f32>) -> Style {
+        log::debug!(
+            "[layout] column_style: w={}, grow={}, shrink={}, basis={:?}, align_self={:?}",
+            col.width,
+            col.flex_grow,
+            col.flex_shrink,
+            col.flex_basis,
+            col.align_self
+        );
+        Style {
+            size: Size {
+                width: if col.width > 0.0 {
+                    Dimension::length(col.width)
+                } else {
                     Dimension::auto()
                 },
-                height: match col.height {
-                    DimensionValue::Auto => Dimension::auto(),
-                    DimensionValue::Length(v) => Dimension::length(v),
-                    DimensionValue::Percent(v) => Dimension::percent(v),
-                },
+                height: dimension_to_taffy(col.height, basis.height),
             },
             flex_grow: col.flex_grow,
             flex_shrink: col.flex_shrink,
-            flex_basis: dimension_to_taffy(col.flex_basis),
+            flex_basis: dimension_to_taffy(col.flex_basis, basis.width),
             min_size: Size {
                 width: col.min_width.map_or(Dimension::auto(), Dimension::length),
                 // Leaf nodes have no intrinsic content size; use line_height
@@ -792,17 +1701,17 @@ impl LayoutEngine {
                 // produce visible cells.
                 height: match col.min_height {
                     DimensionValue::Auto => Dimension::length(line_height),
-                    other => dimension_to_taffy(other),
+                    other => dimension_to_taffy(other, basis.height),
                 },
             },
             max_size: Size {
                 width: col.max_width.map_or(Dimension::auto(), Dimension::length),
-                height: dimension_to_taffy(col.max_height),
+                height: dimension_to_taffy(col.max_height, basis.height),
             },
             align_self: col.align_self.and_then(align_value_to_taffy_align),
-            padding: length_rect_to_taffy(&col.padding),
-            margin: length_auto_rect_to_taffy(&col.margin),
-            border: length_rect_to_taffy(&col.border),
+            padding: length_rect_to_taffy(&col.padding, basis.width),
+            margin: length_auto_rect_to_taffy(&col.margin, basis.width),
+            border: length_rect_to_taffy(&col.border, basis.width),
             box_sizing: match col.box_sizing {
                 BoxSizingValue::BorderBox => BoxSizing::BorderBox,
                 BoxSizingValue::ContentBox => BoxSizing::ContentBox,
@@ -813,20 +1722,54 @@ impl LayoutEngine {
                 PositionValue::Absolute => Position::Absolute,
             },
             inset: Rect {
-                top: length_auto_to_taffy(col.inset.top),
-                right: length_auto_to_taffy(col.inset.right),
-                bottom: length_auto_to_taffy(col.inset.bottom),
-                left: length_auto_to_taffy(col.inset.left),
+                top: length_auto_to_taffy(col.inset.top, basis.height),
+                right: length_auto_to_taffy(col.inset.right, basis.width),
+                bottom: length_auto_to_taffy(col.inset.bottom, basis.height),
+                left: length_auto_to_taffy(col.inset.left, basis.width),
             },
-            grid_row: col.grid_row.map_or_else(Line::default, grid_line_to_taffy),
+            grid_row: col
+                .grid_row
+                .as_ref()
+                .map_or_else(Line::default, grid_line_to_taffy),
             grid_column: col
                 .grid_column
+                .as_ref()
                 .map_or_else(Line::default, grid_line_to_taffy),
             justify_self: col.justify_self.and_then(align_value_to_taffy_align),
             ..Style::default()
         }
     }
 
+    /// Positions and effective height of the header cells, with the header
+    /// overrides applied; also returns the header columns.
+    fn compute_header_positions<'a>(
+        &mut self,
+        columns: &'a [ColumnLayout],
+        container: &ContainerLayout,
+        viewport: &Viewport,
+    ) -> (Vec<ColumnPosition>, f32, Cow<'a, [ColumnLayout]>) {
+        let Some(header) = self.header.take() else {
+            let (positions, height) = self.compute_column_positions(
+                columns,
+                container,
+                viewport.width,
+                viewport.header_height,
+                viewport.line_height,
+            );
+            return (positions, height, Cow::Borrowed(columns));
+        };
+        let header_columns = header.header_columns(columns);
+        let (positions, height) = self.compute_column_positions(
+            &header_columns,
+            header.container.as_ref().unwrap_or(container),
+            viewport.width,
+            viewport.header_height,
+            viewport.line_height,
+        );
+        self.header = Some(header);
+        (positions, height, Cow::Owned(header_columns))
+    }
+
     /// Compute positions for each column using Taffy layout, returning full cross-axis info
     /// and the effective row height (which may differ from `row_height` for column directions).
     /// Results are cached; repeated calls with the same inputs return cloned cached data.
@@ -840,16 +1783,18 @@ impl LayoutEngine {
     ) -> (Vec<ColumnPosition>, f32) {
         let key = hash_layout_inputs(columns, container, viewport_width, row_height, line_height);
 
-        for cached in self.cache_slots.iter().flatten() {
-            if cached.key == key {
-                log::debug!(
-                    "[layout] cache HIT: cols={}, viewport_width={}, row_height={}",
-                    columns.len(),
-                    viewport_width,
-                    row_height
-                );
-                return (cached.positions.clone(), cached.effective_height);
-            }
+        if let Some(i) = self.cache.iter().position(|cached| cached.key == key) {
+            log::debug!(
+                "[layout] cache HIT: cols={}, viewport_width={}, row_height={}",
+                columns.len(),
+                viewport_width,
+                row_height
+            );
+            let cached = self.cache.remove(i);
+            self.content_width = cached.content_width;
+            let result = (cached.positions.clone(), cached.effective_height);
+            self.cache.push(cached);
+            return result;
         }
 
         log::debug!(
@@ -863,7 +1808,7 @@ impl LayoutEngine {
             FlexDirectionValue::Column | FlexDirectionValue::ColumnReverse
         );
 
-        let (positions, effective_height) = self.run_taffy_column_layout(
+        let (positions, effective_height, content_width) = self.run_taffy_column_layout(
             columns,
             container,
             viewport_width,
@@ -871,6 +1816,7 @@ impl LayoutEngine {
             line_height,
             is_column_dir,
         );
+        self.content_width = content_width;
 
         for (i, pos) in positions.iter().enumerate() {
             log::debug!(
@@ -895,20 +1841,131 @@ impl LayoutEngine {
             "[layout] effective_height={effective_height:.1} (row_height={row_height:.1}, column_dir={is_column_dir})"
         );
 
-        self.tree.clear();
-
-        let store_slot = self.cache_lru;
-        self.cache_slots[store_slot] = Some(ColumnLayoutCache {
-            key,
-            positions: positions.clone(),
-            effective_height,
-        });
-        self.cache_lru = 1 - store_slot;
+        if self.cache_capacity > 0 {
+            if self.cache.len() == self.cache_capacity {
+                self.cache.remove(0);
+            }
+            self.cache.push(ColumnLayoutCache {
+                key,
+                positions: positions.clone(),
+                effective_height,
+                content_width,
+            });
+        }
 
         (positions, effective_height)
     }
 
+    /// Append the items of the data cells `cells` whose column has `content`, as
+    /// cells `first_cell..`: each item is laid out in its cell's content box and
+    /// tagged `REGION_CELL_ITEM`, with the parent cell's row and column and its
+    /// index among the items in `FIELD_ITEM`. Merged, area, group, and footer cells get no
+    /// items. Returns the cells written (at most `cell_items_per_row` per row).
+    pub fn append_cell_items(
+        &mut self,
+        columns: &[ColumnLayout],
+        line_height: f32,
+        buf: &mut [f32],
+        cells: std::ops::Range<usize>,
+        first_cell: usize,
+    ) -> usize {
+        use layout_buffer::{
+            FIELD_BORDER_BOTTOM, FIELD_BORDER_LEFT, FIELD_BORDER_RIGHT, FIELD_BORDER_TOP,
+            FIELD_COL, FIELD_HEIGHT, FIELD_ITEM, FIELD_PADDING_BOTTOM, FIELD_PADDING_LEFT,
+            FIELD_PADDING_RIGHT, FIELD_PADDING_TOP, FIELD_REGION, FIELD_ROW, FIELD_WIDTH, FIELD_X,
+            FIELD_Y, LAYOUT_STRIDE,
+        };
+        const NO_ITEMS: u32 = (layout_buffer::REGION_DETAIL
+            + layout_buffer::REGION_GROUP_HEADER
+            + layout_buffer::REGION_MERGED
+            + layout_buffer::REGION_GROUP_ROW
+            + layout_buffer::REGION_FOOTER) as u32;
+        const INHERITED: u32 =
+            (layout_buffer::REGION_PINNED_LEFT + layout_buffer::REGION_PINNED_RIGHT) as u32;
+
+        if columns.iter().all(|c| c.content.is_none()) {
+            return 0;
+        }
+        // Items depend only on the column and the content box size
+        let mut layouts: Vec<((usize, u32, u32), Vec<ColumnPosition>)> = Vec::new();
+        let mut cell_idx = first_cell;
+        for cell in cells {
+            let base = cell * LAYOUT_STRIDE;
+            let row = buf[base + FIELD_ROW] as usize;
+            let col = buf[base + FIELD_COL] as usize;
+            let region = buf[base + FIELD_REGION] as u32;
+            let Some(content) = columns.get(col).and_then(|c| c.content.as_deref()) else {
+                continue;
+            };
+            if region & NO_ITEMS != 0 || content.items.is_empty() {
+                continue;
+            }
+            let x = buf[base + FIELD_X]
+                + buf[base + FIELD_PADDING_LEFT]
+                + buf[base + FIELD_BORDER_LEFT];
+            let y =
+                buf[base + FIELD_Y] + buf[base + FIELD_PADDING_TOP] + buf[base + FIELD_BORDER_TOP];
+            let width = (buf[base + FIELD_WIDTH]
+                - buf[base + FIELD_PADDING_LEFT]
+                - buf[base + FIELD_PADDING_RIGHT]
+                - buf[base + FIELD_BORDER_LEFT]
+                - buf[base + FIELD_BORDER_RIGHT])
+                .max(0.0);
+            let height = (buf[base + FIELD_HEIGHT]
+                - buf[base + FIELD_PADDING_TOP]
+                - buf[base + FIELD_PADDING_BOTTOM]
+                - buf[base + FIELD_BORDER_TOP]
+                - buf[base + FIELD_BORDER_BOTTOM])
+                .max(0.0);
+            let key = (col, width.to_bits(), height.to_bits());
+            let layout = layouts
+                .iter()
+                .position(|(k, _)| *k == key)
+                .unwrap_or_else(|| {
+                    let is_column_dir = matches!(
+                        content.container.flex_direction,
+                        FlexDirectionValue::Column | FlexDirectionValue::ColumnReverse
+                    );
+                    let (positions, _, _) = self.run_taffy_column_layout(
+                        &content.items,
+                        &content.container,
+                        width,
+                        height,
+                        line_height,
+                        is_column_dir,
+                    );
+                    layouts.push((key, positions));
+                    layouts.len() - 1
+                });
+            for (item_idx, (pos, item)) in layouts[layout].1.iter().zip(&content.items).enumerate()
+            {
+                layout_buffer::write_cell(
+                    buf,
+                    cell_idx,
+                    row,
+                    col,
+                    x + pos.x,
+                    y + pos.y,
+                    pos.width,
+                    pos.height,
+                    item.align,
+                    pos.padding,
+                    pos.border,
+                );
+                buf[cell_idx * LAYOUT_STRIDE + FIELD_REGION] =
+                    (layout_buffer::REGION_CELL_ITEM as u32 | (region & INHERITED)) as f32;
+                buf[cell_idx * LAYOUT_STRIDE + FIELD_ITEM] = item_idx as f32;
+                cell_idx += 1;
+            }
+        }
+        cell_idx - first_cell
+    }
+
     /// Run Taffy layout for columns and return positions plus effective height.
+    ///
+    /// Lays out the persistent tree for the container and column count, updating
+    /// only the styles and content sizes that changed so Taffy relayouts
+    /// incrementally (e.g. one column resized per mouse move).
     fn run_taffy_column_layout(
         &mut self,
         columns: &[ColumnLayout],
@@ -917,28 +1974,27 @@ impl LayoutEngine {
         row_height: f32,
         line_height: f32,
         is_column_dir: bool,
-    ) -> (Vec<ColumnPosition>, f32) {
+    ) -> (Vec<ColumnPosition>, f32, f32) {
+        let (root, children) = self.column_tree(container, columns.len());
         let root_style = Self::build_container_style(container, viewport_width, row_height);
-        let root = self
-            .tree
-            .new_leaf(root_style)
-            .expect("failed to create root node");
-
-        let children: Vec<_> = columns
-            .iter()
-            .map(|col| {
+        self.update_style(root, root_style);
+        let basis = Size {
+            width: viewport_width,
+            height: row_height,
+        };
+        for (&child, col) in children.iter().zip(columns) {
+            self.update_style(child, Self::column_style(col, line_height, basis));
+            if self.tree.get_node_context(child) != col.content_size.as_ref() {
                 self.tree
-                    .new_leaf(Self::column_style(col, line_height))
-                    .expect("failed to create child node")
-            })
-            .collect();
-
-        self.tree
-            .set_children(root, &children)
-            .expect("failed to set children");
+                    .set_node_context(child, col.content_size)
+                    .expect("failed to set node context");
+            }
+        }
 
+        // Columns with a content size measure as a single line of text; others
+        // have no intrinsic size
         self.tree
-            .compute_layout(
+            .compute_layout_with_measure(
                 root,
                 Size {
                     width: AvailableSpace::Definite(viewport_width),
@@ -948,6 +2004,12 @@ impl LayoutEngine {
                         AvailableSpace::Definite(row_height)
                     },
                 },
+                |known, available, _, size, _| {
+                    size.map_or(Size::ZERO, |size| Size {
+                        width: known.width.unwrap_or_else(|| size.width(available.width)),
+                        height: known.height.unwrap_or(0.0),
+                    })
+                },
             )
             .expect("failed to compute layout");
 
@@ -986,7 +2048,18 @@ impl LayoutEngine {
             })
             .collect();
 
-        (positions, effective_height)
+        let root_layout = self.tree.layout(root).expect("failed to get root layout");
+        let content_width = children
+            .iter()
+            .map(|&child| {
+                let layout = self.tree.layout(child).expect("failed to get layout");
+                layout.location.x + layout.size.width + layout.margin.right
+            })
+            .fold(0.0, f32::max)
+            + root_layout.padding.right
+            + root_layout.border.right;
+
+        (positions, effective_height, content_width)
     }
 
     /// Build a Taffy Style for the container (root) node.
@@ -1013,10 +2086,12 @@ impl LayoutEngine {
             DisplayValue::None => Display::None,
         };
 
-        let grid_template_columns = track_list_to_taffy(&container.grid_template_columns);
-        let grid_template_rows = track_list_to_taffy(&container.grid_template_rows);
-        let grid_auto_columns = auto_tracks_to_taffy(&container.grid_auto_columns);
-        let grid_auto_rows = auto_tracks_to_taffy(&container.grid_auto_rows);
+        let (grid_template_columns, grid_template_column_names) =
+            track_list_to_taffy(&container.grid_template_columns, viewport_width);
+        let (grid_template_rows, grid_template_row_names) =
+            track_list_to_taffy(&container.grid_template_rows, row_height);
+        let grid_auto_columns = auto_tracks_to_taffy(&container.grid_auto_columns, viewport_width);
+        let grid_auto_rows = auto_tracks_to_taffy(&container.grid_auto_rows, row_height);
 
         Style {
             display,
@@ -1041,8 +2116,8 @@ impl LayoutEngine {
                 },
             },
             gap: Size {
-                width: length_to_taffy(col_gap),
-                height: length_to_taffy(row_gap),
+                width: length_to_taffy(col_gap, viewport_width),
+                height: length_to_taffy(row_gap, row_height),
             },
             align_items: container.align_items.and_then(align_value_to_taffy_align),
             align_content: container
@@ -1056,11 +2131,18 @@ impl LayoutEngine {
                 y: overflow_to_taffy(container.overflow_y),
             },
             scrollbar_width: container.scrollbar_width,
-            padding: length_rect_to_taffy(&container.padding),
-            margin: length_auto_rect_to_taffy(&container.margin),
-            border: length_rect_to_taffy(&container.border),
+            padding: length_rect_to_taffy(&container.padding, viewport_width),
+            margin: length_auto_rect_to_taffy(&container.margin, viewport_width),
+            border: length_rect_to_taffy(&container.border, viewport_width),
             grid_template_columns,
             grid_template_rows,
+            grid_template_column_names,
+            grid_template_row_names,
+            grid_template_areas: container
+                .grid_template_areas
+                .iter()
+                .map(grid_area_to_taffy)
+                .collect(),
             grid_auto_columns,
             grid_auto_rows,
             grid_auto_flow: grid_auto_flow_to_taffy(container.grid_auto_flow),
@@ -1069,11 +2151,61 @@ impl LayoutEngine {
         }
     }
 
+    /// Write the footer row at `y` as cells `first_cell..`, tagged `REGION_FOOTER`
+    /// (plus `REGION_STICKY_HEADER` when sticky). Returns the cells written.
+    fn write_footer_row(
+        &mut self,
+        columns: &[ColumnLayout],
+        container: &ContainerLayout,
+        viewport: &Viewport,
+        buf: &mut [f32],
+        first_cell: usize,
+        y: f32,
+    ) -> usize {
+        if viewport.footer_cells(columns.len()) == 0 {
+            return 0;
+        }
+        let (mut positions, _) = self.compute_column_positions(
+            columns,
+            container,
+            viewport.width,
+            viewport.footer_height,
+            viewport.line_height,
+        );
+        pin_columns(columns, &mut positions, viewport);
+        for (col_idx, pos) in positions.iter().enumerate() {
+            layout_buffer::write_cell(
+                buf,
+                first_cell + col_idx,
+                0,
+                col_idx,
+                pos.x,
+                y + pos.y,
+                pos.width,
+                pos.height,
+                columns
+                    .get(col_idx)
+                    .map_or_else(Align::default, |c| c.align),
+                pos.padding,
+                pos.border,
+            );
+        }
+        let cells = first_cell..first_cell + columns.len();
+        layout_buffer::add_region(buf, cells.clone(), layout_buffer::REGION_FOOTER);
+        if viewport.sticky_footer {
+            layout_buffer::add_region(buf, cells, layout_buffer::REGION_STICKY_HEADER);
+        }
+        columns.len()
+    }
+
     /// Compute header + visible row layouts into a pre-allocated flat f32 buffer.
     /// Returns the number of cells written.
     ///
-    /// Buffer layout: first `columns.len()` cells are headers, then data cells.
-    /// Each cell occupies `LAYOUT_STRIDE` f32 values.
+    /// Buffer layout: first `columns.len()` cells are headers, then the footer row
+    /// (`viewport.footer_height`), then data cells. Each cell occupies
+    /// `LAYOUT_STRIDE` f32 values. A non-sticky footer follows the last row of
+    /// `visible_range`, so it is in view once the range reaches the last row.
+    #[allow(clippy::too_many_lines)]
     pub fn compute_into_buffer(
         &mut self,
         columns: &[ColumnLayout],
@@ -1089,7 +2221,8 @@ impl LayoutEngine {
 
         let col_count = columns.len();
         let row_count = visible_range.end.saturating_sub(visible_range.start);
-        let total_cells = col_count + row_count * col_count;
+        let footer_cells = viewport.footer_cells(col_count);
+        let total_cells = col_count + footer_cells + row_count * col_count;
 
         log::debug!(
             "[layout] compute_into_buffer: cols={}, rows={}, total_cells={}, viewport={}x{}, range={}..{}",
@@ -1110,16 +2243,16 @@ impl LayoutEngine {
         );
 
         // Compute column positions (shared by header and all rows)
-        let (positions, effective_header_height) = self.compute_column_positions(
-            columns,
-            container,
-            viewport.width,
-            viewport.header_height,
-            viewport.line_height,
-        );
+        let (mut positions, effective_header_height, header_columns) =
+            self.compute_header_positions(columns, container, viewport);
+        self.pinned_widths = pin_columns(columns, &mut positions, viewport);
 
-        // Write header cells (scroll with content, not sticky)
-        let header_y = -viewport.scroll_top;
+        // Write header cells: scroll with content unless sticky
+        let header_y = if viewport.sticky_header {
+            0.0
+        } else {
+            -viewport.scroll_top as f32
+        };
         for (col_idx, pos) in positions.iter().enumerate() {
             layout_buffer::write_cell(
                 buf,
@@ -1130,34 +2263,57 @@ impl LayoutEngine {
                 header_y + pos.y,
                 pos.width,
                 pos.height,
-                columns
+                header_columns
                     .get(col_idx)
                     .map_or_else(Align::default, |c| c.align),
                 pos.padding,
                 pos.border,
             );
         }
+        rotate_header_labels(&header_columns, buf, 0);
+        if viewport.sticky_header {
+            layout_buffer::add_region(buf, 0..col_count, layout_buffer::REGION_STICKY_HEADER);
+        }
 
-        // Re-compute positions for row height if different from header height
-        let (row_positions, effective_row_height) =
-            if (viewport.row_height - viewport.header_height).abs() > f32::EPSILON {
-                self.compute_column_positions(
-                    columns,
-                    container,
-                    viewport.width,
-                    viewport.row_height,
-                    viewport.line_height,
-                )
-            } else {
-                (positions, effective_header_height)
-            };
+        // Re-compute positions for rows if the header height or styles differ
+        let (row_positions, effective_row_height) = if self.header.is_some()
+            || (viewport.row_height - viewport.header_height).abs() > f32::EPSILON
+        {
+            let (mut row_positions, h) = self.compute_column_positions(
+                columns,
+                container,
+                viewport.width,
+                viewport.row_height,
+                viewport.line_height,
+            );
+            pin_columns(columns, &mut row_positions, viewport);
+            (row_positions, h)
+        } else {
+            (positions, effective_header_height)
+        };
+
+        // Footer row: bottom of the viewport, or below the last row
+        let footer_y = if viewport.sticky_footer {
+            viewport.height - viewport.footer_height
+        } else {
+            scrolled_row_y(
+                visible_range.end,
+                effective_row_height,
+                effective_header_height,
+                viewport.scroll_top,
+            )
+        };
+        self.write_footer_row(columns, container, viewport, buf, col_count, footer_y);
 
         // Write data cells
-        let mut cell_idx = col_count;
+        let mut cell_idx = col_count + footer_cells;
         for row_idx in visible_range {
-            let row_base_y = (row_idx as f32)
-                .mul_add(effective_row_height, effective_header_height)
-                - viewport.scroll_top;
+            let row_base_y = scrolled_row_y(
+                row_idx,
+                effective_row_height,
+                effective_header_height,
+                viewport.scroll_top,
+            );
             for (col_idx, pos) in row_positions.iter().enumerate() {
                 layout_buffer::write_cell(
                     buf,
@@ -1178,13 +2334,76 @@ impl LayoutEngine {
             }
         }
 
+        tag_pinned_cells(columns, buf, total_cells);
+
         log::debug!("[layout] compute_into_buffer: done, cells_written={total_cells}");
 
         total_cells
     }
 
-    /// Compute layout with row pinning: header + top pinned + visible middle + bottom pinned.
-    /// Uses different y formulas so that drawing with clip+translate per row region lines up.
+    /// Transposed layout: one label cell per field (row 0, frozen at x = 0 and
+    /// tagged `REGION_PINNED_LEFT`), then for each record of `records` one cell
+    /// per field (row `record + header_row_count`), record after record.
+    ///
+    /// Field `i` is the row at `y = i * row_height - scroll_top`; record `r` the
+    /// column at `x = label_width + r * record_width - scroll_left`. Padding and
+    /// border come from the columns' px values. Returns the cells written.
+    pub fn compute_transposed_into_buffer(
+        &mut self,
+        columns: &[ColumnLayout],
+        viewport: &Viewport,
+        transposed: TransposedLayout,
+        records: std::ops::Range<usize>,
+        buf: &mut [f32],
+        header_row_count: usize,
+    ) -> usize {
+        let edges = |rect: &RectValue<LengthValue>| {
+            [rect.top, rect.right, rect.bottom, rect.left].map(fixed_length)
+        };
+        let row_height = viewport.row_height;
+        let mut cell_idx = 0;
+        let mut write = |buf: &mut [f32], row, field: usize, x, width| {
+            let column = &columns[field];
+            layout_buffer::write_cell(
+                buf,
+                cell_idx,
+                row,
+                field,
+                x,
+                scrolled_row_y(field, row_height, 0.0, viewport.scroll_top),
+                width,
+                row_height,
+                column.align,
+                edges(&column.padding),
+                edges(&column.border),
+            );
+            cell_idx += 1;
+        };
+        for field in 0..columns.len() {
+            write(buf, 0, field, 0.0, transposed.label_width);
+        }
+        layout_buffer::add_region(buf, 0..columns.len(), layout_buffer::REGION_PINNED_LEFT);
+        for record in records {
+            let x = (record as f32).mul_add(transposed.record_width, transposed.label_width)
+                - viewport.scroll_left;
+            for field in 0..columns.len() {
+                write(
+                    buf,
+                    record + header_row_count,
+                    field,
+                    x,
+                    transposed.record_width,
+                );
+            }
+        }
+        self.pinned_widths = (transposed.label_width, 0.0);
+        cell_idx
+    }
+
+    /// Compute layout with row pinning: header + footer + top pinned + visible middle +
+    /// bottom pinned, all in viewport y. Middle rows are scrolled by
+    /// `params.scroll_top`, bottom pinned rows sit at the bottom of the viewport, and
+    /// a non-sticky footer goes right after them.
     #[allow(clippy::too_many_lines)]
     pub fn compute_into_buffer_row_pinned(
         &mut self,
@@ -1200,39 +2419,36 @@ impl LayoutEngine {
         if columns.is_empty() || total_rows == 0 {
             return 0;
         }
-        let scrollable_count = total_rows
-            .saturating_sub(pinned_top)
-            .saturating_sub(pinned_bottom);
         let col_count = columns.len();
         let top_cells = pinned_top * col_count;
         let middle_cells = middle_range.len() * col_count;
         let bottom_cells = pinned_bottom * col_count;
-        let total_cells = col_count + top_cells + middle_cells + bottom_cells;
+        let footer_cells = params.viewport.footer_cells(col_count);
+        let total_cells = col_count + footer_cells + top_cells + middle_cells + bottom_cells;
 
         debug_assert!(
             buf.len() >= layout_buffer::buf_len(total_cells),
             "buffer too small for row-pinned layout"
         );
 
-        let (positions, effective_header_height) = self.compute_column_positions(
-            columns,
-            params.container,
-            params.viewport.width,
-            params.viewport.header_height,
-            params.viewport.line_height,
-        );
-        let (row_positions, effective_row_height) =
-            if (params.viewport.row_height - params.viewport.header_height).abs() > f32::EPSILON {
-                self.compute_column_positions(
-                    columns,
-                    params.container,
-                    params.viewport.width,
-                    params.viewport.row_height,
-                    params.viewport.line_height,
-                )
-            } else {
-                (positions.clone(), effective_header_height)
-            };
+        let (mut positions, effective_header_height, header_columns) =
+            self.compute_header_positions(columns, params.container, params.viewport);
+        self.pinned_widths = pin_columns(columns, &mut positions, params.viewport);
+        let (row_positions, effective_row_height) = if self.header.is_some()
+            || (params.viewport.row_height - params.viewport.header_height).abs() > f32::EPSILON
+        {
+            let (mut row_positions, h) = self.compute_column_positions(
+                columns,
+                params.container,
+                params.viewport.width,
+                params.viewport.row_height,
+                params.viewport.line_height,
+            );
+            pin_columns(columns, &mut row_positions, params.viewport);
+            (row_positions, h)
+        } else {
+            (positions.clone(), effective_header_height)
+        };
 
         let mut cell_idx = 0;
 
@@ -1247,7 +2463,7 @@ impl LayoutEngine {
                 pos.y,
                 pos.width,
                 pos.height,
-                columns
+                header_columns
                     .get(col_idx)
                     .map_or_else(Align::default, |c| c.align),
                 pos.padding,
@@ -1255,6 +2471,22 @@ impl LayoutEngine {
             );
             cell_idx += 1;
         }
+        rotate_header_labels(&header_columns, buf, 0);
+        layout_buffer::add_region(buf, 0..cell_idx, layout_buffer::REGION_STICKY_HEADER);
+
+        let footer_y = if params.viewport.sticky_footer {
+            params.viewport.height - params.viewport.footer_height
+        } else {
+            params.viewport.height
+        };
+        cell_idx += self.write_footer_row(
+            columns,
+            params.container,
+            params.viewport,
+            buf,
+            cell_idx,
+            footer_y,
+        );
 
         // Top pinned rows: y = header_height + row_idx * row_height
         let header_row_count = params.header_row_count;
@@ -1281,10 +2513,14 @@ impl LayoutEngine {
             }
         }
 
-        // Middle (scrollable) rows: absolute content y (scroll handled by JS translateY)
+        // Middle (scrollable) rows: scrolled in f64 like the unpinned layout
         for row_idx in middle_range.start..middle_range.end {
-            let row_base_y =
-                (row_idx as f32).mul_add(effective_row_height, effective_header_height);
+            let row_base_y = scrolled_row_y(
+                row_idx,
+                effective_row_height,
+                effective_header_height,
+                params.scroll_top,
+            );
             for (col_idx, pos) in row_positions.iter().enumerate() {
                 layout_buffer::write_cell(
                     buf,
@@ -1305,10 +2541,10 @@ impl LayoutEngine {
             }
         }
 
-        // Bottom pinned rows: y = header + (pinned_top + scrollable_count)*rh + (row_idx - (total - pinned_bottom))*rh
+        // Bottom pinned rows: stacked up from the bottom of the viewport
         let bottom_start = total_rows.saturating_sub(pinned_bottom);
-        let bottom_base_y = ((pinned_top + scrollable_count) as f32)
-            .mul_add(effective_row_height, effective_header_height);
+        let bottom_base_y =
+            (pinned_bottom as f32).mul_add(-effective_row_height, params.viewport.height);
         for (i, row_idx) in (bottom_start..total_rows).enumerate() {
             let row_base_y = (i as f32).mul_add(effective_row_height, bottom_base_y);
             for (col_idx, pos) in row_positions.iter().enumerate() {
@@ -1330,6 +2566,7 @@ impl LayoutEngine {
                 cell_idx += 1;
             }
         }
+        tag_pinned_cells(columns, buf, total_cells);
 
         total_cells
     }
@@ -1474,7 +2711,11 @@ impl LayoutEngine {
             })
             .collect();
 
-        self.tree.clear();
+        for node in child_nodes.into_iter().chain([root]) {
+            self.tree
+                .remove(node)
+                .expect("failed to remove composite node");
+        }
         positions
     }
 }
@@ -1497,9 +2738,7 @@ impl LayoutEngine {
         line_height: f32,
     ) -> bool {
         let key = hash_layout_inputs(columns, container, viewport_width, row_height, line_height);
-        self.cache_slots
-            .iter()
-            .any(|s| s.as_ref().is_some_and(|c| c.key == key))
+        self.cache.iter().any(|c| c.key == key)
     }
 }
 
@@ -1589,9 +2828,12 @@ impl LayoutEngine {
             Vec::with_capacity((visible_range.end - visible_range.start) * columns.len());
 
         for row_idx in visible_range {
-            let row_base_y = (row_idx as f32)
-                .mul_add(effective_row_height, effective_header_height)
-                - viewport.scroll_top;
+            let row_base_y = scrolled_row_y(
+                row_idx,
+                effective_row_height,
+                effective_header_height,
+                viewport.scroll_top,
+            );
             for (col_idx, pos) in positions.iter().enumerate() {
                 result.push(CellLayout {
                     row: row_idx,
@@ -1637,7 +2879,11 @@ mod tests {
             row_height: 36.0,
             header_height: 40.0,
             scroll_top: 0.0,
+            scroll_left: 0.0,
             line_height: 20.0,
+            sticky_header: false,
+            footer_height: 0.0,
+            sticky_footer: false,
         }
     }
 
@@ -1823,133 +3069,585 @@ mod tests {
         let mut viewport = make_viewport();
         viewport.scroll_top = 18.0; // half a row (36/2)
 
-        let rows = engine.compute_rows_layout(
-            &make_single_column(),
-            &viewport,
+        let rows = engine.compute_rows_layout(
+            &make_single_column(),
+            &viewport,
+            &default_container(),
+            0..3,
+        );
+        // Row 0: 40 + 0 - 18 = 22  (shifted up by 18px)
+        assert!((rows[0].y - 22.0).abs() < 0.1);
+        // Row 1: 40 + 36 - 18 = 58
+        assert!((rows[1].y - 58.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn scroll_large_offset_far_down() {
+        let mut engine = LayoutEngine::new();
+        let mut viewport = make_viewport();
+        viewport.scroll_top = 35_640.0; // row 990: 990*36 = 35_640
+
+        let rows = engine.compute_rows_layout(
+            &make_single_column(),
+            &viewport,
+            &default_container(),
+            985..1000,
+        );
+        assert_eq!(rows.len(), 15);
+
+        // Row 985: 40 + 985*36 - 35640 = 40 + 35460 - 35640 = -140
+        assert!((rows[0].y - -140.0).abs() < 0.1);
+        // Row 990: 40 + 990*36 - 35640 = 40 + 35640 - 35640 = 40
+        assert!((rows[5].y - 40.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn scroll_beyond_f32_precision_keeps_rows_exact() {
+        let mut engine = LayoutEngine::new();
+        let mut viewport = make_viewport();
+        // Row 12_345_678 half scrolled past: 12_345_678*36 + 18 (above 2^24 px)
+        viewport.scroll_top = 444_444_426.0;
+
+        let rows = engine.compute_rows_layout(
+            &make_single_column(),
+            &viewport,
+            &default_container(),
+            12_345_678..12_345_681,
+        );
+        assert_eq!(
+            rows.iter().map(|r| r.y).collect::<Vec<_>>(),
+            vec![22.0, 58.0, 94.0]
+        );
+    }
+
+    #[test]
+    fn scroll_does_not_affect_header_layout() {
+        let mut engine = LayoutEngine::new();
+        let mut viewport = make_viewport();
+        viewport.scroll_top = 500.0;
+
+        let header =
+            engine.compute_header_layout(&make_single_column(), &viewport, &default_container());
+        // Header is always pinned at y=0, regardless of scroll
+        assert!((header[0].y - 0.0).abs() < 0.1);
+        assert!((header[0].height - viewport.header_height).abs() < 0.1);
+    }
+
+    // ── Buffer output tests ──────────────────────────────────────────
+
+    #[test]
+    fn compute_into_buffer_matches_struct_output() {
+        init_logger();
+        let mut engine = LayoutEngine::new();
+        let columns = vec![col(100.0, Align::Left), col(200.0, Align::Right)];
+        let viewport = make_viewport();
+        let container = default_container();
+
+        // Struct-based output
+        let headers = engine.compute_header_layout(&columns, &viewport, &container);
+        let rows = engine.compute_rows_layout(&columns, &viewport, &container, 0..3);
+
+        // Buffer-based output
+        let col_count = columns.len();
+        let total_cells = col_count + 3 * col_count; // 2 header + 6 data = 8
+        let mut buf = vec![0.0_f32; layout_buffer::buf_len(total_cells)];
+        let header_row_count = 1;
+        let count = engine.compute_into_buffer(
+            &columns,
+            &viewport,
+            &container,
+            0..3,
+            &mut buf,
+            header_row_count,
+        );
+        assert_eq!(count, total_cells);
+
+        // Verify header cells match
+        for (i, h) in headers.iter().enumerate() {
+            let base = i * layout_buffer::LAYOUT_STRIDE;
+            assert!((buf[base + layout_buffer::FIELD_X] - h.x).abs() < 0.1);
+            assert!((buf[base + layout_buffer::FIELD_Y] - h.y).abs() < 0.1);
+            assert!((buf[base + layout_buffer::FIELD_WIDTH] - h.width).abs() < 0.1);
+            assert!((buf[base + layout_buffer::FIELD_HEIGHT] - h.height).abs() < 0.1);
+        }
+
+        // Verify data cells match (row is offset by header_row_count in unified indexing)
+        for (i, r) in rows.iter().enumerate() {
+            let base = (col_count + i) * layout_buffer::LAYOUT_STRIDE;
+            assert!(
+                (buf[base + layout_buffer::FIELD_ROW] - (r.row + header_row_count) as f32).abs()
+                    < 0.1
+            );
+            assert!((buf[base + layout_buffer::FIELD_COL] - r.col as f32).abs() < 0.1);
+            assert!((buf[base + layout_buffer::FIELD_X] - r.x).abs() < 0.1);
+            assert!((buf[base + layout_buffer::FIELD_Y] - r.y).abs() < 0.1);
+            assert!((buf[base + layout_buffer::FIELD_WIDTH] - r.width).abs() < 0.1);
+            assert!((buf[base + layout_buffer::FIELD_HEIGHT] - r.height).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn compute_into_buffer_with_scroll() {
+        let mut engine = LayoutEngine::new();
+        let columns = make_single_column();
+        let mut viewport = make_viewport();
+        viewport.scroll_top = 360.0;
+
+        let total_cells = 1 + 10; // 1 header + 10 data
+        let mut buf = vec![0.0_f32; layout_buffer::buf_len(total_cells)];
+        let count = engine.compute_into_buffer(
+            &columns,
+            &viewport,
+            &default_container(),
+            5..15,
+            &mut buf,
+            1,
+        );
+        assert_eq!(count, total_cells);
+
+        // Header scrolls with content: y = -scroll_top = -360
+        assert!((buf[layout_buffer::FIELD_Y] - -360.0).abs() < 0.1);
+
+        // Row 5: y = 40 + 5*36 - 360 = -140
+        let base = layout_buffer::LAYOUT_STRIDE;
+        assert!((buf[base + layout_buffer::FIELD_Y] - -140.0).abs() < 0.1);
+
+        // Row 10: y = 40 + 10*36 - 360 = 40
+        let base = 6 * layout_buffer::LAYOUT_STRIDE;
+        assert!((buf[base + layout_buffer::FIELD_Y] - 40.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn compute_into_buffer_sticky_header() {
+        let mut engine = LayoutEngine::new();
+        let columns = make_single_column();
+        let mut viewport = make_viewport();
+        viewport.scroll_top = 360.0;
+        viewport.sticky_header = true;
+
+        let mut buf = vec![0.0_f32; layout_buffer::buf_len(11)];
+        engine.compute_into_buffer(
+            &columns,
+            &viewport,
+            &default_container(),
+            5..15,
+            &mut buf,
+            1,
+        );
+
+        // Header pinned at y = 0 and tagged; rows still scroll (row 10 at y = 40)
+        let stride = layout_buffer::LAYOUT_STRIDE;
+        assert!(buf[layout_buffer::FIELD_Y].abs() < 0.1);
+        assert!((buf[6 * stride + layout_buffer::FIELD_Y] - 40.0).abs() < 0.1);
+        let regions: Vec<f32> = (0..11)
+            .map(|i| buf[i * stride + layout_buffer::FIELD_REGION])
+            .collect();
+        let mut expected = vec![layout_buffer::REGION_BODY; 11];
+        expected[0] = layout_buffer::REGION_STICKY_HEADER;
+        assert_eq!(regions, expected);
+    }
+
+    #[test]
+    fn compute_into_buffer_footer_row() {
+        let mut engine = LayoutEngine::new();
+        let columns = make_single_column();
+        let mut viewport = make_viewport();
+        viewport.footer_height = 30.0;
+        let stride = layout_buffer::LAYOUT_STRIDE;
+        let field = |buf: &[f32], cell: usize, f: usize| buf[cell * stride + f];
+
+        // Header, footer, then rows 0..3; the footer follows the last row
+        let mut buf = vec![0.0_f32; layout_buffer::buf_len(5)];
+        let cells = engine.compute_into_buffer(
+            &columns,
+            &viewport,
+            &default_container(),
+            0..3,
+            &mut buf,
+            1,
+        );
+        assert_eq!(cells, 5);
+        assert!((field(&buf, 1, layout_buffer::FIELD_Y) - 148.0).abs() < 0.1);
+        assert!((field(&buf, 1, layout_buffer::FIELD_HEIGHT) - 30.0).abs() < 0.1);
+        assert!((field(&buf, 2, layout_buffer::FIELD_Y) - 40.0).abs() < 0.1);
+        assert_eq!(
+            vec![
+                field(&buf, 1, layout_buffer::FIELD_REGION),
+                field(&buf, 2, layout_buffer::FIELD_ROW)
+            ],
+            vec![layout_buffer::REGION_FOOTER, 1.0]
+        );
+
+        // Sticky: bottom of the viewport
+        viewport.sticky_footer = true;
+        engine.compute_into_buffer(&columns, &viewport, &default_container(), 0..3, &mut buf, 1);
+        assert!((field(&buf, 1, layout_buffer::FIELD_Y) - 370.0).abs() < 0.1);
+        assert_eq!(
+            vec![field(&buf, 1, layout_buffer::FIELD_REGION)],
+            vec![layout_buffer::REGION_FOOTER + layout_buffer::REGION_STICKY_HEADER]
+        );
+
+        // Row-pinned: after the bottom pinned rows at the bottom of the viewport
+        viewport.sticky_footer = false;
+        let params = RowPinnedLayoutParams {
+            viewport: &viewport,
+            container: &default_container(),
+            pinned_top: 1,
+            pinned_bottom: 1,
+            scroll_top: 0.0,
+            total_rows: 10,
+            middle_range: 1..3,
+            header_row_count: 1,
+        };
+        let mut buf = vec![0.0_f32; layout_buffer::buf_len(6)];
+        let cells = engine.compute_into_buffer_row_pinned(&columns, &params, &mut buf);
+        assert_eq!(cells, 6);
+        assert!((field(&buf, 1, layout_buffer::FIELD_Y) - 400.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn row_pinned_layout_uses_viewport_y_deep_in_large_tables() {
+        let mut engine = LayoutEngine::new();
+        let columns = vec![col(100.0, Align::Left)];
+        let viewport = make_viewport();
+        // Row 9_999_998 scrolled to just below the pinned top row
+        let params = RowPinnedLayoutParams {
+            viewport: &viewport,
+            container: &default_container(),
+            pinned_top: 1,
+            pinned_bottom: 1,
+            scroll_top: 9_999_997.0 * 36.0,
+            total_rows: 10_000_001,
+            middle_range: 9_999_998..10_000_000,
+            header_row_count: 1,
+        };
+        let mut buf = vec![0.0_f32; layout_buffer::buf_len(5)];
+        let cells = engine.compute_into_buffer_row_pinned(&columns, &params, &mut buf);
+        let ys: Vec<f32> = (0..cells)
+            .map(|i| buf[i * layout_buffer::LAYOUT_STRIDE + layout_buffer::FIELD_Y])
+            .collect();
+        // Header, top pinned, two middle rows, bottom pinned at the viewport bottom
+        assert_eq!(ys, vec![0.0, 40.0, 76.0, 112.0, 364.0]);
+    }
+
+    #[test]
+    fn header_layout_overrides_header_cells_only() {
+        let mut engine = LayoutEngine::new();
+        let columns = vec![col(100.0, Align::Left), col(200.0, Align::Left)];
+        let viewport = make_viewport();
+        let stride = layout_buffer::LAYOUT_STRIDE;
+        let field = |buf: &[f32], cell: usize, f: usize| buf[cell * stride + f];
+        engine.set_header_layout(Some(HeaderLayout {
+            container: Some(ContainerLayout {
+                padding: RectValue {
+                    left: LengthValue::Length(10.0),
+                    ..RectValue::default()
+                },
+                ..ContainerLayout::default()
+            }),
+            columns: vec![
+                Some(ColumnLayout {
+                    padding: RectValue {
+                        left: LengthValue::Length(8.0),
+                        ..RectValue::default()
+                    },
+                    // Width comes from the data column
+                    ..col(0.0, Align::Right)
+                }),
+                None,
+            ],
+        }));
+
+        let mut buf = vec![0.0_f32; layout_buffer::buf_len(4)];
+        engine.compute_into_buffer(&columns, &viewport, &default_container(), 0..1, &mut buf, 1);
+        // Header: shifted by the container padding, first column restyled
+        assert!((field(&buf, 0, layout_buffer::FIELD_X) - 10.0).abs() < 0.1);
+        assert!((field(&buf, 0, layout_buffer::FIELD_WIDTH) - 100.0).abs() < 0.1);
+        assert!((field(&buf, 0, layout_buffer::FIELD_PADDING_LEFT) - 8.0).abs() < 0.1);
+        assert_eq!(
+            vec![
+                field(&buf, 0, layout_buffer::FIELD_ALIGN),
+                field(&buf, 1, layout_buffer::FIELD_ALIGN)
+            ],
+            vec![2.0, 0.0]
+        );
+        // Rows keep the data styles
+        assert!(field(&buf, 2, layout_buffer::FIELD_X).abs() < 0.1);
+        assert!(field(&buf, 2, layout_buffer::FIELD_PADDING_LEFT).abs() < 0.1);
+        assert!((field(&buf, 3, layout_buffer::FIELD_X) - 100.0).abs() < 0.1);
+
+        engine.set_header_layout(None);
+        engine.compute_into_buffer(&columns, &viewport, &default_container(), 0..1, &mut buf, 1);
+        assert!(field(&buf, 0, layout_buffer::FIELD_X).abs() < 0.1);
+        assert!(field(&buf, 0, layout_buffer::FIELD_ALIGN).abs() < 0.1);
+    }
+
+    #[test]
+    fn rotated_header_labels() {
+        let (width, height) = HeaderOrientation::Vertical.extent(80.0, 20.0);
+        assert!((width - 20.0).abs() < 0.01 && (height - 80.0).abs() < 0.01);
+        let (width, height) = HeaderOrientation::Angled(30.0).extent(80.0, 20.0);
+        assert!((width - 79.28).abs() < 0.01 && (height - 57.32).abs() < 0.01);
+
+        let mut columns = vec![
+            ColumnLayout {
+                header_orientation: HeaderOrientation::Vertical,
+                header_label_width: 80.0,
+                padding: RectValue {
+                    top: LengthValue::Length(6.0),
+                    bottom: LengthValue::Length(6.0),
+                    ..RectValue::default()
+                },
+                ..col(40.0, Align::Left)
+            },
+            ColumnLayout {
+                header_label_width: 200.0,
+                ..col(100.0, Align::Left)
+            },
+        ];
+        assert!((rotated_header_height(&columns, 20.0) - 92.0).abs() < 0.01);
+
+        // Header cells carry the rotation; data cells do not
+        let mut engine = LayoutEngine::new();
+        let mut buf = vec![0.0_f32; layout_buffer::buf_len(4)];
+        engine.compute_into_buffer(
+            &columns,
+            &make_viewport(),
             &default_container(),
-            0..3,
+            0..1,
+            &mut buf,
+            1,
         );
-        // Row 0: 40 + 0 - 18 = 22  (shifted up by 18px)
-        assert!((rows[0].y - 22.0).abs() < 0.1);
-        // Row 1: 40 + 36 - 18 = 58
-        assert!((rows[1].y - 58.0).abs() < 0.1);
+        let rotations: Vec<f32> = (0..4)
+            .map(|cell| buf[cell * layout_buffer::LAYOUT_STRIDE + layout_buffer::FIELD_ROTATION])
+            .collect();
+        assert_eq!(rotations, vec![90.0, 0.0, 0.0, 0.0]);
+
+        columns[0].header_orientation = HeaderOrientation::Horizontal;
+        assert!(rotated_header_height(&columns, 20.0).abs() < 0.01);
     }
 
     #[test]
-    fn scroll_large_offset_far_down() {
-        let mut engine = LayoutEngine::new();
-        let mut viewport = make_viewport();
-        viewport.scroll_top = 35_640.0; // row 990: 990*36 = 35_640
+    fn header_layout_selects_columns() {
+        let header = HeaderLayout {
+            container: None,
+            columns: vec![None, Some(col(0.0, Align::Center)), None],
+        };
+        let selected = header.select(&[1, 2]);
+        assert!(selected.columns[0].is_some());
+        assert!(selected.columns[1].is_none());
+        assert_eq!(selected.key(), header.select(&[1, 0]).key());
+        assert_ne!(selected.key(), header.key());
+    }
 
-        let rows = engine.compute_rows_layout(
-            &make_single_column(),
+    #[test]
+    fn cell_items_laid_out_in_content_box() {
+        let mut engine = LayoutEngine::new();
+        let columns = vec![
+            ColumnLayout {
+                padding: RectValue {
+                    top: LengthValue::Zero,
+                    right: LengthValue::Length(8.0),
+                    bottom: LengthValue::Zero,
+                    left: LengthValue::Length(8.0),
+                },
+                content: Some(Box::new(CellContent {
+                    container: ContainerLayout::default(),
+                    items: vec![
+                        col(16.0, Align::Left),
+                        col_flex(0.0, 1.0, 1.0),
+                        col(30.0, Align::Right),
+                    ],
+                })),
+                ..col(200.0, Align::Left)
+            },
+            col(100.0, Align::Left),
+        ];
+        assert_eq!(cell_items_per_row(&columns), 3);
+        let viewport = make_viewport();
+        let mut buf = vec![0.0_f32; layout_buffer::buf_len(12)];
+        let cells = engine.compute_into_buffer(
+            &columns,
             &viewport,
             &default_container(),
-            985..1000,
+            0..2,
+            &mut buf,
+            1,
         );
-        assert_eq!(rows.len(), 15);
-
-        // Row 985: 40 + 985*36 - 35640 = 40 + 35460 - 35640 = -140
-        assert!((rows[0].y - -140.0).abs() < 0.1);
-        // Row 990: 40 + 990*36 - 35640 = 40 + 35640 - 35640 = 40
-        assert!((rows[5].y - 40.0).abs() < 0.1);
+        assert_eq!(cells, 6);
+        let items = engine.append_cell_items(&columns, 20.0, &mut buf, 2..cells, cells);
+        assert_eq!(items, 6);
+
+        let stride = layout_buffer::LAYOUT_STRIDE;
+        let item = |i: usize| {
+            [
+                layout_buffer::FIELD_ROW,
+                layout_buffer::FIELD_COL,
+                layout_buffer::FIELD_ITEM,
+                layout_buffer::FIELD_X,
+                layout_buffer::FIELD_Y,
+                layout_buffer::FIELD_WIDTH,
+                layout_buffer::FIELD_REGION,
+            ]
+            .map(|f| buf[(cells + i) * stride + f])
+            .to_vec()
+        };
+        // Items of row 0 (cell 2), then of row 1 (cell 4), in their cell's row
+        // and column
+        assert_eq!(item(0), vec![1.0, 0.0, 0.0, 8.0, 40.0, 16.0, 256.0]);
+        assert_eq!(item(1), vec![1.0, 0.0, 1.0, 24.0, 40.0, 138.0, 256.0]);
+        assert_eq!(item(2), vec![1.0, 0.0, 2.0, 162.0, 40.0, 30.0, 256.0]);
+        assert_eq!(item(3), vec![2.0, 0.0, 0.0, 8.0, 76.0, 16.0, 256.0]);
     }
 
     #[test]
-    fn scroll_does_not_affect_header_layout() {
+    fn baselines_follow_vertical_align() {
         let mut engine = LayoutEngine::new();
-        let mut viewport = make_viewport();
-        viewport.scroll_top = 500.0;
-
-        let header =
-            engine.compute_header_layout(&make_single_column(), &viewport, &default_container());
-        // Header is always pinned at y=0, regardless of scroll
-        assert!((header[0].y - 0.0).abs() < 0.1);
-        assert!((header[0].height - viewport.header_height).abs() < 0.1);
+        let padded = |vertical_align| ColumnLayout {
+            vertical_align,
+            padding: RectValue {
+                top: LengthValue::Length(4.0),
+                right: LengthValue::Zero,
+                bottom: LengthValue::Length(4.0),
+                left: LengthValue::Zero,
+            },
+            ..col(100.0, Align::Left)
+        };
+        let columns = vec![
+            padded(VerticalAlign::Top),
+            padded(VerticalAlign::Middle),
+            padded(VerticalAlign::Bottom),
+        ];
+        let mut buf = vec![0.0_f32; layout_buffer::buf_len(6)];
+        let cells = engine.compute_into_buffer(
+            &columns,
+            &make_viewport(),
+            &default_container(),
+            0..1,
+            &mut buf,
+            1,
+        );
+        write_baselines(&columns, 20.0, &mut buf, 0..cells);
+        let baselines: Vec<f32> = (3..6)
+            .map(|i| buf[i * layout_buffer::LAYOUT_STRIDE + layout_buffer::FIELD_BASELINE])
+            .collect();
+        // Row 0 content box: y 44..72
+        assert_eq!(baselines, vec![54.0, 58.0, 62.0]);
     }
 
-    // ── Buffer output tests ──────────────────────────────────────────
-
     #[test]
-    fn compute_into_buffer_matches_struct_output() {
-        init_logger();
+    fn transposed_layout_places_records_as_columns() {
+        use layout_buffer::{FIELD_COL, FIELD_ROW, FIELD_X, FIELD_Y, LAYOUT_STRIDE};
         let mut engine = LayoutEngine::new();
-        let columns = vec![col(100.0, Align::Left), col(200.0, Align::Right)];
-        let viewport = make_viewport();
-        let container = default_container();
-
-        // Struct-based output
-        let headers = engine.compute_header_layout(&columns, &viewport, &container);
-        let rows = engine.compute_rows_layout(&columns, &viewport, &container, 0..3);
-
-        // Buffer-based output
-        let col_count = columns.len();
-        let total_cells = col_count + 3 * col_count; // 2 header + 6 data = 8
-        let mut buf = vec![0.0_f32; layout_buffer::buf_len(total_cells)];
-        let header_row_count = 1;
-        let count = engine.compute_into_buffer(
+        let columns = vec![col(100.0, Align::Left), col(100.0, Align::Right)];
+        let viewport = Viewport {
+            scroll_top: 10.0,
+            scroll_left: 50.0,
+            ..make_viewport()
+        };
+        let transposed = TransposedLayout {
+            record_width: 150.0,
+            label_width: 120.0,
+        };
+        let mut buf = vec![0.0; layout_buffer::buf_len(6)];
+        let cells = engine.compute_transposed_into_buffer(
             &columns,
             &viewport,
-            &container,
-            0..3,
+            transposed,
+            3..5,
             &mut buf,
-            header_row_count,
+            1,
         );
-        assert_eq!(count, total_cells);
-
-        // Verify header cells match
-        for (i, h) in headers.iter().enumerate() {
-            let base = i * layout_buffer::LAYOUT_STRIDE;
-            assert!((buf[base + layout_buffer::FIELD_X] - h.x).abs() < 0.1);
-            assert!((buf[base + layout_buffer::FIELD_Y] - h.y).abs() < 0.1);
-            assert!((buf[base + layout_buffer::FIELD_WIDTH] - h.width).abs() < 0.1);
-            assert!((buf[base + layout_buffer::FIELD_HEIGHT] - h.height).abs() < 0.1);
-        }
-
-        // Verify data cells match (row is offset by header_row_count in unified indexing)
-        for (i, r) in rows.iter().enumerate() {
-            let base = (col_count + i) * layout_buffer::LAYOUT_STRIDE;
-            assert!(
-                (buf[base + layout_buffer::FIELD_ROW] - (r.row + header_row_count) as f32).abs()
-                    < 0.1
-            );
-            assert!((buf[base + layout_buffer::FIELD_COL] - r.col as f32).abs() < 0.1);
-            assert!((buf[base + layout_buffer::FIELD_X] - r.x).abs() < 0.1);
-            assert!((buf[base + layout_buffer::FIELD_Y] - r.y).abs() < 0.1);
-            assert!((buf[base + layout_buffer::FIELD_WIDTH] - r.width).abs() < 0.1);
-            assert!((buf[base + layout_buffer::FIELD_HEIGHT] - r.height).abs() < 0.1);
-        }
+        assert_eq!(cells, 6);
+        let read = |cell: usize| {
+            [FIELD_ROW, FIELD_COL, FIELD_X, FIELD_Y].map(|f| buf[cell * LAYOUT_STRIDE + f])
+        };
+        // Labels, then record 3 (x = 120 + 450 - 50) and record 4, field by field
+        assert_eq!(
+            (0..cells).map(read).collect::<Vec<_>>(),
+            vec![
+                [0.0, 0.0, 0.0, -10.0],
+                [0.0, 1.0, 0.0, 26.0],
+                [4.0, 0.0, 520.0, -10.0],
+                [4.0, 1.0, 520.0, 26.0],
+                [5.0, 0.0, 670.0, -10.0],
+                [5.0, 1.0, 670.0, 26.0],
+            ]
+        );
+        assert_eq!(engine.pinned_widths(), (120.0, 0.0));
+        assert!((transposed.content_width(10) - 1620.0).abs() < 0.1);
     }
 
     #[test]
-    fn compute_into_buffer_with_scroll() {
+    fn compute_into_buffer_pinned_columns() {
         let mut engine = LayoutEngine::new();
-        let columns = make_single_column();
+        let columns = vec![
+            ColumnLayout {
+                pinned: Some(ColumnPin::Left),
+                ..col(100.0, Align::Left)
+            },
+            col(200.0, Align::Left),
+            ColumnLayout {
+                pinned: Some(ColumnPin::Right),
+                ..col(150.0, Align::Left)
+            },
+        ];
         let mut viewport = make_viewport();
-        viewport.scroll_top = 360.0;
+        viewport.scroll_left = 50.0;
 
-        let total_cells = 1 + 10; // 1 header + 10 data
-        let mut buf = vec![0.0_f32; layout_buffer::buf_len(total_cells)];
+        let mut buf = vec![0.0_f32; layout_buffer::buf_len(6)];
         let count = engine.compute_into_buffer(
             &columns,
             &viewport,
             &default_container(),
-            5..15,
+            0..1,
             &mut buf,
             1,
         );
-        assert_eq!(count, total_cells);
+        assert_eq!(count, 6);
+        let stride = layout_buffer::LAYOUT_STRIDE;
+        let field = |f: usize| -> Vec<f32> { (0..6).map(|i| buf[i * stride + f]).collect() };
+
+        // Left pinned at 0, scrollable shifted by scroll_left, right flush with the edge
+        assert_eq!(
+            field(layout_buffer::FIELD_X),
+            vec![0.0, 50.0, 450.0, 0.0, 50.0, 450.0]
+        );
+        assert_eq!(
+            field(layout_buffer::FIELD_REGION),
+            vec![2.0, 0.0, 4.0, 2.0, 0.0, 4.0]
+        );
+        assert_eq!(engine.pinned_widths(), (100.0, 150.0));
+    }
 
-        // Header scrolls with content: y = -scroll_top = -360
-        assert!((buf[layout_buffer::FIELD_Y] - -360.0).abs() < 0.1);
+    #[test]
+    fn compute_into_buffer_scroll_left_and_content_width() {
+        let mut engine = LayoutEngine::new();
+        let columns = vec![col(400.0, Align::Left), col(300.0, Align::Left)];
+        let mut viewport = make_viewport();
+        viewport.scroll_left = 120.0;
+        let container = ContainerLayout {
+            padding: RectValue {
+                top: LengthValue::Length(0.0),
+                right: LengthValue::Length(10.0),
+                bottom: LengthValue::Length(0.0),
+                left: LengthValue::Length(5.0),
+            },
+            ..default_container()
+        };
 
-        // Row 5: y = 40 + 5*36 - 360 = -140
-        let base = 1 * layout_buffer::LAYOUT_STRIDE;
-        assert!((buf[base + layout_buffer::FIELD_Y] - -140.0).abs() < 0.1);
+        let mut buf = vec![0.0_f32; layout_buffer::buf_len(4)];
+        engine.compute_into_buffer(&columns, &viewport, &container, 0..1, &mut buf, 1);
+        let stride = layout_buffer::LAYOUT_STRIDE;
+        let xs: Vec<f32> = (0..4)
+            .map(|i| buf[i * stride + layout_buffer::FIELD_X])
+            .collect();
+        assert_eq!(xs, vec![-115.0, 285.0, -115.0, 285.0]);
+        assert!((engine.content_width() - 715.0).abs() < f32::EPSILON);
 
-        // Row 10: y = 40 + 10*36 - 360 = 40
-        let base = 6 * layout_buffer::LAYOUT_STRIDE;
-        assert!((buf[base + layout_buffer::FIELD_Y] - 40.0).abs() < 0.1);
+        // Cache hit keeps the content width
+        engine.compute_into_buffer(&columns, &viewport, &container, 0..1, &mut buf, 1);
+        assert!((engine.content_width() - 715.0).abs() < f32::EPSILON);
     }
 
     #[test]
@@ -2105,6 +3803,41 @@ mod tests {
         assert!((header[2].width - 200.0).abs() < 1.0);
     }
 
+    #[test]
+    fn content_sized_tracks_use_content_size() {
+        let mut engine = LayoutEngine::new();
+        let container = grid_container(vec![
+            TrackListItem::Single(TrackSizeValue::MinContent),
+            TrackListItem::Single(TrackSizeValue::MaxContent),
+            TrackListItem::Single(TrackSizeValue::FitContentPx(100.0)),
+            TrackListItem::Single(TrackSizeValue::Fr(1.0)),
+        ]);
+        let text = ColumnLayout {
+            content_size: Some(ContentSize {
+                min: 40.0,
+                max: 120.0,
+            }),
+            ..grid_col_default()
+        };
+        let columns = vec![text.clone(), text.clone(), text, grid_col_default()];
+        let viewport = make_viewport(); // width=600
+
+        let header = engine.compute_header_layout(&columns, &viewport, &container);
+        let widths: Vec<f32> = header.iter().map(|c| c.width.round()).collect();
+        assert_eq!(widths, vec![40.0, 120.0, 100.0, 340.0]);
+
+        // A flex column without a width takes its max-content width
+        let flex = vec![ColumnLayout {
+            content_size: Some(ContentSize {
+                min: 40.0,
+                max: 120.0,
+            }),
+            ..ColumnLayout::default()
+        }];
+        let header = engine.compute_header_layout(&flex, &viewport, &default_container());
+        assert!((header[0].width - 120.0).abs() < 0.5);
+    }
+
     #[test]
     fn grid_auto_flow_column() {
         let mut engine = LayoutEngine::new();
@@ -2346,6 +4079,65 @@ mod tests {
         assert!((header[0].width - 300.0).abs() < 1.0);
     }
 
+    #[test]
+    fn calc_value_parse_and_resolve() {
+        let resolve = |expr: &str, basis: f32| CalcValue::parse(expr).map(|c| c.resolve(basis));
+        let cases = [
+            ("calc(100% - 40px)", 600.0, 560.0),
+            ("calc((100% - 20px) / 2)", 600.0, 290.0),
+            ("calc(2 * min(10%, 50px) + 5px)", 1000.0, 105.0),
+            ("min(300px, 50%)", 400.0, 200.0),
+            ("min(300px, 50%)", 800.0, 300.0),
+            ("max(100px, 10%)", 600.0, 100.0),
+            ("clamp(100px, 25%, 300px)", 200.0, 100.0),
+            ("clamp(100px, 25%, 300px)", 600.0, 150.0),
+            ("clamp(100px, 25%, 300px)", 2000.0, 300.0),
+            ("calc(-1 * max(10px, 5%))", 400.0, -20.0),
+        ];
+        for (expr, basis, expected) in cases {
+            let px = resolve(expr, basis).unwrap();
+            assert!((px - expected).abs() < 0.01, "{expr} @ {basis}: {px}");
+        }
+        for expr in [
+            "50%",
+            "calc(10px",
+            "calc(10px * 2px)",
+            "calc(10px / 0)",
+            "min(1px, 2px, 3px)",
+            "calc(min(1px, 2%) + max(3px, 4%))",
+        ] {
+            assert!(CalcValue::parse(expr).is_none(), "{expr}");
+        }
+    }
+
+    #[test]
+    fn calc_resolves_against_viewport() {
+        let calc = |expr| CalcValue::parse(expr).unwrap();
+        let mut engine = LayoutEngine::new();
+        let viewport = make_viewport(); // width=600
+        let columns = vec![ColumnLayout {
+            width: 0.0,
+            flex_basis: DimensionValue::Calc(calc("calc(50% - 20px)")),
+            ..ColumnLayout::default()
+        }];
+        let header = engine.compute_header_layout(&columns, &viewport, &default_container());
+        assert!((header[0].width - 280.0).abs() < 1.0);
+
+        let container = ContainerLayout {
+            display: DisplayValue::Grid,
+            grid_template_columns: vec![
+                TrackListItem::Single(TrackSizeValue::Calc(calc("calc(100% - 160px)"))),
+                TrackListItem::Single(TrackSizeValue::Fr(1.0)),
+            ],
+            column_gap: Some(LengthValue::Calc(calc("min(60px, 5%)"))),
+            ..ContainerLayout::default()
+        };
+        let columns = vec![grid_col_default(), grid_col_default()];
+        let header = engine.compute_header_layout(&columns, &viewport, &container);
+        let rects: Vec<(f32, f32)> = header.iter().map(|c| (c.x, c.width)).collect();
+        assert_eq!(rects, vec![(0.0, 440.0), (470.0, 130.0)]);
+    }
+
     // ── Coverage: LengthValue Percent branch ───────────────────────────
 
     #[test]
@@ -3454,6 +5246,89 @@ mod tests {
         assert!(header[0].y > 0.0);
     }
 
+    #[test]
+    fn grid_named_line_placement() {
+        let mut engine = LayoutEngine::new();
+        // [side] 100px [main-start] 1fr [main-end]
+        let container = ContainerLayout {
+            display: DisplayValue::Grid,
+            grid_template_columns: vec![
+                TrackListItem::Names(vec!["side".into()]),
+                TrackListItem::Single(TrackSizeValue::Length(100.0)),
+                TrackListItem::Names(vec!["main-start".into()]),
+                TrackListItem::Single(TrackSizeValue::Fr(1.0)),
+                TrackListItem::Names(vec!["main-end".into()]),
+            ],
+            ..ContainerLayout::default()
+        };
+        let columns = vec![ColumnLayout {
+            grid_column: Some(GridLineValue {
+                start: GridPlacementValue::Named("main".into(), 1),
+                end: GridPlacementValue::Named("main".into(), 1),
+            }),
+            ..grid_col_default()
+        }];
+        let header = engine.compute_header_layout(&columns, &make_viewport(), &container);
+        assert!((header[0].x - 100.0).abs() < 0.1);
+        assert!((header[0].width - 500.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn grid_template_areas_place_columns() {
+        let areas = parse_grid_template_areas(&["side head head", "side . main"]);
+        assert_eq!(
+            areas
+                .iter()
+                .map(|a| (
+                    a.name.as_str(),
+                    a.row_start,
+                    a.row_end,
+                    a.column_start,
+                    a.column_end
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("side", 1, 3, 1, 2),
+                ("head", 1, 2, 2, 4),
+                ("main", 2, 3, 3, 4)
+            ]
+        );
+
+        let mut engine = LayoutEngine::new();
+        let container = ContainerLayout {
+            display: DisplayValue::Grid,
+            grid_template_columns: vec![
+                TrackListItem::Single(TrackSizeValue::Length(100.0)),
+                TrackListItem::Single(TrackSizeValue::Length(200.0)),
+                TrackListItem::Single(TrackSizeValue::Fr(1.0)),
+            ],
+            grid_template_rows: vec![
+                TrackListItem::Single(TrackSizeValue::Fr(1.0)),
+                TrackListItem::Single(TrackSizeValue::Fr(1.0)),
+            ],
+            grid_template_areas: areas,
+            ..ContainerLayout::default()
+        };
+        let area = |name: &str| {
+            let line = GridLineValue {
+                start: GridPlacementValue::Named(name.into(), 1),
+                end: GridPlacementValue::Named(name.into(), 1),
+            };
+            ColumnLayout {
+                grid_row: Some(line.clone()),
+                grid_column: Some(line),
+                ..grid_col_default()
+            }
+        };
+        let columns = vec![area("main"), area("head"), area("side")];
+        let header = engine.compute_header_layout(&columns, &make_viewport(), &container);
+        let rects: Vec<(f32, f32, f32)> = header.iter().map(|c| (c.x, c.y, c.width)).collect();
+        assert_eq!(
+            rects,
+            vec![(300.0, 20.0, 300.0), (100.0, 0.0, 500.0), (0.0, 0.0, 100.0)]
+        );
+    }
+
     // ── Coverage: container margin/border/padding with percent ──────────
 
     #[test]
@@ -3980,30 +5855,81 @@ mod tests {
     }
 
     #[test]
-    fn two_slot_cache() {
+    fn keyed_lru_cache() {
         init_logger();
         let mut engine = LayoutEngine::new();
         let columns = vec![col(200.0, Align::Left), col(100.0, Align::Right)];
         let container = default_container();
+        let cached = |engine: &LayoutEngine, height| {
+            engine.cache_contains(&columns, &container, 600.0, height, 20.0)
+        };
+
+        // Header, row, and group row heights alternating all stay cached
+        for height in [40.0, 36.0, 28.0, 40.0, 36.0, 28.0] {
+            engine.compute_column_positions(&columns, &container, 600.0, height, 20.0);
+        }
+        assert!(cached(&engine, 40.0) && cached(&engine, 36.0) && cached(&engine, 28.0));
 
-        // Compute with header_height=40 and row_height=36 (two different row heights)
-        engine.compute_column_positions(&columns, &container, 600.0, 40.0, 20.0);
+        // Shrinking the capacity evicts the least recently used entries; a hit
+        // makes an entry the most recently used
+        engine.set_cache_capacity(2);
+        assert!(!cached(&engine, 40.0) && cached(&engine, 36.0) && cached(&engine, 28.0));
         engine.compute_column_positions(&columns, &container, 600.0, 36.0, 20.0);
+        engine.compute_column_positions(&columns, &container, 600.0, 50.0, 20.0);
+        assert!(cached(&engine, 36.0) && cached(&engine, 50.0) && !cached(&engine, 28.0));
 
-        // Both should still be in cache (2 slots)
-        assert!(engine.cache_contains(&columns, &container, 600.0, 40.0, 20.0));
-        assert!(engine.cache_contains(&columns, &container, 600.0, 36.0, 20.0));
+        engine.set_cache_capacity(0);
+        engine.compute_column_positions(&columns, &container, 600.0, 36.0, 20.0);
+        assert!(!cached(&engine, 36.0));
+    }
 
-        // A third unique call evicts the LRU slot
-        engine.compute_column_positions(&columns, &container, 600.0, 50.0, 20.0);
-        assert!(engine.cache_contains(&columns, &container, 600.0, 50.0, 20.0));
-        // One of the previous two should be evicted
-        let both_present = engine.cache_contains(&columns, &container, 600.0, 40.0, 20.0)
-            && engine.cache_contains(&columns, &container, 600.0, 36.0, 20.0);
-        assert!(
-            !both_present,
-            "one of the two original entries should have been evicted"
+    #[test]
+    fn column_tree_persists_across_layouts() {
+        let mut engine = LayoutEngine::new();
+        let container = default_container();
+        let mut columns = vec![col(200.0, Align::Left), col_flex(0.0, 1.0, 1.0)];
+
+        engine.compute_column_positions(&columns, &container, 600.0, 36.0, 20.0);
+        assert_eq!(engine.tree.total_node_count(), 3);
+
+        // Resizing a column restyles the same nodes
+        let nodes = (
+            engine.column_trees[0].root,
+            engine.column_trees[0].children.clone(),
+        );
+        columns[0].width = 250.0;
+        engine.invalidate_cache();
+        let (positions, _) =
+            engine.compute_column_positions(&columns, &container, 600.0, 36.0, 20.0);
+        assert_eq!(engine.tree.total_node_count(), 3);
+        assert_eq!(
+            (
+                engine.column_trees[0].root,
+                engine.column_trees[0].children.clone()
+            ),
+            nodes
+        );
+        let widths: Vec<f32> = positions.iter().map(|p| p.width).collect();
+        assert_eq!(widths, vec![250.0, 350.0]);
+
+        // Another column count gets its own tree; composite layout leaves none
+        engine.compute_column_positions(&columns[..1], &container, 600.0, 36.0, 20.0);
+        let params = composite_params(
+            100.0,
+            36.0,
+            FlexDirectionValue::Row,
+            0.0,
+            None,
+            None,
+            [0.0; 4],
         );
+        engine.compute_composite_layout(&params, &[child(10.0, 10.0)]);
+        assert_eq!(engine.tree.total_node_count(), 5);
+
+        engine.set_cache_capacity(1);
+        assert_eq!(engine.tree.total_node_count(), 2);
+        engine.invalidate_cache();
+        assert_eq!(engine.tree.total_node_count(), 2);
     }
 
     // ── Composite layout tests ──────────────────────────────────────────
@@ -1,7 +1,14 @@
+use std::collections::HashMap;
+
 use crate::layout::Align;
+use crate::virtual_scroll::RowHeightIndex;
 
 /// Number of f32 fields per cell in the layout buffer.
-pub const LAYOUT_STRIDE: usize = 16;
+pub const LAYOUT_STRIDE: usize = 22;
+
+/// Version of the buffer format (stride and fields); bumped on every change so
+/// readers can check they match the engine.
+pub const LAYOUT_BUFFER_VERSION: u32 = 7;
 
 // Field offsets within each cell's stride
 pub const FIELD_ROW: usize = 0;
@@ -19,7 +26,53 @@ pub const FIELD_BORDER_TOP: usize = 11;
 pub const FIELD_BORDER_RIGHT: usize = 12;
 pub const FIELD_BORDER_BOTTOM: usize = 13;
 pub const FIELD_BORDER_LEFT: usize = 14;
-pub const FIELD_RESERVED: usize = 15;
+pub const FIELD_REGION: usize = 15; // bit set of REGION_* flags
+pub const FIELD_BASELINE: usize = 16; // y of the first text line's middle (`layout::write_baselines`)
+pub const FIELD_FLAGS: usize = 17; // bit set of FLAG_* style flags (`write_style_flags`)
+pub const FIELD_ROTATION: usize = 18; // header label rotation, degrees counterclockwise (0 elsewhere)
+pub const FIELD_STYLE: usize = 19; // conditional format style ID (`write_style_ids`; 0 = none)
+pub const FIELD_UPDATE_AGE: usize = 20; // frames since the row's last update (`write_update_ages`; -1 = none)
+pub const FIELD_ITEM: usize = 21; // index of a cell item among its cell's items (-1 for other cells)
+
+/// Cell scrolls with the body; renderers clip it below any sticky header and
+/// between the pinned column regions.
+pub const REGION_BODY: f32 = 0.0;
+/// Header cell fixed at the top of the viewport; drawn over the body.
+pub const REGION_STICKY_HEADER: f32 = 1.0;
+/// Cell in a left-pinned column; fixed horizontally, drawn over scrolled columns.
+pub const REGION_PINNED_LEFT: f32 = 2.0;
+/// Cell in a right-pinned column; fixed horizontally, drawn over scrolled columns.
+pub const REGION_PINNED_RIGHT: f32 = 4.0;
+/// Detail area of an expanded row, spanning the full width below the row's cells.
+pub const REGION_DETAIL: f32 = 8.0;
+/// Group header row above the first row of a group; with `REGION_STICKY_HEADER`,
+/// the current group's header pinned to the top of the data region.
+pub const REGION_GROUP_HEADER: f32 = 16.0;
+/// Cell covered by a merged cell above it (row span); zero height, not drawn.
+pub const REGION_MERGED: f32 = 32.0;
+/// Cell of a synthetic group row (`group_by`); its row index is the group's
+/// first data row, so renderers draw the group key and count instead.
+pub const REGION_GROUP_ROW: f32 = 64.0;
+/// Footer row cell (`Viewport::footer_height`), e.g. for aggregate totals; with
+/// `REGION_STICKY_HEADER`, fixed at the bottom of the viewport.
+pub const REGION_FOOTER: f32 = 128.0;
+/// Item inside a data cell (`ColumnLayout::content`); it has the row and column
+/// of its parent cell and its index among the cell's items in `FIELD_ITEM`.
+pub const REGION_CELL_ITEM: f32 = 256.0;
+
+/// Cell of an odd view row (1, 3, ...), for row striping; areas of a row and
+/// the items of its cells share the row's parity.
+pub const FLAG_ODD_ROW: u32 = 1;
+/// Cell of a group header (`REGION_GROUP_HEADER`) or group row (`REGION_GROUP_ROW`).
+pub const FLAG_GROUP: u32 = 2;
+/// Cell of a left- or right-pinned column.
+pub const FLAG_PINNED: u32 = 4;
+/// Data cell inside a selected range.
+pub const FLAG_SELECTED: u32 = 8;
+/// Data cell whose text matches the global filter.
+pub const FLAG_FILTER_MATCH: u32 = 16;
+/// Data cell failing its column's validation rules (`add_invalid_flags`).
+pub const FLAG_INVALID: u32 = 32;
 
 /// Write a single cell's layout data into the flat buffer at `cell_idx`.
 #[allow(clippy::too_many_arguments)]
@@ -44,11 +97,7 @@ pub fn write_cell(
     buf[base + FIELD_Y] = y;
     buf[base + FIELD_WIDTH] = w;
     buf[base + FIELD_HEIGHT] = h;
-    buf[base + FIELD_ALIGN] = match align {
-        Align::Left => 0.0,
-        Align::Center => 1.0,
-        Align::Right => 2.0,
-    };
+    buf[base + FIELD_ALIGN] = encode_align(align);
     buf[base + FIELD_PADDING_TOP] = padding[0];
     buf[base + FIELD_PADDING_RIGHT] = padding[1];
     buf[base + FIELD_PADDING_BOTTOM] = padding[2];
@@ -57,7 +106,488 @@ pub fn write_cell(
     buf[base + FIELD_BORDER_RIGHT] = border[1];
     buf[base + FIELD_BORDER_BOTTOM] = border[2];
     buf[base + FIELD_BORDER_LEFT] = border[3];
-    buf[base + FIELD_RESERVED] = 0.0;
+    buf[base + FIELD_REGION] = REGION_BODY;
+    buf[base + FIELD_ROTATION] = 0.0;
+    buf[base + FIELD_STYLE] = 0.0;
+    buf[base + FIELD_UPDATE_AGE] = -1.0;
+    buf[base + FIELD_ITEM] = -1.0;
+}
+
+/// `FIELD_ALIGN` value of `align`.
+const fn encode_align(align: Align) -> f32 {
+    match align {
+        Align::Left => 0.0,
+        Align::Center => 1.0,
+        Align::Right => 2.0,
+    }
+}
+
+/// Layout override of a single cell on top of its column's style (`None` = the
+/// column's value).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CellStyle {
+    /// `[top, right, bottom, left]` px.
+    pub padding: Option<[f32; 4]>,
+    pub align: Option<Align>,
+    /// Cell height, px, from the top of the row.
+    pub height: Option<f32>,
+}
+
+/// Apply `style(view_row, col)` overrides to the data cells in `cells`.
+pub fn apply_cell_styles<'a>(
+    buf: &mut [f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+    style: impl Fn(usize, usize) -> Option<&'a CellStyle>,
+) {
+    for cell in cells {
+        let base = cell * LAYOUT_STRIDE;
+        let Some(row) = (buf[base + FIELD_ROW] as usize).checked_sub(header_row_count) else {
+            continue;
+        };
+        let Some(style) = style(row, buf[base + FIELD_COL] as usize) else {
+            continue;
+        };
+        if let Some(padding) = style.padding {
+            buf[base + FIELD_PADDING_TOP..=base + FIELD_PADDING_LEFT].copy_from_slice(&padding);
+        }
+        if let Some(align) = style.align {
+            buf[base + FIELD_ALIGN] = encode_align(align);
+        }
+        if let Some(height) = style.height {
+            buf[base + FIELD_HEIGHT] = height;
+        }
+    }
+}
+
+/// Add `indent(view_row)` px to the left padding of the data cells of layout
+/// column `col` in `cells` (tree depth indentation).
+pub fn indent_cells(
+    buf: &mut [f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+    col: usize,
+    indent: impl Fn(usize) -> f32,
+) {
+    for cell in cells {
+        let base = cell * LAYOUT_STRIDE;
+        if buf[base + FIELD_COL] as usize != col {
+            continue;
+        }
+        if let Some(row) = (buf[base + FIELD_ROW] as usize).checked_sub(header_row_count) {
+            buf[base + FIELD_PADDING_LEFT] += indent(row);
+        }
+    }
+}
+
+/// Replace the column of cells `cells` by `col_ids[col]`: maps the positions of
+/// laid-out columns back to table column indices when some are hidden.
+pub fn remap_columns(buf: &mut [f32], cells: std::ops::Range<usize>, col_ids: &[usize]) {
+    for cell in cells {
+        let field = &mut buf[cell * LAYOUT_STRIDE + FIELD_COL];
+        if let Some(&col) = col_ids.get(*field as usize) {
+            *field = col as f32;
+        }
+    }
+}
+
+/// Add `region` to the data cells in `cells` whose view row is `marked`.
+pub fn add_row_region(
+    buf: &mut [f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+    region: f32,
+    marked: impl Fn(usize) -> bool,
+) {
+    for cell in cells {
+        let row = buf[cell * LAYOUT_STRIDE + FIELD_ROW] as usize;
+        if row.checked_sub(header_row_count).is_some_and(&marked) {
+            add_region(buf, cell..cell + 1, region);
+        }
+    }
+}
+
+/// Add the `REGION_*` flag `region` to cells `cells`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn add_region(buf: &mut [f32], cells: std::ops::Range<usize>, region: f32) {
+    for cell in cells {
+        let field = &mut buf[cell * LAYOUT_STRIDE + FIELD_REGION];
+        *field = ((*field as u32) | (region as u32)) as f32;
+    }
+}
+
+/// Set the `FLAG_*` style flags of cells `cells` from their row, column, and region.
+///
+/// `selected(view_row, col)` and `matches(view_row, col)` test data cells;
+/// header and footer cells only get `FLAG_PINNED`; cell items share their
+/// parent cell's row and column, so they get its flags.
+pub fn write_style_flags(
+    buf: &mut [f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+    selected: impl Fn(usize, usize) -> bool,
+    matches: impl Fn(usize, usize) -> bool,
+) {
+    const PINNED: u32 = (REGION_PINNED_LEFT + REGION_PINNED_RIGHT) as u32;
+    const GROUP: u32 = (REGION_GROUP_HEADER + REGION_GROUP_ROW) as u32;
+    const AREA: u32 = (REGION_DETAIL + REGION_GROUP_HEADER) as u32;
+    for cell in cells {
+        let base = cell * LAYOUT_STRIDE;
+        let region = buf[base + FIELD_REGION] as u32;
+        let row = buf[base + FIELD_ROW] as usize;
+        let col = buf[base + FIELD_COL] as usize;
+        let mut flags = if region & PINNED == 0 { 0 } else { FLAG_PINNED };
+        if let Some(pos) = row
+            .checked_sub(header_row_count)
+            .filter(|_| region & REGION_FOOTER as u32 == 0)
+        {
+            if pos % 2 == 1 {
+                flags |= FLAG_ODD_ROW;
+            }
+            if region & GROUP != 0 {
+                flags |= FLAG_GROUP;
+            }
+            if region & AREA == 0 {
+                if selected(pos, col) {
+                    flags |= FLAG_SELECTED;
+                }
+                if matches(pos, col) {
+                    flags |= FLAG_FILTER_MATCH;
+                }
+            }
+        }
+        buf[base + FIELD_FLAGS] = flags as f32;
+    }
+}
+
+/// View row and column of `cell` if it is a data cell: not a header, footer,
+/// group, area, merged, or item cell.
+fn data_cell(buf: &[f32], cell: usize, header_row_count: usize) -> Option<(usize, usize)> {
+    const NOT_DATA: u32 = (REGION_DETAIL
+        + REGION_GROUP_HEADER
+        + REGION_MERGED
+        + REGION_GROUP_ROW
+        + REGION_FOOTER
+        + REGION_CELL_ITEM) as u32;
+    let base = cell * LAYOUT_STRIDE;
+    let pos = (buf[base + FIELD_ROW] as usize).checked_sub(header_row_count)?;
+    (buf[base + FIELD_REGION] as u32 & NOT_DATA == 0).then(|| (pos, buf[base + FIELD_COL] as usize))
+}
+
+/// The data cells among `cells`, as `(cell, view_row, col)`.
+pub fn data_cells(
+    buf: &[f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+    cells.filter_map(move |cell| {
+        data_cell(buf, cell, header_row_count).map(|(pos, col)| (cell, pos, col))
+    })
+}
+
+/// Set the conditional format style ID of the data cells in `cells` to
+/// `style(view_row, col)`; other cells get 0.
+pub fn write_style_ids(
+    buf: &mut [f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+    style: impl Fn(usize, usize) -> u32,
+) {
+    for cell in cells {
+        let id = data_cell(buf, cell, header_row_count).map_or(0, |(pos, col)| style(pos, col));
+        buf[cell * LAYOUT_STRIDE + FIELD_STYLE] = id as f32;
+    }
+}
+
+/// Add `FLAG_INVALID` to the data cells in `cells` for which
+/// `invalid(view_row, col)` holds (after `write_style_flags`).
+pub fn add_invalid_flags(
+    buf: &mut [f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+    invalid: impl Fn(usize, usize) -> bool,
+) {
+    for cell in cells {
+        if data_cell(buf, cell, header_row_count).is_some_and(|(pos, col)| invalid(pos, col)) {
+            let flags = &mut buf[cell * LAYOUT_STRIDE + FIELD_FLAGS];
+            *flags = (*flags as u32 | FLAG_INVALID) as f32;
+        }
+    }
+}
+
+/// Set the update age of the data cells in `cells` to `age(view_row)`, frames
+/// since their row was updated; other cells and rows without a recent update
+/// get -1.
+pub fn write_update_ages(
+    buf: &mut [f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+    age: impl Fn(usize) -> Option<u32>,
+) {
+    for cell in cells {
+        let age = data_cell(buf, cell, header_row_count).and_then(|(pos, _)| age(pos));
+        buf[cell * LAYOUT_STRIDE + FIELD_UPDATE_AGE] = age.map_or(-1.0, |a| a as f32);
+    }
+}
+
+/// Concatenate the display text of cells `cells` into `text`, written by
+/// `write(view_row, col, out)` for data cells (other cells get no text).
+///
+/// The text of cell `cells.start + i` is `offsets[i]..offsets[i + 1]`, in UTF-16
+/// code units as JS strings are indexed.
+pub fn write_cell_texts(
+    buf: &[f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+    mut write: impl FnMut(usize, usize, &mut String),
+    text: &mut String,
+    offsets: &mut Vec<u32>,
+) {
+    text.clear();
+    offsets.clear();
+    let mut end = 0_u32;
+    offsets.push(end);
+    for cell in cells {
+        if let Some((pos, col)) = data_cell(buf, cell, header_row_count) {
+            let start = text.len();
+            write(pos, col, text);
+            end += text[start..].encode_utf16().count() as u32;
+        }
+        offsets.push(end);
+    }
+}
+
+/// Per-cell data visualization of cells `cells`, from `visual(view_row, col)`.
+///
+/// Data cells get `(value, color)`: a 0..1 fraction (NaN = none) and a packed
+/// `0xRRGGBBAA` (0 = none). Other cells get NaN and 0.
+pub fn write_data_visuals(
+    buf: &[f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+    visual: impl Fn(usize, usize) -> (f32, u32),
+    values: &mut Vec<f32>,
+    colors: &mut Vec<u32>,
+) {
+    values.clear();
+    colors.clear();
+    for cell in cells {
+        let (value, color) = data_cell(buf, cell, header_row_count)
+            .map_or((f32::NAN, 0), |(pos, col)| visual(pos, col));
+        values.push(value);
+        colors.push(color);
+    }
+}
+
+/// Per-cell text truncation of cells `cells`, as `[truncated, cut]` pairs.
+///
+/// Data cells whose text `text(view_row, col)` and `cut(text, content_width)`
+/// (see `text_wrap::truncate_at`) gives a cut get `[1, cut]`, others `[0,
+/// length]` with their text's full UTF-16 length (0 for non-data cells). The
+/// content width is the cell width less padding and border, at least 0.
+pub fn write_truncations(
+    buf: &[f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+    mut text: impl FnMut(usize, usize, &mut String),
+    mut cut: impl FnMut(&str, f32) -> Option<usize>,
+    out: &mut Vec<u32>,
+) {
+    out.clear();
+    let mut cell_text = String::new();
+    for cell in cells {
+        let Some((pos, col)) = data_cell(buf, cell, header_row_count) else {
+            out.extend([0, 0]);
+            continue;
+        };
+        cell_text.clear();
+        text(pos, col, &mut cell_text);
+        let base = cell * LAYOUT_STRIDE;
+        let width = buf[base + FIELD_WIDTH]
+            - buf[base + FIELD_PADDING_LEFT]
+            - buf[base + FIELD_PADDING_RIGHT]
+            - buf[base + FIELD_BORDER_LEFT]
+            - buf[base + FIELD_BORDER_RIGHT];
+        match cut(&cell_text, width.max(0.0)) {
+            Some(at) => out.extend([1, at as u32]),
+            None => out.extend([0, cell_text.encode_utf16().count() as u32]),
+        }
+    }
+}
+
+/// Multiply the geometry of cells `cells` (position, size, padding, border,
+/// and baseline) by `factor`, e.g. a zoom level.
+pub fn scale_cells(buf: &mut [f32], cells: std::ops::Range<usize>, factor: f32) {
+    const FIELDS: [usize; 13] = [
+        FIELD_X,
+        FIELD_Y,
+        FIELD_WIDTH,
+        FIELD_HEIGHT,
+        FIELD_PADDING_TOP,
+        FIELD_PADDING_RIGHT,
+        FIELD_PADDING_BOTTOM,
+        FIELD_PADDING_LEFT,
+        FIELD_BORDER_TOP,
+        FIELD_BORDER_RIGHT,
+        FIELD_BORDER_BOTTOM,
+        FIELD_BORDER_LEFT,
+        FIELD_BASELINE,
+    ];
+    for cell in cells {
+        let base = cell * LAYOUT_STRIDE;
+        for field in FIELDS {
+            buf[base + field] *= factor;
+        }
+    }
+}
+
+/// Snap the edges of cells `cells` to device pixels (`pixel_ratio` per px).
+///
+/// Each edge is rounded on its own and the size follows from the rounded
+/// edges, so cells that share an edge still share it after snapping.
+pub fn snap_cells(buf: &mut [f32], cells: std::ops::Range<usize>, pixel_ratio: f32) {
+    let snap = |v: f32| (v * pixel_ratio).round() / pixel_ratio;
+    for cell in cells {
+        let base = cell * LAYOUT_STRIDE;
+        for (pos, size) in [(FIELD_X, FIELD_WIDTH), (FIELD_Y, FIELD_HEIGHT)] {
+            let start = snap(buf[base + pos]);
+            let end = snap(buf[base + pos] + buf[base + size]);
+            buf[base + pos] = start;
+            buf[base + size] = end - start;
+        }
+    }
+}
+
+/// Cells of a layout buffer whose geometry differs from a previous frame
+/// (`changed_cells`).
+#[derive(Debug, Default, PartialEq)]
+pub struct CellChanges {
+    /// Cells whose x, y, width, or height differ from their previous cell.
+    pub moved: Vec<usize>,
+    /// Cells with no previous cell.
+    pub added: Vec<usize>,
+    /// `(dx, dy)` when every moved cell moved by that offset without resizing,
+    /// so a renderer can apply one transform instead of moving each cell.
+    pub translate: Option<(f32, f32)>,
+}
+
+/// Compare cells `..cells` of `buf` with cells `..prev_cells` of `prev`.
+///
+/// A cell's previous cell is the one with the same row, column, and region;
+/// cells sharing all three (cell items) pair up in buffer order.
+pub fn changed_cells(prev: &[f32], prev_cells: usize, buf: &[f32], cells: usize) -> CellChanges {
+    const GEOMETRY: [usize; 4] = [FIELD_X, FIELD_Y, FIELD_WIDTH, FIELD_HEIGHT];
+    let previous: HashMap<_, _> = cell_keys(prev, prev_cells)
+        .into_iter()
+        .enumerate()
+        .map(|(cell, key)| (key, cell))
+        .collect();
+
+    let mut changes = CellChanges::default();
+    let mut offset: Option<Option<(f32, f32)>> = None;
+    for (cell, key) in cell_keys(buf, cells).into_iter().enumerate() {
+        let Some(&prev_cell) = previous.get(&key) else {
+            changes.added.push(cell);
+            continue;
+        };
+        let [x, y, w, h] = GEOMETRY.map(|f| buf[cell * LAYOUT_STRIDE + f]);
+        let [px, py, pw, ph] = GEOMETRY.map(|f| prev[prev_cell * LAYOUT_STRIDE + f]);
+        if [x, y, w, h].map(f32::to_bits) == [px, py, pw, ph].map(f32::to_bits) {
+            continue;
+        }
+        changes.moved.push(cell);
+        let resized = (w - pw).abs() > 1e-3 || (h - ph).abs() > 1e-3;
+        let delta = (x - px, y - py);
+        offset = Some(match offset {
+            _ if resized => None,
+            None => Some(delta),
+            Some(Some((dx, dy))) if (delta.0 - dx).abs() < 1e-3 && (delta.1 - dy).abs() < 1e-3 => {
+                Some((dx, dy))
+            }
+            Some(_) => None,
+        });
+    }
+    changes.translate = offset.flatten();
+    changes
+}
+
+/// Identity of cells `..cells` for `changed_cells`: row, column, and region
+/// bits, plus the occurrence of that triple so far.
+fn cell_keys(buf: &[f32], cells: usize) -> Vec<([u32; 3], u32)> {
+    let mut seen: HashMap<[u32; 3], u32> = HashMap::new();
+    (0..cells)
+        .map(|cell| {
+            let base = cell * LAYOUT_STRIDE;
+            let id = [FIELD_ROW, FIELD_COL, FIELD_REGION].map(|f| buf[base + f].to_bits());
+            let nth = seen.entry(id).or_default();
+            *nth += 1;
+            (id, *nth)
+        })
+        .collect()
+}
+
+/// Grid line positions of a buffer of `cell_count` cells, into `out`.
+///
+/// Column rules come first: the distinct left and right edges of the first
+/// `header_cells` cells (the header). Row rules follow: the distinct top and
+/// bottom edges of all cells except merged cells and cell items. Both are
+/// sorted, and edges closer than 0.01 px count as one. Returns the column rule
+/// count.
+pub fn write_rules(
+    buf: &[f32],
+    cell_count: usize,
+    header_cells: usize,
+    out: &mut Vec<f32>,
+) -> usize {
+    const NO_RULES: u32 = (REGION_MERGED + REGION_CELL_ITEM) as u32;
+    fn sort_distinct(edges: &mut Vec<f32>) {
+        edges.sort_by(f32::total_cmp);
+        edges.dedup_by(|a, b| (*a - *b).abs() < 0.01);
+    }
+    let edges = |pos: usize, size: usize, cell: usize| {
+        let base = cell * LAYOUT_STRIDE;
+        [buf[base + pos], buf[base + pos] + buf[base + size]]
+    };
+    out.clear();
+    out.extend(
+        (0..header_cells.min(cell_count)).flat_map(|cell| edges(FIELD_X, FIELD_WIDTH, cell)),
+    );
+    sort_distinct(out);
+    let column_rules = out.len();
+    let mut rows: Vec<f32> = (0..cell_count)
+        .filter(|&cell| buf[cell * LAYOUT_STRIDE + FIELD_REGION] as u32 & NO_RULES == 0)
+        .flat_map(|cell| edges(FIELD_Y, FIELD_HEIGHT, cell))
+        .collect();
+    sort_distinct(&mut rows);
+    out.extend(rows);
+    column_rules
+}
+
+// Header buffer (`write_header_meta`): HEADER_STRIDE f32 fields per header
+// cell, in the order of the layout buffer's header cells.
+pub const HEADER_STRIDE: usize = 4;
+pub const HEADER_FIELD_SORT: usize = 0; // 0 = unsorted, 1 = ascending, 2 = descending
+pub const HEADER_FIELD_SORT_PRIORITY: usize = 1; // 1-based place among the sort keys (0 = unsorted)
+pub const HEADER_FIELD_FILTERED: usize = 2; // 1 = a column filter is active
+pub const HEADER_FIELD_RESIZE_X: usize = 3; // x of the resize handle (the cell's right edge)
+
+/// Header chrome of the first `header_cells` of `cell_count` cells, into `out`.
+///
+/// `meta(col)` gives each cell's sort, sort priority, and filtered fields; its
+/// right edge is the resize handle.
+pub fn write_header_meta(
+    buf: &[f32],
+    cell_count: usize,
+    header_cells: usize,
+    meta: impl Fn(usize) -> [f32; 3],
+    out: &mut Vec<f32>,
+) {
+    out.clear();
+    for cell in 0..header_cells.min(cell_count) {
+        let base = cell * LAYOUT_STRIDE;
+        out.extend(meta(buf[base + FIELD_COL] as usize));
+        out.push(buf[base + FIELD_X] + buf[base + FIELD_WIDTH]);
+    }
 }
 
 /// Required buffer length (in f32 elements) for `cell_count` cells.
@@ -66,6 +596,340 @@ pub const fn buf_len(cell_count: usize) -> usize {
     cell_count * LAYOUT_STRIDE
 }
 
+// Shared layout output: a header of i32 words followed by two slots of whole
+// cells. The engine fills the slot not in `SHARED_ACTIVE_SLOT`, stores its cell
+// count, then publishes it by storing the slot and bumping the generation.
+pub const SHARED_GENERATION: usize = 0;
+pub const SHARED_ACTIVE_SLOT: usize = 1;
+/// Cell count of slot 0; slot 1's follows.
+pub const SHARED_CELL_COUNT: usize = 2;
+pub const SHARED_HEADER_WORDS: usize = 4;
+
+/// Cells each slot of a shared output buffer of `byte_len` bytes holds.
+pub const fn shared_slot_cells(byte_len: usize) -> usize {
+    (byte_len / 4).saturating_sub(SHARED_HEADER_WORDS) / 2 / LAYOUT_STRIDE
+}
+
+/// Offset (in f32 elements) of `slot` in a shared output buffer of `byte_len`
+/// bytes.
+pub const fn shared_slot_offset(byte_len: usize, slot: usize) -> usize {
+    SHARED_HEADER_WORDS + slot * buf_len(shared_slot_cells(byte_len))
+}
+
+/// Byte length of a shared output buffer with room for `cell_count` cells per
+/// slot.
+pub const fn shared_buffer_len(cell_count: usize) -> usize {
+    (SHARED_HEADER_WORDS + 2 * buf_len(cell_count)) * 4
+}
+
+/// Extra areas of variable-height rows, by view row (empty slices = none). Row
+/// heights in the `RowHeightIndex` include them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RowAreas<'a> {
+    /// Detail area below the row's cells (expanded rows).
+    pub detail: &'a [f32],
+    /// Group header above the row's cells (first row of each group).
+    pub group_header: &'a [f32],
+}
+
+impl RowAreas<'_> {
+    fn get(areas: &[f32], row: usize) -> f32 {
+        areas.get(row).copied().unwrap_or(0.0)
+    }
+
+    /// Heights of the group header above and the detail area below view row
+    /// `row`'s cells.
+    pub fn margins(&self, row: usize) -> (f32, f32) {
+        (
+            Self::get(self.group_header, row),
+            Self::get(self.detail, row),
+        )
+    }
+
+    /// Number of area cells `append_area_cells` emits for view rows `rows`.
+    pub fn cell_count(&self, rows: impl IntoIterator<Item = usize>) -> usize {
+        rows.into_iter()
+            .map(|row| {
+                usize::from(Self::get(self.detail, row) > 0.0)
+                    + usize::from(Self::get(self.group_header, row) > 0.0)
+            })
+            .sum()
+    }
+}
+
+/// Move data cells laid out at a uniform `row_height` to their variable-height rows.
+///
+/// Each cell shifts by (its row's offset − uniform offset) plus any group header
+/// above it, and grows by (its row's height − `row_height`) minus its `areas`.
+/// Cells whose row is outside `heights` are left as is.
+pub fn apply_row_heights(
+    buf: &mut [f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+    row_height: f32,
+    heights: &RowHeightIndex,
+    areas: &RowAreas<'_>,
+) {
+    for cell in cells {
+        let base = cell * LAYOUT_STRIDE;
+        let Some(row) = (buf[base + FIELD_ROW] as usize).checked_sub(header_row_count) else {
+            continue;
+        };
+        if row >= heights.len() {
+            continue;
+        }
+        let group_header = RowAreas::get(areas.group_header, row);
+        // In f64: both offsets exceed f32 precision deep into large tables
+        let shift = (row as f64).mul_add(-f64::from(row_height), heights.offset(row));
+        buf[base + FIELD_Y] += shift as f32 + group_header;
+        buf[base + FIELD_HEIGHT] += heights.height(row) as f32
+            - row_height
+            - RowAreas::get(areas.detail, row)
+            - group_header;
+    }
+}
+
+/// Append full-width area cells for the rows in `cells`, starting at cell `at`:
+/// a `REGION_GROUP_HEADER` cell above each group's first row and a
+/// `REGION_DETAIL` cell below each expanded row.
+///
+/// Rows are recognized by their column-0 cell, already moved by
+/// `apply_row_heights`. Returns the number of cells appended.
+pub fn append_area_cells(
+    buf: &mut [f32],
+    cells: std::ops::Range<usize>,
+    at: usize,
+    header_row_count: usize,
+    areas: &RowAreas<'_>,
+    width: f32,
+) -> usize {
+    let mut out = at;
+    for cell in cells {
+        let base = cell * LAYOUT_STRIDE;
+        if buf[base + FIELD_COL] != 0.0 {
+            continue;
+        }
+        let view_row = buf[base + FIELD_ROW] as usize;
+        let Some(row) = view_row.checked_sub(header_row_count) else {
+            continue;
+        };
+        let (y, height) = (buf[base + FIELD_Y], buf[base + FIELD_HEIGHT]);
+        let group_header = RowAreas::get(areas.group_header, row);
+        let detail = RowAreas::get(areas.detail, row);
+        for (area_y, area_height, region) in [
+            (y - group_header, group_header, REGION_GROUP_HEADER),
+            (y + height, detail, REGION_DETAIL),
+        ] {
+            if area_height > 0.0 {
+                write_area_cell(buf, out, view_row, area_y, width, area_height, region);
+                out += 1;
+            }
+        }
+    }
+    out - at
+}
+
+/// Merge the cells of layout column `col` into row spans.
+///
+/// A cell whose view row is `covered` (joins the row above) is folded into the
+/// previous cell of the column when the two rows are adjacent in the buffer: that
+/// cell grows down over it, and the covered cell gets zero height and
+/// `REGION_MERGED`. Spans start at the first rendered row of a merge.
+pub fn merge_row_spans(
+    buf: &mut [f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+    col: usize,
+    covered: impl Fn(usize) -> bool,
+) {
+    // Host cell of the current span and the view row last added to it
+    let mut span: Option<(usize, usize)> = None;
+    for cell in cells {
+        let base = cell * LAYOUT_STRIDE;
+        if buf[base + FIELD_COL] as usize != col {
+            continue;
+        }
+        let Some(row) = (buf[base + FIELD_ROW] as usize).checked_sub(header_row_count) else {
+            continue;
+        };
+        let (y, height) = (buf[base + FIELD_Y], buf[base + FIELD_HEIGHT]);
+        match span {
+            Some((host, last)) if last + 1 == row && covered(row) => {
+                let host_base = host * LAYOUT_STRIDE;
+                let host_bottom = buf[host_base + FIELD_Y] + buf[host_base + FIELD_HEIGHT];
+                if (y - host_bottom).abs() < 1.0 {
+                    buf[host_base + FIELD_HEIGHT] = y + height - buf[host_base + FIELD_Y];
+                    buf[base + FIELD_HEIGHT] = 0.0;
+                    add_region(buf, cell..cell + 1, REGION_MERGED);
+                    span = Some((host, row));
+                    continue;
+                }
+                span = Some((cell, row));
+            }
+            _ => span = Some((cell, row)),
+        }
+    }
+}
+
+/// Merge data cells into column spans.
+///
+/// A cell whose `span(view_row, col)` is greater than 1 grows right over the next
+/// `span - 1` cells of its row in the buffer, which get zero width and
+/// `REGION_MERGED` so renderers skip them. Spans stop at the end of the row.
+pub fn merge_col_spans(
+    buf: &mut [f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+    span: impl Fn(usize, usize) -> Option<usize>,
+) {
+    // Host cell of the current span, its view row, and the cells it still covers
+    let mut host: Option<(usize, usize, usize)> = None;
+    for cell in cells {
+        let base = cell * LAYOUT_STRIDE;
+        let Some(row) = (buf[base + FIELD_ROW] as usize).checked_sub(header_row_count) else {
+            continue;
+        };
+        match host {
+            Some((at, host_row, left)) if host_row == row && left > 0 => {
+                let host_base = at * LAYOUT_STRIDE;
+                let right = buf[base + FIELD_X] + buf[base + FIELD_WIDTH];
+                let width = right - buf[host_base + FIELD_X];
+                let host_width = &mut buf[host_base + FIELD_WIDTH];
+                *host_width = host_width.max(width);
+                buf[base + FIELD_WIDTH] = 0.0;
+                add_region(buf, cell..cell + 1, REGION_MERGED);
+                host = Some((at, row, left - 1));
+            }
+            _ => {
+                let col = buf[base + FIELD_COL] as usize;
+                host = span(row, col)
+                    .filter(|&span| span > 1)
+                    .map(|span| (cell, row, span - 1));
+            }
+        }
+    }
+}
+
+/// The data cells a drag from point `a` to point `b` covers.
+///
+/// Returns `[first_row, first_col, last_row, last_col]`: view rows top to
+/// bottom, and the table columns of the leftmost and rightmost cells in layout
+/// order. Each point hits the data cell under it, preferring pinned cells over the
+/// scrolled cells beneath them, or else the nearest one, so drags past the
+/// edges of the body clamp to its outer rows and columns. Merged, area, footer,
+/// and item cells are never hit. `None` without data cells.
+pub fn hit_test_range(
+    buf: &[f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+    a: (f32, f32),
+    b: (f32, f32),
+) -> Option<[usize; 4]> {
+    let a = hit_cell(buf, cells.clone(), header_row_count, a)?;
+    let b = hit_cell(buf, cells, header_row_count, b)?;
+    let (left, right) = if a.1 <= b.1 { (a, b) } else { (b, a) };
+    Some([a.0.min(b.0), left.2, a.0.max(b.0), right.2])
+}
+
+/// `(view_row, position in its row, table column)` of the data cell nearest to
+/// `point` (see `hit_test_range`).
+fn hit_cell(
+    buf: &[f32],
+    cells: std::ops::Range<usize>,
+    header_row_count: usize,
+    (x, y): (f32, f32),
+) -> Option<(usize, usize, usize)> {
+    const SKIP: u32 =
+        (REGION_DETAIL + REGION_GROUP_HEADER + REGION_FOOTER + REGION_CELL_ITEM) as u32;
+    const PINNED: u32 = (REGION_PINNED_LEFT + REGION_PINNED_RIGHT) as u32;
+    // (distance², not pinned) of the best cell so far
+    let mut best_score = (f32::INFINITY, true);
+    let mut best = None;
+    let mut position = (usize::MAX, 0);
+    for cell in cells {
+        let base = cell * LAYOUT_STRIDE;
+        let region = buf[base + FIELD_REGION] as u32;
+        let Some(row) = (buf[base + FIELD_ROW] as usize)
+            .checked_sub(header_row_count)
+            .filter(|_| region & SKIP == 0)
+        else {
+            continue;
+        };
+        position = if position.0 == row {
+            (row, position.1 + 1)
+        } else {
+            (row, 0)
+        };
+        let (left, top) = (buf[base + FIELD_X], buf[base + FIELD_Y]);
+        let (width, height) = (buf[base + FIELD_WIDTH], buf[base + FIELD_HEIGHT]);
+        if region & REGION_MERGED as u32 != 0 || width <= 0.0 || height <= 0.0 {
+            continue;
+        }
+        let dx = (left - x).max(x - (left + width)).max(0.0);
+        let dy = (top - y).max(y - (top + height)).max(0.0);
+        let score = (dx.mul_add(dx, dy * dy), region & PINNED == 0);
+        if best.is_none() || score < best_score {
+            best_score = score;
+            best = Some((row, position.1, buf[base + FIELD_COL] as usize));
+        }
+    }
+    best
+}
+
+/// Move the footer row `footer` below the lowest of the data cells `rows`, for a
+/// footer that follows the last row after rows were resized or areas appended.
+pub fn place_footer_after(
+    buf: &mut [f32],
+    footer: std::ops::Range<usize>,
+    rows: std::ops::Range<usize>,
+) {
+    let Some(bottom) = rows
+        .map(|cell| {
+            let base = cell * LAYOUT_STRIDE;
+            buf[base + FIELD_Y] + buf[base + FIELD_HEIGHT]
+        })
+        .reduce(f32::max)
+    else {
+        return;
+    };
+    let Some(top) = footer
+        .clone()
+        .map(|cell| buf[cell * LAYOUT_STRIDE + FIELD_Y])
+        .reduce(f32::min)
+    else {
+        return;
+    };
+    for cell in footer {
+        buf[cell * LAYOUT_STRIDE + FIELD_Y] += bottom - top;
+    }
+}
+
+/// Write a full-width area cell for `view_row` tagged with `region`.
+pub fn write_area_cell(
+    buf: &mut [f32],
+    cell_idx: usize,
+    view_row: usize,
+    y: f32,
+    width: f32,
+    height: f32,
+    region: f32,
+) {
+    write_cell(
+        buf,
+        cell_idx,
+        view_row,
+        0,
+        0.0,
+        y,
+        width,
+        height,
+        Align::Left,
+        [0.0; 4],
+        [0.0; 4],
+    );
+    add_region(buf, cell_idx..cell_idx + 1, region);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -136,6 +1000,766 @@ mod tests {
         assert!((buf[base1 + FIELD_BORDER_LEFT] - 2.0).abs() < f32::EPSILON);
     }
 
+    #[test]
+    fn apply_row_heights_shifts_cells() {
+        let mut buf = vec![0.0_f32; buf_len(3)];
+        // Header, then rows 0 and 2 laid out at 30px under a 40px header
+        write_cell(
+            &mut buf,
+            0,
+            0,
+            0,
+            0.0,
+            0.0,
+            100.0,
+            40.0,
+            Align::Left,
+            NO_PADDING,
+            NO_BORDER,
+        );
+        write_cell(
+            &mut buf,
+            1,
+            1,
+            0,
+            0.0,
+            40.0,
+            100.0,
+            30.0,
+            Align::Left,
+            NO_PADDING,
+            NO_BORDER,
+        );
+        write_cell(
+            &mut buf,
+            2,
+            3,
+            0,
+            0.0,
+            100.0,
+            100.0,
+            30.0,
+            Align::Left,
+            NO_PADDING,
+            NO_BORDER,
+        );
+        let heights = RowHeightIndex::new(vec![50.0, 10.0, 80.0]);
+        apply_row_heights(&mut buf, 1..3, 1, 30.0, &heights, &RowAreas::default());
+
+        let y_h = |cell: usize| {
+            let base = cell * LAYOUT_STRIDE;
+            (buf[base + FIELD_Y], buf[base + FIELD_HEIGHT])
+        };
+        assert_eq!(
+            (y_h(0), y_h(1), y_h(2)),
+            ((0.0, 40.0), (40.0, 50.0), (100.0, 80.0))
+        );
+    }
+
+    #[test]
+    fn detail_cells_below_expanded_rows() {
+        let mut buf = vec![0.0_f32; buf_len(5)];
+        // Rows 0 and 2 at 30px under a 40px header; heights include the detail areas
+        for (cell, row, y) in [(1, 1, 40.0), (2, 3, 100.0)] {
+            write_cell(
+                &mut buf,
+                cell,
+                row,
+                0,
+                0.0,
+                y,
+                100.0,
+                30.0,
+                Align::Left,
+                NO_PADDING,
+                NO_BORDER,
+            );
+        }
+        let areas = RowAreas {
+            detail: &[20.0, 0.0, 30.0],
+            group_header: &[],
+        };
+        let heights = RowHeightIndex::new(vec![50.0, 10.0, 80.0]);
+        apply_row_heights(&mut buf, 1..3, 1, 30.0, &heights, &areas);
+        assert_eq!(areas.cell_count([0, 2]), 2);
+        assert_eq!(append_area_cells(&mut buf, 1..3, 3, 1, &areas, 300.0), 2);
+
+        let cell = |i: usize| {
+            let base = i * LAYOUT_STRIDE;
+            [
+                buf[base + FIELD_ROW],
+                buf[base + FIELD_Y],
+                buf[base + FIELD_WIDTH],
+                buf[base + FIELD_HEIGHT],
+                buf[base + FIELD_REGION],
+            ]
+        };
+        assert_eq!(
+            [cell(1), cell(2), cell(3), cell(4)],
+            [
+                [1.0, 40.0, 100.0, 30.0, 0.0],
+                [3.0, 100.0, 100.0, 50.0, 0.0],
+                [1.0, 70.0, 300.0, 20.0, REGION_DETAIL],
+                [3.0, 150.0, 300.0, 30.0, REGION_DETAIL],
+            ]
+        );
+    }
+
+    #[test]
+    fn group_header_cells_above_group_rows() {
+        let mut buf = vec![0.0_f32; buf_len(5)];
+        // Rows 0 and 1 at 30px under a 40px header, each starting a group
+        for (cell, row, y) in [(1, 1, 40.0), (2, 2, 70.0)] {
+            write_cell(
+                &mut buf,
+                cell,
+                row,
+                0,
+                0.0,
+                y,
+                100.0,
+                30.0,
+                Align::Left,
+                NO_PADDING,
+                NO_BORDER,
+            );
+        }
+        let areas = RowAreas {
+            detail: &[],
+            group_header: &[12.0, 12.0],
+        };
+        let heights = RowHeightIndex::new(vec![42.0, 42.0]);
+        apply_row_heights(&mut buf, 1..3, 1, 30.0, &heights, &areas);
+        assert_eq!(append_area_cells(&mut buf, 1..3, 3, 1, &areas, 300.0), 2);
+
+        let cell = |i: usize| {
+            let base = i * LAYOUT_STRIDE;
+            [
+                buf[base + FIELD_ROW],
+                buf[base + FIELD_Y],
+                buf[base + FIELD_HEIGHT],
+                buf[base + FIELD_REGION],
+            ]
+        };
+        assert_eq!(
+            [cell(1), cell(2), cell(3), cell(4)],
+            [
+                [1.0, 52.0, 30.0, 0.0],
+                [2.0, 94.0, 30.0, 0.0],
+                [1.0, 40.0, 12.0, REGION_GROUP_HEADER],
+                [2.0, 82.0, 12.0, REGION_GROUP_HEADER],
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_col_spans_skip_covered_cells() {
+        let mut buf = vec![0.0_f32; buf_len(9)];
+        // Header, then rows 0..2 in four 100px columns
+        let mut cell = 1;
+        for row in 0..2 {
+            for col in 0..4 {
+                write_cell(
+                    &mut buf,
+                    cell,
+                    row + 1,
+                    col,
+                    col as f32 * 100.0,
+                    (row as f32).mul_add(30.0, 40.0),
+                    100.0,
+                    30.0,
+                    Align::Left,
+                    NO_PADDING,
+                    NO_BORDER,
+                );
+                cell += 1;
+            }
+        }
+        // Row 0 spans columns 1..3; row 1 asks for more columns than it has left
+        merge_col_spans(&mut buf, 1..9, 1, |row, col| match (row, col) {
+            (0, 1) => Some(2),
+            (1, 2) => Some(5),
+            _ => None,
+        });
+
+        let widths: Vec<[f32; 2]> = (1..9)
+            .map(|i| {
+                let base = i * LAYOUT_STRIDE;
+                [buf[base + FIELD_WIDTH], buf[base + FIELD_REGION]]
+            })
+            .collect();
+        assert_eq!(
+            widths,
+            [
+                [100.0, 0.0],
+                [200.0, 0.0],
+                [0.0, REGION_MERGED],
+                [100.0, 0.0],
+                [100.0, 0.0],
+                [100.0, 0.0],
+                [200.0, 0.0],
+                [0.0, REGION_MERGED],
+            ]
+        );
+    }
+
+    #[test]
+    fn hit_test_range_clamps_to_cells() {
+        let mut buf = vec![0.0_f32; buf_len(7)];
+        // Header, then rows 0..2 in a pinned 50px column 2 and 100px columns 0..1
+        // scrolled 30px left, under the pinned column
+        let mut cell = 1;
+        for row in 0..2 {
+            for (col, x, width) in [(2, 0.0, 50.0), (0, 20.0, 100.0), (1, 120.0, 100.0)] {
+                write_cell(
+                    &mut buf,
+                    cell,
+                    row + 1,
+                    col,
+                    x,
+                    (row as f32).mul_add(30.0, 40.0),
+                    width,
+                    30.0,
+                    Align::Left,
+                    NO_PADDING,
+                    NO_BORDER,
+                );
+                cell += 1;
+            }
+            add_region(&mut buf, cell - 3..cell - 2, REGION_PINNED_LEFT);
+        }
+
+        // Inside the pinned column, over the scrolled column 0
+        assert_eq!(
+            hit_test_range(&buf, 1..7, 1, (30.0, 45.0), (130.0, 75.0)),
+            Some([0, 2, 1, 1])
+        );
+        // Dragged right to left, up into the header and past the right edge
+        assert_eq!(
+            hit_test_range(&buf, 1..7, 1, (500.0, 80.0), (60.0, 10.0)),
+            Some([0, 0, 1, 1])
+        );
+        assert_eq!(hit_test_range(&buf, 0..0, 1, (0.0, 0.0), (0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn merge_row_spans_folds_covered_cells() {
+        let mut buf = vec![0.0_f32; buf_len(9)];
+        // Header, then rows 0..4 at 30px in two columns; row 3 is in another segment
+        let mut cell = 1;
+        for (row, y) in [(0, 40.0), (1, 70.0), (2, 100.0), (3, 200.0)] {
+            for col in 0..2 {
+                write_cell(
+                    &mut buf,
+                    cell,
+                    row + 1,
+                    col,
+                    col as f32 * 100.0,
+                    y,
+                    100.0,
+                    30.0,
+                    Align::Left,
+                    NO_PADDING,
+                    NO_BORDER,
+                );
+                cell += 1;
+            }
+        }
+        merge_row_spans(&mut buf, 1..9, 1, 0, |row| row != 0);
+
+        let cell = |i: usize| {
+            let base = i * LAYOUT_STRIDE;
+            [
+                buf[base + FIELD_Y],
+                buf[base + FIELD_HEIGHT],
+                buf[base + FIELD_REGION],
+            ]
+        };
+        assert_eq!(
+            [cell(1), cell(3), cell(5), cell(7), cell(4)],
+            [
+                [40.0, 90.0, 0.0],
+                [70.0, 0.0, REGION_MERGED],
+                [100.0, 0.0, REGION_MERGED],
+                [200.0, 30.0, 0.0],
+                [70.0, 30.0, 0.0], // column 1 is left alone
+            ]
+        );
+    }
+
+    #[test]
+    fn cell_styles_and_indents_override_column_values() {
+        let mut buf = vec![0.0_f32; buf_len(3)];
+        for (cell, col) in [(1, 0), (2, 1)] {
+            write_cell(
+                &mut buf,
+                cell,
+                1,
+                col,
+                col as f32 * 100.0,
+                40.0,
+                100.0,
+                30.0,
+                Align::Left,
+                [4.0; 4],
+                NO_BORDER,
+            );
+        }
+        let style = CellStyle {
+            padding: Some([1.0, 2.0, 3.0, 24.0]),
+            align: Some(Align::Right),
+            height: Some(20.0),
+        };
+        apply_cell_styles(&mut buf, 0..3, 1, |row, col| {
+            (row == 0 && col == 1).then_some(&style)
+        });
+
+        indent_cells(&mut buf, 0..3, 1, 0, |row| (row + 1) as f32 * 16.0);
+        let fields = |i: usize| {
+            buf[i * LAYOUT_STRIDE + FIELD_HEIGHT..=i * LAYOUT_STRIDE + FIELD_PADDING_LEFT].to_vec()
+        };
+        assert_eq!(fields(1), vec![30.0, 0.0, 4.0, 4.0, 4.0, 20.0]);
+        assert_eq!(fields(2), vec![20.0, 2.0, 1.0, 2.0, 3.0, 24.0]);
+    }
+
+    #[test]
+    fn footer_follows_the_lowest_row() {
+        let mut buf = vec![0.0_f32; buf_len(4)];
+        for (cell, y, h) in [
+            (0, 300.0, 30.0),
+            (1, 300.0, 30.0),
+            (2, 40.0, 36.0),
+            (3, 76.0, 80.0),
+        ] {
+            write_cell(
+                &mut buf,
+                cell,
+                0,
+                cell % 2,
+                0.0,
+                y,
+                100.0,
+                h,
+                Align::Left,
+                NO_PADDING,
+                NO_BORDER,
+            );
+        }
+        place_footer_after(&mut buf, 0..2, 2..4);
+        assert_eq!(
+            vec![buf[FIELD_Y], buf[LAYOUT_STRIDE + FIELD_Y]],
+            vec![156.0, 156.0]
+        );
+    }
+
+    #[test]
+    fn remap_columns_to_table_indices() {
+        let mut buf = vec![0.0_f32; buf_len(4)];
+        for cell in 0..4 {
+            write_cell(
+                &mut buf,
+                cell,
+                cell / 2,
+                cell % 2,
+                0.0,
+                0.0,
+                100.0,
+                36.0,
+                Align::Left,
+                NO_PADDING,
+                NO_BORDER,
+            );
+        }
+        // Column 1 of 3 is hidden
+        remap_columns(&mut buf, 0..4, &[0, 2]);
+        assert_eq!(
+            (0..4).map(|c| read_col(&buf, c)).collect::<Vec<_>>(),
+            vec![0, 2, 0, 2]
+        );
+        assert_eq!(
+            (0..4).map(|c| read_row(&buf, c)).collect::<Vec<_>>(),
+            vec![0, 0, 1, 1]
+        );
+    }
+
+    #[test]
+    fn rules_at_distinct_cell_edges() {
+        let mut buf = vec![0.0_f32; buf_len(7)];
+        // Header, two rows (the second's y drifted by float error), an item
+        for (cell, row, col, x, y, w) in [
+            (0, 0, 0, 0.0, 0.0, 100.0),
+            (1, 0, 1, 100.0, 0.0, 50.0),
+            (2, 1, 0, 0.0, 40.0, 100.0),
+            (3, 1, 1, 100.0, 40.0, 50.0),
+            (4, 2, 0, 0.0, 76.001, 100.0),
+            (5, 2, 1, 100.0, 76.0, 50.0),
+            (6, 2, 0, 4.0, 80.0, 20.0),
+        ] {
+            let h = if row == 0 { 40.0 } else { 36.0 };
+            write_cell(
+                &mut buf,
+                cell,
+                row,
+                col,
+                x,
+                y,
+                w,
+                if cell == 6 { 10.0 } else { h },
+                Align::Left,
+                NO_PADDING,
+                NO_BORDER,
+            );
+        }
+        buf[6 * LAYOUT_STRIDE + FIELD_REGION] = REGION_CELL_ITEM;
+        let mut rules = vec![1.0];
+        let columns = write_rules(&buf, 7, 2, &mut rules);
+        assert_eq!(columns, 3);
+        assert_eq!(rules, vec![0.0, 100.0, 150.0, 0.0, 40.0, 76.0, 112.0]);
+    }
+
+    #[test]
+    fn header_meta_per_header_cell() {
+        let mut buf = vec![0.0_f32; buf_len(3)];
+        for (cell, row, col, x, w) in [
+            (0, 0, 2, 0.0, 80.0),
+            (1, 0, 0, 80.0, 50.0),
+            (2, 1, 2, 0.0, 80.0),
+        ] {
+            write_cell(
+                &mut buf,
+                cell,
+                row,
+                col,
+                x,
+                0.0,
+                w,
+                36.0,
+                Align::Left,
+                NO_PADDING,
+                NO_BORDER,
+            );
+        }
+        let mut meta = Vec::new();
+        write_header_meta(
+            &buf,
+            3,
+            2,
+            |col| if col == 2 { [2.0, 1.0, 1.0] } else { [0.0; 3] },
+            &mut meta,
+        );
+        assert_eq!(meta.len(), 2 * HEADER_STRIDE);
+        assert_eq!(meta, vec![2.0, 1.0, 1.0, 80.0, 0.0, 0.0, 0.0, 130.0]);
+    }
+
+    #[test]
+    fn style_flags_from_row_column_and_region() {
+        let mut buf = vec![0.0_f32; buf_len(7)];
+        // Header, two rows of one column, a group header, a footer, an item
+        for (cell, row) in [(0, 0), (1, 1), (2, 2), (3, 2), (4, 0), (5, 1), (6, 2)] {
+            write_cell(
+                &mut buf,
+                cell,
+                row,
+                0,
+                0.0,
+                0.0,
+                100.0,
+                36.0,
+                Align::Left,
+                NO_PADDING,
+                NO_BORDER,
+            );
+        }
+        buf[FIELD_REGION] = REGION_STICKY_HEADER + REGION_PINNED_LEFT;
+        buf[2 * LAYOUT_STRIDE + FIELD_REGION] = REGION_PINNED_LEFT;
+        buf[3 * LAYOUT_STRIDE + FIELD_REGION] = REGION_GROUP_HEADER;
+        buf[4 * LAYOUT_STRIDE + FIELD_REGION] = REGION_FOOTER;
+        buf[5 * LAYOUT_STRIDE + FIELD_REGION] = REGION_CELL_ITEM;
+        buf[6 * LAYOUT_STRIDE + FIELD_REGION] = REGION_CELL_ITEM + REGION_PINNED_LEFT;
+        // Row 1 (cell 2) is selected, row 0 (cell 1) matches the filter
+        write_style_flags(&mut buf, 0..7, 1, |pos, _| pos == 1, |pos, _| pos == 0);
+        let flags: Vec<u32> = (0..7)
+            .map(|cell| buf[cell * LAYOUT_STRIDE + FIELD_FLAGS] as u32)
+            .collect();
+        assert_eq!(
+            flags,
+            vec![
+                FLAG_PINNED,
+                FLAG_FILTER_MATCH,
+                FLAG_ODD_ROW | FLAG_PINNED | FLAG_SELECTED,
+                FLAG_ODD_ROW | FLAG_GROUP,
+                0,
+                FLAG_FILTER_MATCH, // item of cell 1
+                FLAG_ODD_ROW | FLAG_PINNED | FLAG_SELECTED,
+            ]
+        );
+    }
+
+    #[test]
+    fn cell_texts_cover_data_cells_with_utf16_offsets() {
+        let mut buf = vec![0.0_f32; buf_len(5)];
+        // Header, two data cells of row 0, a group header, a data cell of row 2
+        for (cell, row, col) in [(0, 0, 0), (1, 1, 0), (2, 1, 1), (3, 2, 0), (4, 3, 1)] {
+            write_cell(
+                &mut buf,
+                cell,
+                row,
+                col,
+                0.0,
+                0.0,
+                100.0,
+                36.0,
+                Align::Left,
+                NO_PADDING,
+                NO_BORDER,
+            );
+        }
+        buf[FIELD_REGION] = REGION_STICKY_HEADER;
+        buf[3 * LAYOUT_STRIDE + FIELD_REGION] = REGION_GROUP_HEADER;
+        let (mut text, mut offsets) = (String::new(), Vec::new());
+        write_cell_texts(
+            &buf,
+            0..5,
+            1,
+            |pos, col, out| {
+                out.push_str(match (pos, col) {
+                    (0, 0) => "a",
+                    (0, 1) => "😀b",
+                    _ => "c",
+                });
+            },
+            &mut text,
+            &mut offsets,
+        );
+        assert_eq!(text, "a😀bc");
+        assert_eq!(offsets, vec![0, 0, 1, 4, 4, 5]);
+    }
+
+    #[test]
+    fn style_ids_only_on_data_cells() {
+        let mut buf = vec![0.0_f32; buf_len(4)];
+        // Header, a data cell, a footer, a data cell
+        for (cell, row) in [(0, 0), (1, 1), (2, 0), (3, 2)] {
+            write_cell(
+                &mut buf,
+                cell,
+                row,
+                1,
+                0.0,
+                0.0,
+                100.0,
+                36.0,
+                Align::Left,
+                NO_PADDING,
+                NO_BORDER,
+            );
+        }
+        buf[2 * LAYOUT_STRIDE + FIELD_REGION] = REGION_FOOTER;
+        write_style_ids(&mut buf, 0..4, 1, |pos, col| (pos * 10 + col + 1) as u32);
+        let ids: Vec<f32> = (0..4)
+            .map(|cell| buf[cell * LAYOUT_STRIDE + FIELD_STYLE])
+            .collect();
+        assert_eq!(ids, vec![0.0, 2.0, 0.0, 12.0]);
+    }
+
+    #[test]
+    fn invalid_flags_only_on_failing_data_cells() {
+        let mut buf = vec![0.0_f32; buf_len(4)];
+        // Header, then data cells of view rows 0, 1, 2
+        for (cell, row) in [(0, 0), (1, 1), (2, 2), (3, 3)] {
+            write_cell(
+                &mut buf,
+                cell,
+                row,
+                0,
+                0.0,
+                0.0,
+                100.0,
+                36.0,
+                Align::Left,
+                NO_PADDING,
+                NO_BORDER,
+            );
+        }
+        buf[2 * LAYOUT_STRIDE + FIELD_FLAGS] = FLAG_ODD_ROW as f32;
+        add_invalid_flags(&mut buf, 0..4, 1, |pos, _| pos != 2);
+        let flags: Vec<u32> = (0..4)
+            .map(|cell| buf[cell * LAYOUT_STRIDE + FIELD_FLAGS] as u32)
+            .collect();
+        assert_eq!(flags, vec![0, FLAG_INVALID, FLAG_ODD_ROW | FLAG_INVALID, 0]);
+    }
+
+    #[test]
+    fn truncations_use_content_width() {
+        let mut buf = vec![0.0_f32; buf_len(3)];
+        // Header, then data cells of view rows 0 and 1
+        for (cell, row) in [(0, 0), (1, 1), (2, 2)] {
+            write_cell(
+                &mut buf,
+                cell,
+                row,
+                0,
+                0.0,
+                0.0,
+                60.0,
+                36.0,
+                Align::Left,
+                [0.0, 5.0, 0.0, 5.0],
+                NO_BORDER,
+            );
+        }
+        let texts = ["", "short", "much longer"];
+        let mut out = Vec::new();
+        write_truncations(
+            &buf,
+            0..3,
+            1,
+            |pos, _, text| text.push_str(texts[pos + 1]),
+            |text, width| {
+                assert!((width - 50.0).abs() < f32::EPSILON);
+                (text.len() > 5).then_some(4)
+            },
+            &mut out,
+        );
+        assert_eq!(out, vec![0, 0, 0, 5, 1, 4]);
+    }
+
+    #[test]
+    fn truncations_of_cells_narrower_than_padding() {
+        let mut buf = vec![0.0_f32; buf_len(2)];
+        // Data cells of view rows 0 and 1, 6 px wide with 10 px of padding
+        for cell in 0..2 {
+            write_cell(
+                &mut buf,
+                cell,
+                cell,
+                0,
+                0.0,
+                0.0,
+                6.0,
+                36.0,
+                Align::Left,
+                [0.0, 5.0, 0.0, 5.0],
+                NO_BORDER,
+            );
+        }
+        let texts = ["", "ab"];
+        let mut out = Vec::new();
+        write_truncations(
+            &buf,
+            0..2,
+            0,
+            |pos, _, text| text.push_str(texts[pos]),
+            |text, width| {
+                assert!(width.abs() < f32::EPSILON);
+                crate::text_wrap::truncate_at(text, f64::from(width), 7.0, |t| {
+                    t.chars().count() as f64 * 7.0
+                })
+            },
+            &mut out,
+        );
+        assert_eq!(out, vec![0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn update_ages_only_on_recent_data_rows() {
+        let mut buf = vec![0.0_f32; buf_len(4)];
+        // Header, then data cells of view rows 0, 1, 2
+        for (cell, row) in [(0, 0), (1, 1), (2, 2), (3, 3)] {
+            write_cell(
+                &mut buf,
+                cell,
+                row,
+                0,
+                0.0,
+                0.0,
+                100.0,
+                36.0,
+                Align::Left,
+                NO_PADDING,
+                NO_BORDER,
+            );
+        }
+        write_update_ages(&mut buf, 0..4, 1, |pos| (pos != 1).then_some(pos as u32));
+        let ages: Vec<f32> = (0..4)
+            .map(|cell| buf[cell * LAYOUT_STRIDE + FIELD_UPDATE_AGE])
+            .collect();
+        assert_eq!(ages, vec![-1.0, 0.0, -1.0, 2.0]);
+    }
+
+    #[test]
+    fn snapped_neighbors_share_edges() {
+        let mut buf = vec![0.0_f32; buf_len(3)];
+        for (cell, x) in [(0, 0.0), (1, 33.3), (2, 66.6)] {
+            write_cell(
+                &mut buf,
+                cell,
+                1,
+                cell,
+                x,
+                10.2,
+                33.3,
+                20.1,
+                Align::Left,
+                NO_PADDING,
+                NO_BORDER,
+            );
+        }
+        snap_cells(&mut buf, 0..3, 1.5);
+        let edges: Vec<(f32, f32)> = (0..3)
+            .map(|cell| {
+                let base = cell * LAYOUT_STRIDE;
+                (
+                    buf[base + FIELD_X],
+                    buf[base + FIELD_X] + buf[base + FIELD_WIDTH],
+                )
+            })
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                (0.0, 100.0 / 3.0),
+                (100.0 / 3.0, 200.0 / 3.0),
+                (200.0 / 3.0, 100.0)
+            ]
+        );
+        assert_eq!(vec![buf[FIELD_Y], buf[FIELD_HEIGHT]], vec![10.0, 20.0]);
+    }
+
+    #[test]
+    fn scale_cells_keeps_row_col_and_region() {
+        let mut buf = vec![0.0_f32; buf_len(2)];
+        for cell in 0..2 {
+            write_cell(
+                &mut buf,
+                cell,
+                3,
+                cell,
+                100.0 * cell as f32,
+                40.0,
+                100.0,
+                36.0,
+                Align::Right,
+                [2.0, 4.0, 2.0, 4.0],
+                [1.0, 0.0, 1.0, 0.0],
+            );
+            buf[cell * LAYOUT_STRIDE + FIELD_REGION] = REGION_PINNED_LEFT;
+            buf[cell * LAYOUT_STRIDE + FIELD_BASELINE] = 58.0;
+        }
+        scale_cells(&mut buf, 1..2, 1.5);
+        assert_eq!(
+            vec![buf[FIELD_Y], buf[FIELD_WIDTH], buf[FIELD_BASELINE]],
+            vec![40.0, 100.0, 58.0]
+        );
+        assert_eq!(
+            buf[LAYOUT_STRIDE..].to_vec(),
+            vec![
+                3.0, 1.0, 150.0, 60.0, 150.0, 54.0, 2.0, 3.0, 6.0, 3.0, 6.0, 1.5, 0.0, 1.5, 0.0,
+                2.0, 87.0, 0.0, 0.0, 0.0, -1.0, -1.0
+            ]
+        );
+    }
+
     #[test]
     fn buf_len_calculation() {
         assert_eq!(buf_len(0), 0);
@@ -143,6 +1767,20 @@ mod tests {
         assert_eq!(buf_len(10), 10 * LAYOUT_STRIDE);
     }
 
+    #[test]
+    fn shared_buffer_slots() {
+        let len = shared_buffer_len(3);
+        assert_eq!(len, (4 + 6 * LAYOUT_STRIDE) * 4);
+        assert_eq!(shared_slot_cells(len), 3);
+        assert_eq!(shared_slot_cells(len + 4 * LAYOUT_STRIDE), 3);
+        assert_eq!(shared_slot_offset(len, 0), SHARED_HEADER_WORDS);
+        assert_eq!(
+            shared_slot_offset(len, 1),
+            SHARED_HEADER_WORDS + 3 * LAYOUT_STRIDE
+        );
+        assert_eq!(shared_slot_cells(8), 0);
+    }
+
     #[test]
     fn write_cell_center_align() {
         let mut buf = vec![0.0_f32; buf_len(1)];
@@ -161,4 +1799,44 @@ mod tests {
         );
         assert!((buf[FIELD_ALIGN] - 1.0).abs() < f32::EPSILON); // Center = 1.0
     }
+
+    #[test]
+    fn changed_cells_detects_uniform_translate() {
+        // (row, y) per cell: the header stays, rows scroll up by 46 px
+        let frame = |cells: &[(usize, f32)]| {
+            let mut buf = vec![0.0_f32; buf_len(cells.len())];
+            for (i, &(row, y)) in cells.iter().enumerate() {
+                write_cell(
+                    &mut buf,
+                    i,
+                    row,
+                    0,
+                    0.0,
+                    y,
+                    100.0,
+                    36.0,
+                    Align::Left,
+                    NO_PADDING,
+                    NO_BORDER,
+                );
+            }
+            buf
+        };
+        let prev = frame(&[(0, 0.0), (1, 36.0), (2, 72.0)]);
+        let cur = frame(&[(0, 0.0), (2, 26.0), (3, 62.0)]);
+        assert_eq!(
+            changed_cells(&prev, 3, &cur, 3),
+            CellChanges {
+                moved: vec![1],
+                added: vec![2],
+                translate: Some((0.0, -46.0)),
+            }
+        );
+        assert_eq!(changed_cells(&cur, 3, &cur, 3), CellChanges::default());
+
+        let mut resized = cur;
+        resized[LAYOUT_STRIDE + FIELD_WIDTH] = 120.0;
+        let changes = changed_cells(&prev, 3, &resized, 3);
+        assert_eq!((changes.moved, changes.translate), (vec![1], None));
+    }
 }
@@ -1,5 +1,39 @@
+pub mod aggregate;
+pub mod arrow_ipc;
+pub mod bitmap;
+pub mod cardinality;
+#[cfg(feature = "collation")]
+pub mod collation;
+pub mod column_sizing;
 pub mod columnar_store;
+pub mod commands;
+pub mod dedupe;
+pub mod editing;
+pub mod export;
+pub mod expr;
+pub mod format;
+pub mod history;
 pub mod layout;
 pub mod layout_buffer;
+pub mod natural_sort;
+pub mod navigation;
+pub mod overlay;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+pub mod prefix_index;
+pub mod print;
+pub mod radix_sort;
+pub mod relative_date;
+pub mod selection;
+pub mod sparkline;
+pub mod state;
+pub mod text_wrap;
+pub mod tree;
 pub mod types;
+pub mod upsert;
+pub mod validation;
 pub mod virtual_scroll;
+pub mod visualization;
+pub mod window;
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
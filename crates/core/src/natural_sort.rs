@@ -0,0 +1,82 @@
+//! Natural ("alphanumeric") string ordering: digit runs compare by numeric value,
+//! so `"file2" < "file10"` and `"v1.9" < "v1.10"`.
+
+use std::cmp::Ordering;
+
+/// Compare two strings naturally without allocating.
+///
+/// Non-digit bytes compare byte-wise (same as `str::cmp`); runs of ASCII digits
+/// compare by value, ignoring leading zeros. Strings that differ only in leading
+/// zeros fall back to byte-wise order so the result stays total.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut x, mut y) = (a.as_bytes(), b.as_bytes());
+    loop {
+        match (x.first(), y.first()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(cx), Some(cy)) if cx.is_ascii_digit() && cy.is_ascii_digit() => {
+                let (nx, rest_x) = split_digits(x);
+                let (ny, rest_y) = split_digits(y);
+                let ordering = nx.len().cmp(&ny.len()).then_with(|| nx.cmp(ny));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                x = rest_x;
+                y = rest_y;
+            }
+            (Some(cx), Some(cy)) => {
+                if cx != cy {
+                    return cx.cmp(cy);
+                }
+                x = &x[1..];
+                y = &y[1..];
+            }
+        }
+    }
+}
+
+/// Split a leading digit run off `s`, returning it without leading zeros.
+fn split_digits(s: &[u8]) -> (&[u8], &[u8]) {
+    let end = s
+        .iter()
+        .position(|c| !c.is_ascii_digit())
+        .unwrap_or(s.len());
+    let (digits, rest) = s.split_at(end);
+    let zeros = digits.iter().take_while(|&&c| c == b'0').count();
+    (&digits[zeros..], rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digit_runs_compare_by_value() {
+        assert_eq!(natural_cmp("file2", "file10"), Ordering::Less);
+        assert_eq!(natural_cmp("v1.9", "v1.10"), Ordering::Less);
+        assert_eq!(natural_cmp("v2.0", "v1.10"), Ordering::Greater);
+        assert_eq!(natural_cmp("a", "a1"), Ordering::Less);
+    }
+
+    #[test]
+    fn text_compares_bytewise() {
+        assert_eq!(natural_cmp("apple", "banana"), Ordering::Less);
+        assert_eq!(natural_cmp("B1", "a1"), Ordering::Less);
+        assert_eq!(natural_cmp("x10", "x10"), Ordering::Equal);
+    }
+
+    #[test]
+    fn leading_zeros_tie_break() {
+        assert_eq!(natural_cmp("007", "7"), Ordering::Less);
+        assert_eq!(natural_cmp("007", "8"), Ordering::Less);
+        assert_eq!(natural_cmp("item 02b", "item 2a"), Ordering::Greater);
+    }
+
+    #[test]
+    fn sorts_list() {
+        let mut names = vec!["file10", "file1", "file2", "file", "file02"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, vec!["file", "file1", "file02", "file2", "file10"]);
+    }
+}
@@ -0,0 +1,212 @@
+//! Keyboard navigation: the next focused cell for arrow, page, and home/end keys
+//! over the view rows and the laid-out columns.
+
+use crate::layout::{ColumnLayout, ColumnPin};
+use crate::virtual_scroll::RowHeightIndex;
+
+/// A navigation key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavKey {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+}
+
+impl NavKey {
+    /// Parse a direction: `up`, `down`, `left`, `right`, `pageUp`, `pageDown`,
+    /// `home`, `end`, or the matching DOM key name (`ArrowUp`, `PageDown`, ...).
+    pub fn parse(s: &str) -> Result<Self, String> {
+        Ok(match s {
+            "up" | "ArrowUp" => Self::Up,
+            "down" | "ArrowDown" => Self::Down,
+            "left" | "ArrowLeft" => Self::Left,
+            "right" | "ArrowRight" => Self::Right,
+            "pageUp" | "PageUp" => Self::PageUp,
+            "pageDown" | "PageDown" => Self::PageDown,
+            "home" | "Home" => Self::Home,
+            "end" | "End" => Self::End,
+            _ => return Err(format!("unknown navigation key: {s:?}")),
+        })
+    }
+}
+
+/// The cells keys move through.
+#[derive(Debug, Clone, Copy)]
+pub struct NavGrid<'a> {
+    /// View rows.
+    pub rows: usize,
+    /// Columns in on-screen order (`column_order`).
+    pub cols: usize,
+    pub row_height: f64,
+    /// Per-row heights, in place of `row_height`.
+    pub heights: Option<&'a RowHeightIndex>,
+    /// Height a page key moves by: the height rows scroll through.
+    pub page_height: f64,
+}
+
+/// Table column indices of `columns` in on-screen order: left-pinned, then
+/// scrollable, then right-pinned columns, each in column order, without the
+/// `hidden` ones.
+pub fn column_order(columns: &[ColumnLayout], hidden: impl Fn(usize) -> bool) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..columns.len()).filter(|&col| !hidden(col)).collect();
+    order.sort_by_key(|&col| match columns[col].pinned {
+        Some(ColumnPin::Left) => 0,
+        None => 1,
+        Some(ColumnPin::Right) => 2,
+    });
+    order
+}
+
+/// The cell focus moves to from `(row, col)` (view row, position in the column
+/// order) when `key` is pressed; `None` on an empty grid.
+///
+/// Arrows move one cell and stop at the edges; with `jump` (Ctrl/Cmd) they go to
+/// the first or last row or column. Page keys move by `page_height`, at least one
+/// row. Home and End go to the first and last column, and with `jump` to the
+/// first and last cell.
+pub fn navigate(
+    grid: &NavGrid,
+    (row, col): (usize, usize),
+    key: NavKey,
+    jump: bool,
+) -> Option<(usize, usize)> {
+    let last_row = grid.rows.checked_sub(1)?;
+    let last_col = grid.cols.checked_sub(1)?;
+    let (row, col) = (row.min(last_row), col.min(last_col));
+    Some(match key {
+        NavKey::Up if jump => (0, col),
+        NavKey::Up => (row.saturating_sub(1), col),
+        NavKey::Down if jump => (last_row, col),
+        NavKey::Down => ((row + 1).min(last_row), col),
+        NavKey::Left if jump => (row, 0),
+        NavKey::Left => (row, col.saturating_sub(1)),
+        NavKey::Right if jump => (row, last_col),
+        NavKey::Right => (row, (col + 1).min(last_col)),
+        NavKey::PageUp => (
+            page_row(grid, row, -grid.page_height).min(row.saturating_sub(1)),
+            col,
+        ),
+        NavKey::PageDown => (
+            page_row(grid, row, grid.page_height).max((row + 1).min(last_row)),
+            col,
+        ),
+        NavKey::Home if jump => (0, 0),
+        NavKey::Home => (row, 0),
+        NavKey::End if jump => (last_row, last_col),
+        NavKey::End => (row, last_col),
+    })
+}
+
+/// The row `delta` px below the top of `row`, clamped to the grid.
+fn page_row(grid: &NavGrid, row: usize, delta: f64) -> usize {
+    let last = grid.rows - 1;
+    grid.heights.map_or_else(
+        || {
+            let rows = (delta / grid.row_height.max(1.0)).trunc() as isize;
+            row.saturating_add_signed(rows).min(last)
+        },
+        |heights| {
+            heights
+                .row_at_offset((heights.offset(row) + delta).max(0.0))
+                .min(last)
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(heights: Option<&RowHeightIndex>) -> NavGrid<'_> {
+        NavGrid {
+            rows: 100,
+            cols: 5,
+            row_height: 20.0,
+            heights,
+            page_height: 200.0,
+        }
+    }
+
+    #[test]
+    fn arrows_stop_at_edges_and_jump() {
+        let grid = grid(None);
+        assert_eq!(navigate(&grid, (0, 0), NavKey::Up, false), Some((0, 0)));
+        assert_eq!(navigate(&grid, (3, 4), NavKey::Right, false), Some((3, 4)));
+        assert_eq!(navigate(&grid, (3, 2), NavKey::Down, false), Some((4, 2)));
+        assert_eq!(navigate(&grid, (3, 2), NavKey::Down, true), Some((99, 2)));
+        assert_eq!(navigate(&grid, (3, 2), NavKey::Left, true), Some((3, 0)));
+        assert_eq!(navigate(&grid, (3, 2), NavKey::End, false), Some((3, 4)));
+        assert_eq!(navigate(&grid, (3, 2), NavKey::Home, true), Some((0, 0)));
+        assert_eq!(
+            navigate(&NavGrid { rows: 0, ..grid }, (0, 0), NavKey::Up, false),
+            None
+        );
+    }
+
+    #[test]
+    fn page_keys_move_by_viewport_height() {
+        let uniform = grid(None);
+        assert_eq!(
+            navigate(&uniform, (5, 1), NavKey::PageDown, false),
+            Some((15, 1))
+        );
+        assert_eq!(
+            navigate(&uniform, (95, 1), NavKey::PageDown, false),
+            Some((99, 1))
+        );
+        assert_eq!(
+            navigate(&uniform, (5, 1), NavKey::PageUp, false),
+            Some((0, 1))
+        );
+
+        // Rows 0..10 are 100px tall, the rest 20px
+        let heights = RowHeightIndex::new(
+            (0..100)
+                .map(|row| if row < 10 { 100.0 } else { 20.0 })
+                .collect(),
+        );
+        let variable = grid(Some(&heights));
+        assert_eq!(
+            navigate(&variable, (0, 0), NavKey::PageDown, false),
+            Some((2, 0))
+        );
+        assert_eq!(
+            navigate(&variable, (20, 0), NavKey::PageUp, false),
+            Some((10, 0))
+        );
+        // A page shorter than the row still moves one row
+        assert_eq!(
+            navigate(
+                &NavGrid {
+                    page_height: 50.0,
+                    ..variable
+                },
+                (0, 0),
+                NavKey::PageDown,
+                false
+            ),
+            Some((1, 0))
+        );
+    }
+
+    #[test]
+    fn column_order_puts_pinned_columns_at_the_edges() {
+        let column = |pinned| ColumnLayout {
+            pinned,
+            ..ColumnLayout::default()
+        };
+        let columns = [
+            column(None),
+            column(Some(ColumnPin::Right)),
+            column(None),
+            column(Some(ColumnPin::Left)),
+            column(None),
+        ];
+        assert_eq!(column_order(&columns, |col| col == 2), vec![3, 0, 4, 1]);
+    }
+}
@@ -0,0 +1,198 @@
+//! Overlay rectangles anchored to cell ranges (selections, edit outlines,
+//! comment badges), resolved against each laid-out frame.
+
+use std::ops::RangeInclusive;
+
+use crate::layout_buffer::{
+    FIELD_COL, FIELD_HEIGHT, FIELD_REGION, FIELD_ROW, FIELD_WIDTH, FIELD_X, FIELD_Y, LAYOUT_STRIDE,
+    REGION_CELL_ITEM, REGION_DETAIL, REGION_FOOTER, REGION_GROUP_HEADER, REGION_MERGED,
+    REGION_PINNED_LEFT, REGION_PINNED_RIGHT,
+};
+
+/// f32 values per resolved overlay: [id, x, y, width, height, region].
+pub const OVERLAY_STRIDE: usize = 6;
+
+/// What the row range of an `Overlay` counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverlayAnchor {
+    /// View positions: the overlay stays at the same place on screen when the
+    /// view is sorted or filtered (selection ranges).
+    #[default]
+    View,
+    /// Data row indices: the overlay follows its rows (comments on a record).
+    Data,
+}
+
+/// A rectangle covering the cells of `rows` × `cols`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overlay {
+    /// Caller's ID, written back with the resolved rectangle.
+    pub id: u32,
+    pub anchor: OverlayAnchor,
+    pub rows: RangeInclusive<usize>,
+    /// Table column indices (`FIELD_COL`).
+    pub cols: RangeInclusive<usize>,
+}
+
+/// Bounding box of an overlay's cells so far.
+#[derive(Clone, Copy)]
+struct Bounds {
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+    region: u32,
+}
+
+/// Resolve `overlays` against a layout buffer of `cell_count` cells into `out`
+/// (`OVERLAY_STRIDE` values each); returns the number written.
+///
+/// Each overlay becomes the bounding box of its laid-out data cells (header,
+/// footer, area, merged, and item cells don't count), so it follows scrolling,
+/// row heights, pinning, and zoom like the cells themselves. Its region is the
+/// union of the pinned flags of those cells. Overlays without a laid-out cell
+/// (scrolled out or filtered away) are left out. `data_row` maps a view position
+/// to its data row.
+pub fn write_overlays(
+    overlays: &[Overlay],
+    buf: &[f32],
+    cell_count: usize,
+    header_row_count: usize,
+    data_row: impl Fn(usize) -> Option<usize>,
+    out: &mut Vec<f32>,
+) -> usize {
+    const NOT_DATA: u32 =
+        (REGION_FOOTER + REGION_DETAIL + REGION_GROUP_HEADER + REGION_MERGED + REGION_CELL_ITEM)
+            as u32;
+    const PINNED: u32 = (REGION_PINNED_LEFT + REGION_PINNED_RIGHT) as u32;
+
+    out.clear();
+    if overlays.is_empty() {
+        return 0;
+    }
+    let mut bounds: Vec<Option<Bounds>> = vec![None; overlays.len()];
+    for cell in 0..cell_count {
+        let base = cell * LAYOUT_STRIDE;
+        let region = buf[base + FIELD_REGION] as u32;
+        let Some(pos) = (buf[base + FIELD_ROW] as usize).checked_sub(header_row_count) else {
+            continue;
+        };
+        if region & NOT_DATA != 0 {
+            continue;
+        }
+        let col = buf[base + FIELD_COL] as usize;
+        let row = data_row(pos);
+        let cell_bounds = Bounds {
+            left: buf[base + FIELD_X],
+            top: buf[base + FIELD_Y],
+            right: buf[base + FIELD_X] + buf[base + FIELD_WIDTH],
+            bottom: buf[base + FIELD_Y] + buf[base + FIELD_HEIGHT],
+            region: region & PINNED,
+        };
+        for (overlay, bound) in overlays.iter().zip(&mut bounds) {
+            let anchored = match overlay.anchor {
+                OverlayAnchor::View => Some(pos),
+                OverlayAnchor::Data => row,
+            };
+            if !overlay.cols.contains(&col) || !anchored.is_some_and(|r| overlay.rows.contains(&r))
+            {
+                continue;
+            }
+            let b = bound.get_or_insert(cell_bounds);
+            b.left = b.left.min(cell_bounds.left);
+            b.top = b.top.min(cell_bounds.top);
+            b.right = b.right.max(cell_bounds.right);
+            b.bottom = b.bottom.max(cell_bounds.bottom);
+            b.region |= cell_bounds.region;
+        }
+    }
+    for (overlay, bound) in overlays.iter().zip(bounds) {
+        if let Some(b) = bound {
+            out.extend([
+                overlay.id as f32,
+                b.left,
+                b.top,
+                b.right - b.left,
+                b.bottom - b.top,
+                b.region as f32,
+            ]);
+        }
+    }
+    out.len() / OVERLAY_STRIDE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Align;
+    use crate::layout_buffer::{buf_len, write_cell, REGION_STICKY_HEADER};
+
+    /// Header row plus 3 rows × 2 columns of 100 × 30 cells below a 40 px header.
+    fn frame() -> Vec<f32> {
+        let mut buf = vec![0.0_f32; buf_len(8)];
+        for cell in 0..8 {
+            let (row, col) = (cell / 2, cell % 2);
+            let (y, h) = if row == 0 {
+                (0.0, 40.0)
+            } else {
+                (30.0f32.mul_add(row as f32, 10.0), 30.0)
+            };
+            write_cell(
+                &mut buf,
+                cell,
+                row,
+                col,
+                100.0 * col as f32,
+                y,
+                100.0,
+                h,
+                Align::Left,
+                [0.0; 4],
+                [0.0; 4],
+            );
+        }
+        buf[FIELD_REGION] = REGION_STICKY_HEADER;
+        buf[3 * LAYOUT_STRIDE + FIELD_REGION] = REGION_PINNED_RIGHT;
+        buf
+    }
+
+    fn overlay(id: u32, anchor: OverlayAnchor, rows: RangeInclusive<usize>) -> Overlay {
+        Overlay {
+            id,
+            anchor,
+            rows,
+            cols: 1..=usize::MAX,
+        }
+    }
+
+    #[test]
+    fn overlays_cover_their_laid_out_cells() {
+        let overlays = [
+            Overlay {
+                cols: 0..=0,
+                ..overlay(7, OverlayAnchor::View, 1..=5)
+            },
+            overlay(8, OverlayAnchor::View, 0..=0),
+            overlay(9, OverlayAnchor::View, 10..=12),
+        ];
+        let mut out = Vec::new();
+        let count = write_overlays(&overlays, &frame(), 8, 1, Some, &mut out);
+        assert_eq!(count, 2);
+        assert_eq!(
+            out,
+            vec![
+                7.0, 0.0, 70.0, 100.0, 60.0, 0.0, // rows 1-2 of column 0 are laid out
+                8.0, 100.0, 40.0, 100.0, 30.0, 4.0, // pinned right
+            ]
+        );
+    }
+
+    #[test]
+    fn data_anchored_overlays_follow_their_rows() {
+        // The view is sorted in reverse: position 0 shows data row 2
+        let overlays = [overlay(1, OverlayAnchor::Data, 2..=2)];
+        let mut out = Vec::new();
+        write_overlays(&overlays, &frame(), 8, 1, |pos| Some(2 - pos), &mut out);
+        assert_eq!(out, vec![1.0, 100.0, 40.0, 100.0, 30.0, 4.0]);
+    }
+}
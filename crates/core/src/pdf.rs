@@ -0,0 +1,532 @@
+//! PDF export of the view: a header row on every page, the display text of the
+//! cells cut to the column widths, and group rows with subtotals.
+//!
+//! Text is set in the standard Helvetica font with `WinAnsiEncoding`, so
+//! characters outside Latin-1 print as `?`. Content streams are uncompressed.
+
+use std::io::Write;
+
+use crate::aggregate::Aggregate;
+use crate::columnar_store::{ColumnData, ColumnarStore};
+use crate::layout::Align;
+
+/// A4 portrait, pt.
+const A4: (f64, f64) = (595.28, 841.89);
+const DEFAULT_FONT_SIZE: f64 = 9.0;
+/// CSS px to PDF pt.
+const PT_PER_PX: f64 = 0.75;
+/// Page margin, pt.
+const MARGIN: f64 = 36.0;
+/// Horizontal cell padding, pt.
+const CELL_PADDING: f64 = 3.0;
+/// Row height as a multiple of the font size.
+const ROW_HEIGHT_EM: f64 = 1.6;
+/// Width of columns without one, pt.
+const MIN_COLUMN_WIDTH: f64 = 40.0;
+/// `…` in `WinAnsiEncoding`.
+const ELLIPSIS: u8 = 0x85;
+
+/// Helvetica glyph widths of ASCII 32..=126, 1/1000 em.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// A column of the document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfColumn {
+    /// Table column.
+    pub col: usize,
+    pub header: String,
+    /// Width in px (0 = fit the header).
+    pub width: f64,
+    pub align: Align,
+    /// Value shown in group header rows, formatted like the column.
+    pub subtotal: Option<Aggregate>,
+}
+
+/// The document `write_pdf` writes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfDocument {
+    /// Printed above the table and stored as the document title (none when empty).
+    pub title: String,
+    pub columns: Vec<PdfColumn>,
+    /// Page size, pt (A4 portrait by default).
+    pub page_width: f64,
+    pub page_height: f64,
+    /// Font size of the cells, pt.
+    pub font_size: f64,
+}
+
+impl Default for PdfDocument {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            columns: Vec::new(),
+            page_width: A4.0,
+            page_height: A4.1,
+            font_size: DEFAULT_FONT_SIZE,
+        }
+    }
+}
+
+/// A PDF of `doc` over the view rows of `store` (call after `rebuild_view`), in
+/// view order.
+///
+/// Columns keep their widths, scaled down together when they are wider than
+/// the page. Rows flow onto as many pages as needed, each starting with the
+/// header row. Group header rows show their group's value and row count and
+/// the columns' subtotals.
+pub fn write_pdf(store: &ColumnarStore, doc: &PdfDocument) -> Vec<u8> {
+    let font_size = if doc.font_size > 0.0 {
+        doc.font_size
+    } else {
+        DEFAULT_FONT_SIZE
+    };
+    let row_height = font_size * ROW_HEIGHT_EM;
+    let widths = column_widths(doc, font_size);
+    let mut pages = Vec::new();
+    let mut page = Page::new(doc, font_size, row_height, &widths);
+    if !doc.title.trim().is_empty() {
+        page.title(doc.title.trim());
+    }
+    page.header_row();
+
+    let view = store.view_indices();
+    let mut text = String::new();
+    let mut cells = Vec::with_capacity(doc.columns.len());
+    for (pos, &row) in view.iter().enumerate() {
+        if page.y - row_height < MARGIN {
+            pages.push(page.finish());
+            page = Page::new(doc, font_size, row_height, &widths);
+            page.header_row();
+        }
+        let row = row as usize;
+        let group = store.view_group(pos);
+        cells.clear();
+        for column in &doc.columns {
+            text.clear();
+            match group {
+                Some(group) if store.group_by()[group.depth as usize] == column.col => {
+                    store.write_cell_text(column.col, row, &mut text);
+                    let _ = std::fmt::Write::write_fmt(
+                        &mut text,
+                        format_args!(" ({})", group.rows.len()),
+                    );
+                }
+                Some(group) => {
+                    if let Some(aggregate) = column.subtotal {
+                        let value = store.group_aggregate(group, column.col, aggregate);
+                        write_number(store, column.col, value, aggregate, &mut text);
+                    }
+                }
+                None => store.write_cell_text(column.col, row, &mut text),
+            }
+            cells.push(text.clone());
+        }
+        page.row(&cells, group.is_some());
+    }
+    pages.push(page.finish());
+    assemble(doc, &pages)
+}
+
+/// Widths of the columns, pt, scaled to fit between the margins.
+fn column_widths(doc: &PdfDocument, font_size: f64) -> Vec<f64> {
+    let mut widths: Vec<f64> = doc
+        .columns
+        .iter()
+        .map(|column| {
+            if column.width > 0.0 {
+                column.width * PT_PER_PX
+            } else {
+                let header = encode(&column.header);
+                2.0f64
+                    .mul_add(CELL_PADDING, text_width(&header, font_size))
+                    .max(MIN_COLUMN_WIDTH)
+            }
+        })
+        .collect();
+    let total: f64 = widths.iter().sum();
+    let available = 2.0f64.mul_add(-MARGIN, doc.page_width);
+    if total > available && total > 0.0 {
+        let scale = available / total;
+        for width in &mut widths {
+            *width *= scale;
+        }
+    }
+    widths
+}
+
+/// A subtotal in the column's format; counts as plain numbers.
+fn write_number(
+    store: &ColumnarStore,
+    col: usize,
+    value: f64,
+    aggregate: Aggregate,
+    out: &mut String,
+) {
+    if value.is_nan() {
+        return;
+    }
+    let spec = store.column_format(col).filter(|_| {
+        aggregate != Aggregate::Count && matches!(store.data.get(col), Some(ColumnData::Float64(_)))
+    });
+    match spec {
+        Some(spec) => spec.write_f64(value, out),
+        None => {
+            let _ = std::fmt::Write::write_fmt(out, format_args!("{value}"));
+        }
+    }
+}
+
+/// The content stream of a page being filled top to bottom.
+struct Page<'a> {
+    doc: &'a PdfDocument,
+    widths: &'a [f64],
+    font_size: f64,
+    row_height: f64,
+    /// Top of the next row, pt from the bottom of the page.
+    y: f64,
+    content: Vec<u8>,
+}
+
+impl<'a> Page<'a> {
+    fn new(doc: &'a PdfDocument, font_size: f64, row_height: f64, widths: &'a [f64]) -> Self {
+        Self {
+            doc,
+            widths,
+            font_size,
+            row_height,
+            y: doc.page_height - MARGIN,
+            content: Vec::new(),
+        }
+    }
+
+    fn title(&mut self, title: &str) {
+        let size = self.font_size * 1.5;
+        self.y -= size;
+        self.text(MARGIN, self.y, size, &encode(title));
+        self.y -= size * 0.5;
+    }
+
+    fn header_row(&mut self) {
+        let headers: Vec<&str> = self.doc.columns.iter().map(|c| c.header.as_str()).collect();
+        self.fill(0.85);
+        self.cells(&headers, None);
+        // Rule under the header
+        let width: f64 = self.widths.iter().sum();
+        let _ = writeln!(
+            self.content,
+            "0.5 w {MARGIN:.2} {y:.2} m {x:.2} {y:.2} l S",
+            y = self.y,
+            x = MARGIN + width,
+        );
+    }
+
+    fn row(&mut self, cells: &[String], group: bool) {
+        if group {
+            self.fill(0.93);
+        }
+        let cells: Vec<&str> = cells.iter().map(String::as_str).collect();
+        let aligns: Vec<Align> = self.doc.columns.iter().map(|c| c.align).collect();
+        self.cells(&cells, Some(&aligns));
+    }
+
+    /// Fill the next row's band in `gray`.
+    fn fill(&mut self, gray: f64) {
+        let width: f64 = self.widths.iter().sum();
+        let _ = writeln!(
+            self.content,
+            "{gray} g {MARGIN:.2} {:.2} {width:.2} {:.2} re f 0 g",
+            self.y - self.row_height,
+            self.row_height,
+        );
+    }
+
+    /// Write one row of `cells`, left-aligned unless `aligns` says otherwise.
+    fn cells(&mut self, cells: &[&str], aligns: Option<&[Align]>) {
+        self.y -= self.row_height;
+        let baseline = self.font_size.mul_add(-0.3, self.row_height / 2.0) + self.y;
+        let mut x = MARGIN;
+        for (i, (cell, &width)) in cells.iter().zip(self.widths).enumerate() {
+            let available = 2.0f64.mul_add(-CELL_PADDING, width);
+            let text = fit(&encode(cell), available, self.font_size);
+            if !text.is_empty() {
+                let text_width = text_width(&text, self.font_size);
+                let offset = match aligns.map_or(Align::Left, |aligns| aligns[i]) {
+                    Align::Left => 0.0,
+                    Align::Center => (available - text_width) / 2.0,
+                    Align::Right => available - text_width,
+                };
+                self.text(x + CELL_PADDING + offset, baseline, self.font_size, &text);
+            }
+            x += width;
+        }
+    }
+
+    fn text(&mut self, x: f64, y: f64, size: f64, text: &[u8]) {
+        let _ = write!(self.content, "BT /F1 {size} Tf {x:.2} {y:.2} Td (");
+        for &byte in text {
+            if matches!(byte, b'(' | b')' | b'\\') {
+                self.content.push(b'\\');
+            }
+            self.content.push(byte);
+        }
+        self.content.extend_from_slice(b") Tj ET\n");
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.content
+    }
+}
+
+/// `text` in `WinAnsiEncoding`: Latin-1 as is, line breaks as spaces, anything
+/// else as `?`.
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match u32::from(c) {
+            0x0A | 0x0D | 0x09 => b' ',
+            code @ (0x20..=0x7E | 0xA0..=0xFF) => code as u8,
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// Width of `WinAnsiEncoding` text in Helvetica at `size`, pt.
+fn text_width(text: &[u8], size: f64) -> f64 {
+    let units: u32 = text
+        .iter()
+        .map(|&byte| match byte {
+            32..=126 => u32::from(HELVETICA_WIDTHS[usize::from(byte - 32)]),
+            ELLIPSIS => 1000,
+            _ => 556,
+        })
+        .sum();
+    f64::from(units) * size / 1000.0
+}
+
+/// `text` cut to `width` pt, ending with an ellipsis when cut.
+fn fit(text: &[u8], width: f64, size: f64) -> Vec<u8> {
+    if text_width(text, size) <= width {
+        return text.to_vec();
+    }
+    let mut cut = text.to_vec();
+    loop {
+        cut.pop();
+        cut.push(ELLIPSIS);
+        if cut.len() == 1 || text_width(&cut, size) <= width {
+            break;
+        }
+        cut.pop();
+    }
+    if text_width(&cut, size) <= width {
+        cut
+    } else {
+        Vec::new()
+    }
+}
+
+/// The PDF file of `pages` (their content streams): catalog, page tree, font,
+/// info, then a page and a content object per page, and the cross-reference
+/// table.
+fn assemble(doc: &PdfDocument, pages: &[Vec<u8>]) -> Vec<u8> {
+    const FIRST_PAGE_OBJECT: usize = 5;
+    let mut objects: Vec<Vec<u8>> = Vec::with_capacity(FIRST_PAGE_OBJECT - 1 + 2 * pages.len());
+    objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", FIRST_PAGE_OBJECT + 2 * i))
+        .collect();
+    objects.push(
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        )
+        .into_bytes(),
+    );
+    objects.push(
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_vec(),
+    );
+    let mut info = b"<< /Producer (react-wasm-table)".to_vec();
+    if !doc.title.trim().is_empty() {
+        info.extend_from_slice(b" /Title (");
+        for byte in encode(doc.title.trim()) {
+            if matches!(byte, b'(' | b')' | b'\\') {
+                info.push(b'\\');
+            }
+            info.push(byte);
+        }
+        info.push(b')');
+    }
+    info.extend_from_slice(b" >>");
+    objects.push(info);
+    for (i, content) in pages.iter().enumerate() {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                 /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                doc.page_width,
+                doc.page_height,
+                FIRST_PAGE_OBJECT + 2 * i + 1
+            )
+            .into_bytes(),
+        );
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend_from_slice(content);
+        stream.extend_from_slice(b"\nendstream");
+        objects.push(stream);
+    }
+
+    let mut out = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        let _ = writeln!(out, "{} 0 obj", i + 1);
+        out.extend_from_slice(object);
+        out.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = out.len();
+    let _ = write!(out, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(out, "{offset:010} 00000 n ");
+    }
+    let _ = write!(
+        out,
+        "trailer\n<< /Size {} /Root 1 0 R /Info 4 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::FormatSpec;
+
+    fn column(col: usize, header: &str) -> PdfColumn {
+        PdfColumn {
+            col,
+            header: header.to_string(),
+            width: 0.0,
+            align: Align::Left,
+            subtotal: None,
+        }
+    }
+
+    /// The text shown by the `Tj` operators of `pdf`.
+    fn shown_text(pdf: &[u8]) -> Vec<String> {
+        let pdf = String::from_utf8_lossy(pdf);
+        pdf.lines()
+            .filter_map(|line| {
+                let start = line.find(" Td (")? + 5;
+                let end = line.rfind(") Tj")?;
+                Some(line[start..end].to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn text_is_cut_to_the_column_width() {
+        assert_eq!(encode("a(é)\n€"), b"a(\xE9) ?".to_vec());
+        assert!((text_width(b"Hi", 10.0) - 9.44).abs() < 1e-9);
+        assert_eq!(fit(b"Hello", 100.0, 10.0), b"Hello".to_vec());
+        let cut = fit(b"Hello world", 30.0, 10.0);
+        assert_eq!(cut.last(), Some(&ELLIPSIS));
+        assert!(text_width(&cut, 10.0) <= 30.0);
+        assert!(fit(b"Hello", 5.0, 10.0).is_empty());
+    }
+
+    #[test]
+    fn rows_flow_onto_pages_with_the_header() {
+        let mut store = ColumnarStore::new();
+        store.init(2, 100);
+        let values: Vec<f64> = (0..100).map(f64::from).collect();
+        store.set_column_float64(0, &values);
+        store.set_column_strings(1, &["(x)".to_string()], &[0; 100]);
+        store.finalize();
+        store.rebuild_view();
+
+        let doc = PdfDocument {
+            title: "Report".to_string(),
+            columns: vec![column(0, "N"), column(1, "Label")],
+            ..PdfDocument::default()
+        };
+        let pdf = write_pdf(&store, &doc);
+        assert!(pdf.starts_with(b"%PDF-1.4\n"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+
+        // 100 rows of 14.4 pt (plus header) need two A4 pages
+        // Byte for byte, so offsets hold
+        let text: String = pdf
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii() {
+                    char::from(byte)
+                } else {
+                    '?'
+                }
+            })
+            .collect();
+        assert!(text.contains("/Type /Pages /Kids [5 0 R 7 0 R] /Count 2"));
+        let shown = shown_text(&pdf);
+        assert_eq!(shown[..4], ["Report", "N", "Label", "0"]);
+        assert_eq!(shown[4], "\\(x\\)");
+        assert_eq!(shown.iter().filter(|t| *t == "Label").count(), 2);
+
+        // Every cross-reference points at its object
+        let xref: usize = text
+            .split("startxref\n")
+            .nth(1)
+            .and_then(|tail| tail.lines().next())
+            .unwrap()
+            .parse()
+            .unwrap();
+        let entries: Vec<&str> = text[xref..].lines().skip(3).take(8).collect();
+        for (i, entry) in entries.iter().enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(text[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+
+    #[test]
+    fn group_rows_show_subtotals() {
+        let mut store = ColumnarStore::new();
+        store.init(2, 3);
+        store.set_column_strings(0, &["a".to_string(), "b".to_string()], &[0, 1, 0]);
+        store.set_column_float64(1, &[1.5, 2.0, 3.0]);
+        store.finalize();
+        store.set_column_format(1, Some(FormatSpec::parse("0.00").unwrap()));
+        store.set_group_by(vec![0]);
+        store.rebuild_view();
+
+        let mut amount = column(1, "Amount");
+        amount.align = Align::Right;
+        amount.subtotal = Some(Aggregate::Sum);
+        let doc = PdfDocument {
+            columns: vec![column(0, "Name"), amount],
+            ..PdfDocument::default()
+        };
+        let shown = shown_text(&write_pdf(&store, &doc));
+        assert_eq!(
+            shown,
+            [
+                "Name",
+                "Amount",
+                "a \\(2\\)",
+                "4.50",
+                "a",
+                "1.50",
+                "a",
+                "3.00",
+                "b \\(1\\)",
+                "2.00",
+                "b",
+                "2.00"
+            ]
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use crate::bitmap::RowBitmap;
+use crate::columnar_store::StringInternTable;
+
+/// Sorted, lowercased views of a string column's intern table.
+///
+/// `starts_with`/`ends_with` become a binary-search range over unique strings
+/// instead of lowercasing every row. Matches are case-insensitive, like the
+/// scan path, and the empty-string null sentinel never matches.
+#[derive(Debug)]
+pub struct PrefixIndex {
+    /// (lowercased string, intern ID), sorted by string.
+    forward: Vec<(String, u32)>,
+    /// (reversed lowercased string, intern ID), sorted by string.
+    reversed: Vec<(String, u32)>,
+    intern_len: usize,
+}
+
+impl PrefixIndex {
+    /// Build the index over every non-empty string in `intern`.
+    pub fn build(intern: &StringInternTable) -> Self {
+        let mut forward: Vec<(String, u32)> = intern
+            .iter()
+            .filter(|(_, s)| !s.is_empty())
+            .map(|(id, s)| (s.to_lowercase(), id))
+            .collect();
+        let mut reversed: Vec<(String, u32)> = forward
+            .iter()
+            .map(|(s, id)| (s.chars().rev().collect(), *id))
+            .collect();
+        forward.sort_unstable();
+        reversed.sort_unstable();
+        Self {
+            forward,
+            reversed,
+            intern_len: intern.len(),
+        }
+    }
+
+    /// Size of the intern table the index was built from (staleness check).
+    pub const fn intern_len(&self) -> usize {
+        self.intern_len
+    }
+
+    /// Intern IDs whose string starts with `prefix`, as a bitmap over ID space.
+    pub fn starts_with(&self, prefix: &str) -> RowBitmap {
+        self.range_ids(&self.forward, &prefix.to_lowercase())
+    }
+
+    /// Intern IDs whose string ends with `suffix`, as a bitmap over ID space.
+    pub fn ends_with(&self, suffix: &str) -> RowBitmap {
+        let rev: String = suffix.to_lowercase().chars().rev().collect();
+        self.range_ids(&self.reversed, &rev)
+    }
+
+    fn range_ids(&self, sorted: &[(String, u32)], prefix: &str) -> RowBitmap {
+        let start = sorted.partition_point(|(s, _)| s.as_str() < prefix);
+        let len = sorted[start..].partition_point(|(s, _)| s.starts_with(prefix));
+        let mut ids = RowBitmap::new(self.intern_len);
+        for (_, id) in &sorted[start..start + len] {
+            ids.insert(*id as usize);
+        }
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_intern(strings: &[&str]) -> StringInternTable {
+        let mut intern = StringInternTable::new();
+        for s in strings {
+            intern.intern(s);
+        }
+        intern
+    }
+
+    fn ids(bitmap: &RowBitmap) -> Vec<u32> {
+        bitmap.iter_ones().collect()
+    }
+
+    #[test]
+    fn starts_with_range() {
+        let intern = make_intern(&["", "Charlie", "chad", "Bob", "Chloe", "Alice"]);
+        let index = PrefixIndex::build(&intern);
+        assert_eq!(ids(&index.starts_with("ch")), vec![1, 2, 4]);
+        assert_eq!(ids(&index.starts_with("CHA")), vec![1, 2]);
+        assert!(ids(&index.starts_with("z")).is_empty());
+    }
+
+    #[test]
+    fn ends_with_range() {
+        let intern = make_intern(&["", "Charlie", "Alice", "Bob", "Dave"]);
+        let index = PrefixIndex::build(&intern);
+        assert_eq!(ids(&index.ends_with("E")), vec![1, 2, 4]);
+        assert_eq!(ids(&index.ends_with("ce")), vec![2]);
+    }
+
+    #[test]
+    fn empty_prefix_matches_all_but_null() {
+        let intern = make_intern(&["", "a", "b"]);
+        let index = PrefixIndex::build(&intern);
+        assert_eq!(ids(&index.starts_with("")), vec![1, 2]);
+        assert_eq!(index.intern_len(), 3);
+    }
+}
@@ -0,0 +1,65 @@
+//! Print layout: the view split into pages of whole rows.
+
+use std::ops::Range;
+
+/// Split view rows `0..row_count` into pages of whole rows.
+///
+/// `offset(pos)` is the top of view row `pos` relative to the first row, and
+/// `offset(row_count)` the height of all rows. The first page has `first_body`
+/// px for rows, later pages `body`. A row taller than its page gets a page of
+/// its own. A `trailer` (footer) of that height follows the last row, on a page
+/// with no rows when it does not fit. There is always at least one page.
+pub fn paginate(
+    row_count: usize,
+    first_body: f64,
+    body: f64,
+    trailer: f64,
+    mut offset: impl FnMut(usize) -> f64,
+) -> Vec<Range<usize>> {
+    let mut pages = Vec::new();
+    let mut start = 0;
+    while start < row_count {
+        let height = if pages.is_empty() { first_body } else { body };
+        let top = offset(start);
+        let mut end = start + 1;
+        while end < row_count && offset(end + 1) - top <= height {
+            end += 1;
+        }
+        pages.push(start..end);
+        start = end;
+    }
+    let fits_trailer = pages.last().is_some_and(|last: &Range<usize>| {
+        let height = if pages.len() == 1 { first_body } else { body };
+        offset(row_count) - offset(last.start) + trailer <= height
+    });
+    if !fits_trailer && (trailer > 0.0 || pages.is_empty()) {
+        pages.push(row_count..row_count);
+    }
+    pages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_pages_with_whole_rows() {
+        let pages = paginate(10, 75.0, 100.0, 0.0, |pos| pos as f64 * 20.0);
+        assert_eq!(pages, vec![0..3, 3..8, 8..10]);
+    }
+
+    #[test]
+    fn tall_row_gets_its_own_page() {
+        let offsets = [0.0, 20.0, 170.0, 190.0];
+        let pages = paginate(3, 100.0, 100.0, 0.0, |pos| offsets[pos]);
+        assert_eq!(pages, vec![0..1, 1..2, 2..3]);
+    }
+
+    #[test]
+    fn trailer_follows_last_row() {
+        let offset = |pos: usize| pos as f64 * 20.0;
+        assert_eq!(paginate(4, 100.0, 100.0, 20.0, offset), vec![0..4]);
+        assert_eq!(paginate(5, 100.0, 100.0, 20.0, offset), vec![0..5, 5..5]);
+        assert_eq!(paginate(0, 100.0, 100.0, 0.0, offset), vec![0..0]);
+    }
+}
@@ -0,0 +1,211 @@
+//! Non-comparison sorts over row indices.
+//!
+//! LSD radix sort on `u64` keys and counting sort on small integer ranks. Both are
+//! stable, so rows with equal keys keep their input order — the same result as a
+//! stable comparison sort.
+
+/// Order-preserving `u64` key for an `f64`: `a < b` ⇔ `key(a) < key(b)`.
+/// `-0.0` and `0.0` map to the same key. NaN must be handled by the caller.
+pub fn f64_key(value: f64) -> u64 {
+    let value = if value == 0.0 { 0.0 } else { value };
+    let bits = value.to_bits();
+    if bits >> 63 == 1 {
+        !bits
+    } else {
+        bits | 1 << 63
+    }
+}
+
+/// Stable LSD radix sort of `indices` by `key(index)`, one byte per pass. Passes
+/// where every key shares the same byte are skipped.
+pub fn radix_sort_by_key(indices: &mut [u32], key: impl Fn(u32) -> u64) {
+    let keys: Vec<u64> = indices.iter().map(|&i| key(i)).collect();
+    radix_sort_keys(indices, keys, histogram);
+}
+
+/// Parallel `radix_sort_by_key`: keys and per-pass digit counts are computed on
+/// the rayon pool; the scatter stays sequential. Same order as the sequential sort.
+#[cfg(feature = "threads")]
+pub fn par_radix_sort_by_key(indices: &mut [u32], key: impl Fn(u32) -> u64 + Sync) {
+    use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+    use rayon::slice::ParallelSlice;
+
+    let keys: Vec<u64> = indices.par_iter().map(|&i| key(i)).collect();
+    radix_sort_keys(indices, keys, |keys, shift| {
+        keys.par_chunks(1 << 14)
+            .map(|chunk| histogram(chunk, shift))
+            .reduce(
+                || [0; 256],
+                |mut a, b| {
+                    for (x, y) in a.iter_mut().zip(b) {
+                        *x += y;
+                    }
+                    a
+                },
+            )
+    });
+}
+
+/// Count of each byte of `keys` at `shift`.
+fn histogram(keys: &[u64], shift: u32) -> [usize; 256] {
+    let mut counts = [0usize; 256];
+    for &k in keys {
+        counts[((k >> shift) & 0xFF) as usize] += 1;
+    }
+    counts
+}
+
+/// Radix sort `indices` by their precomputed `keys`, counting digits with `count`.
+fn radix_sort_keys(
+    indices: &mut [u32],
+    mut keys: Vec<u64>,
+    count: impl Fn(&[u64], u32) -> [usize; 256],
+) {
+    let n = indices.len();
+    let mut idx = indices.to_vec();
+    let mut keys_buf = vec![0u64; n];
+    let mut idx_buf = vec![0u32; n];
+
+    for shift in (0..64).step_by(8) {
+        let mut counts = count(&keys, shift);
+        if counts.contains(&n) {
+            continue;
+        }
+        let mut offset = 0;
+        for count in &mut counts {
+            let c = *count;
+            *count = offset;
+            offset += c;
+        }
+        for (&k, &i) in keys.iter().zip(&idx) {
+            let digit = &mut counts[((k >> shift) & 0xFF) as usize];
+            keys_buf[*digit] = k;
+            idx_buf[*digit] = i;
+            *digit += 1;
+        }
+        std::mem::swap(&mut keys, &mut keys_buf);
+        std::mem::swap(&mut idx, &mut idx_buf);
+    }
+    indices.copy_from_slice(&idx);
+}
+
+/// Stable counting sort of `indices` by `rank(index)`, which must be `< buckets`.
+pub fn counting_sort_by_rank(indices: &mut [u32], buckets: usize, rank: impl Fn(u32) -> usize) {
+    let mut starts = vec![0usize; buckets + 1];
+    for &i in indices.iter() {
+        starts[rank(i) + 1] += 1;
+    }
+    for b in 1..=buckets {
+        starts[b] += starts[b - 1];
+    }
+    let mut out = vec![0u32; indices.len()];
+    for &i in indices.iter() {
+        let slot = &mut starts[rank(i)];
+        out[*slot] = i;
+        *slot += 1;
+    }
+    indices.copy_from_slice(&out);
+}
+
+/// Parallel `counting_sort_by_rank`: ranks are computed once, on the rayon pool.
+#[cfg(feature = "threads")]
+pub fn par_counting_sort_by_rank(
+    indices: &mut [u32],
+    buckets: usize,
+    rank: impl Fn(u32) -> usize + Sync,
+) {
+    use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+    let ranks: Vec<usize> = indices.par_iter().map(|&i| rank(i)).collect();
+    counting_sort_ranks(indices, buckets, &ranks);
+}
+
+/// Counting sort of `indices` by `ranks` (parallel to `indices`).
+#[cfg(feature = "threads")]
+fn counting_sort_ranks(indices: &mut [u32], buckets: usize, ranks: &[usize]) {
+    let mut starts = vec![0usize; buckets + 1];
+    for &r in ranks {
+        starts[r + 1] += 1;
+    }
+    for b in 1..=buckets {
+        starts[b] += starts[b - 1];
+    }
+    let mut out = vec![0u32; indices.len()];
+    for (&i, &r) in indices.iter().zip(ranks) {
+        let slot = &mut starts[r];
+        out[*slot] = i;
+        *slot += 1;
+    }
+    indices.copy_from_slice(&out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn f64_key_preserves_order() {
+        let values = [
+            f64::NEG_INFINITY,
+            -1e300,
+            -2.5,
+            -f64::MIN_POSITIVE,
+            0.0,
+            f64::MIN_POSITIVE,
+            1.0,
+            3.5e10,
+            f64::INFINITY,
+        ];
+        for pair in values.windows(2) {
+            assert!(f64_key(pair[0]) < f64_key(pair[1]), "{pair:?}");
+        }
+        assert_eq!(f64_key(-0.0), f64_key(0.0));
+    }
+
+    #[test]
+    fn radix_sort_is_stable() {
+        let keys = [5u64, 1, 300, 1, 70_000, 5, 0, u64::MAX];
+        let mut indices: Vec<u32> = (0..keys.len() as u32).collect();
+        radix_sort_by_key(&mut indices, |i| keys[i as usize]);
+        assert_eq!(indices, vec![6, 1, 3, 0, 5, 2, 4, 7]);
+    }
+
+    #[test]
+    fn radix_sort_matches_comparison_sort() {
+        let values: Vec<f64> = (0..2000)
+            .map(|i| f64::from((i * 7919) % 1013) - 500.5)
+            .collect();
+        let mut indices: Vec<u32> = (0..2000).rev().collect();
+        let mut expected = indices.clone();
+        radix_sort_by_key(&mut indices, |i| f64_key(values[i as usize]));
+        expected.sort_by(|&a, &b| values[a as usize].total_cmp(&values[b as usize]));
+        assert_eq!(indices, expected);
+    }
+
+    #[test]
+    fn counting_sort_is_stable() {
+        let ranks = [2usize, 0, 1, 0, 2, 1];
+        let mut indices: Vec<u32> = vec![5, 4, 3, 2, 1, 0];
+        counting_sort_by_rank(&mut indices, 3, |i| ranks[i as usize]);
+        assert_eq!(indices, vec![3, 1, 5, 2, 4, 0]);
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn parallel_sorts_match_sequential() {
+        let keys: Vec<u64> = (0..100_000u64).map(|i| ((i * 7919) % 4099) << 20).collect();
+        let mut indices: Vec<u32> = (0..keys.len() as u32).rev().collect();
+        let mut expected = indices.clone();
+        par_radix_sort_by_key(&mut indices, |i| keys[i as usize]);
+        radix_sort_by_key(&mut expected, |i| keys[i as usize]);
+        assert_eq!(indices, expected);
+
+        par_counting_sort_by_rank(&mut indices, 4099, |i| {
+            (keys[i as usize] >> 20) as usize % 7
+        });
+        counting_sort_by_rank(&mut expected, 4099, |i| {
+            (keys[i as usize] >> 20) as usize % 7
+        });
+        assert_eq!(indices, expected);
+    }
+}
@@ -0,0 +1,238 @@
+//! Relative date ranges ("last 7 days", "this month", "year to date") resolved
+//! against a reference time and a fixed UTC offset.
+//!
+//! Timestamps are epoch milliseconds. Calendar boundaries (midnight, week start,
+//! month start) are computed in local time = UTC + offset. Weeks start on Monday.
+
+use crate::format::{civil_from_days, days_from_civil};
+
+const MS_PER_DAY: i64 = 86_400_000;
+
+/// A date range relative to "now".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelativeDate {
+    Today,
+    Yesterday,
+    /// The last `n` calendar days, including today.
+    LastDays(u32),
+    /// The next `n` calendar days, including today.
+    NextDays(u32),
+    ThisWeek,
+    LastWeek,
+    ThisMonth,
+    LastMonth,
+    ThisQuarter,
+    ThisYear,
+    LastYear,
+    /// Start of this month through the end of today.
+    MonthToDate,
+    /// Start of this year through the end of today.
+    YearToDate,
+}
+
+impl RelativeDate {
+    /// Parse a range name: `today`, `yesterday`, `last7Days`, `next30Days`,
+    /// `thisWeek`, `lastWeek`, `thisMonth`, `lastMonth`, `thisQuarter`,
+    /// `thisYear`, `lastYear`, `monthToDate`, `yearToDate`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let days = |prefix: &str| {
+            s.strip_prefix(prefix)
+                .and_then(|rest| rest.strip_suffix("Days"))
+                .and_then(|n| n.parse::<u32>().ok())
+        };
+        Ok(match s {
+            "today" => Self::Today,
+            "yesterday" => Self::Yesterday,
+            "thisWeek" => Self::ThisWeek,
+            "lastWeek" => Self::LastWeek,
+            "thisMonth" => Self::ThisMonth,
+            "lastMonth" => Self::LastMonth,
+            "thisQuarter" => Self::ThisQuarter,
+            "thisYear" => Self::ThisYear,
+            "lastYear" => Self::LastYear,
+            "monthToDate" => Self::MonthToDate,
+            "yearToDate" => Self::YearToDate,
+            _ => {
+                if let Some(n) = days("last") {
+                    Self::LastDays(n)
+                } else if let Some(n) = days("next") {
+                    Self::NextDays(n)
+                } else {
+                    return Err(format!("unknown relative date: {s:?}"));
+                }
+            }
+        })
+    }
+
+    /// Range name accepted by `parse`.
+    pub fn name(self) -> String {
+        match self {
+            Self::Today => "today".to_string(),
+            Self::Yesterday => "yesterday".to_string(),
+            Self::LastDays(n) => format!("last{n}Days"),
+            Self::NextDays(n) => format!("next{n}Days"),
+            Self::ThisWeek => "thisWeek".to_string(),
+            Self::LastWeek => "lastWeek".to_string(),
+            Self::ThisMonth => "thisMonth".to_string(),
+            Self::LastMonth => "lastMonth".to_string(),
+            Self::ThisQuarter => "thisQuarter".to_string(),
+            Self::ThisYear => "thisYear".to_string(),
+            Self::LastYear => "lastYear".to_string(),
+            Self::MonthToDate => "monthToDate".to_string(),
+            Self::YearToDate => "yearToDate".to_string(),
+        }
+    }
+
+    /// Half-open `[start, end)` bounds in epoch milliseconds for the given
+    /// reference time and UTC offset (minutes east of UTC).
+    pub fn bounds(self, now_ms: f64, utc_offset_minutes: i32) -> (f64, f64) {
+        let offset_ms = i64::from(utc_offset_minutes) * 60_000;
+        let local_now = now_ms as i64 + offset_ms;
+        let today = local_now.div_euclid(MS_PER_DAY);
+        let (year, month, _) = civil_from_days(today);
+        let monday = today - (today + 3).rem_euclid(7);
+        let month_start = |y: i64, m: i64| {
+            let (y, m) = (y + (m - 1).div_euclid(12), (m - 1).rem_euclid(12) + 1);
+            days_from_civil(y, m as u32, 1)
+        };
+        let m = i64::from(month);
+
+        let (start_day, end_day) = match self {
+            Self::Today => (today, today + 1),
+            Self::Yesterday => (today - 1, today),
+            Self::LastDays(n) => (today + 1 - i64::from(n), today + 1),
+            Self::NextDays(n) => (today, today + i64::from(n)),
+            Self::ThisWeek => (monday, monday + 7),
+            Self::LastWeek => (monday - 7, monday),
+            Self::ThisMonth => (month_start(year, m), month_start(year, m + 1)),
+            Self::LastMonth => (month_start(year, m - 1), month_start(year, m)),
+            Self::ThisQuarter => {
+                let q = (m - 1) / 3 * 3 + 1;
+                (month_start(year, q), month_start(year, q + 3))
+            }
+            Self::ThisYear => (month_start(year, 1), month_start(year + 1, 1)),
+            Self::LastYear => (month_start(year - 1, 1), month_start(year, 1)),
+            Self::MonthToDate => (month_start(year, m), today + 1),
+            Self::YearToDate => (month_start(year, 1), today + 1),
+        };
+        let to_utc = |day: i64| (day * MS_PER_DAY - offset_ms) as f64;
+        (to_utc(start_day), to_utc(end_day))
+    }
+
+    /// Whether a timestamp falls within the range.
+    pub fn contains(self, ts_ms: f64, now_ms: f64, utc_offset_minutes: i32) -> bool {
+        let (start, end) = self.bounds(now_ms, utc_offset_minutes);
+        ts_ms >= start && ts_ms < end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-05-15T10:30:00Z (a Wednesday).
+    const NOW: f64 = 1_715_769_000_000.0;
+
+    fn ymd(y: i64, m: u32, d: u32) -> f64 {
+        (days_from_civil(y, m, d) * MS_PER_DAY) as f64
+    }
+
+    #[test]
+    fn parse_names() {
+        assert_eq!(
+            RelativeDate::parse("last7Days"),
+            Ok(RelativeDate::LastDays(7))
+        );
+        assert_eq!(
+            RelativeDate::parse("next30Days"),
+            Ok(RelativeDate::NextDays(30))
+        );
+        assert_eq!(
+            RelativeDate::parse("yearToDate"),
+            Ok(RelativeDate::YearToDate)
+        );
+        assert!(RelativeDate::parse("lastDays").is_err());
+        assert!(RelativeDate::parse("fortnight").is_err());
+        for range in [RelativeDate::LastDays(14), RelativeDate::MonthToDate] {
+            assert_eq!(RelativeDate::parse(&range.name()), Ok(range));
+        }
+    }
+
+    #[test]
+    fn day_ranges() {
+        assert_eq!(
+            RelativeDate::Today.bounds(NOW, 0),
+            (ymd(2024, 5, 15), ymd(2024, 5, 16))
+        );
+        assert_eq!(
+            RelativeDate::Yesterday.bounds(NOW, 0),
+            (ymd(2024, 5, 14), ymd(2024, 5, 15))
+        );
+        assert_eq!(
+            RelativeDate::LastDays(7).bounds(NOW, 0),
+            (ymd(2024, 5, 9), ymd(2024, 5, 16))
+        );
+        assert_eq!(
+            RelativeDate::NextDays(2).bounds(NOW, 0),
+            (ymd(2024, 5, 15), ymd(2024, 5, 17))
+        );
+    }
+
+    #[test]
+    fn week_starts_monday() {
+        assert_eq!(
+            RelativeDate::ThisWeek.bounds(NOW, 0),
+            (ymd(2024, 5, 13), ymd(2024, 5, 20))
+        );
+        assert_eq!(
+            RelativeDate::LastWeek.bounds(NOW, 0),
+            (ymd(2024, 5, 6), ymd(2024, 5, 13))
+        );
+    }
+
+    #[test]
+    fn calendar_ranges() {
+        assert_eq!(
+            RelativeDate::ThisMonth.bounds(NOW, 0),
+            (ymd(2024, 5, 1), ymd(2024, 6, 1))
+        );
+        assert_eq!(
+            RelativeDate::LastMonth.bounds(NOW, 0),
+            (ymd(2024, 4, 1), ymd(2024, 5, 1))
+        );
+        assert_eq!(
+            RelativeDate::ThisQuarter.bounds(NOW, 0),
+            (ymd(2024, 4, 1), ymd(2024, 7, 1))
+        );
+        assert_eq!(
+            RelativeDate::LastYear.bounds(NOW, 0),
+            (ymd(2023, 1, 1), ymd(2024, 1, 1))
+        );
+        assert_eq!(
+            RelativeDate::YearToDate.bounds(NOW, 0),
+            (ymd(2024, 1, 1), ymd(2024, 5, 16))
+        );
+    }
+
+    #[test]
+    fn last_month_wraps_year() {
+        let jan = ymd(2024, 1, 10);
+        assert_eq!(
+            RelativeDate::LastMonth.bounds(jan, 0),
+            (ymd(2023, 12, 1), ymd(2024, 1, 1))
+        );
+    }
+
+    #[test]
+    fn utc_offset_shifts_day_boundaries() {
+        // 2024-05-15T20:00Z is already May 16 in UTC+9
+        let now = ymd(2024, 5, 15) + 72_000_000.0;
+        let nine_hours = 32_400_000.0;
+        assert_eq!(
+            RelativeDate::Today.bounds(now, 540),
+            (ymd(2024, 5, 16) - nine_hours, ymd(2024, 5, 17) - nine_hours)
+        );
+        assert!(RelativeDate::Today.contains(now, now, 540));
+        assert!(!RelativeDate::Today.contains(ymd(2024, 5, 15), now, 540));
+    }
+}
@@ -0,0 +1,269 @@
+//! Selection state: rows, cells, and ranges picked by the user, kept by data row
+//! so they survive sorts and filters.
+
+use std::ops::RangeInclusive;
+
+use crate::aggregate::Accumulator;
+use crate::bitmap::RowBitmap;
+use crate::columnar_store::ColumnarStore;
+
+/// Every table column.
+const ALL_COLUMNS: RangeInclusive<usize> = 0..=usize::MAX;
+
+/// What one selection step picks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionMode {
+    /// Whole rows: cells and ranges select the rows they touch.
+    #[default]
+    Row,
+    /// Single cells: ranges select their anchor cell.
+    Cell,
+    /// Rectangles of cells.
+    Range,
+}
+
+impl SelectionMode {
+    /// Parse a mode name: `row`, `cell`, `range`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        Ok(match s {
+            "row" => Self::Row,
+            "cell" => Self::Cell,
+            "range" => Self::Range,
+            _ => return Err(format!("unknown selection mode: {s:?}")),
+        })
+    }
+}
+
+/// Selected cells: data rows × table columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Block {
+    rows: RowBitmap,
+    cols: RangeInclusive<usize>,
+}
+
+/// Totals over the selected cells (`Selection::summary`).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SelectionSummary {
+    /// Rows with at least one selected cell.
+    pub rows: usize,
+    /// Every selected cell; `Aggregate::Count` is the cell count, and the other
+    /// aggregates cover the numeric cells.
+    pub cells: Accumulator,
+}
+
+/// The selection of a table: blocks of cells added by `select_cell`,
+/// `select_range`, `toggle_row`, and `select_all`.
+///
+/// Rows are data rows, so the selection follows its rows through sorts and
+/// filters (rows filtered out stay selected). In single mode each step replaces
+/// the selection; in multi mode additive steps add to it.
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    mode: SelectionMode,
+    multi: bool,
+    blocks: Vec<Block>,
+    /// Bumped on every change, for frame keys.
+    version: u64,
+}
+
+impl Selection {
+    /// Switch modes, clearing the selection when they change.
+    pub fn set_mode(&mut self, mode: SelectionMode, multi: bool) {
+        if (mode, multi) != (self.mode, self.multi) {
+            self.mode = mode;
+            self.multi = multi;
+            self.clear();
+        }
+    }
+
+    pub const fn mode(&self) -> (SelectionMode, bool) {
+        (self.mode, self.multi)
+    }
+
+    pub const fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.version += 1;
+    }
+
+    /// Select the cell at data row `row`, column `col` (its row in row mode).
+    /// `additive` adds it to the selection in multi mode.
+    pub fn select_cell(&mut self, row_count: usize, row: u32, col: usize, additive: bool) {
+        self.select_range(row_count, &[row], col..=col, additive);
+    }
+
+    /// Select data rows `rows` (anchor first) × table columns `cols`: whole rows in
+    /// row mode (only the anchor row in single row mode), the anchor cell in cell
+    /// mode. `additive` adds them to the selection in multi mode.
+    pub fn select_range(
+        &mut self,
+        row_count: usize,
+        rows: &[u32],
+        cols: RangeInclusive<usize>,
+        additive: bool,
+    ) {
+        if rows.is_empty() {
+            return;
+        }
+        let (rows, cols) = match self.mode {
+            SelectionMode::Row if self.multi => (rows, ALL_COLUMNS),
+            SelectionMode::Row => (&rows[..1], ALL_COLUMNS),
+            SelectionMode::Cell => (&rows[..1], *cols.start()..=*cols.start()),
+            SelectionMode::Range => (rows, cols),
+        };
+        self.push(row_count, rows, cols, additive);
+    }
+
+    /// Deselect data row `row` when all of it is selected, else select the whole
+    /// row (alone in single mode).
+    pub fn toggle_row(&mut self, row_count: usize, row: u32) {
+        let selected = self
+            .blocks
+            .iter()
+            .any(|b| b.cols == ALL_COLUMNS && b.rows.contains(row as usize));
+        if selected {
+            for block in &mut self.blocks {
+                block.rows.remove(row as usize);
+            }
+            self.blocks.retain(|b| b.rows.count_ones() > 0);
+            self.version += 1;
+        } else {
+            self.push(row_count, &[row], ALL_COLUMNS, true);
+        }
+    }
+
+    /// Select every row of `rows` (e.g. the rows passing the filters), in multi
+    /// mode only.
+    pub fn select_all(&mut self, row_count: usize, rows: &[u32]) {
+        if self.multi {
+            self.push(row_count, rows, ALL_COLUMNS, false);
+        }
+    }
+
+    /// Whether the cell at data row `row`, column `col` is selected.
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        self.blocks
+            .iter()
+            .any(|b| b.cols.contains(&col) && b.rows.contains(row))
+    }
+
+    /// Totals over the selected cells of `store`, each cell counted once.
+    pub fn summary(&self, store: &ColumnarStore) -> SelectionSummary {
+        let mut summary = SelectionSummary::default();
+        if self.blocks.is_empty() {
+            return summary;
+        }
+        let mut any = RowBitmap::new(store.row_count);
+        for col in 0..store.data.len() {
+            let mut rows = RowBitmap::new(store.row_count);
+            for block in self.blocks.iter().filter(|b| b.cols.contains(&col)) {
+                for row in block.rows.iter_ones() {
+                    rows.insert(row as usize);
+                }
+            }
+            any.or_assign(&rows);
+            let rows: Vec<u32> = rows.iter_ones().collect();
+            summary.cells.merge(&store.accumulate(col, &rows));
+        }
+        summary.rows = any.count_ones();
+        summary
+    }
+
+    fn push(
+        &mut self,
+        row_count: usize,
+        rows: &[u32],
+        cols: RangeInclusive<usize>,
+        additive: bool,
+    ) {
+        if !(self.multi && additive) {
+            self.blocks.clear();
+        }
+        let mut bitmap = RowBitmap::new(row_count);
+        for &row in rows {
+            bitmap.insert(row as usize);
+        }
+        self.blocks.push(Block { rows: bitmap, cols });
+        self.version += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregate::Aggregate;
+
+    fn store() -> ColumnarStore {
+        let mut store = ColumnarStore::new();
+        store.init(2, 4);
+        store.set_column_float64(0, &[1.0, 2.0, 4.0, 8.0]);
+        store.set_column_strings(1, &[String::new(), "a".to_string()], &[1, 1, 1, 1]);
+        store.finalize();
+        store
+    }
+
+    #[test]
+    fn modes_shape_each_step() {
+        let mut selection = Selection::default();
+        selection.select_range(4, &[2, 3], 1..=1, true);
+        // Single row mode keeps the anchor row, across all columns
+        assert!(selection.contains(2, 0) && selection.contains(2, 1));
+        assert!(!selection.contains(3, 1));
+
+        selection.set_mode(SelectionMode::Cell, true);
+        assert!(selection.is_empty());
+        selection.select_range(4, &[1, 2], 0..=1, false);
+        selection.select_cell(4, 3, 1, true);
+        assert!(selection.contains(1, 0) && selection.contains(3, 1));
+        assert!(!selection.contains(1, 1) && !selection.contains(2, 0));
+
+        selection.set_mode(SelectionMode::Range, false);
+        selection.select_range(4, &[1, 2], 0..=0, false);
+        selection.select_range(4, &[0], 1..=1, true);
+        // Single mode: the second range replaced the first
+        assert!(selection.contains(0, 1) && !selection.contains(1, 0));
+    }
+
+    #[test]
+    fn toggle_rows_and_select_all() {
+        let mut selection = Selection::default();
+        selection.set_mode(SelectionMode::Row, true);
+        selection.toggle_row(4, 1);
+        selection.toggle_row(4, 3);
+        assert!(selection.contains(1, 5) && selection.contains(3, 0));
+        selection.toggle_row(4, 1);
+        assert!(!selection.contains(1, 0));
+
+        selection.select_all(4, &[0, 2]);
+        assert!(selection.contains(0, 0) && !selection.contains(3, 0));
+    }
+
+    #[test]
+    fn summary_counts_each_cell_once() {
+        let store = store();
+        let mut selection = Selection::default();
+        selection.set_mode(SelectionMode::Range, true);
+        selection.select_range(4, &[0, 1, 2], 0..=0, false);
+        selection.select_range(4, &[2, 3], 0..=1, true);
+
+        let summary = selection.summary(&store);
+        assert_eq!(summary.rows, 4);
+        let cells = summary.cells;
+        assert_eq!(
+            vec![
+                cells.value(Aggregate::Count),
+                cells.value(Aggregate::Sum),
+                cells.value(Aggregate::Min),
+                cells.value(Aggregate::Max),
+            ],
+            vec![6.0, 15.0, 1.0, 8.0]
+        );
+    }
+}
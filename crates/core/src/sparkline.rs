@@ -0,0 +1,50 @@
+//! Sparklines: numeric series down-sampled into a fixed number of buckets.
+
+/// Append `buckets` values to `out`: the mean of each equal slice of `series`.
+///
+/// NaN values are ignored. Series shorter than `buckets` repeat values across buckets;
+/// buckets with no values (e.g. an empty series) are NaN.
+pub fn downsample(series: &[f64], buckets: usize, out: &mut Vec<f32>) {
+    let n = series.len();
+    out.extend((0..buckets).map(|b| {
+        let start = b * n / buckets;
+        let end = ((b + 1) * n / buckets).max(start + 1).min(n);
+        let (sum, count) = series[start.min(n)..end]
+            .iter()
+            .filter(|v| !v.is_nan())
+            .fold((0.0, 0_usize), |(sum, count), v| (sum + v, count + 1));
+        if count == 0 {
+            f32::NAN
+        } else {
+            (sum / count as f64) as f32
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(series: &[f64], buckets: usize) -> Vec<f32> {
+        let mut out = Vec::new();
+        downsample(series, buckets, &mut out);
+        out
+    }
+
+    #[test]
+    fn averages_each_bucket() {
+        let series: Vec<f64> = (0..10).map(f64::from).collect();
+        assert_eq!(sample(&series, 5), vec![0.5, 2.5, 4.5, 6.5, 8.5]);
+        assert_eq!(sample(&series, 3), vec![1.0, 4.0, 7.5]);
+        assert_eq!(sample(&[1.0, f64::NAN, 3.0, 5.0], 2), vec![1.0, 4.0]);
+    }
+
+    #[test]
+    fn short_and_empty_series() {
+        assert_eq!(sample(&[1.0, 2.0], 4), vec![1.0, 1.0, 2.0, 2.0]);
+        let empty = sample(&[], 3);
+        assert_eq!(empty.len(), 3);
+        assert!(empty.iter().all(|v| v.is_nan()));
+        assert!(sample(&[1.0], 0).is_empty());
+    }
+}
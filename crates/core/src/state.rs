@@ -0,0 +1,511 @@
+//! Saved view preferences in a compact versioned binary format.
+//!
+//! Covers sort, filters, grouping, column widths and visibility, expanded rows,
+//! and the scroll position (not data), for persisting to `localStorage` or a
+//! backend.
+//!
+//! Layout: the magic `RWTS`, a version byte, then the fields of `SavedState` in
+//! declaration order. Counts and indices are LEB128 varints, floats are
+//! little-endian, strings are a byte length followed by UTF-8.
+
+use crate::relative_date::RelativeDate;
+use crate::types::{
+    ColumnFilter, FilterOp, FilterValue, GlobalFilter, NullOrder, SortConfig, SortDirection,
+    ViewPreset,
+};
+
+const MAGIC: &[u8; 4] = b"RWTS";
+
+/// Hidden column indices at or above this are rejected by `decode` rather than
+/// allocated for.
+const MAX_COLUMNS: usize = 1 << 16;
+
+/// Format version written by `encode`; `decode` reads this and older versions.
+pub const VERSION: u8 = 1;
+
+/// Filter operators by their code in the format. Append only.
+const FILTER_OPS: [FilterOp; 16] = [
+    FilterOp::Eq,
+    FilterOp::Neq,
+    FilterOp::Gt,
+    FilterOp::Gte,
+    FilterOp::Lt,
+    FilterOp::Lte,
+    FilterOp::Contains,
+    FilterOp::StartsWith,
+    FilterOp::EndsWith,
+    FilterOp::IsNull,
+    FilterOp::IsNotNull,
+    FilterOp::IsBlank,
+    FilterOp::ContainsAny,
+    FilterOp::ContainsAll,
+    FilterOp::Within,
+    FilterOp::Expression,
+];
+
+/// One level of a collapsed group's path, by value rather than intern ID so it
+/// still matches after the data is reloaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupKey {
+    /// A multi-value column level (never grouped by value).
+    None,
+    /// Sort key of a number or bool value (`f64_key`; `u64::MAX` = null).
+    Number(u64),
+    Text(String),
+}
+
+/// Everything `encode` persists.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SavedState {
+    /// Column filters, global filter, and sort.
+    pub view: ViewPreset,
+    pub group_by: Vec<usize>,
+    /// Paths of the collapsed groups, outermost level first.
+    pub collapsed_groups: Vec<Vec<GroupKey>>,
+    /// Expanded tree rows (data indices).
+    pub expanded_rows: Vec<u32>,
+    /// Manual row order by data row (`None` = sorted).
+    pub row_order: Option<Vec<u32>>,
+    /// Width override per table column (`None` = JS width).
+    pub column_widths: Vec<Option<f32>>,
+    /// Hidden table columns.
+    pub hidden_columns: Vec<bool>,
+    /// Vertical scroll offset in layout px.
+    pub scroll_top: f64,
+}
+
+impl SavedState {
+    /// Serialize as the current format version.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = Writer(MAGIC.to_vec());
+        w.0.push(VERSION);
+
+        w.sort_configs(&self.view.sort_configs);
+        w.column_filters(&self.view.column_filters);
+        w.opt_str(self.view.global_filter.as_ref().map(|g| g.query.as_str()));
+
+        w.len(self.group_by.len());
+        for &col in &self.group_by {
+            w.len(col);
+        }
+        w.len(self.collapsed_groups.len());
+        for path in &self.collapsed_groups {
+            w.len(path.len());
+            for key in path {
+                match key {
+                    GroupKey::None => w.0.push(0),
+                    GroupKey::Number(bits) => {
+                        w.0.push(1);
+                        w.0.extend_from_slice(&bits.to_le_bytes());
+                    }
+                    GroupKey::Text(s) => {
+                        w.0.push(2);
+                        w.str(s);
+                    }
+                }
+            }
+        }
+
+        w.rows(&self.expanded_rows);
+        w.0.push(u8::from(self.row_order.is_some()));
+        if let Some(order) = &self.row_order {
+            w.rows(order);
+        }
+
+        w.len(self.column_widths.len());
+        for width in &self.column_widths {
+            w.0.push(u8::from(width.is_some()));
+            if let Some(width) = width {
+                w.0.extend_from_slice(&width.to_le_bytes());
+            }
+        }
+        let hidden: Vec<u32> = (0..self.hidden_columns.len() as u32)
+            .filter(|&col| self.hidden_columns[col as usize])
+            .collect();
+        w.rows(&hidden);
+        w.f64(self.scroll_top);
+        w.0
+    }
+
+    /// Parse bytes written by `encode` of this or an older version.
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let mut r = Reader::new(bytes);
+        if r.take(4)? != MAGIC {
+            return Err("not a saved table state".to_string());
+        }
+        let version = r.byte()?;
+        if version == 0 || version > VERSION {
+            return Err(format!("unsupported saved state version {version}"));
+        }
+        let mut state = Self::default();
+
+        state.view.sort_configs = r.sort_configs()?;
+        state.view.column_filters = r.column_filters()?;
+        state.view.global_filter = r.opt_str()?.map(|query| GlobalFilter { query });
+
+        state.group_by = (0..r.len()?).map(|_| r.len()).collect::<Result<_, _>>()?;
+        for _ in 0..r.len()? {
+            let path = (0..r.len()?)
+                .map(|_| {
+                    Ok(match r.byte()? {
+                        0 => GroupKey::None,
+                        1 => GroupKey::Number(u64::from_le_bytes(r.array()?)),
+                        2 => GroupKey::Text(r.str()?),
+                        tag => return Err(format!("unknown group key tag {tag}")),
+                    })
+                })
+                .collect::<Result<_, String>>()?;
+            state.collapsed_groups.push(path);
+        }
+
+        state.expanded_rows = r.rows()?;
+        if r.byte()? != 0 {
+            state.row_order = Some(r.rows()?);
+        }
+
+        for _ in 0..r.len()? {
+            let width = if r.byte()? != 0 {
+                Some(f32::from_le_bytes(r.array()?))
+            } else {
+                None
+            };
+            state.column_widths.push(width);
+        }
+        for col in r.rows()? {
+            let col = col as usize;
+            if col >= MAX_COLUMNS {
+                return Err(format!("hidden column {col} out of range"));
+            }
+            if state.hidden_columns.len() <= col {
+                state.hidden_columns.resize(col + 1, false);
+            }
+            state.hidden_columns[col] = true;
+        }
+        state.scroll_top = r.f64()?;
+        Ok(state)
+    }
+
+    /// Check that every column index (sort, filters, grouping, widths, hidden
+    /// columns) is below `columns`, the table's column count.
+    pub fn check_columns(&self, columns: usize) -> Result<(), String> {
+        let view = &self.view;
+        let used = view
+            .sort_configs
+            .iter()
+            .map(|c| c.column_index)
+            .chain(view.column_filters.iter().map(|f| f.column_index))
+            .chain(self.group_by.iter().copied())
+            .chain(self.column_widths.iter().rposition(Option::is_some))
+            .chain(self.hidden_columns.iter().rposition(|&hidden| hidden));
+        match used.max() {
+            Some(col) if col >= columns => Err(format!(
+                "saved state column {col} out of range for {columns} columns"
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub(crate) struct Writer(pub(crate) Vec<u8>);
+
+impl Writer {
+    pub(crate) fn sort_configs(&mut self, sorts: &[SortConfig]) {
+        self.len(sorts.len());
+        for sort in sorts {
+            self.len(sort.column_index);
+            let nulls = match sort.nulls {
+                None => 0,
+                Some(NullOrder::First) => 1,
+                Some(NullOrder::Last) => 2,
+            };
+            self.0.push(
+                u8::from(sort.direction == SortDirection::Descending)
+                    | u8::from(sort.natural) << 1
+                    | nulls << 2,
+            );
+            self.opt_str(sort.expression.as_deref());
+        }
+    }
+
+    pub(crate) fn column_filters(&mut self, filters: &[ColumnFilter]) {
+        self.len(filters.len());
+        for filter in filters {
+            self.len(filter.column_index);
+            let op = FILTER_OPS.iter().position(|&op| op == filter.op);
+            self.0.push(op.unwrap_or(0) as u8);
+            self.0.push(u8::from(filter.formatted));
+            match &filter.value {
+                FilterValue::Float64(v) => {
+                    self.0.push(0);
+                    self.f64(*v);
+                }
+                FilterValue::String(s) => {
+                    self.0.push(1);
+                    self.str(s);
+                }
+                FilterValue::Bool(b) => {
+                    self.0.push(2);
+                    self.0.push(u8::from(*b));
+                }
+                FilterValue::StringList(values) => {
+                    self.0.push(3);
+                    self.len(values.len());
+                    for s in values {
+                        self.str(s);
+                    }
+                }
+                FilterValue::RelativeDate(range) => {
+                    self.0.push(4);
+                    self.str(&range.name());
+                }
+            }
+        }
+    }
+
+    pub(crate) fn len(&mut self, mut n: usize) {
+        while n >= 0x80 {
+            self.0.push(n as u8 | 0x80);
+            n >>= 7;
+        }
+        self.0.push(n as u8);
+    }
+
+    pub(crate) fn f64(&mut self, v: f64) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub(crate) fn str(&mut self, s: &str) {
+        self.len(s.len());
+        self.0.extend_from_slice(s.as_bytes());
+    }
+
+    pub(crate) fn opt_str(&mut self, s: Option<&str>) {
+        self.0.push(u8::from(s.is_some()));
+        if let Some(s) = s {
+            self.str(s);
+        }
+    }
+
+    pub(crate) fn rows(&mut self, rows: &[u32]) {
+        self.len(rows.len());
+        for &row in rows {
+            self.len(row as usize);
+        }
+    }
+}
+
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    pub(crate) fn sort_configs(&mut self) -> Result<Vec<SortConfig>, String> {
+        let mut sorts = Vec::new();
+        for _ in 0..self.len()? {
+            let column_index = self.len()?;
+            let flags = self.byte()?;
+            sorts.push(SortConfig {
+                column_index,
+                direction: if flags & 1 == 0 {
+                    SortDirection::Ascending
+                } else {
+                    SortDirection::Descending
+                },
+                natural: flags & 2 != 0,
+                nulls: match flags >> 2 & 3 {
+                    1 => Some(NullOrder::First),
+                    2 => Some(NullOrder::Last),
+                    _ => None,
+                },
+                expression: self.opt_str()?,
+            });
+        }
+        Ok(sorts)
+    }
+
+    pub(crate) fn column_filters(&mut self) -> Result<Vec<ColumnFilter>, String> {
+        let mut filters = Vec::new();
+        for _ in 0..self.len()? {
+            let column_index = self.len()?;
+            let op = *FILTER_OPS
+                .get(self.byte()? as usize)
+                .ok_or("unknown filter operator")?;
+            let formatted = self.byte()? != 0;
+            let value = match self.byte()? {
+                0 => FilterValue::Float64(self.f64()?),
+                1 => FilterValue::String(self.str()?),
+                2 => FilterValue::Bool(self.byte()? != 0),
+                3 => FilterValue::StringList(
+                    (0..self.len()?)
+                        .map(|_| self.str())
+                        .collect::<Result<_, _>>()?,
+                ),
+                4 => FilterValue::RelativeDate(RelativeDate::parse(&self.str()?)?),
+                tag => return Err(format!("unknown filter value tag {tag}")),
+            };
+            filters.push(ColumnFilter {
+                column_index,
+                op,
+                value,
+                formatted,
+            });
+        }
+        Ok(filters)
+    }
+
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos.saturating_add(n))
+            .ok_or("unexpected end of data")?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    pub(crate) fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.take(N)?.try_into().expect("slice of N bytes"))
+    }
+
+    pub(crate) fn len(&mut self) -> Result<usize, String> {
+        let mut n = 0_usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let b = self.byte()?;
+            n |= usize::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err("invalid length".to_string())
+    }
+
+    pub(crate) fn f64(&mut self) -> Result<f64, String> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn str(&mut self) -> Result<String, String> {
+        let len = self.len()?;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|e| e.to_string())
+    }
+
+    pub(crate) fn opt_str(&mut self) -> Result<Option<String>, String> {
+        if self.byte()? == 0 {
+            Ok(None)
+        } else {
+            self.str().map(Some)
+        }
+    }
+
+    pub(crate) fn rows(&mut self) -> Result<Vec<u32>, String> {
+        (0..self.len()?)
+            .map(|_| {
+                let row = self.len()?;
+                u32::try_from(row).map_err(|e| e.to_string())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> SavedState {
+        SavedState {
+            view: ViewPreset {
+                column_filters: vec![
+                    ColumnFilter::new(2, FilterOp::Gte, FilterValue::Float64(-1.5)),
+                    ColumnFilter::new(
+                        300,
+                        FilterOp::ContainsAny,
+                        FilterValue::StringList(vec!["a".to_string(), "비".to_string()]),
+                    ),
+                    ColumnFilter {
+                        column_index: 0,
+                        op: FilterOp::Within,
+                        value: FilterValue::RelativeDate(RelativeDate::LastDays(7)),
+                        formatted: true,
+                    },
+                ],
+                global_filter: Some(GlobalFilter {
+                    query: "needle".to_string(),
+                }),
+                sort_configs: vec![SortConfig {
+                    column_index: 1,
+                    direction: SortDirection::Descending,
+                    natural: true,
+                    nulls: Some(NullOrder::Last),
+                    expression: Some("$1 * 2".to_string()),
+                }],
+            },
+            group_by: vec![3, 1],
+            collapsed_groups: vec![
+                vec![GroupKey::Text("x".to_string())],
+                vec![GroupKey::Number(u64::MAX), GroupKey::None],
+            ],
+            expanded_rows: vec![0, 200_000],
+            row_order: Some(vec![2, 0, 1]),
+            column_widths: vec![None, Some(120.5)],
+            hidden_columns: vec![false, true, false, true],
+            scroll_top: 840.0,
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let state = state();
+        let bytes = state.encode();
+        assert_eq!(&bytes[..5], b"RWTS\x01");
+        assert_eq!(SavedState::decode(&bytes).unwrap(), state);
+
+        let empty = SavedState::default();
+        assert_eq!(SavedState::decode(&empty.encode()).unwrap(), empty);
+    }
+
+    #[test]
+    fn rejects_foreign_truncated_and_newer_bytes() {
+        let bytes = state().encode();
+        assert!(SavedState::decode(b"{}").is_err());
+        assert!(SavedState::decode(&bytes[..bytes.len() - 1]).is_err());
+        let mut newer = bytes;
+        newer[4] = VERSION + 1;
+        assert!(SavedState::decode(&newer).is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_range_columns() {
+        let mut w = Writer(MAGIC.to_vec());
+        w.0.push(VERSION);
+        w.sort_configs(&[]);
+        w.column_filters(&[]);
+        w.opt_str(None);
+        w.len(0);
+        w.len(0);
+        w.rows(&[]);
+        w.0.push(0);
+        w.len(0);
+        w.rows(&[u32::MAX]);
+        w.f64(0.0);
+        assert!(SavedState::decode(&w.0).is_err());
+
+        // The filter on column 300 is the highest index
+        let state = state();
+        assert!(state.check_columns(301).is_ok());
+        assert!(state.check_columns(300).is_err());
+        let mut state = SavedState {
+            hidden_columns: vec![false, true, false, false],
+            ..SavedState::default()
+        };
+        assert!(state.check_columns(2).is_ok());
+        state.group_by = vec![2];
+        assert!(state.check_columns(2).is_err());
+    }
+}
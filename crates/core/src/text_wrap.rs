@@ -0,0 +1,182 @@
+//! Character-count text wrapping, used to estimate cell heights without a font,
+//! line breaking for drawing wrapped text, and truncation of cell text to a
+//! width with an ellipsis.
+
+use std::ops::Range;
+
+/// Number of lines `text` occupies when greedily word-wrapped to `max_chars` per
+/// line. Explicit newlines start a new line; words longer than a line are broken.
+/// Empty text is one line.
+pub fn wrapped_line_count(text: &str, max_chars: usize) -> usize {
+    let max_chars = max_chars.max(1);
+    text.split('\n')
+        .map(|paragraph| {
+            let mut lines = 1;
+            let mut used = 0;
+            for word in paragraph.split_whitespace() {
+                let mut len = word.chars().count();
+                let needed = if used == 0 { len } else { used + 1 + len };
+                if needed <= max_chars {
+                    used = needed;
+                    continue;
+                }
+                if used > 0 {
+                    lines += 1;
+                }
+                while len > max_chars {
+                    lines += 1;
+                    len -= max_chars;
+                }
+                used = len;
+            }
+            lines
+        })
+        .sum()
+}
+
+/// Break `text` into lines greedily at whitespace, appending each line's byte
+/// range (without the whitespace around it) to `out`.
+///
+/// `fits(line)` says whether a line fits the width, e.g. by character count as
+/// in `wrapped_line_count`, which gives the same number of lines for
+/// single-spaced text. Explicit newlines start a new line; words too long for a
+/// line are broken between characters. Empty text is one empty line.
+pub fn wrap_lines(text: &str, mut fits: impl FnMut(&str) -> bool, out: &mut Vec<Range<usize>>) {
+    let mut base = 0;
+    for paragraph in text.split('\n') {
+        let mut line: Option<Range<usize>> = None;
+        let words = paragraph
+            .split(char::is_whitespace)
+            .filter(|word| !word.is_empty());
+        for word in words {
+            let start = base + (word.as_ptr() as usize - paragraph.as_ptr() as usize);
+            let end = start + word.len();
+            if let Some(current) = line.take() {
+                if fits(&text[current.start..end]) {
+                    line = Some(current.start..end);
+                    continue;
+                }
+                out.push(current);
+            }
+            let mut start = start;
+            while !fits(&text[start..end]) {
+                let cut = longest_fit(text, start..end, &mut fits);
+                out.push(start..cut);
+                start = cut;
+            }
+            line = Some(start..end);
+        }
+        out.push(line.unwrap_or(base..base));
+        base += paragraph.len() + 1;
+    }
+}
+
+/// End of the longest prefix of `text[range]` that fits, at least one character.
+fn longest_fit(text: &str, range: Range<usize>, fits: &mut impl FnMut(&str) -> bool) -> usize {
+    let ends: Vec<usize> = text[range.clone()]
+        .char_indices()
+        .skip(1)
+        .map(|(i, _)| range.start + i)
+        .chain([range.end])
+        .collect();
+    // `ends[..fit]` all fit; the first end always counts
+    let (mut fit, mut rest) = (1, ends.len());
+    while fit < rest {
+        let mid = (fit + rest).div_ceil(2);
+        if fits(&text[range.start..ends[mid - 1]]) {
+            fit = mid;
+        } else {
+            rest = mid - 1;
+        }
+    }
+    ends[fit - 1]
+}
+
+/// Where to cut `text` to draw it in `max_width` px followed by an ellipsis of
+/// `ellipsis_width` px.
+///
+/// `measure(text)` gives widths (e.g. a character count times an average glyph
+/// width). `None` when the text, without trailing whitespace, fits or is empty.
+/// Otherwise the UTF-16 length (as JS strings are indexed) of the longest prefix
+/// that fits with the ellipsis, never inside a character and without trailing
+/// whitespace; 0 when only the ellipsis fits, if that.
+pub fn truncate_at(
+    text: &str,
+    max_width: f64,
+    ellipsis_width: f64,
+    mut measure: impl FnMut(&str) -> f64,
+) -> Option<usize> {
+    let trimmed = text.trim_end();
+    if trimmed.is_empty() || measure(trimmed) <= max_width {
+        return None;
+    }
+    let ends: Vec<usize> = text.char_indices().skip(1).map(|(i, _)| i).collect();
+    // Prefixes grow wider with their length, so the longest fitting one is found
+    // by bisection: `ends[..fit]` all fit
+    let (mut fit, mut rest) = (0, ends.len());
+    while fit < rest {
+        let mid = (fit + rest).div_ceil(2);
+        if measure(text[..ends[mid - 1]].trim_end()) + ellipsis_width <= max_width {
+            fit = mid;
+        } else {
+            rest = mid - 1;
+        }
+    }
+    let end = if fit == 0 { 0 } else { ends[fit - 1] };
+    Some(text[..end].trim_end().encode_utf16().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_on_word_boundaries() {
+        assert_eq!(wrapped_line_count("", 10), 1);
+        assert_eq!(wrapped_line_count("short", 10), 1);
+        assert_eq!(wrapped_line_count("aaaa bbbb cccc", 9), 2);
+        assert_eq!(wrapped_line_count("aaaa bbbb cccc", 4), 3);
+    }
+
+    #[test]
+    fn breaks_long_words_and_newlines() {
+        assert_eq!(wrapped_line_count("abcdefghij", 4), 3);
+        assert_eq!(wrapped_line_count("ab abcdefgh", 4), 3);
+        assert_eq!(wrapped_line_count("one\ntwo\n\nfour", 20), 4);
+    }
+
+    #[test]
+    fn wrap_lines_match_line_count() {
+        fn lines(text: &str, max_chars: usize) -> Vec<&str> {
+            let mut out = Vec::new();
+            wrap_lines(text, |line| line.chars().count() <= max_chars, &mut out);
+            out.into_iter().map(|range| &text[range]).collect()
+        }
+        assert_eq!(lines("aaaa bbbb cccc", 9), vec!["aaaa bbbb", "cccc"]);
+        assert_eq!(lines("ab abcdefgh", 4), vec!["ab", "abcd", "efgh"]);
+        assert_eq!(
+            lines("one\ntwo\n\nfour", 20),
+            vec!["one", "two", "", "four"]
+        );
+        assert_eq!(lines("", 5), vec![""]);
+        for (text, max_chars) in [("aaaa bbbb cccc", 4), ("abcdefghij", 4), ("a b c d", 3)] {
+            assert_eq!(
+                lines(text, max_chars).len(),
+                wrapped_line_count(text, max_chars),
+                "{text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn truncates_to_fit_with_ellipsis() {
+        let chars = |text: &str| text.chars().count() as f64 * 10.0;
+        assert_eq!(truncate_at("hello  ", 50.0, 10.0, chars), None);
+        assert_eq!(truncate_at("hello world", 70.0, 10.0, chars), Some(5));
+        assert_eq!(truncate_at("hello world", 100.0, 10.0, chars), Some(9));
+        assert_eq!(truncate_at("abc", 15.0, 10.0, chars), Some(0));
+        assert_eq!(truncate_at("😀😀😀", 25.0, 10.0, chars), Some(2));
+        assert_eq!(truncate_at("", -4.0, 10.0, chars), None);
+        assert_eq!(truncate_at("a", -4.0, 10.0, chars), Some(0));
+    }
+}
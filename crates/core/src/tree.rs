@@ -0,0 +1,243 @@
+//! Parent-child rows (tree data): flattening of expanded branches and roll-ups.
+
+use crate::aggregate::Accumulator;
+
+/// Parent of a root row.
+pub const NO_PARENT: u32 = u32::MAX;
+
+/// Tree structure over the data rows, with each row's expansion state.
+#[derive(Debug, Clone)]
+pub struct TreeData {
+    /// Parent data row of each row; `NO_PARENT` (or out of range, or itself) = root.
+    parents: Vec<u32>,
+    /// Children of row `r` are `children[child_offsets[r]..child_offsets[r + 1]]`,
+    /// in row order; the roots are the children of the virtual row `parents.len()`.
+    child_offsets: Vec<u32>,
+    children: Vec<u32>,
+    expanded: Vec<bool>,
+}
+
+impl TreeData {
+    /// Build the tree from each row's parent, all rows collapsed.
+    pub fn new(parents: Vec<u32>) -> Self {
+        let n = parents.len();
+        let parent_slot = |row: usize, p: u32| {
+            if (p as usize) < n && p as usize != row {
+                p as usize
+            } else {
+                n
+            }
+        };
+        let mut child_offsets = vec![0_u32; n + 2];
+        for (row, &p) in parents.iter().enumerate() {
+            child_offsets[parent_slot(row, p) + 1] += 1;
+        }
+        for i in 1..child_offsets.len() {
+            child_offsets[i] += child_offsets[i - 1];
+        }
+        let mut next = child_offsets.clone();
+        let mut children = vec![0_u32; n];
+        for (row, &p) in parents.iter().enumerate() {
+            let slot = &mut next[parent_slot(row, p)];
+            children[*slot as usize] = row as u32;
+            *slot += 1;
+        }
+        Self {
+            parents,
+            child_offsets,
+            children,
+            expanded: vec![false; n],
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Parent of `row`, or `None` for roots.
+    pub fn parent(&self, row: usize) -> Option<usize> {
+        let p = *self.parents.get(row)? as usize;
+        (p < self.len() && p != row).then_some(p)
+    }
+
+    /// Children of `row` in row order (the roots for `row == len()`).
+    pub fn children(&self, row: usize) -> &[u32] {
+        match (self.child_offsets.get(row), self.child_offsets.get(row + 1)) {
+            (Some(&start), Some(&end)) => &self.children[start as usize..end as usize],
+            _ => &[],
+        }
+    }
+
+    /// Number of ancestors of `row`.
+    pub fn depth(&self, row: usize) -> usize {
+        let mut depth = 0;
+        let mut current = row;
+        while let Some(parent) = self.parent(current) {
+            depth += 1;
+            current = parent;
+            if depth > self.len() {
+                break; // cycle
+            }
+        }
+        depth
+    }
+
+    pub fn is_expanded(&self, row: usize) -> bool {
+        self.expanded.get(row).copied().unwrap_or(false)
+    }
+
+    pub fn set_expanded(&mut self, row: usize, expanded: bool) {
+        if let Some(e) = self.expanded.get_mut(row) {
+            *e = expanded;
+        }
+    }
+
+    pub fn set_all_expanded(&mut self, expanded: bool) {
+        self.expanded.fill(expanded);
+    }
+
+    /// Expanded data rows, in row order.
+    pub fn expanded_rows(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.expanded.len() as u32).filter(|&row| self.expanded[row as usize])
+    }
+
+    /// Flatten the tree for a view: the rows of `sorted` plus their ancestors, in
+    /// depth-first order with siblings in `sorted` order (added ancestors after
+    /// them, by row), skipping the descendants of collapsed rows.
+    pub fn flatten(&self, sorted: &[u32]) -> Vec<u32> {
+        let n = self.len();
+        let mut rank = vec![usize::MAX; n];
+        for (i, &row) in sorted.iter().enumerate() {
+            if let Some(r) = rank.get_mut(row as usize) {
+                *r = i;
+            }
+        }
+        for &row in sorted {
+            let mut current = row as usize;
+            while let Some(parent) = self.parent(current) {
+                if rank[parent] != usize::MAX {
+                    break;
+                }
+                rank[parent] = n + parent;
+                current = parent;
+            }
+        }
+        let included_children = |row: usize| {
+            let mut kids: Vec<u32> = self
+                .children(row)
+                .iter()
+                .copied()
+                .filter(|&c| rank[c as usize] != usize::MAX)
+                .collect();
+            kids.sort_unstable_by_key(|&c| rank[c as usize]);
+            kids
+        };
+
+        let mut out = Vec::with_capacity(sorted.len());
+        let mut stack = included_children(n);
+        stack.reverse();
+        while let Some(row) = stack.pop() {
+            out.push(row);
+            if self.is_expanded(row as usize) {
+                stack.extend(included_children(row as usize).into_iter().rev());
+            }
+        }
+        out
+    }
+
+    /// Per row, the totals of `values` over all its descendants (empty for leaves).
+    pub fn rollup(&self, values: impl Fn(usize) -> f64) -> Vec<Accumulator> {
+        let n = self.len();
+        // Depth-first preorder from the roots; children come after their parents
+        let mut order = Vec::with_capacity(n);
+        let mut stack: Vec<u32> = self.children(n).to_vec();
+        while let Some(row) = stack.pop() {
+            order.push(row as usize);
+            stack.extend_from_slice(self.children(row as usize));
+        }
+        let mut totals = vec![Accumulator::default(); n];
+        for &row in order.iter().rev() {
+            if let Some(parent) = self.parent(row) {
+                let below = totals[row];
+                totals[parent].push(values(row));
+                totals[parent].merge(&below);
+            }
+        }
+        totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregate::Aggregate;
+
+    /// 0 ─┬─ 1 ─── 3
+    ///    └─ 2
+    /// 4 ─── 5
+    fn sample() -> TreeData {
+        TreeData::new(vec![NO_PARENT, 0, 0, 1, NO_PARENT, 4])
+    }
+
+    #[test]
+    fn children_and_depths() {
+        let tree = sample();
+        assert_eq!(tree.children(6), &[0, 4]);
+        assert_eq!(tree.children(0), &[1, 2]);
+        assert_eq!(tree.children(3), &[] as &[u32]);
+        assert_eq!(
+            (0..6).map(|r| tree.depth(r)).collect::<Vec<_>>(),
+            vec![0, 1, 1, 2, 0, 1]
+        );
+    }
+
+    #[test]
+    fn flatten_shows_expanded_branches() {
+        let mut tree = sample();
+        let all: Vec<u32> = (0..6).collect();
+        assert_eq!(tree.flatten(&all), vec![0, 4]);
+
+        tree.set_expanded(0, true);
+        assert_eq!(tree.flatten(&all), vec![0, 1, 2, 4]);
+        tree.set_all_expanded(true);
+        assert_eq!(tree.flatten(&all), vec![0, 1, 3, 2, 4, 5]);
+
+        // Sibling order follows the sort; filtered-out ancestors stay for context
+        assert_eq!(tree.flatten(&[5, 2, 3]), vec![0, 2, 1, 3, 4, 5]);
+    }
+
+    #[test]
+    fn rollup_covers_descendants() {
+        let tree = sample();
+        let values = [1.0, 2.0, 4.0, 8.0, 16.0, f64::NAN];
+        let totals = tree.rollup(|row| values[row]);
+        assert_eq!(
+            totals
+                .iter()
+                .map(|t| t.value(Aggregate::Count))
+                .collect::<Vec<_>>(),
+            vec![3.0, 1.0, 0.0, 0.0, 1.0, 0.0]
+        );
+        assert_eq!(
+            vec![
+                totals[0].value(Aggregate::Sum),
+                totals[1].value(Aggregate::Sum)
+            ],
+            vec![14.0, 8.0]
+        );
+        assert!(totals[4].value(Aggregate::Sum).is_nan());
+    }
+
+    #[test]
+    fn cycles_do_not_hang() {
+        let mut tree = TreeData::new(vec![1, 0, NO_PARENT]);
+        tree.set_all_expanded(true);
+        // Rows in a cycle never reach a root
+        assert_eq!(tree.flatten(&[0, 1, 2]), vec![2]);
+        assert!(tree.depth(0) > 0);
+    }
+}
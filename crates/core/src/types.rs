@@ -1,20 +1,57 @@
+use crate::aggregate::Aggregate;
+use crate::relative_date::RelativeDate;
+
 /// Sort direction.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum SortDirection {
+    #[default]
     Ascending,
     Descending,
 }
 
+/// Where null cells go in a sort, regardless of direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NullOrder {
+    First,
+    Last,
+}
+
 /// Configuration for a single sort operation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct SortConfig {
     pub column_index: usize,
     pub direction: SortDirection,
+    /// Compare string digit runs by value ("file2" < "file10").
+    pub natural: bool,
+    /// Null placement. `None` treats null as the smallest value, so nulls lead
+    /// ascending sorts and trail descending ones.
+    pub nulls: Option<NullOrder>,
+    /// Sort by a numeric expression over columns (e.g. `"$2 * $5"`, see `expr`)
+    /// instead of `column_index`. Evaluated once per rebuild; unparseable
+    /// expressions sort every row as null.
+    pub expression: Option<String>,
+}
+
+/// Group-aware sort ("sort grouped table by group subtotal").
+///
+/// Rows with equal `group_column` values stay contiguous, groups are ordered by an
+/// aggregate of `aggregate_column` over their visible rows, and rows within a group
+/// by the regular sort keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GroupSort {
+    pub group_column: usize,
+    pub aggregate_column: usize,
+    pub aggregate: Aggregate,
+    pub direction: SortDirection,
 }
 
 // ── Filter types ─────────────────────────────────────────────────────
 
 /// Filter comparison operator.
+///
+/// Value comparisons never match null cells. Null cells are NaN in Float64/Bool
+/// columns and the empty-string sentinel in string columns; use the null-aware
+/// operators (which ignore the filter value) to select them explicitly.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FilterOp {
     Eq,
@@ -26,6 +63,23 @@ pub enum FilterOp {
     Contains,
     StartsWith,
     EndsWith,
+    /// Cell is null.
+    IsNull,
+    /// Cell is not null.
+    IsNotNull,
+    /// Cell is null, or a string containing only whitespace.
+    IsBlank,
+    /// Multi-value cell holds at least one of the listed values (exact match).
+    /// On single-value string columns: the value is one of the list.
+    ContainsAny,
+    /// Multi-value cell holds every listed value (exact match).
+    ContainsAll,
+    /// Timestamp cell falls within a relative date range (e.g. last 7 days).
+    Within,
+    /// Row's result of an expression over columns (the `String` value, see
+    /// `expr`, e.g. `$3 > $2`) is neither 0 nor null. Does not read `column_index`
+    /// except to skip it in that column's facets.
+    Expression,
 }
 
 /// A typed filter value.
@@ -34,18 +88,199 @@ pub enum FilterValue {
     Float64(f64),
     String(String),
     Bool(bool),
+    /// Value list for `ContainsAny` / `ContainsAll`.
+    StringList(Vec<String>),
+    /// Range for `Within`, resolved against the store's reference time.
+    RelativeDate(RelativeDate),
 }
 
 /// Filter on a single column.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ColumnFilter {
     pub column_index: usize,
     pub op: FilterOp,
     pub value: FilterValue,
+    /// Match a string value against the column's registered display format
+    /// (e.g. "Jan" against timestamps formatted as `MMM YYYY`) instead of the raw value.
+    pub formatted: bool,
+}
+
+impl ColumnFilter {
+    /// A filter on the raw values of `column_index`.
+    pub const fn new(column_index: usize, op: FilterOp, value: FilterValue) -> Self {
+        Self {
+            column_index,
+            op,
+            value,
+            formatted: false,
+        }
+    }
 }
 
 /// Global text filter across all string columns.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GlobalFilter {
     pub query: String,
 }
+
+/// When a conditional format applies to a data cell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatCondition {
+    /// The cell's row passes a filter (e.g. `Lt 0`, `Contains "x"`), which may
+    /// test another column to style a cell by its neighbor.
+    Match(ColumnFilter),
+    /// The cell is among the `count` largest values of the column over the
+    /// filtered rows (smallest with `bottom`); `percent` reads `count` as a
+    /// percentage of the non-null values. Ties at the cut-off all match.
+    Top {
+        count: f64,
+        percent: bool,
+        bottom: bool,
+    },
+    /// Color scale: the column's min–max range over the filtered rows split into
+    /// `buckets` equal steps; a cell in step `i` (0 = lowest) gets `style_id + i`.
+    Scale { buckets: u32 },
+}
+
+/// Conditional formatting rule: data cells of `column_index` meeting `condition`
+/// get style `style_id` (0 = none).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConditionalFormat {
+    pub column_index: usize,
+    pub condition: FormatCondition,
+    pub style_id: u32,
+}
+
+/// A named snapshot of the view state: column filters, global filter, and sort.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ViewPreset {
+    pub column_filters: Vec<ColumnFilter>,
+    pub global_filter: Option<GlobalFilter>,
+    pub sort_configs: Vec<SortConfig>,
+}
+
+/// Sort and filter state for a server to evaluate in the remote row model
+/// (`query_descriptor`), with filters ordered by column and an empty global
+/// filter dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryDescriptor {
+    pub view: ViewPreset,
+    /// Canonical encoding of `view`: equal keys mean equal queries.
+    pub key: String,
+}
+
+// ── View stats types ─────────────────────────────────────────────────
+
+/// Which stage of `rebuild_view` a condition belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionKind {
+    /// A column filter with the given operator.
+    Column(FilterOp),
+    /// The global text filter.
+    Global,
+    /// A custom (externally evaluated) predicate.
+    Custom,
+}
+
+/// Evaluation statistics for one filter condition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConditionStats {
+    pub kind: ConditionKind,
+    /// Filtered column; `None` for the global filter.
+    pub column_index: Option<usize>,
+    /// Rows the condition was evaluated on (0 when served from cache).
+    pub rows_scanned: usize,
+    /// Rows that passed the condition.
+    pub rows_passed: usize,
+    /// Evaluation time in microseconds (0 when no clock is configured).
+    pub elapsed_us: f64,
+    /// Result came from the per-condition bitmap cache.
+    pub cached: bool,
+}
+
+/// Progress of a time-sliced view rebuild (`rebuild_view_step`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RebuildProgress {
+    /// The new view is in place (or nothing needed rebuilding).
+    pub done: bool,
+    /// Approximate fraction of the rebuild completed, 0..=1.
+    pub progress: f64,
+    /// A view change since the previous step discarded the rebuild in progress.
+    pub restarted: bool,
+}
+
+/// Statistics for the most recent `rebuild_view`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ViewStats {
+    pub conditions: Vec<ConditionStats>,
+    /// Rows after all filters (before pagination).
+    pub rows_filtered: usize,
+    pub sort_us: f64,
+    pub total_us: f64,
+}
+
+// ── Facet types ──────────────────────────────────────────────────────
+
+/// A distinct column value and the number of rows holding it. `None` = null.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FacetCount {
+    pub value: Option<FilterValue>,
+    pub count: usize,
+}
+
+// ── Scroll types ─────────────────────────────────────────────────────
+
+/// A data row and its distance from the top of the scrolled viewport, used to
+/// keep it on screen at the same place when the view is rebuilt.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollAnchor {
+    pub row: usize,
+    /// Row top minus `scroll_top`, px (negative when partly scrolled out).
+    pub offset: f64,
+}
+
+// ── Row height types ─────────────────────────────────────────────────
+
+/// A column whose text is measured for row auto-height.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoHeightColumn {
+    pub column_index: usize,
+    /// Width available to the text (cell width minus horizontal padding), px.
+    pub text_width: f64,
+}
+
+/// Row spans of a column's cells (`ColumnarStore::set_row_merge`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowMerge {
+    /// Consecutive view rows with equal values share one cell.
+    EqualValues,
+    /// Per data row, the number of view rows its cell spans; rows with 0 are
+    /// covered by a span from above (or get their own cell when none reaches
+    /// them). Rows past the end span 1.
+    Spans(Vec<u32>),
+}
+
+/// Header rows above each group of a view (`ColumnarStore::set_group_headers`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroupHeaders {
+    /// Column whose value changes start a new group; `None` = the `GroupSort`
+    /// group column (no headers without one).
+    pub column: Option<usize>,
+    pub height: f64,
+    /// Also pin the current group's header to the top of the data region.
+    pub sticky: bool,
+}
+
+/// Row auto-height: each row is as tall as its tallest measured cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoHeight {
+    pub columns: Vec<AutoHeightColumn>,
+    pub line_height: f64,
+    /// Vertical padding added to the text height, px.
+    pub padding: f64,
+    /// Rows are never shorter than this, px.
+    pub min_height: f64,
+    /// Average glyph width for the character-count estimate, px. Unused when a
+    /// text measure or text width measure callback is set.
+    pub char_width: f64,
+}
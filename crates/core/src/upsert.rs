@@ -0,0 +1,300 @@
+//! Keyed row upserts for streaming updates.
+//!
+//! Changes are queued and coalesced (the last value per key and column wins)
+//! until applied, typically once per frame. Rows are found by the value of a key
+//! column, and unknown keys append rows.
+
+use std::collections::HashMap;
+
+use crate::columnar_store::{ColumnData, ColumnarStore};
+use crate::editing::{self, CellValue};
+use crate::radix_sort::f64_key;
+
+/// Value of a row's key column.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RowKey {
+    /// Float64 or Bool key, as its order-preserving bits (`-0.0` = `0.0`).
+    Number(u64),
+    Text(String),
+}
+
+impl RowKey {
+    pub fn number(value: f64) -> Self {
+        Self::Number(f64_key(value))
+    }
+
+    /// Key of data row `row` in column `col`; `None` for a missing cell or a
+    /// multi-value column.
+    fn of(store: &ColumnarStore, col: usize, row: usize) -> Option<Self> {
+        match store.data.get(col)? {
+            ColumnData::Float64(v) | ColumnData::Bool(v) => v.get(row).map(|&v| Self::number(v)),
+            ColumnData::Strings { ids, intern } => ids
+                .get(row)
+                .map(|&id| Self::Text(intern.resolve(id).to_string())),
+            ColumnData::StringList { .. } | ColumnData::Float64List { .. } => None,
+        }
+    }
+
+    fn value(&self) -> CellValue {
+        match self {
+            // Invert `f64_key`
+            &Self::Number(bits) => CellValue::Number(f64::from_bits(if bits >> 63 == 1 {
+                bits & !(1 << 63)
+            } else {
+                !bits
+            })),
+            Self::Text(text) => CellValue::Text(text.clone()),
+        }
+    }
+}
+
+/// Pending upserts over a store whose rows are keyed by `key_column`.
+#[derive(Debug, Clone)]
+pub struct UpsertQueue {
+    key_column: usize,
+    /// Queued keys in arrival order, each with its changed columns.
+    pending: Vec<(RowKey, Vec<(usize, CellValue)>)>,
+    slots: HashMap<RowKey, usize>,
+    /// Data row of each key, as of store generation `index_generation`.
+    index: HashMap<RowKey, usize>,
+    index_generation: Option<u64>,
+}
+
+impl UpsertQueue {
+    pub fn new(key_column: usize) -> Self {
+        Self {
+            key_column,
+            pending: Vec::new(),
+            slots: HashMap::new(),
+            index: HashMap::new(),
+            index_generation: None,
+        }
+    }
+
+    pub const fn key_column(&self) -> usize {
+        self.key_column
+    }
+
+    /// Queue `value` for column `col` of the row keyed `key`, replacing a value
+    /// queued earlier for the same cell. Writes to the key column are ignored.
+    pub fn push(&mut self, key: RowKey, col: usize, value: CellValue) {
+        if col == self.key_column {
+            return;
+        }
+        let slot = *self.slots.entry(key.clone()).or_insert_with(|| {
+            self.pending.push((key, Vec::new()));
+            self.pending.len() - 1
+        });
+        let cells = &mut self.pending[slot].1;
+        match cells.iter_mut().find(|(c, _)| *c == col) {
+            Some(cell) => cell.1 = value,
+            None => cells.push((col, value)),
+        }
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Write every queued upsert through the store's cell update path and clear
+    /// the queue. Unknown keys append rows (a full view rebuild); other rows are
+    /// re-positioned only if a changed column filters or sorts the view. Returns
+    /// the `(data row, column)` cells whose value changed.
+    pub fn apply(&mut self, store: &mut ColumnarStore) -> Vec<(usize, usize)> {
+        self.slots.clear();
+        let pending = std::mem::take(&mut self.pending);
+        if pending.is_empty() {
+            return Vec::new();
+        }
+        self.refresh_index(store);
+
+        let rows: Vec<Option<usize>> = pending
+            .iter()
+            .map(|(key, _)| self.row_of(store, key))
+            .collect();
+        let new_keys: Vec<RowKey> = pending
+            .iter()
+            .zip(&rows)
+            .filter(|(_, row)| row.is_none())
+            .map(|((key, _), _)| key.clone())
+            .collect();
+        let mut changed = Vec::new();
+        if !new_keys.is_empty() {
+            let appended = store.append_empty_rows(new_keys.len());
+            for (key, row) in new_keys.into_iter().zip(appended) {
+                editing::write_cell(store, row, self.key_column, key.value());
+                changed.push((row, self.key_column));
+                self.index.insert(key, row);
+            }
+            self.index_generation = Some(store.generation);
+        }
+
+        for ((key, cells), row) in pending.into_iter().zip(rows) {
+            let row = row.unwrap_or_else(|| self.index[&key]);
+            for (col, value) in cells {
+                if editing::write_cell(store, row, col, value).is_some() {
+                    changed.push((row, col));
+                }
+            }
+        }
+        changed
+    }
+
+    /// Insert a row with `cells` right away (not queued), returning its data row.
+    /// The key column must be among `cells` with a key no row holds yet.
+    pub fn insert(
+        &mut self,
+        store: &mut ColumnarStore,
+        cells: Vec<(usize, CellValue)>,
+    ) -> Result<usize, String> {
+        let key = cells
+            .iter()
+            .find(|(col, _)| *col == self.key_column)
+            .and_then(|(_, value)| match value {
+                CellValue::Number(n) if !n.is_nan() => Some(RowKey::number(*n)),
+                CellValue::Text(text) if !text.is_empty() => Some(RowKey::Text(text.clone())),
+                _ => None,
+            })
+            .ok_or("the row has no key")?;
+        if self.row_of(store, &key).is_some() {
+            return Err("a row with this key already exists".to_string());
+        }
+        let row = store.append_empty_rows(1).start;
+        self.index.insert(key, row);
+        self.index_generation = Some(store.generation);
+        for (col, value) in cells {
+            editing::write_cell(store, row, col, value);
+        }
+        Ok(row)
+    }
+
+    /// Data row holding `key`, checked against the store (the key cell may have
+    /// been edited since the index was built). Rows taken out by `remove_row` are
+    /// skipped.
+    pub fn row_of(&mut self, store: &ColumnarStore, key: &RowKey) -> Option<usize> {
+        self.refresh_index(store);
+        let row = *self.index.get(key)?;
+        if RowKey::of(store, self.key_column, row).as_ref() == Some(key)
+            && !store.is_row_removed(row)
+        {
+            return Some(row);
+        }
+        self.index_generation = None;
+        self.refresh_index(store);
+        self.index.get(key).copied()
+    }
+
+    /// Rebuild the key index if the data changed since it was built. The first
+    /// row wins for duplicate keys.
+    fn refresh_index(&mut self, store: &ColumnarStore) {
+        if self.index_generation == Some(store.generation) {
+            return;
+        }
+        self.index.clear();
+        for row in (0..store.row_count).filter(|&row| !store.is_row_removed(row)) {
+            if let Some(key) = RowKey::of(store, self.key_column, row) {
+                self.index.entry(key).or_insert(row);
+            }
+        }
+        self.index_generation = Some(store.generation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ColumnFilter, FilterOp, FilterValue, SortConfig, SortDirection};
+
+    fn store() -> ColumnarStore {
+        let mut store = ColumnarStore::new();
+        store.init(3, 3);
+        let symbols = ["AAPL", "MSFT", "TSLA"].map(String::from);
+        store.set_column_strings(0, &symbols, &[0, 1, 2]);
+        store.set_column_float64(1, &[10.0, 20.0, 30.0]);
+        store.set_column_float64(2, &[1.0, 2.0, 3.0]);
+        store.finalize();
+        store.set_sort(vec![SortConfig {
+            column_index: 1,
+            direction: SortDirection::Descending,
+            ..SortConfig::default()
+        }]);
+        store.rebuild_view();
+        store
+    }
+
+    fn key(symbol: &str) -> RowKey {
+        RowKey::Text(symbol.to_string())
+    }
+
+    #[test]
+    fn coalesces_and_writes_by_key() {
+        let mut store = store();
+        let mut queue = UpsertQueue::new(0);
+        queue.push(key("MSFT"), 2, CellValue::Number(5.0));
+        queue.push(key("MSFT"), 2, CellValue::Number(6.0));
+        queue.push(key("AAPL"), 2, CellValue::Number(1.0));
+        queue.push(key("AAPL"), 0, CellValue::Text("X".to_string()));
+        assert_eq!(queue.apply(&mut store), vec![(1, 2)]);
+        assert!(queue.is_empty());
+        assert_eq!(store.cell_value(2, 1), Some(FilterValue::Float64(6.0)));
+
+        // An unsorted, unfiltered column leaves the view in place
+        assert!(store.view_needs_rebuild());
+        let version = store.view_version();
+        store.rebuild_view();
+        assert!(store.view_version() > version);
+        assert!(store.updated_rows().is_empty());
+        assert_eq!(store.view_indices(), &[2, 1, 0]);
+    }
+
+    #[test]
+    fn repositions_on_sort_keys_and_appends_new_keys() {
+        let mut store = store();
+        store.set_column_filters(vec![ColumnFilter::new(
+            1,
+            FilterOp::Lt,
+            FilterValue::Float64(100.0),
+        )]);
+        store.rebuild_view();
+        let mut queue = UpsertQueue::new(0);
+        queue.push(key("AAPL"), 1, CellValue::Number(25.0));
+        queue.push(key("TSLA"), 1, CellValue::Number(150.0));
+        queue.push(key("NVDA"), 1, CellValue::Number(22.0));
+        let changed = queue.apply(&mut store);
+        assert_eq!(changed, vec![(3, 0), (0, 1), (2, 1), (3, 1)]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 3, 1]);
+
+        queue.push(key("NVDA"), 1, CellValue::Number(5.0));
+        assert_eq!(queue.apply(&mut store), vec![(3, 1)]);
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[0, 1, 3]);
+    }
+
+    #[test]
+    fn inserts_rows_and_skips_removed_keys() {
+        let mut store = store();
+        let mut queue = UpsertQueue::new(0);
+        let cells = |symbol: &str, price| {
+            vec![
+                (0, CellValue::Text(symbol.to_string())),
+                (1, CellValue::Number(price)),
+            ]
+        };
+        assert!(queue.insert(&mut store, cells("MSFT", 1.0)).is_err());
+        assert!(queue
+            .insert(&mut store, vec![(1, CellValue::Number(1.0))])
+            .is_err());
+        assert_eq!(queue.insert(&mut store, cells("NVDA", 25.0)), Ok(3));
+        assert_eq!(queue.row_of(&store, &key("NVDA")), Some(3));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 3, 1, 0]);
+
+        // A removed row's key is free again
+        assert!(store.remove_row(1));
+        assert_eq!(queue.row_of(&store, &key("MSFT")), None);
+        assert_eq!(queue.insert(&mut store, cells("MSFT", 5.0)), Ok(4));
+        store.rebuild_view();
+        assert_eq!(store.view_indices(), &[2, 3, 0, 4]);
+    }
+}
@@ -0,0 +1,196 @@
+//! Column validation rules (`ColumnarStore::set_column_validation`): checked for
+//! every row when data is loaded and for each cell as it is written.
+
+pub use regex_lite::Regex;
+
+use crate::columnar_store::{ColumnData, ColumnarStore};
+
+/// Null cell (or empty string or list) in a `required` column.
+pub const ERROR_REQUIRED: u32 = 1;
+/// Number (or string length) below `min`.
+pub const ERROR_MIN: u32 = 2;
+/// Number (or string length) above `max`.
+pub const ERROR_MAX: u32 = 4;
+/// String not matching `pattern`.
+pub const ERROR_PATTERN: u32 = 8;
+/// Value not among `allowed`.
+pub const ERROR_ENUM: u32 = 16;
+
+/// Rules a column's cells must pass. Rules other than `required` skip nulls;
+/// list cells are checked item by item.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationRules {
+    pub required: bool,
+    /// Bounds of numbers, or of the length in characters of strings.
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// Strings must match it somewhere (anchor with `^...$` for whole strings).
+    pub pattern: Option<Regex>,
+    /// Allowed strings; numbers match the ones that parse to them.
+    pub allowed: Option<Vec<String>>,
+}
+
+impl ValidationRules {
+    /// `ERROR_*` bits of the rules the cell of data row `row`, column `col` fails
+    /// (0 = valid).
+    pub fn check(&self, store: &ColumnarStore, col: usize, row: usize) -> u32 {
+        match store.data.get(col) {
+            Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => match v.get(row) {
+                Some(n) if !n.is_nan() => self.check_number(*n),
+                _ => self.missing(),
+            },
+            Some(ColumnData::Strings { ids, intern }) => match ids.get(row) {
+                Some(&id) if !intern.resolve(id).is_empty() => self.check_text(intern.resolve(id)),
+                _ => self.missing(),
+            },
+            Some(data @ ColumnData::StringList { intern, .. }) => {
+                let items = data.list_ids(row);
+                if items.is_empty() {
+                    return self.missing();
+                }
+                items.iter().fold(0, |errors, &id| {
+                    errors | self.check_text(intern.resolve(id))
+                })
+            }
+            Some(data @ ColumnData::Float64List { .. }) => {
+                let items = data.series(row);
+                if items.is_empty() {
+                    return self.missing();
+                }
+                items
+                    .iter()
+                    .filter(|n| !n.is_nan())
+                    .fold(0, |errors, &n| errors | self.check_number(n))
+            }
+            None => 0,
+        }
+    }
+
+    const fn missing(&self) -> u32 {
+        if self.required {
+            ERROR_REQUIRED
+        } else {
+            0
+        }
+    }
+
+    fn check_number(&self, n: f64) -> u32 {
+        let mut errors = self.bounds(n);
+        if let Some(allowed) = &self.allowed {
+            if !allowed.iter().any(|a| a.trim().parse::<f64>() == Ok(n)) {
+                errors |= ERROR_ENUM;
+            }
+        }
+        errors
+    }
+
+    fn check_text(&self, text: &str) -> u32 {
+        let mut errors = if self.min.is_some() || self.max.is_some() {
+            self.bounds(text.chars().count() as f64)
+        } else {
+            0
+        };
+        if self.pattern.as_ref().is_some_and(|p| !p.is_match(text)) {
+            errors |= ERROR_PATTERN;
+        }
+        if let Some(allowed) = &self.allowed {
+            if !allowed.iter().any(|a| a == text) {
+                errors |= ERROR_ENUM;
+            }
+        }
+        errors
+    }
+
+    fn bounds(&self, n: f64) -> u32 {
+        let mut errors = 0;
+        if self.min.is_some_and(|min| n < min) {
+            errors |= ERROR_MIN;
+        }
+        if self.max.is_some_and(|max| n > max) {
+            errors |= ERROR_MAX;
+        }
+        errors
+    }
+}
+
+/// A column's rules with the `ERROR_*` bits they found, by data row.
+#[derive(Debug, Clone)]
+pub struct ColumnValidation {
+    pub rules: ValidationRules,
+    errors: Vec<u8>,
+}
+
+impl ColumnValidation {
+    pub const fn new(rules: ValidationRules) -> Self {
+        Self {
+            rules,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Check every row of column `col`.
+    pub fn check_all(&mut self, store: &ColumnarStore, col: usize) {
+        self.errors = (0..store.row_count)
+            .map(|row| self.rules.check(store, col, row) as u8)
+            .collect();
+    }
+
+    /// Check data row `row` of column `col` again, e.g. after an edit.
+    pub fn check_row(&mut self, store: &ColumnarStore, col: usize, row: usize) {
+        if self.errors.len() <= row {
+            self.errors.resize(store.row_count.max(row + 1), 0);
+        }
+        self.errors[row] = self.rules.check(store, col, row) as u8;
+    }
+
+    /// `ERROR_*` bits of data row `row` as of the last check.
+    pub fn errors(&self, row: usize) -> u32 {
+        self.errors.get(row).map_or(0, |&e| u32::from(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_report_each_failure() {
+        let mut store = ColumnarStore::new();
+        store.init(3, 4);
+        store.set_column_float64(0, &[5.0, -1.0, f64::NAN, 12.0]);
+        let names = ["ann", "Bob", "", "carol"].map(String::from);
+        store.set_column_strings(1, &names, &[0, 1, 2, 3]);
+        let tags = ["a", "x"].map(String::from);
+        store.set_column_string_list(2, &tags, &[0, 1, 1, 3, 4], &[0, 0, 1, 0]);
+        store.finalize();
+
+        let numbers = ValidationRules {
+            required: true,
+            min: Some(0.0),
+            max: Some(10.0),
+            allowed: Some(vec!["5".to_string(), "12".to_string()]),
+            ..ValidationRules::default()
+        };
+        let checks: Vec<u32> = (0..4).map(|row| numbers.check(&store, 0, row)).collect();
+        assert_eq!(
+            checks,
+            vec![0, ERROR_MIN | ERROR_ENUM, ERROR_REQUIRED, ERROR_MAX]
+        );
+
+        let names = ValidationRules {
+            max: Some(3.0),
+            pattern: Some(Regex::new("^[a-z]+$").unwrap()),
+            ..ValidationRules::default()
+        };
+        let checks: Vec<u32> = (0..4).map(|row| names.check(&store, 1, row)).collect();
+        assert_eq!(checks, vec![0, ERROR_PATTERN, 0, ERROR_MAX]);
+
+        let tags = ValidationRules {
+            required: true,
+            allowed: Some(vec!["a".to_string()]),
+            ..ValidationRules::default()
+        };
+        let checks: Vec<u32> = (0..4).map(|row| tags.check(&store, 2, row)).collect();
+        assert_eq!(checks, vec![0, ERROR_REQUIRED, ERROR_ENUM, 0]);
+    }
+}
@@ -75,6 +75,219 @@ pub fn compute_virtual_slice(state: &ScrollState) -> VirtualSlice {
     }
 }
 
+/// Per-row heights with O(log n) prefix sums (a Fenwick tree), for variable-height
+/// rows. Index = view position.
+#[derive(Debug, Clone)]
+pub struct RowHeightIndex {
+    heights: Vec<f64>,
+    /// 1-based Fenwick tree: `tree[i]` sums `heights[i - lowbit(i)..i]`.
+    tree: Vec<f64>,
+}
+
+impl RowHeightIndex {
+    /// Build from heights in view order, in O(n).
+    pub fn new(heights: Vec<f64>) -> Self {
+        let n = heights.len();
+        let mut tree = vec![0.0; n + 1];
+        for i in 1..=n {
+            tree[i] += heights[i - 1];
+            let parent = i + (i & i.wrapping_neg());
+            if parent <= n {
+                tree[parent] += tree[i];
+            }
+        }
+        Self { heights, tree }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.heights.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.heights.is_empty()
+    }
+
+    pub fn height(&self, row: usize) -> f64 {
+        self.heights[row]
+    }
+
+    /// Change one row's height in O(log n).
+    pub fn set_height(&mut self, row: usize, height: f64) {
+        let delta = height - self.heights[row];
+        self.heights[row] = height;
+        let mut i = row + 1;
+        while i < self.tree.len() {
+            self.tree[i] += delta;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Top of `row`: the sum of the heights before it.
+    pub fn offset(&self, row: usize) -> f64 {
+        let mut sum = 0.0;
+        let mut i = row.min(self.heights.len());
+        while i > 0 {
+            sum += self.tree[i];
+            i &= i - 1;
+        }
+        sum
+    }
+
+    pub fn total(&self) -> f64 {
+        self.offset(self.heights.len())
+    }
+
+    /// Row containing `y` (clamped to the first / last row). Assumes non-negative
+    /// heights.
+    pub fn row_at_offset(&self, y: f64) -> usize {
+        let n = self.heights.len();
+        let mut pos = 0;
+        let mut remaining = y;
+        let mut step = n.checked_next_power_of_two().unwrap_or(0);
+        while step > 0 {
+            let next = pos + step;
+            if next <= n && self.tree[next] <= remaining {
+                pos = next;
+                remaining -= self.tree[next];
+            }
+            step >>= 1;
+        }
+        pos.min(n.saturating_sub(1))
+    }
+}
+
+/// Like `compute_virtual_slice`, with per-row heights from `heights` in place of
+/// `state.row_height`. `scroll_top` is relative to the top of the scrollable
+/// segment (the first row after `pinned_top`).
+pub fn compute_virtual_slice_variable(
+    state: &ScrollState,
+    heights: &RowHeightIndex,
+) -> VirtualSlice {
+    let total_rows = state.total_rows.min(heights.len());
+    let pinned_top = state.pinned_top.unwrap_or(0);
+    let pinned_bottom = state.pinned_bottom.unwrap_or(0);
+    let scrollable_count = total_rows
+        .saturating_sub(pinned_top)
+        .saturating_sub(pinned_bottom);
+    let total_height = heights.offset(total_rows);
+
+    if scrollable_count == 0 {
+        return VirtualSlice {
+            start_index: pinned_top.min(total_rows),
+            end_index: pinned_top.min(total_rows),
+            total_height,
+            visible_count: 0,
+            scrollable_count,
+        };
+    }
+
+    let middle_end = pinned_top + scrollable_count;
+    let base = heights.offset(pinned_top);
+    let first_visible = heights
+        .row_at_offset(base + state.scroll_top.max(0.0))
+        .clamp(pinned_top, middle_end - 1);
+    let bottom = base + state.scroll_top.max(0.0) + state.viewport_height;
+    let mut last_visible = first_visible;
+    while last_visible + 1 < middle_end && heights.offset(last_visible + 1) < bottom {
+        last_visible += 1;
+    }
+    let visible_count = last_visible + 1 - first_visible;
+
+    VirtualSlice {
+        start_index: first_visible.saturating_sub(state.overscan).max(pinned_top),
+        end_index: (last_visible + 1 + state.overscan).min(middle_end),
+        total_height,
+        visible_count,
+        scrollable_count,
+    }
+}
+
+/// Where a row should land in the viewport when scrolling to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScrollAlign {
+    Start,
+    Center,
+    End,
+    /// Scroll as little as possible; keep the current position if the row is
+    /// already fully visible.
+    #[default]
+    Nearest,
+}
+
+/// `scroll_top` that brings view row `view_index` into view, clamped to the
+/// scrollable range. Uses `heights` when given, else `state.row_height`.
+///
+/// Matches the two layouts of the hot path: without pinned rows the header
+/// (`header_height`) scrolls with the rows unless `sticky_header`, where it covers
+/// the top of the viewport; with pinned rows, `scroll_top` is relative to the
+/// scrollable segment, which the fixed header and pinned rows shorten. Pinned
+/// rows are always visible, so they leave `scroll_top` unchanged.
+pub fn scroll_top_for_row(
+    state: &ScrollState,
+    heights: Option<&RowHeightIndex>,
+    header_height: f64,
+    sticky_header: bool,
+    view_index: usize,
+    align: ScrollAlign,
+) -> f64 {
+    let total = heights.map_or(state.total_rows, |h| h.len().min(state.total_rows));
+    let offset = |row: usize| heights.map_or(row as f64 * state.row_height, |h| h.offset(row));
+    let pinned_top = state.pinned_top.unwrap_or(0).min(total);
+    let pinned_bottom = state.pinned_bottom.unwrap_or(0).min(total - pinned_top);
+    let middle_end = total - pinned_bottom;
+    if view_index < pinned_top || view_index >= middle_end {
+        return state.scroll_top;
+    }
+
+    // Scroll-space origin of row 0 and the height of the band rows scroll through
+    let (origin, band) = if pinned_top > 0 || pinned_bottom > 0 {
+        let pinned_height = offset(pinned_top) + offset(total) - offset(middle_end);
+        (
+            offset(pinned_top),
+            state.viewport_height - header_height - pinned_height,
+        )
+    } else if sticky_header {
+        (0.0, state.viewport_height - header_height)
+    } else {
+        (-header_height, state.viewport_height)
+    };
+    let top = offset(view_index) - origin;
+    let bottom = offset(view_index + 1) - origin;
+    let max_scroll = (offset(middle_end) - origin - band).max(0.0);
+
+    let target = match align {
+        ScrollAlign::Start => top,
+        ScrollAlign::End => bottom - band,
+        ScrollAlign::Center => (top + bottom - band) / 2.0,
+        ScrollAlign::Nearest => {
+            if top < state.scroll_top {
+                top
+            } else if bottom > state.scroll_top + band {
+                (bottom - band).min(top)
+            } else {
+                state.scroll_top
+            }
+        }
+    };
+    target.clamp(0.0, max_scroll)
+}
+
+/// Rows of `next` that were not rendered in `prev`, as a leading and a trailing
+/// range (either may be empty). When the ranges do not overlap, all of `next` is
+/// returned as the leading range.
+pub fn exposed_rows(
+    prev: std::ops::Range<usize>,
+    next: std::ops::Range<usize>,
+) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+    if prev.end <= next.start || next.end <= prev.start || prev.is_empty() {
+        return (next.clone(), next.end..next.end);
+    }
+    (
+        next.start..prev.start.max(next.start),
+        prev.end.min(next.end)..next.end,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -95,7 +308,7 @@ mod tests {
 
         assert_eq!(slice.start_index, 0);
         assert_eq!(slice.end_index, 15); // 10 visible + 5 overscan
-        assert_eq!(slice.total_height, 40000.0);
+        assert!((slice.total_height - 40000.0).abs() < f64::EPSILON);
         assert_eq!(slice.visible_count, 10);
         assert_eq!(slice.scrollable_count, 1000);
     }
@@ -152,7 +365,7 @@ mod tests {
 
         assert_eq!(slice.start_index, 0);
         assert_eq!(slice.end_index, 0);
-        assert_eq!(slice.total_height, 0.0);
+        assert!((slice.total_height - 0.0).abs() < f64::EPSILON);
         assert_eq!(slice.visible_count, 0);
         assert_eq!(slice.scrollable_count, 0);
     }
@@ -173,7 +386,7 @@ mod tests {
 
         assert_eq!(slice.start_index, 0);
         assert_eq!(slice.end_index, 3);
-        assert_eq!(slice.total_height, 120.0);
+        assert!((slice.total_height - 120.0).abs() < f64::EPSILON);
     }
 
     #[test]
@@ -194,4 +407,168 @@ mod tests {
         assert_eq!(slice.start_index, 2); // pinned_top
         assert_eq!(slice.end_index, 9); // 2 + 5 visible + 2 overscan
     }
+
+    fn state(scroll_top: f64, total_rows: usize) -> ScrollState {
+        ScrollState {
+            scroll_top,
+            viewport_height: 100.0,
+            row_height: 0.0,
+            total_rows,
+            overscan: 1,
+            pinned_top: None,
+            pinned_bottom: None,
+        }
+    }
+
+    #[test]
+    fn row_height_index_prefix_sums() {
+        let mut index = RowHeightIndex::new(vec![10.0, 20.0, 30.0, 40.0, 50.0]);
+        assert_eq!(
+            (index.offset(0), index.offset(3), index.total()),
+            (0.0, 60.0, 150.0)
+        );
+        assert_eq!(
+            [0.0, 9.9, 10.0, 59.0, 60.0, 149.0, 500.0].map(|y| index.row_at_offset(y)),
+            [0, 0, 1, 2, 3, 4, 4]
+        );
+
+        index.set_height(1, 5.0);
+        assert_eq!(
+            (index.offset(2), index.total(), index.height(1)),
+            (15.0, 135.0, 5.0)
+        );
+        assert_eq!(index.row_at_offset(15.0), 2);
+    }
+
+    #[test]
+    fn row_height_index_matches_linear_scan() {
+        let heights: Vec<f64> = (0..1000).map(|i| f64::from(20 + (i * 37) % 61)).collect();
+        let index = RowHeightIndex::new(heights.clone());
+        let mut top = 0.0;
+        for (row, &h) in heights.iter().enumerate() {
+            assert!((index.offset(row) - top).abs() < 1e-6);
+            assert_eq!(index.row_at_offset(top + h / 2.0), row);
+            top += h;
+        }
+    }
+
+    #[test]
+    fn variable_slice_covers_viewport() {
+        // 10 rows: five 10px, then five 100px
+        let heights =
+            RowHeightIndex::new((0..10).map(|i| if i < 5 { 10.0 } else { 100.0 }).collect());
+        let slice = compute_virtual_slice_variable(&state(0.0, 10), &heights);
+        // Rows 0..=5 intersect [0, 100)
+        assert_eq!(
+            (slice.start_index, slice.end_index, slice.visible_count),
+            (0, 7, 6)
+        );
+        assert!((slice.total_height - 550.0).abs() < f64::EPSILON);
+
+        let slice = compute_virtual_slice_variable(&state(260.0, 10), &heights);
+        // y 260..360 → rows 7 (250..350) and 8 (350..450)
+        assert_eq!(
+            (slice.start_index, slice.end_index, slice.visible_count),
+            (6, 10, 2)
+        );
+    }
+
+    #[test]
+    fn variable_slice_with_pinned_rows() {
+        let heights = RowHeightIndex::new(vec![30.0; 20]);
+        let slice = compute_virtual_slice_variable(
+            &ScrollState {
+                pinned_top: Some(2),
+                pinned_bottom: Some(3),
+                ..state(0.0, 20)
+            },
+            &heights,
+        );
+        // Middle starts at row 2; 100px shows rows 2..=5
+        assert_eq!(
+            (slice.start_index, slice.end_index, slice.scrollable_count),
+            (2, 7, 15)
+        );
+    }
+
+    #[test]
+    fn scroll_top_for_row_alignments() {
+        // 100 rows × 20px, 100px viewport, 40px header scrolling with the rows
+        let state = ScrollState {
+            viewport_height: 100.0,
+            row_height: 20.0,
+            total_rows: 100,
+            ..state(300.0, 100)
+        };
+        let to = |row, align| scroll_top_for_row(&state, None, 40.0, false, row, align);
+        assert_eq!(
+            (
+                to(10, ScrollAlign::Start),
+                to(10, ScrollAlign::End),
+                to(10, ScrollAlign::Center)
+            ),
+            (240.0, 160.0, 200.0)
+        );
+        // Row 14 (320..340 in scroll space) is visible at 300: no change
+        assert_eq!(
+            (
+                to(14, ScrollAlign::Nearest),
+                to(20, ScrollAlign::Nearest),
+                to(2, ScrollAlign::Nearest)
+            ),
+            (300.0, 360.0, 80.0)
+        );
+        // Clamped to the scrollable range
+        assert_eq!(
+            (to(0, ScrollAlign::End), to(99, ScrollAlign::Start)),
+            (0.0, 1940.0)
+        );
+
+        // A sticky header covers the top 40px: rows land below it, and row 14
+        // (280..300) is hidden under it at 300
+        let to = |row, align| scroll_top_for_row(&state, None, 40.0, true, row, align);
+        assert_eq!(
+            (
+                to(10, ScrollAlign::Start),
+                to(10, ScrollAlign::End),
+                to(14, ScrollAlign::Nearest),
+                to(99, ScrollAlign::Start)
+            ),
+            (200.0, 160.0, 280.0, 1940.0)
+        );
+    }
+
+    #[test]
+    fn scroll_top_for_row_with_pins_and_heights() {
+        let heights = RowHeightIndex::new((0..20).map(|i| f64::from(10 + i)).collect());
+        let state = ScrollState {
+            viewport_height: 200.0,
+            pinned_top: Some(2),
+            pinned_bottom: Some(1),
+            ..state(0.0, 20)
+        };
+        let to = |row, align| scroll_top_for_row(&state, Some(&heights), 30.0, false, row, align);
+        // Middle starts at row 2 (offset 21); row 5 starts at offset 60
+        // Band = 200 − 30 header − 21 top − 29 bottom = 120; row 10 ends at 165
+        // Pinned rows never scroll
+        assert_eq!(
+            (
+                to(5, ScrollAlign::Start),
+                to(10, ScrollAlign::End),
+                to(0, ScrollAlign::Start),
+                to(19, ScrollAlign::Start)
+            ),
+            (39.0, 165.0 - 21.0 - 120.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn exposed_rows_after_scroll() {
+        assert_eq!(exposed_rows(10..30, 14..34), (14..14, 30..34));
+        assert_eq!(exposed_rows(10..30, 5..25), (5..10, 25..25));
+        assert_eq!(exposed_rows(10..30, 10..30), (10..10, 30..30));
+        assert_eq!(exposed_rows(10..30, 8..32), (8..10, 30..32));
+        assert_eq!(exposed_rows(10..30, 40..60), (40..60, 60..60));
+        assert_eq!(exposed_rows(0..0, 0..20), (0..20, 20..20));
+    }
 }
@@ -0,0 +1,125 @@
+//! Heatmap colors and data bars: numeric cells placed in their column's range.
+
+/// An RGBA color, one byte per channel.
+pub type Rgba = [u8; 4];
+
+/// How a column's cells are visualized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VisualKind {
+    /// Background color interpolated between evenly spaced color stops, low to
+    /// high.
+    ColorScale(Vec<Rgba>),
+    /// Bar filling the given fraction of the cell.
+    DataBar,
+}
+
+/// Data visualization of a numeric column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataVisualization {
+    pub kind: VisualKind,
+    /// Fixed `(min, max)` range; `None` = the column's range over the filtered
+    /// rows.
+    pub domain: Option<(f64, f64)>,
+}
+
+impl DataVisualization {
+    /// Fraction of the way `value` lies through `range` (the domain, if set),
+    /// clamped to 0..=1. NaN for nulls; 1 when the range is a single value.
+    pub fn normalize(&self, value: f64, range: (f64, f64)) -> f64 {
+        let (lo, hi) = self.domain.unwrap_or(range);
+        if value.is_nan() || lo.is_nan() || lo > hi {
+            f64::NAN
+        } else if hi > lo {
+            ((value - lo) / (hi - lo)).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// Packed `0xRRGGBBAA` color of fraction `t` on a color scale; 0 for NaN and
+    /// data bars.
+    pub fn color(&self, t: f64) -> u32 {
+        let VisualKind::ColorScale(stops) = &self.kind else {
+            return 0;
+        };
+        if t.is_nan() || stops.is_empty() {
+            return 0;
+        }
+        let scaled = t * (stops.len() - 1) as f64;
+        let low = (scaled.floor() as usize).min(stops.len() - 1);
+        let high = (low + 1).min(stops.len() - 1);
+        let frac = scaled - low as f64;
+        let channels: [u8; 4] = std::array::from_fn(|c| {
+            let (from, to) = (f64::from(stops[low][c]), f64::from(stops[high][c]));
+            (to - from).mul_add(frac, from).round() as u8
+        });
+        u32::from_be_bytes(channels)
+    }
+}
+
+/// Parse a CSS hex color: `#rgb`, `#rgba`, `#rrggbb`, or `#rrggbbaa`.
+pub fn parse_color(s: &str) -> Result<Rgba, String> {
+    let err = || format!("invalid color: {s:?}");
+    let hex = s.strip_prefix('#').ok_or_else(err)?;
+    let digit = |i: usize| {
+        hex.get(i..=i)
+            .and_then(|d| u8::from_str_radix(d, 16).ok())
+            .ok_or_else(err)
+    };
+    let (short, channels) = match hex.len() {
+        3 | 4 => (true, hex.len()),
+        6 | 8 => (false, hex.len() / 2),
+        _ => return Err(err()),
+    };
+    let mut rgba = [255_u8; 4];
+    for (c, channel) in rgba.iter_mut().enumerate().take(channels) {
+        *channel = if short {
+            digit(c)? * 17
+        } else {
+            digit(2 * c)? * 16 + digit(2 * c + 1)?
+        };
+    }
+    Ok(rgba)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_into_range_or_domain() {
+        let bar = DataVisualization {
+            kind: VisualKind::DataBar,
+            domain: None,
+        };
+        assert!((bar.normalize(15.0, (10.0, 30.0)) - 0.25).abs() < 1e-12);
+        assert!((bar.normalize(99.0, (10.0, 30.0)) - 1.0).abs() < f64::EPSILON);
+        assert!((bar.normalize(5.0, (5.0, 5.0)) - 1.0).abs() < f64::EPSILON);
+        assert!(bar.normalize(f64::NAN, (0.0, 1.0)).is_nan());
+        assert_eq!(bar.color(0.5), 0);
+
+        let fixed = DataVisualization {
+            domain: Some((0.0, 100.0)),
+            ..bar
+        };
+        assert!((fixed.normalize(15.0, (10.0, 30.0)) - 0.15).abs() < 1e-12);
+    }
+
+    #[test]
+    fn color_scale_interpolates_between_stops() {
+        let scale = DataVisualization {
+            kind: VisualKind::ColorScale(vec![
+                parse_color("#f00").unwrap(),
+                parse_color("#ffffff").unwrap(),
+                parse_color("#0000ff80").unwrap(),
+            ]),
+            domain: None,
+        };
+        assert_eq!(scale.color(0.0), 0xFF00_00FF);
+        assert_eq!(scale.color(0.25), 0xFF80_80FF);
+        assert_eq!(scale.color(1.0), 0x0000_FF80);
+        assert_eq!(scale.color(f64::NAN), 0);
+        assert!(parse_color("red").is_err());
+        assert!(parse_color("#12345").is_err());
+    }
+}
@@ -0,0 +1,168 @@
+//! Window functions over the sorted view (running totals, ranks, moving averages),
+//! computed on view rebuild for derived columns.
+
+use crate::aggregate::{Accumulator, Aggregate};
+
+/// Window function over a numeric column in view order. Null (NaN) cells are
+/// skipped like in `Aggregate`; their own result is NaN except for running values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowFn {
+    /// `Aggregate` of the rows up to and including each row.
+    Running(Aggregate),
+    /// 1-based position in the view; runs of equal values share the rank of the
+    /// first of them (gaps after ties).
+    Rank,
+    /// Value as a percentage of the column total over the view.
+    PercentOfTotal,
+    /// Mean over the row and the `n - 1` rows before it.
+    MovingAverage(usize),
+}
+
+impl WindowFn {
+    /// Parse a window function name: `runningSum`, `runningMean` (or `runningAvg`),
+    /// `runningMin`, `runningMax`, `runningCount`, `rank`, `percentOfTotal`, or
+    /// `movingAverage` (over `size` rows).
+    pub fn parse(s: &str, size: Option<usize>) -> Result<Self, String> {
+        Ok(match s {
+            "rank" => Self::Rank,
+            "percentOfTotal" => Self::PercentOfTotal,
+            "movingAverage" => match size {
+                Some(n) if n > 0 => Self::MovingAverage(n),
+                _ => return Err("movingAverage needs a window size of at least 1".into()),
+            },
+            _ => {
+                let aggregate = s
+                    .strip_prefix("running")
+                    .and_then(|name| Aggregate::parse(&name.to_ascii_lowercase()).ok())
+                    .ok_or_else(|| format!("unknown window function: {s:?}"))?;
+                Self::Running(aggregate)
+            }
+        })
+    }
+
+    /// The function over `values` (a column in view order), per view position.
+    pub fn compute(self, values: &[f64]) -> Vec<f64> {
+        match self {
+            Self::Running(aggregate) => {
+                let mut acc = Accumulator::default();
+                values
+                    .iter()
+                    .map(|&v| {
+                        acc.push(v);
+                        acc.value(aggregate)
+                    })
+                    .collect()
+            }
+            Self::Rank => {
+                let mut rank = 0;
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &v)| {
+                        if v.is_nan() {
+                            return f64::NAN;
+                        }
+                        if i == 0 || values[i - 1].total_cmp(&v).is_ne() {
+                            rank = i + 1;
+                        }
+                        rank as f64
+                    })
+                    .collect()
+            }
+            Self::PercentOfTotal => {
+                let total: f64 = values.iter().filter(|v| !v.is_nan()).sum();
+                values.iter().map(|&v| v / total * 100.0).collect()
+            }
+            Self::MovingAverage(n) => {
+                // Running sum and count of the non-null values in the window
+                let (mut sum, mut count) = (0.0, 0_usize);
+                (0..values.len())
+                    .map(|i| {
+                        if !values[i].is_nan() {
+                            sum += values[i];
+                            count += 1;
+                        }
+                        if let Some(&out) = i.checked_sub(n).map(|j| &values[j]) {
+                            if !out.is_nan() {
+                                sum -= out;
+                                count -= 1;
+                            }
+                        }
+                        if values[i].is_nan() || count == 0 {
+                            f64::NAN
+                        } else {
+                            sum / count as f64
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALUES: [f64; 5] = [10.0, 20.0, 20.0, f64::NAN, 50.0];
+
+    fn compute(function: WindowFn) -> Vec<String> {
+        function
+            .compute(&VALUES)
+            .iter()
+            .map(|v| format!("{v:.1}"))
+            .collect()
+    }
+
+    #[test]
+    fn parse_names() {
+        assert_eq!(
+            WindowFn::parse("runningSum", None),
+            Ok(WindowFn::Running(Aggregate::Sum))
+        );
+        assert_eq!(
+            WindowFn::parse("runningAvg", None),
+            Ok(WindowFn::Running(Aggregate::Mean))
+        );
+        assert_eq!(
+            WindowFn::parse("movingAverage", Some(3)),
+            Ok(WindowFn::MovingAverage(3))
+        );
+        assert!(WindowFn::parse("movingAverage", None).is_err());
+        assert!(WindowFn::parse("running", None).is_err());
+        assert!(WindowFn::parse("median", None).is_err());
+    }
+
+    #[test]
+    fn running_values_skip_nulls() {
+        assert_eq!(
+            compute(WindowFn::Running(Aggregate::Sum)),
+            ["10.0", "30.0", "50.0", "50.0", "100.0"]
+        );
+        assert_eq!(
+            compute(WindowFn::Running(Aggregate::Count)),
+            ["1.0", "2.0", "3.0", "4.0", "5.0"]
+        );
+    }
+
+    #[test]
+    fn rank_shares_ties() {
+        assert_eq!(compute(WindowFn::Rank), ["1.0", "2.0", "2.0", "NaN", "5.0"]);
+    }
+
+    #[test]
+    fn percent_and_moving_average() {
+        assert_eq!(
+            compute(WindowFn::PercentOfTotal),
+            ["10.0", "20.0", "20.0", "NaN", "50.0"]
+        );
+        assert_eq!(
+            compute(WindowFn::MovingAverage(2)),
+            ["10.0", "15.0", "20.0", "NaN", "50.0"]
+        );
+        assert_eq!(
+            compute(WindowFn::MovingAverage(3)),
+            ["10.0", "15.0", "16.7", "NaN", "35.0"]
+        );
+    }
+}
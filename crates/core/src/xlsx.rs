@@ -0,0 +1,669 @@
+//! XLSX export of the view: one worksheet with a header row, cells typed and
+//! number-formatted from the columnar data, column widths, a frozen header, and
+//! row outlines for groups and tree data.
+//!
+//! The workbook is a ZIP of `SpreadsheetML` parts, stored uncompressed.
+
+use std::fmt::Write;
+
+use crate::columnar_store::{ColumnData, ColumnarStore};
+use crate::format::{DateToken, FormatSpec};
+
+/// Days from Excel's epoch (1899-12-30) to the Unix epoch.
+const EXCEL_UNIX_EPOCH_DAYS: f64 = 25_569.0;
+const MS_PER_DAY: f64 = 86_400_000.0;
+/// Width of a digit in Excel's default font, px, for column widths.
+const EXCEL_DIGIT_WIDTH: f64 = 7.0;
+/// Deepest row outline Excel shows.
+const MAX_OUTLINE_LEVEL: usize = 7;
+/// First id of custom number formats.
+const FIRST_CUSTOM_NUM_FMT: usize = 164;
+
+/// A column of the sheet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XlsxColumn {
+    /// Table column.
+    pub col: usize,
+    pub header: String,
+    /// Width in px (0 = Excel's default).
+    pub width: f64,
+}
+
+/// The sheet `write_xlsx` writes.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct XlsxSheet {
+    /// Sheet name (`Sheet1` when empty; cut to Excel's 31 characters).
+    pub name: String,
+    pub columns: Vec<XlsxColumn>,
+    /// Freeze the header row.
+    pub freeze_header: bool,
+    /// Columns frozen at the left (e.g. the left-pinned ones).
+    pub frozen_columns: usize,
+    /// Outline rows by group and tree depth, so Excel can fold them.
+    pub outline: bool,
+}
+
+/// An XLSX workbook of `sheet` over the view rows of `store` (call after
+/// `rebuild_view`), in view order.
+///
+/// Numbers keep their value with the column's display format as an Excel number
+/// format (dates become Excel dates); compact-formatted numbers and lists are
+/// written as their display text. Group header rows hold their group's value.
+pub fn write_xlsx(store: &ColumnarStore, sheet: &XlsxSheet) -> Vec<u8> {
+    let mut styles = Styles::default();
+    let column_styles: Vec<CellStyle> = sheet
+        .columns
+        .iter()
+        .map(|column| styles.column(store, column.col))
+        .collect();
+    let worksheet = worksheet(store, sheet, &column_styles);
+
+    let name = sheet_name(&sheet.name);
+    let mut workbook = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+        r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+        r#"<sheets><sheet name=""#,
+    ));
+    write_escaped(&name, &mut workbook);
+    workbook.push_str(r#"" sheetId="1" r:id="rId1"/></sheets></workbook>"#);
+
+    let mut zip = ZipWriter::default();
+    zip.add("[Content_Types].xml", CONTENT_TYPES.as_bytes());
+    zip.add("_rels/.rels", ROOT_RELS.as_bytes());
+    zip.add("xl/workbook.xml", workbook.as_bytes());
+    zip.add("xl/_rels/workbook.xml.rels", WORKBOOK_RELS.as_bytes());
+    zip.add("xl/styles.xml", styles.xml().as_bytes());
+    zip.add("xl/worksheets/sheet1.xml", worksheet.as_bytes());
+    zip.finish()
+}
+
+const CONTENT_TYPES: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+    r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+    r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+    r#"<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+    r#"<Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
+    r#"<Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>"#,
+    r#"</Types>"#,
+);
+
+const ROOT_RELS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>"#,
+    r#"</Relationships>"#,
+);
+
+const WORKBOOK_RELS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/>"#,
+    r#"<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>"#,
+    r#"</Relationships>"#,
+);
+
+/// How the cells of a column are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CellStyle {
+    /// Display text as an inline string.
+    Text,
+    /// Number with cell format `xf` (0 = general).
+    Number {
+        xf: usize,
+        date: bool,
+    },
+    Bool,
+}
+
+/// Number formats used by the sheet: cell format `i + 2` has number format
+/// `formats[i]` (0 is the default format, 1 the bold header).
+#[derive(Debug, Default)]
+struct Styles {
+    formats: Vec<String>,
+}
+
+impl Styles {
+    fn column(&mut self, store: &ColumnarStore, col: usize) -> CellStyle {
+        match store.data.get(col) {
+            Some(ColumnData::Float64(_)) => {
+                store
+                    .column_format(col)
+                    .map_or(CellStyle::Number { xf: 0, date: false }, |spec| {
+                        number_format(spec).map_or(CellStyle::Text, |code| CellStyle::Number {
+                            xf: self.format_xf(code),
+                            date: matches!(spec, FormatSpec::Date(_)),
+                        })
+                    })
+            }
+            Some(ColumnData::Bool(_)) => CellStyle::Bool,
+            _ => CellStyle::Text,
+        }
+    }
+
+    /// Cell format of number format `code`, added when new.
+    fn format_xf(&mut self, code: String) -> usize {
+        let i = self
+            .formats
+            .iter()
+            .position(|f| *f == code)
+            .unwrap_or_else(|| {
+                self.formats.push(code);
+                self.formats.len() - 1
+            });
+        i + 2
+    }
+
+    fn xml(&self) -> String {
+        let mut xml = String::from(concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            r#"<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+        ));
+        if !self.formats.is_empty() {
+            let _ = write!(xml, r#"<numFmts count="{}">"#, self.formats.len());
+            for (i, code) in self.formats.iter().enumerate() {
+                let _ = write!(
+                    xml,
+                    r#"<numFmt numFmtId="{}" formatCode=""#,
+                    FIRST_CUSTOM_NUM_FMT + i
+                );
+                write_escaped(code, &mut xml);
+                xml.push_str(r#""/>"#);
+            }
+            xml.push_str("</numFmts>");
+        }
+        xml.push_str(concat!(
+            r#"<fonts count="2"><font><sz val="11"/><name val="Calibri"/></font>"#,
+            r#"<font><b/><sz val="11"/><name val="Calibri"/></font></fonts>"#,
+            r#"<fills count="2"><fill><patternFill patternType="none"/></fill>"#,
+            r#"<fill><patternFill patternType="gray125"/></fill></fills>"#,
+            r#"<borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders>"#,
+            r#"<cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs>"#,
+        ));
+        let _ = write!(
+            xml,
+            concat!(
+                r#"<cellXfs count="{}"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/>"#,
+                r#"<xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/>"#,
+            ),
+            self.formats.len() + 2
+        );
+        for i in 0..self.formats.len() {
+            let _ = write!(
+                xml,
+                r#"<xf numFmtId="{}" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/>"#,
+                FIRST_CUSTOM_NUM_FMT + i
+            );
+        }
+        xml.push_str(concat!(
+            r#"</cellXfs><cellStyles count="1"><cellStyle name="Normal" xfId="0" builtinId="0"/></cellStyles>"#,
+            r#"</styleSheet>"#,
+        ));
+        xml
+    }
+}
+
+/// Excel number format code of a display format; `None` for formats Excel can't
+/// express (compact numbers).
+fn number_format(spec: &FormatSpec) -> Option<String> {
+    match spec {
+        FormatSpec::Compact | FormatSpec::Text { .. } => None,
+        FormatSpec::Number {
+            decimals,
+            grouping,
+            prefix,
+            suffix,
+            ..
+        } => {
+            let mut code = String::new();
+            push_literal(prefix, &mut code);
+            code.push_str(if *grouping { "#,##0" } else { "0" });
+            if *decimals > 0 {
+                code.push('.');
+                code.push_str(&"0".repeat(*decimals));
+            }
+            push_literal(suffix, &mut code);
+            Some(code)
+        }
+        FormatSpec::Date(tokens) => {
+            let mut code = String::new();
+            for token in tokens {
+                match token {
+                    DateToken::Year4 => code.push_str("yyyy"),
+                    DateToken::Year2 => code.push_str("yy"),
+                    DateToken::MonthName => code.push_str("mmmm"),
+                    DateToken::MonthShort => code.push_str("mmm"),
+                    DateToken::Month2 | DateToken::Minute2 => code.push_str("mm"),
+                    DateToken::Month => code.push('m'),
+                    DateToken::Day2 => code.push_str("dd"),
+                    DateToken::Day => code.push('d'),
+                    DateToken::Hour2 => code.push_str("hh"),
+                    DateToken::Hour => code.push('h'),
+                    DateToken::Second2 => code.push_str("ss"),
+                    DateToken::Literal(text) => push_literal(text, &mut code),
+                }
+            }
+            Some(code)
+        }
+    }
+}
+
+/// Append literal text to a number format code, escaped (`%` stays a percent
+/// sign).
+fn push_literal(text: &str, code: &mut String) {
+    for c in text.chars() {
+        if c != '%' {
+            code.push('\\');
+        }
+        code.push(c);
+    }
+}
+
+fn worksheet(store: &ColumnarStore, sheet: &XlsxSheet, styles: &[CellStyle]) -> String {
+    let view = store.view_indices();
+    let group_levels = store.group_by().len();
+    let outline = |pos: usize| {
+        let level = match store.view_group(pos) {
+            Some(group) => group.depth as usize,
+            None if group_levels > 0 => group_levels,
+            None => store.view_depth(pos),
+        };
+        level.min(MAX_OUTLINE_LEVEL)
+    };
+    let max_outline = if sheet.outline {
+        (0..view.len()).map(outline).max().unwrap_or(0)
+    } else {
+        0
+    };
+
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+    ));
+    if max_outline > 0 {
+        // Group headers sit above their rows
+        xml.push_str(r#"<sheetPr><outlinePr summaryBelow="0"/></sheetPr>"#);
+    }
+    let frozen_columns = sheet.frozen_columns.min(sheet.columns.len());
+    let frozen_rows = usize::from(sheet.freeze_header);
+    if frozen_columns > 0 || frozen_rows > 0 {
+        xml.push_str(r#"<sheetViews><sheetView workbookViewId="0"><pane"#);
+        if frozen_columns > 0 {
+            let _ = write!(xml, r#" xSplit="{frozen_columns}""#);
+        }
+        if frozen_rows > 0 {
+            let _ = write!(xml, r#" ySplit="{frozen_rows}""#);
+        }
+        let pane = match (frozen_columns > 0, frozen_rows > 0) {
+            (true, true) => "bottomRight",
+            (true, false) => "topRight",
+            _ => "bottomLeft",
+        };
+        xml.push_str(r#" topLeftCell=""#);
+        write_cell_ref(frozen_rows, frozen_columns, &mut xml);
+        let _ = write!(
+            xml,
+            r#"" activePane="{pane}" state="frozen"/></sheetView></sheetViews>"#
+        );
+    }
+    let _ = write!(
+        xml,
+        r#"<sheetFormatPr defaultRowHeight="15" outlineLevelRow="{max_outline}"/>"#
+    );
+    if sheet.columns.iter().any(|c| c.width > 0.0) {
+        xml.push_str("<cols>");
+        for (i, column) in sheet.columns.iter().enumerate() {
+            if column.width > 0.0 {
+                let width = ((column.width - 5.0) / EXCEL_DIGIT_WIDTH).max(1.0);
+                let _ = write!(
+                    xml,
+                    r#"<col min="{n}" max="{n}" width="{width:.2}" customWidth="1"/>"#,
+                    n = i + 1
+                );
+            }
+        }
+        xml.push_str("</cols>");
+    }
+
+    xml.push_str(r#"<sheetData><row r="1">"#);
+    for (i, column) in sheet.columns.iter().enumerate() {
+        write_inline_string(0, i, 1, &column.header, &mut xml);
+    }
+    xml.push_str("</row>");
+    let mut text = String::new();
+    for (pos, &row) in view.iter().enumerate() {
+        let row = row as usize;
+        let _ = write!(xml, r#"<row r="{}""#, pos + 2);
+        let level = if sheet.outline { outline(pos) } else { 0 };
+        if level > 0 {
+            let _ = write!(xml, r#" outlineLevel="{level}""#);
+        }
+        xml.push('>');
+        // Group header rows show only their group's column
+        let group_col = store
+            .view_group(pos)
+            .map(|group| store.group_by()[group.depth as usize]);
+        for (i, (column, &style)) in sheet.columns.iter().zip(styles).enumerate() {
+            if group_col.is_some_and(|col| col != column.col) {
+                continue;
+            }
+            write_cell(
+                store,
+                row,
+                column.col,
+                style,
+                (pos + 1, i),
+                &mut text,
+                &mut xml,
+            );
+        }
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+/// Write the cell of data row `row`, table column `col` at sheet position `at`
+/// (0-based row and column); nothing for nulls.
+fn write_cell(
+    store: &ColumnarStore,
+    row: usize,
+    col: usize,
+    style: CellStyle,
+    (sheet_row, sheet_col): (usize, usize),
+    text: &mut String,
+    xml: &mut String,
+) {
+    let number = match store.data.get(col) {
+        Some(ColumnData::Float64(v) | ColumnData::Bool(v)) => v[row],
+        _ => f64::NAN,
+    };
+    match style {
+        // Excel has no infinities: they are left out like nulls
+        CellStyle::Number { .. } | CellStyle::Bool if !number.is_finite() => {}
+        CellStyle::Number { xf, date } => {
+            let value = if date {
+                number / MS_PER_DAY + EXCEL_UNIX_EPOCH_DAYS
+            } else {
+                number
+            };
+            xml.push_str("<c r=\"");
+            write_cell_ref(sheet_row, sheet_col, xml);
+            if xf > 0 {
+                let _ = write!(xml, r#"" s="{xf}"#);
+            }
+            let _ = write!(xml, r#""><v>{value}</v></c>"#);
+        }
+        CellStyle::Bool => {
+            xml.push_str("<c r=\"");
+            write_cell_ref(sheet_row, sheet_col, xml);
+            let _ = write!(xml, r#"" t="b"><v>{}</v></c>"#, u8::from(number != 0.0));
+        }
+        CellStyle::Text => {
+            text.clear();
+            store.write_cell_text(col, row, text);
+            if !text.is_empty() {
+                write_inline_string(sheet_row, sheet_col, 0, text, xml);
+            }
+        }
+    }
+}
+
+/// `name` without the characters Excel forbids in sheet names, cut to 31
+/// characters; `Sheet1` when nothing is left.
+fn sheet_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .take(31)
+        .collect();
+    if name.trim().is_empty() {
+        "Sheet1".to_string()
+    } else {
+        name
+    }
+}
+
+fn write_inline_string(row: usize, col: usize, xf: usize, text: &str, xml: &mut String) {
+    xml.push_str("<c r=\"");
+    write_cell_ref(row, col, xml);
+    if xf > 0 {
+        let _ = write!(xml, r#"" s="{xf}"#);
+    }
+    xml.push_str(r#"" t="inlineStr"><is><t xml:space="preserve">"#);
+    write_escaped(text, xml);
+    xml.push_str("</t></is></c>");
+}
+
+/// A1-style reference of 0-based `row`, `col`.
+fn write_cell_ref(row: usize, col: usize, out: &mut String) {
+    let mut letters = Vec::new();
+    let mut n = col + 1;
+    while n > 0 {
+        letters.push(b'A' + ((n - 1) % 26) as u8);
+        n = (n - 1) / 26;
+    }
+    out.extend(letters.iter().rev().map(|&b| b as char));
+    let _ = write!(out, "{}", row + 1);
+}
+
+/// XML text with markup characters escaped and characters XML can't hold dropped.
+fn write_escaped(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c < ' ' => {}
+            c => out.push(c),
+        }
+    }
+}
+
+/// ZIP archive of stored (uncompressed) files.
+#[derive(Debug, Default)]
+struct ZipWriter {
+    bytes: Vec<u8>,
+    central: Vec<u8>,
+    files: u16,
+}
+
+impl ZipWriter {
+    fn add(&mut self, name: &str, data: &[u8]) {
+        let offset = self.bytes.len() as u32;
+        let crc = crc32(data);
+        let size = data.len() as u32;
+        // Local file header
+        self.bytes.extend_from_slice(&0x0403_4b50_u32.to_le_bytes());
+        write_entry_fields(&mut self.bytes, name, crc, size);
+        self.bytes.extend_from_slice(name.as_bytes());
+        self.bytes.extend_from_slice(data);
+        // Central directory header
+        self.central
+            .extend_from_slice(&0x0201_4b50_u32.to_le_bytes());
+        self.central.extend_from_slice(&20_u16.to_le_bytes()); // made by
+        write_entry_fields(&mut self.central, name, crc, size);
+        self.central.extend_from_slice(&[0; 6]); // comment length, disk, internal attributes
+        self.central.extend_from_slice(&0_u32.to_le_bytes()); // external attributes
+        self.central.extend_from_slice(&offset.to_le_bytes());
+        self.central.extend_from_slice(name.as_bytes());
+        self.files += 1;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let offset = self.bytes.len() as u32;
+        let size = self.central.len() as u32;
+        self.bytes.append(&mut self.central);
+        self.bytes.extend_from_slice(&0x0605_4b50_u32.to_le_bytes());
+        self.bytes.extend_from_slice(&[0; 4]); // disk numbers
+        self.bytes.extend_from_slice(&self.files.to_le_bytes());
+        self.bytes.extend_from_slice(&self.files.to_le_bytes());
+        self.bytes.extend_from_slice(&size.to_le_bytes());
+        self.bytes.extend_from_slice(&offset.to_le_bytes());
+        self.bytes.extend_from_slice(&0_u16.to_le_bytes()); // comment length
+        self.bytes
+    }
+}
+
+/// Header fields shared by local and central entries, from "version needed" to
+/// the extra field length.
+fn write_entry_fields(out: &mut Vec<u8>, name: &str, crc: u32, size: u32) {
+    out.extend_from_slice(&20_u16.to_le_bytes()); // version needed
+    out.extend_from_slice(&0x0800_u16.to_le_bytes()); // UTF-8 names
+    out.extend_from_slice(&0_u16.to_le_bytes()); // stored
+    out.extend_from_slice(&0_u16.to_le_bytes()); // time 00:00
+    out.extend_from_slice(&0x21_u16.to_le_bytes()); // date 1980-01-01
+    out.extend_from_slice(&crc.to_le_bytes());
+    out.extend_from_slice(&size.to_le_bytes());
+    out.extend_from_slice(&size.to_le_bytes());
+    out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    out.extend_from_slice(&0_u16.to_le_bytes()); // extra field length
+}
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0_u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0_u32, |crc, &byte| {
+        CRC_TABLE[((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The stored parts of a ZIP written by `ZipWriter`, by name.
+    fn unzip(bytes: &[u8]) -> Vec<(String, String)> {
+        let mut files = Vec::new();
+        let mut at = 0;
+        while bytes[at..].starts_with(&0x0403_4b50_u32.to_le_bytes()) {
+            let field = |offset: usize| {
+                u32::from_le_bytes(bytes[at + offset..at + offset + 4].try_into().unwrap())
+            };
+            let (crc, size) = (field(14), field(18) as usize);
+            let name_len = u16::from_le_bytes([bytes[at + 26], bytes[at + 27]]) as usize;
+            let name = &bytes[at + 30..at + 30 + name_len];
+            let data = &bytes[at + 30 + name_len..at + 30 + name_len + size];
+            assert_eq!(crc32(data), crc);
+            files.push((
+                String::from_utf8(name.to_vec()).unwrap(),
+                String::from_utf8(data.to_vec()).unwrap(),
+            ));
+            at += 30 + name_len + size;
+        }
+        // The end record points at the central directory
+        let end = bytes.len() - 22;
+        let central = u32::from_le_bytes(bytes[end + 16..end + 20].try_into().unwrap()) as usize;
+        assert_eq!(central, at);
+        assert!(bytes[at..].starts_with(&0x0201_4b50_u32.to_le_bytes()));
+        assert_eq!(usize::from(bytes[end + 10]), files.len());
+        files
+    }
+
+    #[test]
+    fn crc32_matches_the_standard_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn number_formats_translate_to_excel_codes() {
+        let code = |spec: &str| number_format(&FormatSpec::parse(spec).unwrap());
+        assert_eq!(code("#,##0.00"), Some("#,##0.00".to_string()));
+        assert_eq!(code("0.0%"), Some("0.0%".to_string()));
+        assert_eq!(code("$0 kg"), Some("\\$0\\ \\k\\g".to_string()));
+        assert_eq!(code("YYYY-MM-DD"), Some("yyyy\\-mm\\-dd".to_string()));
+        assert_eq!(code("compact"), None);
+    }
+
+    #[test]
+    fn sheet_holds_typed_cells_widths_and_frozen_header() {
+        let mut store = ColumnarStore::new();
+        store.init(3, 3);
+        store.set_column_float64(0, &[1.5, f64::NAN, f64::INFINITY]);
+        store.set_column_bool(1, &[1.0, 0.0, 1.0]);
+        store.set_column_strings(2, &[String::new(), "a<b".to_string()], &[1, 0, 0]);
+        store.finalize();
+        store.set_column_format(0, Some(FormatSpec::parse("0.00").unwrap()));
+        store.rebuild_view();
+
+        let column = |col, header: &str, width| XlsxColumn {
+            col,
+            header: header.to_string(),
+            width,
+        };
+        let sheet = XlsxSheet {
+            name: "Q1/Q2".to_string(),
+            columns: vec![
+                column(2, "Name", 75.0),
+                column(0, "Price", 0.0),
+                column(1, "Ok", 0.0),
+            ],
+            freeze_header: true,
+            frozen_columns: 1,
+            outline: true,
+        };
+        let files = unzip(&write_xlsx(&store, &sheet));
+        let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "[Content_Types].xml",
+                "_rels/.rels",
+                "xl/workbook.xml",
+                "xl/_rels/workbook.xml.rels",
+                "xl/styles.xml",
+                "xl/worksheets/sheet1.xml",
+            ]
+        );
+        assert!(files[2].1.contains(r#"<sheet name="Q1Q2""#));
+        assert!(files[4]
+            .1
+            .contains(r#"<numFmt numFmtId="164" formatCode="0.00"/>"#));
+
+        let sheet = &files[5].1;
+        assert!(sheet.contains(
+            r#"<pane xSplit="1" ySplit="1" topLeftCell="B2" activePane="bottomRight" state="frozen"/>"#
+        ));
+        assert!(sheet.contains(r#"<col min="1" max="1" width="10.00" customWidth="1"/>"#));
+        assert!(sheet.contains(
+            r#"<c r="B1" s="1" t="inlineStr"><is><t xml:space="preserve">Price</t></is></c>"#
+        ));
+        assert!(sheet.contains(concat!(
+            r#"<row r="2"><c r="A2" t="inlineStr"><is><t xml:space="preserve">a&lt;b</t></is></c>"#,
+            r#"<c r="B2" s="2"><v>1.5</v></c><c r="C2" t="b"><v>1</v></c></row>"#,
+        )));
+        // Nulls and infinities are left out
+        assert!(sheet.contains(r#"<row r="3"><c r="C3" t="b"><v>0</v></c></row>"#));
+        assert!(sheet.contains(r#"<row r="4"><c r="C4" t="b"><v>1</v></c></row>"#));
+    }
+
+    #[test]
+    fn sheet_names_fall_back_to_sheet1() {
+        assert_eq!(sheet_name(" Q1/Q2 "), "Q1Q2");
+        assert_eq!(sheet_name("[*]"), "Sheet1");
+        assert_eq!(sheet_name(" / ? "), "Sheet1");
+        assert_eq!(sheet_name(&"x".repeat(40)).len(), 31);
+    }
+}
@@ -1,21 +1,344 @@
 /// Conversion helpers: JS bridge types → core layout types.
 use crate::types::{
-    JsColumnLayout, JsContainerLayout, JsDimension, JsFilterValue, JsGridLine, JsGridPlacement,
-    JsGridTrackList, JsGridTrackSize, JsRect,
+    JsAutoHeight, JsCellStyle, JsColumnFilter, JsColumnLayout, JsConditionStats,
+    JsConditionalFormat, JsContainerLayout, JsDataVisualization, JsDimension, JsFacetCount,
+    JsFilterValue, JsGridLine, JsGridPlacement, JsGridTemplateAreas, JsGridTrackList,
+    JsGridTrackSize, JsGroupHeaders, JsGroupSort, JsRect, JsSortConfig, JsViewState, JsViewStats,
 };
+use react_wasm_table_core::aggregate::Aggregate;
 use react_wasm_table_core::layout::{
-    Align, AlignValue, BoxSizingValue, ColumnLayout, ContainerLayout, DimensionValue, DisplayValue,
-    FlexDirectionValue, FlexWrapValue, GridAutoFlowValue, GridLineValue, GridPlacementValue,
+    parse_grid_template_areas, Align, AlignValue, BoxSizingValue, CalcValue, CellContent,
+    ColumnLayout, ColumnPin, ContainerLayout, DimensionValue, DisplayValue, FlexDirectionValue,
+    FlexWrapValue, GridAutoFlowValue, GridLineValue, GridPlacementValue, HeaderOrientation,
     LengthAutoValue, LengthValue, OverflowValue, PositionValue, RectValue, RepeatValue,
-    TrackListItem, TrackSizeValue,
+    TrackListItem, TrackSizeValue, VerticalAlign,
 };
-use react_wasm_table_core::types::FilterValue;
+use react_wasm_table_core::layout_buffer::CellStyle;
+use react_wasm_table_core::relative_date::RelativeDate;
+use react_wasm_table_core::types::{
+    AutoHeight, AutoHeightColumn, ColumnFilter, ConditionKind, ConditionalFormat, FacetCount,
+    FilterOp, FilterValue, FormatCondition, GlobalFilter, GroupHeaders, GroupSort, NullOrder,
+    SortConfig, SortDirection, ViewPreset, ViewStats,
+};
+use react_wasm_table_core::virtual_scroll::ScrollAlign;
+use react_wasm_table_core::visualization::{parse_color, DataVisualization, VisualKind};
+
+pub fn parse_scroll_align(s: Option<&str>) -> ScrollAlign {
+    match s {
+        Some("start") => ScrollAlign::Start,
+        Some("center") => ScrollAlign::Center,
+        Some("end") => ScrollAlign::End,
+        _ => ScrollAlign::Nearest,
+    }
+}
+
+fn parse_sort_direction(s: &str) -> SortDirection {
+    match s {
+        "Descending" | "desc" => SortDirection::Descending,
+        _ => SortDirection::Ascending,
+    }
+}
+
+pub fn convert_sort_config(c: &JsSortConfig) -> SortConfig {
+    SortConfig {
+        column_index: c.column_index,
+        direction: parse_sort_direction(&c.direction),
+        natural: c.natural,
+        nulls: match c.nulls.as_deref() {
+            Some("first") => Some(NullOrder::First),
+            Some("last") => Some(NullOrder::Last),
+            _ => None,
+        },
+        expression: c.expression.clone(),
+    }
+}
+
+pub fn sort_config_to_js(c: &SortConfig) -> JsSortConfig {
+    JsSortConfig {
+        column_index: c.column_index,
+        direction: match c.direction {
+            SortDirection::Ascending => "asc",
+            SortDirection::Descending => "desc",
+        }
+        .to_string(),
+        natural: c.natural,
+        nulls: c.nulls.map(|n| {
+            match n {
+                NullOrder::First => "first",
+                NullOrder::Last => "last",
+            }
+            .to_string()
+        }),
+        expression: c.expression.clone(),
+    }
+}
+
+const FILTER_OPS: [(&str, FilterOp); 16] = [
+    ("eq", FilterOp::Eq),
+    ("neq", FilterOp::Neq),
+    ("gt", FilterOp::Gt),
+    ("gte", FilterOp::Gte),
+    ("lt", FilterOp::Lt),
+    ("lte", FilterOp::Lte),
+    ("contains", FilterOp::Contains),
+    ("startsWith", FilterOp::StartsWith),
+    ("endsWith", FilterOp::EndsWith),
+    ("isNull", FilterOp::IsNull),
+    ("isNotNull", FilterOp::IsNotNull),
+    ("isBlank", FilterOp::IsBlank),
+    ("containsAny", FilterOp::ContainsAny),
+    ("containsAll", FilterOp::ContainsAll),
+    ("within", FilterOp::Within),
+    ("expression", FilterOp::Expression),
+];
+
+/// Convert JS collation options for `locale`; an unknown strength is an error.
+#[cfg(feature = "collation")]
+pub fn convert_collation(
+    locale: &str,
+    o: &crate::types::JsCollationOptions,
+) -> Result<react_wasm_table_core::collation::Collation, String> {
+    use react_wasm_table_core::collation::{Collation, CollationOptions, CollationStrength};
+    let strength = match o.strength.as_deref() {
+        Some("primary") => CollationStrength::Primary,
+        Some("secondary") => CollationStrength::Secondary,
+        Some("tertiary") | None => CollationStrength::Tertiary,
+        Some(other) => return Err(format!("unknown collation strength: {other:?}")),
+    };
+    Collation::new(
+        locale,
+        CollationOptions {
+            strength,
+            numeric: o.numeric,
+        },
+    )
+}
+
+pub fn convert_group_sort(g: &JsGroupSort) -> Result<GroupSort, String> {
+    Ok(GroupSort {
+        group_column: g.group_column,
+        aggregate_column: g.aggregate_column,
+        aggregate: Aggregate::parse(&g.aggregate)?,
+        direction: parse_sort_direction(&g.direction),
+    })
+}
+
+pub fn convert_auto_height(a: &JsAutoHeight) -> AutoHeight {
+    AutoHeight {
+        columns: a
+            .columns
+            .iter()
+            .map(|c| AutoHeightColumn {
+                column_index: c.column_index,
+                text_width: c.text_width,
+            })
+            .collect(),
+        line_height: a.line_height,
+        padding: a.padding,
+        min_height: a.min_height,
+        char_width: a.char_width.unwrap_or(7.0),
+    }
+}
+
+pub fn convert_cell_style(s: &JsCellStyle) -> CellStyle {
+    CellStyle {
+        padding: s.padding,
+        align: s.align.as_deref().map(parse_align),
+        height: s.height,
+    }
+}
+
+fn parse_align(s: &str) -> Align {
+    match s {
+        "center" => Align::Center,
+        "right" => Align::Right,
+        _ => Align::Left,
+    }
+}
+
+pub const fn convert_group_headers(g: &JsGroupHeaders) -> GroupHeaders {
+    GroupHeaders {
+        column: g.column,
+        height: g.height,
+        sticky: g.sticky,
+    }
+}
+
+/// Convert a JS column filter. Unknown operators fall back to `eq`; `within` values
+/// are parsed as relative date names (unknown names match nothing).
+pub fn convert_column_filter(f: &JsColumnFilter) -> ColumnFilter {
+    let op = FILTER_OPS
+        .iter()
+        .find(|(name, _)| *name == f.op)
+        .map_or(FilterOp::Eq, |(_, op)| *op);
+    let value = match (op, f.value.as_ref()) {
+        (FilterOp::Within, Some(JsFilterValue::String(s))) => RelativeDate::parse(s).map_or_else(
+            |_| convert_filter_value(f.value.as_ref()),
+            FilterValue::RelativeDate,
+        ),
+        _ => convert_filter_value(f.value.as_ref()),
+    };
+    ColumnFilter {
+        column_index: f.column_index,
+        op,
+        value,
+        formatted: f.formatted,
+    }
+}
+
+/// Convert a JS conditional format rule: `scale`, then `top`, then a filter
+/// condition, whichever is set first.
+pub fn convert_conditional_format(rule: JsConditionalFormat) -> Result<ConditionalFormat, String> {
+    let c = rule.condition;
+    let condition = if let Some(buckets) = c.scale {
+        FormatCondition::Scale { buckets }
+    } else if let Some(count) = c.top {
+        FormatCondition::Top {
+            count,
+            percent: c.percent,
+            bottom: c.bottom,
+        }
+    } else if let Some(op) = c.op {
+        FormatCondition::Match(convert_column_filter(&JsColumnFilter {
+            column_index: c.column_index.unwrap_or(rule.target),
+            op,
+            value: c.value,
+            formatted: c.formatted,
+        }))
+    } else {
+        return Err("conditional format needs `scale`, `top`, or `op`".to_string());
+    };
+    Ok(ConditionalFormat {
+        column_index: rule.target,
+        condition,
+        style_id: rule.style_id,
+    })
+}
+
+/// Default color scale: white to blue.
+const DEFAULT_SCALE: [&str; 2] = ["#ffffff", "#2f6fde"];
+
+pub fn convert_data_visualization(v: JsDataVisualization) -> Result<DataVisualization, String> {
+    let kind = match v.kind.as_str() {
+        "colorScale" => VisualKind::ColorScale(
+            v.colors
+                .unwrap_or_else(|| DEFAULT_SCALE.map(String::from).to_vec())
+                .iter()
+                .map(|c| parse_color(c))
+                .collect::<Result<_, _>>()?,
+        ),
+        "dataBar" => VisualKind::DataBar,
+        other => return Err(format!("unknown data visualization: {other:?}")),
+    };
+    Ok(DataVisualization {
+        kind,
+        domain: v.domain,
+    })
+}
+
+fn filter_op_name(op: FilterOp) -> &'static str {
+    FILTER_OPS
+        .iter()
+        .find(|(_, o)| *o == op)
+        .map_or("eq", |(name, _)| name)
+}
+
+pub fn column_filter_to_js(f: &ColumnFilter) -> JsColumnFilter {
+    JsColumnFilter {
+        column_index: f.column_index,
+        op: filter_op_name(f.op).to_string(),
+        value: Some(filter_value_to_js(f.value.clone())),
+        formatted: f.formatted,
+    }
+}
+
+pub fn view_stats_to_js(stats: &ViewStats) -> JsViewStats {
+    JsViewStats {
+        conditions: stats
+            .conditions
+            .iter()
+            .map(|c| {
+                let (kind, op) = match c.kind {
+                    ConditionKind::Column(op) => ("column", Some(filter_op_name(op))),
+                    ConditionKind::Global => ("global", None),
+                    ConditionKind::Custom => ("custom", None),
+                };
+                JsConditionStats {
+                    kind,
+                    op,
+                    column_index: c.column_index,
+                    rows_scanned: c.rows_scanned,
+                    rows_passed: c.rows_passed,
+                    elapsed_us: c.elapsed_us,
+                    cached: c.cached,
+                }
+            })
+            .collect(),
+        rows_filtered: stats.rows_filtered,
+        sort_us: stats.sort_us,
+        total_us: stats.total_us,
+    }
+}
+
+pub fn convert_view_state(s: &JsViewState) -> ViewPreset {
+    ViewPreset {
+        column_filters: s.filters.iter().map(convert_column_filter).collect(),
+        global_filter: s.global_filter.clone().map(|query| GlobalFilter { query }),
+        sort_configs: s.sort.iter().map(convert_sort_config).collect(),
+    }
+}
+
+pub fn view_state_to_js(p: &ViewPreset) -> JsViewState {
+    JsViewState {
+        filters: p.column_filters.iter().map(column_filter_to_js).collect(),
+        global_filter: p.global_filter.as_ref().map(|g| g.query.clone()),
+        sort: p.sort_configs.iter().map(sort_config_to_js).collect(),
+    }
+}
+
+/// Convert a JS filter value. A missing value (null-aware operators) becomes an empty string,
+/// which those operators ignore.
+pub fn convert_filter_value(v: Option<&JsFilterValue>) -> FilterValue {
+    match v {
+        Some(JsFilterValue::Bool(b)) => FilterValue::Bool(*b),
+        Some(JsFilterValue::Float64(f)) => FilterValue::Float64(*f),
+        Some(JsFilterValue::String(s)) => FilterValue::String(s.clone()),
+        Some(JsFilterValue::StringList(list)) => FilterValue::StringList(list.clone()),
+        None => FilterValue::String(String::new()),
+    }
+}
 
-pub fn convert_filter_value(v: &JsFilterValue) -> FilterValue {
+pub fn filter_value_to_js(v: FilterValue) -> JsFilterValue {
     match v {
-        JsFilterValue::Bool(b) => FilterValue::Bool(*b),
-        JsFilterValue::Float64(f) => FilterValue::Float64(*f),
-        JsFilterValue::String(s) => FilterValue::String(s.clone()),
+        FilterValue::Bool(b) => JsFilterValue::Bool(b),
+        FilterValue::Float64(n) => JsFilterValue::Float64(n),
+        FilterValue::String(s) => JsFilterValue::String(s),
+        FilterValue::StringList(list) => JsFilterValue::StringList(list),
+        FilterValue::RelativeDate(range) => JsFilterValue::String(range.name()),
+    }
+}
+
+/// Convert a cell value to a plain JS value for callbacks (`null` for nulls,
+/// an array of strings for string-list cells).
+pub fn cell_value_to_js(v: Option<FilterValue>) -> wasm_bindgen::JsValue {
+    use wasm_bindgen::JsValue;
+    match v {
+        None | Some(FilterValue::RelativeDate(_)) => JsValue::NULL,
+        Some(FilterValue::Float64(n)) => JsValue::from_f64(n),
+        Some(FilterValue::Bool(b)) => JsValue::from_bool(b),
+        Some(FilterValue::String(s)) => JsValue::from_str(&s),
+        Some(FilterValue::StringList(list)) => list
+            .iter()
+            .map(|s| JsValue::from_str(s))
+            .collect::<js_sys::Array>()
+            .into(),
+    }
+}
+
+pub fn facet_to_js(f: FacetCount) -> JsFacetCount {
+    JsFacetCount {
+        value: f.value.map(filter_value_to_js),
+        count: f.count,
     }
 }
 
@@ -26,6 +349,8 @@ pub fn parse_dimension(d: Option<&JsDimension>) -> DimensionValue {
         Some(JsDimension::Str(s)) => {
             if s == "auto" {
                 DimensionValue::Auto
+            } else if let Some(calc) = CalcValue::parse(s) {
+                DimensionValue::Calc(calc)
             } else if let Some(pct) = s.strip_suffix('%') {
                 pct.parse::<f32>()
                     .map_or(DimensionValue::Auto, |v| DimensionValue::Percent(v / 100.0))
@@ -41,15 +366,20 @@ pub fn parse_length(d: Option<&JsDimension>) -> LengthValue {
     match d {
         None => LengthValue::Zero,
         Some(JsDimension::Number(v)) => LengthValue::Length(*v),
-        Some(JsDimension::Str(s)) => s.strip_suffix('%').map_or_else(
+        Some(JsDimension::Str(s)) => CalcValue::parse(s).map_or_else(
             || {
-                s.parse::<f32>()
-                    .map_or(LengthValue::Zero, LengthValue::Length)
-            },
-            |pct| {
-                pct.parse::<f32>()
-                    .map_or(LengthValue::Zero, |v| LengthValue::Percent(v / 100.0))
+                s.strip_suffix('%').map_or_else(
+                    || {
+                        s.parse::<f32>()
+                            .map_or(LengthValue::Zero, LengthValue::Length)
+                    },
+                    |pct| {
+                        pct.parse::<f32>()
+                            .map_or(LengthValue::Zero, |v| LengthValue::Percent(v / 100.0))
+                    },
+                )
             },
+            LengthValue::Calc,
         ),
     }
 }
@@ -61,6 +391,8 @@ fn parse_length_auto(d: Option<&JsDimension>) -> LengthAutoValue {
         Some(JsDimension::Str(s)) => {
             if s == "auto" {
                 LengthAutoValue::Auto
+            } else if let Some(calc) = CalcValue::parse(s) {
+                LengthAutoValue::Calc(calc)
             } else if let Some(pct) = s.strip_suffix('%') {
                 pct.parse::<f32>().map_or(LengthAutoValue::Auto, |v| {
                     LengthAutoValue::Percent(v / 100.0)
@@ -146,6 +478,8 @@ fn parse_grid_track_size(v: &JsGridTrackSize) -> TrackSizeValue {
                 TrackSizeValue::MinContent
             } else if s == "max-content" {
                 TrackSizeValue::MaxContent
+            } else if let Some(calc) = CalcValue::parse(s) {
+                TrackSizeValue::Calc(calc)
             } else if let Some(fr) = s.strip_suffix("fr") {
                 fr.trim()
                     .parse::<f32>()
@@ -156,7 +490,7 @@ fn parse_grid_track_size(v: &JsGridTrackSize) -> TrackSizeValue {
                     .map_or(TrackSizeValue::Auto, TrackSizeValue::Percent)
             } else if s.starts_with("minmax(") && s.ends_with(')') {
                 let inner = &s[7..s.len() - 1];
-                if let Some((min_s, max_s)) = inner.split_once(',') {
+                if let Some((min_s, max_s)) = split_top_level_comma(inner) {
                     let min =
                         parse_grid_track_size(&JsGridTrackSize::Str(min_s.trim().to_string()));
                     let max =
@@ -193,8 +527,25 @@ fn parse_grid_track_size(v: &JsGridTrackSize) -> TrackSizeValue {
     }
 }
 
+/// Split at the first comma outside parentheses (`minmax(min(a, b), 1fr)`).
+fn split_top_level_comma(s: &str) -> Option<(&str, &str)> {
+    let mut depth: u32 = 0;
+    for (i, ch) in s.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => return Some((&s[..i], &s[i + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
+
 fn parse_grid_track_list_item(s: &str) -> TrackListItem {
     let s = s.trim();
+    if let Some(names) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        return TrackListItem::Names(names.split_whitespace().map(String::from).collect());
+    }
     if s.starts_with("repeat(") && s.ends_with(')') {
         let inner = &s[7..s.len() - 1];
         if let Some((count_s, tracks_s)) = inner.split_once(',') {
@@ -205,9 +556,13 @@ fn parse_grid_track_list_item(s: &str) -> TrackListItem {
                     .parse::<u16>()
                     .map_or(RepeatValue::Count(1), RepeatValue::Count),
             };
-            let tracks: Vec<TrackSizeValue> = tracks_s
-                .split_whitespace()
-                .map(|t| parse_grid_track_size(&JsGridTrackSize::Str(t.to_string())))
+            // Line names inside repeat() are not supported and are skipped
+            let tracks: Vec<TrackSizeValue> = parse_space_separated_tracks(tracks_s)
+                .into_iter()
+                .filter_map(|item| match item {
+                    TrackListItem::Single(t) => Some(t),
+                    _ => None,
+                })
                 .collect();
             TrackListItem::Repeat(count, tracks)
         } else {
@@ -226,10 +581,11 @@ pub fn parse_grid_track_list(v: Option<&JsGridTrackList>) -> Vec<TrackListItem>
             // or a repeat() function
             if let JsGridTrackSize::Str(s) = t {
                 let s = s.trim();
-                if s.contains(' ') {
+                if s.contains(' ') || s.starts_with('[') {
                     // Space-separated track list — parse each token
                     // parse_space_separated_tracks handles parenthesized expressions
-                    // (e.g. "minmax(100px, 1fr) 2fr 1fr") via paren-depth tracking.
+                    // (e.g. "minmax(100px, 1fr) 2fr 1fr") and line names
+                    // ("[main-start] 1fr [main-end]") via bracket-depth tracking.
                     return parse_space_separated_tracks(s);
                 }
             }
@@ -237,14 +593,9 @@ pub fn parse_grid_track_list(v: Option<&JsGridTrackList>) -> Vec<TrackListItem>
         }
         Some(JsGridTrackList::Array(arr)) => arr
             .iter()
-            .map(|t| {
-                if let JsGridTrackSize::Str(s) = t {
-                    let s = s.trim();
-                    if s.starts_with("repeat(") {
-                        return parse_grid_track_list_item(s);
-                    }
-                }
-                TrackListItem::Single(parse_grid_track_size(t))
+            .map(|t| match t {
+                JsGridTrackSize::Str(s) => parse_grid_track_list_item(s),
+                JsGridTrackSize::Number(_) => TrackListItem::Single(parse_grid_track_size(t)),
             })
             .collect(),
     }
@@ -257,11 +608,11 @@ fn parse_space_separated_tracks(s: &str) -> Vec<TrackListItem> {
 
     for ch in s.chars() {
         match ch {
-            '(' => {
+            '(' | '[' => {
                 paren_depth += 1;
                 current.push(ch);
             }
-            ')' => {
+            ')' | ']' => {
                 paren_depth = paren_depth.saturating_sub(1);
                 current.push(ch);
             }
@@ -293,26 +644,85 @@ pub fn parse_auto_tracks(v: Option<&JsGridTrackList>) -> Vec<TrackSizeValue> {
 fn parse_grid_placement(v: &JsGridPlacement) -> GridPlacementValue {
     match v {
         JsGridPlacement::Number(n) => GridPlacementValue::Line(*n),
-        JsGridPlacement::Str(s) => {
-            let s = s.trim();
-            if s == "auto" {
-                GridPlacementValue::Auto
-            } else if let Some(span_s) = s.strip_prefix("span ") {
-                span_s
-                    .trim()
-                    .parse::<u16>()
-                    .map_or(GridPlacementValue::Auto, GridPlacementValue::Span)
-            } else {
-                s.parse::<i16>()
-                    .map_or(GridPlacementValue::Auto, GridPlacementValue::Line)
-            }
+        JsGridPlacement::Str(s) => parse_grid_placement_str(s),
+    }
+}
+
+/// `auto`, a line number, `span N`, or a line name with an optional count
+/// (`main`, `main 2`, `span main`).
+fn parse_grid_placement_str(s: &str) -> GridPlacementValue {
+    let s = s.trim();
+    let (span, rest) = s
+        .strip_prefix("span ")
+        .map_or((false, s), |rest| (true, rest.trim()));
+    if s == "auto" {
+        return GridPlacementValue::Auto;
+    }
+    let mut count = None;
+    let mut name = None;
+    for token in rest.split_whitespace() {
+        match token.parse::<i16>() {
+            Ok(n) => count = Some(n),
+            Err(_) => name = Some(token.to_string()),
+        }
+    }
+    match (span, name, count) {
+        (false, None, Some(n)) => GridPlacementValue::Line(n),
+        (false, Some(name), n) => GridPlacementValue::Named(name, n.unwrap_or(1)),
+        (true, None, Some(n)) => {
+            u16::try_from(n).map_or(GridPlacementValue::Auto, GridPlacementValue::Span)
+        }
+        (true, Some(name), n) => {
+            GridPlacementValue::NamedSpan(name, n.and_then(|n| u16::try_from(n).ok()).unwrap_or(1))
         }
+        (_, None, None) => GridPlacementValue::Auto,
     }
 }
 
+/// Start and end of a `start / end` grid line string.
+fn parse_grid_line_str(s: &str) -> GridLineValue {
+    let (start, end) = s.split_once('/').unwrap_or((s, "auto"));
+    GridLineValue {
+        start: parse_grid_placement_str(start),
+        end: parse_grid_placement_str(end),
+    }
+}
+
+/// `grid-area` as `[grid_row, grid_column]`: a name places both edges of both
+/// axes on the area's lines; otherwise `row-start / column-start / row-end /
+/// column-end`, with omitted ends `auto`.
+fn parse_grid_area(s: &str) -> [GridLineValue; 2] {
+    let parts: Vec<&str> = s.split('/').map(str::trim).collect();
+    if let [name] = parts.as_slice() {
+        if name.parse::<i16>().is_err() && !name.starts_with("span ") && *name != "auto" {
+            let line = GridLineValue {
+                start: GridPlacementValue::Named((*name).to_string(), 1),
+                end: GridPlacementValue::Named((*name).to_string(), 1),
+            };
+            return [line.clone(), line];
+        }
+    }
+    let part = |i: usize| {
+        parts
+            .get(i)
+            .map_or(GridPlacementValue::Auto, |p| parse_grid_placement_str(p))
+    };
+    [
+        GridLineValue {
+            start: part(0),
+            end: part(2),
+        },
+        GridLineValue {
+            start: part(1),
+            end: part(3),
+        },
+    ]
+}
+
 fn parse_grid_line(v: Option<&JsGridLine>) -> Option<GridLineValue> {
     match v {
         None => None,
+        Some(JsGridLine::Single(JsGridPlacement::Str(s))) => Some(parse_grid_line_str(s)),
         Some(JsGridLine::Single(p)) => Some(GridLineValue {
             start: parse_grid_placement(p),
             end: GridPlacementValue::Auto,
@@ -330,16 +740,18 @@ fn parse_grid_line(v: Option<&JsGridLine>) -> Option<GridLineValue> {
 }
 
 pub fn convert_column(c: &JsColumnLayout) -> ColumnLayout {
+    let area = c.grid_area.as_deref().map(parse_grid_area);
     ColumnLayout {
         width: c.width,
         flex_grow: c.flex_grow,
         flex_shrink: c.flex_shrink,
         min_width: c.min_width,
         max_width: c.max_width,
-        align: match c.align.as_deref() {
-            Some("center") => Align::Center,
-            Some("right") => Align::Right,
-            _ => Align::Left,
+        align: c.align.as_deref().map_or(Align::Left, parse_align),
+        vertical_align: match c.vertical_align.as_deref() {
+            Some("top") => VerticalAlign::Top,
+            Some("bottom") => VerticalAlign::Bottom,
+            _ => VerticalAlign::Middle,
         },
         flex_basis: parse_dimension(c.flex_basis.as_ref()),
         height: parse_dimension(c.height.as_ref()),
@@ -359,9 +771,39 @@ pub fn convert_column(c: &JsColumnLayout) -> ColumnLayout {
             _ => PositionValue::Relative,
         },
         inset: parse_length_auto_rect(c.inset.as_ref()),
-        grid_row: parse_grid_line(c.grid_row.as_ref()),
-        grid_column: parse_grid_line(c.grid_column.as_ref()),
+        grid_row: parse_grid_line(c.grid_row.as_ref()).or_else(|| area.clone().map(|[row, _]| row)),
+        grid_column: parse_grid_line(c.grid_column.as_ref()).or_else(|| area.map(|[_, col]| col)),
         justify_self: parse_align_value(c.justify_self.as_ref()),
+        pinned: match c.pinned.as_deref() {
+            Some("left") => Some(ColumnPin::Left),
+            Some("right") => Some(ColumnPin::Right),
+            _ => None,
+        },
+        content: c.items.as_ref().map(|items| {
+            Box::new(CellContent {
+                container: c
+                    .item_container
+                    .as_ref()
+                    .map_or_else(ContainerLayout::default, convert_container),
+                items: items.iter().map(convert_column).collect(),
+            })
+        }),
+        content_size: None,
+        header_orientation: parse_header_orientation(c.header_orientation.as_ref()),
+        header_label_width: 0.0,
+    }
+}
+
+/// Header label rotation: "vertical", degrees (number or "45deg"), or horizontal.
+pub fn parse_header_orientation(d: Option<&JsDimension>) -> HeaderOrientation {
+    match d {
+        Some(JsDimension::Number(degrees)) => HeaderOrientation::Angled(*degrees),
+        Some(JsDimension::Str(s)) if s == "vertical" => HeaderOrientation::Vertical,
+        Some(JsDimension::Str(s)) => s
+            .strip_suffix("deg")
+            .and_then(|degrees| degrees.trim().parse::<f32>().ok())
+            .map_or(HeaderOrientation::Horizontal, HeaderOrientation::Angled),
+        None => HeaderOrientation::Horizontal,
     }
 }
 
@@ -408,6 +850,19 @@ pub fn convert_container(c: &JsContainerLayout) -> ContainerLayout {
         border: parse_length_rect(c.border.as_ref()),
         grid_template_rows: parse_grid_track_list(c.grid_template_rows.as_ref()),
         grid_template_columns: parse_grid_track_list(c.grid_template_columns.as_ref()),
+        grid_template_areas: match &c.grid_template_areas {
+            None => Vec::new(),
+            // Quoted rows: `"a a" "b c"`; unquoted is a single row
+            Some(JsGridTemplateAreas::Single(s)) if s.contains('"') => {
+                let rows: Vec<&str> = s.split('"').skip(1).step_by(2).collect();
+                parse_grid_template_areas(&rows)
+            }
+            Some(JsGridTemplateAreas::Single(s)) => parse_grid_template_areas(&[s.as_str()]),
+            Some(JsGridTemplateAreas::Rows(rows)) => {
+                let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+                parse_grid_template_areas(&rows)
+            }
+        },
         grid_auto_rows: parse_auto_tracks(c.grid_auto_rows.as_ref()),
         grid_auto_columns: parse_auto_tracks(c.grid_auto_columns.as_ref()),
         grid_auto_flow: match c.grid_auto_flow.as_deref() {
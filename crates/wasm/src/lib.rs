@@ -6,27 +6,272 @@ mod convert;
 mod types;
 
 use convert::{
-    convert_column, convert_container, convert_filter_value, decode_align, decode_justify,
+    cell_value_to_js, convert_auto_height, convert_cell_style, convert_column,
+    convert_column_filter, convert_container, convert_group_headers, convert_group_sort,
+    convert_sort_config, convert_view_state, decode_align, decode_justify, facet_to_js,
+    parse_scroll_align, view_state_to_js, view_stats_to_js,
+};
+use types::{
+    JsAggregation, JsAriaColumn, JsAriaRow, JsAriaSnapshot, JsAutoHeight, JsCellRange, JsCellStyle,
+    JsColumnFilter, JsColumnLayout, JsContainerLayout, JsEngineState, JsGroupHeaders, JsGroupSort,
+    JsHeaderLayout, JsNamedPreset, JsNavModifiers, JsOverlay, JsQueryDescriptor, JsRebuildProgress,
+    JsSortConfig, JsTransposedLayout, JsValidationRules, JsViewport, JsWindowColumn,
 };
-use types::{JsColumnFilter, JsColumnLayout, JsContainerLayout, JsSortConfig, JsViewport};
 
-use react_wasm_table_core::columnar_store::ColumnarStore;
+use react_wasm_table_core::aggregate::Aggregate;
+use react_wasm_table_core::arrow_ipc;
+use react_wasm_table_core::column_sizing::{self, ResizeMode};
+use react_wasm_table_core::columnar_store::{
+    parse_filter_expressions, ColumnData, ColumnarStore, CustomFilterFn, MeasureTextFn,
+    MeasureWidthFn,
+};
+use react_wasm_table_core::commands::{self, Command};
+use react_wasm_table_core::dedupe::{self, Keep};
+use react_wasm_table_core::editing::{self, CellEdit, CellValue, Editor};
+use react_wasm_table_core::export::{self, ExportFormat, ExportRange};
+use react_wasm_table_core::expr::Expr;
+use react_wasm_table_core::format::FormatSpec;
+use react_wasm_table_core::history::History;
 use react_wasm_table_core::layout::{
-    ColumnLayout, ContainerLayout, FlexDirectionValue, LayoutEngine, Viewport,
+    cell_items_per_row, rotated_header_height, viewport_layout_key, write_baselines, ColumnLayout,
+    ContainerLayout, ContentSize, FlexDirectionValue, HeaderLayout, LayoutEngine, LengthValue,
+    TransposedLayout, VerticalAlign, Viewport,
 };
 use react_wasm_table_core::layout_buffer;
+use react_wasm_table_core::navigation::{self, NavGrid, NavKey};
+use react_wasm_table_core::overlay::{self, Overlay, OverlayAnchor};
+use react_wasm_table_core::print;
+use react_wasm_table_core::selection::{Selection, SelectionMode};
+use react_wasm_table_core::state::SavedState;
+use react_wasm_table_core::text_wrap;
 use react_wasm_table_core::types::{
-    ColumnFilter, FilterOp, GlobalFilter, SortConfig, SortDirection,
+    ColumnFilter, GlobalFilter, RowMerge, ScrollAnchor, SortDirection,
+};
+use react_wasm_table_core::upsert::{RowKey, UpsertQueue};
+use react_wasm_table_core::validation::{Regex, ValidationRules};
+use react_wasm_table_core::virtual_scroll::{
+    compute_virtual_slice, exposed_rows, scroll_top_for_row, ScrollAlign, ScrollState,
+};
+use react_wasm_table_core::window::WindowFn;
+#[cfg(feature = "pdf")]
+use react_wasm_table_core::{
+    layout::Align,
+    pdf::{self, PdfColumn, PdfDocument},
 };
+#[cfg(feature = "xlsx")]
+use react_wasm_table_core::{
+    layout::ColumnPin,
+    xlsx::{self, XlsxColumn, XlsxSheet},
+};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::{Range, RangeInclusive};
 use wasm_bindgen::prelude::*;
 
+/// `initThreadPool(navigator.hardwareConcurrency)` — start the rayon worker pool used
+/// for large sorts, filters and view writes. Requires the `threads` feature, a build with
+/// `+atomics,+bulk-memory` (nightly `build-std`), and a cross-origin isolated page
+/// (SharedArrayBuffer).
+#[cfg(all(feature = "threads", target_arch = "wasm32"))]
+pub use wasm_bindgen_rayon::init_thread_pool;
+
+/// Mark the worker pool started (after `await initThreadPool(n)`) so large view
+/// rebuilds run on it; until then they run on the calling thread. Returns whether
+/// this build has the `threads` feature.
+#[wasm_bindgen(js_name = setThreadPoolReady)]
+pub fn set_thread_pool_ready(ready: bool) -> bool {
+    react_wasm_table_core::columnar_store::set_parallel_enabled(ready);
+    cfg!(feature = "threads")
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// High-resolution timestamp in milliseconds, used to time view rebuilds.
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+}
+
 /// The main WASM-exposed table engine.
 #[wasm_bindgen]
 pub struct TableEngine {
     layout: LayoutEngine,
     layout_buf: Vec<f32>,
     layout_cell_count: usize,
+    /// Pages of the last `computePrintLayout`, one layout buffer after another.
+    print_buf: Vec<f32>,
+    /// Back buffer filled by `predictNextSlice`.
+    predicted_buf: Vec<f32>,
+    predicted_cell_count: usize,
+    /// Copy of the layout buffer before the last frame (`getChangedCells`).
+    previous_buf: Vec<f32>,
+    previous_cell_count: usize,
+    /// Fill `previous_buf` every frame (`setTrackChangedCells`).
+    track_changed_cells: bool,
+    /// Selected cell ranges, view positions × table columns (`setSelectedCells`).
+    selection: Vec<(RangeInclusive<usize>, RangeInclusive<usize>)>,
+    /// Selected rows and cells by data row (`selectCell`, `toggleRow`, ...).
+    selection_model: Selection,
+    /// Grid line positions (`getRuleBufferInfo`).
+    rule_buf: Vec<f32>,
+    /// Header cell sort, filter, and resize handle fields (`getHeaderInfo`).
+    header_meta: Vec<f32>,
+    /// Cell range overlays (`setOverlays`) and their rectangles in the layout
+    /// buffer (`getOverlayBufferInfo`).
+    overlays: Vec<Overlay>,
+    overlay_buf: Vec<f32>,
+    /// View version, rows, and `scrollTop` the back buffer was laid out for.
+    predicted: Option<(u64, Range<usize>, f64)>,
     columnar: ColumnarStore,
+    /// Injected reference time for relative date filters (`None` = wall clock).
+    filter_now: Option<f64>,
+    utc_offset_minutes: i32,
+    /// Effective row and header heights (and header stickiness) of the last
+    /// `updateViewportColumnar`.
+    last_row_height: f64,
+    last_header_height: f64,
+    last_sticky_header: bool,
+    last_frame: Option<LastFrame>,
+    /// Keep a row in place across view rebuilds (`setScrollAnchoring`).
+    scroll_anchoring: bool,
+    /// Data row to anchor; `None` = the top visible row.
+    anchor_row: Option<usize>,
+    /// Focused cell for keyboard navigation: view position and table column.
+    focus: Option<(usize, usize)>,
+    /// Open cell edit (`beginEdit`).
+    editor: Editor,
+    /// Undoable changes (`undo`, `beginTransaction`).
+    history: History<Change>,
+    /// Column of the last `resizeColumn` width change, for merging a drag into
+    /// one `undo` step.
+    resized_column: Option<usize>,
+    /// `SharedArrayBuffer` each frame is mirrored into (`setSharedLayoutBuffer`).
+    shared_layout: Option<SharedLayout>,
+    /// Inputs of the last `updateViewportColumnar`, replayed by `applyCommands`.
+    frame_inputs: Option<FrameInputs>,
+    /// Re-anchored `scrollTop` for the next frame after a rebuild moved the anchor.
+    anchored_scroll_top: Option<f64>,
+    /// Anchor and pinned top row captured when a `rebuildViewStep` rebuild started.
+    rebuild_anchor: Option<(ScrollAnchor, usize)>,
+    /// Queued keyed upserts, applied at the next frame (`applyUpserts`).
+    upserts: Option<UpsertQueue>,
+    /// `(data row, column)` cells changed by upserts not yet reported by a frame.
+    upserted_cells: Vec<(usize, usize)>,
+    /// `[view row, column]` pairs of the last frame's rendered cells changed by
+    /// upserts (`getUpdatedCellsInfo`).
+    updated_cells: Vec<u32>,
+    /// Zoomed px per layout px (`setZoom`).
+    zoom: f64,
+    /// Device pixels per px that emitted cell edges snap to (`setPixelSnapping`).
+    pixel_ratio: Option<f32>,
+    /// UTF-16 offsets of each layout buffer cell's text in the last
+    /// `getFormattedSlice` string.
+    text_offsets: Vec<u32>,
+    /// Per-cell data visualization values and colors (`getDataVisualizationInfo`).
+    visual_values: Vec<f32>,
+    visual_colors: Vec<u32>,
+    /// Per-cell `[truncated, cut]` pairs (`getTruncationInfo`).
+    truncations: Vec<u32>,
+    /// `[cell, start, end, y]` per line of wrapped cells (`getWrappedLinesInfo`).
+    wrapped_lines: Vec<f32>,
+    /// Down-sampled series of the last `getSparklineData` rows.
+    sparkline_values: Vec<f32>,
+    /// Per-column width overrides from `autoSizeColumns` and `resizeColumn`
+    /// (`None` = JS width).
+    column_widths: Vec<Option<f32>>,
+    /// Column `key` of each column of the last `updateViewportColumnar`, which
+    /// `column_widths` are re-indexed by when columns move, and `setColumnWidths`
+    /// widths for keys not among them yet.
+    column_keys: Vec<Option<String>>,
+    pending_widths: HashMap<String, f32>,
+    /// Columns hidden by `setColumnVisibility`, by table column index.
+    hidden_columns: Vec<bool>,
+    /// Header row container and column overrides (`setHeaderLayout`), by table
+    /// column index.
+    header_layout: Option<HeaderLayout>,
+    /// Records laid out as columns (`setTransposed`).
+    transposed: Option<TransposedLayout>,
+    /// `(text) => width` callback for `autoSizeColumns` and content sizing.
+    text_width_measure: Option<js_sys::Function>,
+    /// Rows sampled for content sizing (`setContentSizing`; 0 = every row), or
+    /// `None` when columns have no content size.
+    content_sizing: Option<usize>,
+    /// View version and per-column content sizes measured for it (`None` = not
+    /// measured yet).
+    content_sizes: (u64, Vec<Option<ContentSize>>),
+    /// Line height and the header label widths measured at it (`header_label_width`).
+    label_widths: (f32, HashMap<String, f32>),
+    /// `(column, fn)` per `setAggregations` entry.
+    aggregations: Vec<(usize, AggregateFn)>,
+    /// `(values) => number` callbacks by name (`setCustomAggregate`).
+    custom_aggregates: HashMap<String, js_sys::Function>,
+    /// `onViewChanged`, `onSelectionChanged`, and `onDataEdited` subscribers.
+    events: EngineEvents,
+}
+
+/// Event callbacks, with the versions last reported to them.
+#[derive(Default)]
+struct EngineEvents {
+    view_changed: Option<js_sys::Function>,
+    view_version: u64,
+    selection_changed: Option<js_sys::Function>,
+    selection_version: u64,
+    data_edited: Option<js_sys::Function>,
+}
+
+/// How one aggregation reduces a column.
+enum AggregateFn {
+    Builtin(Aggregate),
+    /// Name of a `setCustomAggregate` callback.
+    Custom(String),
+}
+
+/// An undoable change (`undo`), with the state before and after.
+#[derive(Clone)]
+enum Change {
+    Cell(CellEdit),
+    ColumnWidths(Vec<Option<f32>>, Vec<Option<f32>>),
+    HiddenColumns(Vec<bool>, Vec<bool>),
+    /// Manual row order (`None` = sorted).
+    RowOrder(Option<Vec<u32>>, Option<Vec<u32>>),
+}
+
+/// Views of a caller-provided shared layout output buffer.
+struct SharedLayout {
+    header: js_sys::Int32Array,
+    cells: js_sys::Float32Array,
+    byte_len: usize,
+}
+
+/// Parsed inputs of an `updateViewportColumnar` frame, in layout px.
+#[derive(Clone)]
+struct FrameInputs {
+    scroll_top: f64,
+    viewport: Viewport,
+    /// Columns as passed in, before width overrides and content sizes.
+    columns: Vec<ColumnLayout>,
+    container: ContainerLayout,
+    /// Requested row pins (top, bottom), besides the always-visible rows.
+    pins: (usize, usize),
+    header_row_count: usize,
+}
+
+/// What the previous `updateViewportColumnar` rendered, for repaint ranges.
+struct LastFrame {
+    /// Layout inputs other than the scroll offset (`viewport_layout_key` + row pins).
+    key: u64,
+    view_version: u64,
+    /// Scrolled rows in the layout buffer.
+    rows: Range<usize>,
+    scroll_top: f64,
+    pinned_top: usize,
+    /// Inputs of the frame, replayed by `predictNextSlice`.
+    columns: Vec<ColumnLayout>,
+    container: ContainerLayout,
+    viewport: Viewport,
+    pinned_bottom: usize,
+    header_row_count: usize,
+    /// Header cells at the start of the layout buffer (the laid-out columns).
+    header_cells: usize,
 }
 
 #[wasm_bindgen]
@@ -34,16 +279,78 @@ impl TableEngine {
     /// Create a new `TableEngine` instance.
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
+        let mut columnar = ColumnarStore::new();
+        columnar.set_clock(Some(performance_now));
         Self {
             layout: LayoutEngine::new(),
             layout_buf: Vec::new(),
+            print_buf: Vec::new(),
             layout_cell_count: 0,
-            columnar: ColumnarStore::new(),
+            predicted_buf: Vec::new(),
+            predicted_cell_count: 0,
+            previous_buf: Vec::new(),
+            previous_cell_count: 0,
+            track_changed_cells: false,
+            selection: Vec::new(),
+            selection_model: Selection::default(),
+            rule_buf: Vec::new(),
+            header_meta: Vec::new(),
+            overlays: Vec::new(),
+            overlay_buf: Vec::new(),
+            predicted: None,
+            columnar,
+            filter_now: None,
+            utc_offset_minutes: 0,
+            last_row_height: 0.0,
+            last_header_height: 0.0,
+            last_sticky_header: false,
+            last_frame: None,
+            scroll_anchoring: false,
+            anchor_row: None,
+            focus: None,
+            editor: Editor::default(),
+            history: History::default(),
+            resized_column: None,
+            anchored_scroll_top: None,
+            rebuild_anchor: None,
+            upserts: None,
+            upserted_cells: Vec::new(),
+            updated_cells: Vec::new(),
+            shared_layout: None,
+            frame_inputs: None,
+            zoom: 1.0,
+            pixel_ratio: None,
+            text_offsets: Vec::new(),
+            visual_values: Vec::new(),
+            visual_colors: Vec::new(),
+            truncations: Vec::new(),
+            wrapped_lines: Vec::new(),
+            sparkline_values: Vec::new(),
+            column_widths: Vec::new(),
+            column_keys: Vec::new(),
+            pending_widths: HashMap::new(),
+            hidden_columns: Vec::new(),
+            header_layout: None,
+            transposed: None,
+            text_width_measure: None,
+            content_sizing: None,
+            content_sizes: (0, Vec::new()),
+            label_widths: (0.0, HashMap::new()),
+            aggregations: Vec::new(),
+            custom_aggregates: HashMap::new(),
+            events: EngineEvents::default(),
         }
     }
 
     // ── Layout buffer ─────────────────────────────────────────────────
 
+    /// Layout buffer format version; readers built for another version must not
+    /// read the buffer (the stride or field meanings differ).
+    #[wasm_bindgen(js_name = getLayoutBufferVersion)]
+    pub fn get_layout_buffer_version(&self) -> u32 {
+        layout_buffer::LAYOUT_BUFFER_VERSION
+    }
+
     /// Return [pointer_offset, f32_count] for the layout buffer.
     /// JS creates: `new Float32Array(wasmMemory.buffer, offset, len)`
     #[wasm_bindgen(js_name = getLayoutBufferInfo)]
@@ -54,6 +361,1075 @@ impl TableEngine {
         ]
     }
 
+    /// Mirror every frame's layout buffer into `buffer`, a `SharedArrayBuffer` a
+    /// render thread reads while a worker runs the engine, or stop with
+    /// `undefined`. The buffer holds a header of 4 i32 words (generation,
+    /// published slot, and the cell count of slots 0 and 1) followed by two
+    /// slots of whole cells. Each frame is written to the unpublished slot, then
+    /// published by storing its slot and atomically incrementing the generation
+    /// (`Atomics.wait` on word 0 to block for the next frame). The published
+    /// slot is only rewritten after the next frame is published, so a reader
+    /// that sees the generation change while reading must re-read.
+    /// Frames with more cells than a slot holds are cut off at the slot size
+    /// (`sharedLayoutBufferLength`).
+    #[wasm_bindgen(js_name = setSharedLayoutBuffer)]
+    pub fn set_shared_layout_buffer(
+        &mut self,
+        buffer: Option<js_sys::SharedArrayBuffer>,
+    ) -> Result<(), JsError> {
+        self.shared_layout = None;
+        let Some(buffer) = buffer else {
+            return Ok(());
+        };
+        let byte_len = buffer.byte_length() as usize;
+        if layout_buffer::shared_slot_cells(byte_len) == 0 {
+            return Err(JsError::new(
+                "shared layout buffer is too small for one cell per slot",
+            ));
+        }
+        let header = js_sys::Int32Array::new_with_byte_offset_and_length(
+            &buffer,
+            0,
+            layout_buffer::SHARED_HEADER_WORDS as u32,
+        );
+        header.fill(0, 0, layout_buffer::SHARED_HEADER_WORDS as u32);
+        self.shared_layout = Some(SharedLayout {
+            header,
+            cells: js_sys::Float32Array::new(&buffer),
+            byte_len,
+        });
+        Ok(())
+    }
+
+    /// Byte length of a `setSharedLayoutBuffer` buffer with room for
+    /// `cell_count` layout cells per slot.
+    #[wasm_bindgen(js_name = sharedLayoutBufferLength)]
+    pub fn shared_layout_buffer_length(cell_count: usize) -> usize {
+        layout_buffer::shared_buffer_len(cell_count)
+    }
+
+    /// Display text of the layout buffer's data cells, formatted in Rust by the
+    /// column formats (`setColumnFormat`), concatenated into one string. Cell `i`'s
+    /// text is `text.slice(offsets[i], offsets[i + 1])` with the offsets from
+    /// `getFormattedOffsetsInfo`; header, footer, group, and area cells are empty.
+    #[wasm_bindgen(js_name = getFormattedSlice)]
+    pub fn get_formatted_slice(&mut self) -> String {
+        let header_row_count = self.last_frame.as_ref().map_or(0, |f| f.header_row_count);
+        let view = self.columnar.view_indices();
+        let mut text = String::new();
+        layout_buffer::write_cell_texts(
+            &self.layout_buf,
+            0..self.layout_cell_count,
+            header_row_count,
+            |pos, col, out| {
+                if let Some(&row) = view.get(pos) {
+                    self.columnar.write_cell_text(col, row as usize, out);
+                }
+            },
+            &mut text,
+            &mut self.text_offsets,
+        );
+        text
+    }
+
+    /// Return [pointer_offset, u32_count] of the `getFormattedSlice` text offsets
+    /// (layout buffer cell count + 1). JS creates:
+    /// `new Uint32Array(wasmMemory.buffer, offset, len)`
+    #[wasm_bindgen(js_name = getFormattedOffsetsInfo)]
+    pub fn get_formatted_offsets_info(&self) -> Vec<usize> {
+        vec![self.text_offsets.as_ptr() as usize, self.text_offsets.len()]
+    }
+
+    /// Data visualization of the layout buffer's cells (`setDataVisualization`):
+    /// returns [values_offset, colors_offset, cell_count] for a Float32Array of
+    /// each cell's 0..1 place in its column's range (NaN = none, e.g. the
+    /// length of a data bar) and a Uint32Array of packed `0xRRGGBBAA` color-scale
+    /// colors (0 = none).
+    #[wasm_bindgen(js_name = getDataVisualizationInfo)]
+    pub fn get_data_visualization_info(&mut self) -> Vec<usize> {
+        self.columnar.resolve_data_visualizations();
+        let header_row_count = self.last_frame.as_ref().map_or(0, |f| f.header_row_count);
+        let view = self.columnar.view_indices();
+        layout_buffer::write_data_visuals(
+            &self.layout_buf,
+            0..self.layout_cell_count,
+            header_row_count,
+            |pos, col| {
+                view.get(pos).map_or((f32::NAN, 0), |&row| {
+                    let (value, color) = self.columnar.data_visual(col, row as usize);
+                    (value as f32, color)
+                })
+            },
+            &mut self.visual_values,
+            &mut self.visual_colors,
+        );
+        vec![
+            self.visual_values.as_ptr() as usize,
+            self.visual_colors.as_ptr() as usize,
+            self.layout_cell_count,
+        ]
+    }
+
+    /// Where the layout buffer's cell texts (as `getFormattedSlice`) need an
+    /// ellipsis to fit their cell's content box: returns [pointer_offset, length]
+    /// of a Uint32Array of `[truncated, cut]` per cell. Draw
+    /// `text.slice(0, cut)`, then "…" when `truncated` (e.g. only then show a
+    /// tooltip); `cut` never splits a character and drops trailing whitespace.
+    /// Text is measured as `charWidth` px per character when given, else by
+    /// `setTextWidthMeasure` (or an estimate), in unzoomed px.
+    #[wasm_bindgen(js_name = getTruncationInfo)]
+    pub fn get_truncation_info(&mut self, char_width: Option<f64>) -> Vec<usize> {
+        let header_row_count = self.last_frame.as_ref().map_or(0, |f| f.header_row_count);
+        let view = self.columnar.view_indices();
+        let callback = self.text_width_measure.as_ref();
+        let measure = |text: &str| {
+            char_width.map_or_else(
+                || text_width(callback, text),
+                |w| text.chars().count() as f64 * w,
+            )
+        };
+        let ellipsis_width = measure("\u{2026}");
+        let zoom = self.zoom;
+        layout_buffer::write_truncations(
+            &self.layout_buf,
+            0..self.layout_cell_count,
+            header_row_count,
+            |pos, col, out| {
+                if let Some(&row) = view.get(pos) {
+                    self.columnar.write_cell_text(col, row as usize, out);
+                }
+            },
+            |text, width| {
+                text_wrap::truncate_at(text, f64::from(width) / zoom, ellipsis_width, measure)
+            },
+            &mut self.truncations,
+        );
+        vec![self.truncations.as_ptr() as usize, self.truncations.len()]
+    }
+
+    /// Lines of the layout buffer's cells in `setAutoHeight` columns, broken as
+    /// their row heights were estimated (by `charWidth`, or word-wrapped by
+    /// `setTextWidthMeasure` widths when set; keep a `setTextMeasure` height
+    /// callback consistent with it) to the column's `textWidth`. Returns
+    /// [pointer_offset, length] of a Float32Array of `[cell, start, end, y]` per
+    /// line: the line is `text.slice(start, end)` of the cell's
+    /// `getFormattedSlice` text, drawn with `textBaseline = "middle"` at `y`,
+    /// `lineHeight` apart and placed by the column's vertical alignment.
+    #[wasm_bindgen(js_name = getWrappedLinesInfo)]
+    pub fn get_wrapped_lines_info(&mut self) -> Vec<usize> {
+        self.wrapped_lines.clear();
+        if let (Some(frame), Some(auto)) = (&self.last_frame, self.columnar.auto_height().cloned())
+        {
+            let line_height = (auto.line_height * self.zoom) as f32;
+            let mut text = String::new();
+            let mut lines = Vec::new();
+            let cells = layout_buffer::data_cells(
+                &self.layout_buf,
+                0..self.layout_cell_count,
+                frame.header_row_count,
+            );
+            for (cell, pos, col) in cells {
+                let (Some(column), Some(&row)) = (
+                    auto.columns.iter().find(|c| c.column_index == col),
+                    self.columnar.view_indices().get(pos),
+                ) else {
+                    continue;
+                };
+                text.clear();
+                self.columnar.write_cell_text(col, row as usize, &mut text);
+                lines.clear();
+                self.columnar
+                    .wrap_auto_height_text(&text, column.text_width, &mut lines);
+                let align = frame
+                    .columns
+                    .get(col)
+                    .map_or_else(VerticalAlign::default, |c| c.vertical_align);
+                let block = (lines.len() - 1) as f32 * line_height;
+                let first = self.layout_buf
+                    [cell * layout_buffer::LAYOUT_STRIDE + layout_buffer::FIELD_BASELINE]
+                    - match align {
+                        VerticalAlign::Top => 0.0,
+                        VerticalAlign::Middle => block / 2.0,
+                        VerticalAlign::Bottom => block,
+                    };
+                let (mut offset, mut scanned) = (0, 0);
+                for (i, line) in lines.iter().enumerate() {
+                    offset += text[scanned..line.start].encode_utf16().count();
+                    let start = offset;
+                    offset += text[line.clone()].encode_utf16().count();
+                    scanned = line.end;
+                    let y = (i as f32).mul_add(line_height, first);
+                    self.wrapped_lines
+                        .extend([cell as f32, start as f32, offset as f32, y]);
+                }
+            }
+        }
+        vec![
+            self.wrapped_lines.as_ptr() as usize,
+            self.wrapped_lines.len(),
+        ]
+    }
+
+    /// Sparkline series of a list column for view positions `start..end`: each
+    /// row's values averaged into `bucket_count` buckets (NaN = no values).
+    /// Returns [pointer_offset, row_count] for a Float32Array of
+    /// `row_count * bucket_count` values, row by row.
+    #[wasm_bindgen(js_name = getSparklineData)]
+    pub fn get_sparkline_data(
+        &mut self,
+        start: usize,
+        end: usize,
+        col_idx: usize,
+        bucket_count: usize,
+    ) -> Vec<usize> {
+        self.sparkline_values.clear();
+        self.columnar.sparkline_data(
+            start..end,
+            col_idx,
+            bucket_count,
+            &mut self.sparkline_values,
+        );
+        let row_count = self
+            .sparkline_values
+            .len()
+            .checked_div(bucket_count)
+            .unwrap_or(0);
+        vec![self.sparkline_values.as_ptr() as usize, row_count]
+    }
+
+    /// Grid line positions of the layout buffer, for drawing rules without
+    /// deriving them from cell rects: returns [pointer_offset, column_count,
+    /// row_count] for a Float32Array of `column_count` x positions (distinct left
+    /// and right edges of the header cells) followed by `row_count` y positions
+    /// (distinct top and bottom edges of the header, footer, row, and area cells),
+    /// each ascending, in the buffer's coordinates. Edges of pinned and scrolled
+    /// columns are listed together.
+    #[wasm_bindgen(js_name = getRuleBufferInfo)]
+    pub fn get_rule_buffer_info(&mut self) -> Vec<usize> {
+        let header_cells = self.last_frame.as_ref().map_or(0, |f| f.header_cells);
+        let columns = layout_buffer::write_rules(
+            &self.layout_buf,
+            self.layout_cell_count,
+            header_cells,
+            &mut self.rule_buf,
+        );
+        vec![
+            self.rule_buf.as_ptr() as usize,
+            columns,
+            self.rule_buf.len() - columns,
+        ]
+    }
+
+    /// Header chrome of the layout buffer's header cells, for drawing sort
+    /// indicators, filter marks, and resize handles from buffers alone: returns
+    /// [pointer_offset, header_cell_count] for a Float32Array of `HEADER_STRIDE`
+    /// (4) fields per header cell, in buffer order: sort (0 = unsorted, 1 =
+    /// ascending, 2 = descending), 1-based sort priority (0 = unsorted), filter
+    /// active (0/1), and the x of the resize handle (the cell's right edge).
+    #[wasm_bindgen(js_name = getHeaderInfo)]
+    pub fn get_header_info(&mut self) -> Vec<usize> {
+        let header_cells = self.last_frame.as_ref().map_or(0, |f| f.header_cells);
+        let columnar = &self.columnar;
+        layout_buffer::write_header_meta(
+            &self.layout_buf,
+            self.layout_cell_count,
+            header_cells,
+            |col| {
+                let (sort, priority) = match columnar.column_sort(col) {
+                    Some((SortDirection::Ascending, priority)) => (1.0, priority as f32),
+                    Some((SortDirection::Descending, priority)) => (2.0, priority as f32),
+                    None => (0.0, 0.0),
+                };
+                [
+                    sort,
+                    priority,
+                    f32::from(u8::from(columnar.column_filtered(col))),
+                ]
+            },
+            &mut self.header_meta,
+        );
+        vec![
+            self.header_meta.as_ptr() as usize,
+            self.header_meta.len() / layout_buffer::HEADER_STRIDE,
+        ]
+    }
+
+    /// Rectangle of the cell at view position `row`, table column `col`, as the
+    /// last frame's layout places it, whether or not that frame laid the row out
+    /// (e.g. to position an editor or popover over an off-screen cell): returns
+    /// [x, y, width, height] in the layout buffer's coordinates, or an empty array
+    /// past the view, for a column the frame didn't lay out, or before a frame with
+    /// data rows. Transposed mode is not supported.
+    #[wasm_bindgen(js_name = getCellRect)]
+    pub fn get_cell_rect(&mut self, row: usize, col: usize) -> Vec<f64> {
+        self.row_rect(row, Some(col))
+    }
+
+    /// Bounds of the cells of view position `row`, like `getCellRect`.
+    #[wasm_bindgen(js_name = getRowRect)]
+    pub fn get_row_rect(&mut self, row: usize) -> Vec<f64> {
+        self.row_rect(row, None)
+    }
+
+    /// Geometry of custom vertical and horizontal scrollbars for the last frame,
+    /// from the engine's content size, viewport, and scroll offsets, in output px:
+    /// returns [v_track_start, v_track_length, v_thumb_start, v_thumb_length,
+    /// v_max_scroll, h_track_start, h_track_length, h_thumb_start,
+    /// h_thumb_length, h_max_scroll], or an empty array before the first frame.
+    ///
+    /// Tracks span the scrolling band: below the header and pinned top rows and
+    /// above pinned bottom rows and a sticky footer vertically, between the pinned
+    /// column regions horizontally. The thumb covers the band's share of the
+    /// scrollable content, at least `minThumb` px (default 0) and at most the
+    /// track, and its length is 0 when nothing scrolls.
+    #[wasm_bindgen(js_name = getScrollbarInfo)]
+    pub fn get_scrollbar_info(&mut self, min_thumb: Option<f64>) -> Vec<f64> {
+        let Some(frame) = &self.last_frame else {
+            return Vec::new();
+        };
+        let (viewport, scroll_top, pinned_top, pinned_bottom) = (
+            frame.viewport.clone(),
+            frame.scroll_top,
+            frame.pinned_top,
+            frame.pinned_bottom,
+        );
+        let row_height = self.last_row_height;
+        let rows = self.columnar.view_indices().len();
+        let body_height = self.columnar.view_row_offset(rows, row_height);
+        let top_height = self
+            .columnar
+            .view_row_offset(pinned_top.min(rows), row_height);
+        let bottom_height = body_height
+            - self
+                .columnar
+                .view_row_offset(rows.saturating_sub(pinned_bottom), row_height);
+        let footer_height = f64::from(viewport.footer_height);
+        let sticky_footer = if viewport.sticky_footer {
+            footer_height
+        } else {
+            0.0
+        };
+        let content_height = self.last_header_height + body_height + footer_height;
+        let height = f64::from(viewport.height);
+        let track_top = self.last_header_height + top_height;
+
+        let (pinned_left, pinned_right) = self.layout.pinned_widths();
+        let width = f64::from(viewport.width);
+        let track_left = f64::from(pinned_left);
+
+        let min_thumb = min_thumb.unwrap_or(0.0);
+        let mut info = Vec::with_capacity(10);
+        for (start, length, scroll, max_scroll) in [
+            (
+                track_top,
+                height - track_top - bottom_height - sticky_footer,
+                scroll_top,
+                content_height - height,
+            ),
+            (
+                track_left,
+                width - track_left - f64::from(pinned_right),
+                f64::from(viewport.scroll_left),
+                f64::from(self.layout.content_width()) - width,
+            ),
+        ] {
+            let [start, length, scroll, max_scroll] =
+                [start, length.max(0.0), scroll, max_scroll.max(0.0)].map(|v| v * self.zoom);
+            let (thumb_start, thumb_length) = if max_scroll > 0.0 && length > 0.0 {
+                let thumb = (length / (length + max_scroll) * length)
+                    .max(min_thumb)
+                    .min(length);
+                let fraction = (scroll / max_scroll).clamp(0.0, 1.0);
+                (fraction.mul_add(length - thumb, start), thumb)
+            } else {
+                (start, 0.0)
+            };
+            info.extend([start, length, thumb_start, thumb_length, max_scroll]);
+        }
+        info
+    }
+
+    /// Layout buffer cells whose geometry differs from the frame before the last
+    /// `updateViewportColumnar` (or `promotePredictedLayout`), for renderers that
+    /// keep one element per cell: returns [translate, dx, dy, moved_count,
+    /// ...moved, ...added]. `moved` cells have a different x, y, width, or height
+    /// than the previous cell with the same row, column, and region; `added` cells
+    /// had none. `translate` = 1 when every moved cell moved by (dx, dy) without
+    /// resizing (a scroll frame), so one transform can replace per-cell writes.
+    /// Needs `setTrackChangedCells(true)`; without it every cell counts as added.
+    #[wasm_bindgen(js_name = getChangedCells)]
+    pub fn get_changed_cells(&self) -> Vec<f64> {
+        let changes = layout_buffer::changed_cells(
+            &self.previous_buf,
+            self.previous_cell_count,
+            &self.layout_buf,
+            self.layout_cell_count,
+        );
+        let (dx, dy) = changes.translate.unwrap_or_default();
+        let mut out = vec![
+            f64::from(u8::from(changes.translate.is_some())),
+            f64::from(dx),
+            f64::from(dy),
+            changes.moved.len() as f64,
+        ];
+        out.extend(
+            changes
+                .moved
+                .iter()
+                .chain(&changes.added)
+                .map(|&c| c as f64),
+        );
+        out
+    }
+
+    /// Keep a copy of the previous frame's layout buffer for `getChangedCells`,
+    /// or stop (the default) to skip copying the buffer every frame.
+    #[wasm_bindgen(js_name = setTrackChangedCells)]
+    pub fn set_track_changed_cells(&mut self, enabled: bool) {
+        self.track_changed_cells = enabled;
+        self.previous_buf = Vec::new();
+        self.previous_cell_count = 0;
+    }
+
+    /// Flag the data cells of view positions `startRow..=endRow` × table columns
+    /// `startCol..=endCol` (all columns when omitted) with `FLAG_SELECTED` in the
+    /// layout buffer, from an array of `{ startRow, endRow?, startCol?, endCol? }`
+    /// replacing the previous selection.
+    #[wasm_bindgen(js_name = setSelectedCells)]
+    pub fn set_selected_cells(&mut self, ranges: JsValue) -> Result<(), JsError> {
+        let ranges: Vec<JsCellRange> = serde_wasm_bindgen::from_value(ranges)?;
+        self.selection = ranges.iter().map(JsCellRange::bounds).collect();
+        Ok(())
+    }
+
+    /// Set how selection steps pick cells: `"row"` (whole rows, the default),
+    /// `"cell"` (single cells), or `"range"` (rectangles), with `multi` letting
+    /// additive steps add to the selection. Clears the selection when it changes.
+    #[wasm_bindgen(js_name = setSelectionMode)]
+    pub fn set_selection_mode(&mut self, mode: &str, multi: bool) -> Result<(), JsError> {
+        let mode = SelectionMode::parse(mode).map_err(|e| JsError::new(&e))?;
+        self.selection_model.set_mode(mode, multi);
+        self.emit_selection_changed();
+        Ok(())
+    }
+
+    /// Select the cell at view position `row`, table column `col` (its row in row
+    /// mode), replacing the selection unless `additive` in multi mode. Selected
+    /// cells are kept by data row, so they follow sorts and filters, and get
+    /// `FLAG_SELECTED` in the layout buffer. Group header rows can't be selected.
+    #[wasm_bindgen(js_name = selectCell)]
+    pub fn select_cell(&mut self, row: usize, col: usize, additive: Option<bool>) {
+        if let Some(data_row) = self.columnar.view_data_row(row) {
+            self.selection_model.select_cell(
+                self.columnar.row_count,
+                data_row,
+                col,
+                additive.unwrap_or(false),
+            );
+            self.emit_selection_changed();
+        }
+    }
+
+    /// Select view positions `startRow..=endRow` (either way round, anchored at
+    /// `startRow`) × table columns `startCol..=endCol`, as shaped by the selection
+    /// mode, without group header rows; see `selectCell`.
+    #[wasm_bindgen(js_name = selectRange)]
+    pub fn select_range(
+        &mut self,
+        start_row: usize,
+        end_row: usize,
+        start_col: usize,
+        end_col: usize,
+        additive: Option<bool>,
+    ) {
+        let last = self.columnar.view_indices().len().saturating_sub(1);
+        let (from, to) = (start_row.min(last), end_row.min(last));
+        let mut rows: Vec<u32> = self
+            .columnar
+            .view_data_rows(from.min(to)..from.max(to) + 1)
+            .map(|(_, row)| row)
+            .collect();
+        if from > to {
+            rows.reverse();
+        }
+        self.selection_model.select_range(
+            self.columnar.row_count,
+            &rows,
+            start_col.min(end_col)..=start_col.max(end_col),
+            additive.unwrap_or(false),
+        );
+        self.emit_selection_changed();
+    }
+
+    /// Deselect the row at view position `row` when all of it is selected, else
+    /// select it (alone in single mode). Group header rows are ignored.
+    #[wasm_bindgen(js_name = toggleRow)]
+    pub fn toggle_row(&mut self, row: usize) {
+        if let Some(data_row) = self.columnar.view_data_row(row) {
+            self.selection_model
+                .toggle_row(self.columnar.row_count, data_row);
+            self.emit_selection_changed();
+        }
+    }
+
+    /// Select every row passing the filters, across all pages (multi mode only).
+    #[wasm_bindgen(js_name = selectAllFiltered)]
+    pub fn select_all_filtered(&mut self) {
+        self.columnar.rebuild_view();
+        self.emit_view_changed();
+        self.selection_model
+            .select_all(self.columnar.row_count, self.columnar.filtered_rows());
+        self.emit_selection_changed();
+    }
+
+    #[wasm_bindgen(js_name = clearSelection)]
+    pub fn clear_selection(&mut self) {
+        self.selection_model.clear();
+        self.emit_selection_changed();
+    }
+
+    /// Totals over the selected cells, each counted once, including rows filtered
+    /// out of view: returns [cell_count, row_count, sum, mean, min, max], with
+    /// NaN aggregates when no selected cell is numeric.
+    #[wasm_bindgen(js_name = getSelectionSummary)]
+    pub fn get_selection_summary(&self) -> Vec<f64> {
+        let summary = self.selection_model.summary(&self.columnar);
+        let cells = summary.cells;
+        vec![
+            cells.value(Aggregate::Count),
+            summary.rows as f64,
+            cells.value(Aggregate::Sum),
+            cells.value(Aggregate::Mean),
+            cells.value(Aggregate::Min),
+            cells.value(Aggregate::Max),
+        ]
+    }
+
+    /// Export cells as `"tsv"`, `"csv"`, or `"json"` text in one call, for copy
+    /// and download. `scope` is `"view"` (every view row), `"selection"` (rows and
+    /// columns with selected cells, from `setSelectedCells` or the selection model,
+    /// other cells left empty), or a `{ startRow, endRow?, startCol?, endCol? }`
+    /// range of view positions × table columns.
+    ///
+    /// Rows follow the view order and columns the on-screen order of the last
+    /// frame, without hidden columns; cells are written with their display
+    /// formats. `headers` (by table column) adds a header line, or keys JSON rows.
+    #[wasm_bindgen(js_name = exportRange)]
+    pub fn export_range(
+        &mut self,
+        format: &str,
+        scope: JsValue,
+        headers: JsValue,
+    ) -> Result<String, JsError> {
+        let format = ExportFormat::parse(format).map_err(|e| JsError::new(&e))?;
+        let headers: Option<Vec<String>> = serde_wasm_bindgen::from_value(headers)?;
+        self.columnar.rebuild_view();
+        let order = self.screen_columns();
+        let view = self.columnar.view_indices();
+        let selected = |pos: usize, col: usize| {
+            self.selection
+                .iter()
+                .any(|(rows, cols)| rows.contains(&pos) && cols.contains(&col))
+                || self.selection_model.contains(view[pos] as usize, col)
+        };
+        // Group header rows repeat their group's first data row
+        let data_positions =
+            |range: Range<usize>| self.columnar.view_data_rows(range).map(|(pos, _)| pos);
+        let (positions, cols, selection_only): (Vec<usize>, Vec<usize>, bool) =
+            match scope.as_string().as_deref() {
+                Some("view") => (data_positions(0..view.len()).collect(), order, false),
+                Some("selection") => {
+                    let positions: Vec<usize> = data_positions(0..view.len())
+                        .filter(|&pos| order.iter().any(|&col| selected(pos, col)))
+                        .collect();
+                    let cols = order
+                        .into_iter()
+                        .filter(|&col| positions.iter().any(|&pos| selected(pos, col)))
+                        .collect();
+                    (positions, cols, true)
+                }
+                Some(scope) => {
+                    return Err(JsError::new(&format!("unknown export scope: {scope:?}")));
+                }
+                None => {
+                    let range: JsCellRange = serde_wasm_bindgen::from_value(scope)?;
+                    let (rows, cols) = range.bounds();
+                    let positions = data_positions(*rows.start()..rows.end().saturating_add(1));
+                    let cols = order.into_iter().filter(|col| cols.contains(col));
+                    (positions.collect(), cols.collect(), false)
+                }
+            };
+        let rows: Vec<u32> = positions.iter().map(|&pos| view[pos]).collect();
+        Ok(export::export(
+            &self.columnar,
+            format,
+            &ExportRange {
+                rows: &rows,
+                cols: &cols,
+                headers: headers.as_deref(),
+                include: |i: usize, col| !selection_only || selected(positions[i], col),
+            },
+        ))
+    }
+
+    /// Snapshot view rows for an accessible mirror of the grid (e.g. an ARIA grid
+    /// or live region): `{ rowCount, colCount, columns, rows }`, where columns
+    /// carry their 1-based on-screen `colIndex`, `name` (by table column from
+    /// `headers`, else empty), and `aria-sort` value, and rows their 1-based
+    /// `rowIndex` over the rows passing the filters across pages, data `row`,
+    /// `selected`, and the display text of each column. Group header rows are
+    /// left out.
+    ///
+    /// Rows are the rendered rows of the last frame (pinned ones included), or the
+    /// view positions × table columns of a `{ startRow, endRow?, startCol?, endCol? }`
+    /// range; columns follow the on-screen order, without hidden columns.
+    #[wasm_bindgen(js_name = getAriaSnapshot)]
+    pub fn get_aria_snapshot(&self, range: JsValue, headers: JsValue) -> Result<JsValue, JsError> {
+        let range: Option<JsCellRange> = serde_wasm_bindgen::from_value(range)?;
+        let headers: Option<Vec<String>> = serde_wasm_bindgen::from_value(headers)?;
+        let view = self.columnar.view_indices();
+        let order = self.screen_columns();
+        let col_count = order.len();
+        let (positions, cols): (Vec<usize>, Vec<(usize, usize)>) = if let Some(range) = range {
+            let (rows, cols) = range.bounds();
+            let end = (*rows.end()).min(view.len().saturating_sub(1));
+            let positions = (*rows.start()..=end).filter(|&pos| pos < view.len());
+            let cols = order
+                .into_iter()
+                .enumerate()
+                .filter(|(_, col)| cols.contains(col));
+            (positions.collect(), cols.collect())
+        } else {
+            let mut positions: Vec<usize> =
+                self.last_frame.as_ref().map_or_else(Vec::new, |frame| {
+                    (0..frame.pinned_top)
+                        .chain(frame.rows.clone())
+                        .chain(view.len().saturating_sub(frame.pinned_bottom)..view.len())
+                        .filter(|&pos| pos < view.len())
+                        .collect()
+                });
+            positions.sort_unstable();
+            positions.dedup();
+            (positions, order.into_iter().enumerate().collect())
+        };
+
+        let sorts = self.columnar.view_state().sort_configs;
+        let columns = cols
+            .iter()
+            .map(|&(i, col)| JsAriaColumn {
+                col_index: i + 1,
+                column: col,
+                name: headers
+                    .as_ref()
+                    .and_then(|headers| headers.get(col))
+                    .cloned()
+                    .unwrap_or_default(),
+                sort: match sorts.iter().find(|s| s.column_index == col) {
+                    Some(s) if s.direction == SortDirection::Descending => "descending",
+                    Some(_) => "ascending",
+                    None => "none",
+                },
+            })
+            .collect();
+        // Group header rows are left out: `rowIndex` counts data rows
+        let rows = positions
+            .into_iter()
+            .filter_map(|pos| Some((pos, self.columnar.view_filtered_index(pos)?)))
+            .map(|(pos, index)| {
+                let row = view[pos] as usize;
+                let selected = |col: usize| {
+                    self.selection
+                        .iter()
+                        .any(|(rows, cols)| rows.contains(&pos) && cols.contains(&col))
+                        || self.selection_model.contains(row, col)
+                };
+                JsAriaRow {
+                    row_index: index + 1,
+                    row,
+                    selected: cols.iter().any(|&(_, col)| selected(col)),
+                    cells: cols
+                        .iter()
+                        .map(|&(_, col)| {
+                            let mut text = String::new();
+                            self.columnar.write_cell_text(col, row, &mut text);
+                            text
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&JsAriaSnapshot {
+            row_count: self.columnar.filtered_total(),
+            col_count,
+            columns,
+            rows,
+        })?)
+    }
+
+    /// Serialize rows as an Arrow IPC stream (schema and one record batch) for
+    /// Arrow readers such as DuckDB-WASM, Perspective, and arrow-js.
+    ///
+    /// Rows are the view's rows passing the filters in sort order, across all
+    /// pages and without group rows, or every data row with `fullTable`. `columns`
+    /// picks table columns (all when omitted), named by `names` (by table column,
+    /// else the column index). Float64, Bool, string, and multi-value string
+    /// columns become `Float64`, `Bool`, `Utf8`, and `List<Utf8>`, with engine
+    /// nulls as Arrow nulls.
+    #[wasm_bindgen(js_name = exportArrowIpc)]
+    pub fn export_arrow_ipc(
+        &mut self,
+        columns: Option<Vec<usize>>,
+        names: JsValue,
+        full_table: Option<bool>,
+    ) -> Result<Vec<u8>, JsError> {
+        let names: Option<Vec<String>> = serde_wasm_bindgen::from_value(names)?;
+        let columns: Vec<(usize, String)> = columns
+            .unwrap_or_else(|| (0..self.columnar.data.len()).collect())
+            .into_iter()
+            .map(|col| {
+                let name = names.as_ref().and_then(|names| names.get(col));
+                (col, name.cloned().unwrap_or_else(|| col.to_string()))
+            })
+            .collect();
+        let all_rows: Vec<u32>;
+        let rows = if full_table.unwrap_or(false) {
+            all_rows = (0..self.columnar.row_count as u32).collect();
+            &all_rows
+        } else {
+            self.columnar.rebuild_view();
+            self.columnar.filtered_rows()
+        };
+        Ok(arrow_ipc::write_ipc_stream(&self.columnar, rows, &columns))
+    }
+
+    /// Export the view as an XLSX workbook (`xlsx` feature), with options
+    /// `{ sheetName?, headers?: string[] (by table column), freezeHeader?: boolean,
+    /// outline?: boolean }` (both true by default).
+    ///
+    /// Columns follow the on-screen order of the last frame without hidden
+    /// columns, at their laid-out widths; the header row and left-pinned columns
+    /// are frozen. Numbers keep their values, with display formats as Excel number
+    /// formats, and rows of groups and tree data get outline levels.
+    #[cfg(feature = "xlsx")]
+    #[wasm_bindgen(js_name = exportXlsx)]
+    pub fn export_xlsx(&mut self, options: JsValue) -> Result<Vec<u8>, JsError> {
+        let options: types::JsXlsxOptions = if options.is_undefined() {
+            types::JsXlsxOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)?
+        };
+        self.columnar.rebuild_view();
+        let widths = self.laid_out_widths();
+        let columns: Vec<XlsxColumn> = self
+            .screen_columns()
+            .into_iter()
+            .map(|col| XlsxColumn {
+                col,
+                header: options.headers.get(col).cloned().unwrap_or_default(),
+                width: widths.get(col).map_or(0.0, |&w| f64::from(w)),
+            })
+            .collect();
+        let frozen_columns = self.last_frame.as_ref().map_or(0, |frame| {
+            columns
+                .iter()
+                .take_while(|c| matches!(frame.columns[c.col].pinned, Some(ColumnPin::Left)))
+                .count()
+        });
+        let sheet = XlsxSheet {
+            name: options.sheet_name,
+            columns,
+            freeze_header: options.freeze_header.unwrap_or(true),
+            frozen_columns,
+            outline: options.outline.unwrap_or(true),
+        };
+        Ok(xlsx::write_xlsx(&self.columnar, &sheet))
+    }
+
+    /// Export the view as a PDF (`pdf` feature), with options `{ title?, headers?:
+    /// string[], subtotals?: (string | null)[] (both by table column), pageWidth?,
+    /// pageHeight?, landscape?: boolean, fontSize? }` (A4 portrait, 9 pt by
+    /// default; sizes in pt).
+    ///
+    /// Columns follow the on-screen order of the last frame without hidden
+    /// columns, at their laid-out widths and alignment (scaled down to fit the
+    /// page); cells show their display text. Each page repeats the header row,
+    /// and group header rows show the `subtotals` aggregates (`sum`, `mean`,
+    /// `min`, `max`, `count`) of their rows.
+    #[cfg(feature = "pdf")]
+    #[wasm_bindgen(js_name = exportPdf)]
+    pub fn export_pdf(&mut self, options: JsValue) -> Result<Vec<u8>, JsError> {
+        let options: types::JsPdfOptions = if options.is_undefined() {
+            types::JsPdfOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)?
+        };
+        self.columnar.rebuild_view();
+        let widths = self.laid_out_widths();
+        let mut columns = Vec::new();
+        for col in self.screen_columns() {
+            let subtotal = match options.subtotals.get(col).cloned().flatten() {
+                Some(name) => Some(Aggregate::parse(&name).map_err(|e| JsError::new(&e))?),
+                None => None,
+            };
+            columns.push(PdfColumn {
+                col,
+                header: options.headers.get(col).cloned().unwrap_or_default(),
+                width: widths.get(col).map_or(0.0, |&w| f64::from(w)),
+                align: self
+                    .last_frame
+                    .as_ref()
+                    .map_or(Align::Left, |frame| frame.columns[col].align),
+                subtotal,
+            });
+        }
+        let mut doc = PdfDocument {
+            title: options.title,
+            columns,
+            ..PdfDocument::default()
+        };
+        if let Some(font_size) = options.font_size {
+            doc.font_size = font_size;
+        }
+        doc.page_width = options.page_width.unwrap_or(doc.page_width);
+        doc.page_height = options.page_height.unwrap_or(doc.page_height);
+        if options.landscape {
+            std::mem::swap(&mut doc.page_width, &mut doc.page_height);
+        }
+        Ok(pdf::write_pdf(&self.columnar, &doc))
+    }
+
+    /// Lay out the whole view on pages of `page_width` x `page_height` px for
+    /// print and PDF export, with options `{ repeatHeader?: boolean, footer?:
+    /// boolean }` (both true by default).
+    ///
+    /// Pages hold whole rows (a row taller than a page gets one of its own) below
+    /// the header, which starts every page unless `repeatHeader` is false, when
+    /// later pages lay it out at height 0. The footer follows the last row. Uses
+    /// the columns, container, and header of the last frame at `page_width`,
+    /// without selection styling. Columns wider than the page are not split.
+    ///
+    /// Returns `[pageCount, ...]` then `[ptr, cellCount, startRow, endRow]` per
+    /// page: a layout buffer like `getLayoutBuffer`'s and its view rows, valid
+    /// until the next call.
+    #[allow(clippy::too_many_lines)]
+    #[wasm_bindgen(js_name = computePrintLayout)]
+    pub fn compute_print_layout(
+        &mut self,
+        page_width: f32,
+        page_height: f32,
+        options: JsValue,
+    ) -> Result<Vec<f64>, JsError> {
+        let options: types::JsPrintOptions = if options.is_undefined() || options.is_null() {
+            types::JsPrintOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)?
+        };
+        if self.transposed.is_some() {
+            return Err(JsError::new(
+                "computePrintLayout: not available when transposed",
+            ));
+        }
+        let Some(inputs) = &self.frame_inputs else {
+            return Err(JsError::new(
+                "computePrintLayout: no frame yet (call updateViewportColumnar first)",
+            ));
+        };
+        let input_columns = inputs.columns.clone();
+        let container = inputs.container.clone();
+        let header_row_count = inputs.header_row_count;
+        let mut viewport = Viewport {
+            width: page_width,
+            height: page_height,
+            scroll_top: 0.0,
+            scroll_left: 0.0,
+            sticky_header: true,
+            sticky_footer: false,
+            ..inputs.viewport.clone()
+        };
+        if !options.footer.unwrap_or(true) {
+            viewport.footer_height = 0.0;
+        }
+        self.columnar.rebuild_view();
+        let (columns, visible, col_ids, header) = self.frame_columns(&input_columns, &mut viewport);
+        let mut layout = LayoutEngine::new();
+        layout.set_header_layout(header);
+        let row_height = f64::from(layout.compute_effective_row_height(
+            &visible,
+            &container,
+            viewport.width,
+            viewport.row_height,
+            viewport.line_height,
+        ));
+
+        let repeat_header = options.repeat_header.unwrap_or(true);
+        let header_height = viewport.header_height;
+        let first_body = f64::from(page_height - header_height);
+        let body = if repeat_header {
+            first_body
+        } else {
+            f64::from(page_height)
+        };
+        let view_len = self.columnar.view_indices().len();
+        let pages = print::paginate(
+            view_len,
+            first_body,
+            body,
+            f64::from(viewport.footer_height),
+            |pos| self.columnar.view_row_offset(pos, row_height),
+        );
+
+        let col_count = visible.len();
+        let footer_height = viewport.footer_height;
+        let no_selection = Selection::default();
+        let mut out = vec![pages.len() as f64];
+        let mut offsets = Vec::with_capacity(pages.len());
+        self.print_buf.clear();
+        for (index, rows) in pages.into_iter().enumerate() {
+            viewport.header_height = if index == 0 || repeat_header {
+                header_height
+            } else {
+                0.0
+            };
+            viewport.footer_height = if rows.end == view_len {
+                footer_height
+            } else {
+                0.0
+            };
+            viewport.scroll_top = self.columnar.view_row_offset(rows.start, row_height);
+            let detail_cells = self
+                .columnar
+                .view_row_layout(row_height)
+                .map_or(0, |(_, areas)| areas.cell_count(rows.clone()));
+            let footer_cells = viewport.footer_cells(col_count);
+            let total_cells = col_count
+                + rows.len() * col_count
+                + detail_cells
+                + footer_cells
+                + rows.len() * cell_items_per_row(&visible);
+            let mut buf = vec![0.0; layout_buffer::buf_len(total_cells)];
+            let mut cell_count = layout.compute_into_buffer(
+                &visible,
+                &viewport,
+                &container,
+                rows.clone(),
+                &mut buf,
+                header_row_count,
+            );
+            layout_buffer::remap_columns(&mut buf, 0..cell_count, &col_ids);
+            cell_count = apply_row_layout(
+                &mut self.columnar,
+                &mut buf,
+                col_count + footer_cells..cell_count,
+                header_row_count,
+                row_height,
+                viewport.width,
+            );
+            place_footer(&mut buf, &viewport, col_count, cell_count);
+            cell_count += layout.append_cell_items(
+                &columns,
+                viewport.line_height,
+                &mut buf,
+                col_count + footer_cells..cell_count,
+                cell_count,
+            );
+            write_frame_baselines(
+                &columns,
+                self.header_layout.as_ref(),
+                viewport.line_height,
+                &mut buf,
+                col_count..cell_count,
+            );
+            style_cells(
+                &mut self.columnar,
+                &[],
+                &no_selection,
+                &mut buf,
+                0..cell_count,
+                header_row_count,
+            );
+            output_cells(&mut buf, 0..cell_count, 1.0, self.pixel_ratio);
+            offsets.push(self.print_buf.len());
+            self.print_buf
+                .extend_from_slice(&buf[..layout_buffer::buf_len(cell_count)]);
+            out.extend([0.0, cell_count as f64, rows.start as f64, rows.end as f64]);
+        }
+        for (page, offset) in offsets.into_iter().enumerate() {
+            out[1 + page * 4] = self.print_buf[offset..].as_ptr() as usize as f64;
+        }
+        Ok(out)
+    }
+
+    /// Anchor overlay rectangles to cell ranges, replacing earlier ones: an array of
+    /// `{ id, startRow, endRow?, startCol?, endCol?, anchor? }` covering view
+    /// positions `startRow..=endRow` (data rows with `anchor: "data"`, so the
+    /// overlay follows its rows through sorts and filters) and table columns
+    /// `startCol..=endCol` (all columns when omitted).
+    #[wasm_bindgen(js_name = setOverlays)]
+    pub fn set_overlays(&mut self, overlays: JsValue) -> Result<(), JsError> {
+        let overlays: Vec<JsOverlay> = serde_wasm_bindgen::from_value(overlays)?;
+        self.overlays = overlays
+            .into_iter()
+            .map(|o| Overlay {
+                anchor: match o.anchor.as_deref() {
+                    Some("data") => OverlayAnchor::Data,
+                    _ => OverlayAnchor::View,
+                },
+                rows: o.range.bounds().0,
+                cols: o.range.bounds().1,
+                id: o.id,
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// The overlays resolved against the layout buffer: returns [pointer_offset,
+    /// count] for a Float32Array of `count` × [id, x, y, width, height, region],
+    /// each the bounding box of the overlay's laid-out data cells in the buffer's
+    /// coordinates, with the pinned flags of those cells as its region. Overlays
+    /// with no laid-out cell (scrolled out, filtered away) are left out.
+    #[wasm_bindgen(js_name = getOverlayBufferInfo)]
+    pub fn get_overlay_buffer_info(&mut self) -> Vec<usize> {
+        let header_row_count = self.last_frame.as_ref().map_or(1, |f| f.header_row_count);
+        let view = self.columnar.view_indices();
+        let count = overlay::write_overlays(
+            &self.overlays,
+            &self.layout_buf,
+            self.layout_cell_count,
+            header_row_count,
+            |pos| view.get(pos).map(|&row| row as usize),
+            &mut self.overlay_buf,
+        );
+        vec![self.overlay_buf.as_ptr() as usize, count]
+    }
+
+    /// The cells covered by a drag from `(x1, y1)` to `(x2, y2)` in layout buffer
+    /// coordinates, for marquee selection and fill handles: returns [startRow,
+    /// startCol, endRow, endCol] with view positions top to bottom and the table
+    /// columns of the leftmost and rightmost covered cells. Pinned cells win over
+    /// scrolled cells beneath them, and points outside the body clamp to its
+    /// nearest cell. Empty when no data cell is laid out.
+    #[wasm_bindgen(js_name = hitTestRange)]
+    pub fn hit_test_range(&self, x1: f32, y1: f32, x2: f32, y2: f32) -> Vec<usize> {
+        let header_row_count = self.last_frame.as_ref().map_or(1, |f| f.header_row_count);
+        layout_buffer::hit_test_range(
+            &self.layout_buf,
+            0..self.layout_cell_count,
+            header_row_count,
+            (x1, y1),
+            (x2, y2),
+        )
+        .map_or_else(Vec::new, Vec::from)
+    }
+
+    /// Return [pointer_offset, f32_count] for the `predictNextSlice` back buffer.
+    #[wasm_bindgen(js_name = getPredictedLayoutBufferInfo)]
+    pub fn get_predicted_layout_buffer_info(&self) -> Vec<usize> {
+        vec![
+            self.predicted_buf.as_ptr() as usize,
+            self.predicted_cell_count * layout_buffer::LAYOUT_STRIDE,
+        ]
+    }
+
     /// Get Float64 column pointer info: [offset, length].
     /// Returns empty vec if column is not Float64.
     #[wasm_bindgen(js_name = getColumnFloat64Info)]
@@ -69,6 +1445,8 @@ impl TableEngine {
     #[wasm_bindgen(js_name = initColumnar)]
     pub fn init_columnar(&mut self, col_count: usize, row_count: usize) {
         self.columnar.init(col_count, row_count);
+        self.editor.cancel();
+        self.history.clear();
     }
 
     /// Ingest a Float64 column directly from a TypedArray (no serde).
@@ -96,6 +1474,30 @@ impl TableEngine {
         Ok(())
     }
 
+    /// Ingest a multi-value string column (tags): unique strings via JsValue, per-row
+    /// list boundaries via `offsets` (`rowCount + 1` entries), and intern IDs via `ids`.
+    #[wasm_bindgen(js_name = ingestStringListColumn)]
+    pub fn ingest_string_list_column(
+        &mut self,
+        col_idx: usize,
+        unique_strings: JsValue,
+        offsets: &[u32],
+        ids: &[u32],
+    ) -> Result<(), JsError> {
+        let unique: Vec<String> = serde_wasm_bindgen::from_value(unique_strings)?;
+        self.columnar
+            .set_column_string_list(col_idx, &unique, offsets, ids);
+        Ok(())
+    }
+
+    /// Ingest a numeric series column (e.g. for sparklines): per-row series
+    /// boundaries via `offsets` (`rowCount + 1` entries) into `values`.
+    #[wasm_bindgen(js_name = ingestFloat64ListColumn)]
+    pub fn ingest_float64_list_column(&mut self, col_idx: usize, offsets: &[u32], values: &[f64]) {
+        self.columnar
+            .set_column_float64_list(col_idx, offsets, values);
+    }
+
     /// Finalize columnar ingestion (marks view dirty).
     #[wasm_bindgen(js_name = finalizeColumnar)]
     pub fn finalize_columnar(&mut self) {
@@ -137,275 +1539,2037 @@ impl TableEngine {
         Ok(())
     }
 
-    /// Finalize append (marks view dirty).
-    #[wasm_bindgen(js_name = finalizeAppendColumnar)]
-    pub fn finalize_append_columnar(&mut self) {
-        self.columnar.finalize_append();
-    }
-
-    // ── Hot path ──────────────────────────────────────────────────────
-
-    /// Set sort configuration on the columnar store.
-    #[wasm_bindgen(js_name = setColumnarSort)]
-    pub fn set_columnar_sort(&mut self, configs: JsValue) -> Result<(), JsError> {
-        let configs: Vec<JsSortConfig> = serde_wasm_bindgen::from_value(configs)?;
-        let configs: Vec<SortConfig> = configs
-            .into_iter()
-            .map(|c| SortConfig {
-                column_index: c.column_index,
-                direction: match c.direction.as_str() {
-                    "Descending" | "desc" => SortDirection::Descending,
-                    _ => SortDirection::Ascending,
-                },
-            })
-            .collect();
-        self.columnar.set_sort(configs);
+    /// Append multi-value string rows with intern table merge.
+    #[wasm_bindgen(js_name = appendStringListColumn)]
+    pub fn append_string_list_column(
+        &mut self,
+        col_idx: usize,
+        offset: usize,
+        unique_strings: JsValue,
+        offsets: &[u32],
+        ids: &[u32],
+    ) -> Result<(), JsError> {
+        let unique: Vec<String> = serde_wasm_bindgen::from_value(unique_strings)?;
+        self.columnar
+            .append_column_string_list(col_idx, offset, &unique, offsets, ids);
         Ok(())
     }
 
-    /// Set column filters on the columnar store.
-    #[wasm_bindgen(js_name = setColumnarFilters)]
-    pub fn set_columnar_filters(&mut self, filters: JsValue) -> Result<(), JsError> {
-        let filters: Vec<JsColumnFilter> = serde_wasm_bindgen::from_value(filters)?;
-        let filters: Vec<ColumnFilter> = filters
-            .into_iter()
-            .map(|f| ColumnFilter {
-                column_index: f.column_index,
-                op: match f.op.as_str() {
-                    "neq" => FilterOp::Neq,
-                    "gt" => FilterOp::Gt,
-                    "gte" => FilterOp::Gte,
-                    "lt" => FilterOp::Lt,
-                    "lte" => FilterOp::Lte,
-                    "contains" => FilterOp::Contains,
-                    "startsWith" => FilterOp::StartsWith,
-                    "endsWith" => FilterOp::EndsWith,
-                    _ => FilterOp::Eq,
-                },
-                value: convert_filter_value(&f.value),
-            })
-            .collect();
-        self.columnar.set_column_filters(filters);
-        Ok(())
+    /// Append numeric series rows.
+    #[wasm_bindgen(js_name = appendFloat64ListColumn)]
+    pub fn append_float64_list_column(
+        &mut self,
+        col_idx: usize,
+        offset: usize,
+        offsets: &[u32],
+        values: &[f64],
+    ) {
+        self.columnar
+            .append_column_float64_list(col_idx, offset, offsets, values);
     }
 
-    /// Set global filter on the columnar store.
-    #[wasm_bindgen(js_name = setGlobalFilter)]
-    pub fn set_global_filter(&mut self, query: Option<String>) {
-        self.columnar
-            .set_global_filter(query.map(|q| GlobalFilter { query: q }));
+    /// Finalize append (marks view dirty).
+    #[wasm_bindgen(js_name = finalizeAppendColumnar)]
+    pub fn finalize_append_columnar(&mut self) {
+        self.columnar.finalize_append();
     }
 
-    /// Set pagination state on the columnar store.
-    #[wasm_bindgen(js_name = setPagination)]
-    pub fn set_pagination(&mut self, page_index: Option<u32>, page_size: Option<u32>) {
-        self.columnar.set_pagination(page_index, page_size);
+    // ── Lazy row blocks ───────────────────────────────────────────────
+
+    /// Lazy-row mode: `initColumnar` with the full row count, then load rows in
+    /// blocks of `size`. `updateViewportColumnar` reports the unloaded blocks the
+    /// current slice needs. `undefined` turns it off.
+    #[wasm_bindgen(js_name = setRowBlockSize)]
+    pub fn set_row_block_size(&mut self, size: Option<usize>) {
+        self.columnar.set_row_block_size(size);
     }
 
-    /// Set scroll configuration on the columnar store.
-    #[wasm_bindgen(js_name = setColumnarScrollConfig)]
-    pub fn set_columnar_scroll_config(
-        &mut self,
-        row_height: f64,
-        viewport_height: f64,
-        overscan: usize,
-    ) {
-        self.columnar
-            .set_scroll_config(row_height, viewport_height, overscan);
+    /// Mark a block loaded after writing its rows with the `append*Column` methods at
+    /// offset `block * size`. Finalizes like `finalizeAppendColumnar`.
+    #[wasm_bindgen(js_name = markBlockLoaded)]
+    pub fn mark_block_loaded(&mut self, block: usize) {
+        self.columnar.mark_block_loaded(block);
     }
 
-    /// Rebuild view indices only (for row pinning: call before getViewIndices, then updateViewportColumnar with skipRebuild=true).
-    #[wasm_bindgen(js_name = rebuildView)]
-    pub fn rebuild_view_only(&mut self) {
-        self.columnar.rebuild_view();
+    /// Forget a loaded block so it is requested again.
+    #[wasm_bindgen(js_name = markBlockUnloaded)]
+    pub fn mark_block_unloaded(&mut self, block: usize) {
+        self.columnar.mark_block_unloaded(block);
     }
 
-    /// Unified hot path: rebuild view + virtual slice + layout buffer.
-    /// Returns metadata as Float64Array:
-    /// [cell_count, visible_start, visible_end, total_height, filtered_count, generation, total_count, visible_count, effective_row_height, filtered_total]
-    /// filtered_total ([9]) = row count after filter+sort but before pagination.
-    /// Optional 5th/6th: pinnedTop, pinnedBottom. Optional 7th: skipRebuild (when true, skip rebuild_view; use after rebuildView() for row pinning).
-    #[allow(clippy::too_many_arguments, clippy::too_many_lines)]
-    #[wasm_bindgen(js_name = updateViewportColumnar)]
-    pub fn update_viewport_columnar(
+    // ── Remote row model ──────────────────────────────────────────────
+
+    /// Leave sorting and filtering to a server: sort and filter calls are only
+    /// recorded (`getQueryDescriptor`), and rows are shown in stored order. Load the
+    /// server's result with `setRemoteRowCount` and lazy row blocks; a query change
+    /// forgets every loaded block, so `updateViewportColumnar` requests them again.
+    #[wasm_bindgen(js_name = setRemoteRowModel)]
+    pub fn set_remote_row_model(&mut self, enabled: bool) {
+        self.columnar.set_remote_rows(enabled);
+    }
+
+    /// Set the total row count of the server's result (a changed count forgets
+    /// every loaded block).
+    #[wasm_bindgen(js_name = setRemoteRowCount)]
+    pub fn set_remote_row_count(&mut self, total: usize) {
+        self.columnar.set_remote_row_count(total);
+    }
+
+    /// `{ sort, filters, globalFilter, key }`: the query for the server, with
+    /// filters ordered by column. Compare `key` with the one a block was requested
+    /// for before loading it.
+    #[wasm_bindgen(js_name = getQueryDescriptor)]
+    pub fn get_query_descriptor(&self) -> Result<JsValue, JsError> {
+        let query = self.columnar.query_descriptor();
+        Ok(serde_wasm_bindgen::to_value(&JsQueryDescriptor {
+            state: view_state_to_js(&query.view),
+            key: query.key,
+        })?)
+    }
+
+    // ── Cell updates ──────────────────────────────────────────────────
+
+    /// Overwrite one Float64 cell (NaN = null). The next rebuild re-positions only
+    /// the updated rows when nothing else changed.
+    #[wasm_bindgen(js_name = updateFloat64Cell)]
+    pub fn update_float64_cell(&mut self, col_idx: usize, row: usize, value: f64) {
+        self.columnar.update_float64(col_idx, row, value);
+        self.emit_data_edited("update", [(row, col_idx)]);
+    }
+
+    /// Overwrite one Bool cell (0 = false, 1 = true, NaN = null).
+    #[wasm_bindgen(js_name = updateBoolCell)]
+    pub fn update_bool_cell(&mut self, col_idx: usize, row: usize, value: f64) {
+        self.columnar.update_bool(col_idx, row, value);
+        self.emit_data_edited("update", [(row, col_idx)]);
+    }
+
+    /// Overwrite one string cell (empty string = null).
+    #[wasm_bindgen(js_name = updateStringCell)]
+    pub fn update_string_cell(&mut self, col_idx: usize, row: usize, value: &str) {
+        self.columnar.update_string(col_idx, row, value);
+        self.emit_data_edited("update", [(row, col_idx)]);
+    }
+
+    // ── Keyed upserts ─────────────────────────────────────────────────
+
+    /// Key rows by the values of column `col_idx` for `applyUpserts`
+    /// (`undefined` turns upserts off and drops queued ones).
+    #[wasm_bindgen(js_name = setRowKeyColumn)]
+    pub fn set_row_key_column(&mut self, col_idx: Option<usize>) {
+        self.upserts = col_idx.map(UpsertQueue::new);
+        self.upserted_cells.clear();
+    }
+
+    /// Queue streaming updates: for each of `keys` (numbers or strings), the values
+    /// of `columns`, row by row in `values` (`keys.length * columns.length`; `null`
+    /// = null). Queued updates are coalesced and written at the next frame; unknown
+    /// keys append rows. Rows move only when a filtered or sorted column changes.
+    #[wasm_bindgen(js_name = applyUpserts)]
+    pub fn apply_upserts(
         &mut self,
-        scroll_top: f64,
-        viewport_js: JsValue,
-        columns_js: JsValue,
-        container_js: JsValue,
-        pinned_top_js: Option<f64>,
-        pinned_bottom_js: Option<f64>,
-        skip_rebuild_js: Option<bool>,
-    ) -> Result<Vec<f64>, JsError> {
-        if !skip_rebuild_js.unwrap_or(false) {
-            self.columnar.rebuild_view();
+        keys: &js_sys::Array,
+        columns: &[u32],
+        values: &js_sys::Array,
+    ) -> Result<(), JsError> {
+        let Some(queue) = &mut self.upserts else {
+            return Err(JsError::new("no row key column (setRowKeyColumn)"));
+        };
+        if values.length() as usize != keys.length() as usize * columns.len() {
+            return Err(JsError::new(
+                "values must hold keys.length * columns.length items",
+            ));
+        }
+        let mut values = values.iter();
+        for key in keys.iter() {
+            let key = row_key(&key)?;
+            for (&col, value) in columns.iter().zip(values.by_ref()) {
+                let col = col as usize;
+                queue.push(key.clone(), col, cell_value(&self.columnar, col, &value));
+            }
         }
+        Ok(())
+    }
 
-        let filtered_count = self.columnar.view_indices().len();
-        log::debug!(
-            "[wasm] updateViewport: scroll_top={:.1}, total_rows={}, filtered={}",
-            scroll_top,
-            self.columnar.row_count,
-            self.columnar.view_indices().len(),
-        );
+    /// Insert a row right away (not queued) from `values` by table column (`null`
+    /// = null), holding a row key no other row has. With `at` it goes to that
+    /// view position, switching to a manual row order; otherwise the sort places
+    /// it. Returns the row's version, for reconciling the optimistic insert with
+    /// the server later. The key of a row taken out by `removeRowById` or
+    /// `dedupeKeep` is free again, but the new row is a new data row: the removed
+    /// one keeps its data row index (for `undo`) until the data is reloaded.
+    #[wasm_bindgen(js_name = insertRow)]
+    pub fn insert_row(
+        &mut self,
+        values: &js_sys::Array,
+        at: Option<usize>,
+    ) -> Result<u32, JsError> {
+        let cells: Vec<(usize, CellValue)> = values
+            .iter()
+            .enumerate()
+            .map(|(col, value)| (col, cell_value(&self.columnar, col, &value)))
+            .collect();
+        let col_count = cells.len();
+        let Some(queue) = &mut self.upserts else {
+            return Err(JsError::new("no row key column (setRowKeyColumn)"));
+        };
+        let row = queue
+            .insert(&mut self.columnar, cells)
+            .map_err(|e| JsError::new(&e))?;
+        if let Some(at) = at {
+            self.columnar.place_row(row, at);
+        }
+        self.emit_data_edited("insert", (0..col_count).map(|col| (row, col)));
+        Ok(self.columnar.bump_row_version(row))
+    }
 
-        // 2. Parse viewport + columns + container BEFORE virtual scroll
-        let vp: JsViewport = serde_wasm_bindgen::from_value(viewport_js)?;
-        let cols: Vec<JsColumnLayout> = serde_wasm_bindgen::from_value(columns_js)?;
+    /// Write `values` to `columns` (like `applyUpserts`, but right away) of the
+    /// row keyed `id`. Returns the row's new version, or `undefined` when no row
+    /// has the key or, with `expectedVersion`, the row's version differs (a
+    /// newer change won). The key column is not written.
+    #[wasm_bindgen(js_name = updateRowById)]
+    pub fn update_row_by_id(
+        &mut self,
+        id: &JsValue,
+        columns: &[u32],
+        values: &js_sys::Array,
+        expected_version: Option<u32>,
+    ) -> Result<Option<u32>, JsError> {
+        if values.length() as usize != columns.len() {
+            return Err(JsError::new("values must hold columns.length items"));
+        }
+        let Some((row, key_column)) = self.row_by_id(id, expected_version)? else {
+            return Ok(None);
+        };
+        let mut changed = Vec::new();
+        for (&col, value) in columns.iter().zip(values.iter()) {
+            let col = col as usize;
+            if col == key_column {
+                continue;
+            }
+            let value = cell_value(&self.columnar, col, &value);
+            if editing::write_cell(&mut self.columnar, row, col, value).is_some() {
+                changed.push((row, col));
+            }
+        }
+        self.emit_data_edited("update", changed);
+        Ok(Some(self.columnar.bump_row_version(row)))
+    }
 
-        let container = if container_js.is_undefined() || container_js.is_null() {
-            ContainerLayout::default()
-        } else {
-            let jc: JsContainerLayout = serde_wasm_bindgen::from_value(container_js)?;
-            convert_container(&jc)
+    /// Take the row keyed `id` out of the view, keeping its data in place; its
+    /// key is free for `insertRow` and upserts again. Returns the row's new
+    /// version, or `undefined` as for `updateRowById`. A rejected removal comes
+    /// back with `insertRow`.
+    #[wasm_bindgen(js_name = removeRowById)]
+    pub fn remove_row_by_id(
+        &mut self,
+        id: &JsValue,
+        expected_version: Option<u32>,
+    ) -> Result<Option<u32>, JsError> {
+        let Some((row, _)) = self.row_by_id(id, expected_version)? else {
+            return Ok(None);
         };
+        self.columnar.remove_row(row);
+        Ok(Some(self.columnar.bump_row_version(row)))
+    }
 
-        let viewport = Viewport {
-            width: vp.width,
-            height: vp.height,
-            row_height: vp.row_height,
-            header_height: vp.header_height,
-            scroll_top: vp.scroll_top,
-            line_height: vp.line_height,
+    /// Version of the row keyed `id`: 0 until `insertRow`, `updateRowById`, or
+    /// `removeRowById` changes it, then counting up; `undefined` without such a
+    /// row.
+    #[wasm_bindgen(js_name = getRowVersion)]
+    pub fn get_row_version(&mut self, id: &JsValue) -> Result<Option<u32>, JsError> {
+        Ok(self
+            .row_by_id(id, None)?
+            .map(|(row, _)| self.columnar.row_version(row)))
+    }
+
+    /// Groups of data rows passing the filters whose cells in `colIndices` are
+    /// all equal (nulls equal each other): `[[row, ...], ...]`, each group of two
+    /// or more rows in data row order. Throws for an empty `colIndices` or a
+    /// column index past the data columns.
+    #[wasm_bindgen(js_name = findDuplicates)]
+    pub fn find_duplicates(&mut self, col_indices: &[u32]) -> Result<JsValue, JsError> {
+        self.columnar.rebuild_view();
+        self.emit_view_changed();
+        let cols: Vec<usize> = col_indices.iter().map(|&c| c as usize).collect();
+        let groups = dedupe::find_duplicates(&self.columnar, self.columnar.matched_rows(), &cols)
+            .map_err(|e| JsError::new(&e))?;
+        Ok(serde_wasm_bindgen::to_value(&groups)?)
+    }
+
+    /// Remove the duplicates `findDuplicates(colIndices)` finds, keeping the
+    /// `"first"` (default) or `"last"` row of each group in data row order.
+    /// Removed rows leave the view like `removeRowById`. Returns their data rows.
+    /// Throws, removing nothing, for the `colIndices` `findDuplicates` rejects.
+    #[allow(clippy::needless_pass_by_value)] // wasm_bindgen takes optional strings owned
+    #[wasm_bindgen(js_name = dedupeKeep)]
+    pub fn dedupe_keep(
+        &mut self,
+        col_indices: &[u32],
+        keep: Option<String>,
+    ) -> Result<Vec<u32>, JsError> {
+        let keep = match keep.as_deref() {
+            None | Some("first") => Keep::First,
+            Some("last") => Keep::Last,
+            Some(other) => return Err(JsError::new(&format!("unknown keep: {other:?}"))),
         };
+        self.columnar.rebuild_view();
+        let cols: Vec<usize> = col_indices.iter().map(|&c| c as usize).collect();
+        let removed =
+            dedupe::dedupe(&mut self.columnar, &cols, keep).map_err(|e| JsError::new(&e))?;
+        for &row in &removed {
+            self.columnar.bump_row_version(row as usize);
+        }
+        Ok(removed)
+    }
 
-        let columns: Vec<ColumnLayout> = cols.into_iter().map(|c| convert_column(&c)).collect();
+    /// Return [pointer_offset, length] of a Uint32Array of `[viewRow, column]`
+    /// pairs: the cells rendered by the last frame whose values changed through
+    /// `applyUpserts` since the frame before (e.g. to flash them).
+    #[wasm_bindgen(js_name = getUpdatedCellsInfo)]
+    pub fn get_updated_cells_info(&self) -> Vec<usize> {
+        vec![
+            self.updated_cells.as_ptr() as usize,
+            self.updated_cells.len(),
+        ]
+    }
 
-        // 3. Compute effective row height
-        let effective_row_height = f64::from(self.layout.compute_effective_row_height(
-            &columns,
-            &container,
-            viewport.width,
-            viewport.row_height,
-            viewport.line_height,
-        ));
+    /// Report cell updates for `frames` frames: each layout buffer cell of a row
+    /// updated within the window carries its age in frames (`FIELD_UPDATE_AGE`,
+    /// 0 = this frame), e.g. to flash changed values. 0 turns it off.
+    #[wasm_bindgen(js_name = setUpdateFlashFrames)]
+    pub fn set_update_flash_frames(&mut self, frames: u32) {
+        self.columnar.set_update_flash_frames(frames);
+    }
 
-        // 4. Row pinning: optional pinned_top, pinned_bottom (row counts)
-        let pinned_top = pinned_top_js.map_or(0, |v| v as usize);
-        let pinned_bottom = pinned_bottom_js.map_or(0, |v| v as usize);
+    // ── Events ────────────────────────────────────────────────────────
 
-        let total_count = self.columnar.row_count;
-        let col_count = columns.len();
+    /// Call `(viewVersion, viewRowCount, rowCount)` after rebuilds that change the
+    /// rows of the view or their order, or `undefined` to unsubscribe. Callbacks run while
+    /// the engine is in use: defer engine calls (e.g. `queueMicrotask`). Errors
+    /// thrown by callbacks are ignored.
+    #[wasm_bindgen(js_name = onViewChanged)]
+    pub fn on_view_changed(&mut self, callback: Option<js_sys::Function>) {
+        self.events.view_changed = callback;
+        self.events.view_version = self.columnar.view_version();
+    }
 
-        let header_row_count: usize = vp.header_row_count.max(1);
+    /// Call `(version, isEmpty)` when the selection model changes (`selectCell`,
+    /// `selectRange`, `toggleRow`, `selectAllFiltered`, `clearSelection`, and mode
+    /// changes), or `undefined` to unsubscribe; see `onViewChanged`.
+    #[wasm_bindgen(js_name = onSelectionChanged)]
+    pub fn on_selection_changed(&mut self, callback: Option<js_sys::Function>) {
+        self.events.selection_changed = callback;
+        self.events.selection_version = self.selection_model.version();
+    }
 
-        if pinned_top > 0 || pinned_bottom > 0 {
-            // Row pinning path: three segments (top, middle visible, bottom)
-            let scroll_state = react_wasm_table_core::virtual_scroll::ScrollState {
-                scroll_top,
-                viewport_height: self.columnar.viewport_height(),
-                row_height: effective_row_height,
-                total_rows: filtered_count,
-                overscan: self.columnar.overscan(),
-                pinned_top: Some(pinned_top),
-                pinned_bottom: Some(pinned_bottom),
-            };
-            let virtual_slice =
-                react_wasm_table_core::virtual_scroll::compute_virtual_slice(&scroll_state);
+    /// Call `(cells, source)` when cell values are written: `cells` is a
+    /// `Uint32Array` of `[row, col, ...]` data cells and `source` one of `"update"`
+    /// (`update*Cell`, `updateRowById`), `"edit"` (`commitEdit`), `"paste"`, `"undo"`,
+    /// `"redo"`, `"insert"` (`insertRow`), or `"upsert"` (at the frame that applies
+    /// them). `undefined` unsubscribes; see `onViewChanged`.
+    #[wasm_bindgen(js_name = onDataEdited)]
+    pub fn on_data_edited(&mut self, callback: Option<js_sys::Function>) {
+        self.events.data_edited = callback;
+    }
 
-            let middle_range = virtual_slice.start_index..virtual_slice.end_index;
-            let total_cells = col_count
-                + pinned_top * col_count
-                + middle_range.len() * col_count
-                + pinned_bottom * col_count;
-            let needed = layout_buffer::buf_len(total_cells);
+    // ── Cell editing ──────────────────────────────────────────────────
 
-            if self.layout_buf.len() < needed {
-                self.layout_buf.resize(needed, 0.0);
+    /// Start editing the cell at data row `row`, column `col`, replacing any open
+    /// edit; `getEditValue` starts as the cell's current value. Throws for missing
+    /// cells and multi-value columns.
+    #[wasm_bindgen(js_name = beginEdit)]
+    pub fn begin_edit(&mut self, row: usize, col: usize) -> Result<(), JsError> {
+        self.editor
+            .begin(&self.columnar, row, col)
+            .map_err(|e| JsError::new(&e))
+    }
+
+    /// Pending text of the open edit, or `undefined` when none is open.
+    #[wasm_bindgen(js_name = getEditValue)]
+    pub fn get_edit_value(&self) -> Option<String> {
+        self.editor.value().map(str::to_string)
+    }
+
+    /// Replace the pending text of the open edit; false when none is open.
+    #[wasm_bindgen(js_name = setEditValue)]
+    pub fn set_edit_value(&mut self, value: &str) -> bool {
+        self.editor.set_value(value)
+    }
+
+    /// Coerce the pending text to the column type (numbers, `true`/`false`/`yes`/
+    /// `no`/`1`/`0`, text; blank = null) and write it like the `update*Cell`
+    /// methods, closing the edit and recording it for `undo`. Returns `undefined`
+    /// on success, or the validation error with the edit left open.
+    #[wasm_bindgen(js_name = commitEdit)]
+    pub fn commit_edit(&mut self) -> Option<String> {
+        match self.editor.commit(&mut self.columnar) {
+            Ok(edit) => {
+                if let Some(edit) = edit {
+                    self.emit_data_edited("edit", [(edit.row, edit.col)]);
+                    self.history.record(Change::Cell(edit));
+                }
+                None
             }
+            Err(e) => Some(e),
+        }
+    }
 
-            let row_pinned_params = react_wasm_table_core::layout::RowPinnedLayoutParams {
-                viewport: &viewport,
-                container: &container,
-                pinned_top,
-                pinned_bottom,
-                scroll_top: scroll_top as f32,
-                total_rows: filtered_count,
-                middle_range,
-                header_row_count,
-            };
-            self.layout_cell_count = self.layout.compute_into_buffer_row_pinned(
-                &columns,
-                &row_pinned_params,
-                &mut self.layout_buf,
-            );
+    /// Close the open edit without writing.
+    #[wasm_bindgen(js_name = cancelEdit)]
+    pub fn cancel_edit(&mut self) {
+        self.editor.cancel();
+    }
 
-            Ok(vec![
-                self.layout_cell_count as f64,
-                virtual_slice.start_index as f64,
-                virtual_slice.end_index as f64,
-                virtual_slice.total_height,
-                filtered_count as f64,
-                self.columnar.generation as f64,
-                total_count as f64,
-                virtual_slice.visible_count as f64,
-                effective_row_height,
-                self.columnar.filtered_total() as f64,
-            ])
-        } else {
-            // Default path: single visible range
-            let scroll_state = react_wasm_table_core::virtual_scroll::ScrollState {
-                scroll_top,
-                viewport_height: self.columnar.viewport_height(),
-                row_height: effective_row_height,
-                total_rows: filtered_count,
-                overscan: self.columnar.overscan(),
-                pinned_top: None,
-                pinned_bottom: None,
-            };
-            let virtual_slice =
-                react_wasm_table_core::virtual_scroll::compute_virtual_slice(&scroll_state);
+    /// Paste clipboard text at the cell of data row `row`, table column `col`:
+    /// tab-separated lines (comma-separated with `format` `"csv"`) go to that row
+    /// and the rows after it in view order, fields to that column and the columns
+    /// after it in on-screen order. Fields are coerced to their column type like
+    /// `commitEdit`, and lines past the last view row append rows. The paste is
+    /// one `undo` step (appended rows stay, emptied).
+    ///
+    /// Returns [applied, rejected, clipped, added_rows, row, col, row, col, ...]:
+    /// cells written or already holding the value, cells whose text didn't fit
+    /// the column, fields past the last column, appended rows, then the data row
+    /// and column of each rejected cell.
+    #[wasm_bindgen(js_name = pasteTsvAt)]
+    pub fn paste_tsv_at(
+        &mut self,
+        row: usize,
+        col: usize,
+        text: &str,
+        format: Option<String>,
+    ) -> Result<Vec<f64>, JsError> {
+        let format = format
+            .map(|f| ExportFormat::parse(&f))
+            .transpose()
+            .map_err(|e| JsError::new(&e))?;
+        let separator = match format {
+            None | Some(ExportFormat::Tsv) => '\t',
+            Some(ExportFormat::Csv) => ',',
+            Some(ExportFormat::Json) => return Err(JsError::new("JSON can't be pasted")),
+        };
+        let lines = editing::parse_clipboard(text, separator);
+        self.columnar.rebuild_view();
+        let pos = self
+            .columnar
+            .view_position(row)
+            .ok_or_else(|| JsError::new(&format!("row {row} is not in the view")))?;
+        let order = self.screen_columns();
+        let first = order
+            .iter()
+            .position(|&c| c == col)
+            .ok_or_else(|| JsError::new(&format!("column {col} is not shown")))?;
+        let mut rows: Vec<u32> = self
+            .columnar
+            .view_data_rows(pos..usize::MAX)
+            .map(|(_, row)| row)
+            .take(lines.len())
+            .collect();
+        let added = lines.len() - rows.len();
+        if added > 0 {
+            rows.extend(self.columnar.append_empty_rows(added).map(|r| r as u32));
+        }
 
-            let row_count = virtual_slice
-                .end_index
-                .saturating_sub(virtual_slice.start_index);
-            let total_cells = col_count + row_count * col_count;
-            let needed = layout_buffer::buf_len(total_cells);
+        self.editor.cancel();
+        let paste = editing::paste(&mut self.columnar, &rows, &order[first..], &lines);
+        self.emit_data_edited("paste", paste.edits.iter().map(|e| (e.row, e.col)));
+        self.history.begin();
+        for edit in paste.edits {
+            self.history.record(Change::Cell(edit));
+        }
+        self.history.end();
+        let mut out = vec![
+            paste.applied as f64,
+            paste.rejected.len() as f64,
+            paste.clipped as f64,
+            added as f64,
+        ];
+        out.extend(
+            paste
+                .rejected
+                .iter()
+                .flat_map(|&(row, col)| [row as f64, col as f64]),
+        );
+        Ok(out)
+    }
 
-            if self.layout_buf.len() < needed {
-                self.layout_buf.resize(needed, 0.0);
-            }
+    // ── Undo/redo ─────────────────────────────────────────────────────
 
-            self.layout_cell_count = self.layout.compute_into_buffer(
-                &columns,
-                &viewport,
-                &container,
-                virtual_slice.start_index..virtual_slice.end_index,
-                &mut self.layout_buf,
-                header_row_count,
-            );
+    /// Revert the last transaction of recorded changes: cell edits and pastes
+    /// (`commitEdit`, `pasteTsvAt`), row moves and manual row orders, column widths
+    /// (`resizeColumn`, `autoSizeColumns`, `clearColumnWidths`), and column
+    /// visibility. `update*Cell` writes and data loads aren't recorded; loading
+    /// data with `initColumnar` clears the log. Closes an open transaction first.
+    /// Returns whether anything was undone.
+    #[wasm_bindgen]
+    pub fn undo(&mut self) -> bool {
+        self.editor.cancel();
+        let Some(changes) = self.history.undo().map(<[Change]>::to_vec) else {
+            return false;
+        };
+        for change in changes.iter().rev() {
+            self.apply_change(change, true);
+        }
+        self.emit_data_edited("undo", edited_cells(&changes));
+        true
+    }
 
-            Ok(vec![
-                self.layout_cell_count as f64,
-                virtual_slice.start_index as f64,
-                virtual_slice.end_index as f64,
-                virtual_slice.total_height,
-                filtered_count as f64,
-                self.columnar.generation as f64,
-                total_count as f64,
-                virtual_slice.visible_count as f64,
-                effective_row_height,
-                self.columnar.filtered_total() as f64,
-            ])
+    /// Re-apply the last undone transaction. Returns whether anything was redone.
+    #[wasm_bindgen]
+    pub fn redo(&mut self) -> bool {
+        self.editor.cancel();
+        let Some(changes) = self.history.redo().map(<[Change]>::to_vec) else {
+            return false;
+        };
+        for change in &changes {
+            self.apply_change(change, false);
         }
+        self.emit_data_edited("redo", edited_cells(&changes));
+        true
     }
 
-    /// Return [pointer_offset, length] for the view indices buffer.
-    #[wasm_bindgen(js_name = getColumnarViewIndicesInfo)]
-    pub fn get_columnar_view_indices_info(&self) -> Vec<usize> {
-        let indices = self.columnar.view_indices();
-        vec![indices.as_ptr() as usize, indices.len()]
+    #[wasm_bindgen(js_name = canUndo)]
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
     }
 
-    // ── Layout cache ──────────────────────────────────────────────
+    #[wasm_bindgen(js_name = canRedo)]
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
 
-    /// Invalidate cached layout results. Forces recomputation on next frame.
-    #[wasm_bindgen(js_name = invalidateLayout)]
-    pub fn invalidate_layout(&mut self) {
-        self.layout.invalidate_cache();
+    /// Group the changes until the matching `endTransaction` into one `undo` step
+    /// (e.g. a paste over many cells). Transactions nest.
+    #[wasm_bindgen(js_name = beginTransaction)]
+    pub fn begin_transaction(&mut self) {
+        self.history.begin();
     }
 
-    // ── Composite (in-cell) layout ──────────────────────────────────
+    /// Close the innermost `beginTransaction`; returns false when none is open.
+    #[wasm_bindgen(js_name = endTransaction)]
+    pub fn end_transaction(&mut self) -> bool {
+        self.history.end()
+    }
+
+    /// Keep at most `limit` transactions for undo (default 100).
+    #[wasm_bindgen(js_name = setHistoryLimit)]
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history.set_limit(limit);
+    }
+
+    // ── Row heights ───────────────────────────────────────────────────
+
+    /// Per-row heights indexed by data row (NaN = default row height); `undefined`
+    /// returns to uniform rows. Heights stay with their rows across sort and filter.
+    #[wasm_bindgen(js_name = setRowHeights)]
+    pub fn set_row_heights(&mut self, heights: Option<Vec<f64>>) {
+        self.columnar.set_row_heights(heights);
+    }
+
+    /// Set one data row's height (NaN = default), e.g. after measuring it.
+    #[wasm_bindgen(js_name = setRowHeight)]
+    pub fn set_row_height(&mut self, row: usize, height: f64) {
+        self.columnar.set_row_height(row, height);
+    }
+
+    /// Expand the rows at view indices `view_indices` with a `detail_height` px
+    /// detail area below each (collapsing all others; empty = none). The layout
+    /// buffer gets a full-width `REGION_DETAIL` cell under each expanded row.
+    #[wasm_bindgen(js_name = setExpandedRows)]
+    pub fn set_expanded_rows(&mut self, view_indices: Vec<u32>, detail_height: f64) {
+        let rows: Vec<usize> = view_indices.into_iter().map(|i| i as usize).collect();
+        self.columnar.set_expanded_rows(&rows, detail_height);
+    }
+
+    /// Header rows above each group: `{ column?, height, sticky? }`, or `null` to
+    /// turn them off. `column` defaults to the `setGroupSort` group column; a new
+    /// group starts wherever its value changes between view rows. Each header is
+    /// a full-width `REGION_GROUP_HEADER` cell; with `sticky`, the current group's
+    /// header is also pinned to the top of the data region (default path only).
+    #[wasm_bindgen(js_name = setGroupHeaders)]
+    pub fn set_group_headers(&mut self, group_headers: JsValue) -> Result<(), JsError> {
+        let group_headers: Option<JsGroupHeaders> = serde_wasm_bindgen::from_value(group_headers)?;
+        self.columnar
+            .set_group_headers(group_headers.as_ref().map(convert_group_headers));
+        Ok(())
+    }
+
+    /// Keep data rows `top` (and `bottom`) in every slice, pinned to the top (and
+    /// bottom) of the scroll area like `pinnedTop`/`pinnedBottom` rows, while they
+    /// pass the filters; they stay out of pagination. Empty = none.
+    #[wasm_bindgen(js_name = setAlwaysVisibleRows)]
+    pub fn set_always_visible_rows(&mut self, top: &[u32], bottom: Option<Vec<u32>>) {
+        self.columnar
+            .set_always_visible_rows(top, &bottom.unwrap_or_default());
+    }
+
+    /// Size rows to their wrapped text: `{ columns: [{ columnIndex, textWidth }],
+    /// lineHeight, padding?, minHeight?, charWidth? }`, or `null` to turn it off.
+    /// Rows are measured as they scroll into view, by character count unless a
+    /// `setTextMeasure` callback is set.
+    #[wasm_bindgen(js_name = setAutoHeight)]
+    pub fn set_auto_height(&mut self, auto_height: JsValue) -> Result<(), JsError> {
+        let auto_height: Option<JsAutoHeight> = serde_wasm_bindgen::from_value(auto_height)?;
+        self.columnar
+            .set_auto_height(auto_height.as_ref().map(convert_auto_height));
+        Ok(())
+    }
+
+    /// Measure auto-height text with `(text, width) => height` (e.g. canvas
+    /// `measureText` plus wrapping), or `undefined` for the character-count estimate.
+    /// A callback that throws counts as zero height.
+    #[wasm_bindgen(js_name = setTextMeasure)]
+    pub fn set_text_measure(&mut self, callback: Option<js_sys::Function>) {
+        let measure = callback.map(|f| -> MeasureTextFn {
+            Box::new(move |text, width| {
+                f.call2(
+                    &JsValue::NULL,
+                    &JsValue::from_str(text),
+                    &JsValue::from_f64(width),
+                )
+                .ok()
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0)
+            })
+        });
+        self.columnar.set_text_measure(measure);
+    }
+
+    // ── Tree data ─────────────────────────────────────────────────────
+
+    /// Arrange rows as a tree: `parents[row]` is each data row's parent row
+    /// (`0xFFFFFFFF` = root), or `undefined` for flat rows. The view shows
+    /// expanded branches only (all collapsed at first), siblings in sort order,
+    /// with filtered-out ancestors kept above matching rows. Cells of
+    /// `indent_column` (default 0) are indented by `indent` px (default 16) per level.
+    #[wasm_bindgen(js_name = setTreeStructure)]
+    pub fn set_tree_structure(
+        &mut self,
+        parents: Option<Vec<u32>>,
+        indent_column: Option<usize>,
+        indent: Option<f32>,
+    ) {
+        self.columnar.set_tree_structure(parents);
+        self.columnar
+            .set_tree_indent(indent_column.unwrap_or(0), indent.unwrap_or(16.0));
+    }
+
+    /// Expand or collapse the tree row at data row `row`.
+    #[wasm_bindgen(js_name = setTreeExpanded)]
+    pub fn set_tree_expanded(&mut self, row: usize, expanded: bool) {
+        self.columnar.set_tree_expanded(row, expanded);
+    }
+
+    /// Expand or collapse every tree row.
+    #[wasm_bindgen(js_name = setTreeAllExpanded)]
+    pub fn set_tree_all_expanded(&mut self, expanded: bool) {
+        self.columnar.set_tree_all_expanded(expanded);
+    }
+
+    /// `[depth, childCount, expanded]` per view position in `start..end`, for
+    /// drawing expander toggles. Empty without tree data.
+    #[wasm_bindgen(js_name = getTreeRows)]
+    pub fn get_tree_rows(&self, start: usize, end: usize) -> Vec<f64> {
+        let Some(tree) = self.columnar.tree() else {
+            return Vec::new();
+        };
+        let view = self.columnar.view_indices();
+        let start = start.min(view.len());
+        view[start..end.clamp(start, view.len())]
+            .iter()
+            .flat_map(|&row| {
+                let row = row as usize;
+                [
+                    tree.depth(row) as f64,
+                    tree.children(row).len() as f64,
+                    f64::from(u8::from(tree.is_expanded(row))),
+                ]
+            })
+            .collect()
+    }
+
+    /// Per data row, `aggregate` (`sum`, `mean`, `min`, `max`, `count`) of column
+    /// `col_idx` over the row's tree descendants; NaN for leaves.
+    #[wasm_bindgen(js_name = getTreeRollup)]
+    pub fn get_tree_rollup(&self, col_idx: usize, aggregate: &str) -> Result<Vec<f64>, JsError> {
+        let aggregate = Aggregate::parse(aggregate).map_err(|e| JsError::new(&e))?;
+        Ok(self.columnar.tree_rollup(col_idx, aggregate))
+    }
+
+    // ── Row grouping ──────────────────────────────────────────────────
+
+    /// Group the view by columns `columns`, outermost first (empty = off). Each
+    /// group gets a header row before its rows, whose cells carry
+    /// `REGION_GROUP_ROW` and the group's first data row; groups are ordered by
+    /// their first row in the current sort and start expanded.
+    #[wasm_bindgen(js_name = setGroupBy)]
+    pub fn set_group_by(&mut self, columns: Vec<u32>) {
+        self.columnar
+            .set_group_by(columns.into_iter().map(|c| c as usize).collect());
+    }
+
+    /// Expand or collapse the group whose header row is at view position `pos`.
+    #[wasm_bindgen(js_name = setGroupExpanded)]
+    pub fn set_group_expanded(&mut self, pos: usize, expanded: bool) {
+        self.columnar.set_group_expanded(pos, expanded);
+    }
+
+    /// `[pos, depth, count, expanded, firstRow]` per group header row at view
+    /// positions `start..end`. The group key is the value of the `depth`-th
+    /// group-by column in `firstRow`.
+    #[wasm_bindgen(js_name = getGroupRows)]
+    pub fn get_group_rows(&self, start: usize, end: usize) -> Vec<f64> {
+        let view = self.columnar.view_indices();
+        self.columnar
+            .view_groups_in(start..end)
+            .iter()
+            .flat_map(|g| {
+                [
+                    f64::from(g.pos),
+                    f64::from(g.depth),
+                    f64::from(g.rows.end - g.rows.start),
+                    f64::from(u8::from(g.expanded)),
+                    f64::from(view[g.pos as usize]),
+                ]
+            })
+            .collect()
+    }
+
+    /// `aggregate` (`sum`, `mean`, `min`, `max`, `count`) of column `col_idx` over
+    /// the rows of the group whose header row is at view position `pos`; NaN for
+    /// other rows.
+    #[wasm_bindgen(js_name = getGroupAggregate)]
+    pub fn get_group_aggregate(
+        &self,
+        pos: usize,
+        col_idx: usize,
+        aggregate: &str,
+    ) -> Result<f64, JsError> {
+        let aggregate = Aggregate::parse(aggregate).map_err(|e| JsError::new(&e))?;
+        Ok(self.columnar.view_group(pos).map_or(f64::NAN, |g| {
+            self.columnar.group_aggregate(g, col_idx, aggregate)
+        }))
+    }
+
+    // ── Aggregations ──────────────────────────────────────────────────
+
+    /// Register `(values: Float64Array) => number` as aggregate `name` for
+    /// `setAggregations` (`undefined` removes it). Nulls and non-numeric cells
+    /// arrive as NaN; a callback that throws yields NaN.
+    #[wasm_bindgen(js_name = setCustomAggregate)]
+    pub fn set_custom_aggregate(&mut self, name: String, callback: Option<js_sys::Function>) {
+        match callback {
+            Some(f) => self.custom_aggregates.insert(name, f),
+            None => self.custom_aggregates.remove(&name),
+        };
+    }
+
+    /// Aggregate the view with `[{ columnIndex, fn }]`, read via `getAggregates`
+    /// (all filtered rows) and `getGroupAggregates` (per group row). Draw totals
+    /// in the footer row of a viewport `footerHeight` and group totals on the
+    /// group rows; groups get no footer rows of their own.
+    #[wasm_bindgen(js_name = setAggregations)]
+    pub fn set_aggregations(&mut self, aggregations: JsValue) -> Result<(), JsError> {
+        let aggregations: Vec<JsAggregation> = serde_wasm_bindgen::from_value(aggregations)?;
+        self.aggregations = aggregations
+            .into_iter()
+            .map(|a| {
+                let function = match Aggregate::parse(&a.function) {
+                    Ok(aggregate) => AggregateFn::Builtin(aggregate),
+                    Err(_) if self.custom_aggregates.contains_key(&a.function) => {
+                        AggregateFn::Custom(a.function)
+                    }
+                    Err(e) => return Err(JsError::new(&e)),
+                };
+                Ok((a.column_index, function))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(())
+    }
+
+    /// One value per `setAggregations` entry, over every row passing the filters
+    /// (all pages, without group rows) as of the last view rebuild.
+    #[wasm_bindgen(js_name = getAggregates)]
+    pub fn get_aggregates(&self) -> Vec<f64> {
+        self.aggregates(self.columnar.filtered_rows())
+    }
+
+    /// One value per `setAggregations` entry, over the rows of the group whose
+    /// header row is at view position `pos`; empty for other rows.
+    #[wasm_bindgen(js_name = getGroupAggregates)]
+    pub fn get_group_aggregates(&self, pos: usize) -> Vec<f64> {
+        self.columnar
+            .view_group(pos)
+            .map_or_else(Vec::new, |g| self.aggregates(self.columnar.group_rows(g)))
+    }
+
+    // ── Window columns ────────────────────────────────────────────────
+
+    /// Add a derived column `{ columnIndex, fn, window? }` computed over the sorted
+    /// view of all pages on every rebuild: a running total, rank, percent of total,
+    /// or moving average. Returns its ID for `getWindowValues`.
+    #[wasm_bindgen(js_name = addWindowColumn)]
+    pub fn add_window_column(&mut self, spec: JsValue) -> Result<usize, JsError> {
+        let spec: JsWindowColumn = serde_wasm_bindgen::from_value(spec)?;
+        let function =
+            WindowFn::parse(&spec.function, spec.window).map_err(|e| JsError::new(&e))?;
+        Ok(self.columnar.add_window_column(spec.column_index, function))
+    }
+
+    /// Remove window column `id`.
+    #[wasm_bindgen(js_name = removeWindowColumn)]
+    pub fn remove_window_column(&mut self, id: usize) {
+        self.columnar.remove_window_column(id);
+    }
+
+    /// Values of window column `id` at view positions `start..end` (NaN for
+    /// unknown IDs and group header rows; empty when `end <= start`).
+    #[wasm_bindgen(js_name = getWindowValues)]
+    pub fn get_window_values(&self, id: usize, start: usize, end: usize) -> Vec<f64> {
+        let view = self.columnar.view_indices();
+        let start = start.min(view.len());
+        (start..end.clamp(start, view.len()))
+            .map(|pos| match self.columnar.view_group(pos) {
+                Some(_) => f64::NAN,
+                None => self.columnar.window_value(id, view[pos] as usize),
+            })
+            .collect()
+    }
+
+    // ── Column sizing ─────────────────────────────────────────────────
+
+    /// Measure cell text for `autoSizeColumns`, content sizing and line breaks of
+    /// `setAutoHeight` rows with `(text) => width` (e.g. canvas `measureText`), or
+    /// `undefined` to estimate from character counts. A callback that throws
+    /// counts as zero width.
+    #[wasm_bindgen(js_name = setTextWidthMeasure)]
+    pub fn set_text_width_measure(&mut self, callback: Option<js_sys::Function>) {
+        let measure = callback
+            .clone()
+            .map(|f| -> MeasureWidthFn { Box::new(move |text| text_width(Some(&f), text)) });
+        self.columnar.set_text_width_measure(measure);
+        self.text_width_measure = callback;
+        self.content_sizes.1.clear();
+        self.label_widths.1.clear();
+    }
+
+    /// Size columns without a `width` by their cell text, like CSS table cells:
+    /// `auto`, `min-content`, `max-content`, and `fit-content()` tracks (and flex
+    /// columns without a basis) see the average text width as the min-content
+    /// width and the widest text as the max-content width, measured over
+    /// `sample_size` rows spread over the view (every row when 0 or omitted).
+    /// Widths are remeasured when the view changes. `enabled = false` sizes those
+    /// columns like empty cells again.
+    #[wasm_bindgen(js_name = setContentSizing)]
+    pub fn set_content_sizing(&mut self, enabled: bool, sample_size: Option<u32>) {
+        self.content_sizing = enabled.then(|| sample_size.map_or(0, |n| n as usize));
+        self.content_sizes.1.clear();
+        self.layout.invalidate_cache();
+    }
+
+    /// Fit the columns of the last frame to their content: the widest display text
+    /// over the `mode` rows (`"visible"`: the rows of the last frame; otherwise the
+    /// whole view, spread over `sample_size` rows when given) or the width of the
+    /// column's `headerLabel` at its `headerOrientation` if wider, plus horizontal
+    /// padding and border, within the column's min/max width. The widths replace
+    /// the JS `width` of those columns in later frames until `clearColumnWidths`.
+    /// Returns the new widths (empty before the first frame).
+    #[wasm_bindgen(js_name = autoSizeColumns)]
+    pub fn auto_size_columns(&mut self, mode: &str, sample_size: Option<u32>) -> Vec<f32> {
+        let Some(frame) = &self.last_frame else {
+            return Vec::new();
+        };
+        let view = self.columnar.view_indices();
+        let rows: Vec<usize> = if mode == "visible" {
+            let bottom = view.len().saturating_sub(frame.pinned_bottom);
+            (0..frame.pinned_top)
+                .chain(frame.rows.clone())
+                .chain(bottom..view.len())
+                .filter_map(|pos| view.get(pos).map(|&row| row as usize))
+                .collect()
+        } else {
+            self.columnar
+                .view_sample(sample_size.map_or(0, |n| n as usize))
+                .collect()
+        };
+        let measure = self.text_width_measure.as_ref();
+        let measure_text = |text: &str| text_width(measure, text);
+        let widths: Vec<f32> = frame
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(col_idx, column)| {
+                let content =
+                    self.columnar
+                        .column_content_width(col_idx, rows.iter().copied(), measure_text);
+                let (label, _) = column
+                    .header_orientation
+                    .extent(column.header_label_width, frame.viewport.line_height);
+                let (min, max) = column_sizing::width_limits(column);
+                ((content as f32).max(label) + horizontal_insets(column)).clamp(min, max.max(min))
+            })
+            .collect();
+        self.set_column_widths(widths.iter().copied().map(Some).collect());
+        widths
+    }
+
+    /// Resize column `col_idx` of the last frame to `new_width` px within its
+    /// min/max width. `mode` `"neighbor"` takes the difference from the next column
+    /// (within its limits too) so the table keeps its width; otherwise the table
+    /// grows. All columns are fixed at their laid-out widths from then on, until
+    /// `clearColumnWidths`. Consecutive resizes of the same column (one drag) are
+    /// one `undo` step. Returns the new widths (empty before the first frame).
+    #[wasm_bindgen(js_name = resizeColumn)]
+    pub fn resize_column(&mut self, col_idx: usize, new_width: f32, mode: &str) -> Vec<f32> {
+        let Some(frame) = &self.last_frame else {
+            return Vec::new();
+        };
+        let mut widths = self.laid_out_widths();
+        let limits: Vec<(f32, f32)> = frame
+            .columns
+            .iter()
+            .map(column_sizing::width_limits)
+            .collect();
+        let mode = if mode == "neighbor" {
+            ResizeMode::Neighbor
+        } else {
+            ResizeMode::Grow
+        };
+        column_sizing::resize_column(&mut widths, &limits, col_idx, new_width, mode);
+        self.merge_column_widths(widths.iter().copied().map(Some).collect(), Some(col_idx));
+        widths
+    }
+
+    /// Width overrides (`autoSizeColumns`, `resizeColumn`, `setColumnWidths`) of the
+    /// columns with a `key`, as `{ [key]: width }`, for saving.
+    #[wasm_bindgen(js_name = getColumnWidths)]
+    pub fn get_column_widths(&self) -> Result<JsValue, JsError> {
+        let widths = column_sizing::keyed_widths(
+            &self.column_keys,
+            &self.column_widths,
+            &self.pending_widths,
+        );
+        let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+        Ok(serde::Serialize::serialize(&widths, &serializer)?)
+    }
+
+    /// Replace the width overrides with `{ [key]: width }` (from `getColumnWidths`)
+    /// by column `key`, so saved widths survive column reorders and visibility
+    /// changes; other columns go back to their JS widths. Widths of keys not among
+    /// the current columns apply once such a column arrives. One `undo` step.
+    #[wasm_bindgen(js_name = setColumnWidths)]
+    pub fn set_column_widths_by_key(&mut self, widths: JsValue) -> Result<(), JsError> {
+        let mut pending: HashMap<String, f32> = serde_wasm_bindgen::from_value(widths)?;
+        let widths = column_sizing::widths_by_key(&self.column_keys, &mut pending);
+        self.pending_widths = pending;
+        self.set_column_widths(widths);
+        Ok(())
+    }
+
+    /// Drop the `autoSizeColumns` and `resizeColumn` widths, going back to the JS
+    /// column widths.
+    #[wasm_bindgen(js_name = clearColumnWidths)]
+    pub fn clear_column_widths(&mut self) {
+        self.set_column_widths(Vec::new());
+    }
+
+    /// Show or hide column `col_idx` of the columns passed to
+    /// `updateViewportColumnar`. Hidden columns are left out of the layout and the
+    /// layout buffer; the other cells keep their table column index in `col`, and
+    /// hidden columns still filter and sort.
+    #[wasm_bindgen(js_name = setColumnVisibility)]
+    pub fn set_column_visibility(&mut self, col_idx: usize, visible: bool) {
+        if self.hidden_columns.get(col_idx).copied().unwrap_or(false) != visible {
+            return;
+        }
+        let before = self.hidden_columns.clone();
+        if self.hidden_columns.len() <= col_idx {
+            self.hidden_columns.resize(col_idx + 1, false);
+        }
+        self.hidden_columns[col_idx] = !visible;
+        self.history
+            .record(Change::HiddenColumns(before, self.hidden_columns.clone()));
+    }
+
+    /// Lay out the header row with its own container and column styles instead
+    /// of the data row's: `{ container?, columns? }`, where `columns[i]` (by table
+    /// column index, `null` to keep the data style) sets padding, border,
+    /// alignment, heights, and so on. Header cells keep their column's width and
+    /// pin. `null` or `undefined` goes back to the data row styles.
+    #[wasm_bindgen(js_name = setHeaderLayout)]
+    pub fn set_header_layout(&mut self, header: JsValue) -> Result<(), JsError> {
+        self.header_layout = if header.is_undefined() || header.is_null() {
+            None
+        } else {
+            let header: JsHeaderLayout = serde_wasm_bindgen::from_value(header)?;
+            Some(HeaderLayout {
+                container: header.container.as_ref().map(convert_container),
+                columns: header
+                    .columns
+                    .iter()
+                    .map(|c| c.as_ref().map(convert_column))
+                    .collect(),
+            })
+        };
+        Ok(())
+    }
+
+    /// Lay records out as columns and fields as rows, e.g. for single-record
+    /// inspectors and comparison views: `{ recordWidth, labelWidth? }`, or `null`
+    /// for the normal layout. See `updateViewportColumnar`.
+    #[wasm_bindgen(js_name = setTransposed)]
+    pub fn set_transposed(&mut self, transposed: JsValue) -> Result<(), JsError> {
+        self.transposed = if transposed.is_undefined() || transposed.is_null() {
+            None
+        } else {
+            let t: JsTransposedLayout = serde_wasm_bindgen::from_value(transposed)?;
+            Some(TransposedLayout {
+                record_width: t.record_width,
+                label_width: t.label_width,
+            })
+        };
+        self.last_frame = None;
+        Ok(())
+    }
+
+    // ── Hot path ──────────────────────────────────────────────────────
+
+    /// Set sort configuration on the columnar store. Entries with an `expression`
+    /// (e.g. `"$2 * $5"`) sort by its value; a malformed expression is an error.
+    #[wasm_bindgen(js_name = setColumnarSort)]
+    pub fn set_columnar_sort(&mut self, configs: JsValue) -> Result<(), JsError> {
+        let configs: Vec<JsSortConfig> = serde_wasm_bindgen::from_value(configs)?;
+        for expression in configs.iter().filter_map(|c| c.expression.as_deref()) {
+            Expr::parse(expression).map_err(|e| JsError::new(&e))?;
+        }
+        self.columnar
+            .set_sort(configs.iter().map(convert_sort_config).collect());
+        Ok(())
+    }
+
+    /// Sort string columns by locale collation (e.g. `"de"`, `"sv-SE"`) with options
+    /// `{ strength?: "primary" | "secondary" | "tertiary", numeric?: boolean }`.
+    /// Applies to every string column, or only `colIdx` when given (overriding the
+    /// engine default). Pass `locale = undefined` to restore byte-wise ordering.
+    #[cfg(feature = "collation")]
+    #[wasm_bindgen(js_name = setCollation)]
+    pub fn set_collation(
+        &mut self,
+        locale: Option<String>,
+        options: JsValue,
+        col_idx: Option<usize>,
+    ) -> Result<(), JsError> {
+        let collation = match locale {
+            Some(locale) => {
+                let options: types::JsCollationOptions = if options.is_undefined() {
+                    types::JsCollationOptions::default()
+                } else {
+                    serde_wasm_bindgen::from_value(options)?
+                };
+                Some(convert::convert_collation(&locale, &options).map_err(|e| JsError::new(&e))?)
+            }
+            None => None,
+        };
+        self.columnar.set_collation(col_idx, collation);
+        Ok(())
+    }
+
+    /// Keep groups contiguous while sorting: `{ groupColumn, aggregateColumn,
+    /// aggregate: "sum" | "mean" | "min" | "max" | "count", direction: "asc" | "desc" }`
+    /// orders groups by the aggregate of their visible rows; rows within a group follow
+    /// `setColumnarSort`. `null` turns it off.
+    #[wasm_bindgen(js_name = setGroupSort)]
+    pub fn set_group_sort(&mut self, group_sort: JsValue) -> Result<(), JsError> {
+        let group_sort: Option<JsGroupSort> = serde_wasm_bindgen::from_value(group_sort)?;
+        let group_sort = group_sort
+            .as_ref()
+            .map(convert_group_sort)
+            .transpose()
+            .map_err(|e| JsError::new(&e))?;
+        self.columnar.set_group_sort(group_sort);
+        Ok(())
+    }
+
+    /// Break sort ties by original row index (default `true`) so equal-valued rows
+    /// keep a fixed order across rebuilds. `false` keeps ties in arrival order.
+    #[wasm_bindgen(js_name = setRowOrderTiebreak)]
+    pub fn set_row_order_tiebreak(&mut self, enabled: bool) {
+        self.columnar.set_row_order_tiebreak(enabled);
+    }
+
+    /// Order rows by `ids`, a list of data row indices, instead of sorting; rows
+    /// missing from it follow in row order. Applied after filtering and kept across
+    /// data reloads. `undefined` returns to sorting.
+    #[wasm_bindgen(js_name = setManualRowOrder)]
+    pub fn set_manual_row_order(&mut self, ids: Option<Vec<u32>>) {
+        self.record_row_order(|columnar| {
+            columnar.set_manual_row_order(ids);
+            true
+        });
+    }
+
+    /// The manual row order as data row indices, or `undefined` while sorting.
+    #[wasm_bindgen(js_name = getManualRowOrder)]
+    pub fn get_manual_row_order(&self) -> Option<Vec<u32>> {
+        self.columnar.manual_row_order().map(<[u32]>::to_vec)
+    }
+
+    /// Move the row at view position `from_view_idx` to `to_view_idx` (e.g. after a
+    /// drag), switching to a manual row order seeded with the current view order.
+    /// Returns false when either position is outside the view.
+    #[wasm_bindgen(js_name = moveRow)]
+    pub fn move_row(&mut self, from_view_idx: usize, to_view_idx: usize) -> bool {
+        self.record_row_order(|columnar| columnar.move_row(from_view_idx, to_view_idx))
+    }
+
+    /// Sort a string column by an explicit value order (e.g. `["Open", "In Progress",
+    /// "Done"]`); unlisted values follow in their usual order. `null` clears it.
+    #[wasm_bindgen(js_name = setCustomSortOrder)]
+    pub fn set_custom_sort_order(
+        &mut self,
+        col_idx: usize,
+        values: JsValue,
+    ) -> Result<(), JsError> {
+        let values: Option<Vec<String>> = serde_wasm_bindgen::from_value(values)?;
+        self.columnar.set_custom_sort_order(col_idx, values);
+        Ok(())
+    }
+
+    /// Set column filters on the columnar store. An `expression` filter keeps rows
+    /// where its `value`, a numeric expression over columns like computed sort
+    /// keys (e.g. `"$3 > $2"` or `"$1 * $2 > 1000"`), is neither 0 nor null; its
+    /// `columnIndex` only takes it out of that column's facets.
+    #[wasm_bindgen(js_name = setColumnarFilters)]
+    pub fn set_columnar_filters(&mut self, filters: JsValue) -> Result<(), JsError> {
+        let filters: Vec<JsColumnFilter> = serde_wasm_bindgen::from_value(filters)?;
+        let filters: Vec<ColumnFilter> = filters.iter().map(convert_column_filter).collect();
+        for (_, expr) in parse_filter_expressions(&filters) {
+            expr.map_err(|e| JsError::new(&e))?;
+        }
+        self.sync_time_reference();
+        self.columnar.set_column_filters(filters);
+        Ok(())
+    }
+
+    /// Fix the "now" (epoch ms) that `within` relative date filters resolve against,
+    /// or pass `undefined` to use the wall clock at the time filters are set.
+    #[wasm_bindgen(js_name = setFilterNow)]
+    pub fn set_filter_now(&mut self, now: Option<f64>) {
+        self.filter_now = now;
+        self.sync_time_reference();
+    }
+
+    /// Set the UTC offset (minutes east of UTC, i.e. `-new Date().getTimezoneOffset()`)
+    /// used for calendar boundaries of relative date filters.
+    #[wasm_bindgen(js_name = setTimezoneOffset)]
+    pub fn set_timezone_offset(&mut self, minutes: i32) {
+        self.utc_offset_minutes = minutes;
+        self.sync_time_reference();
+    }
+
+    /// Register a display format spec for a column (e.g. `"MMM YYYY"`, `"compact"`,
+    /// `"$#,##0.00"`, `"0.0%"`, `"@ kg"`), or clear it with `undefined`. Used by
+    /// `formatted` filters, exports, and `getFormattedSlice`.
+    #[wasm_bindgen(js_name = setColumnFormat)]
+    pub fn set_column_format(
+        &mut self,
+        col_idx: usize,
+        spec: Option<String>,
+    ) -> Result<(), JsError> {
+        let spec = spec
+            .map(|s| FormatSpec::parse(&s))
+            .transpose()
+            .map_err(|e| JsError::new(&e))?;
+        self.columnar.set_column_format(col_idx, spec);
+        Ok(())
+    }
+
+    /// Validate the cells of a column against `{ required?, min?, max?, pattern?,
+    /// enum? }` (`undefined` drops the rules): `min`/`max` bound numbers and the
+    /// length of strings, `pattern` is a regular expression strings must match,
+    /// and `enum` lists the allowed values. Checked for every row on data loads
+    /// and for each cell as it is written; failing cells get the `CELL_FLAG_INVALID`
+    /// style flag (`getValidationErrors` lists them).
+    #[wasm_bindgen(js_name = setColumnValidation)]
+    pub fn set_column_validation(&mut self, col_idx: usize, rules: JsValue) -> Result<(), JsError> {
+        let rules: Option<JsValidationRules> = serde_wasm_bindgen::from_value(rules)?;
+        let rules = rules
+            .map(|rules| -> Result<ValidationRules, JsError> {
+                Ok(ValidationRules {
+                    required: rules.required,
+                    min: rules.min,
+                    max: rules.max,
+                    pattern: rules
+                        .pattern
+                        .map(|p| Regex::new(&p))
+                        .transpose()
+                        .map_err(|e| JsError::new(&e.to_string()))?,
+                    allowed: rules.allowed,
+                })
+            })
+            .transpose()?;
+        self.columnar.set_column_validation(col_idx, rules);
+        Ok(())
+    }
+
+    /// `[viewRow, col, errors, ...]` of the cells failing their column's rules in a
+    /// `{ startRow, endRow?, startCol?, endCol? }` range of view positions × table
+    /// columns (the whole view for `undefined`). `errors` is a bit set: 1 required,
+    /// 2 below `min`, 4 above `max`, 8 `pattern`, 16 `enum`.
+    #[wasm_bindgen(js_name = getValidationErrors)]
+    pub fn get_validation_errors(&self, range: JsValue) -> Result<Vec<u32>, JsError> {
+        let range: Option<JsCellRange> = serde_wasm_bindgen::from_value(range)?;
+        let view = self.columnar.view_indices();
+        let (rows, cols) =
+            range.map_or_else(|| (0..=usize::MAX, 0..=usize::MAX), |range| range.bounds());
+        let col_count = self.columnar.data.len();
+        let mut out = Vec::new();
+        if !self.columnar.has_validation() || view.is_empty() || col_count == 0 {
+            return Ok(out);
+        }
+        let rows = *rows.start()..=(*rows.end()).min(view.len() - 1);
+        let cols = *cols.start()..=(*cols.end()).min(col_count - 1);
+        for pos in rows {
+            let row = view[pos] as usize;
+            if self.columnar.view_group(pos).is_some() {
+                continue;
+            }
+            for col in cols.clone() {
+                let errors = self.columnar.cell_validation_errors(col, row);
+                if errors != 0 {
+                    out.extend([pos as u32, col as u32, errors]);
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Conditional formatting rules, evaluated for the data cells of each frame
+    /// into the layout buffer's style field (`readCellStyle`): `[{ target,
+    /// condition, styleId }]`, where `condition` is a filter (`{ op, value?,
+    /// columnIndex?, formatted? }`, e.g. `{ op: "lt", value: 0 }`), a rank (`{ top,
+    /// percent?, bottom? }`, e.g. `{ top: 10, percent: true }`), or a color scale
+    /// (`{ scale: buckets }`, giving `styleId + bucket`). A cell takes the first
+    /// rule of its column it meets; ranks and scales cover the filtered rows.
+    #[wasm_bindgen(js_name = setConditionalFormats)]
+    pub fn set_conditional_formats(&mut self, rules: JsValue) -> Result<(), JsError> {
+        let rules: Vec<types::JsConditionalFormat> = serde_wasm_bindgen::from_value(rules)?;
+        let rules = rules
+            .into_iter()
+            .map(convert::convert_conditional_format)
+            .collect::<Result<_, _>>()
+            .map_err(|e| JsError::new(&e))?;
+        self.columnar.set_conditional_formats(rules);
+        Ok(())
+    }
+
+    /// Show numeric column `col_idx` as a heatmap or data bars: `{ type:
+    /// "colorScale" | "dataBar", domain?: [min, max], colors?: string[] }`, or
+    /// `undefined` to turn it off. Without `domain`, cells are placed in the
+    /// column's min–max range over the filtered rows; `colors` are hex color
+    /// stops from low to high (default white to blue). Read the results with
+    /// `getDataVisualizationInfo`.
+    #[wasm_bindgen(js_name = setDataVisualization)]
+    pub fn set_data_visualization(
+        &mut self,
+        col_idx: usize,
+        options: JsValue,
+    ) -> Result<(), JsError> {
+        let visual = if options.is_undefined() || options.is_null() {
+            None
+        } else {
+            let options: types::JsDataVisualization = serde_wasm_bindgen::from_value(options)?;
+            Some(convert::convert_data_visualization(options).map_err(|e| JsError::new(&e))?)
+        };
+        self.columnar.set_data_visualization(col_idx, visual);
+        Ok(())
+    }
+
+    /// Draw equal consecutive values of column `col_idx` as one cell spanning their
+    /// rows (`false` = one cell per row). Covered cells stay in the layout buffer
+    /// with zero height and `REGION_MERGED`.
+    #[wasm_bindgen(js_name = setMergeEqualValues)]
+    pub fn set_merge_equal_values(&mut self, col_idx: usize, enabled: bool) {
+        self.columnar
+            .set_row_merge(col_idx, enabled.then_some(RowMerge::EqualValues));
+    }
+
+    /// Explicit row spans for column `col_idx`: per data row, the number of view
+    /// rows its cell spans (0 = covered by a span above), or `undefined` to clear.
+    #[wasm_bindgen(js_name = setRowSpans)]
+    pub fn set_row_spans(&mut self, col_idx: usize, spans: Option<Vec<u32>>) {
+        self.columnar
+            .set_row_merge(col_idx, spans.map(RowMerge::Spans));
+    }
+
+    /// Override the layout of one data cell on top of its column's style:
+    /// `{ padding?: [top, right, bottom, left], align?, height? }`, or `null` to
+    /// clear. `row` is a data row index, so the override follows sorts and filters.
+    #[wasm_bindgen(js_name = setCellStyle)]
+    pub fn set_cell_style(
+        &mut self,
+        row: usize,
+        col_idx: usize,
+        style: JsValue,
+    ) -> Result<(), JsError> {
+        let style: Option<JsCellStyle> = serde_wasm_bindgen::from_value(style)?;
+        self.columnar
+            .set_cell_style(row, col_idx, style.as_ref().map(convert_cell_style));
+        Ok(())
+    }
+
+    /// Drop all `setCellStyle` overrides.
+    #[wasm_bindgen(js_name = clearCellStyles)]
+    pub fn clear_cell_styles(&mut self) {
+        self.columnar.clear_cell_styles();
+    }
+
+    /// Make the data cell at `row`, `col_idx` span `span` visible columns; the
+    /// cells it covers in that row get zero width and `REGION_MERGED`. A span of
+    /// 0 or 1 clears it. Follows the row through sorts.
+    #[wasm_bindgen(js_name = setCellColSpans)]
+    pub fn set_cell_col_spans(&mut self, row: usize, col_idx: usize, span: usize) {
+        self.columnar.set_cell_col_span(row, col_idx, span);
+    }
+
+    /// Drop all `setCellColSpans` spans.
+    #[wasm_bindgen(js_name = clearCellColSpans)]
+    pub fn clear_cell_col_spans(&mut self) {
+        self.columnar.clear_col_spans();
+    }
+
+    /// Register a JS predicate `(value, rowIndex) => boolean` for a column, or clear it
+    /// with `undefined`. Called only for rows that pass every native filter; `value` is
+    /// `null` for null cells. A callback that throws rejects the row.
+    #[wasm_bindgen(js_name = setCustomFilter)]
+    pub fn set_custom_filter(&mut self, col_idx: usize, callback: Option<js_sys::Function>) {
+        let predicate = callback.map(|f| -> CustomFilterFn {
+            Box::new(move |row, value| {
+                f.call2(
+                    &JsValue::NULL,
+                    &cell_value_to_js(value),
+                    &JsValue::from_f64(row as f64),
+                )
+                .is_ok_and(|r| r.is_truthy())
+            })
+        });
+        self.columnar.set_custom_filter(col_idx, predicate);
+    }
+
+    /// Re-run custom filter callbacks on the next rebuild (call when the state they
+    /// depend on changes).
+    #[wasm_bindgen(js_name = invalidateCustomFilters)]
+    pub fn invalidate_custom_filters(&mut self) {
+        self.columnar.invalidate_custom_filters();
+    }
+
+    /// Enable or disable the sorted prefix index for a string column, turning
+    /// `startsWith`/`endsWith` filters into a binary-search range over unique values.
+    #[wasm_bindgen(js_name = setPrefixIndex)]
+    pub fn set_prefix_index(&mut self, col_idx: usize, enabled: bool) {
+        self.columnar.set_prefix_index(col_idx, enabled);
+    }
+
+    /// Distinct values and counts for a column under all filters except its own.
+    /// Returns `[{ value, count }]` sorted by count descending; `value` is null for nulls.
+    #[wasm_bindgen(js_name = getFilterFacets)]
+    pub fn get_filter_facets(&mut self, col_idx: usize) -> Result<JsValue, JsError> {
+        let facets: Vec<_> = self
+            .columnar
+            .filter_facets(col_idx)
+            .into_iter()
+            .map(facet_to_js)
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&facets)?)
+    }
+
+    /// Number of distinct values of a column under all filters except its own (as
+    /// `getFilterFacets` counts them, without nulls), e.g. to disable a facet list
+    /// for high-cardinality columns. Unless `exact`, columns over 100k rows get a
+    /// `HyperLogLog` estimate (about 1% off) in fixed memory.
+    #[wasm_bindgen(js_name = getDistinctCount)]
+    pub fn get_distinct_count(&mut self, col_idx: usize, exact: bool) -> usize {
+        self.columnar.distinct_count(col_idx, exact)
+    }
+
+    /// Set global filter on the columnar store.
+    #[wasm_bindgen(js_name = setGlobalFilter)]
+    pub fn set_global_filter(&mut self, query: Option<String>) {
+        self.columnar
+            .set_global_filter(query.map(|q| GlobalFilter { query: q }));
+    }
+
+    // ── Filter presets ────────────────────────────────────────────────
+
+    /// Save the current column filters, global filter, and sort as a named preset.
+    #[wasm_bindgen(js_name = saveFilterPreset)]
+    pub fn save_filter_preset(&mut self, name: &str) {
+        self.columnar.save_preset(name);
+    }
+
+    /// Apply a saved preset. Returns `false` if no preset has that name.
+    #[wasm_bindgen(js_name = applyFilterPreset)]
+    pub fn apply_filter_preset(&mut self, name: &str) -> bool {
+        self.columnar.apply_preset(name)
+    }
+
+    /// Delete a saved preset. Returns `false` if no preset has that name.
+    #[wasm_bindgen(js_name = deleteFilterPreset)]
+    pub fn delete_filter_preset(&mut self, name: &str) -> bool {
+        self.columnar.delete_preset(name)
+    }
+
+    /// Names of saved presets, sorted.
+    #[wasm_bindgen(js_name = listFilterPresets)]
+    pub fn list_filter_presets(&self) -> Vec<String> {
+        self.columnar
+            .presets()
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    /// Current view state and all presets as a plain JS object, suitable for
+    /// `JSON.stringify` and a later `importState()`.
+    #[wasm_bindgen(js_name = exportState)]
+    pub fn export_state(&self) -> Result<JsValue, JsError> {
+        let state = JsEngineState {
+            current: view_state_to_js(&self.columnar.view_state()),
+            presets: self
+                .columnar
+                .presets()
+                .map(|(name, p)| JsNamedPreset {
+                    name: name.to_string(),
+                    state: view_state_to_js(p),
+                })
+                .collect(),
+        };
+        Ok(serde_wasm_bindgen::to_value(&state)?)
+    }
+
+    /// Restore a blob produced by `exportState()`: presets are added (replacing
+    /// same-named ones) and the current view state is applied.
+    #[wasm_bindgen(js_name = importState)]
+    pub fn import_state(&mut self, state: JsValue) -> Result<(), JsError> {
+        let state: JsEngineState = serde_wasm_bindgen::from_value(state)?;
+        for preset in &state.presets {
+            self.columnar
+                .insert_preset(preset.name.clone(), convert_view_state(&preset.state));
+        }
+        self.columnar
+            .set_view_state(convert_view_state(&state.current));
+        Ok(())
+    }
+
+    /// View preferences as compact versioned bytes (not data), for
+    /// `localStorage` or a backend: sort, filters, global filter, grouping and
+    /// collapsed groups, expanded tree rows, manual row order, column widths and
+    /// visibility, and the last frame's scroll position. Column definitions and
+    /// their order stay with the columns passed to `updateViewportColumnar`.
+    #[wasm_bindgen(js_name = serializeState)]
+    pub fn serialize_state(&self) -> Vec<u8> {
+        SavedState {
+            column_widths: self.column_widths.clone(),
+            hidden_columns: self.hidden_columns.clone(),
+            scroll_top: self.last_frame.as_ref().map_or(0.0, |f| f.scroll_top),
+            ..self.columnar.saved_state()
+        }
+        .encode()
+    }
+
+    /// Restore bytes from `serializeState()` (of this or an older version). The
+    /// next `updateViewportColumnar` lays out at the saved scroll offset and
+    /// returns it as `anchored_scroll_top`. Not recorded for `undo`. Nothing is
+    /// restored if the state refers to a column past the loaded ones.
+    #[wasm_bindgen(js_name = restoreState)]
+    pub fn restore_state(&mut self, bytes: &[u8]) -> Result<(), JsError> {
+        let state = SavedState::decode(bytes).map_err(|e| JsError::new(&e))?;
+        let columns = self.columnar.data.len().max(self.column_keys.len());
+        state.check_columns(columns).map_err(|e| JsError::new(&e))?;
+        self.columnar.restore_saved_state(&state);
+        self.column_widths = state.column_widths;
+        self.hidden_columns = state.hidden_columns;
+        self.layout.invalidate_cache();
+        self.anchored_scroll_top = Some(state.scroll_top);
+        Ok(())
+    }
+
+    /// Apply a batch of commands (set sort, column filters, global filter, scroll,
+    /// viewport resize, column resize) encoded by `encodeCommands`, in one call
+    /// from e.g. a Web Worker that received the buffer by transfer. Then lays out
+    /// a frame like `updateViewportColumnar`, reusing the last frame's columns,
+    /// container, and row pins at the batch's scroll offsets and viewport size,
+    /// and returns its metadata (empty before the first `updateViewportColumnar`).
+    /// Nothing is applied if the batch fails to parse.
+    #[wasm_bindgen(js_name = applyCommands)]
+    pub fn apply_commands(&mut self, bytes: &[u8]) -> Result<Vec<f64>, JsError> {
+        let commands = commands::decode(bytes).map_err(|e| JsError::new(&e))?;
+        for command in &commands {
+            match command {
+                Command::SetSort(configs) => {
+                    for expression in configs.iter().filter_map(|c| c.expression.as_deref()) {
+                        Expr::parse(expression).map_err(|e| JsError::new(&e))?;
+                    }
+                }
+                Command::SetFilters(filters) => {
+                    for (_, expr) in parse_filter_expressions(filters) {
+                        expr.map_err(|e| JsError::new(&e))?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let mut inputs = self.frame_inputs.clone();
+        for command in commands {
+            match command {
+                Command::SetSort(configs) => self.columnar.set_sort(configs),
+                Command::SetFilters(filters) => {
+                    self.sync_time_reference();
+                    self.columnar.set_column_filters(filters);
+                }
+                Command::SetGlobalFilter(query) => self
+                    .columnar
+                    .set_global_filter(query.map(|query| GlobalFilter { query })),
+                Command::Scroll { top, left } => {
+                    if let Some(inputs) = &mut inputs {
+                        inputs.scroll_top = top / self.zoom;
+                        inputs.viewport.scroll_top = top / self.zoom;
+                        inputs.viewport.scroll_left = (left / self.zoom) as f32;
+                    }
+                }
+                Command::Resize { width, height } => {
+                    self.columnar.set_scroll_config(
+                        self.columnar.row_height(),
+                        height,
+                        self.columnar.overscan(),
+                    );
+                    if let Some(inputs) = &mut inputs {
+                        inputs.viewport.width = (width / self.zoom) as f32;
+                        inputs.viewport.height = (height / self.zoom) as f32;
+                    }
+                }
+                Command::ResizeColumn {
+                    column_index,
+                    width,
+                    neighbor,
+                } => {
+                    let mode = if neighbor { "neighbor" } else { "grow" };
+                    self.resize_column(column_index, width as f32, mode);
+                }
+            }
+        }
+        Ok(inputs.map_or_else(Vec::new, |inputs| self.lay_out_frame(inputs, false)))
+    }
+
+    /// Set pagination state on the columnar store.
+    #[wasm_bindgen(js_name = setPagination)]
+    pub fn set_pagination(&mut self, page_index: Option<u32>, page_size: Option<u32>) {
+        self.columnar.set_pagination(page_index, page_size);
+    }
+
+    /// Set scroll configuration on the columnar store.
+    #[wasm_bindgen(js_name = setColumnarScrollConfig)]
+    pub fn set_columnar_scroll_config(
+        &mut self,
+        row_height: f64,
+        viewport_height: f64,
+        overscan: usize,
+    ) {
+        self.columnar
+            .set_scroll_config(row_height, viewport_height, overscan);
+    }
+
+    /// Rebuild view indices only (for row pinning: call before getViewIndices, then updateViewportColumnar with skipRebuild=true).
+    #[wasm_bindgen(js_name = rebuildView)]
+    pub fn rebuild_view_only(&mut self) {
+        self.rebuild_view_anchored();
+    }
+
+    /// Keep the on-screen position of an anchor row when filters, sorts, or data
+    /// change the view: `row` (data index), or the top visible row when omitted.
+    /// The next `updateViewportColumnar` lays out at the adjusted scroll offset and
+    /// returns it in its metadata.
+    #[wasm_bindgen(js_name = setScrollAnchoring)]
+    pub fn set_scroll_anchoring(&mut self, enabled: bool, row: Option<u32>) {
+        self.scroll_anchoring = enabled;
+        self.anchor_row = row.map(|r| r as usize);
+        self.anchored_scroll_top = None;
+    }
+
+    /// Zoom the table by `scale` (1 = 100%; non-positive values reset it). Column
+    /// widths, row and header heights, and line height stay in layout px, while the
+    /// viewport size and scroll offsets passed in, and every position, size, and
+    /// height returned (layout buffer, total and effective row height, content and
+    /// pinned widths, scroll offsets), are in zoomed px. Rows are sliced in layout
+    /// px, so the visible range stays exact at any zoom.
+    #[wasm_bindgen(js_name = setZoom)]
+    pub fn set_zoom(&mut self, scale: f64) {
+        self.zoom = if scale.is_finite() && scale > 0.0 {
+            scale
+        } else {
+            1.0
+        };
+    }
+
+    /// Snap the x, y, width, and height of emitted cells to device pixels at
+    /// `devicePixelRatio` device px per px (after zoom), or `undefined` to emit
+    /// unrounded positions. Edges are snapped rather than sizes, so adjacent cells
+    /// keep meeting without seams or overlaps; draw at the emitted positions
+    /// without rounding them again.
+    #[wasm_bindgen(js_name = setPixelSnapping)]
+    pub fn set_pixel_snapping(&mut self, device_pixel_ratio: Option<f64>) {
+        self.pixel_ratio = device_pixel_ratio
+            .filter(|ratio| ratio.is_finite() && *ratio > 0.0)
+            .map(|ratio| ratio as f32);
+    }
+
+    /// Unified hot path: rebuild view + virtual slice + layout buffer.
+    /// Returns metadata as Float64Array:
+    /// `filtered_count` counts view rows, group header rows included; `filtered_total` counts
+    /// the data rows passing the filters across pages.
+    /// [cell_count, visible_start, visible_end, total_height, filtered_count, generation, total_count, visible_count, effective_row_height, filtered_total, pinned_left_width, pinned_right_width, full_repaint, dy, lead_start, lead_end, trail_start, trail_end, anchored_scroll_top, content_width, missing_block_count, ...missing_blocks]
+    /// Repaint ([12..18]): `full_repaint` = 1 when anything besides the scroll offset changed
+    /// since the last call. Otherwise the previously drawn scrolled rows are still valid,
+    /// moved by `dy` px, and only layout cells lead_start..lead_end and trail_start..trail_end
+    /// (newly exposed rows) need painting.
+    /// anchored_scroll_top ([18]): with `setScrollAnchoring`, the scroll offset this frame was
+    /// laid out at to keep the anchor row in place after a view rebuild (NaN otherwise);
+    /// apply it to the scroll container.
+    /// content_width ([19]): total width of the laid-out columns incl. gaps, margins and container
+    /// padding, for sizing the horizontal scrollbar. Viewport `scrollLeft` shifts the x of every
+    /// non-pinned cell (leave it 0 when the renderer translates horizontally itself).
+    /// pinned_*_width: frozen column regions (columns with `pinned`); scrollable columns
+    /// are laid out at `scrollLeft` between them and should be clipped to that band.
+    /// missing_blocks: in lazy-row mode (`setRowBlockSize`), unloaded blocks the rendered rows need.
+    /// filtered_total ([9]) = row count after filter+sort but before pagination.
+    /// Optional 5th/6th: pinnedTop, pinnedBottom. Optional 7th: skipRebuild (when true, skip rebuild_view; use after rebuildView() for row pinning).
+    /// With variable row heights (`setRowHeights`), the slice and total_height come from
+    /// the per-row heights and each cell's y/height in the layout buffer is exact.
+    /// Cell y positions are viewport-relative and computed from the f64 scroll offset,
+    /// so rows stay exact at offsets beyond f32 precision (tens of millions of rows);
+    /// only row-pinned scrolled rows carry content y, which JS translates.
+    /// With a viewport `footerHeight`, the footer cells follow the header cells and
+    /// total_height includes the footer.
+    /// Columns with a rotated `headerOrientation` grow the header row to fit their
+    /// measured `headerLabel`; header cells carry the rotation in `FIELD_ROTATION`.
+    /// In transposed mode (`setTransposed`) the header cells are the field labels,
+    /// frozen at the left, and each rendered record is a column of data cells:
+    /// records are virtualized horizontally by viewport `scrollLeft` (visible_start
+    /// and visible_end are records), fields scroll vertically by `scrollTop`,
+    /// total_height is the height of the fields, and every frame is a full repaint.
+    /// Row pinning, cell items, and `predictNextSlice` do not apply.
+    #[allow(clippy::too_many_arguments)]
+    #[wasm_bindgen(js_name = updateViewportColumnar)]
+    pub fn update_viewport_columnar(
+        &mut self,
+        scroll_top: f64,
+        viewport_js: JsValue,
+        columns_js: JsValue,
+        container_js: JsValue,
+        pinned_top_js: Option<f64>,
+        pinned_bottom_js: Option<f64>,
+        skip_rebuild_js: Option<bool>,
+    ) -> Result<Vec<f64>, JsError> {
+        // Parse viewport + columns + container BEFORE virtual scroll
+        let vp: JsViewport = serde_wasm_bindgen::from_value(viewport_js)?;
+        let cols: Vec<JsColumnLayout> = serde_wasm_bindgen::from_value(columns_js)?;
+        self.sync_column_keys(cols.iter().map(|c| c.key.clone()).collect());
+
+        let container = if container_js.is_undefined() || container_js.is_null() {
+            ContainerLayout::default()
+        } else {
+            let jc: JsContainerLayout = serde_wasm_bindgen::from_value(container_js)?;
+            convert_container(&jc)
+        };
+
+        let zoom = self.zoom as f32;
+        let viewport = Viewport {
+            width: vp.width / zoom,
+            height: vp.height / zoom,
+            row_height: vp.row_height,
+            header_height: vp.header_height,
+            scroll_top: vp.scroll_top / self.zoom,
+            scroll_left: vp.scroll_left / zoom,
+            line_height: vp.line_height,
+            sticky_header: vp.sticky_header,
+            footer_height: vp.footer_height,
+            sticky_footer: vp.sticky_footer,
+        };
+
+        let columns: Vec<ColumnLayout> = cols
+            .iter()
+            .map(|c| ColumnLayout {
+                header_label_width: c
+                    .header_label
+                    .as_deref()
+                    .map_or(0.0, |label| self.header_label_width(label, vp.line_height)),
+                ..convert_column(c)
+            })
+            .collect();
+        let pins = (
+            pinned_top_js.map_or(0, |v| v as usize),
+            pinned_bottom_js.map_or(0, |v| v as usize),
+        );
+        Ok(self.lay_out_frame(
+            FrameInputs {
+                scroll_top: scroll_top / self.zoom,
+                viewport,
+                columns,
+                container,
+                pins,
+                header_row_count: vp.header_row_count.max(1),
+            },
+            skip_rebuild_js.unwrap_or(false),
+        ))
+    }
+
+    /// Lay out the slice the viewport reaches after scrolling `velocity` px from the
+    /// last `updateViewportColumnar` (e.g. the expected per-frame delta of a fling)
+    /// into a back buffer (`getPredictedLayoutBufferInfo`), reusing that frame's
+    /// viewport, columns, and row pins. Returns [cell_count, visible_start,
+    /// visible_end, scroll_top], or an empty array before the first frame.
+    #[allow(clippy::too_many_lines)]
+    #[wasm_bindgen(js_name = predictNextSlice)]
+    pub fn predict_next_slice(&mut self, velocity: f64) -> Vec<f64> {
+        let Some(frame) = self.last_frame.take() else {
+            return Vec::new();
+        };
+        let scroll_top = (frame.scroll_top + velocity / self.zoom).max(0.0);
+        let total_rows = self.columnar.view_indices().len();
+        let row_pinned = frame.pinned_top > 0 || frame.pinned_bottom > 0;
+        let scroll_state = react_wasm_table_core::virtual_scroll::ScrollState {
+            scroll_top,
+            viewport_height: self.columnar.viewport_height() / self.zoom,
+            row_height: self.last_row_height,
+            total_rows,
+            overscan: self.columnar.overscan(),
+            pinned_top: row_pinned.then_some(frame.pinned_top),
+            pinned_bottom: row_pinned.then_some(frame.pinned_bottom),
+        };
+        let slice = self
+            .columnar
+            .virtual_slice(&scroll_state, self.last_row_height);
+        let rows = slice.start_index..slice.end_index;
+
+        let (visible, col_ids) = self.visible_columns(&frame.columns);
+        self.layout.set_header_layout(
+            self.header_layout
+                .as_ref()
+                .map(|header| header.select(&col_ids)),
+        );
+        let col_count = visible.len();
+        let detail_cells = self.detail_cell_count(
+            (0..frame.pinned_top)
+                .chain(rows.clone())
+                .chain(total_rows.saturating_sub(frame.pinned_bottom)..total_rows),
+        );
+        let footer_cells = frame.viewport.footer_cells(col_count);
+        let row_count = frame.pinned_top + rows.len() + frame.pinned_bottom;
+        let total_cells = col_count * (1 + row_count)
+            + detail_cells
+            + footer_cells
+            + row_count * cell_items_per_row(&visible);
+        let needed = layout_buffer::buf_len(total_cells);
+        if self.predicted_buf.len() < needed {
+            self.predicted_buf.resize(needed, 0.0);
+        }
+        let viewport = Viewport {
+            scroll_top,
+            ..frame.viewport
+        };
+        self.predicted_cell_count = if row_pinned {
+            let params = react_wasm_table_core::layout::RowPinnedLayoutParams {
+                viewport: &viewport,
+                container: &frame.container,
+                pinned_top: frame.pinned_top,
+                pinned_bottom: frame.pinned_bottom,
+                scroll_top,
+                total_rows,
+                middle_range: rows.clone(),
+                header_row_count: frame.header_row_count,
+            };
+            self.layout
+                .compute_into_buffer_row_pinned(&visible, &params, &mut self.predicted_buf)
+        } else {
+            self.layout.compute_into_buffer(
+                &visible,
+                &viewport,
+                &frame.container,
+                rows.clone(),
+                &mut self.predicted_buf,
+                frame.header_row_count,
+            )
+        };
+        layout_buffer::remap_columns(
+            &mut self.predicted_buf,
+            0..self.predicted_cell_count,
+            &col_ids,
+        );
+        self.predicted_cell_count = apply_row_layout(
+            &mut self.columnar,
+            &mut self.predicted_buf,
+            col_count + footer_cells..self.predicted_cell_count,
+            frame.header_row_count,
+            self.last_row_height,
+            viewport.width,
+        );
+        place_footer(
+            &mut self.predicted_buf,
+            &viewport,
+            col_count,
+            self.predicted_cell_count,
+        );
+        self.predicted_cell_count += self.layout.append_cell_items(
+            &frame.columns,
+            viewport.line_height,
+            &mut self.predicted_buf,
+            col_count + footer_cells..self.predicted_cell_count,
+            self.predicted_cell_count,
+        );
+        write_frame_baselines(
+            &frame.columns,
+            self.header_layout.as_ref(),
+            viewport.line_height,
+            &mut self.predicted_buf,
+            col_count..self.predicted_cell_count,
+        );
+        style_cells(
+            &mut self.columnar,
+            &self.selection,
+            &self.selection_model,
+            &mut self.predicted_buf,
+            0..self.predicted_cell_count,
+            frame.header_row_count,
+        );
+        output_cells(
+            &mut self.predicted_buf,
+            0..self.predicted_cell_count,
+            self.zoom,
+            self.pixel_ratio,
+        );
+        self.predicted = Some((self.columnar.view_version(), rows.clone(), scroll_top));
+        self.last_frame = Some(frame);
+        vec![
+            self.predicted_cell_count as f64,
+            rows.start as f64,
+            rows.end as f64,
+            scroll_top * self.zoom,
+        ]
+    }
+
+    /// Swap the `predictNextSlice` back buffer in as the layout buffer
+    /// (`getLayoutBufferInfo`), if the view has not changed since the prediction.
+    /// Returns whether it was swapped; the swapped-in slice becomes the baseline
+    /// for the next repaint ranges.
+    #[wasm_bindgen(js_name = promotePredictedLayout)]
+    pub fn promote_predicted_layout(&mut self) -> bool {
+        let Some((version, rows, scroll_top)) = self.predicted.take() else {
+            return false;
+        };
+        if version != self.columnar.view_version() || self.columnar.view_needs_rebuild() {
+            return false;
+        }
+        self.retain_layout();
+        std::mem::swap(&mut self.layout_buf, &mut self.predicted_buf);
+        std::mem::swap(&mut self.layout_cell_count, &mut self.predicted_cell_count);
+        if let Some(frame) = &mut self.last_frame {
+            frame.rows = rows;
+            frame.scroll_top = scroll_top;
+        }
+        true
+    }
+
+    /// `scrollTop` that brings view row `viewIndex` into view, using the row and
+    /// header heights of the last `updateViewportColumnar`. `align` is `"start"`,
+    /// `"center"`, `"end"`, or `"nearest"` (default: only scroll if the row is not
+    /// fully visible at `scrollTop`). Pass the same pinned counts as the hot path.
+    #[allow(clippy::needless_pass_by_value)] // wasm_bindgen takes optional strings owned
+    #[wasm_bindgen(js_name = computeScrollTopForRow)]
+    pub fn compute_scroll_top_for_row(
+        &mut self,
+        view_index: usize,
+        scroll_top: f64,
+        align: Option<String>,
+        pinned_top: Option<usize>,
+        pinned_bottom: Option<usize>,
+    ) -> f64 {
+        let state = react_wasm_table_core::virtual_scroll::ScrollState {
+            scroll_top: scroll_top / self.zoom,
+            viewport_height: self.columnar.viewport_height() / self.zoom,
+            row_height: self.last_row_height,
+            total_rows: self.columnar.view_indices().len(),
+            overscan: 0,
+            pinned_top,
+            pinned_bottom,
+        };
+        let heights = self.columnar.view_row_heights(self.last_row_height);
+        react_wasm_table_core::virtual_scroll::scroll_top_for_row(
+            &state,
+            heights,
+            self.last_header_height,
+            self.last_sticky_header,
+            view_index,
+            parse_scroll_align(align.as_deref()),
+        ) * self.zoom
+    }
+
+    /// Like `computeScrollTopForRow`, by data row index. `undefined` when the row is
+    /// filtered out or on another page.
+    #[wasm_bindgen(js_name = computeScrollTopForDataRow)]
+    pub fn compute_scroll_top_for_data_row(
+        &mut self,
+        row: usize,
+        scroll_top: f64,
+        align: Option<String>,
+        pinned_top: Option<usize>,
+        pinned_bottom: Option<usize>,
+    ) -> Option<f64> {
+        let view_index = self.columnar.view_position(row)?;
+        Some(self.compute_scroll_top_for_row(
+            view_index,
+            scroll_top,
+            align,
+            pinned_top,
+            pinned_bottom,
+        ))
+    }
+
+    /// Focus the cell at view position `row`, table column `col` for `navigate`.
+    #[wasm_bindgen(js_name = setFocusedCell)]
+    pub fn set_focused_cell(&mut self, row: usize, col: usize) {
+        self.focus = Some((row, col));
+    }
+
+    /// [row, col] of the focused cell, or empty when nothing is focused.
+    #[wasm_bindgen(js_name = getFocusedCell)]
+    pub fn get_focused_cell(&self) -> Vec<usize> {
+        self.focus
+            .map_or_else(Vec::new, |(row, col)| vec![row, col])
+    }
+
+    /// Move the focus for a key press and return [row, col, scrollTop]: the newly
+    /// focused view position and table column, and the `scrollTop` that reveals
+    /// it. `direction` is `up`, `down`, `left`, `right`, `pageUp`, `pageDown`,
+    /// `home`, or `end` (or the DOM key name); `modifiers` is `{ ctrl?, meta? }`,
+    /// either of which jumps to the first or last row or column.
+    ///
+    /// Columns follow the last `updateViewportColumnar` in on-screen order
+    /// (left-pinned, scrollable, right-pinned) without hidden columns; page keys
+    /// move by the viewport height below the header, over variable row heights.
+    /// Without a focused cell the first cell is focused. Empty before the first
+    /// frame or when the view is empty.
+    #[wasm_bindgen(js_name = navigate)]
+    pub fn navigate(&mut self, direction: &str, modifiers: JsValue) -> Result<Vec<f64>, JsError> {
+        let key = NavKey::parse(direction).map_err(|e| JsError::new(&e))?;
+        let modifiers: JsNavModifiers = if modifiers.is_undefined() || modifiers.is_null() {
+            JsNavModifiers::default()
+        } else {
+            serde_wasm_bindgen::from_value(modifiers)?
+        };
+        let Some(frame) = &self.last_frame else {
+            return Ok(Vec::new());
+        };
+        let order = navigation::column_order(&frame.columns, |col| {
+            self.hidden_columns.get(col).copied().unwrap_or(false)
+        });
+        let (scroll_top, pinned_top, pinned_bottom) =
+            (frame.scroll_top, frame.pinned_top, frame.pinned_bottom);
+        let row_height = self.last_row_height;
+        let viewport_height = self.columnar.viewport_height() / self.zoom;
+        let total_rows = self.columnar.view_indices().len();
+        let heights = self.columnar.view_row_heights(row_height);
+        let grid = NavGrid {
+            rows: total_rows,
+            cols: order.len(),
+            row_height,
+            heights,
+            page_height: (viewport_height - self.last_header_height).max(row_height),
+        };
+        let from = self.focus.map_or((0, 0), |(row, col)| {
+            (row, order.iter().position(|&c| c == col).unwrap_or(0))
+        });
+        let Some((row, position)) =
+            navigation::navigate(&grid, from, key, modifiers.ctrl || modifiers.meta)
+        else {
+            return Ok(Vec::new());
+        };
+        let state = ScrollState {
+            scroll_top,
+            viewport_height,
+            row_height,
+            total_rows,
+            overscan: 0,
+            pinned_top: (pinned_top > 0).then_some(pinned_top),
+            pinned_bottom: (pinned_bottom > 0).then_some(pinned_bottom),
+        };
+        let scroll_top = scroll_top_for_row(
+            &state,
+            heights,
+            self.last_header_height,
+            self.last_sticky_header,
+            row,
+            ScrollAlign::Nearest,
+        );
+        let col = order[position];
+        self.focus = Some((row, col));
+        Ok(vec![row as f64, col as f64, scroll_top * self.zoom])
+    }
+
+    /// Advance a time-sliced view rebuild by about `budget_ms`, starting one when
+    /// filters, sorts, or data changed: `{ done, progress, restarted }`. Frames keep
+    /// showing the previous view until a step reports `done`; a change between
+    /// steps abandons the stale rebuild and starts over (`restarted`).
+    #[wasm_bindgen(js_name = rebuildViewStep)]
+    pub fn rebuild_view_step(&mut self, budget_ms: f64) -> Result<JsValue, JsError> {
+        if self.columnar.view_needs_rebuild() {
+            self.rebuild_anchor = self.scroll_anchor_before_rebuild();
+        }
+        let progress = self.columnar.rebuild_view_step(budget_ms);
+        if progress.done {
+            if let Some(anchor) = self.rebuild_anchor.take() {
+                self.restore_scroll_anchor(anchor);
+            }
+            self.emit_view_changed();
+        }
+        Ok(serde_wasm_bindgen::to_value(&JsRebuildProgress {
+            done: progress.done,
+            progress: progress.progress,
+            restarted: progress.restarted,
+        })?)
+    }
+
+    /// Per-condition statistics (rows scanned/passed, time in µs, cache hits) of the
+    /// most recent view rebuild.
+    #[wasm_bindgen(js_name = getLastViewStats)]
+    pub fn get_last_view_stats(&self) -> Result<JsValue, JsError> {
+        let stats = view_stats_to_js(self.columnar.last_view_stats());
+        Ok(serde_wasm_bindgen::to_value(&stats)?)
+    }
+
+    /// Return [pointer_offset, length] for the view indices buffer.
+    #[wasm_bindgen(js_name = getColumnarViewIndicesInfo)]
+    pub fn get_columnar_view_indices_info(&self) -> Vec<usize> {
+        let indices = self.columnar.view_indices();
+        vec![indices.as_ptr() as usize, indices.len()]
+    }
+
+    // ── Layout cache ──────────────────────────────────────────────
+
+    /// Invalidate cached layout results. Forces recomputation on next frame.
+    #[wasm_bindgen(js_name = invalidateLayout)]
+    pub fn invalidate_layout(&mut self) {
+        self.layout.invalidate_cache();
+    }
+
+    /// Keep up to `capacity` column layouts (one per distinct header, row, or
+    /// group row height and column set; default 8, 0 disables the cache), and
+    /// as many (at least one) Taffy trees reused across layouts.
+    #[wasm_bindgen(js_name = setLayoutCacheCapacity)]
+    pub fn set_layout_cache_capacity(&mut self, capacity: usize) {
+        self.layout.set_cache_capacity(capacity);
+    }
+
+    // ── Composite (in-cell) layout ──────────────────────────────────
 
     /// Compute flexbox layout for children inside a composite cell container.
     ///
@@ -436,57 +3600,1288 @@ impl TableEngine {
             3 => FlexDirectionValue::ColumnReverse,
             _ => FlexDirectionValue::Row,
         };
-        let gap = input[3];
-        let align_items = decode_align(input[4]);
-        let justify_content = decode_justify(input[5]);
-        let padding = [input[6], input[7], input[8], input[9]];
-        let child_count = input[10] as usize;
+        let gap = input[3];
+        let align_items = decode_align(input[4]);
+        let justify_content = decode_justify(input[5]);
+        let padding = [input[6], input[7], input[8], input[9]];
+        let child_count = input[10] as usize;
+
+        let mut children = Vec::with_capacity(child_count);
+        for i in 0..child_count {
+            let base = 11 + i * 2;
+            children.push(react_wasm_table_core::layout::ChildSize {
+                width: input[base],
+                height: input[base + 1],
+            });
+        }
+
+        let params = react_wasm_table_core::layout::CompositeLayoutParams {
+            container_w,
+            container_h,
+            flex_direction,
+            gap,
+            align_items,
+            justify_content,
+            padding,
+        };
+        let positions = self.layout.compute_composite_layout(&params, &children);
+
+        let mut out = Vec::with_capacity(positions.len() * 4);
+        for p in &positions {
+            out.push(p.x);
+            out.push(p.y);
+            out.push(p.width);
+            out.push(p.height);
+        }
+        out
+    }
+
+    // ── Debug logging ──────────────────────────────────────────────
+
+    /// Initialize console_log backend and enable Debug-level logging.
+    #[cfg(feature = "debug-log")]
+    #[wasm_bindgen(js_name = enableDebugLog)]
+    pub fn enable_debug_log(&self) {
+        console_log::init_with_level(log::Level::Debug).ok();
+    }
+
+    /// Disable all log output at runtime.
+    #[cfg(feature = "debug-log")]
+    #[wasm_bindgen(js_name = disableDebugLog)]
+    pub fn disable_debug_log(&self) {
+        log::set_max_level(log::LevelFilter::Off);
+    }
+}
+
+impl TableEngine {
+    /// Lay out a frame of `updateViewportColumnar` from its parsed inputs (in
+    /// layout px) and return the frame metadata.
+    #[allow(clippy::too_many_lines)]
+    fn lay_out_frame(&mut self, inputs: FrameInputs, skip_rebuild: bool) -> Vec<f64> {
+        self.columnar.advance_update_tick();
+        if let Some(queue) = &mut self.upserts {
+            let changed = queue.apply(&mut self.columnar);
+            self.emit_data_edited("upsert", changed.iter().copied());
+            self.upserted_cells.extend(changed);
+        }
+        if !skip_rebuild {
+            self.rebuild_view_anchored();
+        }
+        let FrameInputs {
+            scroll_top,
+            mut viewport,
+            columns: input_columns,
+            container,
+            pins,
+            header_row_count,
+        } = inputs;
+        let anchored_scroll_top = self.anchored_scroll_top.take();
+        let scroll_top = anchored_scroll_top.unwrap_or(scroll_top);
+        if let Some(top) = anchored_scroll_top {
+            viewport.scroll_top = top;
+        }
+
+        let filtered_count = self.columnar.view_indices().len();
+        log::debug!(
+            "[wasm] updateViewport: scroll_top={:.1}, total_rows={}, filtered={}",
+            scroll_top,
+            self.columnar.row_count,
+            self.columnar.view_indices().len(),
+        );
+
+        let (columns, visible, col_ids, header) = self.frame_columns(&input_columns, &mut viewport);
+        self.frame_inputs = Some(FrameInputs {
+            scroll_top,
+            viewport: viewport.clone(),
+            columns: input_columns,
+            container: container.clone(),
+            pins,
+            header_row_count,
+        });
+        self.layout.set_header_layout(header);
+
+        if let Some(transposed) = self.transposed {
+            return self.update_transposed(
+                transposed,
+                &columns,
+                &visible,
+                &col_ids,
+                &viewport,
+                header_row_count,
+            );
+        }
+
+        // 3. Compute effective row height
+        let effective_row_height = f64::from(self.layout.compute_effective_row_height(
+            &visible,
+            &container,
+            viewport.width,
+            viewport.row_height,
+            viewport.line_height,
+        ));
+
+        self.last_row_height = effective_row_height;
+        self.last_header_height = f64::from(viewport.header_height);
+        self.last_sticky_header = viewport.sticky_header;
+
+        // 4. Row pinning: optional pinned_top, pinned_bottom (row counts), after
+        // the always-visible rows at either end of the view
+        let (always_top, always_bottom) = self.columnar.always_visible_counts();
+        let pinned_top = always_top + pins.0;
+        let pinned_bottom = always_bottom + pins.1;
+
+        let total_count = self.columnar.row_count;
+        let col_count = visible.len();
+
+        let frame_key = {
+            let mut hasher = DefaultHasher::new();
+            viewport_layout_key(&visible, &container, &viewport).hash(&mut hasher);
+            (pinned_top, pinned_bottom, header_row_count, &col_ids).hash(&mut hasher);
+            self.zoom.to_bits().hash(&mut hasher);
+            self.pixel_ratio.map(f32::to_bits).hash(&mut hasher);
+            self.selection.hash(&mut hasher);
+            self.selection_model.version().hash(&mut hasher);
+            self.header_layout
+                .as_ref()
+                .map(HeaderLayout::key)
+                .hash(&mut hasher);
+            hasher.finish()
+        };
+
+        self.retain_layout();
+        if pinned_top > 0 || pinned_bottom > 0 {
+            // Row pinning path: three segments (top, middle visible, bottom)
+            let scroll_state = react_wasm_table_core::virtual_scroll::ScrollState {
+                scroll_top,
+                viewport_height: self.columnar.viewport_height() / self.zoom,
+                row_height: effective_row_height,
+                total_rows: filtered_count,
+                overscan: self.columnar.overscan(),
+                pinned_top: Some(pinned_top),
+                pinned_bottom: Some(pinned_bottom),
+            };
+            let virtual_slice = self
+                .columnar
+                .virtual_slice(&scroll_state, effective_row_height);
+
+            let middle_range = virtual_slice.start_index..virtual_slice.end_index;
+            let detail_cells = self.detail_cell_count(
+                (0..pinned_top)
+                    .chain(middle_range.clone())
+                    .chain(filtered_count.saturating_sub(pinned_bottom)..filtered_count),
+            );
+            let footer_cells = viewport.footer_cells(col_count);
+            let total_cells = col_count
+                + pinned_top * col_count
+                + middle_range.len() * col_count
+                + pinned_bottom * col_count
+                + detail_cells
+                + footer_cells
+                + (pinned_top + middle_range.len() + pinned_bottom) * cell_items_per_row(&visible);
+            let needed = layout_buffer::buf_len(total_cells);
+
+            if self.layout_buf.len() < needed {
+                self.layout_buf.resize(needed, 0.0);
+            }
+
+            let row_pinned_params = react_wasm_table_core::layout::RowPinnedLayoutParams {
+                viewport: &viewport,
+                container: &container,
+                pinned_top,
+                pinned_bottom,
+                scroll_top,
+                total_rows: filtered_count,
+                middle_range: middle_range.clone(),
+                header_row_count,
+            };
+            self.layout_cell_count = self.layout.compute_into_buffer_row_pinned(
+                &visible,
+                &row_pinned_params,
+                &mut self.layout_buf,
+            );
+            layout_buffer::remap_columns(&mut self.layout_buf, 0..self.layout_cell_count, &col_ids);
+            let (pinned_left_width, pinned_right_width) = self.layout.pinned_widths();
+            self.layout_cell_count = apply_row_layout(
+                &mut self.columnar,
+                &mut self.layout_buf,
+                col_count + footer_cells..self.layout_cell_count,
+                header_row_count,
+                effective_row_height,
+                viewport.width,
+            );
+            place_footer(
+                &mut self.layout_buf,
+                &viewport,
+                col_count,
+                self.layout_cell_count,
+            );
+            self.layout_cell_count += self.layout.append_cell_items(
+                &columns,
+                viewport.line_height,
+                &mut self.layout_buf,
+                col_count + footer_cells..self.layout_cell_count,
+                self.layout_cell_count,
+            );
+            write_frame_baselines(
+                &columns,
+                self.header_layout.as_ref(),
+                viewport.line_height,
+                &mut self.layout_buf,
+                col_count..self.layout_cell_count,
+            );
+            style_cells(
+                &mut self.columnar,
+                &self.selection,
+                &self.selection_model,
+                &mut self.layout_buf,
+                0..self.layout_cell_count,
+                header_row_count,
+            );
+
+            let mut meta = vec![
+                self.layout_cell_count as f64,
+                virtual_slice.start_index as f64,
+                virtual_slice.end_index as f64,
+                virtual_slice.total_height + f64::from(viewport.footer_height),
+                filtered_count as f64,
+                self.columnar.generation as f64,
+                total_count as f64,
+                virtual_slice.visible_count as f64,
+                effective_row_height,
+                self.columnar.filtered_total() as f64,
+                f64::from(pinned_left_width),
+                f64::from(pinned_right_width),
+            ];
+            let frame = LastFrame {
+                key: frame_key,
+                view_version: self.columnar.view_version(),
+                rows: middle_range,
+                scroll_top,
+                pinned_top,
+                columns,
+                container,
+                viewport,
+                pinned_bottom,
+                header_row_count,
+                header_cells: col_count,
+            };
+            meta.extend(self.repaint_ranges(
+                frame,
+                col_count * (1 + pinned_top) + footer_cells,
+                col_count,
+            ));
+            meta.push(anchored_scroll_top.unwrap_or(f64::NAN));
+            meta.push(f64::from(self.layout.content_width()));
+            let missing = self.columnar.missing_blocks(
+                (0..pinned_top)
+                    .chain(virtual_slice.start_index..virtual_slice.end_index)
+                    .chain(filtered_count.saturating_sub(pinned_bottom)..filtered_count),
+            );
+            meta.push(missing.len() as f64);
+            meta.extend(missing.iter().map(|&b| b as f64));
+            self.finish_frame(&mut meta);
+            meta
+        } else {
+            // Default path: single visible range
+            let scroll_state = react_wasm_table_core::virtual_scroll::ScrollState {
+                scroll_top,
+                viewport_height: self.columnar.viewport_height() / self.zoom,
+                row_height: effective_row_height,
+                total_rows: filtered_count,
+                overscan: self.columnar.overscan(),
+                pinned_top: None,
+                pinned_bottom: None,
+            };
+            let virtual_slice = self
+                .columnar
+                .virtual_slice(&scroll_state, effective_row_height);
+
+            let row_count = virtual_slice
+                .end_index
+                .saturating_sub(virtual_slice.start_index);
+            let detail_cells =
+                self.detail_cell_count(virtual_slice.start_index..virtual_slice.end_index);
+            let sticky_group_header = self.columnar.group_headers().is_some_and(|g| g.sticky);
+            let footer_cells = viewport.footer_cells(col_count);
+            let total_cells = col_count
+                + row_count * col_count
+                + detail_cells
+                + usize::from(sticky_group_header)
+                + footer_cells
+                + row_count * cell_items_per_row(&visible);
+            let needed = layout_buffer::buf_len(total_cells);
+
+            if self.layout_buf.len() < needed {
+                self.layout_buf.resize(needed, 0.0);
+            }
+
+            self.layout_cell_count = self.layout.compute_into_buffer(
+                &visible,
+                &viewport,
+                &container,
+                virtual_slice.start_index..virtual_slice.end_index,
+                &mut self.layout_buf,
+                header_row_count,
+            );
+            layout_buffer::remap_columns(&mut self.layout_buf, 0..self.layout_cell_count, &col_ids);
+            let (pinned_left_width, pinned_right_width) = self.layout.pinned_widths();
+            self.layout_cell_count = apply_row_layout(
+                &mut self.columnar,
+                &mut self.layout_buf,
+                col_count + footer_cells..self.layout_cell_count,
+                header_row_count,
+                effective_row_height,
+                viewport.width,
+            );
+            place_footer(
+                &mut self.layout_buf,
+                &viewport,
+                col_count,
+                self.layout_cell_count,
+            );
+            self.layout_cell_count += self.layout.append_cell_items(
+                &columns,
+                viewport.line_height,
+                &mut self.layout_buf,
+                col_count + footer_cells..self.layout_cell_count,
+                self.layout_cell_count,
+            );
+            if sticky_group_header {
+                self.layout_cell_count +=
+                    self.write_sticky_group_header(&viewport, scroll_top, header_row_count);
+            }
+            write_frame_baselines(
+                &columns,
+                self.header_layout.as_ref(),
+                viewport.line_height,
+                &mut self.layout_buf,
+                col_count..self.layout_cell_count,
+            );
+            style_cells(
+                &mut self.columnar,
+                &self.selection,
+                &self.selection_model,
+                &mut self.layout_buf,
+                0..self.layout_cell_count,
+                header_row_count,
+            );
+
+            let mut meta = vec![
+                self.layout_cell_count as f64,
+                virtual_slice.start_index as f64,
+                virtual_slice.end_index as f64,
+                virtual_slice.total_height + f64::from(viewport.footer_height),
+                filtered_count as f64,
+                self.columnar.generation as f64,
+                total_count as f64,
+                virtual_slice.visible_count as f64,
+                effective_row_height,
+                self.columnar.filtered_total() as f64,
+                f64::from(pinned_left_width),
+                f64::from(pinned_right_width),
+            ];
+            let frame = LastFrame {
+                key: frame_key,
+                view_version: self.columnar.view_version(),
+                rows: virtual_slice.start_index..virtual_slice.end_index,
+                scroll_top,
+                pinned_top: 0,
+                columns,
+                container,
+                viewport,
+                pinned_bottom: 0,
+                header_row_count,
+                header_cells: col_count,
+            };
+            meta.extend(self.repaint_ranges(frame, col_count + footer_cells, col_count));
+            meta.push(anchored_scroll_top.unwrap_or(f64::NAN));
+            meta.push(f64::from(self.layout.content_width()));
+            let missing = self
+                .columnar
+                .missing_blocks(virtual_slice.start_index..virtual_slice.end_index);
+            meta.push(missing.len() as f64);
+            meta.extend(missing.iter().map(|&b| b as f64));
+            self.finish_frame(&mut meta);
+            meta
+        }
+    }
+
+    /// Push the injected (or current wall-clock) time to the store.
+    fn sync_time_reference(&mut self) {
+        let now = self.filter_now.unwrap_or_else(js_sys::Date::now);
+        self.columnar
+            .set_time_reference(now, self.utc_offset_minutes);
+    }
+
+    /// Detail and group header cells for the rendered view rows `rows`.
+    fn detail_cell_count(&mut self, rows: impl IntoIterator<Item = usize>) -> usize {
+        self.columnar
+            .view_row_layout(self.last_row_height)
+            .map_or(0, |(_, areas)| areas.cell_count(rows))
+    }
+
+    /// Re-index `column_widths` by key when the column keys change.
+    fn sync_column_keys(&mut self, keys: Vec<Option<String>>) {
+        if keys == self.column_keys {
+            return;
+        }
+        let widths = column_sizing::rekey_widths(
+            &self.column_keys,
+            &self.column_widths,
+            &keys,
+            &mut self.pending_widths,
+        );
+        if widths.iter().any(Option::is_some) || !self.column_widths.is_empty() {
+            self.column_widths = widths;
+            self.layout.invalidate_cache();
+        }
+        self.column_keys = keys;
+    }
+
+    /// Replace the column width overrides, recording the change for `undo`.
+    fn set_column_widths(&mut self, widths: Vec<Option<f32>>) {
+        self.merge_column_widths(widths, None);
+    }
+
+    /// `set_column_widths` for a resize of column `resized`, merged into the
+    /// last `undo` step when that resized the same column.
+    fn merge_column_widths(&mut self, widths: Vec<Option<f32>>, resized: Option<usize>) {
+        if widths != self.column_widths {
+            let before = std::mem::replace(&mut self.column_widths, widths);
+            let change = Change::ColumnWidths(before, self.column_widths.clone());
+            let same_column = resized.is_some() && resized == self.resized_column;
+            self.history
+                .record_merged(change, |last, change| match (last, change) {
+                    (Change::ColumnWidths(_, last), Change::ColumnWidths(_, after))
+                        if same_column =>
+                    {
+                        last.clone_from(after);
+                        true
+                    }
+                    _ => false,
+                });
+        }
+        self.resized_column = resized;
+        self.layout.invalidate_cache();
+    }
+
+    /// Data row and key column of the row keyed `id`, if there is one at
+    /// `expected_version` (any version for `None`).
+    fn row_by_id(
+        &mut self,
+        id: &JsValue,
+        expected_version: Option<u32>,
+    ) -> Result<Option<(usize, usize)>, JsError> {
+        let key = row_key(id)?;
+        let Some(queue) = &mut self.upserts else {
+            return Err(JsError::new("no row key column (setRowKeyColumn)"));
+        };
+        Ok(queue
+            .row_of(&self.columnar, &key)
+            .filter(|&row| expected_version.is_none_or(|v| v == self.columnar.row_version(row)))
+            .map(|row| (row, queue.key_column())))
+    }
 
-        let mut children = Vec::with_capacity(child_count);
-        for i in 0..child_count {
-            let base = 11 + i * 2;
-            children.push(react_wasm_table_core::layout::ChildSize {
-                width: input[base],
-                height: input[base + 1],
+    /// Run `change` on the manual row order, recording it for `undo` when it
+    /// reports a change.
+    fn record_row_order(&mut self, change: impl FnOnce(&mut ColumnarStore) -> bool) -> bool {
+        let before = self.columnar.manual_row_order().map(<[u32]>::to_vec);
+        if !change(&mut self.columnar) {
+            return false;
+        }
+        let after = self.columnar.manual_row_order().map(<[u32]>::to_vec);
+        if after != before {
+            self.history.record(Change::RowOrder(before, after));
+        }
+        true
+    }
+
+    /// Revert (`undo`) or re-apply a recorded change.
+    fn apply_change(&mut self, change: &Change, undo: bool) {
+        match change {
+            Change::Cell(edit) if undo => edit.revert(&mut self.columnar),
+            Change::Cell(edit) => edit.apply(&mut self.columnar),
+            Change::ColumnWidths(before, after) => {
+                self.column_widths
+                    .clone_from(if undo { before } else { after });
+                self.layout.invalidate_cache();
+            }
+            Change::HiddenColumns(before, after) => {
+                self.hidden_columns
+                    .clone_from(if undo { before } else { after });
+            }
+            Change::RowOrder(before, after) => {
+                let order = if undo { before } else { after };
+                self.columnar.set_manual_row_order(order.clone());
+            }
+        }
+    }
+
+    /// Width of each column in the last frame, layout px: the laid-out width of
+    /// shown columns, the column width of the others. Empty before the first frame.
+    fn laid_out_widths(&self) -> Vec<f32> {
+        let Some(frame) = &self.last_frame else {
+            return Vec::new();
+        };
+        let mut widths: Vec<f32> = frame.columns.iter().map(|c| c.width).collect();
+        for cell in 0..frame.columns.len().min(self.layout_cell_count) {
+            let base = cell * layout_buffer::LAYOUT_STRIDE;
+            let col = self.layout_buf[base + layout_buffer::FIELD_COL] as usize;
+            if let Some(width) = widths.get_mut(col) {
+                *width = self.layout_buf[base + layout_buffer::FIELD_WIDTH] / self.zoom as f32;
+            }
+        }
+        widths
+    }
+
+    /// Table columns in the on-screen order of the last frame (left-pinned,
+    /// scrollable, right-pinned), or in table order before one, without hidden
+    /// columns.
+    fn screen_columns(&self) -> Vec<usize> {
+        let hidden = |col: usize| self.hidden_columns.get(col).copied().unwrap_or(false);
+        self.last_frame.as_ref().map_or_else(
+            || {
+                (0..self.columnar.data.len())
+                    .filter(|&col| !hidden(col))
+                    .collect()
+            },
+            |frame| navigation::column_order(&frame.columns, hidden),
+        )
+    }
+
+    /// Columns of a frame from the JS `input_columns`: all columns with width
+    /// overrides and content sizes, the visible ones with their indices, and the
+    /// header layout of the visible ones. Grows the header row of `viewport` to
+    /// fit rotated header labels.
+    fn frame_columns(
+        &mut self,
+        input_columns: &[ColumnLayout],
+        viewport: &mut Viewport,
+    ) -> (
+        Vec<ColumnLayout>,
+        Vec<ColumnLayout>,
+        Vec<usize>,
+        Option<HeaderLayout>,
+    ) {
+        let mut columns = input_columns.to_vec();
+        column_sizing::apply_column_widths(&mut columns, &self.column_widths);
+        self.apply_content_sizes(&mut columns);
+        let (visible, col_ids) = self.visible_columns(&columns);
+        let header = self
+            .header_layout
+            .as_ref()
+            .map(|header| header.select(&col_ids));
+        let rotated_height = header.as_ref().map_or_else(
+            || rotated_header_height(&visible, viewport.line_height),
+            |header| rotated_header_height(&header.header_columns(&visible), viewport.line_height),
+        );
+        viewport.header_height = viewport.header_height.max(rotated_height);
+        (columns, visible, col_ids, header)
+    }
+
+    /// Width of header label `label`, measured once per line height (the font of
+    /// rotated labels follows it) and `setTextWidthMeasure` callback.
+    fn header_label_width(&mut self, label: &str, line_height: f32) -> f32 {
+        /// Labels kept before the cache starts over, for labels that keep changing.
+        const MAX_LABELS: usize = 1024;
+        let (cached_line_height, widths) = &mut self.label_widths;
+        if cached_line_height.to_bits() != line_height.to_bits() || widths.len() >= MAX_LABELS {
+            *cached_line_height = line_height;
+            widths.clear();
+        }
+        if let Some(&width) = widths.get(label) {
+            return width;
+        }
+        let width = text_width(self.text_width_measure.as_ref(), label) as f32;
+        widths.insert(label.to_owned(), width);
+        width
+    }
+
+    /// The columns of `columns` not hidden by `setColumnVisibility`, and the table
+    /// column index of each.
+    fn visible_columns(&self, columns: &[ColumnLayout]) -> (Vec<ColumnLayout>, Vec<usize>) {
+        columns
+            .iter()
+            .enumerate()
+            .filter(|&(col_idx, _)| !self.hidden_columns.get(col_idx).copied().unwrap_or(false))
+            .map(|(col_idx, column)| (column.clone(), col_idx))
+            .unzip()
+    }
+
+    /// Give the columns without a width their content size (`setContentSizing`),
+    /// measuring the columns not yet measured for the current view.
+    fn apply_content_sizes(&mut self, columns: &mut [ColumnLayout]) {
+        let Some(sample_size) = self.content_sizing else {
+            return;
+        };
+        let view_version = self.columnar.view_version();
+        if self.content_sizes.0 != view_version {
+            self.content_sizes = (view_version, Vec::new());
+        }
+        let sizes = &mut self.content_sizes.1;
+        if sizes.len() < columns.len() {
+            sizes.resize(columns.len(), None);
+        }
+        let measure = self.text_width_measure.as_ref();
+        for (col_idx, column) in columns.iter_mut().enumerate() {
+            if column.width > 0.0 {
+                continue;
+            }
+            let size = *sizes[col_idx].get_or_insert_with(|| {
+                let (mean, widest) = self.columnar.column_width_stats(
+                    col_idx,
+                    self.columnar.view_sample(sample_size),
+                    |text| text_width(measure, text),
+                );
+                ContentSize {
+                    min: mean as f32,
+                    max: widest as f32,
+                }
             });
+            column.content_size = Some(size);
         }
+    }
 
-        let params = react_wasm_table_core::layout::CompositeLayoutParams {
-            container_w,
-            container_h,
-            flex_direction,
-            gap,
-            align_items,
-            justify_content,
-            padding,
+    /// Transposed-mode frame of `updateViewportColumnar`: lays out the records the
+    /// viewport reaches horizontally and returns the frame metadata.
+    fn update_transposed(
+        &mut self,
+        transposed: TransposedLayout,
+        columns: &[ColumnLayout],
+        visible: &[ColumnLayout],
+        col_ids: &[usize],
+        viewport: &Viewport,
+        header_row_count: usize,
+    ) -> Vec<f64> {
+        let filtered_count = self.columnar.view_indices().len();
+        let slice = compute_virtual_slice(&ScrollState {
+            scroll_top: f64::from(viewport.scroll_left),
+            viewport_height: f64::from((viewport.width - transposed.label_width).max(0.0)),
+            row_height: f64::from(transposed.record_width),
+            total_rows: filtered_count,
+            overscan: self.columnar.overscan(),
+            pinned_top: None,
+            pinned_bottom: None,
+        });
+        let records = slice.start_index..slice.end_index;
+        let col_count = visible.len();
+        let needed = layout_buffer::buf_len(col_count * (1 + records.len()));
+        if self.layout_buf.len() < needed {
+            self.layout_buf.resize(needed, 0.0);
+        }
+
+        self.retain_layout();
+        self.layout_cell_count = self.layout.compute_transposed_into_buffer(
+            visible,
+            viewport,
+            transposed,
+            records.clone(),
+            &mut self.layout_buf,
+            header_row_count,
+        );
+        layout_buffer::remap_columns(&mut self.layout_buf, 0..self.layout_cell_count, col_ids);
+        write_frame_baselines(
+            columns,
+            self.header_layout.as_ref(),
+            viewport.line_height,
+            &mut self.layout_buf,
+            col_count..self.layout_cell_count,
+        );
+        style_cells(
+            &mut self.columnar,
+            &self.selection,
+            &self.selection_model,
+            &mut self.layout_buf,
+            0..self.layout_cell_count,
+            header_row_count,
+        );
+        self.last_frame = None;
+        self.last_row_height = f64::from(viewport.row_height);
+        self.last_header_height = 0.0;
+        self.last_sticky_header = false;
+
+        let mut meta = vec![
+            self.layout_cell_count as f64,
+            records.start as f64,
+            records.end as f64,
+            col_count as f64 * f64::from(viewport.row_height),
+            filtered_count as f64,
+            self.columnar.generation as f64,
+            self.columnar.row_count as f64,
+            slice.visible_count as f64,
+            f64::from(viewport.row_height),
+            self.columnar.filtered_total() as f64,
+            f64::from(transposed.label_width),
+            0.0,
+            1.0,
+            0.0,
+            0.0,
+            self.layout_cell_count as f64,
+            0.0,
+            0.0,
+            f64::NAN,
+            f64::from(transposed.content_width(filtered_count)),
+        ];
+        let missing = self.columnar.missing_blocks(records);
+        meta.push(missing.len() as f64);
+        meta.extend(missing.iter().map(|&b| b as f64));
+        self.finish_frame(&mut meta);
+        meta
+    }
+
+    /// Copy the layout buffer aside before it is replaced, for `getChangedCells`.
+    fn retain_layout(&mut self) {
+        if !self.track_changed_cells {
+            return;
+        }
+        let len = layout_buffer::buf_len(self.layout_cell_count);
+        self.previous_buf.clear();
+        self.previous_buf.extend_from_slice(&self.layout_buf[..len]);
+        self.previous_cell_count = self.layout_cell_count;
+    }
+
+    /// Convert the layout buffer and the px fields of `updateViewportColumnar`
+    /// metadata `meta` from layout px to output px: zoomed (`setZoom`), with cell
+    /// edges and pinned region widths snapped to device pixels (`setPixelSnapping`).
+    fn finish_frame(&mut self, meta: &mut [f64]) {
+        const PX_FIELDS: [usize; 7] = [3, 8, 10, 11, 13, 18, 19];
+        const PINNED_WIDTH_FIELDS: [usize; 2] = [10, 11];
+        output_cells(
+            &mut self.layout_buf,
+            0..self.layout_cell_count,
+            self.zoom,
+            self.pixel_ratio,
+        );
+        if (self.zoom - 1.0).abs() > f64::EPSILON {
+            for field in PX_FIELDS {
+                meta[field] *= self.zoom;
+            }
+        }
+        if let Some(ratio) = self.pixel_ratio.map(f64::from) {
+            for field in PINNED_WIDTH_FIELDS {
+                meta[field] = (meta[field] * ratio).round() / ratio;
+            }
+        }
+        self.report_updated_cells();
+        self.publish_shared_frame();
+    }
+
+    /// `getCellRect` and `getRowRect`: the bounds of the data cells of column `col`
+    /// (all columns for `None`) in the first laid-out view row of the last frame,
+    /// moved and resized to view position `pos` by the row offsets and heights.
+    fn row_rect(&mut self, pos: usize, col: Option<usize>) -> Vec<f64> {
+        let Some(frame) = self
+            .last_frame
+            .as_ref()
+            .filter(|_| self.transposed.is_none())
+        else {
+            return Vec::new();
         };
-        let positions = self.layout.compute_composite_layout(&params, &children);
+        let mut reference: Option<(usize, [f32; 4])> = None;
+        let cells = layout_buffer::data_cells(
+            &self.layout_buf,
+            0..self.layout_cell_count,
+            frame.header_row_count,
+        );
+        for (cell, cell_pos, cell_col) in cells {
+            if col.is_some_and(|col| col != cell_col)
+                || reference.is_some_and(|(first, _)| first != cell_pos)
+            {
+                continue;
+            }
+            let base = cell * layout_buffer::LAYOUT_STRIDE;
+            let [x, y, w, h] = [
+                layout_buffer::FIELD_X,
+                layout_buffer::FIELD_Y,
+                layout_buffer::FIELD_WIDTH,
+                layout_buffer::FIELD_HEIGHT,
+            ]
+            .map(|field| self.layout_buf[base + field]);
+            let (_, bounds) =
+                reference.get_or_insert((cell_pos, [f32::MAX, f32::MAX, f32::MIN, f32::MIN]));
+            *bounds = [
+                bounds[0].min(x),
+                bounds[1].min(y),
+                bounds[2].max(x + w),
+                bounds[3].max(y + h),
+            ];
+        }
+        let Some((first, [left, top, right, bottom])) = reference else {
+            return Vec::new();
+        };
+        let row_height = self.last_row_height;
+        let (Some((first_top, first_height)), Some((row_top, height))) = (
+            self.columnar.view_row_extent(first, row_height),
+            self.columnar.view_row_extent(pos, row_height),
+        ) else {
+            return Vec::new();
+        };
+        vec![
+            f64::from(left),
+            (row_top - first_top).mul_add(self.zoom, f64::from(top)),
+            f64::from(right - left),
+            (height - first_height).mul_add(self.zoom, f64::from(bottom - top)),
+        ]
+    }
 
-        let mut out = Vec::with_capacity(positions.len() * 4);
-        for p in &positions {
-            out.push(p.x);
-            out.push(p.y);
-            out.push(p.width);
-            out.push(p.height);
+    /// Call the `onViewChanged` subscriber if the view version moved since the
+    /// last call.
+    fn emit_view_changed(&mut self) {
+        let version = self.columnar.view_version();
+        if version == self.events.view_version {
+            return;
+        }
+        self.events.view_version = version;
+        if let Some(f) = &self.events.view_changed {
+            let _ = f.call3(
+                &JsValue::NULL,
+                &JsValue::from_f64(version as f64),
+                &JsValue::from_f64(self.columnar.view_indices().len() as f64),
+                &JsValue::from_f64(self.columnar.row_count as f64),
+            );
         }
-        out
     }
 
-    // ── Debug logging ──────────────────────────────────────────────
+    /// Call the `onSelectionChanged` subscriber if the selection model changed
+    /// since the last call.
+    fn emit_selection_changed(&mut self) {
+        let version = self.selection_model.version();
+        if version == self.events.selection_version {
+            return;
+        }
+        self.events.selection_version = version;
+        if let Some(f) = &self.events.selection_changed {
+            let _ = f.call2(
+                &JsValue::NULL,
+                &JsValue::from_f64(version as f64),
+                &JsValue::from_bool(self.selection_model.is_empty()),
+            );
+        }
+    }
 
-    /// Initialize console_log backend and enable Debug-level logging.
-    #[cfg(feature = "debug-log")]
-    #[wasm_bindgen(js_name = enableDebugLog)]
-    pub fn enable_debug_log(&self) {
-        console_log::init_with_level(log::Level::Debug).ok();
+    /// Call the `onDataEdited` subscriber with the `(row, col)` data cells written
+    /// by `source`, if any.
+    fn emit_data_edited(&self, source: &str, cells: impl IntoIterator<Item = (usize, usize)>) {
+        let Some(f) = &self.events.data_edited else {
+            return;
+        };
+        let cells: Vec<u32> = cells
+            .into_iter()
+            .flat_map(|(row, col)| [row as u32, col as u32])
+            .collect();
+        if !cells.is_empty() {
+            let _ = f.call2(
+                &JsValue::NULL,
+                &js_sys::Uint32Array::from(cells.as_slice()),
+                &JsValue::from_str(source),
+            );
+        }
     }
 
-    /// Disable all log output at runtime.
-    #[cfg(feature = "debug-log")]
-    #[wasm_bindgen(js_name = disableDebugLog)]
-    pub fn disable_debug_log(&self) {
-        log::set_max_level(log::LevelFilter::Off);
+    /// Collect the rendered cells among `upserted_cells` into `updated_cells`.
+    fn report_updated_cells(&mut self) {
+        self.updated_cells.clear();
+        let changed = std::mem::take(&mut self.upserted_cells);
+        let Some(frame) = self.last_frame.as_ref().filter(|_| !changed.is_empty()) else {
+            return;
+        };
+        let mut columns: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (row, col) in changed {
+            columns.entry(row).or_default().push(col);
+        }
+        let view = self.columnar.view_indices();
+        let rendered = (0..frame.pinned_top)
+            .chain(frame.rows.clone())
+            .chain(view.len().saturating_sub(frame.pinned_bottom)..view.len());
+        for pos in rendered {
+            let Some(cols) = view.get(pos).and_then(|&row| columns.get(&(row as usize))) else {
+                continue;
+            };
+            for &col in cols {
+                self.updated_cells.extend([pos as u32, col as u32]);
+            }
+        }
+    }
+
+    /// Copy the frame into the unpublished slot of the shared layout buffer and
+    /// publish it (`setSharedLayoutBuffer`).
+    fn publish_shared_frame(&self) {
+        use js_sys::Atomics;
+        use layout_buffer::{SHARED_ACTIVE_SLOT, SHARED_CELL_COUNT, SHARED_GENERATION};
+
+        let Some(shared) = &self.shared_layout else {
+            return;
+        };
+        let active = Atomics::load(&shared.header, SHARED_ACTIVE_SLOT as u32).unwrap_or(0);
+        let next = i32::from(active & 1 == 0);
+        let slot = next as usize;
+        let cells = self
+            .layout_cell_count
+            .min(layout_buffer::shared_slot_cells(shared.byte_len));
+        let len = layout_buffer::buf_len(cells);
+        let offset = layout_buffer::shared_slot_offset(shared.byte_len, slot);
+        shared
+            .cells
+            .subarray(offset as u32, (offset + len) as u32)
+            .copy_from(&self.layout_buf[..len]);
+        let _ = Atomics::store(
+            &shared.header,
+            (SHARED_CELL_COUNT + slot) as u32,
+            i32::try_from(cells).unwrap_or(i32::MAX),
+        );
+        let _ = Atomics::store(&shared.header, SHARED_ACTIVE_SLOT as u32, next);
+        let _ = Atomics::add(&shared.header, SHARED_GENERATION as u32, 1);
+        let _ = Atomics::notify(&shared.header, SHARED_GENERATION as u32);
+    }
+
+    /// Each aggregation over data rows `rows`.
+    fn aggregates(&self, rows: &[u32]) -> Vec<f64> {
+        self.aggregations
+            .iter()
+            .map(|(col_idx, function)| match function {
+                AggregateFn::Builtin(aggregate) => {
+                    self.columnar.aggregate(*col_idx, rows, *aggregate)
+                }
+                AggregateFn::Custom(name) => {
+                    self.custom_aggregates.get(name).map_or(f64::NAN, |f| {
+                        let values = self.columnar.column_values(*col_idx, rows);
+                        let values = js_sys::Float64Array::from(values.as_slice());
+                        f.call1(&JsValue::NULL, &values)
+                            .ok()
+                            .and_then(|v| v.as_f64())
+                            .unwrap_or(f64::NAN)
+                    })
+                }
+            })
+            .collect()
+    }
+
+    /// Write the current group's header, pinned to the top of the data region
+    /// and pushed up by the next group's header, as cell `layout_cell_count`.
+    /// Returns the number of cells written (0 or 1).
+    fn write_sticky_group_header(
+        &mut self,
+        viewport: &Viewport,
+        scroll_top: f64,
+        header_row_count: usize,
+    ) -> usize {
+        let header_height = f64::from(viewport.header_height);
+        let data_top = if viewport.sticky_header {
+            header_height
+        } else {
+            (header_height - scroll_top).max(0.0)
+        };
+        let Some((start, height, next)) = self
+            .columnar
+            .sticky_group_header(self.last_row_height, data_top - header_height + scroll_top)
+        else {
+            return 0;
+        };
+        let y = next.map_or(data_top, |next| {
+            data_top.min(header_height + next - scroll_top - f64::from(height))
+        });
+        let cell = self.layout_cell_count;
+        layout_buffer::write_area_cell(
+            &mut self.layout_buf,
+            cell,
+            header_row_count + start,
+            y as f32,
+            viewport.width,
+            height,
+            layout_buffer::REGION_GROUP_HEADER,
+        );
+        layout_buffer::add_region(
+            &mut self.layout_buf,
+            cell..cell + 1,
+            layout_buffer::REGION_STICKY_HEADER,
+        );
+        1
+    }
+
+    /// `rebuild_view`, first capturing the scroll anchor of the last frame when
+    /// anchoring is on and the view is about to change.
+    fn rebuild_view_anchored(&mut self) {
+        let anchor = self.scroll_anchor_before_rebuild();
+        self.columnar.rebuild_view();
+        if let Some(anchor) = anchor {
+            self.restore_scroll_anchor(anchor);
+        }
+        self.emit_view_changed();
+    }
+
+    /// With scroll anchoring, the anchor of the last frame if the view is about to
+    /// be rebuilt, and that frame's pinned top row count.
+    fn scroll_anchor_before_rebuild(&mut self) -> Option<(ScrollAnchor, usize)> {
+        match &self.last_frame {
+            Some(frame) if self.scroll_anchoring && self.columnar.view_needs_rebuild() => self
+                .columnar
+                .scroll_anchor(
+                    self.anchor_row,
+                    frame.scroll_top,
+                    self.last_row_height,
+                    frame.pinned_top,
+                )
+                .map(|anchor| (anchor, frame.pinned_top)),
+            _ => None,
+        }
+    }
+
+    /// Re-anchor the next frame's scroll offset after a rebuild.
+    fn restore_scroll_anchor(&mut self, (anchor, pinned_top): (ScrollAnchor, usize)) {
+        self.anchored_scroll_top =
+            self.columnar
+                .anchored_scroll_top(&anchor, self.last_row_height, pinned_top);
+    }
+
+    /// `[full_repaint, dy, lead_start, lead_end, trail_start, trail_end]` for the
+    /// `frame` just laid out, whose scrolled rows start at cell `first_cell`.
+    /// Without a full repaint, the previous frame's scrolled band shifted by `dy`
+    /// px is still valid and only the lead/trail cell ranges need painting.
+    fn repaint_ranges(
+        &mut self,
+        frame: LastFrame,
+        first_cell: usize,
+        col_count: usize,
+    ) -> [f64; 6] {
+        let (key, view_version, scroll_top) = (frame.key, frame.view_version, frame.scroll_top);
+        let rows = frame.rows.clone();
+        let scrolling_footer = frame.viewport.footer_height > 0.0 && !frame.viewport.sticky_footer;
+        let cell_items = frame.columns.iter().any(|c| c.content.is_some());
+        match self.last_frame.replace(frame) {
+            Some(prev)
+                if prev.key == key
+                    && prev.view_version == view_version
+                    && !self.columnar.has_variable_row_heights()
+                    && !self.columnar.has_row_merges()
+                    && !scrolling_footer
+                    && !cell_items =>
+            {
+                let cell = |row: usize| (first_cell + (row - rows.start) * col_count) as f64;
+                let (lead, trail) = exposed_rows(prev.rows, rows.clone());
+                [
+                    0.0,
+                    prev.scroll_top - scroll_top,
+                    cell(lead.start),
+                    cell(lead.end),
+                    cell(trail.start),
+                    cell(trail.end),
+                ]
+            }
+            _ => [1.0, 0.0, 0.0, self.layout_cell_count as f64, 0.0, 0.0],
+        }
+    }
+}
+
+/// Fill the baselines of a layout buffer whose cells `..cells.start` are the
+/// header row, styled by the `setHeaderLayout` overrides, and `cells` the rest.
+fn write_frame_baselines(
+    columns: &[ColumnLayout],
+    header: Option<&HeaderLayout>,
+    line_height: f32,
+    buf: &mut [f32],
+    cells: Range<usize>,
+) {
+    match header {
+        Some(header) => write_baselines(
+            &header.header_columns(columns),
+            line_height,
+            buf,
+            0..cells.start,
+        ),
+        None => write_baselines(columns, line_height, buf, 0..cells.start),
+    }
+    write_baselines(columns, line_height, buf, cells);
+}
+
+/// Set the style flags of cells `cells` of a layout buffer: stripes, groups, and
+/// pinned columns from the buffer, plus `selection` ranges, cells selected in
+/// `model`, and global filter matches. Also sets the conditional format style IDs.
+fn style_cells(
+    columnar: &mut ColumnarStore,
+    selection: &[(RangeInclusive<usize>, RangeInclusive<usize>)],
+    model: &Selection,
+    buf: &mut [f32],
+    cells: Range<usize>,
+    header_row_count: usize,
+) {
+    layout_buffer::write_style_flags(
+        buf,
+        cells.clone(),
+        header_row_count,
+        |pos, col| {
+            selection
+                .iter()
+                .any(|(rows, cols)| rows.contains(&pos) && cols.contains(&col))
+                || columnar
+                    .view_indices()
+                    .get(pos)
+                    .is_some_and(|&row| model.contains(row as usize, col))
+        },
+        |pos, col| columnar.view_cell_matches_global_filter(pos, col),
+    );
+    columnar.resolve_conditional_formats();
+    let view = columnar.view_indices();
+    layout_buffer::write_style_ids(buf, cells.clone(), header_row_count, |pos, col| {
+        view.get(pos)
+            .map_or(0, |&row| columnar.conditional_style(col, row as usize))
+    });
+    if columnar.has_validation() {
+        layout_buffer::add_invalid_flags(buf, cells.clone(), header_row_count, |pos, col| {
+            view.get(pos)
+                .is_some_and(|&row| columnar.cell_validation_errors(col, row as usize) != 0)
+        });
+    }
+    layout_buffer::write_update_ages(buf, cells, header_row_count, |pos| {
+        view.get(pos)
+            .and_then(|&row| columnar.row_update_age(row as usize))
+    });
+}
+
+/// Data cells written by the cell edits among `changes`.
+fn edited_cells(changes: &[Change]) -> impl Iterator<Item = (usize, usize)> + '_ {
+    changes.iter().filter_map(|change| match change {
+        Change::Cell(edit) => Some((edit.row, edit.col)),
+        _ => None,
+    })
+}
+
+/// Convert cells `cells` of a layout buffer from layout px to output px: scaled
+/// by `zoom`, then snapped to device pixels at `pixel_ratio`.
+fn output_cells(buf: &mut [f32], cells: Range<usize>, zoom: f64, pixel_ratio: Option<f32>) {
+    if (zoom - 1.0).abs() > f64::EPSILON {
+        layout_buffer::scale_cells(buf, cells.clone(), zoom as f32);
+    }
+    if let Some(ratio) = pixel_ratio {
+        layout_buffer::snap_cells(buf, cells, ratio);
+    }
+}
+
+/// Row key from a JS number or string.
+fn row_key(key: &JsValue) -> Result<RowKey, JsError> {
+    match (key.as_f64(), key.as_string()) {
+        (Some(n), _) => Ok(RowKey::number(n)),
+        (_, Some(text)) => Ok(RowKey::Text(text)),
+        _ => Err(JsError::new("row keys must be numbers or strings")),
+    }
+}
+
+/// Cell value for column `col` from a JS number, boolean, or string; anything
+/// else is null.
+fn cell_value(columnar: &ColumnarStore, col: usize, value: &JsValue) -> CellValue {
+    let numeric = !matches!(columnar.data.get(col), Some(ColumnData::Strings { .. }));
+    match (value.as_f64(), value.as_bool(), value.as_string()) {
+        (Some(n), _, _) => CellValue::Number(n),
+        (_, Some(b), _) => CellValue::Number(f64::from(u8::from(b))),
+        (_, _, Some(text)) => CellValue::Text(text),
+        _ if numeric => CellValue::Number(f64::NAN),
+        _ => CellValue::Text(String::new()),
+    }
+}
+
+/// Average glyph width, px, for measuring text by character count.
+const ESTIMATED_CHAR_WIDTH: f64 = 7.0;
+
+/// Width of `text` by the `setTextWidthMeasure` callback, or by character count.
+fn text_width(measure: Option<&js_sys::Function>, text: &str) -> f64 {
+    measure.map_or_else(
+        || text.chars().count() as f64 * ESTIMATED_CHAR_WIDTH,
+        |f| {
+            f.call1(&JsValue::NULL, &JsValue::from_str(text))
+                .ok()
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0)
+        },
+    )
+}
+
+/// Fixed horizontal padding plus border of a column (percentages and `calc()`
+/// count as 0).
+fn horizontal_insets(column: &ColumnLayout) -> f32 {
+    let length = |value: LengthValue| match value {
+        LengthValue::Length(px) => px,
+        LengthValue::Zero | LengthValue::Percent(_) | LengthValue::Calc(_) => 0.0,
+    };
+    length(column.padding.left)
+        + length(column.padding.right)
+        + length(column.border.left)
+        + length(column.border.right)
+}
+
+/// Move a footer that follows the last row below the data cells of a buffer of
+/// `cell_count` cells, once rows were resized and areas appended.
+fn place_footer(buf: &mut [f32], viewport: &Viewport, col_count: usize, cell_count: usize) {
+    let footer_cells = viewport.footer_cells(col_count);
+    if footer_cells > 0 && !viewport.sticky_footer {
+        let rows_start = col_count + footer_cells;
+        layout_buffer::place_footer_after(
+            buf,
+            col_count..rows_start,
+            rows_start..cell_count.max(rows_start),
+        );
+    }
+}
+
+/// Fit data cells laid out at a uniform `row_height` to the view's variable row
+/// heights, append detail and group header cells, indent tree rows, mark group
+/// rows, apply cell styles, and merge column and row spans. Returns the new cell
+/// count.
+fn apply_row_layout(
+    columnar: &mut ColumnarStore,
+    buf: &mut [f32],
+    cells: Range<usize>,
+    header_row_count: usize,
+    row_height: f64,
+    width: f32,
+) -> usize {
+    let mut cell_count = cells.end;
+    if let Some((heights, areas)) = columnar.view_row_layout(row_height) {
+        layout_buffer::apply_row_heights(
+            buf,
+            cells.clone(),
+            header_row_count,
+            row_height as f32,
+            heights,
+            &areas,
+        );
+        cell_count += layout_buffer::append_area_cells(
+            buf,
+            cells.clone(),
+            cells.end,
+            header_row_count,
+            &areas,
+            width,
+        );
+    }
+    if let Some((col, indent)) = columnar.tree_indent() {
+        layout_buffer::indent_cells(buf, cells.clone(), header_row_count, col, |pos| {
+            columnar.view_depth(pos) as f32 * indent
+        });
+    }
+    if !columnar.group_by().is_empty() {
+        layout_buffer::add_row_region(
+            buf,
+            cells.clone(),
+            header_row_count,
+            layout_buffer::REGION_GROUP_ROW,
+            |pos| columnar.view_group(pos).is_some(),
+        );
+    }
+    if columnar.has_cell_styles() {
+        layout_buffer::apply_cell_styles(buf, cells.clone(), header_row_count, |pos, col| {
+            columnar.view_cell_style(pos, col)
+        });
+    }
+    if columnar.has_col_spans() {
+        layout_buffer::merge_col_spans(buf, cells.clone(), header_row_count, |pos, col| {
+            columnar.view_col_span(pos, col)
+        });
+    }
+    for col in columnar.row_merge_columns() {
+        layout_buffer::merge_row_spans(buf, cells.clone(), header_row_count, col, |pos| {
+            columnar.merged_with_previous(col, pos)
+        });
     }
+    cell_count
 }
 
 impl Default for TableEngine {
@@ -1,29 +1,411 @@
 //! JS bridge type definitions for WASM ↔ JavaScript interop.
 
+use std::ops::RangeInclusive;
+
 /// Sort configuration from JS.
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, serde::Serialize)]
 pub struct JsSortConfig {
     #[serde(rename = "columnIndex")]
     pub column_index: usize,
     pub direction: String,
+    /// Natural ordering for string columns ("file2" < "file10").
+    #[serde(default)]
+    pub natural: bool,
+    /// `"first"` or `"last"`; omitted keeps nulls as the smallest value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nulls: Option<String>,
+    /// Numeric expression to sort by instead of `columnIndex`, e.g. `"$2 * $5"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expression: Option<String>,
+}
+
+/// Collation options from JS. `strength` is `"primary"`, `"secondary"`, or
+/// `"tertiary"` (default).
+#[cfg(feature = "collation")]
+#[derive(serde::Deserialize, Default)]
+pub struct JsCollationOptions {
+    #[serde(default)]
+    pub strength: Option<String>,
+    #[serde(default)]
+    pub numeric: bool,
+}
+
+/// Modifier keys of a `navigate` key press. Ctrl or Meta jumps to the edge.
+#[derive(serde::Deserialize, Default)]
+pub struct JsNavModifiers {
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub meta: bool,
+}
+
+/// `exportXlsx` options from JS. `headers` are by table column; `freezeHeader`
+/// and `outline` default to true.
+#[cfg(feature = "xlsx")]
+#[derive(serde::Deserialize, Default)]
+pub struct JsXlsxOptions {
+    #[serde(default, rename = "sheetName")]
+    pub sheet_name: String,
+    #[serde(default)]
+    pub headers: Vec<String>,
+    #[serde(default, rename = "freezeHeader")]
+    pub freeze_header: Option<bool>,
+    #[serde(default)]
+    pub outline: Option<bool>,
+}
+
+/// `exportPdf` options from JS. `headers` and `subtotals` (aggregate names, see
+/// `Aggregate::parse`) are by table column; sizes are in pt.
+#[cfg(feature = "pdf")]
+#[derive(serde::Deserialize, Default)]
+pub struct JsPdfOptions {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub headers: Vec<String>,
+    #[serde(default)]
+    pub subtotals: Vec<Option<String>>,
+    #[serde(default, rename = "pageWidth")]
+    pub page_width: Option<f64>,
+    #[serde(default, rename = "pageHeight")]
+    pub page_height: Option<f64>,
+    #[serde(default)]
+    pub landscape: bool,
+    #[serde(default, rename = "fontSize")]
+    pub font_size: Option<f64>,
+}
+
+/// `computePrintLayout` options from JS; `repeatHeader` and `footer` default to
+/// true.
+#[derive(serde::Deserialize, Default)]
+pub struct JsPrintOptions {
+    #[serde(default, rename = "repeatHeader")]
+    pub repeat_header: Option<bool>,
+    #[serde(default)]
+    pub footer: Option<bool>,
+}
+
+/// Group-aware sort from JS. `aggregate` is `sum`, `mean`, `min`, `max`, or `count`.
+#[derive(serde::Deserialize)]
+pub struct JsGroupSort {
+    #[serde(rename = "groupColumn")]
+    pub group_column: usize,
+    #[serde(rename = "aggregateColumn")]
+    pub aggregate_column: usize,
+    pub aggregate: String,
+    pub direction: String,
+}
+
+/// Row auto-height settings from JS (all sizes in px).
+#[derive(serde::Deserialize)]
+pub struct JsAutoHeight {
+    pub columns: Vec<JsAutoHeightColumn>,
+    #[serde(rename = "lineHeight")]
+    pub line_height: f64,
+    #[serde(default)]
+    pub padding: f64,
+    #[serde(rename = "minHeight", default)]
+    pub min_height: f64,
+    /// Average glyph width for the built-in estimate (default 7).
+    #[serde(rename = "charWidth", default)]
+    pub char_width: Option<f64>,
+}
+
+/// Per-cell layout override from JS; omitted fields keep the column's value.
+#[derive(serde::Deserialize)]
+pub struct JsCellStyle {
+    /// `[top, right, bottom, left]` px.
+    #[serde(default)]
+    pub padding: Option<[f32; 4]>,
+    #[serde(default)]
+    pub align: Option<String>,
+    #[serde(default)]
+    pub height: Option<f32>,
+}
+
+/// One aggregation from JS: `fn` is `sum`, `mean`/`avg`, `min`, `max`, `count`,
+/// or the name of a `setCustomAggregate` callback.
+#[derive(serde::Deserialize)]
+pub struct JsAggregation {
+    #[serde(rename = "columnIndex")]
+    pub column_index: usize,
+    #[serde(rename = "fn")]
+    pub function: String,
+}
+
+/// A derived window column from JS: `fn` is `runningSum`, `runningMean`,
+/// `runningMin`, `runningMax`, `runningCount`, `rank`, `percentOfTotal`, or
+/// `movingAverage` over `window` rows.
+#[derive(serde::Deserialize)]
+pub struct JsWindowColumn {
+    #[serde(rename = "columnIndex")]
+    pub column_index: usize,
+    #[serde(rename = "fn")]
+    pub function: String,
+    #[serde(default)]
+    pub window: Option<usize>,
+}
+
+/// `setColumnValidation` rules from JS; `pattern` is a regular expression and
+/// `enum` the allowed values.
+#[derive(serde::Deserialize)]
+pub struct JsValidationRules {
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default, rename = "enum")]
+    pub allowed: Option<Vec<String>>,
+}
+
+/// Cell range from JS: rows `startRow..=endRow` × columns `startCol..=endCol`.
+/// Omitted ends default to the start; omitted columns cover every column.
+#[derive(serde::Deserialize)]
+pub struct JsCellRange {
+    #[serde(rename = "startRow")]
+    pub start_row: usize,
+    #[serde(default, rename = "endRow")]
+    pub end_row: Option<usize>,
+    #[serde(default, rename = "startCol")]
+    pub start_col: Option<usize>,
+    #[serde(default, rename = "endCol")]
+    pub end_col: Option<usize>,
+}
+
+impl JsCellRange {
+    /// Inclusive row and column ranges.
+    pub fn bounds(&self) -> (RangeInclusive<usize>, RangeInclusive<usize>) {
+        let cols = match (self.start_col, self.end_col) {
+            (Some(start), end) => start..=end.unwrap_or(start),
+            (None, _) => 0..=usize::MAX,
+        };
+        (
+            self.start_row..=self.end_row.unwrap_or(self.start_row),
+            cols,
+        )
+    }
+}
+
+/// Overlay rectangle from JS over a cell range of view positions (or data rows
+/// with `anchor: "data"`).
+#[derive(serde::Deserialize)]
+pub struct JsOverlay {
+    pub id: u32,
+    #[serde(flatten)]
+    pub range: JsCellRange,
+    #[serde(default)]
+    pub anchor: Option<String>,
 }
 
-/// Column filter from JS.
+/// Group header rows from JS. `column` defaults to the group sort's column.
 #[derive(serde::Deserialize)]
+pub struct JsGroupHeaders {
+    #[serde(default)]
+    pub column: Option<usize>,
+    pub height: f64,
+    #[serde(default)]
+    pub sticky: bool,
+}
+
+#[derive(serde::Deserialize)]
+pub struct JsAutoHeightColumn {
+    #[serde(rename = "columnIndex")]
+    pub column_index: usize,
+    #[serde(rename = "textWidth")]
+    pub text_width: f64,
+}
+
+/// Column filter from JS. `value` may be omitted for null-aware operators.
+#[derive(serde::Deserialize, serde::Serialize)]
 pub struct JsColumnFilter {
     #[serde(rename = "columnIndex")]
     pub column_index: usize,
     pub op: String,
-    pub value: JsFilterValue,
+    #[serde(default)]
+    pub value: Option<JsFilterValue>,
+    /// Match against the column's registered display format.
+    #[serde(default)]
+    pub formatted: bool,
 }
 
-/// Filter value (bool, number, or string).
+/// Conditional formatting rule from JS: data cells of column `target` meeting
+/// `condition` get style `styleId`.
 #[derive(serde::Deserialize)]
+pub struct JsConditionalFormat {
+    pub target: usize,
+    pub condition: JsFormatCondition,
+    #[serde(rename = "styleId")]
+    pub style_id: u32,
+}
+
+/// Condition of a conditional format: `{ scale }` (color scale bucket count),
+/// `{ top, percent?, bottom? }`, or a filter `{ op, value?, columnIndex?,
+/// formatted? }` on the target column (or `columnIndex`).
+#[derive(serde::Deserialize)]
+pub struct JsFormatCondition {
+    #[serde(default)]
+    pub scale: Option<u32>,
+    #[serde(default)]
+    pub top: Option<f64>,
+    #[serde(default)]
+    pub percent: bool,
+    #[serde(default)]
+    pub bottom: bool,
+    #[serde(default)]
+    pub op: Option<String>,
+    #[serde(default)]
+    pub value: Option<JsFilterValue>,
+    #[serde(rename = "columnIndex", default)]
+    pub column_index: Option<usize>,
+    #[serde(default)]
+    pub formatted: bool,
+}
+
+/// Data visualization of a column from JS: `type` is `colorScale` or `dataBar`;
+/// `colors` are the scale's CSS hex color stops, low to high.
+#[derive(serde::Deserialize)]
+pub struct JsDataVisualization {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub domain: Option<(f64, f64)>,
+    #[serde(default)]
+    pub colors: Option<Vec<String>>,
+}
+
+/// Filters, global filter, and sort — the state captured by a filter preset.
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct JsViewState {
+    #[serde(default)]
+    pub filters: Vec<JsColumnFilter>,
+    #[serde(rename = "globalFilter", default)]
+    pub global_filter: Option<String>,
+    #[serde(default)]
+    pub sort: Vec<JsSortConfig>,
+}
+
+/// Sort and filter state for the server in the remote row model.
+#[derive(serde::Serialize)]
+pub struct JsQueryDescriptor {
+    #[serde(flatten)]
+    pub state: JsViewState,
+    /// Canonical encoding: equal keys mean equal queries.
+    pub key: String,
+}
+
+/// A saved filter preset.
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct JsNamedPreset {
+    pub name: String,
+    pub state: JsViewState,
+}
+
+/// Blob produced by `exportState()` and accepted by `importState()`.
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct JsEngineState {
+    pub current: JsViewState,
+    #[serde(default)]
+    pub presets: Vec<JsNamedPreset>,
+}
+
+/// Filter value (bool, number, string, or string array for `containsAny`/`containsAll`).
+#[derive(serde::Deserialize, serde::Serialize)]
 #[serde(untagged)]
 pub enum JsFilterValue {
     Bool(bool),
     Float64(f64),
     String(String),
+    StringList(Vec<String>),
+}
+
+/// Facet entry returned to JS: distinct value (null for nulls) and its row count.
+#[derive(serde::Serialize)]
+pub struct JsFacetCount {
+    pub value: Option<JsFilterValue>,
+    pub count: usize,
+}
+
+/// Per-condition filter statistics returned by `getLastViewStats()`.
+#[derive(serde::Serialize)]
+pub struct JsConditionStats {
+    /// `"column"`, `"global"`, or `"custom"`.
+    pub kind: &'static str,
+    /// Filter operator for column conditions.
+    pub op: Option<&'static str>,
+    #[serde(rename = "columnIndex")]
+    pub column_index: Option<usize>,
+    #[serde(rename = "rowsScanned")]
+    pub rows_scanned: usize,
+    #[serde(rename = "rowsPassed")]
+    pub rows_passed: usize,
+    #[serde(rename = "elapsedUs")]
+    pub elapsed_us: f64,
+    pub cached: bool,
+}
+
+/// Statistics of the most recent view rebuild.
+#[derive(serde::Serialize)]
+pub struct JsViewStats {
+    pub conditions: Vec<JsConditionStats>,
+    #[serde(rename = "rowsFiltered")]
+    pub rows_filtered: usize,
+    #[serde(rename = "sortUs")]
+    pub sort_us: f64,
+    #[serde(rename = "totalUs")]
+    pub total_us: f64,
+}
+
+/// Rows and columns of a slice of the view for an ARIA grid mirror
+/// (`getAriaSnapshot`).
+#[derive(serde::Serialize)]
+pub struct JsAriaSnapshot {
+    /// Rows passing the filters, across pages (`aria-rowcount` without header
+    /// rows).
+    #[serde(rename = "rowCount")]
+    pub row_count: usize,
+    /// Shown columns (`aria-colcount`).
+    #[serde(rename = "colCount")]
+    pub col_count: usize,
+    pub columns: Vec<JsAriaColumn>,
+    pub rows: Vec<JsAriaRow>,
+}
+
+/// A column of `JsAriaSnapshot`.
+#[derive(serde::Serialize)]
+pub struct JsAriaColumn {
+    /// 1-based on-screen position (`aria-colindex`).
+    #[serde(rename = "colIndex")]
+    pub col_index: usize,
+    /// Table column index.
+    pub column: usize,
+    pub name: String,
+    /// `aria-sort`: `"ascending"`, `"descending"`, or `"none"`.
+    pub sort: &'static str,
+}
+
+/// A row of `JsAriaSnapshot`, with the display text of each snapshot column.
+#[derive(serde::Serialize)]
+pub struct JsAriaRow {
+    /// 1-based position among the rows passing the filters, across pages
+    /// (`aria-rowindex` without header rows).
+    #[serde(rename = "rowIndex")]
+    pub row_index: usize,
+    /// Data row index.
+    pub row: usize,
+    pub selected: bool,
+    pub cells: Vec<String>,
+}
+
+/// Progress of `rebuildViewStep`.
+#[derive(serde::Serialize)]
+pub struct JsRebuildProgress {
+    pub done: bool,
+    pub progress: f64,
+    pub restarted: bool,
 }
 
 /// Viewport configuration from JS.
@@ -36,11 +418,19 @@ pub struct JsViewport {
     #[serde(rename = "headerHeight")]
     pub header_height: f32,
     #[serde(rename = "scrollTop")]
-    pub scroll_top: f32,
+    pub scroll_top: f64,
+    #[serde(rename = "scrollLeft", default)]
+    pub scroll_left: f32,
     #[serde(rename = "lineHeight", default = "default_line_height")]
     pub line_height: f32,
     #[serde(rename = "headerRowCount", default = "default_header_row_count")]
     pub header_row_count: usize,
+    #[serde(rename = "stickyHeader", default)]
+    pub sticky_header: bool,
+    #[serde(rename = "footerHeight", default)]
+    pub footer_height: f32,
+    #[serde(rename = "stickyFooter", default)]
+    pub sticky_footer: bool,
 }
 
 pub fn default_line_height() -> f32 {
@@ -66,6 +456,9 @@ pub struct JsColumnLayout {
     pub max_width: Option<f32>,
     #[serde(default)]
     pub align: Option<String>,
+    /// "top" | "middle" | "bottom": vertical text alignment.
+    #[serde(rename = "verticalAlign")]
+    pub vertical_align: Option<String>,
     // New flex child properties
     #[serde(rename = "flexBasis")]
     pub flex_basis: Option<JsDimension>,
@@ -96,8 +489,50 @@ pub struct JsColumnLayout {
     pub grid_row: Option<JsGridLine>,
     #[serde(rename = "gridColumn")]
     pub grid_column: Option<JsGridLine>,
+    /// "header" (a named area) or "row-start / column-start / row-end / column-end";
+    /// sets `gridRow` and `gridColumn` that are not given.
+    #[serde(rename = "gridArea")]
+    pub grid_area: Option<String>,
     #[serde(rename = "justifySelf")]
     pub justify_self: Option<String>,
+    /// "left" | "right": frozen column.
+    #[serde(default)]
+    pub pinned: Option<String>,
+    /// Items laid out inside each data cell, styled like columns.
+    #[serde(default)]
+    pub items: Option<Vec<Self>>,
+    /// Flex/grid container of the items (the cell's content box).
+    #[serde(rename = "itemContainer")]
+    pub item_container: Option<JsContainerLayout>,
+    /// "horizontal" | "vertical" | degrees (number or "45deg"): header label rotation.
+    #[serde(rename = "headerOrientation")]
+    pub header_orientation: Option<JsDimension>,
+    /// Header label text, measured to size rotated headers.
+    #[serde(rename = "headerLabel")]
+    pub header_label: Option<String>,
+    /// Stable column key (e.g. the column's id) that width overrides follow when
+    /// columns are reordered, added, or removed (`setColumnWidths`).
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+/// Header row overrides from JS (`setHeaderLayout`): a container and per-column
+/// styles (`null` = the column's own style), by table column index.
+#[derive(serde::Deserialize)]
+pub struct JsHeaderLayout {
+    #[serde(default)]
+    pub container: Option<JsContainerLayout>,
+    #[serde(default)]
+    pub columns: Vec<Option<JsColumnLayout>>,
+}
+
+/// Transposed mode from JS (`setTransposed`).
+#[derive(serde::Deserialize)]
+pub struct JsTransposedLayout {
+    #[serde(rename = "recordWidth")]
+    pub record_width: f32,
+    #[serde(rename = "labelWidth", default)]
+    pub label_width: f32,
 }
 
 /// Container layout from JS.
@@ -138,6 +573,9 @@ pub struct JsContainerLayout {
     pub grid_template_rows: Option<JsGridTrackList>,
     #[serde(rename = "gridTemplateColumns")]
     pub grid_template_columns: Option<JsGridTrackList>,
+    /// `"a a" "b c"` or one string per row (`["a a", "b c"]`).
+    #[serde(rename = "gridTemplateAreas")]
+    pub grid_template_areas: Option<JsGridTemplateAreas>,
     #[serde(rename = "gridAutoRows")]
     pub grid_auto_rows: Option<JsGridTrackList>,
     #[serde(rename = "gridAutoColumns")]
@@ -148,7 +586,7 @@ pub struct JsContainerLayout {
     pub justify_items: Option<String>,
 }
 
-/// A CSS dimension: number (px) or string ("50%", "auto").
+/// A CSS dimension: number (px) or string ("50%", "auto", "calc(100% - 40px)").
 #[derive(serde::Deserialize, Clone)]
 #[serde(untagged)]
 pub enum JsDimension {
@@ -185,7 +623,16 @@ pub enum JsGridTrackList {
     Array(Vec<JsGridTrackSize>),
 }
 
-/// A CSS grid placement: number (line) or string ("span 2", "auto").
+/// CSS grid template areas: a string of quoted rows, or an array of rows.
+#[derive(serde::Deserialize, Clone)]
+#[serde(untagged)]
+pub enum JsGridTemplateAreas {
+    Single(String),
+    Rows(Vec<String>),
+}
+
+/// A CSS grid placement: number (line) or string ("span 2", "auto", "header",
+/// "main 2", "span main").
 #[derive(serde::Deserialize, Clone)]
 #[serde(untagged)]
 pub enum JsGridPlacement {
@@ -1,30 +1,45 @@
 import { describe, expect, it } from "bun:test";
 import {
+  CELL_FLAG_ODD_ROW,
+  CELL_FLAG_PINNED,
+  CELL_FLAG_SELECTED,
+  HEADER_STRIDE,
   LAYOUT_STRIDE,
   hitTest,
   readCellAlign,
   readCellAlignCode,
+  readCellBaseline,
   readCellBorderBottom,
   readCellBorderLeft,
   readCellBorderRight,
   readCellBorderTop,
   readCellCol,
+  readCellFlags,
   readCellHeight,
+  readCellItem,
   readCellPaddingBottom,
   readCellPaddingLeft,
   readCellPaddingRight,
   readCellPaddingTop,
+  readCellRotation,
+  readCellStyle,
+  readCellUpdateAge,
   readCellRow,
   readCellWidth,
   readCellX,
   readCellY,
+  readHeaderFiltered,
+  readHeaderResizeX,
+  readHeaderSort,
+  readHeaderSortPriority,
 } from "../adapter/layout-reader";
 
 /**
- * Buffer layout per cell (stride 16):
+ * Buffer layout per cell (stride 22):
  *  [row, col, x, y, width, height, align,
  *   paddingTop, paddingRight, paddingBottom, paddingLeft,
- *   borderTop, borderRight, borderBottom, borderLeft, reserved]
+ *   borderTop, borderRight, borderBottom, borderLeft, region, baseline, flags, rotation, style,
+ *   updateAge, item]
  */
 function makeCell(
   row: number,
@@ -42,6 +57,12 @@ function makeCell(
   borderRight: number,
   borderBottom: number,
   borderLeft: number,
+  baseline = 0,
+  flags = 0,
+  rotation = 0,
+  style = 0,
+  updateAge = -1,
+  item = -1,
 ): number[] {
   return [
     row,
@@ -59,7 +80,13 @@ function makeCell(
     borderRight,
     borderBottom,
     borderLeft,
-    0, // reserved
+    0, // region
+    baseline,
+    flags,
+    rotation,
+    style,
+    updateAge,
+    item,
   ];
 }
 
@@ -68,8 +95,117 @@ function makeBuf(...cells: number[][]): Float32Array {
 }
 
 describe("LAYOUT_STRIDE", () => {
-  it("equals 16", () => {
-    expect(LAYOUT_STRIDE).toBe(16);
+  it("equals 22", () => {
+    expect(LAYOUT_STRIDE).toBe(22);
+  });
+});
+
+describe("readCellBaseline", () => {
+  it("reads the baseline field of each cell", () => {
+    const buf = makeBuf(
+      makeCell(0, 0, 0, 0, 100, 40, 0, 0, 0, 0, 0, 0, 0, 0, 0, 20),
+      makeCell(1, 0, 0, 40, 100, 36, 0, 0, 0, 0, 0, 0, 0, 0, 0, 58),
+    );
+    expect(readCellBaseline(buf, 0)).toBe(20);
+    expect(readCellBaseline(buf, 1)).toBe(58);
+  });
+});
+
+describe("readCellFlags", () => {
+  it("reads the style flag bit set of each cell", () => {
+    const buf = makeBuf(
+      makeCell(1, 0, 0, 0, 100, 36, 0, 0, 0, 0, 0, 0, 0, 0, 0, 18, 0),
+      makeCell(
+        2,
+        0,
+        0,
+        36,
+        100,
+        36,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        54,
+        CELL_FLAG_ODD_ROW | CELL_FLAG_SELECTED,
+      ),
+    );
+    expect(readCellFlags(buf, 0)).toBe(0);
+    expect(readCellFlags(buf, 1) & CELL_FLAG_ODD_ROW).toBe(CELL_FLAG_ODD_ROW);
+    expect(readCellFlags(buf, 1) & CELL_FLAG_SELECTED).toBe(CELL_FLAG_SELECTED);
+    expect(readCellFlags(buf, 1) & CELL_FLAG_PINNED).toBe(0);
+  });
+});
+
+describe("readCellRotation", () => {
+  it("reads the header label rotation of each cell", () => {
+    const buf = makeBuf(
+      makeCell(0, 0, 0, 0, 40, 120, 0, 0, 0, 0, 0, 0, 0, 0, 0, 60, 0, 90),
+      makeCell(1, 0, 0, 120, 40, 36, 0, 0, 0, 0, 0, 0, 0, 0, 0, 138),
+    );
+    expect(readCellRotation(buf, 0)).toBe(90);
+    expect(readCellRotation(buf, 1)).toBe(0);
+  });
+});
+
+describe("readCellStyle", () => {
+  it("reads the conditional format style ID of each cell", () => {
+    const buf = makeBuf(
+      makeCell(1, 0, 0, 0, 100, 36, 0, 0, 0, 0, 0, 0, 0, 0, 0, 18, 0, 0, 7),
+      makeCell(1, 1, 100, 0, 100, 36, 0, 0, 0, 0, 0, 0, 0, 0, 0, 18),
+    );
+    expect(readCellStyle(buf, 0)).toBe(7);
+    expect(readCellStyle(buf, 1)).toBe(0);
+  });
+});
+
+describe("readCellUpdateAge", () => {
+  it("reads frames since the row's last update, -1 for none", () => {
+    const buf = makeBuf(
+      makeCell(1, 0, 0, 0, 100, 36, 0, 0, 0, 0, 0, 0, 0, 0, 0, 18, 0, 0, 0, 2),
+      makeCell(2, 0, 0, 36, 100, 36, 0, 0, 0, 0, 0, 0, 0, 0, 0, 54),
+    );
+    expect(readCellUpdateAge(buf, 0)).toBe(2);
+    expect(readCellUpdateAge(buf, 1)).toBe(-1);
+  });
+});
+
+describe("readCellItem", () => {
+  it("reads the item index of cell items, -1 for other cells", () => {
+    const buf = makeBuf(
+      makeCell(1, 0, 0, 0, 100, 36, 0, 0, 0, 0, 0, 0, 0, 0, 0, 18),
+      makeCell(1, 0, 8, 0, 16, 36, 0, 0, 0, 0, 0, 0, 0, 0, 0, 18, 0, 0, 0, -1, 1),
+    );
+    expect(readCellItem(buf, 0)).toBe(-1);
+    expect(readCellItem(buf, 1)).toBe(1);
+  });
+});
+
+describe("header buffer readers", () => {
+  // [sort, sortPriority, filtered, resizeX] per header cell
+  const buf = new Float32Array([2, 1, 1, 80, 0, 0, 0, 130]);
+
+  it("HEADER_STRIDE equals 4", () => {
+    expect(HEADER_STRIDE).toBe(4);
+  });
+
+  it("reads sort direction and priority", () => {
+    expect(readHeaderSort(buf, 0)).toBe(2);
+    expect(readHeaderSortPriority(buf, 0)).toBe(1);
+    expect(readHeaderSort(buf, 1)).toBe(0);
+    expect(readHeaderSortPriority(buf, 1)).toBe(0);
+  });
+
+  it("reads the filter flag and resize handle x", () => {
+    expect(readHeaderFiltered(buf, 0)).toBe(true);
+    expect(readHeaderFiltered(buf, 1)).toBe(false);
+    expect(readHeaderResizeX(buf, 0)).toBe(80);
+    expect(readHeaderResizeX(buf, 1)).toBe(130);
   });
 });
 
@@ -0,0 +1,42 @@
+import { describe, expect, it } from "bun:test";
+import { encodeCommands } from "../command-encoder";
+
+const bytes = (buffer: ArrayBuffer) => Array.from(new Uint8Array(buffer));
+
+describe("encodeCommands", () => {
+  it("writes the header and command count", () => {
+    expect(bytes(encodeCommands([]))).toEqual([0x52, 0x57, 0x54, 0x43, 1, 0]);
+  });
+
+  it("encodes sorts, filters, and the global filter", () => {
+    const encoded = bytes(
+      encodeCommands([
+        { type: "setSort", sort: [{ columnIndex: 200, direction: "desc", nulls: "last" }] },
+        { type: "setFilters", filters: [{ columnIndex: 1, op: "isBlank", value: null }] },
+        { type: "setGlobalFilter", query: "é" },
+      ]),
+    );
+    // Count, column 200 as a varint, desc | nulls last, no expression
+    const sort = [0, 1, 0xc8, 0x01, 1 | (2 << 2), 0];
+    // Count, column, op code, not formatted, empty string value
+    const filters = [1, 1, 1, 11, 0, 1, 0];
+    // Some, 2 UTF-8 bytes
+    const global = [2, 1, 2, 0xc3, 0xa9];
+    expect(encoded.slice(5)).toEqual([3, ...sort, ...filters, ...global]);
+  });
+
+  it("encodes scroll and resize as little-endian f64", () => {
+    const buffer = encodeCommands([
+      { type: "scroll", top: 1200 },
+      { type: "resizeColumn", columnIndex: 3, width: 140, neighbor: true },
+    ]);
+    const view = new DataView(buffer);
+    expect(view.getUint8(6)).toBe(3);
+    expect(view.getFloat64(7, true)).toBe(1200);
+    expect(view.getFloat64(15, true)).toBe(0);
+    expect(view.getUint8(23)).toBe(5);
+    expect(view.getUint8(24)).toBe(3);
+    expect(view.getFloat64(25, true)).toBe(140);
+    expect(view.getUint8(33)).toBe(1);
+  });
+});
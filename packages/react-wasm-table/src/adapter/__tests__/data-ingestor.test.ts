@@ -4,6 +4,8 @@ import {
   buildFloat64Column,
   buildBoolColumn,
   buildStringColumn,
+  buildStringListColumn,
+  buildFloat64ListColumn,
   ingestData,
   appendData,
 } from "../data-ingestor";
@@ -113,6 +115,35 @@ describe("buildStringColumn", () => {
   });
 });
 
+describe("buildStringListColumn", () => {
+  it("interns tags and records per-row offsets", () => {
+    const data = [{ t: ["a", "b"] }, { t: null }, { t: ["b"] }] as Record<string, unknown>[];
+    const [unique, offsets, ids] = buildStringListColumn(data, "t");
+    expect(unique).toEqual(["a", "b"]);
+    expect(Array.from(offsets)).toEqual([0, 2, 2, 3]);
+    expect(Array.from(ids)).toEqual([0, 1, 1]);
+  });
+
+  it("is classified from array values", () => {
+    const data = [{ t: null }, { t: ["x"] }] as Record<string, unknown>[];
+    expect(classifyColumns(data, ["t"])).toEqual(["stringList"]);
+  });
+});
+
+describe("buildFloat64ListColumn", () => {
+  it("records per-row series with NaN for non-numbers", () => {
+    const data = [{ s: [1, 2] }, { s: null }, { s: [3, null] }] as Record<string, unknown>[];
+    const [offsets, values] = buildFloat64ListColumn(data, "s");
+    expect(Array.from(offsets)).toEqual([0, 2, 2, 4]);
+    expect(Array.from(values)).toEqual([1, 2, 3, Number.NaN]);
+  });
+
+  it("classifies numeric arrays as float64List", () => {
+    const data = [{ s: [null, 4] }, { s: [] }] as Record<string, unknown>[];
+    expect(classifyColumns(data, ["s"])).toEqual(["float64List"]);
+  });
+});
+
 describe("ingestData", () => {
   it("calls engine methods in correct order", () => {
     const engine = {
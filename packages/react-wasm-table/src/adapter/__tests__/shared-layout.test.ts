@@ -0,0 +1,44 @@
+import { describe, expect, it } from "bun:test";
+import { LAYOUT_STRIDE } from "../layout-reader";
+import {
+  SHARED_HEADER_WORDS,
+  isSharedFrameIntact,
+  readSharedLayoutFrame,
+  sharedLayoutBufferLength,
+} from "../shared-layout";
+
+/** Publish `cells` cells (each filled with its index) into `slot`, as the engine does. */
+function publish(buffer: SharedArrayBuffer, slot: number, cells: number) {
+  const header = new Int32Array(buffer, 0, SHARED_HEADER_WORDS);
+  const data = new Float32Array(buffer);
+  const start = SHARED_HEADER_WORDS + slot * 2 * LAYOUT_STRIDE;
+  for (let i = 0; i < cells * LAYOUT_STRIDE; i++) data[start + i] = Math.floor(i / LAYOUT_STRIDE);
+  Atomics.store(header, 2 + slot, cells);
+  Atomics.store(header, 1, slot);
+  Atomics.add(header, 0, 1);
+}
+
+describe("shared layout buffer", () => {
+  it("reads the published slot", () => {
+    const buffer = new SharedArrayBuffer(sharedLayoutBufferLength(2));
+    expect(readSharedLayoutFrame(buffer)).toBeNull();
+
+    publish(buffer, 1, 2);
+    const frame = readSharedLayoutFrame(buffer)!;
+    expect(frame.generation).toBe(1);
+    expect(frame.cellCount).toBe(2);
+    expect(frame.buf.length).toBe(2 * LAYOUT_STRIDE);
+    expect(frame.buf[LAYOUT_STRIDE]).toBe(1);
+    expect(frame.buf.byteOffset).toBe((SHARED_HEADER_WORDS + 2 * LAYOUT_STRIDE) * 4);
+  });
+
+  it("reports frames superseded while reading", () => {
+    const buffer = new SharedArrayBuffer(sharedLayoutBufferLength(2));
+    publish(buffer, 1, 1);
+    const frame = readSharedLayoutFrame(buffer)!;
+    expect(isSharedFrameIntact(buffer, frame.generation)).toBe(true);
+    publish(buffer, 0, 2);
+    expect(isSharedFrameIntact(buffer, frame.generation)).toBe(false);
+    expect(readSharedLayoutFrame(buffer)!.cellCount).toBe(2);
+  });
+});
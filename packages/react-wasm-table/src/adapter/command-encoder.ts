@@ -0,0 +1,173 @@
+/**
+ * Binary encoder for `engine.applyCommands`: several operations in one call,
+ * e.g. posted to a Web Worker with the buffer in the transfer list.
+ *
+ * Format (see `crates/core/src/commands.rs`): the magic `RWTC`, a version byte,
+ * a command count, then each command as a tag byte followed by its fields.
+ * Counts and indices are LEB128 varints, floats little-endian f64, strings a
+ * byte length followed by UTF-8.
+ */
+
+/** Sort config of `setColumnarSort`. */
+export interface WasmSortConfig {
+  columnIndex: number;
+  direction: "asc" | "desc";
+  natural?: boolean;
+  nulls?: "first" | "last";
+  expression?: string;
+}
+
+/** Column filter of `setColumnarFilters`. */
+export interface WasmColumnFilter {
+  columnIndex: number;
+  op: string;
+  value?: boolean | number | string | string[] | null;
+  formatted?: boolean;
+}
+
+/** One operation of an `applyCommands` batch. Scroll offsets and sizes are in px. */
+export type TableCommand =
+  | { type: "setSort"; sort: WasmSortConfig[] }
+  | { type: "setFilters"; filters: WasmColumnFilter[] }
+  | { type: "setGlobalFilter"; query: string | null }
+  | { type: "scroll"; top: number; left?: number }
+  | { type: "resize"; width: number; height: number }
+  | { type: "resizeColumn"; columnIndex: number; width: number; neighbor?: boolean };
+
+export const COMMAND_FORMAT_VERSION = 1;
+
+/** Filter operators by their code in the format. Append only. */
+const FILTER_OPS = [
+  "eq",
+  "neq",
+  "gt",
+  "gte",
+  "lt",
+  "lte",
+  "contains",
+  "startsWith",
+  "endsWith",
+  "isNull",
+  "isNotNull",
+  "isBlank",
+  "containsAny",
+  "containsAll",
+  "within",
+  "expression",
+];
+
+class Writer {
+  private bytes: number[] = [];
+  private readonly encoder = new TextEncoder();
+  private readonly f64 = new DataView(new ArrayBuffer(8));
+
+  byte(b: number): void {
+    this.bytes.push(b & 0xff);
+  }
+
+  len(n: number): void {
+    while (n >= 0x80) {
+      this.bytes.push((n & 0x7f) | 0x80);
+      n = Math.floor(n / 0x80);
+    }
+    this.bytes.push(n);
+  }
+
+  float(v: number): void {
+    this.f64.setFloat64(0, v, true);
+    for (let i = 0; i < 8; i++) this.bytes.push(this.f64.getUint8(i));
+  }
+
+  str(s: string): void {
+    const utf8 = this.encoder.encode(s);
+    this.len(utf8.length);
+    for (const b of utf8) this.bytes.push(b);
+  }
+
+  optStr(s: string | null | undefined): void {
+    this.byte(s == null ? 0 : 1);
+    if (s != null) this.str(s);
+  }
+
+  finish(): ArrayBuffer {
+    return Uint8Array.from(this.bytes).buffer;
+  }
+}
+
+function writeFilterValue(w: Writer, filter: WasmColumnFilter): void {
+  const v = filter.value;
+  if (filter.op === "within" && typeof v === "string") {
+    // Relative date name; unknown names fail the batch
+    w.byte(4);
+    w.str(v);
+  } else if (typeof v === "number") {
+    w.byte(0);
+    w.float(v);
+  } else if (typeof v === "boolean") {
+    w.byte(2);
+    w.byte(v ? 1 : 0);
+  } else if (Array.isArray(v)) {
+    w.byte(3);
+    w.len(v.length);
+    for (const s of v) w.str(s);
+  } else {
+    w.byte(1);
+    w.str(v ?? "");
+  }
+}
+
+/**
+ * Encode `commands` for `engine.applyCommands`, applied in order.
+ * Unknown filter operators fall back to `eq`, as in `setColumnarFilters`.
+ */
+export function encodeCommands(commands: TableCommand[]): ArrayBuffer {
+  const w = new Writer();
+  for (const b of [0x52, 0x57, 0x54, 0x43]) w.byte(b); // "RWTC"
+  w.byte(COMMAND_FORMAT_VERSION);
+  w.len(commands.length);
+  for (const command of commands) {
+    switch (command.type) {
+      case "setSort":
+        w.byte(0);
+        w.len(command.sort.length);
+        for (const sort of command.sort) {
+          w.len(sort.columnIndex);
+          const nulls = sort.nulls === "first" ? 1 : sort.nulls === "last" ? 2 : 0;
+          w.byte((sort.direction === "desc" ? 1 : 0) | (sort.natural ? 2 : 0) | (nulls << 2));
+          w.optStr(sort.expression);
+        }
+        break;
+      case "setFilters":
+        w.byte(1);
+        w.len(command.filters.length);
+        for (const filter of command.filters) {
+          w.len(filter.columnIndex);
+          w.byte(Math.max(FILTER_OPS.indexOf(filter.op), 0));
+          w.byte(filter.formatted ? 1 : 0);
+          writeFilterValue(w, filter);
+        }
+        break;
+      case "setGlobalFilter":
+        w.byte(2);
+        w.optStr(command.query);
+        break;
+      case "scroll":
+        w.byte(3);
+        w.float(command.top);
+        w.float(command.left ?? 0);
+        break;
+      case "resize":
+        w.byte(4);
+        w.float(command.width);
+        w.float(command.height);
+        break;
+      case "resizeColumn":
+        w.byte(5);
+        w.len(command.columnIndex);
+        w.float(command.width);
+        w.byte(command.neighbor ? 1 : 0);
+        break;
+    }
+  }
+  return w.finish();
+}
@@ -1,10 +1,11 @@
 import type { WasmTableEngine } from "../types";
 
 /** Detected column type for ingestion. */
-export type ColumnDataType = "float64" | "bool" | "string";
+export type ColumnDataType = "float64" | "bool" | "string" | "stringList" | "float64List";
 
 /**
  * Detect the data type for each column by sampling the first non-null value.
+ * Arrays whose first non-null item is a number are numeric series.
  */
 export function classifyColumns(
   data: Record<string, unknown>[],
@@ -16,6 +17,10 @@ export function classifyColumns(
       if (v === null || v === undefined) continue;
       if (typeof v === "boolean") return "bool";
       if (typeof v === "number") return "float64";
+      if (Array.isArray(v)) {
+        const item = v.find((x) => x != null);
+        return typeof item === "number" ? "float64List" : "stringList";
+      }
       return "string";
     }
     return "string"; // default for all-null columns
@@ -79,6 +84,64 @@ export function buildStringColumn(
   return [unique, ids];
 }
 
+/**
+ * Build interned multi-value string column data (e.g. tag arrays).
+ * Returns [uniqueStrings, offsets, idArray]: row i holds ids[offsets[i]..offsets[i + 1]].
+ * null/undefined and non-array values become an empty list (null).
+ */
+export function buildStringListColumn(
+  data: Record<string, unknown>[],
+  colId: string,
+): [string[], Uint32Array, Uint32Array] {
+  const lookup = new Map<string, number>();
+  const unique: string[] = [];
+  const offsets = new Uint32Array(data.length + 1);
+  const ids: number[] = [];
+
+  for (let i = 0; i < data.length; i++) {
+    const v = data[i]![colId];
+    if (Array.isArray(v)) {
+      for (const item of v) {
+        if (item == null) continue;
+        const s = String(item);
+        let id = lookup.get(s);
+        if (id === undefined) {
+          id = unique.length;
+          unique.push(s);
+          lookup.set(s, id);
+        }
+        ids.push(id);
+      }
+    }
+    offsets[i + 1] = ids.length;
+  }
+
+  return [unique, offsets, Uint32Array.from(ids)];
+}
+
+/**
+ * Build numeric series column data (e.g. for sparklines).
+ * Returns [offsets, values]: row i holds values[offsets[i]..offsets[i + 1]].
+ * Non-numeric items become NaN; null/undefined and non-array values become an empty series.
+ */
+export function buildFloat64ListColumn(
+  data: Record<string, unknown>[],
+  colId: string,
+): [Uint32Array, Float64Array] {
+  const offsets = new Uint32Array(data.length + 1);
+  const values: number[] = [];
+
+  for (let i = 0; i < data.length; i++) {
+    const v = data[i]![colId];
+    if (Array.isArray(v)) {
+      for (const item of v) values.push(typeof item === "number" ? item : Number.NaN);
+    }
+    offsets[i + 1] = values.length;
+  }
+
+  return [offsets, Float64Array.from(values)];
+}
+
 /**
  * Full ingestion orchestrator: classify → build typed arrays → push to WASM engine.
  * Bypasses serde for numeric and boolean columns.
@@ -113,6 +176,16 @@ export function ingestData(
         engine.ingestStringColumn(i, unique, ids);
         break;
       }
+      case "stringList": {
+        const [unique, offsets, ids] = buildStringListColumn(data, colId);
+        engine.ingestStringListColumn!(i, unique, offsets, ids);
+        break;
+      }
+      case "float64List": {
+        const [offsets, values] = buildFloat64ListColumn(data, colId);
+        engine.ingestFloat64ListColumn!(i, offsets, values);
+        break;
+      }
     }
   }
 
@@ -158,6 +231,16 @@ export function appendData(
         engine.appendStringColumn!(i, startIndex, unique, ids);
         break;
       }
+      case "stringList": {
+        const [unique, offsets, ids] = buildStringListColumn(newRows, colId);
+        engine.appendStringListColumn!(i, startIndex, unique, offsets, ids);
+        break;
+      }
+      case "float64List": {
+        const [offsets, values] = buildFloat64ListColumn(newRows, colId);
+        engine.appendFloat64ListColumn!(i, startIndex, offsets, values);
+        break;
+      }
     }
   }
 
@@ -3,6 +3,9 @@ export { EventManager } from "./event-manager";
 export type { GridEventHandlers } from "./event-manager";
 export { EditorManager } from "./editor-manager";
 export { SelectionManager, buildTSV } from "./selection-manager";
+export { encodeCommands, COMMAND_FORMAT_VERSION } from "./command-encoder";
+export type { TableCommand, WasmSortConfig, WasmColumnFilter } from "./command-encoder";
+export * from "./shared-layout";
 export * from "./layout-reader";
 export { StringTable } from "./string-table";
 export { MemoryBridge } from "./memory-bridge";
@@ -2,15 +2,38 @@
  * Zero-copy layout buffer reader.
  * Reads cell layout data directly from a Float32Array backed by WASM memory.
  *
- * Buffer format: each cell occupies STRIDE (16) f32 values:
+ * Buffer format: each cell occupies STRIDE (22) f32 values:
  *   [row, col, x, y, width, height, align,
  *    paddingTop, paddingRight, paddingBottom, paddingLeft,
- *    borderTop, borderRight, borderBottom, borderLeft, reserved]
+ *    borderTop, borderRight, borderBottom, borderLeft, region, baseline, flags, rotation, style,
+ *    updateAge, item]
  *
  * Align encoding: 0=left, 1=center, 2=right
+ * Region flags: 0=body (scrolls, clip below header and between pinned columns),
+ *   1=sticky header, 2=left-pinned column, 4=right-pinned column,
+ *   8=expanded-row detail area (full width, below the row's cells),
+ *   16=group header (full width, above a group's first row; with 1 when pinned),
+ *   32=covered by a merged cell above (zero height; skip),
+ *   64=synthetic group row (`setGroupBy`; row index is the group's first row),
+ *   128=footer row (viewport `footerHeight`; after the header cells, with 1 when
+ *   pinned to the viewport bottom by `stickyFooter`),
+ *   256=cell item (column `items`; row and col of the parent cell)
+ * Baseline: y of the first text line's center, per the column's `verticalAlign`
+ * Style flags: 1=odd view row (striping), 2=group header or group row, 4=pinned column,
+ *   8=selected (`setSelectedCells`), 16=global filter match
+ * Rotation: header label rotation in degrees counterclockwise, per the column's
+ *   `headerOrientation` (90 = vertical); 0 for every other cell
+ * Style: conditional format style ID (`setConditionalFormats`) of data cells; 0 = none
+ * Update age: frames since the cell's row was last updated, within the
+ *   `setUpdateFlashFrames` window (0 = this frame); -1 = none
+ * Item: index of a cell item among its cell's items; -1 for every other cell
+ *
+ * The format version is `LAYOUT_BUFFER_VERSION`; compare it with the engine's
+ * `getLayoutBufferVersion()` when loading a separately built wasm module.
  */
 
-const STRIDE = 16;
+const STRIDE = 22;
+const LAYOUT_BUFFER_VERSION = 7;
 
 // Field offsets
 const FIELD_ROW = 0;
@@ -28,8 +51,23 @@ const FIELD_BORDER_TOP = 11;
 const FIELD_BORDER_RIGHT = 12;
 const FIELD_BORDER_BOTTOM = 13;
 const FIELD_BORDER_LEFT = 14;
+const FIELD_REGION = 15;
+const FIELD_BASELINE = 16;
+const FIELD_FLAGS = 17;
+const FIELD_ROTATION = 18;
+const FIELD_STYLE = 19;
+const FIELD_UPDATE_AGE = 20;
+const FIELD_ITEM = 21;
+
+export { STRIDE as LAYOUT_STRIDE, LAYOUT_BUFFER_VERSION };
 
-export { STRIDE as LAYOUT_STRIDE };
+// Style flags (`readCellFlags`)
+export const CELL_FLAG_ODD_ROW = 1;
+export const CELL_FLAG_GROUP = 2;
+export const CELL_FLAG_PINNED = 4;
+export const CELL_FLAG_SELECTED = 8;
+export const CELL_FLAG_FILTER_MATCH = 16;
+export const CELL_FLAG_INVALID = 32;
 
 export function readCellRow(buf: Float32Array, i: number): number {
   return buf[i * STRIDE + FIELD_ROW] ?? 0;
@@ -60,6 +98,82 @@ export function readCellAlignCode(buf: Float32Array, i: number): number {
   return buf[i * STRIDE + FIELD_ALIGN] ?? 0;
 }
 
+/** Y of the first text line's center (`textBaseline = "middle"`), from `verticalAlign`. */
+export function readCellBaseline(buf: Float32Array, i: number): number {
+  return buf[i * STRIDE + FIELD_BASELINE] ?? 0;
+}
+
+/** Style flag bit set (`CELL_FLAG_*`), e.g. for stripes and selection highlights. */
+export function readCellFlags(buf: Float32Array, i: number): number {
+  return buf[i * STRIDE + FIELD_FLAGS] ?? 0;
+}
+
+/** Header label rotation, degrees counterclockwise (0 for unrotated and non-header cells). */
+export function readCellRotation(buf: Float32Array, i: number): number {
+  return buf[i * STRIDE + FIELD_ROTATION] ?? 0;
+}
+
+/** Conditional format style ID (`setConditionalFormats`; 0 = none). */
+export function readCellStyle(buf: Float32Array, i: number): number {
+  return buf[i * STRIDE + FIELD_STYLE] ?? 0;
+}
+
+/** Frames since the cell's row was last updated (`setUpdateFlashFrames`; -1 = none). */
+export function readCellUpdateAge(buf: Float32Array, i: number): number {
+  return buf[i * STRIDE + FIELD_UPDATE_AGE] ?? -1;
+}
+
+/** True for header cells pinned at the top (viewport `stickyHeader`). */
+export function readCellIsStickyHeader(buf: Float32Array, i: number): boolean {
+  return ((buf[i * STRIDE + FIELD_REGION] ?? 0) & 1) !== 0;
+}
+
+/** True for the detail area cell of an expanded row (`setExpandedRows`). */
+export function readCellIsDetail(buf: Float32Array, i: number): boolean {
+  return ((buf[i * STRIDE + FIELD_REGION] ?? 0) & 8) !== 0;
+}
+
+/** True for a group header cell (`setGroupHeaders`); the row is the group's first. */
+export function readCellIsGroupHeader(buf: Float32Array, i: number): boolean {
+  return ((buf[i * STRIDE + FIELD_REGION] ?? 0) & 16) !== 0;
+}
+
+/** True for a cell of a synthetic group header row (`setGroupBy`). */
+export function readCellIsGroupRow(buf: Float32Array, i: number): boolean {
+  return ((buf[i * STRIDE + FIELD_REGION] ?? 0) & 64) !== 0;
+}
+
+/**
+ * True for a footer row cell (viewport `footerHeight`), e.g. for `getAggregates`
+ * totals. There is one footer row per table; group totals go on group rows.
+ */
+export function readCellIsFooter(buf: Float32Array, i: number): boolean {
+  return ((buf[i * STRIDE + FIELD_REGION] ?? 0) & 128) !== 0;
+}
+
+/** True for an item inside a cell (column `items`), with its cell's row and column. */
+export function readCellIsItem(buf: Float32Array, i: number): boolean {
+  return ((buf[i * STRIDE + FIELD_REGION] ?? 0) & 256) !== 0;
+}
+
+/** Index of a cell item among its cell's items (`readCellIsItem`); -1 for other cells. */
+export function readCellItem(buf: Float32Array, i: number): number {
+  return buf[i * STRIDE + FIELD_ITEM] ?? -1;
+}
+
+/** True for a cell covered by a row span above it (`setMergeEqualValues`, `setRowSpans`). */
+export function readCellIsMerged(buf: Float32Array, i: number): boolean {
+  return ((buf[i * STRIDE + FIELD_REGION] ?? 0) & 32) !== 0;
+}
+
+/** Side of a frozen column (`pinned` in the engine's column layout), or null. */
+export function readCellPinned(buf: Float32Array, i: number): "left" | "right" | null {
+  const region = buf[i * STRIDE + FIELD_REGION] ?? 0;
+  if (region & 2) return "left";
+  if (region & 4) return "right";
+  return null;
+}
+
 export function readCellAlign(buf: Float32Array, i: number): "left" | "center" | "right" {
   const code = buf[i * STRIDE + FIELD_ALIGN] ?? 0;
   if (code === 1) return "center";
@@ -99,6 +213,36 @@ export function readCellBorderLeft(buf: Float32Array, i: number): number {
   return buf[i * STRIDE + FIELD_BORDER_LEFT] ?? 0;
 }
 
+// Header buffer (`getHeaderInfo`): HEADER_STRIDE fields per header cell, in
+// the order of the layout buffer's header cells.
+const HEADER_STRIDE = 4;
+const HEADER_FIELD_SORT = 0;
+const HEADER_FIELD_SORT_PRIORITY = 1;
+const HEADER_FIELD_FILTERED = 2;
+const HEADER_FIELD_RESIZE_X = 3;
+
+export { HEADER_STRIDE };
+
+/** Sort of header cell `i`: 0 = unsorted, 1 = ascending, 2 = descending. */
+export function readHeaderSort(buf: Float32Array, i: number): number {
+  return buf[i * HEADER_STRIDE + HEADER_FIELD_SORT] ?? 0;
+}
+
+/** 1-based place of header cell `i`'s column among the sort keys (0 = unsorted). */
+export function readHeaderSortPriority(buf: Float32Array, i: number): number {
+  return buf[i * HEADER_STRIDE + HEADER_FIELD_SORT_PRIORITY] ?? 0;
+}
+
+/** True when a column filter is active on header cell `i`'s column. */
+export function readHeaderFiltered(buf: Float32Array, i: number): boolean {
+  return (buf[i * HEADER_STRIDE + HEADER_FIELD_FILTERED] ?? 0) !== 0;
+}
+
+/** x of header cell `i`'s resize handle (its right edge), in layout buffer coordinates. */
+export function readHeaderResizeX(buf: Float32Array, i: number): number {
+  return buf[i * HEADER_STRIDE + HEADER_FIELD_RESIZE_X] ?? 0;
+}
+
 /**
  * Hit-test: find the cell index containing (x, y).
  * Searches cells in range [start, start+count).
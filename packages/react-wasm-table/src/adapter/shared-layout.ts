@@ -0,0 +1,57 @@
+/**
+ * Reader for a shared layout output buffer (`engine.setSharedLayoutBuffer`):
+ * a render thread consumes frames a worker's engine publishes, without copies.
+ *
+ * Buffer format: a header of i32 words (generation, published slot, cell count
+ * of slot 0, cell count of slot 1) followed by two slots of whole layout cells.
+ * The engine writes a frame into the unpublished slot, then stores the slot and
+ * increments the generation, so the published slot is only written again after
+ * the next frame is published.
+ */
+
+import { LAYOUT_STRIDE } from "./layout-reader";
+
+export const SHARED_GENERATION = 0;
+export const SHARED_ACTIVE_SLOT = 1;
+/** Cell count of slot 0; slot 1's follows. */
+export const SHARED_CELL_COUNT = 2;
+export const SHARED_HEADER_WORDS = 4;
+
+export interface SharedLayoutFrame {
+  /** Generation the frame was published at. */
+  generation: number;
+  /** Layout cells of the frame (a view into the shared buffer). */
+  buf: Float32Array;
+  cellCount: number;
+}
+
+/** Byte length of a shared buffer with room for `cellCount` cells per slot. */
+export function sharedLayoutBufferLength(cellCount: number): number {
+  return (SHARED_HEADER_WORDS + 2 * cellCount * LAYOUT_STRIDE) * 4;
+}
+
+/** The latest published frame, or null before the first one. */
+export function readSharedLayoutFrame(buffer: SharedArrayBuffer): SharedLayoutFrame | null {
+  const header = new Int32Array(buffer, 0, SHARED_HEADER_WORDS);
+  const generation = Atomics.load(header, SHARED_GENERATION);
+  if (generation === 0) return null;
+  const slot = Atomics.load(header, SHARED_ACTIVE_SLOT) & 1;
+  const cellCount = Atomics.load(header, SHARED_CELL_COUNT + slot);
+  const slotCells = Math.floor((buffer.byteLength / 4 - SHARED_HEADER_WORDS) / 2 / LAYOUT_STRIDE);
+  const offset = (SHARED_HEADER_WORDS + slot * slotCells * LAYOUT_STRIDE) * 4;
+  return {
+    generation,
+    buf: new Float32Array(buffer, offset, cellCount * LAYOUT_STRIDE),
+    cellCount,
+  };
+}
+
+/**
+ * Whether a frame read at `generation` is still intact: false once a newer frame
+ * is published (the engine may be overwriting the slot), in which case read the
+ * latest frame instead.
+ */
+export function isSharedFrameIntact(buffer: SharedArrayBuffer, generation: number): boolean {
+  const header = new Int32Array(buffer, 0, SHARED_HEADER_WORDS);
+  return Atomics.load(header, SHARED_GENERATION) === generation;
+}
@@ -207,6 +207,18 @@ export {
 export type { ContentCoords } from "./event-helpers";
 export { EditorManager } from "./adapter/editor-manager";
 export { SelectionManager, buildTSV } from "./adapter/selection-manager";
+export { encodeCommands, COMMAND_FORMAT_VERSION } from "./adapter/command-encoder";
+export type {
+  TableCommand,
+  WasmSortConfig,
+  WasmColumnFilter,
+} from "./adapter/command-encoder";
+export {
+  readSharedLayoutFrame,
+  isSharedFrameIntact,
+  sharedLayoutBufferLength,
+} from "./adapter/shared-layout";
+export type { SharedLayoutFrame } from "./adapter/shared-layout";
 
 // Built-in editor components
 export { TextEditor, NumberEditor, SelectEditor, editorStyle } from "./react/editors";
@@ -308,6 +320,7 @@ export { installInspector } from "./debug/inspector";
 
 // WASM loader
 export { initWasm, createTableEngine, isWasmReady, setWasmUrl } from "./wasm-loader";
+export type { InitWasmOptions } from "./wasm-loader";
 
 // Types
 export type {
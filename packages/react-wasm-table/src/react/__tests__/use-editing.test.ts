@@ -5,8 +5,7 @@ import { useEditing } from "../hooks/use-editing";
 import { ColumnRegistry } from "../../adapter/column-registry";
 import { EditorManager } from "../../adapter/editor-manager";
 import { SelectionManager } from "../../adapter/selection-manager";
-
-const STRIDE = 16;
+import { LAYOUT_STRIDE } from "../../adapter/layout-reader";
 
 function makeLayoutBuf(
   cells: {
@@ -18,15 +17,15 @@ function makeLayoutBuf(
     h: number;
   }[],
 ) {
-  const buf = new Float32Array(cells.length * STRIDE);
+  const buf = new Float32Array(cells.length * LAYOUT_STRIDE);
   for (let i = 0; i < cells.length; i++) {
     const c = cells[i]!;
-    buf[i * STRIDE + 0] = c.row;
-    buf[i * STRIDE + 1] = c.col;
-    buf[i * STRIDE + 2] = c.x;
-    buf[i * STRIDE + 3] = c.y;
-    buf[i * STRIDE + 4] = c.w;
-    buf[i * STRIDE + 5] = c.h;
+    buf[i * LAYOUT_STRIDE + 0] = c.row;
+    buf[i * LAYOUT_STRIDE + 1] = c.col;
+    buf[i * LAYOUT_STRIDE + 2] = c.x;
+    buf[i * LAYOUT_STRIDE + 3] = c.y;
+    buf[i * LAYOUT_STRIDE + 4] = c.w;
+    buf[i * LAYOUT_STRIDE + 5] = c.h;
   }
   return buf;
 }
@@ -52,7 +51,7 @@ describe("useEditing (renderHook)", () => {
         selectionManagerRef: { current: sm },
         getLayoutBuf: () => layoutBuf ?? null,
         getHeaderCount: () => headerCount,
-        getTotalCellCount: () => (layoutBuf ? layoutBuf.length / STRIDE : 0),
+        getTotalCellCount: () => (layoutBuf ? layoutBuf.length / LAYOUT_STRIDE : 0),
       }),
     );
   }
@@ -197,7 +196,7 @@ describe("useEditing (renderHook)", () => {
           selectionManagerRef: { current: sm },
           getLayoutBuf: () => layoutBuf,
           getHeaderCount: () => 1,
-          getTotalCellCount: () => layoutBuf.length / STRIDE,
+          getTotalCellCount: () => layoutBuf.length / LAYOUT_STRIDE,
           meta,
         }),
       );
@@ -344,7 +343,7 @@ describe("useEditing (renderHook)", () => {
           selectionManagerRef: { current: sm },
           getLayoutBuf: () => layoutBuf ?? null,
           getHeaderCount: () => 1,
-          getTotalCellCount: () => (layoutBuf ? layoutBuf.length / STRIDE : 0),
+          getTotalCellCount: () => (layoutBuf ? layoutBuf.length / LAYOUT_STRIDE : 0),
           editTrigger: trigger,
         }),
       );
@@ -569,7 +568,7 @@ describe("useEditing (renderHook)", () => {
           selectionManagerRef: { current: sm },
           getLayoutBuf: () => buf,
           getHeaderCount: () => 1,
-          getTotalCellCount: () => buf.length / STRIDE,
+          getTotalCellCount: () => buf.length / LAYOUT_STRIDE,
         }),
       );
 
@@ -606,7 +605,7 @@ describe("useEditing (renderHook)", () => {
           selectionManagerRef: { current: sm },
           getLayoutBuf: () => buf,
           getHeaderCount: () => 1,
-          getTotalCellCount: () => buf.length / STRIDE,
+          getTotalCellCount: () => buf.length / LAYOUT_STRIDE,
         }),
       );
 
@@ -653,7 +652,7 @@ describe("useEditing (renderHook)", () => {
           selectionManagerRef: { current: sm },
           getLayoutBuf: () => buf,
           getHeaderCount: () => 1,
-          getTotalCellCount: () => buf.length / STRIDE,
+          getTotalCellCount: () => buf.length / LAYOUT_STRIDE,
         }),
       );
 
@@ -692,7 +691,7 @@ describe("useEditing (renderHook)", () => {
           selectionManagerRef: { current: sm },
           getLayoutBuf: () => buf,
           getHeaderCount: () => 1,
-          getTotalCellCount: () => buf.length / STRIDE,
+          getTotalCellCount: () => buf.length / LAYOUT_STRIDE,
         }),
       );
 
@@ -849,7 +848,7 @@ describe("useEditing (renderHook)", () => {
           selectionManagerRef: { current: sm },
           getLayoutBuf: () => buf,
           getHeaderCount: () => 1,
-          getTotalCellCount: () => buf.length / STRIDE,
+          getTotalCellCount: () => buf.length / LAYOUT_STRIDE,
         });
         emRef.current = editing.editorManagerRef.current;
         return React.createElement(React.Fragment, null, editing.editorPortal);
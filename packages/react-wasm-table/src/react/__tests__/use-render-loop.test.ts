@@ -5,6 +5,7 @@ import { ColumnRegistry } from "../../adapter/column-registry";
 import { SelectionManager } from "../../adapter/selection-manager";
 import { EventManager } from "../../adapter/event-manager";
 import { StringTable } from "../../adapter/string-table";
+import { LAYOUT_STRIDE } from "../../adapter/layout-reader";
 import { DEFAULT_THEME } from "../../types";
 import type { AfterDrawContext } from "../../types";
 
@@ -38,27 +39,25 @@ afterEach(() => {
   globalThis.cancelAnimationFrame = (globalThis as any).__origCAF;
 });
 
-const STRIDE = 16;
-
 function makeLayoutBuf(
   cells: { row: number; col: number; x: number; y: number; w: number; h: number }[],
 ) {
-  const buf = new Float32Array(cells.length * STRIDE);
+  const buf = new Float32Array(cells.length * LAYOUT_STRIDE);
   for (let i = 0; i < cells.length; i++) {
     const c = cells[i]!;
-    buf[i * STRIDE + 0] = c.row;
-    buf[i * STRIDE + 1] = c.col;
-    buf[i * STRIDE + 2] = c.x;
-    buf[i * STRIDE + 3] = c.y;
-    buf[i * STRIDE + 4] = c.w;
-    buf[i * STRIDE + 5] = c.h;
+    buf[i * LAYOUT_STRIDE + 0] = c.row;
+    buf[i * LAYOUT_STRIDE + 1] = c.col;
+    buf[i * LAYOUT_STRIDE + 2] = c.x;
+    buf[i * LAYOUT_STRIDE + 3] = c.y;
+    buf[i * LAYOUT_STRIDE + 4] = c.w;
+    buf[i * LAYOUT_STRIDE + 5] = c.h;
   }
   return buf;
 }
 
 function makeEngine(layoutBuf: Float32Array, _viewIndices?: Uint32Array) {
   return {
-    updateViewportColumnar: mock(() => new Float64Array([layoutBuf.length / STRIDE, 0])),
+    updateViewportColumnar: mock(() => new Float64Array([layoutBuf.length / LAYOUT_STRIDE, 0])),
   } as any;
 }
 
@@ -312,6 +312,7 @@ export function useRenderLoop({
 
         const isGrid = display === "grid";
         const colLayouts = columns.map((col) => ({
+          key: col.id,
           width:
             typeof col.width === "number"
               ? col.width
@@ -464,6 +465,7 @@ export function useRenderLoop({
         const visStart = meta[1] ?? 0;
         const _generation = meta[5] ?? 0;
         const effectiveRowHeight = meta[8] ?? rowHeight;
+        const contentWidth = meta[19];
         const headerCount = colCount;
         const dataCount = cellCount - headerCount;
 
@@ -501,7 +503,6 @@ export function useRenderLoop({
             height,
             headerHeight,
             effectiveRowHeight,
-            scrollTopRef.current,
             pinnedTop,
             pinnedBottom,
             filteredCount,
@@ -634,6 +635,7 @@ export function useRenderLoop({
           layoutBuf,
           colCount,
           pinningInfo,
+          contentWidth,
         );
         eventManagerRef.current.setRegions(regionLayout);
 
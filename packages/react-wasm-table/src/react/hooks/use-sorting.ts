@@ -50,10 +50,17 @@ export function useSorting({
       // Apply to WASM engine
       if (next.length > 0) {
         engine.setColumnarSort(
-          next.map((s) => ({
-            columnIndex: columns.findIndex((c) => c.id === s.id),
-            direction: s.desc ? "desc" : "asc",
-          })),
+          next.map((s) => {
+            const columnIndex = columns.findIndex((c) => c.id === s.id);
+            const sortCol = columns[columnIndex];
+            return {
+              columnIndex,
+              direction: s.desc ? "desc" : "asc",
+              ...(sortCol?.naturalSort && { natural: true }),
+              ...(sortCol?.sortNulls && { nulls: sortCol.sortNulls }),
+              ...(sortCol?.sortExpression && { expression: sortCol.sortExpression }),
+            };
+          }),
         );
       } else {
         engine.setColumnarSort([]);
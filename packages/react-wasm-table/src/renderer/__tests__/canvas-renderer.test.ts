@@ -4,6 +4,7 @@ import { createCellRendererRegistry } from "../components";
 import type { CellRenderer } from "../components";
 import type { Theme, RenderInstruction } from "../../types";
 import type { GridHeaderGroup } from "../../grid-instance";
+import { LAYOUT_STRIDE } from "../../adapter/layout-reader";
 
 const defaultTheme: Theme = {
   headerBackground: "#f5f5f5",
@@ -19,9 +20,9 @@ const defaultTheme: Theme = {
   selectedBackground: "#1976d2",
 };
 
-/** Stride 16: [row, col, x, y, width, height, align, padT, padR, padB, padL, borderT, borderR, borderB, borderL, reserved] */
+/** Stride LAYOUT_STRIDE: [row, col, x, y, width, height, align, padT, padR, padB, padL, borderT, borderR, borderB, borderL, region, baseline, flags, rotation, style, updateAge] */
 function buildBuf(cells: [number, number, number, number, number, number][]): Float32Array {
-  const stride = 16;
+  const stride = LAYOUT_STRIDE;
   const buf = new Float32Array(cells.length * stride);
   for (let i = 0; i < cells.length; i++) {
     const [row, col, x, y, w, h] = cells[i]!;
@@ -26,6 +26,7 @@ import { selectCellRenderer } from "../components/select";
 import { progressBarCellRenderer, getBarGeometry } from "../components/progressbar";
 import type { CellRenderer, CellRenderContext } from "../components";
 import type { Theme, RenderInstruction } from "../../types";
+import { LAYOUT_STRIDE } from "../../adapter/layout-reader";
 
 const defaultTheme: Theme = {
   headerBackground: "#f5f5f5",
@@ -41,9 +42,9 @@ const defaultTheme: Theme = {
   fontFamily: "system-ui, sans-serif",
 };
 
-/** Stride 16 buffer helper. */
+/** LAYOUT_STRIDE buffer helper. */
 function buildBuf(cells: [number, number, number, number, number, number][]): Float32Array {
-  const stride = 16;
+  const stride = LAYOUT_STRIDE;
   const buf = new Float32Array(cells.length * stride);
   for (let i = 0; i < cells.length; i++) {
     const [row, col, x, y, w, h] = cells[i]!;
@@ -2,6 +2,7 @@ import { describe, expect, it, mock } from "bun:test";
 import { datepickerCellRenderer } from "../components/datepicker";
 import type { CellRenderContext } from "../components";
 import type { Theme, DatePickerInstruction } from "../../types";
+import { LAYOUT_STRIDE } from "../../adapter/layout-reader";
 
 const defaultTheme: Theme = {
   headerBackground: "#f5f5f5",
@@ -18,7 +19,7 @@ const defaultTheme: Theme = {
 };
 
 function buildBuf(cells: [number, number, number, number, number, number][]): Float32Array {
-  const stride = 16;
+  const stride = LAYOUT_STRIDE;
   const buf = new Float32Array(cells.length * stride);
   for (let i = 0; i < cells.length; i++) {
     const [row, col, x, y, w, h] = cells[i]!;
@@ -52,7 +52,7 @@ function mockCtx() {
 
 /**
  * Build a minimal layout buffer for a single cell at index 0.
- * Stride = 16 floats: [row, col, x, y, width, height, align, padTop, padRight, padBottom, padLeft, borderT, borderR, borderB, borderL, reserved]
+ * Stride = LAYOUT_STRIDE floats: [row, col, x, y, width, height, align, padTop, padRight, padBottom, padLeft, borderT, borderR, borderB, borderL, region, baseline, flags, rotation, style, updateAge]
  * align: 0=left, 1=center, 2=right
  */
 function makeBuf(
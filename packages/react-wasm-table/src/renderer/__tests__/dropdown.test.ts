@@ -15,6 +15,7 @@ import {
 } from "../components/dropdown";
 import type { CellRenderContext } from "../components";
 import type { Theme, DropdownInstruction } from "../../types";
+import { LAYOUT_STRIDE } from "../../adapter/layout-reader";
 
 const defaultTheme: Theme = {
   headerBackground: "#f5f5f5",
@@ -31,7 +32,7 @@ const defaultTheme: Theme = {
 };
 
 function buildBuf(cells: [number, number, number, number, number, number][]): Float32Array {
-  const stride = 16;
+  const stride = LAYOUT_STRIDE;
   const buf = new Float32Array(cells.length * stride);
   for (let i = 0; i < cells.length; i++) {
     const [row, col, x, y, w, h] = cells[i]!;
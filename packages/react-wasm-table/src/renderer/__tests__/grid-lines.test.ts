@@ -7,6 +7,7 @@ import {
   type VLine,
 } from "../grid-lines";
 import type { CellLayout } from "../../types";
+import { LAYOUT_STRIDE } from "../../adapter/layout-reader";
 
 // ── Reference implementations (object-based, for comparison tests) ───
 
@@ -99,14 +100,13 @@ function cell(
 
 /**
  * Build a Float32Array layout buffer from CellLayout objects.
- * Buffer format: stride 16 per cell [row, col, x, y, width, height, align, pt, pr, pb, pl, bt, br, bb, bl, reserved]
+ * Buffer format: stride LAYOUT_STRIDE per cell [row, col, x, y, width, height, align, pt, pr, pb, pl, bt, br, bb, bl, region, baseline, flags, rotation, style, updateAge]
  */
 function toBuffer(layouts: CellLayout[]): Float32Array {
-  const STRIDE = 16;
-  const buf = new Float32Array(layouts.length * STRIDE);
+  const buf = new Float32Array(layouts.length * LAYOUT_STRIDE);
   for (let i = 0; i < layouts.length; i++) {
     const l = layouts[i]!;
-    const base = i * STRIDE;
+    const base = i * LAYOUT_STRIDE;
     buf[base] = l.row;
     buf[base + 1] = l.col;
     buf[base + 2] = l.x;
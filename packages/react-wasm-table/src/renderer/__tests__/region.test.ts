@@ -155,7 +155,7 @@ describe("buildRegions", () => {
 
 describe("buildRowRegions", () => {
   it("returns header + center regions when no pinning", () => {
-    const result = buildRowRegions(800, 600, 40, 36, 50, 0, 0, 100);
+    const result = buildRowRegions(800, 600, 40, 36, 0, 0, 100);
     expect(result.regions).toHaveLength(2);
     const header = result.regions.find((r) => r.name === "header")!;
     expect(header.clipRect).toEqual([0, 0, 800, 40]);
@@ -170,8 +170,7 @@ describe("buildRowRegions", () => {
   });
 
   it("non-pinning center region clips below header (no translateY)", () => {
-    // scrollTop=200 should NOT affect translateY for non-pinning
-    const result = buildRowRegions(800, 600, 40, 36, 200, 0, 0, 100);
+    const result = buildRowRegions(800, 600, 40, 36, 0, 0, 100);
     const center = result.regions.find((r) => r.name === "center")!;
     // Center starts at headerHeight=40, height = 600-40 = 560
     expect(center.clipRect).toEqual([0, 40, 800, 560]);
@@ -179,7 +178,7 @@ describe("buildRowRegions", () => {
   });
 
   it("non-pinning header region prevents data cells from overlapping header", () => {
-    const result = buildRowRegions(800, 600, 40, 36, 0, 0, 0, 100);
+    const result = buildRowRegions(800, 600, 40, 36, 0, 0, 100);
     const header = result.regions.find((r) => r.name === "header")!;
     // Header clip ends at headerHeight=40, so data cells at y>=40 are excluded
     expect(header.clipRect[1]).toBe(0);
@@ -189,7 +188,7 @@ describe("buildRowRegions", () => {
 
   it("creates header+top+center+bottom regions with both pinned", () => {
     // canvas=600h, header=40, rowHeight=36, pinnedTop=2, pinnedBottom=1, total=10
-    const result = buildRowRegions(800, 600, 40, 36, 100, 2, 1, 10);
+    const result = buildRowRegions(800, 600, 40, 36, 2, 1, 10);
     expect(result.topHeight).toBe(72); // 2*36
     expect(result.bottomHeight).toBe(36); // 1*36
     expect(result.scrollableCount).toBe(7); // 10-2-1
@@ -203,35 +202,34 @@ describe("buildRowRegions", () => {
   });
 
   it("header region has translateY=0", () => {
-    const result = buildRowRegions(800, 600, 40, 36, 100, 1, 0, 10);
+    const result = buildRowRegions(800, 600, 40, 36, 1, 0, 10);
     const header = result.regions.find((r) => r.name === "header")!;
     expect(header.translateY).toBe(0);
     expect(header.clipRect).toEqual([0, 0, 800, 40]);
   });
 
   it("top pinned region has translateY=0", () => {
-    const result = buildRowRegions(800, 600, 40, 36, 100, 2, 0, 10);
+    const result = buildRowRegions(800, 600, 40, 36, 2, 0, 10);
     const top = result.regions.find((r) => r.name === "top")!;
     expect(top.translateY).toBe(0);
     expect(top.clipRect).toEqual([0, 40, 800, 72]);
   });
 
-  it("center region uses -scrollTop as translateY", () => {
-    const result = buildRowRegions(800, 600, 40, 36, 100, 1, 0, 10);
+  it("center region has translateY=0 (WASM applies scrollTop)", () => {
+    const result = buildRowRegions(800, 600, 40, 36, 1, 0, 10);
     const center = result.regions.find((r) => r.name === "center")!;
-    expect(center.translateY).toBe(-100);
+    expect(center.translateY).toBe(0);
   });
 
-  it("bottom pinned region calculates correct translateY", () => {
-    const result = buildRowRegions(800, 600, 40, 36, 0, 0, 1, 10);
+  it("bottom pinned region clips the bottom of the canvas", () => {
+    const result = buildRowRegions(800, 600, 40, 36, 0, 1, 10);
     const bottom = result.regions.find((r) => r.name === "bottom")!;
-    // firstBottomContentY = 40 + (0 + 9) * 36 = 40 + 324 = 364
-    // translateY = 600 - 36 - 364 = 200
     expect(bottom.clipRect).toEqual([0, 564, 800, 36]);
+    expect(bottom.translateY).toBe(0);
   });
 
   it("handles only top pinning", () => {
-    const result = buildRowRegions(800, 600, 40, 36, 50, 2, 0, 10);
+    const result = buildRowRegions(800, 600, 40, 36, 2, 0, 10);
     const names = result.regions.map((r) => r.name);
     expect(names).toContain("header");
     expect(names).toContain("top");
@@ -240,7 +238,7 @@ describe("buildRowRegions", () => {
   });
 
   it("handles only bottom pinning", () => {
-    const result = buildRowRegions(800, 600, 40, 36, 50, 0, 2, 10);
+    const result = buildRowRegions(800, 600, 40, 36, 0, 2, 10);
     const names = result.regions.map((r) => r.name);
     expect(names).toContain("header");
     expect(names).toContain("center");
@@ -4,7 +4,7 @@ import { LAYOUT_STRIDE } from "../../adapter/layout-reader";
 
 /**
  * Helper to build a layout buffer for testing.
- * Each cell: [row, col, x, y, width, height, align, padT, padR, padB, padL, borT, borR, borB, borL, reserved]
+ * Each cell: [row, col, x, y, width, height, align, padT, padR, padB, padL, borT, borR, borB, borL, region, baseline, flags, rotation, style]
  */
 function buildBuffer(
   cells: { row: number; col: number; x: number; y: number; w: number; h: number }[],
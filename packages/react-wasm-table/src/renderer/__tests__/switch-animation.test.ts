@@ -4,6 +4,7 @@ import { _getAnimationMap } from "../components/switch";
 import { createCellRendererRegistry } from "../components";
 import type { CellRenderContext } from "../components";
 import type { Theme } from "../../types";
+import { LAYOUT_STRIDE } from "../../adapter/layout-reader";
 
 const defaultTheme: Theme = {
   headerBackground: "#f5f5f5",
@@ -19,9 +20,9 @@ const defaultTheme: Theme = {
   fontFamily: "system-ui, sans-serif",
 };
 
-/** Stride 16 buffer helper. */
+/** LAYOUT_STRIDE buffer helper. */
 function buildBuf(cells: [number, number, number, number, number, number][]): Float32Array {
-  const stride = 16;
+  const stride = LAYOUT_STRIDE;
   const buf = new Float32Array(cells.length * stride);
   for (let i = 0; i < cells.length; i++) {
     const [row, col, x, y, w, h] = cells[i]!;
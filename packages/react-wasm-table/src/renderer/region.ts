@@ -16,6 +16,7 @@ export interface RegionLayout {
 
 /**
  * Build canvas regions for clip-based frozen column rendering.
+ * `contentWidth` (updateViewportColumnar meta[19]) skips summing header widths.
  *
  * When pinning is disabled (leftCount=0, rightCount=0), returns a single
  * center region equivalent to the current full-canvas scroll behavior.
@@ -27,13 +28,16 @@ export function buildRegions(
   layoutBuf: Float32Array,
   headerCount: number,
   pinningInfo: PinningInfo,
+  contentWidth?: number,
 ): RegionLayout {
   const { leftCount, rightCount } = pinningInfo;
 
-  // Compute total content width from header cells
-  let totalContentWidth = 0;
-  for (let i = 0; i < headerCount; i++) {
-    totalContentWidth += readCellWidth(layoutBuf, i);
+  // Total content width: from the engine when given, else summed from header cells
+  let totalContentWidth = contentWidth ?? 0;
+  if (contentWidth === undefined) {
+    for (let i = 0; i < headerCount; i++) {
+      totalContentWidth += readCellWidth(layoutBuf, i);
+    }
   }
 
   // No pinning: single center region
@@ -122,7 +126,6 @@ export function buildRowRegions(
   canvasHeight: number,
   headerHeight: number,
   rowHeight: number,
-  scrollTop: number,
   pinnedTop: number,
   pinnedBottom: number,
   totalRows: number,
@@ -171,21 +174,20 @@ export function buildRowRegions(
   }
 
   if (centerHeight > 0) {
-    // WASM outputs absolute content y; JS applies scroll via translateY.
+    // WASM outputs viewport y with the scroll already applied.
     regions.push({
       name: "center",
       clipRect: [0, headerHeight + topHeight, canvasWidth, centerHeight],
-      translateY: -scrollTop,
+      translateY: 0,
     });
   }
 
   if (pinnedBottom > 0) {
-    const firstBottomContentY = headerHeight + (pinnedTop + scrollableCount) * rowHeight;
-    const translateY = canvasHeight - bottomHeight - firstBottomContentY;
+    // WASM lays the bottom pinned rows out at the bottom of the viewport.
     regions.push({
       name: "bottom",
       clipRect: [0, canvasHeight - bottomHeight, canvasWidth, bottomHeight],
-      translateY,
+      translateY: 0,
     });
   }
 
@@ -53,6 +53,9 @@ export function resolveColumns<TData>(
       ...(typeof def.header === "string" && { header: def.header }),
       // Sorting: enableSorting → sortable
       ...(def.enableSorting !== undefined && { sortable: def.enableSorting }),
+      ...(def.sortingFn === "alphanumeric" && { naturalSort: true }),
+      ...(def.sortUndefined !== undefined && { sortNulls: def.sortUndefined }),
+      ...(def.sortExpression !== undefined && { sortExpression: def.sortExpression }),
       // Selection: enableSelection → selectable
       ...(def.enableSelection !== undefined && { selectable: def.enableSelection }),
       // Our extensions pass through
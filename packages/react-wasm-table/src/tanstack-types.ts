@@ -34,7 +34,14 @@ export type FilterOp =
   | "lte"
   | "contains"
   | "startsWith"
-  | "endsWith";
+  | "endsWith"
+  | "isNull"
+  | "isNotNull"
+  | "isBlank"
+  | "containsAny"
+  | "containsAll"
+  | "within"
+  | "expression";
 
 export interface ColumnFilter {
   id: string;
@@ -197,8 +204,12 @@ export interface ColumnDefBase<TData, TValue = unknown> {
   enableSorting?: boolean;
   /** Enable selection starting from this column. Maps to internal `selectable`. @default true */
   enableSelection?: boolean;
-  /** Custom sort function (future support). */
+  /** Sort function name. `"alphanumeric"` maps to natural sort; others are future support. */
   sortingFn?: string;
+  /** Place empty cells first or last regardless of sort direction. Maps to internal `sortNulls`. */
+  sortUndefined?: "first" | "last";
+  /** Sort by a numeric expression over column indices, e.g. `"$2 * $5"`. */
+  sortExpression?: string;
 
   // Filtering
   /** Enable per-column filtering. @default true for accessor columns */
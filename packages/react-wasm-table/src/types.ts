@@ -3,14 +3,24 @@ import type { GridInstance } from "./grid-instance";
 
 // ── CSS value types ──────────────────────────────────────────────────
 
-/** A CSS dimension: pixel number, percentage string, or "auto". */
-export type CssDimension = number | `${number}%` | "auto";
+/**
+ * A CSS math function over px and % lengths (e.g. `"calc(100% - 40px)"`,
+ * `"clamp(80px, 25%, 300px)"`), resolved against the viewport at layout time.
+ */
+export type CssMathFunction =
+  | `calc(${string})`
+  | `min(${string})`
+  | `max(${string})`
+  | `clamp(${string})`;
+
+/** A CSS dimension: pixel number, percentage string, math function, or "auto". */
+export type CssDimension = number | `${number}%` | CssMathFunction | "auto";
 
-/** A CSS length: pixel number or percentage string. */
-export type CssLength = number | `${number}%`;
+/** A CSS length: pixel number, percentage string, or math function. */
+export type CssLength = number | `${number}%` | CssMathFunction;
 
 /** A CSS length or "auto". */
-export type CssLengthAuto = number | `${number}%` | "auto";
+export type CssLengthAuto = number | `${number}%` | CssMathFunction | "auto";
 
 /**
  * CSS rect shorthand (like padding/margin).
@@ -35,7 +45,8 @@ export type CssGridTrackSize =
   | "min-content"
   | "max-content"
   | `minmax(${string})`
-  | `fit-content(${string})`;
+  | `fit-content(${string})`
+  | CssMathFunction;
 
 /** CSS grid track list: single value, space-separated string, or array. */
 export type CssGridTrackList =
@@ -1112,6 +1123,12 @@ export interface ColumnProps extends BoxModelProps {
   align?: "left" | "center" | "right";
   /** Whether the column is sortable. */
   sortable?: boolean;
+  /** Sort digit runs by value ("file2" before "file10"). */
+  naturalSort?: boolean;
+  /** Keep empty cells first or last regardless of sort direction. */
+  sortNulls?: "first" | "last";
+  /** Sort this column by a numeric expression over column indices instead of its own values, e.g. `"$2 * $5"`. */
+  sortExpression?: string;
   /** Whether selection can start from this column. @default true */
   selectable?: boolean;
   /** Editor type for inline editing. */
@@ -1351,6 +1368,26 @@ export interface GridProps extends BoxModelProps {
 export interface WasmTableEngine {
   // Layout buffer (zero-copy pointer API)
   getLayoutBufferInfo(): Uint32Array;
+  /** [ptr, headerCellCount] of per-header-cell sort, sort priority, filter, and resize handle x. */
+  getHeaderInfo?(): Uint32Array;
+  /** [x, y, width, height] of a cell at a view position, laid out or not (empty when unknown). */
+  getCellRect?(row: number, col: number): Float64Array;
+  /** [x, y, width, height] of the cells of a view position, laid out or not (empty when unknown). */
+  getRowRect?(row: number): Float64Array;
+  /** Paginate the whole view: [pageCount, then ptr, cellCount, startRow, endRow per page]. */
+  computePrintLayout?(
+    pageWidth: number,
+    pageHeight: number,
+    options?: { repeatHeader?: boolean; footer?: boolean },
+  ): Float64Array;
+  /** Width overrides of the keyed columns as `{ [key]: width }`, for saving. */
+  getColumnWidths?(): Record<string, number>;
+  /** Restore `getColumnWidths` widths by column key; survives reorders and hidden columns. */
+  setColumnWidths?(widths: Record<string, number>): void;
+  /** Vertical then horizontal [trackStart, trackLength, thumbStart, thumbLength, maxScroll] of the last frame. */
+  getScrollbarInfo?(minThumb?: number): Float64Array;
+  /** Mirror each frame into a double-buffered `SharedArrayBuffer` (`undefined` stops). */
+  setSharedLayoutBuffer?(buffer: SharedArrayBuffer | undefined): void;
   getColumnFloat64Info(colIdx: number): Uint32Array;
 
   // TypedArray direct ingestion (no serde for numerics)
@@ -1358,6 +1395,13 @@ export interface WasmTableEngine {
   ingestFloat64Column(colIdx: number, values: Float64Array): void;
   ingestBoolColumn(colIdx: number, values: Float64Array): void;
   ingestStringColumn(colIdx: number, uniqueStrings: string[], ids: Uint32Array): void;
+  ingestStringListColumn?(
+    colIdx: number,
+    uniqueStrings: string[],
+    offsets: Uint32Array,
+    ids: Uint32Array,
+  ): void;
+  ingestFloat64ListColumn?(colIdx: number, offsets: Uint32Array, values: Float64Array): void;
   finalizeColumnar(): void;
 
   // Streaming append (Phase 2) — incremental data ingestion
@@ -1370,10 +1414,25 @@ export interface WasmTableEngine {
     uniqueStrings: string[],
     ids: Uint32Array,
   ): void;
+  appendStringListColumn?(
+    colIdx: number,
+    offset: number,
+    uniqueStrings: string[],
+    offsets: Uint32Array,
+    ids: Uint32Array,
+  ): void;
+  appendFloat64ListColumn?(
+    colIdx: number,
+    offset: number,
+    offsets: Uint32Array,
+    values: Float64Array,
+  ): void;
   finalizeAppendColumnar?(): void;
 
   // Hot path — single WASM call per frame
   rebuildView?: () => void;
+  /** Time-sliced rebuild; frames show the previous view until `done`. */
+  rebuildViewStep?(budgetMs: number): { done: boolean; progress: number; restarted: boolean };
   updateViewportColumnar(
     scrollTop: number,
     viewport: unknown,
@@ -1385,7 +1444,91 @@ export interface WasmTableEngine {
   ): Float64Array;
   setColumnarSort(configs: unknown): void;
   setColumnarFilters(filters: unknown): void;
+  /** Apply an `encodeCommands` batch, then lay out a frame (empty before the first frame). */
+  applyCommands?(bytes: Uint8Array): Float64Array;
+  /** Distinct non-null values of a column under the other filters; estimated past 100k rows unless `exact`. */
+  getDistinctCount?(colIdx: number, exact: boolean): number;
   setGlobalFilter(query: string | null): void;
+  /** Key rows by a column's values for `applyUpserts` (`undefined` turns it off). */
+  setRowKeyColumn?(colIdx: number | undefined): void;
+  /** Queue keyed updates (`values` row-major); coalesced and written at the next frame. */
+  applyUpserts?(
+    keys: (number | string)[],
+    columns: Uint32Array,
+    values: (number | string | boolean | null)[],
+  ): void;
+  /** Insert a row now (values by column; unique key), at a view position or sorted; returns its version. */
+  insertRow?(values: (number | string | boolean | null)[], at?: number): number;
+  /** Write columns of the keyed row now; new version, or `undefined` when missing or not at `expectedVersion`. */
+  updateRowById?(
+    id: number | string,
+    columns: Uint32Array,
+    values: (number | string | boolean | null)[],
+    expectedVersion?: number,
+  ): number | undefined;
+  /** Take the keyed row out of the view; new version, or `undefined` as for `updateRowById`. */
+  removeRowById?(id: number | string, expectedVersion?: number): number | undefined;
+  /** Version of the keyed row (0 = unchanged), or `undefined` without one. */
+  getRowVersion?(id: number | string): number | undefined;
+  /** Validate a column's cells on load and edit (`undefined` clears); failing cells get `CELL_FLAG_INVALID`. */
+  setColumnValidation?(
+    colIdx: number,
+    rules:
+      | { required?: boolean; min?: number; max?: number; pattern?: string; enum?: string[] }
+      | undefined,
+  ): void;
+  /** `[viewRow, column, errors]` triples of failing cells in the range (whole view by default). */
+  getValidationErrors?(range?: {
+    startRow: number;
+    endRow?: number;
+    startCol?: number;
+    endCol?: number;
+  }): Uint32Array;
+  /** Groups (2+ data rows, ascending) of filtered rows with equal cells in `colIndices`. */
+  findDuplicates?(colIndices: Uint32Array): number[][];
+  /** Remove duplicates, keeping each group's first (default) or last row; returns removed data rows. */
+  dedupeKeep?(colIndices: Uint32Array, keep?: "first" | "last"): Uint32Array;
+  /** [ptr, len] of `[viewRow, column]` pairs rendered by the last frame and changed by upserts. */
+  getUpdatedCellsInfo?(): Uint32Array;
+  /** Report row updates in the layout buffer's update age field for `frames` frames (0 = off). */
+  setUpdateFlashFrames?(frames: number): void;
+  /** Subscribe to view rebuilds that change rows or their order (`undefined` unsubscribes). Defer engine calls from callbacks. */
+  onViewChanged?(
+    callback: ((viewVersion: number, viewRowCount: number, rowCount: number) => void) | undefined,
+  ): void;
+  /** Subscribe to selection model changes (`undefined` unsubscribes). */
+  onSelectionChanged?(callback: ((version: number, isEmpty: boolean) => void) | undefined): void;
+  /** Subscribe to cell writes: `cells` holds `[row, col, ...]` data cells (`undefined` unsubscribes). */
+  onDataEdited?(
+    callback:
+      | ((cells: Uint32Array, source: "update" | "edit" | "paste" | "undo" | "redo" | "insert" | "upsert") => void)
+      | undefined,
+  ): void;
+  /** Leave sorting/filtering to a server; rows show in stored (server) order. */
+  setRemoteRowModel?(enabled: boolean): void;
+  setRemoteRowCount?(total: number): void;
+  /** Canonical sort/filter query for the server; equal `key`s mean equal queries. */
+  getQueryDescriptor?(): {
+    sort: unknown[];
+    filters: unknown[];
+    globalFilter: string | null;
+    key: string;
+  };
+  /** Rendered rows (or a view range) with 1-based indices, header names, sort state, and cell text for an ARIA mirror. */
+  getAriaSnapshot?(
+    range?: { startRow: number; endRow?: number; startCol?: number; endCol?: number },
+    headers?: string[],
+  ): {
+    rowCount: number;
+    colCount: number;
+    columns: {
+      colIndex: number;
+      column: number;
+      name: string;
+      sort: "ascending" | "descending" | "none";
+    }[];
+    rows: { rowIndex: number; row: number; selected: boolean; cells: string[] }[];
+  };
   setColumnarScrollConfig(rowHeight: number, viewportHeight: number, overscan: number): void;
   setPagination(pageIndex: number | undefined, pageSize: number | undefined): void;
   getColumnarViewIndicesInfo(): Uint32Array;
@@ -13,8 +13,33 @@ export function setWasmUrl(url: string | URL): void {
   customWasmUrl = url;
 }
 
+export interface InitWasmOptions {
+  /**
+   * Worker threads for large view rebuilds, for a build with the `threads` feature
+   * (`true` = `navigator.hardwareConcurrency`). Needs a cross-origin isolated page;
+   * otherwise, or if the pool fails to start, rebuilds stay single-threaded.
+   */
+  threads?: boolean | number;
+}
+
+type WasmThreadExports = {
+  initThreadPool?: (threads: number) => Promise<void>;
+  setThreadPoolReady?: (ready: boolean) => boolean;
+};
+
+async function startThreadPool(wasm: WasmThreadExports, threads: boolean | number): Promise<void> {
+  if (!threads || !globalThis.crossOriginIsolated || !wasm.initThreadPool) return;
+  const count = threads === true ? (globalThis.navigator?.hardwareConcurrency ?? 1) : threads;
+  try {
+    await wasm.initThreadPool(count);
+    wasm.setThreadPoolReady?.(true);
+  } catch {
+    // Single-threaded fallback
+  }
+}
+
 /** Initialize the WASM module. Only loads once. */
-export async function initWasm(): Promise<void> {
+export async function initWasm(options: InitWasmOptions = {}): Promise<void> {
   if (wasmModule) return;
 
   if (!initPromise) {
@@ -29,6 +54,7 @@ export async function initWasm(): Promise<void> {
         customWasmUrl ?? new URL("../wasm/react_wasm_table_wasm_bg.wasm", import.meta.url);
 
       const initOutput = await wasm.default(url);
+      await startThreadPool(wasm as WasmThreadExports, options.threads ?? false);
       wasmModule = wasm;
       wasmMemory = initOutput.memory ?? null;
     })();